@@ -197,11 +197,22 @@ impl<'v> Executor<'v> {
         method_sig: &str,
         method_args: &[u8],
     ) -> (ExitReason, Vec<u8>) {
-        self.transact(|exec| {
-            let mut data = vec![];
-            data.extend(derive_method_selector(method_sig));
-            data.extend(method_args);
+        let mut data = vec![];
+        data.extend(derive_method_selector(method_sig));
+        data.extend(method_args);
+        self.call_with_data(caller_address, contract_address, eth_amount, data)
+    }
 
+    /// Call a contract with the given raw calldata, which need not start with a method selector,
+    /// e.g. to reach its fallback function.
+    pub fn call_with_data(
+        &mut self,
+        caller_address: H160,
+        contract_address: H160,
+        eth_amount: U256,
+        data: Vec<u8>,
+    ) -> (ExitReason, Vec<u8>) {
+        self.transact(|exec| {
             let (exit_reason, buffer) = exec.transact_call(
                 caller_address,
                 contract_address,
@@ -0,0 +1,174 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The Solidity ABI of a contract, in the JSON format used by ethers, web3 and solc.
+//!
+//! The ABI is derived from the same model the dispatcher is generated from, and describes the
+//! `#[create]` function as the constructor, each dispatched `#[callable]` function, and the
+//! `#[receive]` and `#[fallback]` functions, so that integrators do not need to write it by hand.
+
+use crate::{
+    attributes, context::Context, generator::Generator, interface_card::Mutability,
+    options::Options,
+};
+use move_model::{
+    model::{FunctionEnv, GlobalEnv},
+    ty::{PrimitiveType, Type},
+};
+use serde::{Deserialize, Serialize};
+
+/// The ABI of a contract, as a list of entries.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ContractAbi {
+    pub entries: Vec<AbiEntry>,
+}
+
+/// An entry of the ABI, describing a function of the contract.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AbiEntry {
+    #[serde(rename = "type")]
+    pub kind: AbiEntryKind,
+    /// The name of the function, for callable functions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The parameters, for callable functions and the constructor.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inputs: Option<Vec<AbiParam>>,
+    /// The returned values, for callable functions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outputs: Option<Vec<AbiParam>>,
+    pub state_mutability: Mutability,
+}
+
+/// The kind of an ABI entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AbiEntryKind {
+    Function,
+    Constructor,
+    Receive,
+    Fallback,
+}
+
+/// A parameter or returned value in the ABI.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AbiParam {
+    pub name: String,
+    /// The Solidity type, where structs are `tuple`s described by their components.
+    #[serde(rename = "type")]
+    pub ty: String,
+    /// The fields of a `tuple`, or of the elements of a `tuple[]`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub components: Vec<AbiParam>,
+}
+
+impl ContractAbi {
+    /// Create the ABI of the contract generated from the given environment.
+    pub fn new(options: &Options, env: &GlobalEnv) -> Self {
+        let ctx = Context::new(options, env, false);
+        let gen = Generator::default();
+        let mut entries = vec![];
+        for fun in ctx.get_target_functions(attributes::is_create_fun) {
+            entries.push(AbiEntry {
+                kind: AbiEntryKind::Constructor,
+                name: None,
+                inputs: Some(abi_inputs(&ctx, &gen, &fun)),
+                outputs: None,
+                state_mutability: Mutability::of_fun(&fun),
+            });
+        }
+        for fun in ctx.get_target_functions(attributes::is_callable_fun) {
+            // Functions which are not dispatched cannot be called through the ABI
+            if !gen.is_suitable_for_dispatch(&ctx, &fun) {
+                continue;
+            }
+            entries.push(AbiEntry {
+                kind: AbiEntryKind::Function,
+                name: Some(fun.symbol_pool().string(fun.get_name()).to_string()),
+                inputs: Some(abi_inputs(&ctx, &gen, &fun)),
+                outputs: Some(
+                    fun.get_return_types()
+                        .iter()
+                        .map(|ty| abi_param(&ctx, &gen, String::new(), ty))
+                        .collect(),
+                ),
+                state_mutability: Mutability::of_fun(&fun),
+            });
+        }
+        entries.extend(
+            ctx.get_target_functions(attributes::is_receive_fun)
+                .iter()
+                .map(|fun| special_entry(AbiEntryKind::Receive, fun)),
+        );
+        entries.extend(
+            ctx.get_target_functions(attributes::is_fallback_fun)
+                .iter()
+                .map(|fun| special_entry(AbiEntryKind::Fallback, fun)),
+        );
+        Self { entries }
+    }
+
+    /// Render the ABI as JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("contract ABI is serializable")
+    }
+}
+
+/// Describe a receive or fallback function, which has neither name nor ABI parameters.
+fn special_entry(kind: AbiEntryKind, fun: &FunctionEnv<'_>) -> AbiEntry {
+    AbiEntry {
+        kind,
+        name: None,
+        inputs: None,
+        outputs: None,
+        state_mutability: Mutability::of_fun(fun),
+    }
+}
+
+fn abi_inputs(ctx: &Context, gen: &Generator, fun: &FunctionEnv<'_>) -> Vec<AbiParam> {
+    fun.get_parameters()
+        .iter()
+        .map(|param| {
+            let name = fun.symbol_pool().string(param.0).to_string();
+            abi_param(ctx, gen, name, &param.1)
+        })
+        .collect()
+}
+
+/// Describe a value of the given type in the ABI.
+fn abi_param(ctx: &Context, gen: &Generator, name: String, ty: &Type) -> AbiParam {
+    match ty {
+        // A `vector<u8>` is described as `bytes`
+        Type::Vector(elem_ty) if **elem_ty != Type::Primitive(PrimitiveType::U8) => {
+            let elem = abi_param(ctx, gen, name, elem_ty);
+            AbiParam {
+                ty: format!("{}[]", elem.ty),
+                ..elem
+            }
+        }
+        Type::Struct(mid, sid, inst)
+            if !ctx.is_native_int(mid.qualified(*sid)) && !ctx.is_string(mid.qualified(*sid)) =>
+        {
+            let struct_env = ctx.env.get_struct(mid.qualified(*sid));
+            AbiParam {
+                name,
+                ty: "tuple".to_string(),
+                components: struct_env
+                    .get_fields()
+                    .map(|field| {
+                        let field_name = ctx.env.symbol_pool().string(field.get_name());
+                        let field_ty = field.get_type().instantiate(inst);
+                        abi_param(ctx, gen, field_name.to_string(), &field_ty)
+                    })
+                    .collect(),
+            }
+        }
+        _ => AbiParam {
+            name,
+            ty: gen.get_evm_type_string(ctx, ty),
+            components: vec![],
+        },
+    }
+}
@@ -4,17 +4,22 @@
 // ! Module defining attributes used by the generator.
 
 use move_model::{
-    ast::Attribute,
-    model::{FunctionEnv, GlobalEnv, ModuleEnv},
+    ast::{Attribute, AttributeValue},
+    model::{FunctionEnv, GlobalEnv, ModuleEnv, StructEnv},
 };
 
 const CREATE_ATTR: &str = "create";
 const CALLABLE_ATTR: &str = "callable";
 const EVM_ARITH_ATTR: &str = "evm_arith";
+const IMMUTABLE_ATTR: &str = "immutable";
+const INLINE_YUL_ATTR: &str = "inline_yul";
 const PAYABLE_ATTR: &str = "payable";
+const PURE_ATTR: &str = "pure";
 const RECEIVE_ATTR: &str = "receive";
 const RECEIVE_FALLBACK_ATTR: &str = "fallback";
 const TEST_ATTR: &str = "evm_test";
+const TEST_ONLY_ATTR: &str = "test_only";
+const VIEW_ATTR: &str = "view";
 
 /// Check whether a simple attribute is present in an attribute list.
 pub fn has_simple_attr(env: &GlobalEnv, attrs: &[Attribute], name: &str) -> bool {
@@ -28,11 +33,31 @@ pub fn has_simple_attr(env: &GlobalEnv, attrs: &[Attribute], name: &str) -> bool
     })
 }
 
+/// Returns the value of an assigned attribute, as in `#[name = value]`, if present in an attribute
+/// list.
+pub fn get_assigned_attr<'a>(
+    env: &GlobalEnv,
+    attrs: &'a [Attribute],
+    name: &str,
+) -> Option<&'a AttributeValue> {
+    attrs.iter().find_map(|a| match a {
+        Attribute::Assign(_, s, value) if env.symbol_pool().string(*s).as_str() == name => {
+            Some(value)
+        }
+        _ => None,
+    })
+}
+
 /// Check whether the module has a `#[evm_arith]` attribute.
 pub fn is_evm_arith_module(module: &ModuleEnv) -> bool {
     has_simple_attr(module.env, module.get_attributes(), EVM_ARITH_ATTR)
 }
 
+/// Check whether the struct has a `#[immutable]` attribute.
+pub fn is_immutable_struct(st: &StructEnv<'_>) -> bool {
+    has_simple_attr(st.module_env.env, st.get_attributes(), IMMUTABLE_ATTR)
+}
+
 /// Check whether the function has a `#[callable]` attribute.
 pub fn is_callable_fun(fun: &FunctionEnv<'_>) -> bool {
     has_simple_attr(fun.module_env.env, fun.get_attributes(), CALLABLE_ATTR)
@@ -43,11 +68,26 @@ pub fn is_create_fun(fun: &FunctionEnv<'_>) -> bool {
     has_simple_attr(fun.module_env.env, fun.get_attributes(), CREATE_ATTR)
 }
 
+/// Returns the value of the `#[inline_yul = b"..."]` attribute of the function, if present.
+pub fn get_inline_yul_attr<'a>(fun: &'a FunctionEnv<'_>) -> Option<&'a AttributeValue> {
+    get_assigned_attr(fun.module_env.env, fun.get_attributes(), INLINE_YUL_ATTR)
+}
+
 /// Check whether the function has a `#[payable]` attribute.
 pub fn is_payable_fun(fun: &FunctionEnv<'_>) -> bool {
     has_simple_attr(fun.module_env.env, fun.get_attributes(), PAYABLE_ATTR)
 }
 
+/// Check whether the function has a `#[view]` attribute.
+pub fn is_view_fun(fun: &FunctionEnv<'_>) -> bool {
+    has_simple_attr(fun.module_env.env, fun.get_attributes(), VIEW_ATTR)
+}
+
+/// Check whether the function has a `#[pure]` attribute.
+pub fn is_pure_fun(fun: &FunctionEnv<'_>) -> bool {
+    has_simple_attr(fun.module_env.env, fun.get_attributes(), PURE_ATTR)
+}
+
 /// Check whether the function has a `#[receive]` attribute.
 pub fn is_receive_fun(fun: &FunctionEnv<'_>) -> bool {
     has_simple_attr(fun.module_env.env, fun.get_attributes(), RECEIVE_ATTR)
@@ -71,3 +111,13 @@ pub fn is_contract_fun(fun: &FunctionEnv) -> bool {
 pub fn is_test_fun(fun: &FunctionEnv<'_>) -> bool {
     has_simple_attr(fun.module_env.env, fun.get_attributes(), TEST_ATTR)
 }
+
+/// Check whether the function, or the module declaring it, has a `#[test_only]` attribute.
+pub fn is_test_only_fun(fun: &FunctionEnv<'_>) -> bool {
+    has_simple_attr(fun.module_env.env, fun.get_attributes(), TEST_ONLY_ATTR)
+        || has_simple_attr(
+            fun.module_env.env,
+            fun.module_env.get_attributes(),
+            TEST_ONLY_ATTR,
+        )
+}
@@ -0,0 +1,261 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generation of BCS serialization and deserialization functions, backing the natives
+//! `Std::BCS::to_bytes` and `Eth::BCS::from_bytes`.
+//!
+//! Values are serialized from the linear memory (or storage) layout of their type: the fields of
+//! a struct are read from the offsets of its `StructLayout`, in declaration order as BCS requires.
+//! Integers are serialized in little-endian, addresses as their bytes, and vectors prefixed by
+//! their length in ULEB128.
+
+use crate::{context::Context, yul_functions::YulFunction, Generator};
+use move_model::{
+    emit, emitln,
+    ty::{PrimitiveType, Type},
+};
+
+/// Determine whether values of the type can be BCS serialized, or deserialized if `deserialize`
+/// is set. A signer can only be serialized, as deserializing it would forge it.
+pub(crate) fn is_bcs_supported_type(ctx: &Context, ty: &Type, deserialize: bool) -> bool {
+    match ty {
+        Type::Primitive(PrimitiveType::Signer) => !deserialize,
+        Type::Primitive(PrimitiveType::Bool)
+        | Type::Primitive(PrimitiveType::U8)
+        | Type::Primitive(PrimitiveType::U64)
+        | Type::Primitive(PrimitiveType::U128)
+        | Type::Primitive(PrimitiveType::Address) => true,
+        Type::Vector(elem_ty) => is_bcs_supported_type(ctx, elem_ty, deserialize),
+        // The entries of a table are in storage, and cannot be serialized with it
+        Type::Struct(mid, sid, _) if ctx.is_table(mid.qualified(*sid)) => false,
+        Type::Struct(mid, sid, inst) => {
+            ctx.is_native_int(mid.qualified(*sid))
+                || ctx
+                    .env
+                    .get_struct(mid.qualified(*sid))
+                    .get_fields()
+                    .all(|field| {
+                        is_bcs_supported_type(ctx, &field.get_type().instantiate(inst), deserialize)
+                    })
+        }
+        _ => false,
+    }
+}
+
+/// Generate the function `(value, pos) -> end` serializing a value of the type to memory at
+/// `pos`, and returning the end of the serialization. Structs and vectors are read through their
+/// pointers, which may point to memory or storage.
+pub(crate) fn generate_bcs_serialization(gen: &mut Generator, ctx: &Context, ty: &Type) -> String {
+    let function_name = format!("bcs_serialize_{}", ctx.mangle_type(ty));
+    let ty = ty.clone(); // need to move into lambda
+
+    let generate_fun = move |gen: &mut Generator, ctx: &Context| {
+        emit!(ctx.writer, "(value, pos) -> end ");
+        ctx.emit_block(|| match &ty {
+            Type::Primitive(PrimitiveType::Bool) | Type::Primitive(PrimitiveType::U8) => {
+                emitln!(ctx.writer, "mstore8(pos, value)");
+                emitln!(ctx.writer, "end := add(pos, 1)");
+            }
+            Type::Primitive(PrimitiveType::Address) | Type::Primitive(PrimitiveType::Signer) => {
+                emitln!(ctx.writer, "mstore(pos, shl(96, value))");
+                emitln!(ctx.writer, "end := add(pos, 20)");
+            }
+            Type::Vector(elem_ty) => {
+                let elem_size = ctx.type_size(elem_ty);
+                let length = gen.call_builtin_str(
+                    ctx,
+                    YulFunction::LoadU64,
+                    std::iter::once("value".to_string()),
+                );
+                let store_length = gen.call_builtin_str(
+                    ctx,
+                    YulFunction::MemoryStoreUleb128,
+                    vec!["pos".to_string(), "length".to_string()].into_iter(),
+                );
+                let elem = load_bytes(
+                    gen,
+                    ctx,
+                    format!("add(${{VECTOR_METADATA_SIZE}}, mul(i, {}))", elem_size),
+                    elem_size,
+                );
+                let serialize_elem = generate_bcs_serialization(gen, ctx, elem_ty);
+                emitln!(ctx.writer, "let length := {}", length);
+                emitln!(ctx.writer, "end := {}", store_length);
+                emit!(
+                    ctx.writer,
+                    "for { let i := 0 } lt(i, length) { i := add(i, 1) } "
+                );
+                ctx.emit_block(|| {
+                    emitln!(ctx.writer, "end := {}({}, end)", serialize_elem, elem);
+                });
+            }
+            Type::Struct(mid, sid, inst) if !ctx.is_native_int(mid.qualified(*sid)) => {
+                // The layout offsets are indexed by the position of the fields in the declaration
+                let layout = ctx.get_struct_layout(&mid.qualified(*sid).instantiate(inst.clone()));
+                emitln!(ctx.writer, "end := pos");
+                for (byte_offs, field_ty) in layout.offsets.values() {
+                    let field =
+                        load_bytes(gen, ctx, byte_offs.to_string(), ctx.type_size(field_ty));
+                    let serialize_field = generate_bcs_serialization(gen, ctx, field_ty);
+                    emitln!(ctx.writer, "end := {}({}, end)", serialize_field, field);
+                }
+            }
+            _ => {
+                // Other integers, including U256
+                let size = ctx.type_size(&ty);
+                emitln!(
+                    ctx.writer,
+                    "{}",
+                    gen.call_builtin_str(
+                        ctx,
+                        YulFunction::MemoryStoreLittleEndian,
+                        vec!["pos".to_string(), size.to_string(), "value".to_string()].into_iter(),
+                    )
+                );
+                emitln!(ctx.writer, "end := add(pos, {})", size);
+            }
+        });
+    };
+    gen.need_auxiliary_function(function_name, Box::new(generate_fun))
+}
+
+/// Generate the function `(pos, end) -> value, next` deserializing a value of the type from
+/// memory at `pos`, and returning the position following it. The function aborts if the
+/// serialization is malformed or exceeds `end`.
+pub(crate) fn generate_bcs_deserialization(
+    gen: &mut Generator,
+    ctx: &Context,
+    ty: &Type,
+) -> String {
+    let function_name = format!("bcs_deserialize_{}", ctx.mangle_type(ty));
+    let ty = ty.clone(); // need to move into lambda
+
+    let generate_fun = move |gen: &mut Generator, ctx: &Context| {
+        emit!(ctx.writer, "(pos, end) -> value, next ");
+        ctx.emit_block(|| match &ty {
+            Type::Vector(elem_ty) => {
+                let elem_size = ctx.type_size(elem_ty);
+                let load_length = gen.call_builtin_str(
+                    ctx,
+                    YulFunction::MemoryLoadUleb128,
+                    vec!["pos".to_string(), "end".to_string()].into_iter(),
+                );
+                let malloc = gen.call_builtin_str(
+                    ctx,
+                    YulFunction::MallocVector,
+                    vec![elem_size.to_string(), "length".to_string()].into_iter(),
+                );
+                let store_elem = gen.call_builtin_str(
+                    ctx,
+                    YulFunction::MemoryStoreBytes,
+                    vec![
+                        format!("add(data, mul(i, {}))", elem_size),
+                        elem_size.to_string(),
+                        "elem".to_string(),
+                    ]
+                    .into_iter(),
+                );
+                let deserialize_elem = generate_bcs_deserialization(gen, ctx, elem_ty);
+                emitln!(ctx.writer, "let length, elems := {}", load_length);
+                emitln!(ctx.writer, "let mem := {}", malloc);
+                emitln!(ctx.writer, "let data := add(mem, ${VECTOR_METADATA_SIZE})");
+                emitln!(ctx.writer, "next := elems");
+                emit!(
+                    ctx.writer,
+                    "for { let i := 0 } lt(i, length) { i := add(i, 1) } "
+                );
+                ctx.emit_block(|| {
+                    emitln!(ctx.writer, "let elem := 0");
+                    emitln!(ctx.writer, "elem, next := {}(next, end)", deserialize_elem);
+                    emitln!(ctx.writer, "{}", store_elem);
+                });
+                emitln!(ctx.writer, "value := {}", make_memory_ptr(gen, ctx, "mem"));
+            }
+            Type::Struct(mid, sid, inst) if !ctx.is_native_int(mid.qualified(*sid)) => {
+                let layout = ctx.get_struct_layout(&mid.qualified(*sid).instantiate(inst.clone()));
+                let malloc = gen.call_builtin_str(
+                    ctx,
+                    YulFunction::Malloc,
+                    std::iter::once(layout.size.to_string()),
+                );
+                emitln!(ctx.writer, "let mem := {}", malloc);
+                emitln!(ctx.writer, "next := pos");
+                for (byte_offs, field_ty) in layout.offsets.values() {
+                    let deserialize_field = generate_bcs_deserialization(gen, ctx, field_ty);
+                    let store_field = gen.call_builtin_str(
+                        ctx,
+                        YulFunction::MemoryStoreBytes,
+                        vec![
+                            format!("add(mem, {})", byte_offs),
+                            ctx.type_size(field_ty).to_string(),
+                            "field".to_string(),
+                        ]
+                        .into_iter(),
+                    );
+                    ctx.emit_block(|| {
+                        emitln!(ctx.writer, "let field := 0");
+                        emitln!(
+                            ctx.writer,
+                            "field, next := {}(next, end)",
+                            deserialize_field
+                        );
+                        emitln!(ctx.writer, "{}", store_field);
+                    });
+                }
+                emitln!(ctx.writer, "value := {}", make_memory_ptr(gen, ctx, "mem"));
+            }
+            _ => {
+                let abort =
+                    gen.call_builtin_str(ctx, YulFunction::AbortBuiltin, std::iter::empty());
+                let size = ctx.type_size(&ty);
+                emitln!(ctx.writer, "next := add(pos, {})", size);
+                emitln!(ctx.writer, "if gt(next, end) {{ {} }}", abort);
+                match &ty {
+                    Type::Primitive(PrimitiveType::Bool) => {
+                        emitln!(ctx.writer, "value := byte(0, mload(pos))");
+                        emitln!(ctx.writer, "if gt(value, 1) {{ {} }}", abort);
+                    }
+                    Type::Primitive(PrimitiveType::U8) => {
+                        emitln!(ctx.writer, "value := byte(0, mload(pos))");
+                    }
+                    Type::Primitive(PrimitiveType::Address) => {
+                        emitln!(ctx.writer, "value := shr(96, mload(pos))");
+                    }
+                    _ => {
+                        // Other integers, including U256
+                        let load = gen.call_builtin_str(
+                            ctx,
+                            YulFunction::MemoryLoadLittleEndian,
+                            vec!["pos".to_string(), size.to_string()].into_iter(),
+                        );
+                        emitln!(ctx.writer, "value := {}", load);
+                    }
+                }
+            }
+        });
+    };
+    gen.need_auxiliary_function(function_name, Box::new(generate_fun))
+}
+
+/// Load size bytes at offset from the struct or vector `value` points to.
+fn load_bytes(gen: &mut Generator, ctx: &Context, offset: String, size: usize) -> String {
+    let ptr = gen.call_builtin_str(
+        ctx,
+        YulFunction::IndexPtr,
+        vec!["value".to_string(), offset].into_iter(),
+    );
+    gen.call_builtin_str(
+        ctx,
+        YulFunction::LoadBytes,
+        vec![ptr, size.to_string()].into_iter(),
+    )
+}
+
+/// Make a pointer to memory at offset.
+fn make_memory_ptr(gen: &mut Generator, ctx: &Context, offset: &str) -> String {
+    gen.call_builtin_str(
+        ctx,
+        YulFunction::MakePtr,
+        vec!["false".to_string(), offset.to_string()].into_iter(),
+    )
+}
@@ -0,0 +1,74 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use anyhow::anyhow;
+use codespan_reporting::term::termcolor::{ColorChoice, StandardStream};
+use move_model::{
+    model::GlobalEnv, options::ModelBuilderOptions, parse_addresses_from_options,
+    run_model_builder_with_options,
+};
+use move_to_yul::mangling;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "move-to-yul-demangle",
+    about = "Translates names generated by move-to-yul back into Move names"
+)]
+struct Args {
+    /// Directories where to lookup dependencies.
+    #[structopt(short)]
+    pub dependencies: Vec<String>,
+    /// Named address mapping.
+    #[structopt(short)]
+    pub named_address_mapping: Vec<String>,
+    /// Sources the names were generated from.
+    #[structopt(long = "source", short = "s")]
+    pub sources: Vec<String>,
+    /// Names to demangle. A name starting with `0x` is interpreted as a storage type hash.
+    pub names: Vec<String>,
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("{}", e);
+        std::process::exit(1)
+    }
+}
+
+fn run() -> anyhow::Result<()> {
+    let args = Args::from_args();
+    let env = run_model_builder_with_options(
+        &args.sources,
+        &args.dependencies,
+        ModelBuilderOptions::default(),
+        parse_addresses_from_options(args.named_address_mapping.clone())?,
+    )?;
+    if env.has_errors() {
+        let mut error_writer = StandardStream::stderr(ColorChoice::Auto);
+        env.report_diag(
+            &mut error_writer,
+            codespan_reporting::diagnostic::Severity::Warning,
+        );
+        return Err(anyhow!("exiting with Move build errors"));
+    }
+    for name in &args.names {
+        println!(
+            "{} => {}",
+            name,
+            demangle(&env, name).unwrap_or_else(|| "<unknown>".to_string())
+        );
+    }
+    Ok(())
+}
+
+fn demangle(env: &GlobalEnv, name: &str) -> Option<String> {
+    match name.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16)
+            .ok()
+            .and_then(|hash| mangling::demangle_type_hash(env, hash)),
+        None => mangling::demangle(env, name),
+    }
+}
@@ -0,0 +1,90 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use anyhow::anyhow;
+use codespan_reporting::term::termcolor::{ColorChoice, StandardStream};
+use move_model::{
+    model::GlobalEnv, options::ModelBuilderOptions, parse_addresses_from_options,
+    run_model_builder_with_options,
+};
+use move_to_yul::{
+    contract_diff::{ContractDiff, ContractSummary},
+    options::Options,
+};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "move-to-yul-diff-contracts",
+    about = "Reports the upgrade impact between two builds of a contract"
+)]
+struct Args {
+    /// Directories where to lookup dependencies.
+    #[structopt(short)]
+    pub dependencies: Vec<String>,
+    /// Named address mapping.
+    #[structopt(short)]
+    pub named_address_mapping: Vec<String>,
+    /// Sources of the currently deployed build of the contract.
+    #[structopt(long = "old", required = true)]
+    pub old_sources: Vec<String>,
+    /// Sources of the build of the contract to upgrade to.
+    #[structopt(long = "new", required = true)]
+    pub new_sources: Vec<String>,
+    /// Exit with an error if the upgrade contains breaking changes.
+    #[structopt(long = "deny-breaking")]
+    pub deny_breaking: bool,
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("{}", e);
+        std::process::exit(1)
+    }
+}
+
+fn run() -> anyhow::Result<()> {
+    let args = Args::from_args();
+    let old = summarize(&args, &args.old_sources)?;
+    let new = summarize(&args, &args.new_sources)?;
+    let diff = ContractDiff::new(&old, &new);
+    println!("Upgrade of contract {}", old.name);
+    diff.report(&mut std::io::stdout())?;
+    if args.deny_breaking && diff.is_breaking() {
+        return Err(anyhow!("upgrade contains breaking changes"));
+    }
+    Ok(())
+}
+
+fn summarize(args: &Args, sources: &[String]) -> anyhow::Result<ContractSummary> {
+    let env = run_model_builder_with_options(
+        sources,
+        &args.dependencies,
+        ModelBuilderOptions::default(),
+        parse_addresses_from_options(args.named_address_mapping.clone())?,
+    )?;
+    check_errors(&env, "exiting with Move build errors")?;
+    let options = Options {
+        dependencies: args.dependencies.clone(),
+        named_address_mapping: args.named_address_mapping.clone(),
+        sources: sources.to_vec(),
+        ..Options::default()
+    };
+    let summary = ContractSummary::new(&options, &env)?;
+    check_errors(&env, "exiting with Yul generation errors")?;
+    Ok(summary)
+}
+
+fn check_errors(env: &GlobalEnv, msg: &'static str) -> anyhow::Result<()> {
+    if env.has_errors() {
+        let mut error_writer = StandardStream::stderr(ColorChoice::Auto);
+        env.report_diag(
+            &mut error_writer,
+            codespan_reporting::diagnostic::Severity::Warning,
+        );
+        return Err(anyhow!(msg));
+    }
+    Ok(())
+}
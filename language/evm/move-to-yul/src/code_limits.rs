@@ -0,0 +1,297 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Diagnostics for the limits the EVM puts on contracts: the size of the deployed code, which
+//! EIP-170 limits to 24KB, and the number of stack slots a function can use, which is limited by
+//! the reach of `DUP16` and `SWAP16`.
+//!
+//! Both are estimated on the generated Yul code of the deployed object, so that users are warned
+//! about the Move functions responsible before solc or the deployment fails. The size estimate
+//! assumes an unoptimized translation of each Yul construct to EVM instructions, so it only
+//! indicates the order of magnitude.
+
+use crate::context::Context;
+use codespan_reporting::diagnostic::Severity;
+use move_model::model::{FunId, FunctionEnv, Loc, QualifiedId};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The maximal size of deployed code in bytes, as given by EIP-170.
+const MAX_CODE_SIZE: usize = 24_576;
+
+/// The maximal number of stack slots of a function which can be accessed, given by the reach of
+/// `DUP16` and `SWAP16`.
+const MAX_STACK_SLOTS: usize = 16;
+
+/// The percentage of a limit from which on a warning is reported.
+const WARNING_PERCENTAGE: usize = 80;
+
+/// The maximal number of Move functions reported as contributing to a limit.
+const MAX_REPORTED_FUNCTIONS: usize = 3;
+
+/// Estimated size of a call of a Yul function: pushing the return label and the target, the
+/// jump, and the jump destination returned to.
+const CALL_SIZE: usize = 8;
+
+/// Estimated size of a control flow construct, like the conditional jump of an `if`.
+const CONTROL_FLOW_SIZE: usize = 5;
+
+static COMMENT_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)/\*.*?\*/|//[^\n]*").unwrap());
+
+static FUNCTION_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"function\s+([\w$]+)\s*\(([^)]*)\)\s*(?:->\s*([^{]*))?\{").unwrap());
+
+static TOKEN_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r#"hex"[0-9a-fA-F]*"|0x[0-9a-fA-F]+|[0-9]+|"(?:[^"\\]|\\.)*"|"#,
+        r#"[A-Za-z_$][\w$.]*(?:\s*\()?|:="#
+    ))
+    .unwrap()
+});
+
+static LET_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\blet\s+([\w$]+(?:\s*,\s*[\w$]+)*)").unwrap());
+
+/// Estimates of a Yul function.
+#[derive(Default)]
+struct FunctionInfo {
+    /// The size of the code of the function in bytes.
+    size: usize,
+    /// The stack slots used by a call of the function: its parameters, results, and local
+    /// variables.
+    slots: usize,
+    /// The functions called by the function.
+    callees: BTreeSet<String>,
+}
+
+/// Check the size of the deployed code and the stack slots of its functions for the given
+/// contract, reporting warnings if they approach the limits of the EVM. The Move functions in the deployed
+/// code are given by the names of the Yul functions they are translated to.
+pub(crate) fn check_code_limits(
+    ctx: &Context,
+    contract_name: &str,
+    move_functions: &BTreeMap<String, QualifiedId<FunId>>,
+) {
+    let deployed_object = format!("object \"{}_deployed\"", contract_name);
+    let code = ctx.writer.process_result(|code| {
+        code.find(&deployed_object)
+            .map(|start| COMMENT_REGEX.replace_all(&code[start..], "").into_owned())
+    });
+    let code = match code {
+        Some(code) => code,
+        None => return,
+    };
+    let (funs, top_level) = analyze_functions(&code);
+
+    let size = top_level.size + funs.values().map(|info| info.size).sum::<usize>();
+    if size * 100 >= MAX_CODE_SIZE * WARNING_PERCENTAGE {
+        let mut contributions: BTreeMap<QualifiedId<FunId>, usize> = BTreeMap::new();
+        // Helpers called by a single Move function, like those creating its constants, count
+        // for this function
+        let mut helper_callers: BTreeMap<&str, BTreeSet<QualifiedId<FunId>>> = BTreeMap::new();
+        for (name, info) in &funs {
+            if let Some(fun_id) = move_functions.get(name) {
+                *contributions.entry(*fun_id).or_default() += info.size;
+                for callee in &info.callees {
+                    if !move_functions.contains_key(callee) {
+                        helper_callers
+                            .entry(callee.as_str())
+                            .or_default()
+                            .insert(*fun_id);
+                    }
+                }
+            }
+        }
+        for (helper, callers) in helper_callers {
+            if callers.len() == 1 {
+                let fun_id = callers.into_iter().next().unwrap();
+                *contributions.entry(fun_id).or_default() += funs[helper].size;
+            }
+        }
+        report(
+            ctx,
+            &format!(
+                "estimated size of the deployed code of contract `{}` is {} bytes, {} the \
+                 limit of {} bytes of EIP-170",
+                contract_name,
+                size,
+                if size > MAX_CODE_SIZE {
+                    "exceeding"
+                } else {
+                    "approaching"
+                },
+                MAX_CODE_SIZE
+            ),
+            contributions,
+            "bytes",
+        );
+    }
+
+    for (name, fun_id) in move_functions {
+        let slots = match funs.get(name) {
+            Some(info) => info.slots,
+            None => continue,
+        };
+        if slots > MAX_STACK_SLOTS {
+            let fun = ctx.env.get_function(*fun_id);
+            ctx.env.diag(
+                Severity::Warning,
+                &fun.get_loc(),
+                &format!(
+                    "`{}` uses an estimated {} stack slots for its parameters, results, and \
+                     locals, exceeding the {} slots reachable by the EVM; solc may fail with \
+                     `stack too deep`",
+                    fun.get_full_name_str(),
+                    slots,
+                    MAX_STACK_SLOTS
+                ),
+            );
+        }
+    }
+}
+
+/// Report a warning at the Move function contributing most to a limit, labelling the next ones.
+fn report(
+    ctx: &Context,
+    msg: &str,
+    contributions: BTreeMap<QualifiedId<FunId>, usize>,
+    unit: &str,
+) {
+    let mut largest = contributions
+        .into_iter()
+        .map(|(fun_id, amount)| (ctx.env.get_function(fun_id), amount))
+        .collect::<Vec<_>>();
+    largest.sort_by(|(_, a1), (_, a2)| a2.cmp(a1));
+    largest.truncate(MAX_REPORTED_FUNCTIONS);
+    match largest.first() {
+        Some((fun, amount)) => {
+            let (loc, first_label) = contribution_label(fun, *amount, unit);
+            ctx.env.diag_with_labels(
+                Severity::Warning,
+                &loc,
+                &format!("{}\n{}", msg, first_label),
+                largest[1..]
+                    .iter()
+                    .map(|(fun, amount)| contribution_label(fun, *amount, unit))
+                    .collect(),
+            )
+        }
+        None => ctx.env.diag(Severity::Warning, &ctx.env.unknown_loc(), msg),
+    }
+}
+
+/// Returns a label at the given Move function, saying how much it contributes to a limit.
+fn contribution_label(fun: &FunctionEnv<'_>, amount: usize, unit: &str) -> (Loc, String) {
+    (
+        fun.get_loc(),
+        format!(
+            "`{}` contributes about {} {}",
+            fun.get_full_name_str(),
+            amount,
+            unit
+        ),
+    )
+}
+
+/// Returns the estimates of the Yul functions defined in the code, by name, and of the code
+/// outside of these functions.
+fn analyze_functions(code: &str) -> (BTreeMap<String, FunctionInfo>, FunctionInfo) {
+    let mut bodies = vec![];
+    let mut top_level = String::new();
+    let mut last_end = 0;
+    for cap in FUNCTION_REGEX.captures_iter(code) {
+        let header = cap.get(0).unwrap();
+        if header.start() < last_end {
+            // A function nested in another one, which is part of the outer body
+            continue;
+        }
+        let end = block_end(code, header.end());
+        let params = cap
+            .get(2)
+            .into_iter()
+            .chain(cap.get(3))
+            .flat_map(|names| names.as_str().split(','))
+            .filter(|name| !name.trim().is_empty())
+            .count();
+        bodies.push((cap[1].to_string(), params, &code[header.end()..end]));
+        top_level.push_str(&code[last_end..header.start()]);
+        last_end = end;
+    }
+    top_level.push_str(&code[last_end..]);
+
+    let names: BTreeSet<_> = bodies.iter().map(|(name, _, _)| name.clone()).collect();
+    let funs = bodies
+        .into_iter()
+        .map(|(name, params, body)| {
+            let mut info = analyze_code(body, &names);
+            // The jump destination of the function, and a pop of each parameter and result
+            info.size += 1 + params;
+            // The parameters and results
+            info.slots += params;
+            (name, info)
+        })
+        .collect();
+    (funs, analyze_code(&top_level, &names))
+}
+
+/// Returns the estimates of a piece of code, given the names of the Yul functions defined.
+fn analyze_code(code: &str, functions: &BTreeSet<String>) -> FunctionInfo {
+    let mut info = FunctionInfo::default();
+    for token in TOKEN_REGEX.find_iter(code).map(|token| token.as_str()) {
+        info.size += if let Some(hex) = token.strip_prefix("hex\"") {
+            // The bytes of a data object
+            (hex.len() - 1) / 2
+        } else if let Some(hex) = token.strip_prefix("0x") {
+            // A push of the bytes of the literal
+            1 + (hex.len() + 1) / 2
+        } else if token.starts_with(|c: char| c.is_ascii_digit()) {
+            1 + (token.len() * 415 + 999) / 1000
+        } else if token.starts_with('"') {
+            33
+        } else if let Some(name) = token.strip_suffix('(') {
+            let name = name.trim_end();
+            if functions.contains(name) {
+                info.callees.insert(name.to_string());
+                CALL_SIZE
+            } else {
+                // An EVM instruction
+                1
+            }
+        } else {
+            match token {
+                "if" | "for" | "switch" | "case" | "default" | "leave" | "break" | "continue" => {
+                    CONTROL_FLOW_SIZE
+                }
+                "let" | "function" | "object" | "code" | "data" => 0,
+                // A push of a constant, a copy of a variable, or a swap to assign one
+                _ => 1,
+            }
+        }
+    }
+    info.slots = LET_REGEX
+        .captures_iter(code)
+        .map(|cap| cap[1].split(',').count())
+        .sum();
+    info
+}
+
+/// Returns the index of the closing brace of the block whose content starts at `start`.
+pub(crate) fn block_end(code: &str, start: usize) -> usize {
+    let mut depth = 1;
+    let mut in_string = false;
+    for (i, c) in code[start..].char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '{' if !in_string => depth += 1,
+            '}' if !in_string => {
+                depth -= 1;
+                if depth == 0 {
+                    return start + i;
+                }
+            }
+            _ => {}
+        }
+    }
+    code.len()
+}
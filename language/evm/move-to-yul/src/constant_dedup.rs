@@ -0,0 +1,313 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Shares large constants between the places they are loaded at.
+//!
+//! A constant is pushed inline on each load, which takes one byte for the push plus the bytes
+//! of the constant. If a large constant is loaded many times, it is cheaper in code size to
+//! push it once in a function which is called on each load instead. A call costs gas though,
+//! so constants are only shared if this saves code, estimated with the sizes below.
+//!
+//! The same holds for the type hash of a resource, which is pushed together with the storage
+//! category on each access to the resource. Byte string constants are already created by one
+//! function per code block, which stores them word by word; large ones are copied from a data
+//! object of the code block instead, which only holds the bytes.
+
+use crate::{context::Context, mangling, options::Options};
+use ethnum::U256;
+use move_model::{
+    model::GlobalEnv,
+    ty::{Type, TypeDisplayContext},
+};
+use move_stackless_bytecode::{
+    function_target_pipeline::FunctionTargetsHolder,
+    stackless_bytecode::{Bytecode, Constant, Operation},
+};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Formatter},
+};
+
+/// The estimated code size of a call to the function of a shared constant: pushing the return
+/// label and the function label, the jump, and the jump destination to return to.
+const CALL_SIZE: usize = 8;
+
+/// The estimated code size of the function of a shared constant, without the push of the
+/// constant: the jump destination, a swap of the constant with the return label, and the jump
+/// back.
+const FUNCTION_SIZE: usize = 3;
+
+/// The estimated gas each load of a shared constant costs in addition to the inline push: two
+/// pushes of labels, two jumps, two jump destinations, and a swap.
+const CALL_GAS: u64 = 3 + 3 + 8 + 8 + 1 + 1 + 3;
+
+/// The estimated code size of the arguments identifying the storage of a resource type: the
+/// pushes of the storage category and of the type hash.
+const STORAGE_BASE_ARGS_SIZE: usize = 2 + 5;
+
+/// The estimated code size of storing a word of a byte string: the push of the word, computing
+/// its offset in the vector, and the store.
+const WORD_STORE_SIZE: usize = 33 + 7;
+
+/// The estimated code size of copying a byte string from a data object, without the bytes
+/// themselves: the pushes of the offset and size of the data, computing the destination in the
+/// vector, and the copy.
+const DATA_COPY_SIZE: usize = 3 + 3 + 4 + 1;
+
+/// A constant loaded through a shared function.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SharedConstant {
+    pub value: U256,
+    /// The number of places the constant is loaded at.
+    pub loads: usize,
+}
+
+impl SharedConstant {
+    /// The number of bytes the constant takes in a push.
+    fn size(&self) -> usize {
+        ((256 - self.value.leading_zeros() as usize + 7) / 8).max(1)
+    }
+
+    /// The estimated code size saved by sharing the constant, which is negative if it is not
+    /// worth it.
+    fn bytes_saved(&self) -> isize {
+        let inline = self.loads * (1 + self.size());
+        let shared = self.loads * CALL_SIZE + 1 + self.size() + FUNCTION_SIZE;
+        inline as isize - shared as isize
+    }
+
+    /// The name of the function the constant is loaded with.
+    pub fn function_name(&self) -> String {
+        format!("const_{:x}", self.value)
+    }
+}
+
+/// A byte string constant copied from a data object.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SharedByteArray {
+    pub bytes: Vec<u8>,
+    /// The number of places the constant is loaded at.
+    pub loads: usize,
+    /// The name of the data object holding the bytes.
+    pub data_name: String,
+}
+
+impl SharedByteArray {
+    /// The estimated code size saved by copying the bytes from a data object, which is negative
+    /// if it is not worth it.
+    fn bytes_saved(&self) -> isize {
+        let stored = (self.bytes.len() + 31) / 32 * WORD_STORE_SIZE;
+        let copied = self.bytes.len() + DATA_COPY_SIZE;
+        stored as isize - copied as isize
+    }
+}
+
+/// A resource type whose storage is identified through a shared function.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SharedTypeHash {
+    pub hash: u32,
+    /// The resource type, as displayed to users.
+    pub display: String,
+    /// The number of places the resource is accessed at.
+    pub loads: usize,
+}
+
+impl SharedTypeHash {
+    /// The estimated code size saved by sharing the type hash, which is negative if it is not
+    /// worth it.
+    fn bytes_saved(&self) -> isize {
+        let inline = self.loads * STORAGE_BASE_ARGS_SIZE;
+        let shared = STORAGE_BASE_ARGS_SIZE + CALL_SIZE + FUNCTION_SIZE;
+        inline as isize - shared as isize
+    }
+
+    /// The name of the function computing the storage base of a resource from its address.
+    pub fn function_name(&self) -> String {
+        format!("resource_base_{:08x}", self.hash)
+    }
+}
+
+/// The constants shared in the generated code.
+#[derive(Debug, Clone, Default)]
+pub struct SharedConstants {
+    shared: BTreeMap<U256, SharedConstant>,
+    byte_arrays: BTreeMap<Vec<u8>, SharedByteArray>,
+    type_hashes: BTreeMap<Type, SharedTypeHash>,
+}
+
+impl SharedConstants {
+    /// Determine the constants worth sharing in the code of the given targets.
+    pub(crate) fn analyze(env: &GlobalEnv, targets: &FunctionTargetsHolder) -> Self {
+        let mut loads: BTreeMap<U256, usize> = BTreeMap::new();
+        let mut byte_array_loads: BTreeMap<Vec<u8>, usize> = BTreeMap::new();
+        let mut resource_accesses: BTreeMap<Type, usize> = BTreeMap::new();
+        for (fun_id, variant) in targets.get_funs_and_variants() {
+            let data = targets
+                .get_data(&fun_id, &variant)
+                .expect("function data exists");
+            for bc in &data.code {
+                match bc {
+                    Bytecode::Load(_, _, Constant::ByteArray(bytes)) => {
+                        *byte_array_loads.entry(bytes.clone()).or_default() += 1;
+                    }
+                    Bytecode::Load(_, _, cons) => {
+                        if let Some(value) = numeric_value(cons) {
+                            *loads.entry(value).or_default() += 1;
+                        }
+                    }
+                    Bytecode::Call(_, _, op, _, _) => {
+                        // Resources of generic types are only known when instantiated
+                        if let Some(ty) = resource_type(op).filter(|ty| !ty.is_open()) {
+                            *resource_accesses.entry(ty).or_default() += 1;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        let shared = loads
+            .into_iter()
+            .map(|(value, loads)| (value, SharedConstant { value, loads }))
+            .filter(|(_, constant)| constant.bytes_saved() > 0)
+            .collect();
+        // The data objects are named by their position, as the bytes may be long
+        let byte_arrays = byte_array_loads
+            .into_iter()
+            .map(|(bytes, loads)| SharedByteArray {
+                bytes,
+                loads,
+                data_name: String::new(),
+            })
+            .filter(|constant| constant.bytes_saved() > 0)
+            .enumerate()
+            .map(|(i, mut constant)| {
+                constant.data_name = format!("bytes_{}", i);
+                (constant.bytes.clone(), constant)
+            })
+            .collect();
+        let display_ctx = TypeDisplayContext::WithEnv {
+            env,
+            type_param_names: None,
+        };
+        let type_hashes = resource_accesses
+            .into_iter()
+            .map(|(ty, loads)| {
+                let constant = SharedTypeHash {
+                    hash: mangling::type_hash(env, &ty),
+                    display: ty.display(&display_ctx).to_string(),
+                    loads,
+                };
+                (ty, constant)
+            })
+            .filter(|(_, constant)| constant.bytes_saved() > 0)
+            .collect();
+        Self {
+            shared,
+            byte_arrays,
+            type_hashes,
+        }
+    }
+
+    /// Report the constants shared when compiling the contract of the given environment.
+    pub fn new(options: &Options, env: &GlobalEnv) -> Self {
+        Context::new(options, env, false).shared_constants
+    }
+
+    /// Get the shared constant loaded by the given constant, if it is shared.
+    pub fn get(&self, cons: &Constant) -> Option<&SharedConstant> {
+        self.shared.get(&numeric_value(cons)?)
+    }
+
+    /// Get the byte string constant with the given bytes, if it is copied from a data object.
+    pub fn get_byte_array(&self, bytes: &[u8]) -> Option<&SharedByteArray> {
+        self.byte_arrays.get(bytes)
+    }
+
+    /// Get the type hash of the given resource type, if it is shared.
+    pub fn get_type_hash(&self, ty: &Type) -> Option<&SharedTypeHash> {
+        self.type_hashes.get(ty)
+    }
+
+    /// The estimated code size saved by sharing the constants.
+    pub fn bytes_saved(&self) -> usize {
+        self.shared
+            .values()
+            .map(|constant| constant.bytes_saved())
+            .chain(
+                self.byte_arrays
+                    .values()
+                    .map(|constant| constant.bytes_saved()),
+            )
+            .chain(
+                self.type_hashes
+                    .values()
+                    .map(|constant| constant.bytes_saved()),
+            )
+            .sum::<isize>() as usize
+    }
+}
+
+impl fmt::Display for SharedConstants {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Shared {} constant(s), saving about {} byte(s) of code at the cost of about {} gas \
+             per load of a number or type hash",
+            self.shared.len() + self.byte_arrays.len() + self.type_hashes.len(),
+            self.bytes_saved(),
+            CALL_GAS
+        )?;
+        for constant in self.shared.values() {
+            writeln!(
+                f,
+                "  0x{:x} ({} load(s), {} byte(s) saved)",
+                constant.value,
+                constant.loads,
+                constant.bytes_saved()
+            )?;
+        }
+        for constant in self.byte_arrays.values() {
+            writeln!(
+                f,
+                "  {} byte(s) in data \"{}\" ({} load(s), {} byte(s) saved)",
+                constant.bytes.len(),
+                constant.data_name,
+                constant.loads,
+                constant.bytes_saved()
+            )?;
+        }
+        for constant in self.type_hashes.values() {
+            writeln!(
+                f,
+                "  type hash 0x{:08x} of `{}` ({} access(es), {} byte(s) saved)",
+                constant.hash,
+                constant.display,
+                constant.loads,
+                constant.bytes_saved()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The value of a numeric constant which may be large enough to be shared.
+fn numeric_value(cons: &Constant) -> Option<U256> {
+    match cons {
+        Constant::U64(v) => Some(U256::from(*v)),
+        Constant::U128(v) => Some(U256::from(*v)),
+        Constant::U256(v) => Some(*v),
+        _ => None,
+    }
+}
+
+/// The type of the resource accessed by an operation, whose storage is identified by its type
+/// hash.
+fn resource_type(op: &Operation) -> Option<Type> {
+    match op {
+        Operation::MoveTo(mid, sid, inst)
+        | Operation::MoveFrom(mid, sid, inst)
+        | Operation::Exists(mid, sid, inst)
+        | Operation::BorrowGlobal(mid, sid, inst) => Some(Type::Struct(*mid, *sid, inst.clone())),
+        _ => None,
+    }
+}
@@ -2,15 +2,21 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    attributes, evm_transformation::EvmTransformationProcessor, native_functions::NativeFunctions,
-    yul_functions, yul_functions::YulFunction, Options,
+    attributes, constant_dedup::SharedConstants, evm_transformation::EvmTransformationProcessor,
+    function_pruning::FunctionPruningProcessor, mangling, native_functions::NativeFunctions,
+    scratch_allocation::ScratchAllocationProcessor, yul_functions, yul_functions::YulFunction,
+    Options,
 };
+use codespan_reporting::diagnostic::Severity;
 use itertools::Itertools;
 use move_model::{
-    ast::TempIndex,
+    ast::{ModuleName, TempIndex},
     code_writer::CodeWriter,
     emitln,
-    model::{FunId, FunctionEnv, GlobalEnv, ModuleEnv, QualifiedId, QualifiedInstId, StructId},
+    model::{
+        FunId, FunctionEnv, GlobalEnv, Loc, ModuleEnv, QualifiedId, QualifiedInstId, StructId,
+    },
+    symbol::Symbol,
     ty::{PrimitiveType, Type},
 };
 use move_stackless_bytecode::{
@@ -19,7 +25,10 @@ use move_stackless_bytecode::{
     livevar_analysis::LiveVarAnalysisProcessor,
     reaching_def_analysis::ReachingDefProcessor,
 };
-use std::{cell::RefCell, collections::BTreeMap};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet},
+};
 
 /// Immutable context passed through the compilation.
 pub(crate) struct Context<'a> {
@@ -35,6 +44,11 @@ pub(crate) struct Context<'a> {
     pub struct_layout: RefCell<BTreeMap<QualifiedInstId<StructId>, StructLayout>>,
     /// Native function info.
     pub native_funs: NativeFunctions,
+    /// The constants loaded through shared functions.
+    pub shared_constants: SharedConstants,
+    /// The location of the Move declaration currently compiled, at which errors about
+    /// unsupported types are reported.
+    decl_loc: RefCell<Loc>,
 }
 
 /// Information about the layout of a struct in linear memory.
@@ -57,13 +71,21 @@ impl<'a> Context<'a> {
     pub fn new(options: &'a Options, env: &'a GlobalEnv, for_test: bool) -> Self {
         let writer = CodeWriter::new(env.unknown_loc());
         writer.set_emit_hook(yul_functions::substitute_placeholders);
+        let targets = Self::create_bytecode(options, env, for_test);
+        let shared_constants = if options.dedup_constants {
+            SharedConstants::analyze(env, &targets)
+        } else {
+            SharedConstants::default()
+        };
         let mut ctx = Self {
             options,
             env,
-            targets: Self::create_bytecode(options, env, for_test),
+            targets,
             writer,
             struct_layout: Default::default(),
             native_funs: NativeFunctions::default(),
+            shared_constants,
+            decl_loc: RefCell::new(env.unknown_loc()),
         };
         ctx.native_funs = NativeFunctions::create(&ctx);
         ctx
@@ -87,23 +109,29 @@ impl<'a> Context<'a> {
                     || attributes::is_fallback_fun(fun)
             }
         };
+        let mut entries = BTreeSet::new();
         for module in env.get_modules() {
             if !module.is_target() {
                 continue;
             }
             for fun in module.get_functions() {
                 if is_used_fun(&fun) {
-                    Self::add_fun(&mut targets, &fun)
+                    Self::add_fun(&mut targets, &fun);
+                    entries.insert(fun.get_qualified_id());
                 }
             }
         }
-        // Run a minimal transformation pipeline. For now, we do some evm pre-processing,
-        // and reaching-def and live-var to clean up some churn created by the conversion from
-        // stack to stackless bytecode.
+        // Run a minimal transformation pipeline. For now, we do some evm pre-processing, prune
+        // the functions no longer called after it, and run reaching-def and live-var to clean
+        // up some churn created by the conversion from stack to stackless bytecode.
         let mut pipeline = FunctionTargetPipeline::default();
         pipeline.add_processor(EvmTransformationProcessor::new());
+        pipeline.add_processor(FunctionPruningProcessor::new(entries));
         pipeline.add_processor(ReachingDefProcessor::new());
         pipeline.add_processor(LiveVarAnalysisProcessor::new());
+        if options.scratch_allocation {
+            pipeline.add_processor(ScratchAllocationProcessor::new());
+        }
         if options.dump_bytecode {
             pipeline.run_with_dump(env, &mut targets, &options.output, false)
         } else {
@@ -135,7 +163,9 @@ impl<'a> Context<'a> {
             .collect()
     }
 
-    /// Check whether given Move function has no generics; report error otherwise.
+    /// Check whether given Move function has no generics; report error otherwise. This applies
+    /// to entry points only, whose type arguments cannot be given by a transaction: the generic
+    /// functions they call are generated once per instantiation.
     pub fn check_no_generics(&self, fun: &FunctionEnv<'_>) {
         if fun.get_type_parameter_count() > 0 {
             self.env.error(
@@ -145,11 +175,90 @@ impl<'a> Context<'a> {
         }
     }
 
+    /// Check that no two callable functions, given with their function selector, share the
+    /// same selector; report errors otherwise.
+    pub fn check_no_selector_collisions(&self, funs: &[(String, FunctionEnv<'_>)]) {
+        let mut selectors: BTreeMap<&str, &FunctionEnv<'_>> = BTreeMap::new();
+        for (selector, fun) in funs {
+            if let Some(other_fun) = selectors.insert(selector, fun) {
+                self.env.diag_with_labels(
+                    Severity::Error,
+                    &fun.get_loc(),
+                    &format!(
+                        "function selector {} of `{}` collides with the one of `{}`",
+                        selector,
+                        fun.get_full_name_str(),
+                        other_fun.get_full_name_str()
+                    ),
+                    vec![(
+                        other_fun.get_loc(),
+                        format!(
+                            "`{}` also has selector {}",
+                            other_fun.get_full_name_str(),
+                            selector
+                        ),
+                    )],
+                );
+                // Keep reporting further collisions against the first function
+                selectors.insert(selector, other_fun);
+            }
+        }
+    }
+
+    /// Check that no `#[test_only]` function is compiled into a contract, which can be the case
+    /// if compiling in test mode; report errors otherwise.
+    pub fn check_no_test_only_code(&self) {
+        let funs: BTreeSet<_> = self
+            .targets
+            .get_funs_and_variants()
+            .map(|(fun_id, _)| fun_id)
+            .collect();
+        for fun_id in funs {
+            let fun = self.env.get_function(fun_id);
+            if attributes::is_test_only_fun(&fun) {
+                self.env.error(
+                    &fun.get_loc(),
+                    &format!(
+                        "`{}` is test-only code, which can be used by #[evm_test] functions \
+                         but cannot be compiled into a contract",
+                        fun.get_full_name_str()
+                    ),
+                )
+            }
+        }
+    }
+
+    /// Check that all `#[immutable]` structs can be embedded in the deployed code, which requires
+    /// them to be non-generic and their fields to be single words; report errors otherwise.
+    pub fn check_immutable_structs(&self) {
+        for struct_id in self.get_immutable_structs() {
+            let struct_env = self.env.get_struct(struct_id);
+            if !struct_env.get_type_parameters().is_empty() {
+                self.env.error(
+                    &struct_env.get_loc(),
+                    "#[immutable] structs cannot be generic",
+                );
+                continue;
+            }
+            for field in struct_env.get_fields() {
+                if !self.is_word_type(&field.get_type()) {
+                    self.env.error(
+                        &struct_env.get_loc(),
+                        &format!(
+                            "field `{}` of #[immutable] struct `{}` must have a primitive \
+                             type, U256 or I256",
+                            field.get_name().display(self.env.symbol_pool()),
+                            struct_env.get_full_name_str()
+                        ),
+                    )
+                }
+            }
+        }
+    }
+
     /// Make the name of a contract.
     pub fn make_contract_name(&self, module: &ModuleEnv) -> String {
-        let mod_name = module.get_name();
-        let mod_sym = module.symbol_pool().string(mod_name.name());
-        format!("A{}_{}", mod_name.addr().to_str_radix(16), mod_sym)
+        mangling::make_contract_name(module)
     }
 
     /// Make the name of function.
@@ -164,53 +273,26 @@ impl<'a> Context<'a> {
         )
     }
 
-    /// Mangle a type for being part of name.
-    ///
-    /// Note that the mangled type representation is also used to create a hash for types
-    /// in `Generator::type_hash` which is used to index storage. Therefore the representation here
-    /// cannot be changed without creating versioning problems for existing storage of contracts.
+    /// Mangle a type for being part of name. See `mangling::mangle_type` for why this
+    /// representation must be kept stable.
     pub fn mangle_type(&self, ty: &Type) -> String {
-        use move_model::ty::{PrimitiveType::*, Type::*};
-        match ty {
-            Primitive(p) => match p {
-                U8 => "u8".to_string(),
-                U64 => "u64".to_string(),
-                U128 => "u128".to_string(),
-                Num => "num".to_string(),
-                Address => "address".to_string(),
-                Signer => "signer".to_string(),
-                Bool => "bool".to_string(),
-                Range => "range".to_string(),
-                _ => format!("<<unsupported {:?}>>", ty),
-            },
-            Vector(et) => format!("vec{}", self.mangle_types(&[et.as_ref().to_owned()])),
-            Struct(mid, sid, inst) => {
-                self.mangle_struct(&mid.qualified(*sid).instantiate(inst.clone()))
-            }
-            TypeParameter(..) | Fun(..) | Tuple(..) | TypeDomain(..) | ResourceDomain(..)
-            | Error | Var(..) | Reference(..) => format!("<<unsupported {:?}>>", ty),
-        }
+        mangling::mangle_type(self.env, ty)
     }
 
-    /// Mangle a struct.
-    fn mangle_struct(&self, struct_id: &QualifiedInstId<StructId>) -> String {
-        let struct_env = &self.env.get_struct(struct_id.to_qualified_id());
-        let module_name = self.make_contract_name(&struct_env.module_env);
+    /// Make the name under which a field of an `#[immutable]` struct is embedded in the
+    /// deployed code.
+    pub fn make_immutable_name(&self, struct_id: QualifiedId<StructId>, field: Symbol) -> String {
+        let ty = Type::Struct(struct_id.module_id, struct_id.id, vec![]);
         format!(
-            "{}_{}{}",
-            module_name,
-            struct_env.get_name().display(struct_env.symbol_pool()),
-            self.mangle_types(&struct_id.inst)
+            "{}_{}",
+            self.mangle_type(&ty),
+            field.display(self.env.symbol_pool())
         )
     }
 
     /// Mangle a slice of types.
     pub fn mangle_types(&self, tys: &[Type]) -> String {
-        if tys.is_empty() {
-            "".to_owned()
-        } else {
-            format!("${}$", tys.iter().map(|ty| self.mangle_type(ty)).join("_"))
-        }
+        mangling::mangle_types(self.env, tys)
     }
 
     /// Make name for a local.
@@ -249,12 +331,110 @@ impl<'a> Context<'a> {
             .collect()
     }
 
+    /// Returns the field types of the struct instantiation, in declaration order.
+    pub fn get_instantiated_field_types(
+        &self,
+        id: QualifiedId<StructId>,
+        inst: &[Type],
+    ) -> Vec<Type> {
+        self.get_field_types(id)
+            .into_iter()
+            .map(|ty| ty.instantiate(inst))
+            .collect()
+    }
+
     /// Returns whether the struct identified by module_id and struct_id is the native U256 struct.
     pub fn is_u256(&self, struct_id: QualifiedId<StructId>) -> bool {
         let struct_env = self.env.get_struct(struct_id);
         attributes::is_evm_arith_module(&struct_env.module_env) && struct_env.is_native()
     }
 
+    /// Returns whether the struct is the native `Eth::I256::I256` struct.
+    pub fn is_i256(&self, struct_id: QualifiedId<StructId>) -> bool {
+        let struct_env = self.env.get_struct(struct_id);
+        let pool = self.env.symbol_pool();
+        *struct_env.module_env.get_name() == ModuleName::from_str("0x2", pool.make("I256"))
+            && struct_env.is_native()
+    }
+
+    /// Returns whether the struct is one of the native 256 bit integer structs, U256 or I256,
+    /// whose values are represented by a word.
+    pub fn is_native_int(&self, struct_id: QualifiedId<StructId>) -> bool {
+        self.is_u256(struct_id) || self.is_i256(struct_id)
+    }
+
+    /// Returns whether the struct is the `Eth::String::String` struct, which is ABI encoded as a
+    /// `string`.
+    pub fn is_string(&self, struct_id: QualifiedId<StructId>) -> bool {
+        let struct_env = self.env.get_struct(struct_id);
+        let pool = self.env.symbol_pool();
+        *struct_env.module_env.get_name() == ModuleName::from_str("0x2", pool.make("String"))
+            && pool.string(struct_env.get_name()).as_str() == "String"
+    }
+
+    /// Returns whether the struct is the native `Eth::Table::Table` struct. A table is represented
+    /// by the storage group holding its entries, and its values are always in storage.
+    pub fn is_table(&self, struct_id: QualifiedId<StructId>) -> bool {
+        let struct_env = self.env.get_struct(struct_id);
+        let pool = self.env.symbol_pool();
+        *struct_env.module_env.get_name() == ModuleName::from_str("0x2", pool.make("Table"))
+            && struct_env.is_native()
+    }
+
+    /// Returns the `#[immutable]` structs declared in target modules, whose values are set
+    /// during contract creation and then embedded in the deployed code.
+    pub fn get_immutable_structs(&self) -> Vec<QualifiedId<StructId>> {
+        self.env
+            .get_modules()
+            .filter(|m| m.is_target())
+            .map(|m| {
+                m.into_structs()
+                    .filter(|s| attributes::is_immutable_struct(s))
+                    .map(|s| s.get_qualified_id())
+                    .collect_vec()
+            })
+            .flatten()
+            .collect()
+    }
+
+    /// Returns the memory location holding the value of an `#[immutable]` struct before it is
+    /// embedded in the deployed code, or None if the struct is not immutable. Those locations
+    /// follow the memory used by the compilation scheme, in the order of
+    /// `get_immutable_structs`.
+    pub fn immutable_value_loc(&self, struct_id: QualifiedId<StructId>) -> Option<String> {
+        self.get_immutable_structs()
+            .into_iter()
+            .position(|id| id == struct_id)
+            .map(|idx| format!("add(${{USED_MEM}}, {})", idx * 32))
+    }
+
+    /// Returns whether values of the type are a single word, i.e. primitive types, U256 and I256.
+    pub fn is_word_type(&self, ty: &Type) -> bool {
+        match ty {
+            Type::Primitive(_) => true,
+            Type::Struct(mid, sid, _) => self.is_native_int(mid.qualified(*sid)),
+            _ => false,
+        }
+    }
+
+    /// Set the location of the Move declaration currently compiled.
+    pub fn set_decl_loc(&self, loc: &Loc) {
+        *self.decl_loc.borrow_mut() = loc.clone()
+    }
+
+    /// Reports an error at the Move declaration currently compiled for a type whose values
+    /// cannot be handled as described by `what`, e.g. ABI encoded.
+    pub fn unsupported_type_error(&self, ty: &Type, what: &str) {
+        self.env.error(
+            &self.decl_loc.borrow(),
+            &format!(
+                "values of type `{}` cannot be {}",
+                ty.display(&self.env.get_type_display_ctx()),
+                what
+            ),
+        )
+    }
+
     /// Check whether ty is a static type in the sense of serialization
     pub fn abi_is_static_type(&self, ty: &Type) -> bool {
         use move_model::ty::{PrimitiveType::*, Type::*};
@@ -270,16 +450,17 @@ impl<'a> Context<'a> {
             Primitive(p) => match p {
                 Bool | U8 | U64 | U128 | Address | Signer => true,
                 _ => {
-                    panic!("unexpected field type")
+                    self.unsupported_type_error(ty, "ABI encoded");
+                    true
                 }
             },
             Vector(_) => false,
             Tuple(tys) => conjunction(tys),
-            Struct(mid, sid, _) => {
-                if self.is_u256(mid.qualified(*sid)) {
+            Struct(mid, sid, inst) => {
+                if self.is_native_int(mid.qualified(*sid)) {
                     true
                 } else {
-                    let tys = self.get_field_types(mid.qualified(*sid));
+                    let tys = self.get_instantiated_field_types(mid.qualified(*sid), inst);
                     conjunction(&tys)
                 }
             }
@@ -290,7 +471,8 @@ impl<'a> Context<'a> {
             | ResourceDomain(_, _, _)
             | Error
             | Var(_) => {
-                panic!("unexpected field type")
+                self.unsupported_type_error(ty, "ABI encoded");
+                true
             }
         }
     }
@@ -349,20 +531,20 @@ impl<'a> Context<'a> {
                             20
                         }
                     }
-                    Num | Range | EventStore => {
-                        panic!("unexpected field type")
-                    }
+                    // Reported as unsupported by `abi_is_static_type`
+                    Num | Range | EventStore => 32,
                 },
                 Tuple(tys) => self.abi_type_head_sizes_sum(tys, padded),
-                Struct(mid, sid, _) => {
-                    if self.is_u256(mid.qualified(*sid)) {
+                Struct(mid, sid, inst) => {
+                    if self.is_native_int(mid.qualified(*sid)) {
                         32
                     } else {
-                        let tys = self.get_field_types(mid.qualified(*sid));
+                        let tys = self.get_instantiated_field_types(mid.qualified(*sid), inst);
                         self.abi_type_head_sizes_sum(&tys, padded)
                     }
                 }
-                _ => panic!("unexpected field type"),
+                // Reported as unsupported by `abi_is_static_type`
+                _ => 32,
             }
         } else {
             // Dynamic types
@@ -424,7 +606,8 @@ impl<'a> Context<'a> {
                 U128 => 16,
                 Address | Signer => 20,
                 Num | Range | EventStore => {
-                    panic!("unexpected field type")
+                    self.unsupported_type_error(ty, "stored in memory");
+                    32
                 }
             },
             Struct(..) | Vector(..) => 32,
@@ -436,7 +619,8 @@ impl<'a> Context<'a> {
             | ResourceDomain(_, _, _)
             | Error
             | Var(_) => {
-                panic!("unexpected field type")
+                self.unsupported_type_error(ty, "stored in memory");
+                32
             }
         }
     }
@@ -468,6 +652,7 @@ impl<'a> Context<'a> {
             1 => YulFunction::LoadU8,
             8 => YulFunction::LoadU64,
             16 => YulFunction::LoadU128,
+            20 => YulFunction::LoadU160,
             32 => YulFunction::LoadU256,
             _ => panic!("unexpected type size"),
         }
@@ -479,6 +664,7 @@ impl<'a> Context<'a> {
             1 => YulFunction::StoreU8,
             8 => YulFunction::StoreU64,
             16 => YulFunction::StoreU128,
+            20 => YulFunction::StoreU160,
             32 => YulFunction::StoreU256,
             _ => panic!("unexpected type size"),
         }
@@ -490,6 +676,7 @@ impl<'a> Context<'a> {
             1 => YulFunction::MemoryLoadU8,
             8 => YulFunction::MemoryLoadU64,
             16 => YulFunction::MemoryLoadU128,
+            20 => YulFunction::MemoryLoadU160,
             32 => YulFunction::MemoryLoadU256,
             _ => panic!("unexpected type size"),
         }
@@ -501,6 +688,7 @@ impl<'a> Context<'a> {
             1 => YulFunction::MemoryStoreU8,
             8 => YulFunction::MemoryStoreU64,
             16 => YulFunction::MemoryStoreU128,
+            20 => YulFunction::MemoryStoreU160,
             32 => YulFunction::MemoryStoreU256,
             _ => panic!("unexpected type size"),
         }
@@ -513,6 +701,7 @@ impl<'a> Context<'a> {
             1 => YulFunction::StorageLoadU8,
             8 => YulFunction::StorageLoadU64,
             16 => YulFunction::StorageLoadU128,
+            20 => YulFunction::StorageLoadU160,
             32 => YulFunction::StorageLoadU256,
             _ => panic!("unexpected type size"),
         }
@@ -525,6 +714,7 @@ impl<'a> Context<'a> {
             1 => YulFunction::StorageStoreU8,
             8 => YulFunction::StorageStoreU64,
             16 => YulFunction::StorageStoreU128,
+            20 => YulFunction::StorageStoreU160,
             32 => YulFunction::StorageStoreU256,
             _ => panic!("unexpected type size"),
         }
@@ -534,7 +724,9 @@ impl<'a> Context<'a> {
     pub fn type_allocates_memory(&self, ty: &Type) -> bool {
         use Type::*;
         match ty {
-            Struct(m, s, _) => !self.is_u256(m.qualified(*s)),
+            Struct(m, s, _) => {
+                !self.is_native_int(m.qualified(*s)) && !self.is_table(m.qualified(*s))
+            }
             Vector(_) => true,
             _ => false,
         }
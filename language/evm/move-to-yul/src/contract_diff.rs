@@ -0,0 +1,388 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Comparison of two builds of the same contract, for reviewing the impact of an upgrade.
+//!
+//! A build is summarized by what is observable from outside the contract: the selectors of its
+//! callable functions, the layout of the resources it keeps in storage, and a hash of the code
+//! of each function of the deployed object, as optimized by solc.
+
+use crate::{
+    attributes, code_limits::block_end, context::Context, evm_asm, generator::Generator, mangling,
+    options::Options,
+};
+use move_model::{
+    model::{GlobalEnv, QualifiedInstId, StructId},
+    ty::Type,
+};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use sha3::{Digest, Keccak256};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    io::{self, Write},
+};
+
+/// The parts of a contract build which matter when upgrading the contract.
+#[derive(Debug, Clone, Default)]
+pub struct ContractSummary {
+    /// The name of the contract object.
+    pub name: String,
+    /// Signatures of the callable functions, by selector.
+    pub callables: BTreeMap<String, String>,
+    /// Layouts of the structs reachable from the resources of the contract, by struct type.
+    pub storage: BTreeMap<String, StorageLayout>,
+    /// Hashes of the optimized code of the functions in the deployed object, by Move name if the
+    /// function was generated from Move code and by Yul name otherwise. The code outside of
+    /// functions is hashed under the name of the object.
+    pub functions: BTreeMap<String, String>,
+}
+
+/// The layout of a struct in storage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageLayout {
+    /// The hash indexing storage, for structs stored as resources.
+    pub type_hash: Option<u32>,
+    /// The size, in bytes, of the struct.
+    pub size: usize,
+    /// Name, offset and type of each field, in declaration order.
+    pub fields: Vec<(String, usize, String)>,
+}
+
+impl fmt::Display for StorageLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} bytes {{", self.size)?;
+        for (i, (name, offset, ty)) in self.fields.iter().enumerate() {
+            let sep = if i == 0 { "" } else { "," };
+            write!(f, "{} {}: {} @ {}", sep, name, ty, offset)?;
+        }
+        write!(f, " }}")
+    }
+}
+
+impl ContractSummary {
+    /// Summarize the contract generated from the given environment. The generated Yul is
+    /// optimized with solc, whose optimizer may inline functions into their callers.
+    pub fn new(options: &Options, env: &GlobalEnv) -> anyhow::Result<Self> {
+        let (name, yul) = Generator::run(options, env);
+        let optimized_yul = evm_asm::optimize_yul(options, &yul)?;
+        let ctx = Context::new(options, env, false);
+        let contract_funs = ctx.get_target_functions(attributes::is_contract_fun);
+
+        let mut gen = Generator::default();
+        let callables = contract_funs
+            .iter()
+            .filter(|fun| attributes::is_callable_fun(fun))
+            .map(|fun| {
+                let (signature, selector) = gen.function_signature_and_selector(&ctx, fun);
+                (selector, signature)
+            })
+            .collect();
+
+        // Start from the resources of the contract modules, and follow the struct fields since
+        // their layout is part of the stored representation.
+        let contract_modules: BTreeSet<_> = contract_funs
+            .iter()
+            .map(|fun| fun.module_env.get_id())
+            .collect();
+        let mut todo: Vec<QualifiedInstId<StructId>> = contract_modules
+            .iter()
+            .flat_map(|mid| env.get_module(*mid).into_structs())
+            .filter(|st| st.get_abilities().has_key() && st.get_type_parameters().is_empty())
+            .map(|st| st.get_qualified_id().instantiate(vec![]))
+            .collect();
+        let resources: BTreeSet<_> = todo.iter().cloned().collect();
+        let mut storage = BTreeMap::new();
+        let tctx = env.get_type_display_ctx();
+        while let Some(st) = todo.pop() {
+            let ty = st.to_type();
+            let key = ty.display(&tctx).to_string();
+            if storage.contains_key(&key) {
+                continue;
+            }
+            let layout = ctx.get_struct_layout(&st);
+            let struct_env = env.get_struct(st.to_qualified_id());
+            let fields = struct_env
+                .get_fields()
+                .map(|field| {
+                    let (offset, field_ty) = &layout.offsets[&field.get_offset()];
+                    collect_structs(&ctx, field_ty, &mut todo);
+                    (
+                        field.get_name().display(env.symbol_pool()).to_string(),
+                        *offset,
+                        field_ty.display(&tctx).to_string(),
+                    )
+                })
+                .collect();
+            let type_hash = if resources.contains(&st) {
+                Some(mangling::type_hash(env, &ty))
+            } else {
+                None
+            };
+            storage.insert(
+                key,
+                StorageLayout {
+                    type_hash,
+                    size: layout.size,
+                    fields,
+                },
+            );
+        }
+
+        let functions = hash_yul_functions(&optimized_yul)
+            .into_iter()
+            .map(|(yul_name, hash)| (mangling::demangle(env, &yul_name).unwrap_or(yul_name), hash))
+            .collect();
+
+        Ok(Self {
+            name,
+            callables,
+            storage,
+            functions,
+        })
+    }
+}
+
+/// Add the non-native structs occurring in `ty` to `structs`.
+fn collect_structs(ctx: &Context, ty: &Type, structs: &mut Vec<QualifiedInstId<StructId>>) {
+    match ty {
+        Type::Vector(elem_ty) => collect_structs(ctx, elem_ty, structs),
+        Type::Struct(mid, sid, inst) if !ctx.is_native_int(mid.qualified(*sid)) => {
+            structs.push(mid.qualified_inst(*sid, inst.clone()))
+        }
+        _ => {}
+    }
+}
+
+static SRC_COMMENT_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?s)///\s*@src[^\n]*|/\*\*\s*@src.*?\*/").unwrap());
+
+static DECLARATION_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"\bobject\s+"([^"]*)"\s*\{|\bfunction\s+([\w$.]+)\s*\("#).unwrap());
+
+/// Hash the code of each function of the deployed objects in the given Yul code, and the code
+/// of these objects outside of functions under the name of the object. Source location comments,
+/// which change with unrelated edits of the Move sources, and the layout of the code are ignored.
+pub fn hash_yul_functions(yul: &str) -> BTreeMap<String, String> {
+    let code = SRC_COMMENT_REGEX.replace_all(yul, "");
+    let mut hashes = BTreeMap::new();
+    // The deployed object being hashed, the end of its block, and its code outside functions
+    let mut object: Option<(&str, usize, String)> = None;
+    let mut last_end = 0;
+    for cap in DECLARATION_REGEX.captures_iter(&code) {
+        let header = cap.get(0).unwrap();
+        if header.start() < last_end {
+            // Part of a function or object hashed already
+            continue;
+        }
+        if let Some((name, end, mut outside)) = object.take() {
+            if header.start() < end {
+                outside.push_str(&code[last_end..header.start()]);
+                object = Some((name, end, outside));
+            } else {
+                outside.push_str(&code[last_end..end]);
+                hashes.insert(name.to_string(), hash_code(&outside));
+            }
+        }
+        if let Some(name) = cap.get(1) {
+            if name.as_str().ends_with("_deployed") {
+                let end = block_end(&code, header.end());
+                object = Some((name.as_str(), end, String::new()));
+                last_end = header.end();
+            }
+        } else if let Some(name) = cap.get(2) {
+            if object.is_some() {
+                let body_start = header.end()
+                    + code[header.end()..]
+                        .find('{')
+                        .map_or(code.len() - header.end(), |offset| offset + 1);
+                let end = (block_end(&code, body_start) + 1).min(code.len());
+                hashes.insert(
+                    name.as_str().to_string(),
+                    hash_code(&code[header.start()..end]),
+                );
+                last_end = end;
+            }
+        }
+    }
+    if let Some((name, end, mut outside)) = object {
+        outside.push_str(&code[last_end..end]);
+        hashes.insert(name.to_string(), hash_code(&outside));
+    }
+    hashes
+}
+
+/// Hash a piece of Yul code, ignoring its layout.
+fn hash_code(code: &str) -> String {
+    let mut hasher = Keccak256::new();
+    for token in code.split_whitespace() {
+        hasher.update(token.as_bytes());
+        hasher.update(b" ");
+    }
+    format!("{:x}", hasher.finalize())[..16].to_string()
+}
+
+/// The part of a contract a change applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChangeArea {
+    Contract,
+    Callable,
+    Storage,
+    Code,
+}
+
+/// The kind of a change between two builds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A change of an item of a contract between two builds.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Change {
+    pub area: ChangeArea,
+    pub kind: ChangeKind,
+    pub item: String,
+    pub details: Option<String>,
+    /// Whether the change breaks clients of the contract or the data it has already stored.
+    pub breaking: bool,
+}
+
+/// The changes between two builds of a contract.
+#[derive(Debug, Clone, Default)]
+pub struct ContractDiff {
+    pub changes: Vec<Change>,
+}
+
+impl ContractDiff {
+    /// Compare the build `old` of a contract with the build `new`.
+    pub fn new(old: &ContractSummary, new: &ContractSummary) -> Self {
+        let mut changes = vec![];
+        if old.name != new.name {
+            changes.push(Change {
+                area: ChangeArea::Contract,
+                kind: ChangeKind::Changed,
+                item: old.name.clone(),
+                details: Some(format!("renamed to {}", new.name)),
+                breaking: true,
+            })
+        }
+        diff_maps(
+            &mut changes,
+            ChangeArea::Callable,
+            &old.callables,
+            &new.callables,
+            |selector, signature| format!("{} {}", selector, signature),
+            |_, _| None,
+        );
+        diff_maps(
+            &mut changes,
+            ChangeArea::Storage,
+            &old.storage,
+            &new.storage,
+            |name, _| name.to_string(),
+            |old_layout, new_layout| Some(format!("{} -> {}", old_layout, new_layout)),
+        );
+        diff_maps(
+            &mut changes,
+            ChangeArea::Code,
+            &old.functions,
+            &new.functions,
+            |name, _| name.to_string(),
+            |old_hash, new_hash| Some(format!("{} -> {}", old_hash, new_hash)),
+        );
+        changes.sort();
+        Self { changes }
+    }
+
+    /// Whether any of the changes breaks clients of the contract or its stored data.
+    pub fn is_breaking(&self) -> bool {
+        self.changes.iter().any(|change| change.breaking)
+    }
+
+    /// Print a human readable report of the changes.
+    pub fn report<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut area = None;
+        for change in &self.changes {
+            if area != Some(change.area) {
+                area = Some(change.area);
+                writeln!(
+                    writer,
+                    "{}:",
+                    match change.area {
+                        ChangeArea::Contract => "Contract",
+                        ChangeArea::Callable => "Callable functions",
+                        ChangeArea::Storage => "Storage layout",
+                        ChangeArea::Code => "Function code",
+                    }
+                )?;
+            }
+            let (marker, kind) = match change.kind {
+                ChangeKind::Added => ('+', "added"),
+                ChangeKind::Removed => ('-', "removed"),
+                ChangeKind::Changed => ('~', "changed"),
+            };
+            write!(writer, "  {} {:<8}{}", marker, kind, change.item)?;
+            if let Some(details) = &change.details {
+                write!(writer, ": {}", details)?;
+            }
+            if change.breaking {
+                write!(writer, " [breaking]")?;
+            }
+            writeln!(writer)?;
+        }
+        let num_breaking = self.changes.iter().filter(|change| change.breaking).count();
+        writeln!(
+            writer,
+            "Upgrade impact: {} breaking change(s), {} other change(s)",
+            num_breaking,
+            self.changes.len() - num_breaking
+        )
+    }
+}
+
+/// Add the differences between two maps of items to `changes`. Removing an item is breaking
+/// for the callable functions and the storage layout, and so is changing it for the latter.
+fn diff_maps<V: PartialEq>(
+    changes: &mut Vec<Change>,
+    area: ChangeArea,
+    old: &BTreeMap<String, V>,
+    new: &BTreeMap<String, V>,
+    item: impl Fn(&str, &V) -> String,
+    details: impl Fn(&V, &V) -> Option<String>,
+) {
+    let breaking = matches!(area, ChangeArea::Callable | ChangeArea::Storage);
+    for (key, old_value) in old {
+        match new.get(key) {
+            None => changes.push(Change {
+                area,
+                kind: ChangeKind::Removed,
+                item: item(key, old_value),
+                details: None,
+                breaking,
+            }),
+            Some(new_value) if new_value != old_value => changes.push(Change {
+                area,
+                kind: ChangeKind::Changed,
+                item: item(key, old_value),
+                details: details(old_value, new_value),
+                breaking,
+            }),
+            Some(_) => {}
+        }
+    }
+    for (key, new_value) in new {
+        if !old.contains_key(key) {
+            changes.push(Change {
+                area,
+                kind: ChangeKind::Added,
+                item: item(key, new_value),
+                details: None,
+                breaking: false,
+            })
+        }
+    }
+}
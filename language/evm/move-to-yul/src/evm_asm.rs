@@ -0,0 +1,126 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Lowers the generated Yul to EVM assembly for inspection, or to optimized Yul.
+//!
+//! The Yul is assembled by solc, which annotates the assembly with the `@src` locations of the
+//! Yul code each block of instructions is generated from. Those locations are byte offsets into
+//! the Move sources, which are rewritten into line and column positions, followed by the Move
+//! source at the location.
+
+use crate::options::Options;
+use anyhow::{anyhow, bail};
+use codespan::{ColumnOffset, FileId, LineOffset, Span};
+use move_model::model::{GlobalEnv, Loc};
+use regex::{Captures, Regex};
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+/// The marker solc prints before the assembly of a Yul object.
+const ASM_OUTPUT_MARKER: &str = "\nText representation:";
+
+/// The markers solc prints before and after the optimized Yul of a Yul object.
+const OPTIMIZED_YUL_MARKER: &str = "\nPretty printed source:";
+const HEX_OUTPUT_MARKER: &str = "\nBinary representation:";
+
+/// The maximal length of the Move source shown with a location.
+const MAX_SNIPPET_LEN: usize = 60;
+
+/// Assemble the given Yul contract with solc into annotated EVM assembly.
+pub fn yul_to_evm_asm(options: &Options, env: &GlobalEnv, yul: &str) -> anyhow::Result<String> {
+    let out = run_solc(options, &["--asm"], yul)?;
+    let start_of_asm = out
+        .find(ASM_OUTPUT_MARKER)
+        .ok_or_else(|| anyhow!("Internal error: unexpected output of solc during assembly"))?;
+    let asm = out[start_of_asm + ASM_OUTPUT_MARKER.len()..].trim();
+    Ok(annotate_locations(env, asm))
+}
+
+/// Optimize the given Yul contract with solc, as it is when deployed.
+pub fn optimize_yul(options: &Options, yul: &str) -> anyhow::Result<String> {
+    let out = run_solc(options, &["--bin", "--ir-optimized"], yul)?;
+    match (out.find(OPTIMIZED_YUL_MARKER), out.find(HEX_OUTPUT_MARKER)) {
+        (Some(start), Some(end)) if start < end => Ok(out[start + OPTIMIZED_YUL_MARKER.len()..end]
+            .trim()
+            .to_string()),
+        _ => bail!("Internal error: unexpected output of solc during optimization"),
+    }
+}
+
+/// Run solc with optimizations on the given Yul contract, returning its standard output.
+fn run_solc(options: &Options, args: &[&str], yul: &str) -> anyhow::Result<String> {
+    if options.solc_exe.is_empty() {
+        bail!("failed to find path to solc -- set it with --solc-exe or SOLC_EXE")
+    }
+    let mut child = Command::new(&options.solc_exe)
+        .arg("--optimize")
+        .arg("--strict-assembly")
+        .args(args)
+        .arg("-")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| anyhow!("failed to call solc: {}", err))?;
+    child
+        .stdin
+        .as_mut()
+        .ok_or_else(|| anyhow!("cannot create pipe"))?
+        .write_all(yul.as_bytes())?;
+    let out = child.wait_with_output()?;
+    if !out.status.success() {
+        bail!("{}", String::from_utf8_lossy(&out.stderr))
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).to_string())
+}
+
+/// Rewrite the `/* "file":start:end */` comments solc emits into the line and column of the
+/// start of the location, followed by the Move source at the location.
+pub fn annotate_locations(env: &GlobalEnv, asm: &str) -> String {
+    let files: BTreeMap<String, FileId> = env
+        .get_file_map()
+        .into_iter()
+        .filter_map(|(_, name, hash)| Some((name, env.get_file_id(hash)?)))
+        .collect();
+    let location_re = Regex::new(r#"/\* "([^"]+)":([0-9]+):([0-9]+)[^*]*\*/"#).unwrap();
+    location_re
+        .replace_all(asm, |caps: &Captures| {
+            let file_id = match files.get(&caps[1]) {
+                Some(file_id) => *file_id,
+                None => return caps[0].to_string(),
+            };
+            let start = caps[2].parse::<u32>().unwrap_or_default();
+            let end = caps[3].parse::<u32>().unwrap_or_default();
+            let loc = Loc::new(file_id, Span::new(start, end.max(start)));
+            let position = match env.get_location(&loc) {
+                Some(location) => format!(
+                    "{}:{}:{}",
+                    &caps[1],
+                    location.line + LineOffset(1),
+                    location.column + ColumnOffset(1)
+                ),
+                None => return caps[0].to_string(),
+            };
+            let snippet = env
+                .get_source(&loc)
+                .ok()
+                .and_then(|source| source.lines().next())
+                .map(|line| line.trim().replace("*/", "* /"))
+                .unwrap_or_default();
+            if snippet.is_empty() {
+                format!("/* {} */", position)
+            } else if snippet.len() > MAX_SNIPPET_LEN {
+                let cut = (0..=MAX_SNIPPET_LEN)
+                    .rev()
+                    .find(|idx| snippet.is_char_boundary(*idx))
+                    .unwrap_or_default();
+                format!("/* {}: {}... */", position, &snippet[..cut])
+            } else {
+                format!("/* {}: {} */", position, snippet)
+            }
+        })
+        .to_string()
+}
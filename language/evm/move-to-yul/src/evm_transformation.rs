@@ -116,6 +116,12 @@ fn transform_u256_op(fun_name: &str) -> Option<Operation> {
         "le" => Some(Operation::Le),
         "shl" => Some(Operation::Shl),
         "shr" => Some(Operation::Shr),
+        "bitand" => Some(Operation::BitAnd),
+        "bitor" => Some(Operation::BitOr),
+        "xor" => Some(Operation::Xor),
+        "to_u8" => Some(Operation::CastU8),
+        "to_u64" => Some(Operation::CastU64),
+        "to_u128" => Some(Operation::CastU128),
         _ => None,
     }
 }
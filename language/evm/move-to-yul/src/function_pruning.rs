@@ -0,0 +1,151 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Removes the functions which are pulled into the targets but never called.
+//!
+//! All functions transitively called by an entry point of the contract (or by a test) are added
+//! to the targets, following the calls in the Move bytecode. Some of these calls do not survive
+//! the EVM transformation, like calls to the `U256` arithmetic functions which become builtin
+//! operations, so the functions they call are never generated. This processor follows the calls
+//! which are left after the transformation from the entry points, and removes the targets which
+//! are not reached anymore, keeping a report of the removed functions.
+
+use crate::{context::Context, options::Options};
+use move_model::model::{FunId, FunctionEnv, GlobalEnv, QualifiedId};
+use move_stackless_bytecode::{
+    function_target::FunctionData,
+    function_target_pipeline::{FunctionTargetProcessor, FunctionTargetsHolder, FunctionVariant},
+    stackless_bytecode::{Bytecode, Operation},
+};
+use std::{
+    cell::RefCell,
+    collections::BTreeSet,
+    fmt::{self, Formatter},
+};
+
+/// A function removed from the targets.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PrunedFunction {
+    /// The fully qualified Move name of the function.
+    pub name: String,
+    /// The number of stackless bytecode instructions of the function, zero for natives.
+    pub instructions: usize,
+}
+
+/// The functions removed from the targets of a compilation.
+#[derive(Debug, Clone, Default)]
+pub struct PruningReport {
+    pub pruned: Vec<PrunedFunction>,
+}
+
+impl PruningReport {
+    /// Report the functions pruned when compiling the contract of the given environment.
+    pub fn new(options: &Options, env: &GlobalEnv) -> Self {
+        // The report is left in the environment by the pruning processor
+        let _ = Context::new(options, env, false);
+        env.get_extension::<PruningReport>()
+            .map(|report| (*report).clone())
+            .unwrap_or_default()
+    }
+
+    /// The number of stackless bytecode instructions which did not need to be processed.
+    pub fn instructions_saved(&self) -> usize {
+        self.pruned.iter().map(|fun| fun.instructions).sum()
+    }
+}
+
+impl fmt::Display for PruningReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Pruned {} function(s) not reachable from the entry points, saving {} instruction(s)",
+            self.pruned.len(),
+            self.instructions_saved()
+        )?;
+        for fun in &self.pruned {
+            writeln!(f, "  {} ({} instruction(s))", fun.name, fun.instructions)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct FunctionPruningProcessor {
+    /// The functions the generated code is entered through.
+    entries: BTreeSet<QualifiedId<FunId>>,
+    /// The functions reached from the entries, computed on initialization.
+    reachable: RefCell<BTreeSet<QualifiedId<FunId>>>,
+    pruned: RefCell<Vec<PrunedFunction>>,
+}
+
+impl FunctionPruningProcessor {
+    pub fn new(entries: BTreeSet<QualifiedId<FunId>>) -> Box<Self> {
+        Box::new(FunctionPruningProcessor {
+            entries,
+            reachable: RefCell::new(BTreeSet::new()),
+            pruned: RefCell::new(vec![]),
+        })
+    }
+}
+
+impl FunctionTargetProcessor for FunctionPruningProcessor {
+    fn initialize(&self, _env: &GlobalEnv, targets: &mut FunctionTargetsHolder) {
+        let mut reachable = self.reachable.borrow_mut();
+        let mut todo = self.entries.iter().cloned().collect::<Vec<_>>();
+        while let Some(fun_id) = todo.pop() {
+            if !reachable.insert(fun_id) {
+                continue;
+            }
+            if let Some(data) = targets.get_data(&fun_id, &FunctionVariant::Baseline) {
+                for bc in &data.code {
+                    if let Bytecode::Call(_, _, Operation::Function(mid, fid, _), _, _) = bc {
+                        todo.push(mid.qualified(*fid));
+                    }
+                }
+            }
+        }
+    }
+
+    fn process_and_maybe_remove(
+        &self,
+        _targets: &mut FunctionTargetsHolder,
+        func_env: &FunctionEnv<'_>,
+        data: FunctionData,
+    ) -> Option<FunctionData> {
+        if self
+            .reachable
+            .borrow()
+            .contains(&func_env.get_qualified_id())
+        {
+            Some(data)
+        } else {
+            self.pruned.borrow_mut().push(PrunedFunction {
+                name: func_env.get_full_name_str(),
+                instructions: data.code.len(),
+            });
+            None
+        }
+    }
+
+    fn finalize(&self, env: &GlobalEnv, _targets: &mut FunctionTargetsHolder) {
+        let mut pruned = std::mem::take(&mut *self.pruned.borrow_mut());
+        pruned.sort();
+        env.set_extension(PruningReport { pruned });
+    }
+
+    fn name(&self) -> String {
+        "function_pruning".to_string()
+    }
+
+    fn dump_result(
+        &self,
+        f: &mut Formatter<'_>,
+        env: &GlobalEnv,
+        _targets: &FunctionTargetsHolder,
+    ) -> fmt::Result {
+        if let Some(report) = env.get_extension::<PruningReport>() {
+            writeln!(f, "\n\n==== function pruning ====\n")?;
+            write!(f, "{}", report)?;
+        }
+        Ok(())
+    }
+}
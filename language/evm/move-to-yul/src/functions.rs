@@ -1,13 +1,16 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{context::Context, yul_functions, yul_functions::YulFunction, Generator};
+use crate::{
+    attributes, context::Context, scratch_allocation::ScratchAllocationAnnotation, storage,
+    yul_functions, yul_functions::YulFunction, Generator,
+};
 use itertools::Itertools;
 use move_model::{
     ast::TempIndex,
     emit, emitln,
     model::{FunId, ModuleId, QualifiedInstId, StructId},
-    ty::{PrimitiveType, Type, TypeDisplayContext},
+    ty::{PrimitiveType, Type},
 };
 use move_stackless_bytecode::{
     function_target::FunctionTarget,
@@ -15,7 +18,6 @@ use move_stackless_bytecode::{
     stackless_bytecode::{Bytecode, Constant, Label, Operation},
     stackless_control_flow_graph::{BlockContent, BlockId, StacklessControlFlowGraph},
 };
-use sha3::{Digest, Keccak256};
 use std::collections::{btree_map::Entry, BTreeMap};
 
 /// Mutable state of the function generator.
@@ -41,12 +43,19 @@ impl<'a> FunctionGenerator<'a> {
     /// Generate Yul function for Move function.
     fn function(&mut self, ctx: &Context, fun_id: &QualifiedInstId<FunId>) {
         let fun = &ctx.env.get_function(fun_id.to_qualified_id());
+        ctx.set_decl_loc(&fun.get_loc());
         if fun.is_native_or_intrinsic() {
             // Special treatment for native functions, which have custom generators.
             ctx.native_funs
                 .gen_native_function(self.parent, ctx, fun_id);
             return;
         }
+        if attributes::get_inline_yul_attr(fun).is_some() {
+            ctx.env.error(
+                &fun.get_loc(),
+                "a function with #[inline_yul] code must be native",
+            );
+        }
         let target = &ctx.targets.get_target(fun, &FunctionVariant::Baseline);
 
         // Emit function header
@@ -236,6 +245,11 @@ impl<'a> FunctionGenerator<'a> {
                 .instantiate(Type::instantiate_slice(inst, &fun_id.inst))
         };
         let get_local_type = |idx: TempIndex| target.get_local_type(idx).instantiate(&fun_id.inst);
+        let releases_scratch = target
+            .get_annotations()
+            .get::<ScratchAllocationAnnotation>()
+            .map(|annotation| annotation.releases_scratch(bc.get_attr_id()))
+            .unwrap_or(false);
         let mut builtin = |yul_fun: YulFunction, dest: &[TempIndex], srcs: &[TempIndex]| {
             print_loc();
             emitln!(
@@ -326,8 +340,8 @@ impl<'a> FunctionGenerator<'a> {
             }
             Abort(_, code) => {
                 print_loc();
-                self.parent
-                    .call_builtin(ctx, YulFunction::Abort, std::iter::once(local(code)))
+                let abort_fun = self.abort_function(ctx, target);
+                emitln!(ctx.writer, "{}({})", abort_fun, local(code))
             }
             Call(_, dest, op, srcs, _) => {
                 use Operation::*;
@@ -364,11 +378,17 @@ impl<'a> FunctionGenerator<'a> {
                             make_struct_id(m, s, inst),
                             dest,
                             local(&srcs[0]),
-                        )
+                        );
+                        if releases_scratch {
+                            self.release_scratch(ctx, local(&srcs[0]))
+                        }
                     }
                     Destroy => {
                         print_loc();
-                        self.destroy(ctx, &get_local_type(srcs[0]), local(&srcs[0]))
+                        self.destroy(ctx, &get_local_type(srcs[0]), local(&srcs[0]));
+                        if releases_scratch {
+                            self.release_scratch(ctx, local(&srcs[0]))
+                        }
                     }
 
                     // Resource management
@@ -416,7 +436,7 @@ impl<'a> FunctionGenerator<'a> {
                     // References
                     BorrowLoc => {
                         print_loc();
-                        self.borrow_loc(ctx, target, dest, srcs)
+                        self.borrow_loc(ctx, target, dest, srcs, &get_local_type(srcs[0]))
                     }
                     BorrowField(m, s, inst, f) => {
                         print_loc();
@@ -538,7 +558,18 @@ impl<'a> FunctionGenerator<'a> {
     }
 
     /// Generate a string representing a constant.
-    fn constant(&self, _ctx: &Context, cons: &Constant) -> String {
+    fn constant(&mut self, ctx: &Context, cons: &Constant) -> String {
+        if let Some(shared) = ctx.shared_constants.get(cons) {
+            let value = shared.value;
+            let generate_fun = move |_gen: &mut Generator, ctx: &Context| {
+                emit!(ctx.writer, "() -> value ");
+                ctx.emit_block(|| emitln!(ctx.writer, "value := {}", value));
+            };
+            let function_name = self
+                .parent
+                .need_auxiliary_function(shared.function_name(), Box::new(generate_fun));
+            return format!("{}()", function_name);
+        }
         match cons {
             Constant::Bool(v) => {
                 if *v {
@@ -562,10 +593,105 @@ impl<'a> FunctionGenerator<'a> {
             Constant::Address(a) => {
                 format!("0x{}", a.to_str_radix(16))
             }
-            Constant::ByteArray(_) => "(ByteArray NYI)".to_string(),
+            Constant::ByteArray(bytes) => self.byte_array_constant(ctx, bytes),
         }
     }
 
+    /// Generate a call to a function returning a new `vector<u8>` holding the bytes, as the
+    /// vector may be mutated.
+    fn byte_array_constant(&mut self, ctx: &Context, bytes: &[u8]) -> String {
+        let function_name = format!("const_bytes_{}", hex::encode(bytes));
+        let data_name = ctx
+            .shared_constants
+            .get_byte_array(bytes)
+            .map(|shared| shared.data_name.clone());
+        let bytes = bytes.to_vec(); // need to move into lambda
+        let generate_fun = move |gen: &mut Generator, ctx: &Context| {
+            emit!(ctx.writer, "() -> value ");
+            ctx.emit_block(|| {
+                let malloc = gen.call_builtin_str(
+                    ctx,
+                    YulFunction::MallocVector,
+                    vec!["1".to_string(), bytes.len().to_string()].into_iter(),
+                );
+                emitln!(ctx.writer, "let mem := {}", malloc);
+                if let Some(data_name) = data_name {
+                    // Large constants are copied from a data object of the code block
+                    let data_name = gen.need_data(&data_name, &bytes);
+                    emitln!(
+                        ctx.writer,
+                        "datacopy(add(mem, ${{VECTOR_METADATA_SIZE}}), dataoffset(\"{}\"), \
+                         datasize(\"{}\"))",
+                        data_name,
+                        data_name
+                    );
+                } else {
+                    // The last word is padded with zeros, which may be written past the vector
+                    // into unallocated memory
+                    for (i, chunk) in bytes.chunks(32).enumerate() {
+                        let mut word = [0u8; 32];
+                        word[..chunk.len()].copy_from_slice(chunk);
+                        emitln!(
+                            ctx.writer,
+                            "mstore(add(mem, add(${{VECTOR_METADATA_SIZE}}, {})), 0x{})",
+                            i * 32,
+                            hex::encode(word)
+                        );
+                    }
+                }
+                let make_ptr = gen.call_builtin_str(
+                    ctx,
+                    YulFunction::MakePtr,
+                    vec!["false".to_string(), "mem".to_string()].into_iter(),
+                );
+                emitln!(ctx.writer, "value := {}", make_ptr);
+            });
+        };
+        let function_name = self
+            .parent
+            .need_auxiliary_function(function_name, Box::new(generate_fun));
+        format!("{}()", function_name)
+    }
+
+    /// Returns the name of a function which aborts the given Move function with a code, reverting
+    /// with an `Error(string)` message like `0x2::M::f aborted with code 1`.
+    fn abort_function(&mut self, ctx: &Context, target: &FunctionTarget) -> String {
+        let fun_id = target.func_env.get_qualified_id().instantiate(vec![]);
+        let function_name = format!("abort_{}", ctx.make_function_name(&fun_id));
+        let prefix = format!("{} aborted with code ", target.func_env.get_full_name_str());
+        let generate_fun = move |gen: &mut Generator, ctx: &Context| {
+            emit!(ctx.writer, "(code) ");
+            ctx.emit_block(|| {
+                emitln!(
+                    ctx.writer,
+                    "let message := add(mload(${{MEM_SIZE_LOC}}), 68)"
+                );
+                for (i, chunk) in prefix.as_bytes().chunks(32).enumerate() {
+                    let mut word = [0u8; 32];
+                    word[..chunk.len()].copy_from_slice(chunk);
+                    emitln!(
+                        ctx.writer,
+                        "mstore(add(message, {}), 0x{})",
+                        i * 32,
+                        hex::encode(word)
+                    );
+                }
+                gen.call_builtin(
+                    ctx,
+                    YulFunction::AbortWithMessage,
+                    vec![
+                        "message".to_string(),
+                        prefix.len().to_string(),
+                        "code".to_string(),
+                    ]
+                    .into_iter(),
+                );
+            });
+        };
+        self.parent
+            .need_auxiliary_function(function_name, Box::new(generate_fun))
+    }
+
     /// Generate call to a Move function.
     fn move_call(
         &mut self,
@@ -637,13 +763,14 @@ impl<'a> FunctionGenerator<'a> {
         })
     }
 
-    /// Borrow a local.
+    /// Borrow a local of the given type.
     fn borrow_loc(
         &mut self,
         ctx: &Context,
         target: &FunctionTarget,
         dest: &[TempIndex],
         srcs: &[TempIndex],
+        ty: &Type,
     ) {
         // Need to adjust the offset for the local by (32 - size) to account for big endian.
         // We need to point to the actual highest byte of the value.
@@ -653,7 +780,7 @@ impl<'a> FunctionGenerator<'a> {
             .expect("local evaded to memory")
             * yul_functions::WORD_SIZE)
             + 32
-            - ctx.type_size(target.get_local_type(srcs[0]));
+            - ctx.type_size(ty);
         let local_ptr = if offs == 0 {
             "$locals".to_string()
         } else {
@@ -674,19 +801,43 @@ impl<'a> FunctionGenerator<'a> {
 
     /// Read the value of reference.
     fn read_ref(&mut self, ctx: &Context, ty: &Type, dest: String, src: String) {
-        let yul_fun = ctx.load_builtin_fun(ty.skip_reference());
+        let ty = ty.skip_reference();
+        let yul_fun = ctx.load_builtin_fun(ty);
         self.parent.call_builtin_with_result(
             ctx,
             "",
-            std::iter::once(dest),
+            std::iter::once(dest.clone()),
             yul_fun,
             std::iter::once(src),
-        )
+        );
+        if ctx.type_allocates_memory(ty) {
+            // The value is copied, from memory or storage, so it does not alias the referenced one
+            let copy_to_memory = storage::generate_copy_to_memory(self.parent, ctx, ty);
+            emitln!(ctx.writer, "{} := {}({})", dest, copy_to_memory, dest);
+        }
     }
 
     /// Write the value of reference.
     fn write_ref(&mut self, ctx: &Context, ty: &Type, dest: String, src: String) {
-        let yul_fun = ctx.store_builtin_fun(ty.skip_reference());
+        let ty = ty.skip_reference();
+        let yul_fun = ctx.store_builtin_fun(ty);
+        if ctx.type_allocates_memory(ty) {
+            // A value written to storage is moved to storage
+            let is_storage = self.parent.call_builtin_str(
+                ctx,
+                YulFunction::IsStoragePtr,
+                std::iter::once(dest.clone()),
+            );
+            let move_to_storage = storage::generate_move_to_storage(self.parent, ctx, ty);
+            emitln!(
+                ctx.writer,
+                "if {} {{ {} := {}({}) }}",
+                is_storage,
+                src,
+                move_to_storage,
+                src
+            );
+        }
         self.parent
             .call_builtin(ctx, yul_fun, vec![dest, src].into_iter())
     }
@@ -749,6 +900,9 @@ impl<'a> FunctionGenerator<'a> {
         src: String,
     ) {
         let layout = ctx.get_struct_layout(&struct_id);
+        let src_offs =
+            self.parent
+                .call_builtin_str(ctx, YulFunction::OffsetPtr, std::iter::once(src.clone()));
 
         // Copy fields
         let struct_env = &ctx.env.get_struct(struct_id.to_qualified_id());
@@ -760,7 +914,7 @@ impl<'a> FunctionGenerator<'a> {
                     .instantiate(&struct_id.inst),
             );
             let (byte_offset, _) = *layout.offsets.get(&logical_offs).unwrap();
-            let field_ptr = format!("add({}, {})", src, byte_offset);
+            let field_ptr = format!("add({}, {})", src_offs, byte_offset);
             let call_str = self
                 .parent
                 .call_builtin_str(ctx, yul_fun, std::iter::once(field_ptr));
@@ -775,6 +929,13 @@ impl<'a> FunctionGenerator<'a> {
         )
     }
 
+    /// Release the memory of a struct which was the most recent allocation, as determined by
+    /// the scratch allocation processor, so that the next allocation reuses it.
+    fn release_scratch(&mut self, ctx: &Context, src: String) {
+        self.parent
+            .call_builtin(ctx, YulFunction::ReleaseScratch, std::iter::once(src))
+    }
+
     /// Destroy (free) a value of type.
     /// TODO: the Destroy instruction is currently not reflecting lifetime of values correctly,
     ///   but is only inserted for the original pop bytecode. We should run lifetime analysis
@@ -910,7 +1071,7 @@ impl<'a> FunctionGenerator<'a> {
             emitln!(ctx.writer, "if {} {{\n  {}\n}}", exists_call, abort_call);
             self.parent.call_builtin(
                 ctx,
-                YulFunction::AlignedStorageStore,
+                YulFunction::StorageStoreU8,
                 vec![base_offset.to_string(), "true".to_string()].into_iter(),
             );
 
@@ -923,113 +1084,18 @@ impl<'a> FunctionGenerator<'a> {
                     "let $dst := add({}, ${{RESOURCE_EXISTS_FLAG_SIZE}})",
                     base_offset
                 );
-                emitln!(ctx.writer, "let $src := {}", value);
-                // Perform the move.
-                self.move_struct_to_storage(
+                let src_offs = self.parent.call_builtin_str(
                     ctx,
-                    &struct_id,
-                    "$src".to_string(),
-                    "$dst".to_string(),
+                    YulFunction::OffsetPtr,
+                    std::iter::once(value),
                 );
+                emitln!(ctx.writer, "let $src := {}", src_offs);
+                // Perform the move.
+                storage::emit_struct_to_storage(self.parent, ctx, &struct_id, "$src", "$dst");
             });
         })
     }
 
-    /// Moves a struct from memory to storage. This recursively moves linked data like
-    /// nested structs and vectors.
-    fn move_struct_to_storage(
-        &mut self,
-        ctx: &Context,
-        struct_id: &QualifiedInstId<StructId>,
-        src: String,
-        dst: String,
-    ) {
-        let layout = ctx.get_struct_layout(struct_id);
-
-        // By invariant we know that the leading fields are pointer fields. Copy them first.
-        for field_offs in layout.field_order.iter().take(layout.pointer_count) {
-            let (byte_offs, ty) = layout.offsets.get(field_offs).unwrap();
-            assert_eq!(byte_offs % 32, 0, "pointer fields are on word boundary");
-            if ty.is_vector() {
-                ctx.env
-                    .error(&self.parent.contract_loc, "vectors not yet implemented");
-                continue;
-            }
-            ctx.emit_block(|| {
-                let hash = self.type_hash(ctx, ty);
-                // Allocate a new storage pointer.
-                emitln!(
-                    ctx.writer,
-                    "let $linked_dst := {}",
-                    self.parent.call_builtin_str(
-                        ctx,
-                        YulFunction::NewLinkedStorageBase,
-                        std::iter::once(format!("0x{:x}", hash))
-                    )
-                );
-                // Load the pointer to the linked memory.
-                emitln!(
-                    ctx.writer,
-                    "let $linked_src := mload(add({}, {}))",
-                    src,
-                    byte_offs
-                );
-                // Recursively move.
-                let field_struct_id = ty.get_struct_id(ctx.env).expect("struct");
-                self.move_struct_to_storage(
-                    ctx,
-                    &field_struct_id,
-                    "$linked_src".to_string(),
-                    "$linked_dst".to_string(),
-                );
-                // Store the result at the destination
-                self.parent.call_builtin(
-                    ctx,
-                    YulFunction::AlignedStorageStore,
-                    vec![
-                        format!("add({}, {})", dst, byte_offs),
-                        "$linked_dst".to_string(),
-                    ]
-                    .into_iter(),
-                )
-            })
-        }
-
-        // The remaining fields are all primitive. We also know that memory is padded to word size,
-        // so we can just copy directly word by word, which has the lowest gas cost.
-        if layout.pointer_count < layout.field_order.len() {
-            let mut byte_offs = layout
-                .offsets
-                .get(&layout.field_order[layout.pointer_count])
-                .unwrap()
-                .0;
-            assert_eq!(
-                byte_offs % 32,
-                0,
-                "first non-pointer field on word boundary"
-            );
-            while byte_offs < layout.size {
-                self.parent.call_builtin(
-                    ctx,
-                    YulFunction::AlignedStorageStore,
-                    vec![
-                        format!("add({}, {})", dst, byte_offs),
-                        format!("mload(add({}, {}))", src, byte_offs),
-                    ]
-                    .into_iter(),
-                );
-                byte_offs += 32
-            }
-        }
-
-        // Free the memory allocated by this struct.
-        self.parent.call_builtin(
-            ctx,
-            YulFunction::Free,
-            vec![src, layout.size.to_string()].into_iter(),
-        )
-    }
-
     /// Move resource from storage to local.
     fn move_from(
         &mut self,
@@ -1065,7 +1131,7 @@ impl<'a> FunctionGenerator<'a> {
                     .call_builtin_str(ctx, YulFunction::AbortBuiltin, std::iter::empty());
             emitln!(
                 ctx.writer,
-                "if not({}) {{\n  {}\n}}",
+                "if iszero({}) {{\n  {}\n}}",
                 exists_call,
                 abort_call
             );
@@ -1086,105 +1152,25 @@ impl<'a> FunctionGenerator<'a> {
                 );
 
                 // Perform the move and assign the result.
-                emitln!(ctx.writer, "let $dst");
-                self.move_struct_to_memory(ctx, &struct_id, "$src".to_string(), "$dst".to_string());
-                self.assign(ctx, target, dst, "$dst".to_string())
-            })
-        })
-    }
-
-    /// Move a struct from storage to memory, zeroing all associated storage. This recursively
-    /// moves linked data like nested structs and vectors.
-    fn move_struct_to_memory(
-        &mut self,
-        ctx: &Context,
-        struct_id: &QualifiedInstId<StructId>,
-        src: String,
-        dst: String,
-    ) {
-        // Allocate struct.
-        let layout = ctx.get_struct_layout(struct_id);
-        emitln!(
-            ctx.writer,
-            "{} := {}",
-            dst,
-            self.parent.call_builtin_str(
-                ctx,
-                YulFunction::Malloc,
-                std::iter::once(layout.size.to_string()),
-            )
-        );
-
-        // Copy fields. By invariant we know that the leading fields are pointer fields.
-        for field_offs in layout.field_order.iter().take(layout.pointer_count) {
-            let (byte_offs, ty) = layout.offsets.get(field_offs).unwrap();
-            assert_eq!(byte_offs % 32, 0, "pointer fields are on word boundary");
-            if ty.is_vector() {
-                ctx.env
-                    .error(&self.parent.contract_loc, "vectors not yet implemented");
-                continue;
-            }
-            ctx.emit_block(|| {
-                let field_src_ptr = format!("add({}, {})", src, byte_offs);
-                let field_dst_ptr = format!("add({}, {})", dst, byte_offs);
-                // Load the pointer to the linked storage.
-                let load_call = self.parent.call_builtin_str(
-                    ctx,
-                    YulFunction::AlignedStorageLoad,
-                    std::iter::once(field_src_ptr.clone()),
+                let layout = ctx.get_struct_layout(&struct_id);
+                emitln!(
+                    ctx.writer,
+                    "let $dst := {}",
+                    self.parent.call_builtin_str(
+                        ctx,
+                        YulFunction::Malloc,
+                        std::iter::once(layout.size.to_string()),
+                    )
                 );
-                emitln!(ctx.writer, "let $linked_src := {}", load_call);
-                // Declare where to store the result and recursively move
-                emitln!(ctx.writer, "let $linked_dst");
-                let field_struct_id = ty.get_struct_id(ctx.env).expect("struct");
-                self.move_struct_to_memory(
+                storage::emit_struct_to_memory(self.parent, ctx, &struct_id, "$src", "$dst");
+                let make_ptr = self.parent.call_builtin_str(
                     ctx,
-                    &field_struct_id,
-                    "$linked_src".to_string(),
-                    "$linked_dst".to_string(),
+                    YulFunction::MakePtr,
+                    vec!["false".to_string(), "$dst".to_string()].into_iter(),
                 );
-                // Store the result at the destination.
-                assert_eq!(byte_offs % 32, 0);
-                emitln!(ctx.writer, "mstore({}, $linked_dst)", field_dst_ptr,);
-                // Clear the storage to get a refund
-                self.parent.call_builtin(
-                    ctx,
-                    YulFunction::AlignedStorageStore,
-                    vec![field_src_ptr, 0.to_string()].into_iter(),
-                )
+                self.assign(ctx, target, dst, make_ptr)
             })
-        }
-
-        // The remaining fields are all primitive. We also know that memory is padded to word size,
-        // so we can just copy directly word by word, which has the lowest gas cost.
-        if layout.pointer_count < layout.field_order.len() {
-            let mut byte_offs = layout
-                .offsets
-                .get(&layout.field_order[layout.pointer_count])
-                .unwrap()
-                .0;
-            assert_eq!(
-                byte_offs % 32,
-                0,
-                "first non-pointer field on word boundary"
-            );
-            while byte_offs < layout.size {
-                let field_src_ptr = format!("add({}, {})", src, byte_offs);
-                let field_dst_ptr = format!("add({}, {})", dst, byte_offs);
-                let load_call = self.parent.call_builtin_str(
-                    ctx,
-                    YulFunction::AlignedStorageLoad,
-                    std::iter::once(field_src_ptr.clone()),
-                );
-                emitln!(ctx.writer, "mstore({}, {})", field_dst_ptr, load_call);
-                self.parent.call_builtin(
-                    ctx,
-                    YulFunction::AlignedStorageStore,
-                    vec![field_src_ptr, 0.to_string()].into_iter(),
-                );
-                byte_offs += 32
-            }
-        }
+        })
     }
 
     /// Borrow a resource.
@@ -1221,7 +1207,7 @@ impl<'a> FunctionGenerator<'a> {
                     .call_builtin_str(ctx, YulFunction::AbortBuiltin, std::iter::empty());
             emitln!(
                 ctx.writer,
-                "if not({}) {{\n  {}\n}}",
+                "if iszero({}) {{\n  {}\n}}",
                 exists_call,
                 abort_call
             );
@@ -1236,7 +1222,24 @@ impl<'a> FunctionGenerator<'a> {
                 ]
                 .into_iter(),
             );
-            self.assign(ctx, target, dst, make_ptr)
+            // A reference to a struct is a pointer to a word holding the struct's pointer, so
+            // the storage pointer is held by a word allocated in memory.
+            emitln!(
+                ctx.writer,
+                "let $ref := {}",
+                self.parent.call_builtin_str(
+                    ctx,
+                    YulFunction::Malloc,
+                    std::iter::once("32".to_string())
+                )
+            );
+            emitln!(ctx.writer, "mstore($ref, {})", make_ptr);
+            let make_ref = self.parent.call_builtin_str(
+                ctx,
+                YulFunction::MakePtr,
+                vec!["false".to_string(), "$ref".to_string()].into_iter(),
+            );
+            self.assign(ctx, target, dst, make_ref)
         })
     }
 
@@ -1248,39 +1251,38 @@ impl<'a> FunctionGenerator<'a> {
         category: &str,
         instance: String,
     ) -> String {
-        let hash = self.type_hash(ctx, ty);
+        let hash = self.parent.type_hash(ctx, ty);
+        // Resources accessed in many places identify their storage through a shared function
+        let shared = ctx
+            .shared_constants
+            .get_type_hash(ty)
+            .filter(|_| category == "${RESOURCE_STORAGE_CATEGORY}");
+        if let Some(shared) = shared {
+            let generate_fun = move |gen: &mut Generator, ctx: &Context| {
+                emit!(ctx.writer, "(id) -> offs ");
+                ctx.emit_block(|| {
+                    let base = gen.call_builtin_str(
+                        ctx,
+                        YulFunction::MakeTypeStorageBase,
+                        vec![
+                            "${RESOURCE_STORAGE_CATEGORY}".to_string(),
+                            format!("0x{:x}", hash),
+                            "id".to_string(),
+                        ]
+                        .into_iter(),
+                    );
+                    emitln!(ctx.writer, "offs := {}", base)
+                });
+            };
+            let function_name = self
+                .parent
+                .need_auxiliary_function(shared.function_name(), Box::new(generate_fun));
+            return format!("{}({})", function_name, instance);
+        }
         self.parent.call_builtin_str(
             ctx,
             YulFunction::MakeTypeStorageBase,
             vec![category.to_string(), format!("0x{:x}", hash), instance].into_iter(),
         )
     }
-
-    /// Derive a 4 byte hash for a type. If this hash creates a collision in the current
-    /// contract, create an error.
-    fn type_hash(&mut self, ctx: &Context, ty: &Type) -> u32 {
-        let sig = ctx.mangle_type(ty);
-        let mut keccak = Keccak256::new();
-        keccak.update(sig.as_bytes());
-        let digest = keccak.finalize();
-        let hash = u32::from_le_bytes([digest[0], digest[1], digest[2], digest[3]]);
-        if let Some(old_ty) = self.parent.type_sig_map.insert(hash, ty.clone()) {
-            if old_ty != *ty {
-                let ty_ctx = &TypeDisplayContext::WithEnv {
-                    env: ctx.env,
-                    type_param_names: None,
-                };
-                ctx.env.error(
-                    &self.parent.contract_loc,
-                    &format!(
-                        "collision of type hash for types `{}` and `{}`\n\
-                         (resolution via attribute not yet implemented)",
-                        ty.display(ty_ctx),
-                        old_ty.display(ty_ctx)
-                    ),
-                )
-            }
-        }
-        hash
-    }
 }
@@ -4,7 +4,7 @@
 use codespan::FileId;
 use codespan_reporting::diagnostic::Severity;
 use std::{
-    collections::{BTreeMap, BTreeSet},
+    collections::{btree_map::Entry, BTreeMap, BTreeSet},
     path::PathBuf,
 };
 
@@ -14,14 +14,15 @@ use sha3::{Digest, Keccak256};
 use move_model::{
     ast::TempIndex,
     emit, emitln,
-    model::{FunId, FunctionEnv, GlobalEnv, Loc, QualifiedId, QualifiedInstId},
-    ty::{PrimitiveType, Type},
+    model::{FunId, FunctionEnv, GlobalEnv, Loc, QualifiedId, QualifiedInstId, StructId},
+    ty::{PrimitiveType, Type, TypeDisplayContext},
 };
 
 use crate::{
-    attributes,
+    attributes, code_limits,
     context::Context,
     functions::FunctionGenerator,
+    mangling, state_mutability,
     yul_functions::{substitute_placeholders, YulFunction},
     Options,
 };
@@ -32,6 +33,8 @@ pub const UNKNOWN_SIGNATURE_AND_NO_FALLBACK_DEFINED: usize = 98;
 pub const NO_RECEIVE_OR_FALLBACK_FUN: usize = 97;
 pub const ABI_DECODING_DATA_TOO_SHORT: usize = 96;
 pub const ABI_DECODING_PARAM_VALIDATION: usize = 95;
+pub const ABI_DECODING_NON_CANONICAL: usize = 94;
+pub const IMMUTABLE_NOT_SET: usize = 93;
 
 /// Mutable state of the generator.
 #[derive(Default)]
@@ -48,8 +51,18 @@ pub struct Generator {
     needed_auxiliary_functions: Vec<(String, Box<AuxilaryFunctionGenerator>)>,
     /// Auxiliary functions for which code has been emitted.
     done_auxiliary_functions: BTreeSet<String>,
+    /// Data objects needed in the current block, by name.
+    needed_data: BTreeMap<String, Vec<u8>>,
     /// Mapping of type signature hash to type, to identify collisions.
     pub(crate) type_sig_map: BTreeMap<u32, Type>,
+    /// Types for which a type hash collision has been reported.
+    type_hash_collisions: BTreeSet<Type>,
+    /// Whether the current code block is the deployed code of a contract, which reads the values
+    /// of `#[immutable]` structs from its own bytecode rather than from memory.
+    pub(crate) in_deployed_code: bool,
+    /// Move functions emitted in the deployed code, by the names of the Yul functions they are
+    /// translated to.
+    deployed_move_functions: BTreeMap<String, QualifiedId<FunId>>,
 }
 
 type AuxilaryFunctionGenerator = dyn FnOnce(&mut Generator, &Context);
@@ -61,6 +74,8 @@ impl Generator {
     /// Run the generator and produce a pair of contract name and Yul contract object.
     pub fn run(options: &Options, env: &GlobalEnv) -> (String, String) {
         let ctx = Context::new(options, env, false);
+        ctx.check_no_test_only_code();
+        ctx.check_immutable_structs();
         let mut gen = Generator::default();
         let contract_funs = ctx.get_target_functions(attributes::is_contract_fun);
         let (contract_name, contract_loc) = if contract_funs.is_empty() {
@@ -72,6 +87,7 @@ impl Generator {
             (ctx.make_contract_name(first_module), env.unknown_loc())
         };
         gen.contract_object(&ctx, contract_loc, &contract_name, &contract_funs);
+        code_limits::check_code_limits(&ctx, &contract_name, &gen.deployed_move_functions);
         (contract_name, ctx.writer.extract_result())
     }
 
@@ -82,6 +98,7 @@ impl Generator {
     ) -> BTreeMap<QualifiedId<FunId>, String> {
         let mut res = BTreeMap::new();
         let ctx = Context::new(options, env, /*for_test*/ true);
+        ctx.check_immutable_structs();
 
         // Go over all evm_test functions which are in modules which are target of compilation,
         // and generate a test object for them.
@@ -122,28 +139,17 @@ impl Generator {
             // Generate the deployment code block
             self.begin_code_block(ctx);
             let contract_deployed_name = format!("{}_deployed", contract_name);
-            emitln!(
-                ctx.writer,
-                "codecopy(0, dataoffset(\"{}\"), datasize(\"{}\"))",
-                contract_deployed_name,
-                contract_deployed_name
-            );
-            self.optional_creator(ctx);
-            emitln!(
-                ctx.writer,
-                "return(0, datasize(\"{}\"))",
-                contract_deployed_name,
-            );
+            // The creator runs before the deployed code is copied to memory
+            let has_creator = self.optional_creator(ctx, contract_name);
+            self.generate_deployment(ctx, &contract_deployed_name, has_creator);
             self.end_code_block(ctx);
 
             // Generate the runtime object
             emit!(ctx.writer, "object \"{}\" ", contract_deployed_name);
             ctx.emit_block(|| {
+                self.in_deployed_code = true;
                 self.begin_code_block(ctx);
-                emitln!(
-                    ctx.writer,
-                    "mstore(${MEM_SIZE_LOC}, memoryguard(${USED_MEM}))"
-                );
+                self.generate_memory_init(ctx);
                 self.callable_functions(ctx, contract_funs);
                 self.end_code_block(ctx);
             })
@@ -170,10 +176,7 @@ impl Generator {
         emit!(ctx.writer, "object \"{}\" ", test_contract_name);
         ctx.emit_block(|| {
             self.begin_code_block(ctx);
-            emitln!(
-                ctx.writer,
-                "mstore(${MEM_SIZE_LOC}, memoryguard(${USED_MEM}))"
-            );
+            self.generate_memory_init(ctx);
             self.need_move_function(&fun_id);
             let fun_name = ctx.make_function_name(&fun_id);
             emitln!(ctx.writer, "{}()", fun_name);
@@ -193,10 +196,11 @@ impl Generator {
             ctx.options.version(),
         );
         emitln!(ctx.writer);
+        // Pruned functions keep an entry in the targets, but without any variant
         let used_files: BTreeSet<FileId> = ctx
             .targets
-            .get_funs()
-            .map(|f| ctx.env.get_function(f).get_loc().file_id())
+            .get_funs_and_variants()
+            .map(|(f, _)| ctx.env.get_function(f).get_loc().file_id())
             .collect();
         for file_id in ctx.env.get_source_file_ids() {
             if used_files.contains(&file_id) {
@@ -220,8 +224,116 @@ impl Generator {
         emitln!(ctx.writer);
     }
 
-    /// Generate optional creator (contract constructor).
-    fn optional_creator(&mut self, ctx: &Context) {
+    /// Generate the initialization of the memory, reserving the memory used by the compilation
+    /// scheme. Outside of deployed code, this is followed by the locations holding the values
+    /// of `#[immutable]` structs, see `Context::immutable_value_loc`.
+    fn generate_memory_init(&mut self, ctx: &Context) {
+        let immutable_count = ctx.get_immutable_structs().len();
+        if self.in_deployed_code || immutable_count == 0 {
+            emitln!(
+                ctx.writer,
+                "mstore(${MEM_SIZE_LOC}, memoryguard(${USED_MEM}))"
+            );
+        } else {
+            // The argument of memoryguard must be a literal
+            let used_mem = substitute_placeholders("${USED_MEM}")
+                .and_then(|s| s.parse::<usize>().ok())
+                .expect("numerical USED_MEM");
+            emitln!(
+                ctx.writer,
+                "mstore(${{MEM_SIZE_LOC}}, memoryguard({}))",
+                used_mem + immutable_count * 32
+            );
+        }
+    }
+
+    /// Generate the code returning the deployed code. If there are `#[immutable]` structs, the
+    /// deployed code is copied to fresh memory, where their values are embedded into it.
+    fn generate_deployment(
+        &mut self,
+        ctx: &Context,
+        contract_deployed_name: &str,
+        has_creator: bool,
+    ) {
+        let immutables = ctx.get_immutable_structs();
+        if immutables.is_empty() || !has_creator {
+            for struct_id in &immutables {
+                ctx.env.error(
+                    &ctx.env.get_struct(*struct_id).get_loc(),
+                    "the value of an #[immutable] struct must be set by a #[create] function",
+                )
+            }
+            emitln!(
+                ctx.writer,
+                "codecopy(0, dataoffset(\"{}\"), datasize(\"{}\"))",
+                contract_deployed_name,
+                contract_deployed_name
+            );
+            emitln!(
+                ctx.writer,
+                "return(0, datasize(\"{}\"))",
+                contract_deployed_name,
+            );
+            return;
+        }
+        emitln!(
+            ctx.writer,
+            "let codeStart := {}",
+            self.call_builtin_str(
+                ctx,
+                YulFunction::Malloc,
+                std::iter::once(format!("datasize(\"{}\")", contract_deployed_name))
+            )
+        );
+        emitln!(
+            ctx.writer,
+            "codecopy(codeStart, dataoffset(\"{}\"), datasize(\"{}\"))",
+            contract_deployed_name,
+            contract_deployed_name
+        );
+        for struct_id in immutables {
+            let loc = ctx
+                .immutable_value_loc(struct_id)
+                .expect("immutable struct");
+            let layout = ctx.get_struct_layout(&struct_id.instantiate(vec![]));
+            ctx.emit_block(|| {
+                emitln!(ctx.writer, "let value := mload({})", loc);
+                emit!(ctx.writer, "if iszero(value) ");
+                ctx.emit_block(|| {
+                    self.call_builtin(
+                        ctx,
+                        YulFunction::Abort,
+                        std::iter::once(IMMUTABLE_NOT_SET.to_string()),
+                    )
+                });
+                let struct_env = ctx.env.get_struct(struct_id);
+                for field in struct_env.get_fields() {
+                    let (offs, ty) = &layout.offsets[&field.get_offset()];
+                    let load = self.call_builtin_str(
+                        ctx,
+                        ctx.memory_load_builtin_fun(ty),
+                        std::iter::once(format!("add(value, {})", offs)),
+                    );
+                    emitln!(
+                        ctx.writer,
+                        "setimmutable(codeStart, \"{}\", {})",
+                        ctx.make_immutable_name(struct_id, field.get_name()),
+                        load
+                    );
+                }
+            });
+        }
+        emitln!(
+            ctx.writer,
+            "return(codeStart, datasize(\"{}\"))",
+            contract_deployed_name,
+        );
+    }
+
+    /// Generate optional creator (contract constructor). Its arguments are ABI encoded and
+    /// appended to the code of the contract object, following the Solidity convention. Returns
+    /// whether there is a creator.
+    fn optional_creator(&mut self, ctx: &Context, contract_name: &str) -> bool {
         let mut creators = ctx.get_target_functions(attributes::is_create_fun);
         if creators.len() > 1 {
             ctx.env
@@ -229,13 +341,66 @@ impl Generator {
         }
         if let Some(creator) = creators.pop() {
             ctx.check_no_generics(&creator);
-            self.function(ctx, &creator.get_qualified_id().instantiate(vec![]));
-            // TODO: implement creator invocation
-            emitln!(
-                ctx.writer,
-                "// TODO: invocation of {}",
-                creator.get_full_name_str()
-            );
+            if creator.get_return_count() > 0 {
+                ctx.env.error(
+                    &creator.get_loc(),
+                    "#[create] function must not have return values",
+                );
+                return true;
+            }
+            let param_types = creator.get_parameter_types();
+            if !param_types
+                .iter()
+                .all(|ty| self.is_abi_supported_type(ctx, ty, false))
+            {
+                ctx.env.error(
+                    &creator.get_loc(),
+                    "cannot decode the parameters of this #[create] function",
+                );
+                return true;
+            }
+            let fun_id = &creator.get_qualified_id().instantiate(vec![]);
+            self.need_move_function(fun_id);
+            self.generate_memory_init(ctx);
+            if !attributes::is_payable_fun(&creator) {
+                self.generate_call_value_check(ctx, REVERT_ERR_NON_PAYABLE_FUN);
+            }
+            let mut params = "".to_string();
+            if !param_types.is_empty() {
+                let decoding_fun_name = self.generate_abi_tuple_decoding(ctx, &param_types, true);
+                params = (0..param_types.len())
+                    .map(|i| format!("param_{}", i))
+                    .join(", ");
+                emitln!(
+                    ctx.writer,
+                    "let argsSize := sub(codesize(), datasize(\"{}\"))",
+                    contract_name
+                );
+                emitln!(
+                    ctx.writer,
+                    "let argsStart := {}",
+                    self.call_builtin_str(
+                        ctx,
+                        YulFunction::Malloc,
+                        std::iter::once("argsSize".to_string())
+                    )
+                );
+                emitln!(
+                    ctx.writer,
+                    "codecopy(argsStart, datasize(\"{}\"), argsSize)",
+                    contract_name
+                );
+                emitln!(
+                    ctx.writer,
+                    "let {} := {}(argsStart, add(argsStart, argsSize))",
+                    params,
+                    decoding_fun_name
+                );
+            }
+            emitln!(ctx.writer, "{}({})", ctx.make_function_name(fun_id), params);
+            true
+        } else {
+            false
         }
     }
 
@@ -305,6 +470,7 @@ impl Generator {
                 .instantiate(vec![]);
             let fun_name = ctx.make_function_name(fun_id);
             let params_size = fallback.get_parameter_count();
+            let bytes_ty = Type::Vector(Box::new(Type::Primitive(PrimitiveType::U8)));
             if params_size == 0 {
                 emitln!(ctx.writer, "{}() stop()", fun_name);
             } else if params_size != 1 || fallback.get_return_count() != 1 {
@@ -312,13 +478,25 @@ impl Generator {
                     &fallback.get_loc(),
                     "fallback function must have at most 1 parameter and 1 return value",
                 );
+            } else if fallback.get_parameter_types()[0] != bytes_ty
+                || fallback.get_return_type(0) != bytes_ty
+            {
+                ctx.env.error(
+                    &fallback.get_loc(),
+                    "fallback function with a parameter must take and return `vector<u8>`",
+                );
             } else {
-                emitln!(
-                    ctx.writer,
-                    "let retval := {}(0, calldatasize()) stop()",
-                    fun_name
+                // The fallback receives the whole calldata, and its result is returned as is
+                let calldata = self.call_builtin_str(
+                    ctx,
+                    YulFunction::CalldataBytes,
+                    vec!["0".to_string(), "calldatasize()".to_string()].into_iter(),
+                );
+                self.call_builtin(
+                    ctx,
+                    YulFunction::ReturnBytes,
+                    std::iter::once(format!("{}({})", fun_name, calldata)),
                 );
-                emitln!(ctx.writer, "return(add(retval, 0x20), mload(retval))");
             }
         } else {
             let mut err_msg = NO_RECEIVE_OR_FALLBACK_FUN;
@@ -346,7 +524,7 @@ impl Generator {
     }
 
     /// Generate type string for encoding the function signature
-    fn get_evm_type_string(&self, ctx: &Context, ty: &Type) -> String {
+    pub(crate) fn get_evm_type_string(&self, ctx: &Context, ty: &Type) -> String {
         use PrimitiveType::*;
         use Type::*;
         let generate_tuple = |tys: &Vec<Type>| {
@@ -367,16 +545,24 @@ impl Generator {
                 Address => "address".to_string(),
                 Signer => "address".to_string(),
                 Num | Range | EventStore => {
-                    panic!("unexpected field type")
+                    ctx.unsupported_type_error(ty, "ABI encoded");
+                    "unsupported".to_string()
                 }
             },
-            Vector(ety) => format!("{}[]", self.get_evm_type_string(ctx, ety)),
+            Vector(ety) => match ety.as_ref() {
+                Primitive(U8) => "bytes".to_string(),
+                _ => format!("{}[]", self.get_evm_type_string(ctx, ety)),
+            },
             Tuple(tys) => generate_tuple(tys),
-            Struct(mid, sid, _) => {
+            Struct(mid, sid, inst) => {
                 if ctx.is_u256(mid.qualified(*sid)) {
                     "uint256".to_string()
+                } else if ctx.is_i256(mid.qualified(*sid)) {
+                    "int256".to_string()
+                } else if ctx.is_string(mid.qualified(*sid)) {
+                    "string".to_string()
                 } else {
-                    let tys = ctx.get_field_types(mid.qualified(*sid));
+                    let tys = ctx.get_instantiated_field_types(mid.qualified(*sid), inst);
                     generate_tuple(&tys)
                 }
             }
@@ -387,7 +573,8 @@ impl Generator {
             | ResourceDomain(_, _, _)
             | Error
             | Var(_) => {
-                panic!("unexpected field type")
+                ctx.unsupported_type_error(ty, "ABI encoded");
+                "unsupported".to_string()
             }
         }
     }
@@ -404,6 +591,46 @@ impl Generator {
         display_type_slice(&param_types)
     }
 
+    /// Compute the Solidity signature of a callable function and the selector derived from it.
+    pub(crate) fn function_signature_and_selector(
+        &mut self,
+        ctx: &Context,
+        fun: &FunctionEnv<'_>,
+    ) -> (String, String) {
+        let original_fun_name = fun.symbol_pool().string(fun.get_name());
+        let fun_sig = format!(
+            "{}({})",
+            original_fun_name,
+            self.compute_param_types(ctx, fun)
+        );
+        let function_selector =
+            format!("0x{:x}", Keccak256::digest(fun_sig.as_bytes()))[..10].to_string();
+        (fun_sig, function_selector)
+    }
+
+    /// Compute the Solidity signature of the custom error given by a struct, as declared by
+    /// `error Name(fields)` in Solidity, and the selector derived from it.
+    pub(crate) fn error_signature_and_selector(
+        &self,
+        ctx: &Context,
+        struct_id: &QualifiedInstId<StructId>,
+    ) -> (String, String) {
+        let struct_env = ctx.env.get_struct(struct_id.to_qualified_id());
+        let error_sig = format!(
+            "{}({})",
+            struct_env.get_name().display(ctx.env.symbol_pool()),
+            struct_env
+                .get_fields()
+                .map(|field| {
+                    self.get_evm_type_string(ctx, &field.get_type().instantiate(&struct_id.inst))
+                })
+                .join(",")
+        );
+        let error_selector =
+            format!("0x{:x}", Keccak256::digest(error_sig.as_bytes()))[..10].to_string();
+        (error_sig, error_selector)
+    }
+
     /// Generate the start position of memory for returning from the external function
     /// Note: currently, we directly return the free memory pointer, may need to use the memory model later
     fn generate_allocate_unbounded(&mut self, ctx: &Context) {
@@ -454,15 +681,29 @@ impl Generator {
     }
 
     /// Generate decoding functions for primitive types.
-    fn generate_abi_decoding_primitive_type(&mut self, ctx: &Context, ty: &Type) -> String {
+    fn generate_abi_decoding_primitive_type(
+        &mut self,
+        ctx: &Context,
+        ty: &Type,
+        from_memory: bool,
+    ) -> String {
         let name_prefix = "abi_decode";
-        let function_name = format!("{}_{}", name_prefix, ctx.mangle_type(ty));
+        let function_name = format!(
+            "{}_{}{}",
+            name_prefix,
+            ctx.mangle_type(ty),
+            decoding_source_suffix(from_memory)
+        );
         let ty = ty.clone(); // need to move into lambda
 
         let generate_fun = move |gen: &mut Generator, ctx: &Context| {
             emit!(ctx.writer, "(offset, end) -> value ");
             ctx.emit_block(|| {
-                emitln!(ctx.writer, "value := calldataload(offset)");
+                emitln!(
+                    ctx.writer,
+                    "value := {}(offset)",
+                    decoding_load_op(from_memory)
+                );
                 let validator = gen.generate_validator(ctx, &ty);
                 emitln!(ctx.writer, "{}(value)", validator);
             });
@@ -470,28 +711,308 @@ impl Generator {
         self.need_auxiliary_function(function_name, Box::new(generate_fun))
     }
 
-    /// Generate decoding functions for ty.
-    fn generate_abi_decoding_type(&mut self, ctx: &Context, ty: &Type) -> String {
+    /// Generate decoding functions for ty, reading the encoded data from memory instead of the
+    /// calldata if `from_memory` is set.
+    fn generate_abi_decoding_type(
+        &mut self,
+        ctx: &Context,
+        ty: &Type,
+        from_memory: bool,
+    ) -> String {
         use Type::*;
-        // TODO: struct and dynamic types
+        // TODO: struct types
         match ty {
-            Primitive(_) => self.generate_abi_decoding_primitive_type(ctx, ty),
+            Primitive(_) => self.generate_abi_decoding_primitive_type(ctx, ty, from_memory),
             Struct(mid, sid, _) => {
-                if ctx.is_u256(mid.qualified(*sid)) {
-                    self.generate_abi_decoding_primitive_type(ctx, ty)
+                if ctx.is_native_int(mid.qualified(*sid)) {
+                    self.generate_abi_decoding_primitive_type(ctx, ty, from_memory)
+                } else if ctx.is_string(mid.qualified(*sid)) {
+                    self.generate_abi_decoding_string_type(ctx, ty, from_memory)
                 } else {
                     "".to_string() // TODO
                 }
             }
-            Tuple(tys) => self.generate_abi_tuple_decoding(ctx, tys),
-            _ => "".to_string(), // TODO: Vector
+            Tuple(tys) => self.generate_abi_tuple_decoding(ctx, tys, from_memory),
+            Vector(elem_ty) => {
+                self.generate_abi_decoding_vector_type(ctx, ty, elem_ty, from_memory)
+            }
+            _ => "".to_string(),
         }
     }
 
+    /// Generate decoding functions for vectors. A `vector<u8>` is decoded from `bytes`, which are
+    /// packed as in memory, and any other vector from an array of its element type.
+    ///
+    /// With strict calldata validation, the function also returns the end of the encoded vector,
+    /// and dynamic elements must be encoded one after the other, as the encoder would do.
+    fn generate_abi_decoding_vector_type(
+        &mut self,
+        ctx: &Context,
+        ty: &Type,
+        elem_ty: &Type,
+        from_memory: bool,
+    ) -> String {
+        let name_prefix = "abi_decode";
+        let function_name = format!(
+            "{}_{}{}",
+            name_prefix,
+            ctx.mangle_type(ty),
+            decoding_source_suffix(from_memory)
+        );
+        let load = decoding_load_op(from_memory);
+        let elem_ty = elem_ty.clone(); // need to move into lambda
+
+        let generate_fun = move |gen: &mut Generator, ctx: &Context| {
+            let strict = ctx.options.strict_calldata;
+            let elem_size = ctx.type_size(&elem_ty);
+            let failure_call = gen.call_builtin_str(
+                ctx,
+                YulFunction::Abort,
+                std::iter::once(ABI_DECODING_DATA_TOO_SHORT.to_string()),
+            );
+            let non_canonical_call = gen.call_builtin_str(
+                ctx,
+                YulFunction::Abort,
+                std::iter::once(ABI_DECODING_NON_CANONICAL.to_string()),
+            );
+            if strict {
+                emit!(ctx.writer, "(offset, end) -> value, value_end ");
+            } else {
+                emit!(ctx.writer, "(offset, end) -> value ");
+            }
+            ctx.emit_block(|| {
+                emitln!(ctx.writer, "let length := {}(offset)", load);
+                emitln!(
+                    ctx.writer,
+                    "if gt(length, 0xffffffffffffffff) {{ {} }}",
+                    failure_call
+                );
+                emitln!(ctx.writer, "let data := add(offset, 32)");
+                emitln!(
+                    ctx.writer,
+                    "let mem := {}",
+                    gen.call_builtin_str(
+                        ctx,
+                        YulFunction::MallocVector,
+                        vec![elem_size.to_string(), "length".to_string()].into_iter()
+                    )
+                );
+                if elem_ty == Type::Primitive(PrimitiveType::U8) {
+                    emitln!(
+                        ctx.writer,
+                        "if gt(add(data, length), end) {{ {} }}",
+                        failure_call
+                    );
+                    if from_memory {
+                        // Copying whole words may write past the vector, into unallocated memory
+                        emitln!(
+                            ctx.writer,
+                            "for { let i := 0 } lt(i, length) { i := add(i, 32) } \
+                             { mstore(add(add(mem, ${VECTOR_METADATA_SIZE}), i), \
+                             mload(add(data, i))) }"
+                        );
+                    } else {
+                        emitln!(
+                            ctx.writer,
+                            "calldatacopy(add(mem, ${VECTOR_METADATA_SIZE}), data, length)"
+                        );
+                    }
+                    if strict {
+                        // The bytes are padded to a multiple of 32
+                        emitln!(
+                            ctx.writer,
+                            "value_end := add(data, and(add(length, 31), not(31)))"
+                        );
+                        emitln!(ctx.writer, "if gt(value_end, end) {{ {} }}", failure_call);
+                    }
+                } else {
+                    let is_static = ctx.abi_is_static_type(&elem_ty);
+                    let elem_head_size = ctx.abi_type_head_size(&elem_ty, true);
+                    emitln!(
+                        ctx.writer,
+                        "if gt(add(data, mul(length, {})), end) {{ {} }}",
+                        elem_head_size,
+                        failure_call
+                    );
+                    if strict {
+                        // Dynamic elements start after the offsets, and end the vector
+                        emitln!(
+                            ctx.writer,
+                            "value_end := add(data, mul(length, {}))",
+                            elem_head_size
+                        );
+                    }
+                    let abi_decode_elem =
+                        gen.generate_abi_decoding_type(ctx, &elem_ty, from_memory);
+                    emit!(
+                        ctx.writer,
+                        "for { let i := 0 } lt(i, length) { i := add(i, 1) } "
+                    );
+                    ctx.emit_block(|| {
+                        if is_static {
+                            emitln!(ctx.writer, "let elem_offset := mul(i, {})", elem_head_size);
+                        } else {
+                            // Dynamic elements are found at an offset from the start of the
+                            // elements
+                            emitln!(
+                                ctx.writer,
+                                "let elem_offset := {}(add(data, mul(i, 32)))",
+                                load
+                            );
+                            if strict {
+                                emitln!(
+                                    ctx.writer,
+                                    "if iszero(eq(add(data, elem_offset), value_end)) {{ {} }}",
+                                    non_canonical_call
+                                );
+                            } else {
+                                emitln!(
+                                    ctx.writer,
+                                    "if gt(elem_offset, 0xffffffffffffffff) {{ {} }}",
+                                    failure_call
+                                );
+                            }
+                        }
+                        if strict && !is_static {
+                            emitln!(ctx.writer, "let elem");
+                            emitln!(
+                                ctx.writer,
+                                "elem, value_end := {}(add(data, elem_offset), end)",
+                                abi_decode_elem
+                            );
+                        } else {
+                            emitln!(
+                                ctx.writer,
+                                "let elem := {}(add(data, elem_offset), end)",
+                                abi_decode_elem
+                            );
+                        }
+                        gen.call_builtin(
+                            ctx,
+                            YulFunction::MemoryStoreBytes,
+                            vec![
+                                format!(
+                                    "add(add(mem, ${{VECTOR_METADATA_SIZE}}), mul(i, {}))",
+                                    elem_size
+                                ),
+                                elem_size.to_string(),
+                                "elem".to_string(),
+                            ]
+                            .into_iter(),
+                        );
+                    });
+                }
+                emitln!(
+                    ctx.writer,
+                    "value := {}",
+                    gen.call_builtin_str(
+                        ctx,
+                        YulFunction::MakePtr,
+                        vec!["false".to_string(), "mem".to_string()].into_iter()
+                    )
+                );
+            });
+        };
+        self.need_auxiliary_function(function_name, Box::new(generate_fun))
+    }
+
+    /// Generate decoding functions for strings, which are decoded like a `vector<u8>` from
+    /// `bytes`. The function aborts if the bytes are not valid UTF-8.
+    fn generate_abi_decoding_string_type(
+        &mut self,
+        ctx: &Context,
+        ty: &Type,
+        from_memory: bool,
+    ) -> String {
+        let name_prefix = "abi_decode";
+        let function_name = format!(
+            "{}_{}{}",
+            name_prefix,
+            ctx.mangle_type(ty),
+            decoding_source_suffix(from_memory)
+        );
+
+        let generate_fun = move |gen: &mut Generator, ctx: &Context| {
+            let strict = ctx.options.strict_calldata;
+            let bytes_ty = Type::Vector(Box::new(Type::Primitive(PrimitiveType::U8)));
+            let abi_decode_bytes = gen.generate_abi_decoding_type(ctx, &bytes_ty, from_memory);
+            if strict {
+                emit!(ctx.writer, "(offset, end) -> value, value_end ");
+            } else {
+                emit!(ctx.writer, "(offset, end) -> value ");
+            }
+            ctx.emit_block(|| {
+                if strict {
+                    emitln!(ctx.writer, "let bytes");
+                    emitln!(
+                        ctx.writer,
+                        "bytes, value_end := {}(offset, end)",
+                        abi_decode_bytes
+                    );
+                } else {
+                    emitln!(ctx.writer, "let bytes := {}(offset, end)", abi_decode_bytes);
+                }
+                let data = gen.call_builtin_str(
+                    ctx,
+                    YulFunction::OffsetPtr,
+                    std::iter::once("bytes".to_string()),
+                );
+                let length = gen.call_builtin_str(
+                    ctx,
+                    YulFunction::MemoryLoadU64,
+                    std::iter::once("data".to_string()),
+                );
+                let is_utf8 = gen.call_builtin_str(
+                    ctx,
+                    YulFunction::MemoryIsUtf8,
+                    vec!["add(data, ${VECTOR_METADATA_SIZE})".to_string(), length].into_iter(),
+                );
+                let failure_call = gen.call_builtin_str(
+                    ctx,
+                    YulFunction::Abort,
+                    std::iter::once(ABI_DECODING_PARAM_VALIDATION.to_string()),
+                );
+                emitln!(ctx.writer, "let data := {}", data);
+                emitln!(ctx.writer, "if iszero({}) {{ {} }}", is_utf8, failure_call);
+                // The string is a struct with the bytes as its only field
+                let malloc = gen.call_builtin_str(
+                    ctx,
+                    YulFunction::Malloc,
+                    std::iter::once("32".to_string()),
+                );
+                emitln!(ctx.writer, "let mem := {}", malloc);
+                emitln!(ctx.writer, "mstore(mem, bytes)");
+                emitln!(
+                    ctx.writer,
+                    "value := {}",
+                    gen.call_builtin_str(
+                        ctx,
+                        YulFunction::MakePtr,
+                        vec!["false".to_string(), "mem".to_string()].into_iter()
+                    )
+                );
+            });
+        };
+        self.need_auxiliary_function(function_name, Box::new(generate_fun))
+    }
+
     /// Generate decoding functions for tuple.
-    fn generate_abi_tuple_decoding(&mut self, ctx: &Context, param_types: &[Type]) -> String {
+    ///
+    /// With strict calldata validation, the data must end with the last encoded value, and
+    /// dynamic values must be encoded one after the other, as the encoder would do.
+    pub(crate) fn generate_abi_tuple_decoding(
+        &mut self,
+        ctx: &Context,
+        param_types: &[Type],
+        from_memory: bool,
+    ) -> String {
         let name_prefix = "abi_decode_tuple";
-        let function_name = format!("{}_{}", name_prefix, ctx.mangle_types(param_types));
+        let function_name = format!(
+            "{}_{}{}",
+            name_prefix,
+            ctx.mangle_types(param_types),
+            decoding_source_suffix(from_memory)
+        );
         let param_types = param_types.to_vec(); // need to move into lambda
 
         let generate_fun = move |gen: &mut Generator, ctx: &Context| {
@@ -505,6 +1026,12 @@ impl Generator {
                 "(headStart, dataEnd) -> {} ",
                 ret_var.iter().join(", ")
             );
+            let strict = ctx.options.strict_calldata;
+            let non_canonical_call = gen.call_builtin_str(
+                ctx,
+                YulFunction::Abort,
+                std::iter::once(ABI_DECODING_NON_CANONICAL.to_string()),
+            );
             ctx.emit_block(|| {
                 emitln!(
                     ctx.writer,
@@ -516,12 +1043,20 @@ impl Generator {
                         std::iter::once(ABI_DECODING_DATA_TOO_SHORT.to_string())
                     ),
                 );
+                if strict {
+                    // Dynamic values start after the heads
+                    emitln!(
+                        ctx.writer,
+                        "let tail := add(headStart, {})",
+                        overall_type_head_size
+                    );
+                }
                 let mut head_pos = 0;
                 for (stack_pos, (ty, ty_size)) in overall_type_head_vec.iter().enumerate() {
                     let is_static = ctx.abi_is_static_type(ty);
                     // TODO: consider the case size_on_stack is not 1
                     let local_typ_var = vec![ret_var[stack_pos].clone()];
-                    let abi_decode_type = gen.generate_abi_decoding_type(ctx, ty);
+                    let abi_decode_type = gen.generate_abi_decoding_type(ctx, ty, from_memory);
                     ctx.emit_block(|| {
                         if is_static {
                             emitln!(ctx.writer, "let offset := {}", head_pos);
@@ -529,28 +1064,50 @@ impl Generator {
                             // TODO: dynamic types need to be revisited
                             emitln!(
                                 ctx.writer,
-                                "let offset := calldataload(add(headStart, {}))",
+                                "let offset := {}(add(headStart, {}))",
+                                decoding_load_op(from_memory),
                                 head_pos
                             );
-                            emitln!(
-                                ctx.writer,
-                                "if gt(offset, 0xffffffffffffffff) {{ {} }}",
-                                gen.call_builtin_str(
-                                    ctx,
-                                    YulFunction::Abort,
-                                    std::iter::once(ABI_DECODING_DATA_TOO_SHORT.to_string())
-                                )
-                            );
+                            if strict {
+                                emitln!(
+                                    ctx.writer,
+                                    "if iszero(eq(add(headStart, offset), tail)) {{ {} }}",
+                                    non_canonical_call
+                                );
+                            } else {
+                                emitln!(
+                                    ctx.writer,
+                                    "if gt(offset, 0xffffffffffffffff) {{ {} }}",
+                                    gen.call_builtin_str(
+                                        ctx,
+                                        YulFunction::Abort,
+                                        std::iter::once(ABI_DECODING_DATA_TOO_SHORT.to_string())
+                                    )
+                                );
+                            }
                         }
+                        let results = if strict && !is_static {
+                            format!("{}, tail", local_typ_var.iter().join(", "))
+                        } else {
+                            local_typ_var.iter().join(", ")
+                        };
                         emitln!(
                             ctx.writer,
                             "{} := {}(add(headStart, offset), dataEnd)",
-                            local_typ_var.iter().join(", "),
+                            results,
                             abi_decode_type
                         );
                     });
                     head_pos += ty_size;
                 }
+                if strict {
+                    // No data may follow the encoded values
+                    emitln!(
+                        ctx.writer,
+                        "if iszero(eq(tail, dataEnd)) {{ {} }}",
+                        non_canonical_call
+                    );
+                }
             });
         };
 
@@ -576,25 +1133,227 @@ impl Generator {
         self.need_auxiliary_function(function_name, Box::new(generate_fun))
     }
 
-    /// Generate encoding functions for ty.
-    fn generate_abi_encoding_type(&mut self, ctx: &Context, ty: &Type) -> String {
+    /// Generate encoding functions for ty. Static types are encoded by a function
+    /// `(value, pos)`, dynamic types by a function `(value, pos) -> end` returning the end of
+    /// the encoding.
+    pub(crate) fn generate_abi_encoding_type(&mut self, ctx: &Context, ty: &Type) -> String {
         use Type::*;
-        // TODO: dynamic types
         match ty {
             Primitive(_) => self.generate_abi_encoding_primitive_type(ctx, ty),
-            Struct(mid, sid, _) => {
-                if ctx.is_u256(mid.qualified(*sid)) {
+            Struct(mid, sid, inst) => {
+                if ctx.is_native_int(mid.qualified(*sid)) {
                     self.generate_abi_encoding_primitive_type(ctx, ty)
+                } else if ctx.is_string(mid.qualified(*sid)) {
+                    self.generate_abi_encoding_string_type(ctx, ty)
                 } else {
-                    "NYI".to_string()
+                    let struct_id = mid.qualified(*sid).instantiate(inst.clone());
+                    self.generate_abi_encoding_struct_type(ctx, ty, struct_id)
                 }
             }
+            Vector(elem_ty) => self.generate_abi_encoding_vector_type(ctx, ty, elem_ty),
             _ => "NYI".to_string(),
         }
     }
 
+    /// Generate encoding functions for structs, which are encoded as tuples of their fields.
+    /// The fields are encoded in declaration order, not in the order of the memory layout.
+    fn generate_abi_encoding_struct_type(
+        &mut self,
+        ctx: &Context,
+        ty: &Type,
+        struct_id: QualifiedInstId<StructId>,
+    ) -> String {
+        let name_prefix = "abi_encode";
+        let function_name = format!("{}_{}", name_prefix, ctx.mangle_type(ty));
+        let is_static = ctx.abi_is_static_type(ty);
+
+        let generate_fun = move |gen: &mut Generator, ctx: &Context| {
+            // The layout offsets are indexed by the position of the fields in the declaration
+            let layout = ctx.get_struct_layout(&struct_id);
+            let field_types = layout
+                .offsets
+                .values()
+                .map(|(_, field_ty)| field_ty.clone())
+                .collect_vec();
+            if is_static {
+                emit!(ctx.writer, "(value, pos) ");
+            } else {
+                emit!(ctx.writer, "(value, pos) -> end ");
+            }
+            ctx.emit_block(|| {
+                emitln!(
+                    ctx.writer,
+                    "let mem := {}",
+                    gen.call_builtin_str(
+                        ctx,
+                        YulFunction::OffsetPtr,
+                        std::iter::once("value".to_string())
+                    )
+                );
+                let fields = layout
+                    .offsets
+                    .values()
+                    .map(|(byte_offs, field_ty)| {
+                        let field_size = ctx.type_size(field_ty);
+                        gen.call_builtin_str(
+                            ctx,
+                            YulFunction::MemoryLoadBytes,
+                            vec![format!("add(mem, {})", byte_offs), field_size.to_string()]
+                                .into_iter(),
+                        )
+                    })
+                    .collect_vec();
+                let abi_encode_fields = gen.generate_abi_tuple_encoding(ctx, &field_types);
+                if is_static {
+                    emitln!(
+                        ctx.writer,
+                        "pop({}(pos, {}))",
+                        abi_encode_fields,
+                        fields.iter().join(", ")
+                    );
+                } else {
+                    emitln!(
+                        ctx.writer,
+                        "end := {}(pos, {})",
+                        abi_encode_fields,
+                        fields.iter().join(", ")
+                    );
+                }
+            });
+        };
+        self.need_auxiliary_function(function_name, Box::new(generate_fun))
+    }
+
+    /// Generate encoding functions for strings, which are encoded like their bytes.
+    fn generate_abi_encoding_string_type(&mut self, ctx: &Context, ty: &Type) -> String {
+        let name_prefix = "abi_encode";
+        let function_name = format!("{}_{}", name_prefix, ctx.mangle_type(ty));
+
+        let generate_fun = move |gen: &mut Generator, ctx: &Context| {
+            let bytes_ty = Type::Vector(Box::new(Type::Primitive(PrimitiveType::U8)));
+            let abi_encode_bytes = gen.generate_abi_encoding_type(ctx, &bytes_ty);
+            emit!(ctx.writer, "(value, pos) -> end ");
+            ctx.emit_block(|| {
+                let mem = gen.call_builtin_str(
+                    ctx,
+                    YulFunction::OffsetPtr,
+                    std::iter::once("value".to_string()),
+                );
+                emitln!(
+                    ctx.writer,
+                    "end := {}(mload({}), pos)",
+                    abi_encode_bytes,
+                    mem
+                );
+            });
+        };
+        self.need_auxiliary_function(function_name, Box::new(generate_fun))
+    }
+
+    /// Generate encoding functions for vectors. A `vector<u8>` is encoded as `bytes`, and any
+    /// other vector as an array of its element type.
+    fn generate_abi_encoding_vector_type(
+        &mut self,
+        ctx: &Context,
+        ty: &Type,
+        elem_ty: &Type,
+    ) -> String {
+        let name_prefix = "abi_encode";
+        let function_name = format!("{}_{}", name_prefix, ctx.mangle_type(ty));
+        let elem_ty = elem_ty.clone(); // need to move into lambda
+
+        let generate_fun = move |gen: &mut Generator, ctx: &Context| {
+            let elem_size = ctx.type_size(&elem_ty);
+            emit!(ctx.writer, "(value, pos) -> end ");
+            ctx.emit_block(|| {
+                emitln!(
+                    ctx.writer,
+                    "let mem := {}",
+                    gen.call_builtin_str(
+                        ctx,
+                        YulFunction::OffsetPtr,
+                        std::iter::once("value".to_string())
+                    )
+                );
+                emitln!(
+                    ctx.writer,
+                    "let length := {}",
+                    gen.call_builtin_str(
+                        ctx,
+                        YulFunction::MemoryLoadU64,
+                        std::iter::once("mem".to_string())
+                    )
+                );
+                emitln!(ctx.writer, "mstore(pos, length)");
+                emitln!(ctx.writer, "pos := add(pos, 32)");
+                emitln!(ctx.writer, "let data := add(mem, ${VECTOR_METADATA_SIZE})");
+                if elem_ty == Type::Primitive(PrimitiveType::U8) {
+                    // Copy the bytes word by word, then clear what was copied past their end, to
+                    // pad them with zeros to a multiple of the word size.
+                    emitln!(
+                        ctx.writer,
+                        "for { let i := 0 } lt(i, length) { i := add(i, 32) } \
+                         { mstore(add(pos, i), mload(add(data, i))) }"
+                    );
+                    emitln!(ctx.writer, "mstore(add(pos, length), 0)");
+                    emitln!(ctx.writer, "end := add(pos, and(add(length, 31), not(31)))");
+                } else {
+                    let elem = gen.call_builtin_str(
+                        ctx,
+                        YulFunction::MemoryLoadBytes,
+                        vec![
+                            format!("add(data, mul(i, {}))", elem_size),
+                            elem_size.to_string(),
+                        ]
+                        .into_iter(),
+                    );
+                    let abi_encode_elem = gen.generate_abi_encoding_type(ctx, &elem_ty);
+                    if ctx.abi_is_static_type(&elem_ty) {
+                        let elem_head_size = ctx.abi_type_head_size(&elem_ty, true);
+                        emit!(
+                            ctx.writer,
+                            "for { let i := 0 } lt(i, length) { i := add(i, 1) } "
+                        );
+                        ctx.emit_block(|| {
+                            emitln!(
+                                ctx.writer,
+                                "{}({}, add(pos, mul(i, {})))",
+                                abi_encode_elem,
+                                elem,
+                                elem_head_size
+                            );
+                        });
+                        emitln!(
+                            ctx.writer,
+                            "end := add(pos, mul(length, {}))",
+                            elem_head_size
+                        );
+                    } else {
+                        // Dynamic elements are preceded by their offsets from the start of the
+                        // elements
+                        emitln!(ctx.writer, "let tail := add(pos, mul(length, 32))");
+                        emit!(
+                            ctx.writer,
+                            "for { let i := 0 } lt(i, length) { i := add(i, 1) } "
+                        );
+                        ctx.emit_block(|| {
+                            emitln!(ctx.writer, "mstore(add(pos, mul(i, 32)), sub(tail, pos))");
+                            emitln!(ctx.writer, "tail := {}({}, tail)", abi_encode_elem, elem);
+                        });
+                        emitln!(ctx.writer, "end := tail");
+                    }
+                }
+            });
+        };
+        self.need_auxiliary_function(function_name, Box::new(generate_fun))
+    }
+
     /// Generate encoding functions for tuple.
-    fn generate_abi_tuple_encoding(&mut self, ctx: &Context, param_types: &[Type]) -> String {
+    pub(crate) fn generate_abi_tuple_encoding(
+        &mut self,
+        ctx: &Context,
+        param_types: &[Type],
+    ) -> String {
         let name_prefix = "abi_encode_tuple";
         let function_name = format!("{}_{}", name_prefix, ctx.mangle_types(param_types));
         let param_types = param_types.to_vec(); // need to move into lambda
@@ -632,13 +1391,12 @@ impl Generator {
                             head_pos
                         );
                     } else {
-                        // TODO: dynamic types need to be revisited
                         emitln!(
                             ctx.writer,
                             "mstore(add(headStart, {}), sub(tail, headStart))",
                             head_pos
                         );
-                        emitln!(ctx.writer, "tail := {}({} tail)", abi_encode_type, values);
+                        emitln!(ctx.writer, "tail := {}({}, tail)", abi_encode_type, values);
                     }
                     head_pos += ty_size;
                 }
@@ -652,30 +1410,13 @@ impl Generator {
         &mut self,
         ctx: &Context,
         fun: &FunctionEnv<'_>,
-        selectors: &mut BTreeMap<String, QualifiedId<FunId>>,
+        function_selector: &str,
     ) {
+        ctx.set_decl_loc(&fun.get_loc());
         let fun_id = &fun.get_qualified_id().instantiate(vec![]);
         self.need_move_function(fun_id);
         let function_name = ctx.make_function_name(fun_id);
-        let original_fun_name = fun.symbol_pool().string(fun.get_name());
-        let fun_sig = format!(
-            "{}({})",
-            original_fun_name,
-            self.compute_param_types(ctx, fun)
-        );
-        let function_selector =
-            format!("0x{:x}", Keccak256::digest(fun_sig.as_bytes()))[..10].to_string();
-        // Check selector collision
-        if let Some(other_fun) = selectors.insert(function_selector.clone(), fun.get_qualified_id())
-        {
-            ctx.env.error(
-                &fun.get_loc(),
-                &format!(
-                    "hash collision for function selector with `{}`",
-                    ctx.env.get_function(other_fun).get_full_name_str()
-                ),
-            );
-        }
+        let (fun_sig, _) = self.function_signature_and_selector(ctx, fun);
         emitln!(ctx.writer, "case {}", function_selector);
         ctx.emit_block(|| {
             emitln!(ctx.writer, "// {}", fun_sig);
@@ -688,7 +1429,7 @@ impl Generator {
             let mut params = "".to_string();
             if param_count > 0 {
                 let decoding_fun_name =
-                    self.generate_abi_tuple_decoding(ctx, &fun.get_parameter_types());
+                    self.generate_abi_tuple_decoding(ctx, &fun.get_parameter_types(), false);
                 params = (0..param_count).map(|i| format!("param_{}", i)).join(", ");
                 let let_params = format!("let {} := ", params);
                 emitln!(
@@ -726,27 +1467,37 @@ impl Generator {
 
     /// Generate dispatcher routine
     fn generate_dispatcher_routine(&mut self, ctx: &Context, contract_funs: &[FunctionEnv<'_>]) {
+        // Only dispatch callables, keyed by the selector of their Solidity signature
+        let mut dispatched = vec![];
+        for fun in contract_funs {
+            if !attributes::is_callable_fun(fun) {
+                continue;
+            }
+            if !self.is_suitable_for_dispatch(ctx, fun) {
+                ctx.env.diag(
+                    Severity::Warning,
+                    &fun.get_loc(),
+                    "cannot dispatch this function because of unsupported parameter types",
+                );
+                continue;
+            }
+            ctx.set_decl_loc(&fun.get_loc());
+            let (_, function_selector) = self.function_signature_and_selector(ctx, fun);
+            dispatched.push((function_selector, fun.clone()));
+        }
+        ctx.check_no_selector_collisions(&dispatched);
         emitln!(ctx.writer, "if iszero(lt(calldatasize(), 4))");
-        let mut selectors = BTreeMap::new();
         let para_vec = vec!["calldataload(0)".to_string(), "224".to_string()];
         let shr224 = self.call_builtin_str(ctx, YulFunction::Shr, para_vec.iter().cloned());
         ctx.emit_block(|| {
             emitln!(ctx.writer, "let selector := {}", shr224);
             emitln!(ctx.writer, "switch selector");
-            for fun in contract_funs {
-                if !attributes::is_callable_fun(fun) {
-                    // Only dispatch callables
-                    continue;
-                }
-                if !self.is_suitable_for_dispatch(ctx, fun) {
-                    ctx.env.diag(
-                        Severity::Warning,
-                        &fun.get_loc(),
-                        "cannot dispatch this function because of unsupported parameter types",
-                    );
-                    continue;
+            // A colliding function has been reported above, and must not produce a duplicate case
+            let mut selectors = BTreeSet::new();
+            for (function_selector, fun) in &dispatched {
+                if selectors.insert(function_selector) {
+                    self.generate_dispatch_item(ctx, fun, function_selector);
                 }
-                self.generate_dispatch_item(ctx, fun, &mut selectors);
             }
             emitln!(ctx.writer, "default {}");
         });
@@ -755,12 +1506,33 @@ impl Generator {
     }
 
     /// Determine whether the function is suitable as a dispatcher item.
-    fn is_suitable_for_dispatch(&self, ctx: &Context, fun: &FunctionEnv) -> bool {
-        // TODO: once we support structs and vectors, remove check for them
+    pub(crate) fn is_suitable_for_dispatch(&self, ctx: &Context, fun: &FunctionEnv) -> bool {
         fun.get_parameter_types()
             .iter()
-            .chain(fun.get_return_types().iter())
-            .all(|ty| !ty.is_reference() && !ctx.type_allocates_memory(ty))
+            .all(|ty| self.is_abi_supported_type(ctx, ty, false))
+            && fun
+                .get_return_types()
+                .iter()
+                .all(|ty| self.is_abi_supported_type(ctx, ty, true))
+    }
+
+    /// Determine whether values of the type can be ABI decoded, or encoded if `encode` is set.
+    pub(crate) fn is_abi_supported_type(&self, ctx: &Context, ty: &Type, encode: bool) -> bool {
+        match ty {
+            Type::Vector(elem_ty) => self.is_abi_supported_type(ctx, elem_ty, encode),
+            Type::Struct(mid, sid, _) if ctx.is_string(mid.qualified(*sid)) => true,
+            Type::Struct(mid, sid, _) if ctx.is_table(mid.qualified(*sid)) => false,
+            // TODO: once we support decoding structs, also accept them as parameters
+            Type::Struct(mid, sid, inst) if encode && !ctx.is_native_int(mid.qualified(*sid)) => {
+                ctx.env
+                    .get_struct(mid.qualified(*sid))
+                    .get_fields()
+                    .all(|field| {
+                        self.is_abi_supported_type(ctx, &field.get_type().instantiate(inst), encode)
+                    })
+            }
+            _ => !ty.is_reference() && !ctx.type_allocates_memory(ty),
+        }
     }
 
     /// Generate Yul definitions for all callable functions.
@@ -768,6 +1540,7 @@ impl Generator {
         self.generate_dispatcher_routine(ctx, contract_funs);
         for fun in contract_funs {
             ctx.check_no_generics(fun);
+            state_mutability::check_state_mutability(ctx, fun);
             self.function(ctx, &fun.get_qualified_id().instantiate(vec![]))
         }
     }
@@ -775,6 +1548,10 @@ impl Generator {
     /// Generate code for a function. This delegates to the function generator.
     fn function(&mut self, ctx: &Context, fun_id: &QualifiedInstId<FunId>) {
         self.done_move_functions.insert(fun_id.clone());
+        if self.in_deployed_code {
+            self.deployed_move_functions
+                .insert(ctx.make_function_name(fun_id), fun_id.to_qualified_id());
+        }
         FunctionGenerator::run(self, ctx, fun_id)
     }
 
@@ -782,6 +1559,9 @@ impl Generator {
     fn begin_code_block(&mut self, ctx: &Context) {
         assert!(self.needed_move_functions.is_empty());
         assert!(self.needed_yul_functions.is_empty());
+        // Functions are scoped by code block, so those emitted for other blocks are not visible
+        self.done_move_functions.clear();
+        self.done_auxiliary_functions.clear();
         emitln!(ctx.writer, "code {");
         ctx.writer.indent();
     }
@@ -808,11 +1588,21 @@ impl Generator {
 
         // We finally emit code for all Yul functions which have been needed by the Move
         // or auxiliary functions.
-        for fun in &self.needed_yul_functions {
+        for fun in std::mem::take(&mut self.needed_yul_functions) {
             emitln!(ctx.writer, &fun.yule_def());
         }
         ctx.writer.unindent();
-        emitln!(ctx.writer, "}")
+        emitln!(ctx.writer, "}");
+
+        // Data objects follow the code of the enclosing object.
+        for (name, bytes) in std::mem::take(&mut self.needed_data) {
+            emitln!(
+                ctx.writer,
+                "data \"{}\" hex\"{}\"",
+                name,
+                hex::encode(bytes)
+            );
+        }
     }
 }
 
@@ -885,10 +1675,85 @@ impl Generator {
         function_name
     }
 
+    /// Indicate that a data object of name holding the bytes is needed. Return the name.
+    pub(crate) fn need_data(&mut self, name: &str, bytes: &[u8]) -> String {
+        self.needed_data
+            .entry(name.to_string())
+            .or_insert_with(|| bytes.to_vec());
+        name.to_string()
+    }
+
     /// Indicate that a move function is needed.
     pub(crate) fn need_move_function(&mut self, fun_id: &QualifiedInstId<FunId>) {
         if !self.done_move_functions.contains(fun_id) {
             self.needed_move_functions.push(fun_id.clone())
         }
     }
+
+    /// Derive a 4 byte hash for a type. If this hash collides with the one of another type used
+    /// in the current contract, whose values would then share storage, create an error.
+    pub(crate) fn type_hash(&mut self, ctx: &Context, ty: &Type) -> u32 {
+        let hash = mangling::type_hash(ctx.env, ty);
+        match self.type_sig_map.entry(hash) {
+            Entry::Vacant(entry) => {
+                entry.insert(ty.clone());
+            }
+            Entry::Occupied(entry) => {
+                let other_ty = entry.get().clone();
+                if &other_ty != ty && self.type_hash_collisions.insert(ty.clone()) {
+                    let ty_ctx = &TypeDisplayContext::WithEnv {
+                        env: ctx.env,
+                        type_param_names: None,
+                    };
+                    ctx.env.diag_with_labels(
+                        Severity::Error,
+                        &self.type_loc(ctx, ty),
+                        &format!(
+                            "type hash 0x{:08x} of `{}` collides with the one of `{}`, \
+                             so that values of both types would share storage",
+                            hash,
+                            ty.display(ty_ctx),
+                            other_ty.display(ty_ctx)
+                        ),
+                        vec![(
+                            self.type_loc(ctx, &other_ty),
+                            format!(
+                                "`{}` also has type hash 0x{:08x}",
+                                other_ty.display(ty_ctx),
+                                hash
+                            ),
+                        )],
+                    )
+                }
+            }
+        }
+        hash
+    }
+
+    /// Returns the location of the declaration of a type, or the one of the current contract if
+    /// the type is not a struct.
+    fn type_loc(&self, ctx: &Context, ty: &Type) -> Loc {
+        match ty {
+            Type::Struct(mid, sid, _) => ctx.env.get_struct(mid.qualified(*sid)).get_loc(),
+            _ => self.contract_loc.clone(),
+        }
+    }
+}
+
+/// The suffix of the names of decoding functions, which differ by where the encoded data is read.
+fn decoding_source_suffix(from_memory: bool) -> &'static str {
+    if from_memory {
+        "_from_memory"
+    } else {
+        ""
+    }
+}
+
+/// The Yul operation loading a word of encoded data, from memory or from the calldata.
+fn decoding_load_op(from_memory: bool) -> &'static str {
+    if from_memory {
+        "mload"
+    } else {
+        "calldataload"
+    }
 }
@@ -0,0 +1,363 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Interface cards, documenting the callable functions of a contract for integrators.
+//!
+//! A card is derived from the same model the dispatcher is generated from, and lists for each
+//! callable function its selector, Solidity signature, mutability, the errors it may revert
+//! with, and the events it may emit. It can be rendered as markdown or JSON.
+
+use crate::{
+    attributes,
+    context::Context,
+    generator::{
+        Generator, ABI_DECODING_DATA_TOO_SHORT, ABI_DECODING_PARAM_VALIDATION,
+        REVERT_ERR_NON_PAYABLE_FUN,
+    },
+    options::Options,
+};
+use itertools::Itertools;
+use move_model::{
+    ast::{ModuleName, TempIndex},
+    model::{FunId, FunctionEnv, GlobalEnv, QualifiedId},
+    ty::Type,
+};
+use move_stackless_bytecode::{
+    function_target_pipeline::FunctionVariant,
+    stackless_bytecode::{Bytecode, Constant, Operation},
+};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::{collections::BTreeSet, fmt::Write};
+
+/// The code the generated runtime aborts with on failed resource operations, as it appears in
+/// the `Error(string)` revert message. Arithmetic errors revert with `Panic(uint256)` instead.
+pub const BUILTIN_ABORT_CODE: u64 = u64::MAX;
+
+/// The documented interface of a contract.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterfaceCard {
+    /// The name of the contract object.
+    pub contract: String,
+    /// The callable functions, in order of their selectors.
+    pub callables: Vec<CallableEntry>,
+}
+
+/// The documentation of a callable function.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallableEntry {
+    /// The fully qualified Move name of the function.
+    pub name: String,
+    pub selector: String,
+    /// The Solidity signature the selector is derived from.
+    pub signature: String,
+    pub mutability: Mutability,
+    /// The Solidity types of the returned values.
+    pub returns: Vec<String>,
+    /// The errors the function may revert with.
+    pub reverts: Vec<RevertEntry>,
+    /// The events the function may emit.
+    pub events: Vec<EventEntry>,
+}
+
+/// The state mutability of a callable function, as in the Solidity ABI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mutability {
+    Pure,
+    View,
+    NonPayable,
+    Payable,
+}
+
+impl Mutability {
+    /// The mutability of a function, as declared by its attributes.
+    pub(crate) fn of_fun(fun: &FunctionEnv<'_>) -> Self {
+        if attributes::is_payable_fun(fun) {
+            Mutability::Payable
+        } else if attributes::is_pure_fun(fun) {
+            Mutability::Pure
+        } else if attributes::is_view_fun(fun) {
+            Mutability::View
+        } else {
+            Mutability::NonPayable
+        }
+    }
+}
+
+/// An error a callable function may revert with.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct RevertEntry {
+    /// The abort code in the `Error(string)` revert message, or `None` if it is computed at
+    /// runtime or the function reverts with other data.
+    pub code: Option<u64>,
+    pub reason: String,
+}
+
+/// An event a callable function may emit.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct EventEntry {
+    /// The Move type of the event.
+    pub name: String,
+    /// The Solidity signature of the event.
+    pub signature: String,
+    /// The keccak hash of the signature, identifying the event in logs.
+    pub topic: String,
+}
+
+impl InterfaceCard {
+    /// Create the interface card of the contract generated from the given environment.
+    pub fn new(options: &Options, env: &GlobalEnv) -> Self {
+        let ctx = Context::new(options, env, false);
+        let contract_funs = ctx.get_target_functions(attributes::is_contract_fun);
+        let contract = match contract_funs.first() {
+            Some(fun) => ctx.make_contract_name(&fun.module_env),
+            None => "Empty".to_string(),
+        };
+        let emit_fun = find_evm_fun(env, "emit");
+        let revert_with_fun = find_evm_fun(env, "revert_with");
+        let mut gen = Generator::default();
+        let callables = contract_funs
+            .iter()
+            .filter(|fun| attributes::is_callable_fun(fun))
+            .map(|fun| {
+                let (signature, selector) = gen.function_signature_and_selector(&ctx, fun);
+                let (reverts, events) = collect_effects(&ctx, &gen, fun, emit_fun, revert_with_fun);
+                CallableEntry {
+                    name: fun.get_full_name_str(),
+                    selector,
+                    signature,
+                    mutability: Mutability::of_fun(fun),
+                    returns: fun
+                        .get_return_types()
+                        .iter()
+                        .map(|ty| gen.get_evm_type_string(&ctx, ty))
+                        .collect(),
+                    reverts,
+                    events,
+                }
+            })
+            .sorted_by(|c1, c2| c1.selector.cmp(&c2.selector))
+            .collect();
+        Self {
+            contract,
+            callables,
+        }
+    }
+
+    /// Render the card as JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("interface card is serializable")
+    }
+
+    /// Render the card as markdown.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "# Contract `{}`", self.contract).unwrap();
+        for callable in &self.callables {
+            writeln!(out).unwrap();
+            writeln!(out, "## `{}`", callable.signature).unwrap();
+            writeln!(out).unwrap();
+            writeln!(out, "- Move function: `{}`", callable.name).unwrap();
+            writeln!(out, "- Selector: `{}`", callable.selector).unwrap();
+            writeln!(
+                out,
+                "- Mutability: {}",
+                match callable.mutability {
+                    Mutability::Pure => "pure",
+                    Mutability::View => "view",
+                    Mutability::NonPayable => "nonpayable",
+                    Mutability::Payable => "payable",
+                }
+            )
+            .unwrap();
+            if !callable.returns.is_empty() {
+                writeln!(out, "- Returns: `({})`", callable.returns.join(",")).unwrap();
+            }
+            if !callable.reverts.is_empty() {
+                writeln!(out).unwrap();
+                writeln!(out, "| Revert code | Reason |").unwrap();
+                writeln!(out, "|---|---|").unwrap();
+                for revert in &callable.reverts {
+                    let code = match revert.code {
+                        Some(code) => code.to_string(),
+                        None => "computed".to_string(),
+                    };
+                    writeln!(out, "| {} | {} |", code, revert.reason).unwrap();
+                }
+            }
+            if !callable.events.is_empty() {
+                writeln!(out).unwrap();
+                writeln!(out, "| Event | Signature | Topic |").unwrap();
+                writeln!(out, "|---|---|---|").unwrap();
+                for event in &callable.events {
+                    writeln!(
+                        out,
+                        "| `{}` | `{}` | `{}` |",
+                        event.name, event.signature, event.topic
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Find a native of the Evm module, like `Evm::emit`, if the module is part of the environment.
+fn find_evm_fun(env: &GlobalEnv, fun_name: &str) -> Option<QualifiedId<FunId>> {
+    let name = ModuleName::from_str("0x2", env.symbol_pool().make("Evm"));
+    env.find_module(&name)
+        .and_then(|module| module.find_function(env.symbol_pool().make(fun_name)))
+        .map(|fun| fun.get_qualified_id())
+}
+
+/// Collect the errors a callable function may revert with and the events it may emit, from the
+/// dispatcher and the bytecode of all functions it transitively calls.
+fn collect_effects(
+    ctx: &Context,
+    gen: &Generator,
+    fun: &FunctionEnv<'_>,
+    emit_fun: Option<QualifiedId<FunId>>,
+    revert_with_fun: Option<QualifiedId<FunId>>,
+) -> (Vec<RevertEntry>, Vec<EventEntry>) {
+    let mut reverts = BTreeSet::new();
+    let mut events = BTreeSet::new();
+    if !attributes::is_payable_fun(fun) {
+        reverts.insert(RevertEntry {
+            code: Some(REVERT_ERR_NON_PAYABLE_FUN as u64),
+            reason: "value sent to a non-payable function".to_string(),
+        });
+    }
+    if fun.get_parameter_count() > 0 {
+        reverts.insert(RevertEntry {
+            code: Some(ABI_DECODING_DATA_TOO_SHORT as u64),
+            reason: "calldata too short for the parameters".to_string(),
+        });
+        reverts.insert(RevertEntry {
+            code: Some(ABI_DECODING_PARAM_VALIDATION as u64),
+            reason: "parameter out of range of its type".to_string(),
+        });
+    }
+
+    let mut todo = vec![fun.get_qualified_id().instantiate(vec![])];
+    let mut done = BTreeSet::new();
+    while let Some(fun_id) = todo.pop() {
+        if !done.insert(fun_id.clone()) {
+            continue;
+        }
+        let fun_env = ctx.env.get_function(fun_id.to_qualified_id());
+        if fun_env.is_native() {
+            continue;
+        }
+        let target = ctx.targets.get_target(&fun_env, &FunctionVariant::Baseline);
+        let code = target.get_bytecode();
+        for bc in code {
+            use Bytecode::*;
+            use Operation::*;
+            match bc {
+                Abort(_, temp) => match abort_codes(code, *temp) {
+                    Some(codes) => reverts.extend(codes.into_iter().map(|code| RevertEntry {
+                        code: Some(code),
+                        reason: format!("abort in `{}`", fun_env.get_full_name_str()),
+                    })),
+                    None => {
+                        reverts.insert(RevertEntry {
+                            code: None,
+                            reason: format!("abort in `{}`", fun_env.get_full_name_str()),
+                        });
+                    }
+                },
+                Call(_, _, Function(m, f, inst), _, _) => {
+                    let inst = Type::instantiate_slice(inst, &fun_id.inst);
+                    let callee = m.qualified(*f);
+                    if Some(callee) == emit_fun {
+                        if let Some(event) = event_entry(ctx, gen, &inst[0]) {
+                            events.insert(event);
+                        }
+                    } else if Some(callee) == revert_with_fun {
+                        if let Type::Struct(mid, sid, inst) = &inst[0] {
+                            let (signature, selector) = gen.error_signature_and_selector(
+                                ctx,
+                                &mid.qualified(*sid).instantiate(inst.clone()),
+                            );
+                            reverts.insert(RevertEntry {
+                                code: None,
+                                reason: format!(
+                                    "custom error `{}` with selector {}",
+                                    signature, selector
+                                ),
+                            });
+                        }
+                    } else {
+                        todo.push(callee.instantiate(inst));
+                    }
+                }
+                Call(_, _, Add | Sub | Mul | Shl, _, _)
+                | Call(_, _, CastU8 | CastU64 | CastU128 | CastU256, _, _) => {
+                    reverts.insert(RevertEntry {
+                        code: None,
+                        reason: "arithmetic overflow, as `Panic(0x11)`".to_string(),
+                    });
+                }
+                Call(_, _, Div | Mod, _, _) => {
+                    reverts.insert(RevertEntry {
+                        code: None,
+                        reason: "division by zero, as `Panic(0x12)`".to_string(),
+                    });
+                }
+                Call(_, _, MoveTo(..) | MoveFrom(..) | BorrowGlobal(..), _, _) => {
+                    reverts.insert(RevertEntry {
+                        code: Some(BUILTIN_ABORT_CODE),
+                        reason: "resource missing or already published".to_string(),
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+    (reverts.into_iter().collect(), events.into_iter().collect())
+}
+
+/// Determine the constant codes a temporary passed to abort may hold, or `None` if it is
+/// computed.
+fn abort_codes(code: &[Bytecode], temp: TempIndex) -> Option<Vec<u64>> {
+    let mut codes = vec![];
+    for bc in code {
+        match bc {
+            Bytecode::Load(_, dest, Constant::U64(c)) if *dest == temp => codes.push(*c),
+            Bytecode::Load(_, dest, _) | Bytecode::Assign(_, dest, _, _) if *dest == temp => {
+                return None
+            }
+            Bytecode::Call(_, dests, _, _, _) if dests.contains(&temp) => return None,
+            _ => {}
+        }
+    }
+    if codes.is_empty() {
+        None
+    } else {
+        Some(codes)
+    }
+}
+
+/// Describe the event emitted for a value of the given type.
+fn event_entry(ctx: &Context, gen: &Generator, ty: &Type) -> Option<EventEntry> {
+    if let Type::Struct(mid, sid, inst) = ty {
+        let struct_env = ctx.env.get_struct(mid.qualified(*sid));
+        let signature = format!(
+            "{}({})",
+            struct_env.get_name().display(ctx.env.symbol_pool()),
+            struct_env
+                .get_fields()
+                .map(|field| gen.get_evm_type_string(ctx, &field.get_type().instantiate(inst)))
+                .join(",")
+        );
+        Some(EventEntry {
+            name: ty.display(&ctx.env.get_type_display_ctx()).to_string(),
+            topic: format!("0x{:x}", Keccak256::digest(signature.as_bytes())),
+            signature,
+        })
+    } else {
+        None
+    }
+}
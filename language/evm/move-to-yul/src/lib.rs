@@ -3,24 +3,42 @@
 
 #![forbid(unsafe_code)]
 
+pub mod abi_json;
 mod attributes;
+mod bcs;
+mod code_limits;
+pub mod constant_dedup;
 mod context;
+pub mod contract_diff;
+pub mod evm_asm;
 mod evm_transformation;
+pub mod function_pruning;
 mod functions;
 pub mod generator;
+pub mod interface_card;
+pub mod mangling;
 mod native_functions;
 pub mod options;
+mod scratch_allocation;
+pub mod solidity_interface;
+mod state_mutability;
+mod storage;
 mod yul_functions;
 // mod object;
 
-use crate::{generator::Generator, options::Options};
+use crate::{
+    abi_json::ContractAbi, constant_dedup::SharedConstants, function_pruning::PruningReport,
+    generator::Generator, interface_card::InterfaceCard, options::Options,
+    solidity_interface::SolidityInterface,
+};
 use anyhow::anyhow;
 use codespan_reporting::term::termcolor::{ColorChoice, StandardStream, WriteColor};
+use move_compiler::Flags;
 use move_model::{
     model::GlobalEnv, options::ModelBuilderOptions, parse_addresses_from_options,
-    run_model_builder_with_options,
+    run_model_builder_with_options_and_compilation_flags,
 };
-use std::fs;
+use std::{fs, path::Path};
 
 /// Run move-to-yul compiler and print errors to stderr.
 pub fn run_to_yul_errors_to_stderr(options: Options) -> anyhow::Result<()> {
@@ -30,11 +48,17 @@ pub fn run_to_yul_errors_to_stderr(options: Options) -> anyhow::Result<()> {
 
 /// Run move-to-yul compiler and print errors to given writer.
 pub fn run_to_yul<W: WriteColor>(error_writer: &mut W, options: Options) -> anyhow::Result<()> {
-    // Run the model builder.
-    let env = run_model_builder_with_options(
+    // Run the model builder, including test-only code in test mode.
+    let flags = if options.testing {
+        Flags::testing()
+    } else {
+        Flags::empty()
+    };
+    let env = run_model_builder_with_options_and_compilation_flags(
         &options.sources,
         &options.dependencies,
         ModelBuilderOptions::default(),
+        flags,
         parse_addresses_from_options(options.named_address_mapping.clone())?,
     )?;
     // If the model contains any errors, report them now and exit.
@@ -51,10 +75,70 @@ pub fn run_to_yul<W: WriteColor>(error_writer: &mut W, options: Options) -> anyh
         error_writer,
         "exiting with Yul generation errors",
     )?;
-    fs::write(options.output, &content)?;
+    if options.emit == "evm-asm" {
+        fs::write(
+            &options.output,
+            evm_asm::yul_to_evm_asm(&options, &env, &content)?,
+        )?;
+    } else {
+        fs::write(&options.output, &content)?;
+    }
+    fs::write(
+        Path::new(&options.output).with_extension("abi.json"),
+        ContractAbi::new(&options, &env).to_json(),
+    )?;
+    fs::write(
+        Path::new(&options.output).with_extension("sol"),
+        SolidityInterface::new(&options, &env).to_solidity(),
+    )?;
+    if let Some(format) = &options.interface_card {
+        let card = InterfaceCard::new(&options, &env);
+        let (extension, card_content) = match format.as_str() {
+            "json" => ("interface.json", card.to_json()),
+            _ => ("interface.md", card.to_markdown()),
+        };
+        fs::write(
+            Path::new(&options.output).with_extension(extension),
+            card_content,
+        )?;
+    }
+    if options.dedup_constants {
+        fs::write(
+            Path::new(&options.output).with_extension("constants.txt"),
+            SharedConstants::new(&options, &env).to_string(),
+        )?;
+    }
+    if options.pruning_report {
+        fs::write(
+            Path::new(&options.output).with_extension("pruned.txt"),
+            PruningReport::new(&options, &env).to_string(),
+        )?;
+    }
+    if options.emit_file_map {
+        fs::write(
+            Path::new(&options.output).with_extension("file_map.json"),
+            file_map_json(&env),
+        )?;
+    }
     Ok(())
 }
 
+/// Render the mapping from source file indices to file names and hashes as JSON.
+pub fn file_map_json(env: &GlobalEnv) -> String {
+    let files = env
+        .get_file_map()
+        .into_iter()
+        .map(|(idx, name, hash)| {
+            serde_json::json!({
+                "index": idx,
+                "path": name,
+                "hash": hash.to_string(),
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::to_string_pretty(&files).expect("file map is serializable")
+}
+
 pub fn check_errors<W: WriteColor>(
     env: &GlobalEnv,
     options: &Options,
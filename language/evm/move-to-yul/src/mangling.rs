@@ -0,0 +1,249 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Mangling and demangling of the names the generator creates for Move entities.
+//!
+//! A contract is named `A<addr>_<module>`, a function `<contract>_<fun><types>`, and a struct
+//! type `<contract>_<struct><types>`, where `<types>` is either empty or `$<ty>_.._<ty>$`.
+//! Because Move identifiers may themselves contain `_`, demangling cannot be done by splitting
+//! the name; instead it is driven by the modules, functions and structs found in the model.
+
+use itertools::Itertools;
+use move_model::{
+    model::{GlobalEnv, ModuleEnv, QualifiedInstId, StructId},
+    ty::Type,
+};
+use sha3::{Digest, Keccak256};
+
+/// Primitive type names as they appear in mangled names. Longer names come first so that
+/// prefix matching does not confuse e.g. `u64` with `u8`.
+const PRIMITIVE_NAMES: &[&str] = &[
+    "address", "signer", "range", "u128", "bool", "num", "u64", "u8",
+];
+
+/// Make the name of a contract.
+pub fn make_contract_name(module: &ModuleEnv) -> String {
+    let mod_name = module.get_name();
+    let mod_sym = module.symbol_pool().string(mod_name.name());
+    format!("A{}_{}", mod_name.addr().to_str_radix(16), mod_sym)
+}
+
+/// Mangle a type for being part of name.
+///
+/// Note that the mangled type representation is also used to create a hash for types
+/// in `type_hash` which is used to index storage. Therefore the representation here
+/// cannot be changed without creating versioning problems for existing storage of contracts.
+pub fn mangle_type(env: &GlobalEnv, ty: &Type) -> String {
+    use move_model::ty::{PrimitiveType::*, Type::*};
+    match ty {
+        Primitive(p) => match p {
+            U8 => "u8".to_string(),
+            U64 => "u64".to_string(),
+            U128 => "u128".to_string(),
+            Num => "num".to_string(),
+            Address => "address".to_string(),
+            Signer => "signer".to_string(),
+            Bool => "bool".to_string(),
+            Range => "range".to_string(),
+            _ => format!("<<unsupported {:?}>>", ty),
+        },
+        Vector(et) => format!("vec{}", mangle_types(env, &[et.as_ref().to_owned()])),
+        Struct(mid, sid, inst) => {
+            mangle_struct(env, &mid.qualified(*sid).instantiate(inst.clone()))
+        }
+        TypeParameter(..) | Fun(..) | Tuple(..) | TypeDomain(..) | ResourceDomain(..) | Error
+        | Var(..) | Reference(..) => format!("<<unsupported {:?}>>", ty),
+    }
+}
+
+/// Mangle a struct.
+fn mangle_struct(env: &GlobalEnv, struct_id: &QualifiedInstId<StructId>) -> String {
+    let struct_env = &env.get_struct(struct_id.to_qualified_id());
+    let module_name = make_contract_name(&struct_env.module_env);
+    format!(
+        "{}_{}{}",
+        module_name,
+        struct_env.get_name().display(struct_env.symbol_pool()),
+        mangle_types(env, &struct_id.inst)
+    )
+}
+
+/// Mangle a slice of types.
+pub fn mangle_types(env: &GlobalEnv, tys: &[Type]) -> String {
+    if tys.is_empty() {
+        "".to_owned()
+    } else {
+        format!("${}$", tys.iter().map(|ty| mangle_type(env, ty)).join("_"))
+    }
+}
+
+/// Derive the 4 byte hash of a type which is used to index storage.
+pub fn type_hash(env: &GlobalEnv, ty: &Type) -> u32 {
+    let sig = mangle_type(env, ty);
+    let mut keccak = Keccak256::new();
+    keccak.update(sig.as_bytes());
+    let digest = keccak.finalize();
+    u32::from_le_bytes([digest[0], digest[1], digest[2], digest[3]])
+}
+
+/// Translate a generated contract or function name back into a qualified Move name, as in
+/// `A2_Token_transfer$u64$` to `0x2::Token::transfer<u64>`. Returns `None` if the name does
+/// not denote a module or function in the given environment.
+pub fn demangle(env: &GlobalEnv, name: &str) -> Option<String> {
+    let name = name.strip_suffix("_deployed").unwrap_or(name);
+    let demangler = Demangler::new(env);
+    demangler
+        .demangle_function(name)
+        .or_else(|| demangler.demangle_module(name))
+        .or_else(|| {
+            // Test objects are named after the test function with a `test_` prefix.
+            name.strip_prefix("test_")
+                .and_then(|fun_name| demangler.demangle_function(fun_name))
+        })
+}
+
+/// Translate a storage type hash back into the name of the struct type it was derived from.
+/// Only struct types without type parameters can be recovered this way, since the
+/// instantiations of generic structs are not known without compiling the program.
+pub fn demangle_type_hash(env: &GlobalEnv, hash: u32) -> Option<String> {
+    env.get_modules()
+        .flat_map(|module| module.into_structs())
+        .filter(|struct_env| struct_env.get_type_parameters().is_empty())
+        .find(|struct_env| {
+            let ty = struct_env.get_qualified_id().instantiate(vec![]).to_type();
+            type_hash(env, &ty) == hash
+        })
+        .map(|struct_env| {
+            format!(
+                "{}::{}",
+                struct_env.module_env.get_full_name_str(),
+                struct_env.get_name().display(struct_env.symbol_pool())
+            )
+        })
+}
+
+/// Helper to demangle names, holding the contract names of all modules in the environment.
+struct Demangler<'env> {
+    modules: Vec<(String, ModuleEnv<'env>)>,
+}
+
+impl<'env> Demangler<'env> {
+    fn new(env: &'env GlobalEnv) -> Self {
+        let modules = env
+            .get_modules()
+            .map(|module| (make_contract_name(&module), module))
+            // Prefer longer contract names, so `A2_M_N` is tried before `A2_M`.
+            .sorted_by(|(n1, _), (n2, _)| n2.len().cmp(&n1.len()))
+            .collect();
+        Self { modules }
+    }
+
+    /// Returns the modules whose contract name is a prefix of `s`, together with the
+    /// remainder of `s` after the contract name.
+    fn modules_at<'s>(&self, s: &'s str) -> Vec<(&ModuleEnv<'env>, &'s str)> {
+        self.modules
+            .iter()
+            .filter_map(|(contract_name, module)| {
+                s.strip_prefix(contract_name.as_str())
+                    .map(|rest| (module, rest))
+            })
+            .collect()
+    }
+
+    fn demangle_module(&self, s: &str) -> Option<String> {
+        self.modules_at(s)
+            .into_iter()
+            .find(|(_, rest)| rest.is_empty())
+            .map(|(module, _)| module.get_full_name_str())
+    }
+
+    fn demangle_function(&self, s: &str) -> Option<String> {
+        for (module, rest) in self.modules_at(s) {
+            let rest = match rest.strip_prefix('_') {
+                Some(rest) => rest,
+                None => continue,
+            };
+            let fun_names = module
+                .clone()
+                .into_functions()
+                .map(|fun| fun.get_simple_name_string().to_string())
+                .sorted_by(|n1, n2| n2.len().cmp(&n1.len()));
+            for fun_name in fun_names {
+                if let Some(types) = rest.strip_prefix(fun_name.as_str()) {
+                    let qualified = format!("{}::{}", module.get_full_name_str(), fun_name);
+                    if types.is_empty() {
+                        return Some(qualified);
+                    }
+                    if let Some((inst, "")) = self.demangle_type_list(types) {
+                        return Some(format!("{}<{}>", qualified, inst.join(", ")));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Demangle a list of types of the form `$<ty>_.._<ty>$`, returning the displayed types
+    /// and the remaining input.
+    fn demangle_type_list<'s>(&self, s: &'s str) -> Option<(Vec<String>, &'s str)> {
+        let mut rest = s.strip_prefix('$')?;
+        let mut tys = vec![];
+        loop {
+            let (ty, after) = self.demangle_type(rest)?;
+            tys.push(ty);
+            if let Some(after) = after.strip_prefix('_') {
+                rest = after
+            } else {
+                return Some((tys, after.strip_prefix('$')?));
+            }
+        }
+    }
+
+    /// Demangle a single type at the beginning of `s`, returning the displayed type and
+    /// the remaining input.
+    fn demangle_type<'s>(&self, s: &'s str) -> Option<(String, &'s str)> {
+        let is_end = |rest: &str| rest.is_empty() || rest.starts_with('_') || rest.starts_with('$');
+        if let Some(rest) = s.strip_prefix("vec") {
+            if rest.starts_with('$') {
+                if let Some((mut elem, rest)) = self.demangle_type_list(rest) {
+                    if elem.len() == 1 && is_end(rest) {
+                        return Some((format!("vector<{}>", elem.pop().unwrap()), rest));
+                    }
+                }
+            }
+        }
+        for prim in PRIMITIVE_NAMES {
+            if let Some(rest) = s.strip_prefix(prim) {
+                if is_end(rest) {
+                    return Some((prim.to_string(), rest));
+                }
+            }
+        }
+        for (module, rest) in self.modules_at(s) {
+            let rest = match rest.strip_prefix('_') {
+                Some(rest) => rest,
+                None => continue,
+            };
+            let struct_names = module
+                .clone()
+                .into_structs()
+                .map(|st| st.get_name().display(st.symbol_pool()).to_string())
+                .sorted_by(|n1, n2| n2.len().cmp(&n1.len()));
+            for struct_name in struct_names {
+                if let Some(rest) = rest.strip_prefix(struct_name.as_str()) {
+                    let qualified = format!("{}::{}", module.get_full_name_str(), struct_name);
+                    if rest.starts_with('$') {
+                        if let Some((inst, rest)) = self.demangle_type_list(rest) {
+                            if is_end(rest) {
+                                return Some((format!("{}<{}>", qualified, inst.join(", ")), rest));
+                            }
+                        }
+                    } else if is_end(rest) {
+                        return Some((qualified, rest));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
@@ -1,11 +1,20 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{context::Context, Generator};
+use crate::{
+    attributes, bcs,
+    context::Context,
+    generator::IMMUTABLE_NOT_SET,
+    storage,
+    yul_functions::{substitute_placeholders, YulFunction},
+    Generator,
+};
+use itertools::Itertools;
 use move_model::{
-    ast::ModuleName,
+    ast::{AttributeValue, ModuleName, Value},
     emit, emitln,
-    model::{FunId, ModuleEnv, QualifiedId, QualifiedInstId},
+    model::{FunId, FunctionEnv, ModuleEnv, Parameter, QualifiedId, QualifiedInstId, StructId},
+    ty::Type,
 };
 use std::collections::BTreeMap;
 
@@ -22,6 +31,17 @@ impl NativeFunctions {
     pub(crate) fn create(ctx: &Context) -> Self {
         let mut funs = NativeFunctions::default();
         funs.define_evm_functions(ctx);
+        funs.define_vector_functions(ctx);
+        funs.define_bcs_functions(ctx);
+        funs.define_crypto_functions(ctx);
+        funs.define_precompile_functions(ctx);
+        funs.define_transient_functions(ctx);
+        funs.define_immutable_functions(ctx);
+        funs.define_address_functions(ctx);
+        funs.define_string_functions(ctx);
+        funs.define_table_functions(ctx);
+        funs.define_i256_functions(ctx);
+        funs.define_u256_functions(ctx);
         funs
     }
 
@@ -32,7 +52,10 @@ impl NativeFunctions {
         ctx: &Context,
         fun_id: &QualifiedInstId<FunId>,
     ) {
-        if let Some(ngen) = self.generators.get(&fun_id.to_qualified_id()) {
+        let fun = ctx.env.get_function(fun_id.to_qualified_id());
+        if let Some(value) = attributes::get_inline_yul_attr(&fun) {
+            gen_inline_yul(ctx, &fun, fun_id, value)
+        } else if let Some(ngen) = self.generators.get(&fun_id.to_qualified_id()) {
             // generate the function header
             let fun_name = ctx.make_function_name(fun_id);
             emit!(ctx.writer, "function {}", fun_name);
@@ -42,9 +65,7 @@ impl NativeFunctions {
                 &gen.contract_loc,
                 &format!(
                     "native function `{}` not implemented",
-                    ctx.env
-                        .get_function(fun_id.to_qualified_id())
-                        .get_full_name_str()
+                    fun.get_full_name_str()
                 ),
             )
         }
@@ -86,6 +107,72 @@ impl NativeFunctions {
     }
 }
 
+// ========================================================================================
+// Native functions with inline Yul code.
+
+/// Generate a native function with a `#[inline_yul = b"..."]` attribute, whose body is the given
+/// Yul code. The code accesses the parameters by their Move names, and assigns the results
+/// named as in generated code, i.e. `$result` if there is one, and `$result0`, `$result1`, ...
+/// otherwise. Placeholders like `${MEM_SIZE_LOC}` are substituted in the code.
+fn gen_inline_yul(
+    ctx: &Context,
+    fun: &FunctionEnv<'_>,
+    fun_id: &QualifiedInstId<FunId>,
+    value: &AttributeValue,
+) {
+    let code = match value {
+        AttributeValue::Value(_, Value::ByteArray(bytes)) => String::from_utf8(bytes.clone()).ok(),
+        _ => None,
+    };
+    let code = if let Some(code) = code {
+        code
+    } else {
+        ctx.env.error(
+            &fun.get_loc(),
+            "#[inline_yul] expects Yul code given as a byte string",
+        );
+        return;
+    };
+    // Placeholders are substituted when the code is emitted, except unknown ones
+    let substituted = substitute_placeholders(&code).unwrap_or_else(|| code.clone());
+    if let Some(start) = substituted.find("${") {
+        let end = substituted[start..]
+            .find('}')
+            .map(|len| start + len + 1)
+            .unwrap_or_else(|| substituted.len());
+        ctx.env.error(
+            &fun.get_loc(),
+            &format!(
+                "unknown placeholder `{}` in #[inline_yul] code",
+                &substituted[start..end]
+            ),
+        );
+        return;
+    }
+    let params = fun
+        .get_parameters()
+        .iter()
+        .map(|Parameter(name, _)| name.display(ctx.env.symbol_pool()).to_string())
+        .join(", ");
+    let results = match fun.get_return_count() {
+        0 => "".to_string(),
+        1 => " -> $result".to_string(),
+        n => format!(" -> {}", (0..n).map(|i| format!("$result{}", i)).join(", ")),
+    };
+    emit!(
+        ctx.writer,
+        "function {}({}){} ",
+        ctx.make_function_name(fun_id),
+        params,
+        results
+    );
+    ctx.emit_block(|| {
+        for line in code.lines() {
+            emitln!(ctx.writer, line.trim())
+        }
+    });
+}
+
 // ========================================================================================
 // Functions in the Evm module.
 
@@ -102,6 +189,1125 @@ impl NativeFunctions {
   signer := addr
 }"
             );
+        });
+
+        self.define(ctx, evm, "calldatasize", |_, ctx: &Context, _| {
+            emitln!(
+                ctx.writer,
+                "\
+() -> size {
+  size := calldatasize()
+}"
+            );
+        });
+
+        self.define(ctx, evm, "calldatacopy", |gen, ctx: &Context, _| {
+            let copy = gen.call_builtin_str(
+                ctx,
+                YulFunction::CalldataBytes,
+                vec!["offset".to_string(), "size".to_string()].into_iter(),
+            );
+            emitln!(
+                ctx.writer,
+                "(offset, size) -> bytes {{\n  bytes := {}\n}}",
+                copy
+            );
+        });
+
+        self.define(ctx, evm, "call", |gen, ctx: &Context, _| {
+            let call = gen.call_builtin_str(
+                ctx,
+                YulFunction::CallBytes,
+                vec![
+                    "addr".to_string(),
+                    "gas_limit".to_string(),
+                    "value".to_string(),
+                    "data".to_string(),
+                ]
+                .into_iter(),
+            );
+            emitln!(
+                ctx.writer,
+                "(addr, value, gas_limit, data) -> success, ret {{\n  success, ret := {}\n}}",
+                call
+            );
+        });
+
+        self.define(ctx, evm, "call_or_revert", |gen, ctx: &Context, _| {
+            let call = gen.call_builtin_str(
+                ctx,
+                YulFunction::CallBytesOrRevert,
+                vec![
+                    "addr".to_string(),
+                    "gas_limit".to_string(),
+                    "value".to_string(),
+                    "data".to_string(),
+                ]
+                .into_iter(),
+            );
+            emitln!(
+                ctx.writer,
+                "(addr, value, gas_limit, data) -> ret {{\n  ret := {}\n}}",
+                call
+            );
+        });
+
+        self.define(ctx, evm, "call_or_abort", |gen, ctx: &Context, _| {
+            let call = gen.call_builtin_str(
+                ctx,
+                YulFunction::CallBytesOrAbort,
+                vec![
+                    "addr".to_string(),
+                    "gas_limit".to_string(),
+                    "value".to_string(),
+                    "data".to_string(),
+                    "code".to_string(),
+                ]
+                .into_iter(),
+            );
+            emitln!(
+                ctx.writer,
+                "(addr, value, gas_limit, data, code) -> ret {{\n  ret := {}\n}}",
+                call
+            );
+        });
+
+        self.define(ctx, evm, "staticcall", |gen, ctx: &Context, _| {
+            let call = gen.call_builtin_str(
+                ctx,
+                YulFunction::StaticCallBytes,
+                vec![
+                    "addr".to_string(),
+                    "gas_limit".to_string(),
+                    "data".to_string(),
+                ]
+                .into_iter(),
+            );
+            emitln!(
+                ctx.writer,
+                "(addr, gas_limit, data) -> success, ret {{\n  success, ret := {}\n}}",
+                call
+            );
+        });
+
+        self.define(ctx, evm, "delegatecall", |gen, ctx: &Context, _| {
+            let call = gen.call_builtin_str(
+                ctx,
+                YulFunction::DelegateCallBytes,
+                vec![
+                    "addr".to_string(),
+                    "gas_limit".to_string(),
+                    "data".to_string(),
+                ]
+                .into_iter(),
+            );
+            emitln!(
+                ctx.writer,
+                "(addr, gas_limit, data) -> success, ret {{\n  success, ret := {}\n}}",
+                call
+            );
+        });
+
+        self.define(ctx, evm, "self", |_, ctx: &Context, _| {
+            emitln!(
+                ctx.writer,
+                "\
+() -> addr {
+  addr := address()
+}"
+            );
+        });
+
+        self.define(ctx, evm, "sender", |_, ctx: &Context, _| {
+            emitln!(
+                ctx.writer,
+                "\
+() -> addr {
+  addr := caller()
+}"
+            );
+        });
+
+        self.define(ctx, evm, "balance", |gen, ctx: &Context, _| {
+            let abort = gen.call_builtin_str(ctx, YulFunction::AbortBuiltin, std::iter::empty());
+            emitln!(
+                ctx.writer,
+                "(addr) -> val {{\n  val := balance(addr)\n  if gt(val, {}) {{ {} }}\n}}",
+                "${MAX_U128}",
+                abort
+            );
+        });
+
+        self.define(ctx, evm, "transfer", |gen, ctx: &Context, _| {
+            let transfer = gen.call_builtin_str(
+                ctx,
+                YulFunction::TransferValue,
+                vec!["addr".to_string(), "amount".to_string()].into_iter(),
+            );
+            emitln!(ctx.writer, "(addr, amount) {{\n  {}\n}}", transfer);
+        });
+
+        self.define(ctx, evm, "send", |gen, ctx: &Context, _| {
+            let send = gen.call_builtin_str(
+                ctx,
+                YulFunction::SendValue,
+                vec!["addr".to_string(), "amount".to_string()].into_iter(),
+            );
+            emitln!(
+                ctx.writer,
+                "(addr, amount) -> success {{\n  success := {}\n}}",
+                send
+            );
+        });
+
+        self.define(ctx, evm, "abi_encode", |gen, ctx: &Context, fun_id| {
+            let ty = &fun_id.inst[0];
+            if !gen.is_abi_supported_type(ctx, ty, true) {
+                ctx.unsupported_type_error(ty, "ABI encoded");
+                return;
+            }
+            let encode = gen.generate_abi_tuple_encoding(ctx, std::slice::from_ref(ty));
+            let make_vector = gen.call_builtin_str(
+                ctx,
+                YulFunction::MallocVector,
+                vec!["1".to_string(), "length".to_string()].into_iter(),
+            );
+            let make_ptr = gen.call_builtin_str(
+                ctx,
+                YulFunction::MakePtr,
+                vec!["false".to_string(), make_vector].into_iter(),
+            );
+            // The value is encoded in the free memory, where the vector holding the encoding is
+            // then allocated
+            emitln!(
+                ctx.writer,
+                "(value) -> bytes {{\n  \
+                 let start := add(mload(${{MEM_SIZE_LOC}}), ${{VECTOR_METADATA_SIZE}})\n  \
+                 let length := sub({}(start, value), start)\n  \
+                 bytes := {}\n}}",
+                encode,
+                make_ptr
+            );
+        });
+
+        self.define(ctx, evm, "revert_with", |gen, ctx: &Context, fun_id| {
+            let ty = &fun_id.inst[0];
+            let struct_id = match ty {
+                Type::Struct(mid, sid, inst)
+                    if !ctx.is_native_int(mid.qualified(*sid))
+                        && !ctx.is_string(mid.qualified(*sid))
+                        && gen.is_abi_supported_type(ctx, ty, true) =>
+                {
+                    mid.qualified(*sid).instantiate(inst.clone())
+                }
+                _ => {
+                    ctx.env.error(
+                        &gen.contract_loc,
+                        &format!(
+                            "type `{}` cannot be a custom error, \
+                             which must be a struct with ABI encodable fields",
+                            ty.display(&ctx.env.get_type_display_ctx())
+                        ),
+                    );
+                    return;
+                }
+            };
+            let (_, selector) = gen.error_signature_and_selector(ctx, &struct_id);
+            let encode = gen.generate_abi_encoding_type(ctx, ty);
+            // The revert data is the selector followed by the fields of the error, encoded as
+            // a tuple in the free memory
+            emitln!(ctx.writer, "(error) {{");
+            ctx.writer.indent();
+            emitln!(ctx.writer, "let start := mload(${{MEM_SIZE_LOC}})");
+            emitln!(ctx.writer, "mstore(start, shl(224, {}))", selector);
+            if ctx.abi_is_static_type(ty) {
+                emitln!(ctx.writer, "{}(error, add(start, 4))", encode);
+                emitln!(
+                    ctx.writer,
+                    "revert(start, {})",
+                    4 + ctx.abi_type_head_size(ty, true)
+                );
+            } else {
+                emitln!(ctx.writer, "let end := {}(error, add(start, 4))", encode);
+                emitln!(ctx.writer, "revert(start, sub(end, start))");
+            }
+            ctx.writer.unindent();
+            emitln!(ctx.writer, "}}");
+        });
+
+        self.define(ctx, evm, "abi_decode", |gen, ctx: &Context, fun_id| {
+            let ty = &fun_id.inst[0];
+            if !gen.is_abi_supported_type(ctx, ty, false) {
+                ctx.unsupported_type_error(ty, "ABI decoded");
+                return;
+            }
+            let decode = gen.generate_abi_tuple_decoding(ctx, std::slice::from_ref(ty), true);
+            let offs = gen.call_builtin_str(
+                ctx,
+                YulFunction::OffsetPtr,
+                std::iter::once("bytes".to_string()),
+            );
+            let length = gen.call_builtin_str(
+                ctx,
+                YulFunction::MemoryLoadU64,
+                std::iter::once("offs".to_string()),
+            );
+            emitln!(
+                ctx.writer,
+                "(bytes) -> value {{\n  \
+                 let offs := {}\n  \
+                 let start := add(offs, ${{VECTOR_METADATA_SIZE}})\n  \
+                 value := {}(start, add(start, {}))\n}}",
+                offs,
+                decode,
+                length
+            );
+        });
+
+        self.define_env_value(ctx, evm, "value", "callvalue()", "${MAX_U128}");
+        self.define_env_value(ctx, evm, "block_number", "number()", "${MAX_U64}");
+        self.define_env_value(ctx, evm, "timestamp", "timestamp()", "${MAX_U64}");
+        self.define_env_value(ctx, evm, "chain_id", "chainid()", "${MAX_U64}");
+        self.define_env_value(ctx, evm, "gas_left", "gas()", "${MAX_U64}");
+    }
+
+    /// Defines a native function returning a value of the environment, as given by a Yul
+    /// builtin. The function aborts if the value is larger than `max`, the largest value of
+    /// the Move type returned.
+    fn define_env_value(
+        &mut self,
+        ctx: &Context,
+        module: &Option<ModuleEnv>,
+        name: &str,
+        builtin: &'static str,
+        max: &'static str,
+    ) {
+        self.define(ctx, module, name, move |gen, ctx: &Context, _| {
+            let abort = gen.call_builtin_str(ctx, YulFunction::AbortBuiltin, std::iter::empty());
+            emitln!(
+                ctx.writer,
+                "() -> val {{\n  val := {}\n  if gt(val, {}) {{ {} }}\n}}",
+                builtin,
+                max,
+                abort
+            );
+        })
+    }
+}
+
+// ========================================================================================
+// Functions in the Vector module.
+
+impl NativeFunctions {
+    fn define_vector_functions(&mut self, ctx: &Context) {
+        let vector = &self.find_module(ctx, "0x1", "Vector");
+
+        self.define(ctx, vector, "empty", |gen, ctx: &Context, fun_id| {
+            let malloc = gen.call_builtin_str(
+                ctx,
+                YulFunction::MallocVector,
+                vec![elem_size(ctx, fun_id), "0".to_string()].into_iter(),
+            );
+            let make_ptr = gen.call_builtin_str(
+                ctx,
+                YulFunction::MakePtr,
+                vec!["false".to_string(), malloc].into_iter(),
+            );
+            emitln!(ctx.writer, "() -> v {{\n  v := {}\n}}", make_ptr);
+        });
+
+        self.define(ctx, vector, "length", |gen, ctx: &Context, _| {
+            let load_vec = gen.call_builtin_str(
+                ctx,
+                YulFunction::LoadU256,
+                std::iter::once("v_ref".to_string()),
+            );
+            let length = gen.call_builtin_str(ctx, YulFunction::LoadU64, std::iter::once(load_vec));
+            emitln!(ctx.writer, "(v_ref) -> len {{\n  len := {}\n}}", length);
+        });
+
+        let borrow = |gen: &mut Generator, ctx: &Context, fun_id: &QualifiedInstId<FunId>| {
+            let load_vec = gen.call_builtin_str(
+                ctx,
+                YulFunction::LoadU256,
+                std::iter::once("v_ref".to_string()),
+            );
+            let elem_ptr = gen.call_builtin_str(
+                ctx,
+                YulFunction::VectorElemPtr,
+                vec![load_vec, elem_size(ctx, fun_id), "i".to_string()].into_iter(),
+            );
+            emitln!(
+                ctx.writer,
+                "(v_ref, i) -> e_ref {{\n  e_ref := {}\n}}",
+                elem_ptr
+            );
+        };
+        self.define(ctx, vector, "borrow", borrow);
+        self.define(ctx, vector, "borrow_mut", borrow);
+
+        self.define(ctx, vector, "push_back", |gen, ctx: &Context, fun_id| {
+            let push_back = gen.call_builtin_str(
+                ctx,
+                YulFunction::VectorPushBack,
+                vec!["v_ref".to_string(), elem_size(ctx, fun_id), "e".to_string()].into_iter(),
+            );
+            emitln!(ctx.writer, "(v_ref, e) {{");
+            ctx.writer.indent();
+            let elem_ty = &fun_id.inst[0];
+            if ctx.type_allocates_memory(elem_ty) {
+                // An element pushed to a vector in storage is moved to storage
+                let is_storage = is_storage_vector(gen, ctx);
+                let move_to_storage = storage::generate_move_to_storage(gen, ctx, elem_ty);
+                emitln!(
+                    ctx.writer,
+                    "if {} {{ e := {}(e) }}",
+                    is_storage,
+                    move_to_storage
+                );
+            }
+            emitln!(ctx.writer, "{}", push_back);
+            ctx.writer.unindent();
+            emitln!(ctx.writer, "}}");
+        });
+
+        self.define(ctx, vector, "pop_back", |gen, ctx: &Context, fun_id| {
+            let pop_back = gen.call_builtin_str(
+                ctx,
+                YulFunction::VectorPopBack,
+                vec!["v_ref".to_string(), elem_size(ctx, fun_id)].into_iter(),
+            );
+            emitln!(ctx.writer, "(v_ref) -> e {{");
+            ctx.writer.indent();
+            emitln!(ctx.writer, "e := {}", pop_back);
+            let elem_ty = &fun_id.inst[0];
+            if ctx.type_allocates_memory(elem_ty) {
+                // An element popped from a vector in storage is moved to memory
+                let is_storage = is_storage_vector(gen, ctx);
+                let move_from_storage = storage::generate_move_from_storage(gen, ctx, elem_ty);
+                emitln!(
+                    ctx.writer,
+                    "if {} {{ e := {}(e) }}",
+                    is_storage,
+                    move_from_storage
+                );
+            }
+            ctx.writer.unindent();
+            emitln!(ctx.writer, "}}");
+        });
+
+        self.define(ctx, vector, "swap", |gen, ctx: &Context, fun_id| {
+            let swap = gen.call_builtin_str(
+                ctx,
+                YulFunction::VectorSwap,
+                vec![
+                    "v_ref".to_string(),
+                    elem_size(ctx, fun_id),
+                    "i".to_string(),
+                    "j".to_string(),
+                ]
+                .into_iter(),
+            );
+            emitln!(ctx.writer, "(v_ref, i, j) {{\n  {}\n}}", swap);
+        });
+
+        self.define(ctx, vector, "destroy_empty", |gen, ctx: &Context, _| {
+            let length =
+                gen.call_builtin_str(ctx, YulFunction::LoadU64, std::iter::once("v".to_string()));
+            let abort = gen.call_builtin_str(ctx, YulFunction::AbortBuiltin, std::iter::empty());
+            emitln!(ctx.writer, "(v) {{\n  if {} {{ {} }}\n}}", length, abort);
+        });
+    }
+}
+
+/// The size of the elements of the vector the native function is instantiated with.
+fn elem_size(ctx: &Context, fun_id: &QualifiedInstId<FunId>) -> String {
+    ctx.type_size(&fun_id.inst[0]).to_string()
+}
+
+/// Whether the vector `v_ref` references is in storage.
+fn is_storage_vector(gen: &mut Generator, ctx: &Context) -> String {
+    let load_vec = gen.call_builtin_str(
+        ctx,
+        YulFunction::LoadU256,
+        std::iter::once("v_ref".to_string()),
+    );
+    gen.call_builtin_str(ctx, YulFunction::IsStoragePtr, std::iter::once(load_vec))
+}
+
+// ========================================================================================
+// Functions in the String module.
+
+impl NativeFunctions {
+    fn define_string_functions(&mut self, ctx: &Context) {
+        let string = &self.find_module(ctx, "0x2", "String");
+
+        // The vector is a local of `String::utf8`, hence in memory
+        self.define(
+            ctx,
+            string,
+            "internal_check_utf8",
+            |gen, ctx: &Context, _| {
+                let load_vec = gen.call_builtin_str(
+                    ctx,
+                    YulFunction::LoadU256,
+                    std::iter::once("v_ref".to_string()),
+                );
+                let offs =
+                    gen.call_builtin_str(ctx, YulFunction::OffsetPtr, std::iter::once(load_vec));
+                let length = gen.call_builtin_str(
+                    ctx,
+                    YulFunction::MemoryLoadU64,
+                    std::iter::once("offs".to_string()),
+                );
+                let is_utf8 = gen.call_builtin_str(
+                    ctx,
+                    YulFunction::MemoryIsUtf8,
+                    vec!["add(offs, ${VECTOR_METADATA_SIZE})".to_string(), length].into_iter(),
+                );
+                emitln!(
+                    ctx.writer,
+                    "(v_ref) -> valid {{\n  let offs := {}\n  valid := {}\n}}",
+                    offs,
+                    is_utf8
+                );
+            },
+        );
+    }
+}
+
+// ========================================================================================
+// Functions in the Table module.
+
+impl NativeFunctions {
+    fn define_table_functions(&mut self, ctx: &Context) {
+        let table = &self.find_module(ctx, "0x2", "Table");
+
+        // The type of the keys is only checked when a table is created, as any other operation
+        // on a table follows its creation
+        self.define(ctx, table, "empty", |gen, ctx: &Context, fun_id| {
+            let key_ty = &fun_id.inst[0];
+            if !ctx.is_word_type(key_ty) {
+                ctx.env.error(
+                    &gen.contract_loc,
+                    &format!(
+                        "type `{}` cannot be the key of a table, \
+                         which only supports primitive types, U256 and I256",
+                        key_ty.display(&ctx.env.get_type_display_ctx())
+                    ),
+                );
+                return;
+            }
+            let group = gen.call_builtin_str(ctx, YulFunction::NewStorageGroup, std::iter::empty());
+            emitln!(ctx.writer, "() -> table {{\n  table := {}\n}}", group);
+        });
+
+        self.define(ctx, table, "add", |gen, ctx: &Context, fun_id| {
+            let val_ty = &fun_id.inst[1];
+            let slot = table_slot(gen, ctx);
+            let abort = gen.call_builtin_str(ctx, YulFunction::AbortBuiltin, std::iter::empty());
+            let hash = gen.type_hash(ctx, val_ty);
+            let base = gen.call_builtin_str(
+                ctx,
+                YulFunction::NewLinkedStorageBase,
+                std::iter::once(format!("0x{:x}", hash)),
+            );
+            let store = gen.call_builtin_str(
+                ctx,
+                ctx.storage_store_builtin_fun(val_ty),
+                vec!["offs".to_string(), "val".to_string()].into_iter(),
+            );
+            emitln!(ctx.writer, "(table_ref, key, val) {{");
+            ctx.writer.indent();
+            emitln!(ctx.writer, "let slot := {}", slot);
+            emitln!(ctx.writer, "if sload(slot) {{ {} }}", abort);
+            emitln!(ctx.writer, "let offs := {}", base);
+            emitln!(ctx.writer, "sstore(slot, offs)");
+            if ctx.type_allocates_memory(val_ty) {
+                let move_to_storage = storage::generate_move_to_storage(gen, ctx, val_ty);
+                emitln!(ctx.writer, "val := {}(val)", move_to_storage);
+            }
+            emitln!(ctx.writer, "{}", store);
+            ctx.writer.unindent();
+            emitln!(ctx.writer, "}}");
+        });
+
+        let borrow = |gen: &mut Generator, ctx: &Context, _: &QualifiedInstId<FunId>| {
+            let slot = table_slot(gen, ctx);
+            let abort = gen.call_builtin_str(ctx, YulFunction::AbortBuiltin, std::iter::empty());
+            let make_ptr = gen.call_builtin_str(
+                ctx,
+                YulFunction::MakePtr,
+                vec!["true".to_string(), "offs".to_string()].into_iter(),
+            );
+            emitln!(
+                ctx.writer,
+                "(table_ref, key) -> val_ref {{\n  let offs := sload({})\n  \
+                 if iszero(offs) {{ {} }}\n  val_ref := {}\n}}",
+                slot,
+                abort,
+                make_ptr
+            );
+        };
+        self.define(ctx, table, "borrow", borrow);
+        self.define(ctx, table, "borrow_mut", borrow);
+
+        self.define(ctx, table, "contains", |gen, ctx: &Context, _| {
+            let slot = table_slot(gen, ctx);
+            emitln!(
+                ctx.writer,
+                "(table_ref, key) -> b {{\n  b := iszero(iszero(sload({})))\n}}",
+                slot
+            );
+        });
+
+        self.define(ctx, table, "remove", |gen, ctx: &Context, fun_id| {
+            let val_ty = &fun_id.inst[1];
+            let slot = table_slot(gen, ctx);
+            let abort = gen.call_builtin_str(ctx, YulFunction::AbortBuiltin, std::iter::empty());
+            let load = gen.call_builtin_str(
+                ctx,
+                ctx.storage_load_builtin_fun(val_ty),
+                std::iter::once("offs".to_string()),
+            );
+            let clear = gen.call_builtin_str(
+                ctx,
+                YulFunction::AlignedStorageStore,
+                vec!["offs".to_string(), "0".to_string()].into_iter(),
+            );
+            emitln!(ctx.writer, "(table_ref, key) -> val {{");
+            ctx.writer.indent();
+            emitln!(ctx.writer, "let slot := {}", slot);
+            emitln!(ctx.writer, "let offs := sload(slot)");
+            emitln!(ctx.writer, "if iszero(offs) {{ {} }}", abort);
+            emitln!(ctx.writer, "sstore(slot, 0)");
+            emitln!(ctx.writer, "val := {}", load);
+            emitln!(ctx.writer, "{}", clear);
+            if ctx.type_allocates_memory(val_ty) {
+                // The value is moved back to memory, clearing the storage it links to
+                let move_from_storage = storage::generate_move_from_storage(gen, ctx, val_ty);
+                emitln!(ctx.writer, "val := {}(val)", move_from_storage);
+            }
+            ctx.writer.unindent();
+            emitln!(ctx.writer, "}}");
+        });
+    }
+}
+
+/// The storage slot of the entry for `key` in the table `table_ref` references, which holds the
+/// storage offset of the value of the entry, or zero if there is none.
+fn table_slot(gen: &mut Generator, ctx: &Context) -> String {
+    let table = gen.call_builtin_str(
+        ctx,
+        YulFunction::LoadU256,
+        std::iter::once("table_ref".to_string()),
+    );
+    gen.call_builtin_str(
+        ctx,
+        YulFunction::StorageKey,
+        vec![table, "key".to_string()].into_iter(),
+    )
+}
+
+// ========================================================================================
+// Functions in the U256 module, which are not lowered to operations by the
+// `EvmTransformationProcessor`.
+
+impl NativeFunctions {
+    fn define_u256_functions(&mut self, ctx: &Context) {
+        let u256 = &self.find_module(ctx, "0x1", "U256");
+
+        self.define_conversion(ctx, u256, "from_u8", None);
+        self.define_conversion(ctx, u256, "from_u64", None);
+        self.define_conversion(ctx, u256, "from_u128", None);
+        self.define_builtin(ctx, u256, "not", "x", YulFunction::BitNot);
+    }
+}
+
+// ========================================================================================
+// Functions in the I256 module.
+
+impl NativeFunctions {
+    fn define_i256_functions(&mut self, ctx: &Context) {
+        let i256 = &self.find_module(ctx, "0x2", "I256");
+
+        self.define_conversion(ctx, i256, "from_u128", None);
+        self.define_conversion(ctx, i256, "from_u256", Some("${MAX_I256}"));
+        // A negative number is larger than the largest I256 as an unsigned number
+        self.define_conversion(ctx, i256, "to_u256", Some("${MAX_I256}"));
+        self.define(ctx, i256, "is_neg", |_, ctx: &Context, _| {
+            emitln!(ctx.writer, "(x) -> r {\n  r := slt(x, 0)\n}")
+        });
+
+        self.define_builtin(ctx, i256, "neg", "x", YulFunction::NegI256);
+        self.define_builtin(ctx, i256, "add", "x, y", YulFunction::AddI256);
+        self.define_builtin(ctx, i256, "sub", "x, y", YulFunction::SubI256);
+        self.define_builtin(ctx, i256, "mul", "x, y", YulFunction::MulI256);
+        self.define_builtin(ctx, i256, "div", "x, y", YulFunction::DivI256);
+        self.define_builtin(ctx, i256, "mod", "x, y", YulFunction::ModI256);
+        self.define_builtin(ctx, i256, "lt", "x, y", YulFunction::LtI256);
+        self.define_builtin(ctx, i256, "gt", "x, y", YulFunction::GtI256);
+        self.define_builtin(ctx, i256, "le", "x, y", YulFunction::LtEqI256);
+        self.define_builtin(ctx, i256, "ge", "x, y", YulFunction::GtEqI256);
+    }
+
+    /// Defines a native function returning the result of the Yul function, which takes the same
+    /// parameters.
+    fn define_builtin(
+        &mut self,
+        ctx: &Context,
+        module: &Option<ModuleEnv>,
+        name: &str,
+        params: &'static str,
+        fun: YulFunction,
+    ) {
+        self.define(ctx, module, name, move |gen, ctx: &Context, _| {
+            let call =
+                gen.call_builtin_str(ctx, fun, params.split(", ").map(|param| param.to_string()));
+            emitln!(ctx.writer, "({}) -> r {{\n  r := {}\n}}", params, call);
         })
     }
 }
+
+// ========================================================================================
+// Functions in the BCS modules.
+
+impl NativeFunctions {
+    fn define_bcs_functions(&mut self, ctx: &Context) {
+        let std_bcs = &self.find_module(ctx, "0x1", "BCS");
+        let eth_bcs = &self.find_module(ctx, "0x2", "BCS");
+
+        self.define(ctx, std_bcs, "to_bytes", |gen, ctx: &Context, fun_id| {
+            let ty = &fun_id.inst[0];
+            if !bcs::is_bcs_supported_type(ctx, ty, false) {
+                ctx.unsupported_type_error(ty, "BCS serialized");
+                return;
+            }
+            let load = gen.call_builtin_str(
+                ctx,
+                YulFunction::LoadBytes,
+                vec!["v".to_string(), ctx.type_size(ty).to_string()].into_iter(),
+            );
+            let serialize = bcs::generate_bcs_serialization(gen, ctx, ty);
+            let make_vector = gen.call_builtin_str(
+                ctx,
+                YulFunction::MallocVector,
+                vec!["1".to_string(), "length".to_string()].into_iter(),
+            );
+            let make_ptr = gen.call_builtin_str(
+                ctx,
+                YulFunction::MakePtr,
+                vec!["false".to_string(), make_vector].into_iter(),
+            );
+            // As for `abi_encode`, the value is serialized in the free memory, where the vector
+            // holding the serialization is then allocated
+            emitln!(
+                ctx.writer,
+                "(v) -> bytes {{\n  \
+                 let start := add(mload(${{MEM_SIZE_LOC}}), ${{VECTOR_METADATA_SIZE}})\n  \
+                 let length := sub({}({}, start), start)\n  \
+                 bytes := {}\n}}",
+                serialize,
+                load,
+                make_ptr
+            );
+        });
+
+        self.define(ctx, eth_bcs, "from_bytes", |gen, ctx: &Context, fun_id| {
+            let ty = &fun_id.inst[0];
+            if !bcs::is_bcs_supported_type(ctx, ty, true) {
+                ctx.unsupported_type_error(ty, "BCS deserialized");
+                return;
+            }
+            let deserialize = bcs::generate_bcs_deserialization(gen, ctx, ty);
+            let offs = gen.call_builtin_str(
+                ctx,
+                YulFunction::OffsetPtr,
+                std::iter::once("bytes".to_string()),
+            );
+            let length = gen.call_builtin_str(
+                ctx,
+                YulFunction::MemoryLoadU64,
+                std::iter::once("offs".to_string()),
+            );
+            let abort = gen.call_builtin_str(ctx, YulFunction::AbortBuiltin, std::iter::empty());
+            // No bytes may follow the serialized value
+            emitln!(
+                ctx.writer,
+                "(bytes) -> value {{\n  \
+                 let offs := {}\n  \
+                 let start := add(offs, ${{VECTOR_METADATA_SIZE}})\n  \
+                 let end := add(start, {})\n  \
+                 let next := 0\n  \
+                 value, next := {}(start, end)\n  \
+                 if iszero(eq(next, end)) {{ {} }}\n}}",
+                offs,
+                length,
+                deserialize,
+                abort
+            );
+        });
+    }
+}
+
+// ========================================================================================
+// Functions in the Crypto module.
+
+impl NativeFunctions {
+    fn define_crypto_functions(&mut self, ctx: &Context) {
+        let crypto = &self.find_module(ctx, "0x2", "Crypto");
+
+        self.define(ctx, crypto, "keccak256", |gen, ctx: &Context, _| {
+            let hash = gen.call_builtin_str(
+                ctx,
+                YulFunction::Keccak256Bytes,
+                std::iter::once("data".to_string()),
+            );
+            emitln!(ctx.writer, "(data) -> hash {{\n  hash := {}\n}}", hash);
+        });
+
+        self.define(ctx, crypto, "sha256", |gen, ctx: &Context, _| {
+            let hash = gen.call_builtin_str(
+                ctx,
+                YulFunction::Sha256Bytes,
+                std::iter::once("data".to_string()),
+            );
+            emitln!(ctx.writer, "(data) -> hash {{\n  hash := {}\n}}", hash);
+        });
+
+        self.define(ctx, crypto, "ecrecover", |gen, ctx: &Context, _| {
+            let addr = gen.call_builtin_str(
+                ctx,
+                YulFunction::Ecrecover,
+                vec![
+                    "hash".to_string(),
+                    "v".to_string(),
+                    "r".to_string(),
+                    "s".to_string(),
+                ]
+                .into_iter(),
+            );
+            emitln!(
+                ctx.writer,
+                "(hash, v, r, s) -> addr {{\n  addr := {}\n}}",
+                addr
+            );
+        });
+    }
+}
+
+// ========================================================================================
+// Functions in the Precompiles module.
+
+impl NativeFunctions {
+    fn define_precompile_functions(&mut self, ctx: &Context) {
+        let precompiles = &self.find_module(ctx, "0x2", "Precompiles");
+        self.define_precompile(
+            ctx,
+            precompiles,
+            "modexp",
+            YulFunction::ModExp,
+            &["base", "exponent", "modulus"],
+            &["result"],
+        );
+        self.define_precompile(
+            ctx,
+            precompiles,
+            "bn256_add",
+            YulFunction::Bn256Add,
+            &["x1", "y1", "x2", "y2"],
+            &["x", "y"],
+        );
+        self.define_precompile(
+            ctx,
+            precompiles,
+            "bn256_mul",
+            YulFunction::Bn256Mul,
+            &["x1", "y1", "scalar"],
+            &["x", "y"],
+        );
+        self.define_precompile(
+            ctx,
+            precompiles,
+            "bn256_pairing",
+            YulFunction::Bn256Pairing,
+            &["input"],
+            &["success"],
+        );
+        self.define_precompile(
+            ctx,
+            precompiles,
+            "blake2f",
+            YulFunction::Blake2f,
+            &["rounds", "h", "m", "t", "f"],
+            &["result"],
+        );
+    }
+
+    /// Defines a native function which forwards its parameters to the Yul function calling a
+    /// precompile, and returns its results.
+    fn define_precompile(
+        &mut self,
+        ctx: &Context,
+        module: &Option<ModuleEnv>,
+        name: &str,
+        builtin: YulFunction,
+        params: &'static [&'static str],
+        results: &'static [&'static str],
+    ) {
+        self.define(ctx, module, name, move |gen, ctx: &Context, _| {
+            let call =
+                gen.call_builtin_str(ctx, builtin, params.iter().map(|param| param.to_string()));
+            emitln!(
+                ctx.writer,
+                "({}) -> {} {{\n  {} := {}\n}}",
+                params.join(", "),
+                results.join(", "),
+                results.join(", "),
+                call
+            );
+        })
+    }
+}
+
+// ========================================================================================
+// Functions in the Address module.
+
+impl NativeFunctions {
+    fn define_address_functions(&mut self, ctx: &Context) {
+        let address = &self.find_module(ctx, "0x2", "Address");
+
+        self.define_conversion(ctx, address, "to_u256", None);
+        self.define_conversion(ctx, address, "from_u256", Some("${ADDRESS_U160}"));
+        self.define_conversion(ctx, address, "to_u128", Some("${MAX_U128}"));
+        self.define_conversion(ctx, address, "from_u128", None);
+
+        self.define(ctx, address, "to_bytes", |gen, ctx: &Context, _| {
+            let malloc = gen.call_builtin_str(
+                ctx,
+                YulFunction::MallocVector,
+                vec!["1".to_string(), "20".to_string()].into_iter(),
+            );
+            let make_ptr = gen.call_builtin_str(
+                ctx,
+                YulFunction::MakePtr,
+                vec!["false".to_string(), "offs".to_string()].into_iter(),
+            );
+            // The address is stored in the higher 20 bytes of the word
+            emitln!(
+                ctx.writer,
+                "(addr) -> bytes {{\n  \
+                 let offs := {}\n  \
+                 mstore(add(offs, ${{VECTOR_METADATA_SIZE}}), shl(96, addr))\n  \
+                 bytes := {}\n}}",
+                malloc,
+                make_ptr
+            );
+        });
+
+        self.define(ctx, address, "from_bytes", |gen, ctx: &Context, _| {
+            let offs = gen.call_builtin_str(
+                ctx,
+                YulFunction::OffsetPtr,
+                std::iter::once("bytes".to_string()),
+            );
+            let length = gen.call_builtin_str(
+                ctx,
+                YulFunction::MemoryLoadU64,
+                std::iter::once("offs".to_string()),
+            );
+            let abort = gen.call_builtin_str(ctx, YulFunction::AbortBuiltin, std::iter::empty());
+            emitln!(
+                ctx.writer,
+                "(bytes) -> addr {{\n  \
+                 let offs := {}\n  \
+                 if iszero(eq({}, 20)) {{ {} }}\n  \
+                 addr := shr(96, mload(add(offs, ${{VECTOR_METADATA_SIZE}})))\n}}",
+                offs,
+                length,
+                abort
+            );
+        });
+    }
+
+    /// Defines a native function converting a number to another type, which aborts with an
+    /// overflow if the number is larger than `max`, the largest value of the target type, if
+    /// given.
+    fn define_conversion(
+        &mut self,
+        ctx: &Context,
+        module: &Option<ModuleEnv>,
+        name: &str,
+        max: Option<&'static str>,
+    ) {
+        self.define(ctx, module, name, move |gen, ctx: &Context, _| match max {
+            Some(max) => {
+                let abort =
+                    gen.call_builtin_str(ctx, YulFunction::AbortOverflow, std::iter::empty());
+                emitln!(
+                    ctx.writer,
+                    "(x) -> r {{\n  if gt(x, {}) {{ {} }}\n  r := x\n}}",
+                    max,
+                    abort
+                );
+            }
+            None => emitln!(ctx.writer, "(x) -> r {\n  r := x\n}"),
+        })
+    }
+}
+
+// ========================================================================================
+// Functions in the Transient module.
+
+impl NativeFunctions {
+    fn define_transient_functions(&mut self, ctx: &Context) {
+        let transient = &self.find_module(ctx, "0x2", "Transient");
+
+        self.define(ctx, transient, "load", |gen, ctx: &Context, fun_id| {
+            if let Some(type_hash) = transient_type_hash(gen, ctx, fun_id) {
+                let load = gen.call_builtin_str(
+                    ctx,
+                    YulFunction::TransientLoad,
+                    vec![type_hash, "key".to_string()].into_iter(),
+                );
+                emitln!(ctx.writer, "(key) -> val {{\n  val := {}\n}}", load);
+            }
+        });
+
+        self.define(ctx, transient, "store", |gen, ctx: &Context, fun_id| {
+            if let Some(type_hash) = transient_type_hash(gen, ctx, fun_id) {
+                let store = gen.call_builtin_str(
+                    ctx,
+                    YulFunction::TransientStore,
+                    vec![type_hash, "key".to_string(), "val".to_string()].into_iter(),
+                );
+                emitln!(ctx.writer, "(key, val) {{\n  {}\n}}", store);
+            }
+        });
+    }
+}
+
+// ========================================================================================
+// Functions in the Immutable module.
+
+impl NativeFunctions {
+    fn define_immutable_functions(&mut self, ctx: &Context) {
+        let immutable = &self.find_module(ctx, "0x2", "Immutable");
+
+        self.define(ctx, immutable, "set", |gen, ctx: &Context, fun_id| {
+            if let Some((_, loc)) = immutable_struct(gen, ctx, fun_id) {
+                if gen.in_deployed_code {
+                    ctx.env.error(
+                        &gen.contract_loc,
+                        "the value of an #[immutable] struct can only be set during \
+                         contract creation",
+                    );
+                    return;
+                }
+                emitln!(ctx.writer, "(value) {{\n  mstore({}, value)\n}}", loc);
+            }
+        });
+
+        self.define(ctx, immutable, "get", |gen, ctx: &Context, fun_id| {
+            let (struct_id, loc) = match immutable_struct(gen, ctx, fun_id) {
+                Some(res) => res,
+                None => return,
+            };
+            let layout = ctx.get_struct_layout(&struct_id.instantiate(vec![]));
+            let malloc = gen.call_builtin_str(
+                ctx,
+                YulFunction::Malloc,
+                std::iter::once(layout.size.to_string()),
+            );
+            emitln!(ctx.writer, "() -> value {{");
+            ctx.writer.indent();
+            if !gen.in_deployed_code {
+                // The value is copied, as it may be mutated by the caller
+                let abort = gen.call_builtin_str(
+                    ctx,
+                    YulFunction::Abort,
+                    std::iter::once(IMMUTABLE_NOT_SET.to_string()),
+                );
+                emitln!(ctx.writer, "let src := mload({})", loc);
+                emitln!(ctx.writer, "if iszero(src) {{ {} }}", abort);
+            }
+            emitln!(ctx.writer, "value := {}", malloc);
+            let struct_env = ctx.env.get_struct(struct_id);
+            for field in struct_env.get_fields() {
+                let (offs, ty) = &layout.offsets[&field.get_offset()];
+                let field_val = if gen.in_deployed_code {
+                    format!(
+                        "loadimmutable(\"{}\")",
+                        ctx.make_immutable_name(struct_id, field.get_name())
+                    )
+                } else {
+                    gen.call_builtin_str(
+                        ctx,
+                        ctx.memory_load_builtin_fun(ty),
+                        std::iter::once(format!("add(src, {})", offs)),
+                    )
+                };
+                let store = gen.call_builtin_str(
+                    ctx,
+                    ctx.memory_store_builtin_fun(ty),
+                    vec![format!("add(value, {})", offs), field_val].into_iter(),
+                );
+                emitln!(ctx.writer, "{}", store);
+            }
+            ctx.writer.unindent();
+            emitln!(ctx.writer, "}}");
+        });
+    }
+}
+
+/// Returns the `#[immutable]` struct an Immutable native is instantiated with, together with the
+/// memory location holding its value during contract creation, or reports an error if the type
+/// is not an immutable struct.
+fn immutable_struct(
+    gen: &Generator,
+    ctx: &Context,
+    fun_id: &QualifiedInstId<FunId>,
+) -> Option<(QualifiedId<StructId>, String)> {
+    let ty = &fun_id.inst[0];
+    let res = match ty {
+        Type::Struct(mid, sid, _) => {
+            let struct_id = mid.qualified(*sid);
+            ctx.immutable_value_loc(struct_id)
+                .map(|loc| (struct_id, loc))
+        }
+        _ => None,
+    };
+    if res.is_none() {
+        ctx.env.error(
+            &gen.contract_loc,
+            &format!(
+                "type `{}` is not an #[immutable] struct",
+                ty.display(&ctx.env.get_type_display_ctx())
+            ),
+        );
+    }
+    res
+}
+
+/// Returns the type hash identifying values of the type a transient storage native is
+/// instantiated with, or reports an error if values of this type do not fit in a word.
+fn transient_type_hash(
+    gen: &mut Generator,
+    ctx: &Context,
+    fun_id: &QualifiedInstId<FunId>,
+) -> Option<String> {
+    let ty = &fun_id.inst[0];
+    if ctx.is_word_type(ty) {
+        Some(format!("0x{:x}", gen.type_hash(ctx, ty)))
+    } else {
+        ctx.env.error(
+            &gen.contract_loc,
+            &format!(
+                "type `{}` cannot be kept in transient storage, \
+                 which only holds primitive types, U256 and I256",
+                ty.display(&ctx.env.get_type_display_ctx())
+            ),
+        );
+        None
+    }
+}
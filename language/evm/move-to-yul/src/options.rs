@@ -24,6 +24,42 @@ pub struct Options {
     /// Whether to dump bytecode to a file.
     #[structopt(long = "dump-bytecode")]
     pub dump_bytecode: bool,
+    /// Whether to reuse the memory of structs which are unpacked or destroyed right after
+    /// being packed, instead of expanding memory for each allocation.
+    #[structopt(long = "scratch-allocation")]
+    pub scratch_allocation: bool,
+    /// Whether to load large constants and resource type hashes which are used in many places
+    /// through a shared function, and to copy byte string constants from data objects, if this
+    /// makes the code smaller. A report of the shared constants is written next to the output
+    /// file.
+    #[structopt(long = "dedup-constants")]
+    pub dedup_constants: bool,
+    /// Also write an interface card documenting the callable functions, in the given format
+    /// (`md` or `json`), next to the output file.
+    #[structopt(long = "interface-card", possible_values = &["md", "json"])]
+    pub interface_card: Option<String>,
+    /// Also write the mapping from the source file indices used in `@src` and `@use-src`
+    /// annotations to file names and hashes, as JSON next to the output file.
+    #[structopt(long = "emit-file-map")]
+    pub emit_file_map: bool,
+    /// Also write a report of the functions which are not generated because no entry point
+    /// calls them once the bytecode is transformed, next to the output file.
+    #[structopt(long = "pruning-report")]
+    pub pruning_report: bool,
+    /// What to emit to the output file: the generated Yul (`yul`), or the EVM assembly solc
+    /// assembles it into, annotated with the Move source of each block (`evm-asm`).
+    #[structopt(long = "emit", default_value = "yul", possible_values = &["yul", "evm-asm"])]
+    pub emit: String,
+    /// Whether to revert on calldata which is not encoded exactly as the ABI encoder would do,
+    /// i.e. with dynamic values out of order or overlapping, or with trailing data, instead of
+    /// only on calldata which is too short.
+    #[structopt(long = "strict-calldata")]
+    pub strict_calldata: bool,
+    /// Whether to compile in test mode, which includes `#[test_only]` modules and functions,
+    /// e.g. helpers shared with Move unit tests, for `#[evm_test]` functions to use. It is an
+    /// error if a contract uses such code.
+    #[structopt(long = "testing")]
+    pub testing: bool,
     /// Sources to compile (positional arg)
     pub sources: Vec<String>,
 }
@@ -36,6 +72,14 @@ impl Default for Options {
             output: "output.yul".to_string(),
             solc_exe: read_env_var("SOLC_EXE"),
             dump_bytecode: false,
+            scratch_allocation: false,
+            dedup_constants: false,
+            interface_card: None,
+            emit_file_map: false,
+            pruning_report: false,
+            emit: "yul".to_string(),
+            strict_calldata: false,
+            testing: false,
             sources: vec![],
         }
     }
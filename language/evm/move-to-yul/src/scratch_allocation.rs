@@ -0,0 +1,137 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Finds short-lived struct allocations whose memory can be reused right away.
+//!
+//! Memory is allocated arena style and never recovered, so a function packing and unpacking
+//! many temporary structs keeps expanding memory, which costs gas. If a struct is packed and
+//! then unpacked or destroyed before anything else is allocated, and the live-variable
+//! analysis shows that the struct is dead afterwards, its memory is the most recent
+//! allocation and can be handed back to the allocator as scratch space for the next one.
+
+use move_binary_format::file_format::CodeOffset;
+use move_model::{ast::TempIndex, model::FunctionEnv};
+use move_stackless_bytecode::{
+    function_target::FunctionData,
+    function_target_pipeline::{FunctionTargetProcessor, FunctionTargetsHolder},
+    livevar_analysis::LiveVarAnnotation,
+    stackless_bytecode::{AttrId, Bytecode, Operation},
+};
+use std::collections::BTreeSet;
+
+/// The unpack and destroy instructions which release the memory of the struct they consume.
+#[derive(Default, Clone)]
+pub(crate) struct ScratchAllocationAnnotation(BTreeSet<AttrId>);
+
+impl ScratchAllocationAnnotation {
+    /// Whether the instruction with the given attribute releases the memory of its struct.
+    pub fn releases_scratch(&self, attr_id: AttrId) -> bool {
+        self.0.contains(&attr_id)
+    }
+}
+
+pub struct ScratchAllocationProcessor {}
+
+impl ScratchAllocationProcessor {
+    pub fn new() -> Box<Self> {
+        Box::new(ScratchAllocationProcessor {})
+    }
+}
+
+impl FunctionTargetProcessor for ScratchAllocationProcessor {
+    fn process(
+        &self,
+        _targets: &mut FunctionTargetsHolder,
+        func_env: &FunctionEnv<'_>,
+        mut data: FunctionData,
+    ) -> FunctionData {
+        if func_env.is_native() || func_env.is_intrinsic() {
+            return data;
+        }
+        let live_vars = data
+            .annotations
+            .get::<LiveVarAnnotation>()
+            .expect("live variable annotation");
+        let mut releases = BTreeSet::new();
+        for (offset, bytecode) in data.code.iter().enumerate() {
+            if let Bytecode::Call(_, dests, Operation::Pack(..), _, _) = bytecode {
+                if let Some(attr_id) = find_release(&data.code, live_vars, offset, dests[0]) {
+                    releases.insert(attr_id);
+                }
+            }
+        }
+        data.annotations
+            .set::<ScratchAllocationAnnotation>(ScratchAllocationAnnotation(releases));
+        data
+    }
+
+    fn name(&self) -> String {
+        "scratch_allocation".to_string()
+    }
+}
+
+/// Find the instruction consuming the struct packed into `temp` at `pack_offset`, provided it
+/// is an unpack or destroy in the same block, nothing is allocated before it, and `temp` is
+/// dead after it.
+fn find_release(
+    code: &[Bytecode],
+    live_vars: &LiveVarAnnotation,
+    pack_offset: usize,
+    temp: TempIndex,
+) -> Option<AttrId> {
+    use Bytecode::*;
+    for (offset, bytecode) in code.iter().enumerate().skip(pack_offset + 1) {
+        match bytecode {
+            Call(attr_id, dests, Operation::Unpack(..) | Operation::Destroy, srcs, _)
+                if srcs[0] == temp && !dests.contains(&temp) =>
+            {
+                let is_dead = live_vars
+                    .get_live_var_info_at(offset as CodeOffset)
+                    .map(|info| !info.after.contains(&temp))
+                    .unwrap_or(false);
+                return if is_dead { Some(*attr_id) } else { None };
+            }
+            Call(_, dests, op, srcs, _)
+                if !allocates_memory(op) && !dests.contains(&temp) && !srcs.contains(&temp) => {}
+            Assign(_, dest, src, _) if *dest != temp && *src != temp => {}
+            Load(_, dest, _) if *dest != temp => {}
+            Nop(_) => {}
+            // Anything else either uses the struct in some other way, may allocate memory, or
+            // leaves the block.
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Whether the operation may allocate memory or reach code which does.
+fn allocates_memory(op: &Operation) -> bool {
+    use Operation::*;
+    !matches!(
+        op,
+        CastU8
+            | CastU64
+            | CastU128
+            | CastU256
+            | Not
+            | Add
+            | Sub
+            | Mul
+            | Div
+            | Mod
+            | BitOr
+            | BitAnd
+            | Xor
+            | Shl
+            | Shr
+            | Lt
+            | Gt
+            | Le
+            | Ge
+            | Or
+            | And
+            | Eq
+            | Neq
+            | Exists(..)
+    )
+}
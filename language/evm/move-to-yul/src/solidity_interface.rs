@@ -0,0 +1,265 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The Solidity interface of a contract, for Solidity code to call it type-safely.
+//!
+//! The interface is projected from the same signatures as the ABI: it declares each dispatched
+//! `#[callable]` function, and the `#[receive]` and `#[fallback]` functions. Move structs passed
+//! to or returned from callable functions are declared as Solidity structs of the interface.
+
+use crate::{
+    abi_json::AbiEntryKind, attributes, context::Context, generator::Generator,
+    interface_card::Mutability, options::Options,
+};
+use move_model::{
+    model::{FunctionEnv, GlobalEnv},
+    ty::{PrimitiveType, Type},
+};
+use std::{collections::BTreeMap, fmt::Write};
+
+/// The Solidity interface of a contract.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SolidityInterface {
+    /// The name of the interface, derived from the name of the Move module.
+    pub name: String,
+    /// The structs used in the signatures of the functions, in order of their names.
+    pub structs: Vec<SolidityStruct>,
+    /// The callable functions in order of their names, followed by the receive and fallback
+    /// functions.
+    pub functions: Vec<SolidityFunction>,
+}
+
+/// A struct declared in the interface.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolidityStruct {
+    pub name: String,
+    pub fields: Vec<SolidityParam>,
+}
+
+/// A function declared in the interface.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolidityFunction {
+    /// Whether this is a callable, receive or fallback function.
+    pub kind: AbiEntryKind,
+    /// The name of the function, for callable functions.
+    pub name: Option<String>,
+    pub inputs: Vec<SolidityParam>,
+    pub outputs: Vec<SolidityParam>,
+    pub mutability: Mutability,
+}
+
+/// A parameter, returned value or struct field in the interface.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolidityParam {
+    pub name: String,
+    pub ty: String,
+    /// Whether the type is an array, `bytes`, `string` or a struct, which needs a data location
+    /// in function signatures.
+    pub is_reference: bool,
+}
+
+impl SolidityInterface {
+    /// Create the Solidity interface of the contract generated from the given environment.
+    pub fn new(options: &Options, env: &GlobalEnv) -> Self {
+        let ctx = Context::new(options, env, false);
+        let gen = Generator::default();
+        let name = match ctx
+            .get_target_functions(attributes::is_contract_fun)
+            .first()
+        {
+            Some(fun) => format!("I{}", fun.module_env.get_name().display(env.symbol_pool())),
+            None => "IEmpty".to_string(),
+        };
+        let mut structs = BTreeMap::new();
+        let mut functions = vec![];
+        for fun in ctx.get_target_functions(attributes::is_callable_fun) {
+            // Functions which are not dispatched cannot be called through the interface
+            if !gen.is_suitable_for_dispatch(&ctx, &fun) {
+                continue;
+            }
+            let inputs = fun
+                .get_parameters()
+                .iter()
+                .map(|param| {
+                    let name = fun.symbol_pool().string(param.0).to_string();
+                    solidity_param(&ctx, &gen, &mut structs, name, &param.1)
+                })
+                .collect();
+            let outputs = fun
+                .get_return_types()
+                .iter()
+                .map(|ty| solidity_param(&ctx, &gen, &mut structs, String::new(), ty))
+                .collect();
+            functions.push(SolidityFunction {
+                kind: AbiEntryKind::Function,
+                name: Some(fun.symbol_pool().string(fun.get_name()).to_string()),
+                inputs,
+                outputs,
+                mutability: Mutability::of_fun(&fun),
+            });
+        }
+        functions.sort_by(|f1, f2| f1.name.cmp(&f2.name));
+        functions.extend(
+            ctx.get_target_functions(attributes::is_receive_fun)
+                .iter()
+                .map(|fun| special_function(AbiEntryKind::Receive, fun)),
+        );
+        functions.extend(
+            ctx.get_target_functions(attributes::is_fallback_fun)
+                .iter()
+                .map(|fun| special_function(AbiEntryKind::Fallback, fun)),
+        );
+        Self {
+            name,
+            structs: structs.into_values().collect(),
+            functions,
+        }
+    }
+
+    /// Render the interface as Solidity source.
+    pub fn to_solidity(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "// SPDX-License-Identifier: UNLICENSED").unwrap();
+        writeln!(out, "pragma solidity ^0.8.0;").unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "interface {} {{", self.name).unwrap();
+        for st in &self.structs {
+            writeln!(out, "    struct {} {{", st.name).unwrap();
+            for field in &st.fields {
+                writeln!(out, "        {} {};", field.ty, field.name).unwrap();
+            }
+            writeln!(out, "    }}").unwrap();
+            writeln!(out).unwrap();
+        }
+        for fun in &self.functions {
+            let mutability = match fun.mutability {
+                Mutability::Pure => " pure",
+                Mutability::View => " view",
+                Mutability::NonPayable => "",
+                Mutability::Payable => " payable",
+            };
+            match fun.kind {
+                AbiEntryKind::Receive => {
+                    writeln!(out, "    receive() external{};", mutability).unwrap()
+                }
+                AbiEntryKind::Fallback => {
+                    writeln!(out, "    fallback() external{};", mutability).unwrap()
+                }
+                _ => {
+                    let returns = if fun.outputs.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" returns ({})", render_params(&fun.outputs, "memory"))
+                    };
+                    writeln!(
+                        out,
+                        "    function {}({}) external{}{};",
+                        fun.name.as_deref().unwrap_or_default(),
+                        render_params(&fun.inputs, "calldata"),
+                        mutability,
+                        returns
+                    )
+                    .unwrap()
+                }
+            }
+        }
+        writeln!(out, "}}").unwrap();
+        out
+    }
+}
+
+/// Describe a receive or fallback function, which has neither name nor parameters.
+fn special_function(kind: AbiEntryKind, fun: &FunctionEnv<'_>) -> SolidityFunction {
+    SolidityFunction {
+        kind,
+        name: None,
+        inputs: vec![],
+        outputs: vec![],
+        mutability: Mutability::of_fun(fun),
+    }
+}
+
+/// Render parameters of a function signature, with the given data location for those which
+/// need one.
+fn render_params(params: &[SolidityParam], location: &str) -> String {
+    params
+        .iter()
+        .map(|param| {
+            let mut decl = param.ty.clone();
+            if param.is_reference {
+                decl = format!("{} {}", decl, location);
+            }
+            if !param.name.is_empty() {
+                decl = format!("{} {}", decl, param.name);
+            }
+            decl
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Describe a value of the given type in the interface, collecting the structs it uses.
+fn solidity_param(
+    ctx: &Context,
+    gen: &Generator,
+    structs: &mut BTreeMap<String, SolidityStruct>,
+    name: String,
+    ty: &Type,
+) -> SolidityParam {
+    match ty {
+        // A `vector<u8>` is described as `bytes`
+        Type::Vector(elem_ty) if **elem_ty != Type::Primitive(PrimitiveType::U8) => {
+            let elem = solidity_param(ctx, gen, structs, name, elem_ty);
+            SolidityParam {
+                ty: format!("{}[]", elem.ty),
+                is_reference: true,
+                ..elem
+            }
+        }
+        Type::Struct(mid, sid, inst)
+            if !ctx.is_native_int(mid.qualified(*sid)) && !ctx.is_string(mid.qualified(*sid)) =>
+        {
+            let struct_env = ctx.env.get_struct(mid.qualified(*sid));
+            let mut struct_name = ctx
+                .env
+                .symbol_pool()
+                .string(struct_env.get_name())
+                .to_string();
+            if !inst.is_empty() {
+                // `$` is valid in Solidity identifiers
+                struct_name = format!("{}_{}", struct_name, ctx.mangle_types(inst));
+            }
+            if !structs.contains_key(&struct_name) {
+                let fields = struct_env
+                    .get_fields()
+                    .map(|field| {
+                        let field_name = ctx.env.symbol_pool().string(field.get_name());
+                        let field_ty = field.get_type().instantiate(inst);
+                        solidity_param(ctx, gen, structs, field_name.to_string(), &field_ty)
+                    })
+                    .collect();
+                structs.insert(
+                    struct_name.clone(),
+                    SolidityStruct {
+                        name: struct_name.clone(),
+                        fields,
+                    },
+                );
+            }
+            SolidityParam {
+                name,
+                ty: struct_name,
+                is_reference: true,
+            }
+        }
+        _ => SolidityParam {
+            name,
+            is_reference: match ty {
+                Type::Vector(_) => true,
+                Type::Struct(mid, sid, _) => ctx.is_string(mid.qualified(*sid)),
+                _ => false,
+            },
+            ty: gen.get_evm_type_string(ctx, ty),
+        },
+    }
+}
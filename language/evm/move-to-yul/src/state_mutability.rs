@@ -0,0 +1,240 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checking of the `#[view]` and `#[pure]` attributes of callable functions.
+//!
+//! A `#[view]` function must not modify the state, and a `#[pure]` function must neither read
+//! nor modify it. This is verified on the stackless bytecode of the function and all functions
+//! it transitively calls, so that the mutability declared in the ABI can be trusted by tools
+//! choosing between `eth_call` and a transaction.
+
+use crate::{attributes, context::Context};
+use move_model::{
+    ast::{AttributeValue, Value},
+    model::FunctionEnv,
+};
+use move_stackless_bytecode::{
+    function_target_pipeline::FunctionVariant,
+    stackless_bytecode::{Bytecode, Operation},
+};
+use num::BigUint;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::BTreeSet;
+
+/// Natives of the modules at the Eth address which read the state or the environment of the
+/// transaction, given as pairs of module and function names.
+const STATE_READING_NATIVES: &[(&str, &str)] = &[
+    ("Evm", "self"),
+    ("Evm", "sender"),
+    ("Evm", "value"),
+    ("Evm", "balance"),
+    ("Evm", "block_number"),
+    ("Evm", "timestamp"),
+    ("Evm", "chain_id"),
+    ("Evm", "gas_left"),
+    ("Evm", "staticcall"),
+    ("Transient", "load"),
+    ("Immutable", "get"),
+    ("Table", "borrow"),
+    ("Table", "contains"),
+];
+
+/// Natives of the modules at the Eth address which modify the state.
+const STATE_MODIFYING_NATIVES: &[(&str, &str)] = &[
+    ("Evm", "transfer"),
+    ("Evm", "send"),
+    ("Evm", "emit"),
+    ("Evm", "call"),
+    ("Evm", "call_or_revert"),
+    ("Evm", "call_or_abort"),
+    ("Evm", "delegatecall"),
+    ("Transient", "store"),
+    ("Table", "empty"),
+    ("Table", "add"),
+    ("Table", "borrow_mut"),
+    ("Table", "remove"),
+];
+
+/// EVM instructions which read the state or the environment of the transaction, looked for in
+/// `#[inline_yul]` code.
+const STATE_READING_INSTRUCTIONS: &[&str] = &[
+    "address",
+    "balance",
+    "selfbalance",
+    "origin",
+    "caller",
+    "callvalue",
+    "gasprice",
+    "extcodesize",
+    "extcodecopy",
+    "extcodehash",
+    "blockhash",
+    "coinbase",
+    "timestamp",
+    "number",
+    "difficulty",
+    "prevrandao",
+    "gaslimit",
+    "chainid",
+    "basefee",
+    "gas",
+    "sload",
+    "tload",
+    "staticcall",
+];
+
+/// EVM instructions which modify the state, looked for in `#[inline_yul]` code.
+const STATE_MODIFYING_INSTRUCTIONS: &[&str] = &[
+    "sstore",
+    "tstore",
+    "log0",
+    "log1",
+    "log2",
+    "log3",
+    "log4",
+    "create",
+    "create2",
+    "call",
+    "callcode",
+    "delegatecall",
+    "selfdestruct",
+];
+
+/// Check that a function with a `#[view]` or `#[pure]` attribute respects it; report errors
+/// otherwise.
+pub(crate) fn check_state_mutability(ctx: &Context, fun: &FunctionEnv<'_>) {
+    let is_view = attributes::is_view_fun(fun);
+    let is_pure = attributes::is_pure_fun(fun);
+    if !is_view && !is_pure {
+        return;
+    }
+    let attr = if is_pure { "#[pure]" } else { "#[view]" };
+    if is_view && is_pure {
+        ctx.env.error(
+            &fun.get_loc(),
+            "function cannot be both #[view] and #[pure]",
+        );
+        return;
+    }
+    if !attributes::is_callable_fun(fun) {
+        ctx.env.error(
+            &fun.get_loc(),
+            &format!("only #[callable] functions can be {}", attr),
+        );
+        return;
+    }
+    if attributes::is_payable_fun(fun) {
+        ctx.env.error(
+            &fun.get_loc(),
+            &format!("{} function cannot be #[payable]", attr),
+        );
+        return;
+    }
+    if let Some((effect, culprit)) = find_state_access(ctx, fun, is_pure) {
+        let culprit = if culprit == fun.get_full_name_str() {
+            String::new()
+        } else {
+            format!(" in `{}`", culprit)
+        };
+        ctx.env.error(
+            &fun.get_loc(),
+            &format!("{} function {}{}", attr, effect, culprit),
+        );
+    }
+}
+
+/// Find an access to the state in the bytecode of the function or the functions it calls, which
+/// is any modification, or also any read if `reads` is set. Returns a description of the access
+/// and the name of the function performing it.
+fn find_state_access(
+    ctx: &Context,
+    fun: &FunctionEnv<'_>,
+    reads: bool,
+) -> Option<(&'static str, String)> {
+    let mut todo = vec![fun.get_qualified_id()];
+    let mut done = BTreeSet::new();
+    while let Some(fun_id) = todo.pop() {
+        if !done.insert(fun_id) {
+            continue;
+        }
+        let fun_env = ctx.env.get_function(fun_id);
+        if let Some(AttributeValue::Value(_, Value::ByteArray(code))) =
+            attributes::get_inline_yul_attr(&fun_env)
+        {
+            if let Some(effect) =
+                find_inline_yul_state_access(&String::from_utf8_lossy(code), reads)
+            {
+                return Some((effect, fun_env.get_full_name_str()));
+            }
+            continue;
+        }
+        if fun_env.is_native() {
+            let module_name = fun_env.module_env.get_name();
+            if module_name.addr() == &BigUint::from(2u8) {
+                let pool = ctx.env.symbol_pool();
+                let native = (
+                    pool.string(module_name.name()).to_string(),
+                    pool.string(fun_env.get_name()).to_string(),
+                );
+                let is_one_of = |natives: &[(&str, &str)]| {
+                    natives
+                        .iter()
+                        .any(|(m, f)| native.0 == *m && native.1 == *f)
+                };
+                if is_one_of(STATE_MODIFYING_NATIVES) {
+                    return Some(("modifies the state", fun_env.get_full_name_str()));
+                }
+                if reads && is_one_of(STATE_READING_NATIVES) {
+                    return Some(("reads the state", fun_env.get_full_name_str()));
+                }
+            }
+            continue;
+        }
+        let target = ctx.targets.get_target(&fun_env, &FunctionVariant::Baseline);
+        for bc in target.get_bytecode() {
+            use Bytecode::*;
+            use Operation::*;
+            let effect = match bc {
+                Call(_, _, MoveTo(..), _, _) => Some("publishes a resource"),
+                Call(_, _, MoveFrom(..), _, _) => Some("removes a resource"),
+                Call(_, dests, BorrowGlobal(..), _, _)
+                    if target.get_local_type(dests[0]).is_mutable_reference() =>
+                {
+                    Some("mutably borrows a resource")
+                }
+                Call(_, _, BorrowGlobal(..) | GetGlobal(..) | Exists(..), _, _) if reads => {
+                    Some("reads a resource")
+                }
+                Call(_, _, Function(m, f, _), _, _) => {
+                    todo.push(m.qualified(*f));
+                    None
+                }
+                _ => None,
+            };
+            if let Some(effect) = effect {
+                return Some((effect, fun_env.get_full_name_str()));
+            }
+        }
+    }
+    None
+}
+
+/// Find an access to the state in `#[inline_yul]` code, which is any call of an instruction
+/// modifying the state, or also any call of one reading it if `reads` is set. Returns a
+/// description of the access.
+fn find_inline_yul_state_access(code: &str, reads: bool) -> Option<&'static str> {
+    static CALL: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"([a-zA-Z_$][a-zA-Z0-9_$.]*)\s*\(").unwrap());
+    let mut effect = None;
+    for cap in CALL.captures_iter(code) {
+        let name = &cap[1];
+        if STATE_MODIFYING_INSTRUCTIONS.contains(&name) {
+            return Some("modifies the state with #[inline_yul] code");
+        }
+        if reads && STATE_READING_INSTRUCTIONS.contains(&name) {
+            effect = Some("reads the state with #[inline_yul] code")
+        }
+    }
+    effect
+}
@@ -0,0 +1,377 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generation of code moving values between memory and storage.
+//!
+//! A resource is stored at its own storage base. The structs and vectors it aggregates are
+//! linked data: each is moved to a new storage base made by `NewLinkedStorageBase`, with the
+//! same layout as in memory, and the word of its parent holds a storage pointer to it. Moving a
+//! value back to memory clears the storage it used, for a refund, while copying it leaves the
+//! storage as is.
+
+use crate::{context::Context, yul_functions::YulFunction, Generator};
+use move_model::{
+    emit, emitln,
+    model::{QualifiedInstId, StructId},
+    ty::Type,
+};
+
+/// Emit code moving the struct at memory offset `src` to storage offset `dst`, moving the data
+/// it links to to new storage.
+pub(crate) fn emit_struct_to_storage(
+    gen: &mut Generator,
+    ctx: &Context,
+    struct_id: &QualifiedInstId<StructId>,
+    src: &str,
+    dst: &str,
+) {
+    let layout = ctx.get_struct_layout(struct_id);
+    // As memory is padded to word size, fields are copied word by word, which has the lowest
+    // gas cost. By invariant, the leading fields are pointer fields on word boundaries.
+    let mut byte_offs = 0;
+    for field_offs in layout.field_order.iter().take(layout.pointer_count) {
+        let (field_byte_offs, ty) = layout.offsets.get(field_offs).unwrap();
+        assert_eq!(
+            *field_byte_offs, byte_offs,
+            "pointer fields are on word boundary"
+        );
+        let mut value = format!("mload(add({}, {}))", src, byte_offs);
+        if ctx.type_allocates_memory(ty) {
+            value = format!("{}({})", generate_move_to_storage(gen, ctx, ty), value);
+        }
+        store_word(gen, ctx, format!("add({}, {})", dst, byte_offs), value);
+        byte_offs += 32
+    }
+    while byte_offs < layout.size {
+        store_word(
+            gen,
+            ctx,
+            format!("add({}, {})", dst, byte_offs),
+            format!("mload(add({}, {}))", src, byte_offs),
+        );
+        byte_offs += 32
+    }
+}
+
+/// Emit code moving the struct at storage offset `src` to memory offset `dst`, moving the data
+/// it links to to new memory, and clearing the storage.
+pub(crate) fn emit_struct_to_memory(
+    gen: &mut Generator,
+    ctx: &Context,
+    struct_id: &QualifiedInstId<StructId>,
+    src: &str,
+    dst: &str,
+) {
+    let layout = ctx.get_struct_layout(struct_id);
+    let mut byte_offs = 0;
+    while byte_offs < layout.size {
+        let field_src = format!("add({}, {})", src, byte_offs);
+        let mut value = load_word(gen, ctx, field_src.clone());
+        if let Some((_, ty)) = layout
+            .field_order
+            .iter()
+            .take(layout.pointer_count)
+            .map(|field_offs| layout.offsets.get(field_offs).unwrap())
+            .find(|(field_byte_offs, _)| *field_byte_offs == byte_offs)
+        {
+            if ctx.type_allocates_memory(ty) {
+                value = format!("{}({})", generate_move_from_storage(gen, ctx, ty), value);
+            }
+        }
+        emitln!(ctx.writer, "mstore(add({}, {}), {})", dst, byte_offs, value);
+        store_word(gen, ctx, field_src, "0".to_string());
+        byte_offs += 32
+    }
+}
+
+/// Generate the function `(src) -> dst` moving the struct or vector the memory pointer `src`
+/// points to to new linked storage, and returning a storage pointer to it.
+pub(crate) fn generate_move_to_storage(gen: &mut Generator, ctx: &Context, ty: &Type) -> String {
+    let function_name = format!("move_to_storage_{}", ctx.mangle_type(ty));
+    let ty = ty.clone(); // need to move into lambda
+
+    let generate_fun = move |gen: &mut Generator, ctx: &Context| {
+        emit!(ctx.writer, "(src) -> dst ");
+        ctx.emit_block(|| {
+            let hash = gen.type_hash(ctx, &ty);
+            let offs = gen.call_builtin_str(
+                ctx,
+                YulFunction::OffsetPtr,
+                std::iter::once("src".to_string()),
+            );
+            let base = gen.call_builtin_str(
+                ctx,
+                YulFunction::NewLinkedStorageBase,
+                std::iter::once(format!("0x{:x}", hash)),
+            );
+            emitln!(ctx.writer, "let offs := {}", offs);
+            emitln!(ctx.writer, "let base := {}", base);
+            match &ty {
+                Type::Vector(elem_ty) => {
+                    let length = gen.call_builtin_str(
+                        ctx,
+                        YulFunction::MemoryLoadU64,
+                        std::iter::once("offs".to_string()),
+                    );
+                    emitln!(ctx.writer, "let length := {}", length);
+                    store_word(gen, ctx, "base".to_string(), "mload(offs)".to_string());
+                    if ctx.type_allocates_memory(elem_ty) {
+                        let move_elem = generate_move_to_storage(gen, ctx, elem_ty);
+                        emit!(
+                            ctx.writer,
+                            "for { let i := 0 } lt(i, length) { i := add(i, 1) } "
+                        );
+                        ctx.emit_block(|| {
+                            emitln!(
+                                ctx.writer,
+                                "let elem_offs := add(${VECTOR_METADATA_SIZE}, shl(5, i))"
+                            );
+                            store_word(
+                                gen,
+                                ctx,
+                                "add(base, elem_offs)".to_string(),
+                                format!("{}(mload(add(offs, elem_offs)))", move_elem),
+                            );
+                        });
+                    } else {
+                        emit_copy_elems(ctx, elem_ty);
+                        ctx.emit_block(|| {
+                            store_word(
+                                gen,
+                                ctx,
+                                "add(base, i)".to_string(),
+                                "mload(add(offs, i))".to_string(),
+                            );
+                        });
+                    }
+                }
+                Type::Struct(mid, sid, inst) => {
+                    let struct_id = mid.qualified(*sid).instantiate(inst.clone());
+                    emit_struct_to_storage(gen, ctx, &struct_id, "offs", "base");
+                }
+                _ => panic!("unexpected linked type"),
+            }
+            emitln!(ctx.writer, "dst := {}", make_ptr(gen, ctx, "true", "base"));
+        });
+    };
+    gen.need_auxiliary_function(function_name, Box::new(generate_fun))
+}
+
+/// Generate the function `(src) -> dst` moving the struct or vector the storage pointer `src`
+/// points to to new memory, and returning a memory pointer to it.
+pub(crate) fn generate_move_from_storage(gen: &mut Generator, ctx: &Context, ty: &Type) -> String {
+    let function_name = format!("move_from_storage_{}", ctx.mangle_type(ty));
+    let ty = ty.clone(); // need to move into lambda
+
+    let generate_fun = move |gen: &mut Generator, ctx: &Context| {
+        emit!(ctx.writer, "(src) -> dst ");
+        ctx.emit_block(|| {
+            let base = gen.call_builtin_str(
+                ctx,
+                YulFunction::OffsetPtr,
+                std::iter::once("src".to_string()),
+            );
+            emitln!(ctx.writer, "let base := {}", base);
+            match &ty {
+                Type::Vector(elem_ty) => {
+                    let elem_size = ctx.type_size(elem_ty);
+                    let length = gen.call_builtin_str(
+                        ctx,
+                        YulFunction::StorageLoadU64,
+                        std::iter::once("base".to_string()),
+                    );
+                    let malloc = gen.call_builtin_str(
+                        ctx,
+                        YulFunction::MallocVector,
+                        vec![elem_size.to_string(), "length".to_string()].into_iter(),
+                    );
+                    emitln!(ctx.writer, "let length := {}", length);
+                    emitln!(ctx.writer, "let offs := {}", malloc);
+                    store_word(gen, ctx, "base".to_string(), "0".to_string());
+                    if ctx.type_allocates_memory(elem_ty) {
+                        let move_elem = generate_move_from_storage(gen, ctx, elem_ty);
+                        emit!(
+                            ctx.writer,
+                            "for { let i := 0 } lt(i, length) { i := add(i, 1) } "
+                        );
+                        ctx.emit_block(|| {
+                            emitln!(
+                                ctx.writer,
+                                "let elem_offs := add(${VECTOR_METADATA_SIZE}, shl(5, i))"
+                            );
+                            let elem = load_word(gen, ctx, "add(base, elem_offs)".to_string());
+                            emitln!(
+                                ctx.writer,
+                                "mstore(add(offs, elem_offs), {}({}))",
+                                move_elem,
+                                elem
+                            );
+                            store_word(
+                                gen,
+                                ctx,
+                                "add(base, elem_offs)".to_string(),
+                                "0".to_string(),
+                            );
+                        });
+                    } else {
+                        emit_copy_elems(ctx, elem_ty);
+                        ctx.emit_block(|| {
+                            let word = load_word(gen, ctx, "add(base, i)".to_string());
+                            emitln!(ctx.writer, "mstore(add(offs, i), {})", word);
+                            store_word(gen, ctx, "add(base, i)".to_string(), "0".to_string());
+                        });
+                    }
+                }
+                Type::Struct(mid, sid, inst) => {
+                    let struct_id = mid.qualified(*sid).instantiate(inst.clone());
+                    let layout = ctx.get_struct_layout(&struct_id);
+                    let malloc = gen.call_builtin_str(
+                        ctx,
+                        YulFunction::Malloc,
+                        std::iter::once(layout.size.to_string()),
+                    );
+                    emitln!(ctx.writer, "let offs := {}", malloc);
+                    emit_struct_to_memory(gen, ctx, &struct_id, "base", "offs");
+                }
+                _ => panic!("unexpected linked type"),
+            }
+            emitln!(ctx.writer, "dst := {}", make_ptr(gen, ctx, "false", "offs"));
+        });
+    };
+    gen.need_auxiliary_function(function_name, Box::new(generate_fun))
+}
+
+/// Generate the function `(src) -> dst` copying the struct or vector the pointer `src` points to,
+/// in memory or storage, and the data it links to, to new memory, and returning a memory pointer
+/// to the copy.
+pub(crate) fn generate_copy_to_memory(gen: &mut Generator, ctx: &Context, ty: &Type) -> String {
+    let function_name = format!("copy_to_memory_{}", ctx.mangle_type(ty));
+    let ty = ty.clone(); // need to move into lambda
+
+    let generate_fun = move |gen: &mut Generator, ctx: &Context| {
+        emit!(ctx.writer, "(src) -> dst ");
+        ctx.emit_block(|| {
+            match &ty {
+                Type::Vector(elem_ty) => {
+                    let elem_size = ctx.type_size(elem_ty);
+                    let length = gen.call_builtin_str(
+                        ctx,
+                        YulFunction::LoadU64,
+                        std::iter::once("src".to_string()),
+                    );
+                    let malloc = gen.call_builtin_str(
+                        ctx,
+                        YulFunction::MallocVector,
+                        vec![elem_size.to_string(), "length".to_string()].into_iter(),
+                    );
+                    emitln!(ctx.writer, "let length := {}", length);
+                    emitln!(ctx.writer, "let offs := {}", malloc);
+                    if ctx.type_allocates_memory(elem_ty) {
+                        let copy_elem = generate_copy_to_memory(gen, ctx, elem_ty);
+                        emit!(
+                            ctx.writer,
+                            "for { let i := 0 } lt(i, length) { i := add(i, 1) } "
+                        );
+                        ctx.emit_block(|| {
+                            emitln!(
+                                ctx.writer,
+                                "let elem_offs := add(${VECTOR_METADATA_SIZE}, shl(5, i))"
+                            );
+                            let elem = load_ptr_word(gen, ctx, "elem_offs");
+                            emitln!(
+                                ctx.writer,
+                                "mstore(add(offs, elem_offs), {}({}))",
+                                copy_elem,
+                                elem
+                            );
+                        });
+                    } else {
+                        emit_copy_elems(ctx, elem_ty);
+                        ctx.emit_block(|| {
+                            let word = load_ptr_word(gen, ctx, "i");
+                            emitln!(ctx.writer, "mstore(add(offs, i), {})", word);
+                        });
+                    }
+                }
+                Type::Struct(mid, sid, inst) => {
+                    let struct_id = mid.qualified(*sid).instantiate(inst.clone());
+                    let layout = ctx.get_struct_layout(&struct_id);
+                    let malloc = gen.call_builtin_str(
+                        ctx,
+                        YulFunction::Malloc,
+                        std::iter::once(layout.size.to_string()),
+                    );
+                    emitln!(ctx.writer, "let offs := {}", malloc);
+                    let mut byte_offs = 0;
+                    while byte_offs < layout.size {
+                        let mut value = load_ptr_word(gen, ctx, &byte_offs.to_string());
+                        if let Some((_, ty)) = layout
+                            .field_order
+                            .iter()
+                            .take(layout.pointer_count)
+                            .map(|field_offs| layout.offsets.get(field_offs).unwrap())
+                            .find(|(field_byte_offs, _)| *field_byte_offs == byte_offs)
+                        {
+                            if ctx.type_allocates_memory(ty) {
+                                value =
+                                    format!("{}({})", generate_copy_to_memory(gen, ctx, ty), value);
+                            }
+                        }
+                        emitln!(ctx.writer, "mstore(add(offs, {}), {})", byte_offs, value);
+                        byte_offs += 32
+                    }
+                }
+                _ => panic!("unexpected linked type"),
+            }
+            emitln!(ctx.writer, "dst := {}", make_ptr(gen, ctx, "false", "offs"));
+        });
+    };
+    gen.need_auxiliary_function(function_name, Box::new(generate_fun))
+}
+
+/// Emit the header of a loop over the words holding the elements of a vector of `length`
+/// elements of the type, which do not link to other data. The metadata word is not included.
+fn emit_copy_elems(ctx: &Context, elem_ty: &Type) {
+    emitln!(
+        ctx.writer,
+        "let size := add(${{VECTOR_METADATA_SIZE}}, mul(length, {}))",
+        ctx.type_size(elem_ty)
+    );
+    emit!(
+        ctx.writer,
+        "for { let i := ${VECTOR_METADATA_SIZE} } lt(i, size) { i := add(i, 32) } "
+    );
+}
+
+/// Load the word at the word-aligned storage offset.
+fn load_word(gen: &mut Generator, ctx: &Context, offs: String) -> String {
+    gen.call_builtin_str(ctx, YulFunction::AlignedStorageLoad, std::iter::once(offs))
+}
+
+/// Load the word at offset from the struct or vector `src` points to, in memory or storage.
+fn load_ptr_word(gen: &mut Generator, ctx: &Context, offs: &str) -> String {
+    let ptr = gen.call_builtin_str(
+        ctx,
+        YulFunction::IndexPtr,
+        vec!["src".to_string(), offs.to_string()].into_iter(),
+    );
+    gen.call_builtin_str(ctx, YulFunction::LoadU256, std::iter::once(ptr))
+}
+
+/// Emit code storing the word to the word-aligned storage offset.
+fn store_word(gen: &mut Generator, ctx: &Context, offs: String, value: String) {
+    gen.call_builtin(
+        ctx,
+        YulFunction::AlignedStorageStore,
+        vec![offs, value].into_iter(),
+    )
+}
+
+/// Make a pointer to memory, or storage if `is_storage` is true, at offset.
+fn make_ptr(gen: &mut Generator, ctx: &Context, is_storage: &str, offset: &str) -> String {
+    gen.call_builtin_str(
+        ctx,
+        YulFunction::MakePtr,
+        vec![is_storage.to_string(), offset.to_string()].into_iter(),
+    )
+}
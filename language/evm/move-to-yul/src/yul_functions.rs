@@ -21,6 +21,10 @@ static PLACEHOLDERS: Lazy<BTreeMap<&'static str, &'static str>> = Lazy::new(|| {
         "MAX_U256" =>
         "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
         "ADDRESS_U160" => "0xffffffffffffffffffffffffffffffffffffffff",
+        "MAX_I256" =>
+        "0x7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        "MIN_I256" =>
+        "0x8000000000000000000000000000000000000000000000000000000000000000",
 
         // ---------------------------------
         // Memory
@@ -41,22 +45,30 @@ static PLACEHOLDERS: Lazy<BTreeMap<&'static str, &'static str>> = Lazy::new(|| {
         // magic if keccak -- to multiplex the 256 bit address space into multiple ones, and
         // to implement tables with 256 bit keys. The LINEAR_STORAGE_GROUP is reserved
         // for Move memory. Other groups are created as tables are dynamically allocated.
-        // STORAGE_GROUP_COUNTER_LOC contains the largest storage group allocated so far.
+        // STORAGE_GROUP_COUNTER_LOC is the storage slot containing the largest storage group
+        // allocated so far, like LINKED_STORAGE_COUNTER_LOC below.
         // A storage group identifier is 4 bytes long.
         "LINEAR_STORAGE_GROUP" => "0",
         "STORAGE_GROUP_COUNTER_LOC" => "96",
         "WORD_AND_STORAGE_GROUP_LENGTH" => "36",
 
         // Categories to distinguish different types of pointers into the LINEAR_STORAGE_GROUP.
-        // See discussion of YulFunction::MakeTypeStorageOffset.
+        // See discussion of YulFunction::MakeTypeStorageOffset. The TRANSIENT_STORAGE_CATEGORY
+        // is used for keys into transient storage, which is separate from the storage.
         "RESOURCE_STORAGE_CATEGORY" => "0",
         "LINKED_STORAGE_CATEGORY" => "1",
+        "TRANSIENT_STORAGE_CATEGORY" => "2",
+        // Storage slot holding the number of linked storage handles allocated so far. It is
+        // outside of the storage groups, whose keys are keccak256 values.
         "LINKED_STORAGE_COUNTER_LOC" => "128",
 
         // Size (in bytes) of the resource exists flag which proceeds any data in storage for
         // a resource.
         "RESOURCE_EXISTS_FLAG_SIZE" => "32",
 
+        // Size (in bytes) of the metadata which precedes the elements of a vector in memory.
+        "VECTOR_METADATA_SIZE" => "32",
+
     }
 });
 
@@ -140,16 +152,56 @@ fn make_yule_def(name: &str, body: &str) -> String {
 functions! {
 // -------------------------------------------------------------------------------------------
 // Abort
-Abort: "(code) {
-    mstore(0, code)
-    revert(24, 8) // TODO: store code as a string?
+// Reverts with the ABI encoding of `Error(string)`, with a message consisting of the `length`
+// bytes at `message` followed by the decimal representation of `code`. The 68 bytes before
+// `message` are overwritten with the selector, the offset, and the length of the message.
+AbortWithMessage: "(message, length, code) {
+    let digits := 1
+    for { let x := div(code, 10) } x { x := div(x, 10) } { digits := add(digits, 1) }
+    for { let i := digits } i { code := div(code, 10) } {
+        i := sub(i, 1)
+        mstore8(add(message, add(length, i)), add(48, mod(code, 10)))
+    }
+    length := add(length, digits)
+    // Zero the padding of the last word of the message
+    mstore(add(message, length), 0)
+    let start := sub(message, 68)
+    mstore(start, shl(224, 0x08c379a0))
+    mstore(add(start, 4), 32)
+    mstore(add(start, 36), length)
+    revert(start, add(68, and(add(length, 31), not(31))))
 }",
+Abort: "(code) {
+    let message := add(mload(${MEM_SIZE_LOC}), 68)
+    // \"abort code \"
+    mstore(message, 0x61626f727420636f646520000000000000000000000000000000000000000000)
+    $AbortWithMessage(message, 11, code)
+}" dep AbortWithMessage,
 AbortBuiltin: "() {
-    $Abort(sub(0, 1))
+    $Abort(0xffffffffffffffff)
 }" dep Abort,
 NotImplemented: "() {
     $AbortBuiltin()
 }" dep AbortBuiltin,
+// Reverts with the ABI encoding of `Panic(uint256)`, like Solidity does on failed arithmetic
+// and array operations, so that tools categorize these failures the same way.
+Panic: "(code) {
+    mstore(0, shl(224, 0x4e487b71))
+    mstore(4, code)
+    revert(0, 36)
+}",
+AbortOverflow: "() {
+    $Panic(0x11)
+}" dep Panic,
+AbortDivisionByZero: "() {
+    $Panic(0x12)
+}" dep Panic,
+AbortEmptyVector: "() {
+    $Panic(0x31)
+}" dep Panic,
+AbortOutOfBounds: "() {
+    $Panic(0x32)
+}" dep Panic,
 
 // -------------------------------------------------------------------------------------------
 // Memory
@@ -173,6 +225,114 @@ Malloc: "(size) -> offs {
 Free: "(offs, size) {
 }",
 
+// Allocates a vector of length elements of elem_size bytes in memory. A vector in memory
+// starts with its length and its capacity, as u64, padded to ${VECTOR_METADATA_SIZE} bytes,
+// followed by the elements.
+MallocVector: "(elem_size, length) -> offs {
+  offs := $Malloc(add(${VECTOR_METADATA_SIZE}, mul(elem_size, length)))
+  $MemoryStoreU64(offs, length)
+  $MemoryStoreU64(add(offs, 8), length)
+}" dep Malloc dep MemoryStoreU64,
+
+// Copies size bytes of the calldata, starting at offset, into a new `vector<u8>`, and returns
+// a pointer to it.
+CalldataBytes: "(offset, size) -> ptr {
+  let offs := $MallocVector(1, size)
+  calldatacopy(add(offs, ${VECTOR_METADATA_SIZE}), offset, size)
+  ptr := $MakePtr(false, offs)
+}" dep MallocVector dep MakePtr,
+
+// Ends execution, returning the contents of the `vector<u8>` ptr points to as raw bytes.
+ReturnBytes: "(ptr) {
+  let offs := $OffsetPtr(ptr)
+  return(add(offs, ${VECTOR_METADATA_SIZE}), $MemoryLoadU64(offs))
+}" dep OffsetPtr dep MemoryLoadU64,
+
+// Copies the data returned by the last external call into a new `vector<u8>`, and returns a
+// pointer to it.
+ReturnDataBytes: "() -> ptr {
+  let size := returndatasize()
+  let offs := $MallocVector(1, size)
+  returndatacopy(add(offs, ${VECTOR_METADATA_SIZE}), 0, size)
+  ptr := $MakePtr(false, offs)
+}" dep MallocVector dep MakePtr,
+
+// Sends amount Wei to addr, forwarding only the gas stipend the EVM grants to calls with value,
+// so that the recipient cannot reenter. Returns whether the transfer succeeded.
+SendValue: "(addr, amount) -> success {
+  success := call(mul(iszero(amount), 2300), addr, amount, 0, 0, 0, 0)
+}",
+
+// Like SendValue, but reverts with the data the recipient reverted with if the transfer fails.
+TransferValue: "(addr, amount) {
+  if iszero($SendValue(addr, amount)) {
+    returndatacopy(0, 0, returndatasize())
+    revert(0, returndatasize())
+  }
+}" dep SendValue,
+
+// Calls the contract at addr with the given gas limit and value, passing the contents of the
+// `vector<u8>` data points to as calldata. Returns whether the call succeeded and a pointer
+// to the returned data.
+CallBytes: "(addr, gas_limit, value, data) -> success, ret {
+  let offs := $OffsetPtr(data)
+  success := call(gas_limit, addr, value, add(offs, ${VECTOR_METADATA_SIZE}), $MemoryLoadU64(offs), 0, 0)
+  ret := $ReturnDataBytes()
+}" dep OffsetPtr dep MemoryLoadU64 dep ReturnDataBytes,
+
+// Reverts with the data returned by the last external call.
+RevertReturnData: "() {
+  returndatacopy(0, 0, returndatasize())
+  revert(0, returndatasize())
+}",
+
+// Like CallBytes, but reverts with the data the callee reverted with if the call fails.
+CallBytesOrRevert: "(addr, gas_limit, value, data) -> ret {
+  let success
+  success, ret := $CallBytes(addr, gas_limit, value, data)
+  if iszero(success) { $RevertReturnData() }
+}" dep CallBytes dep RevertReturnData,
+
+// Like CallBytes, but reverts with the ABI encoding of the custom error
+// `ExternalCallFailed(uint64,bytes)`, holding code and the data the callee reverted with, if the
+// call fails.
+CallBytesOrAbort: "(addr, gas_limit, value, data, code) -> ret {
+  let success
+  success, ret := $CallBytes(addr, gas_limit, value, data)
+  if iszero(success) {
+    let size := returndatasize()
+    let start := mload(${MEM_SIZE_LOC})
+    mstore(start, shl(224, 0xc94ef8a5))
+    mstore(add(start, 4), code)
+    mstore(add(start, 36), 64)
+    mstore(add(start, 68), size)
+    returndatacopy(add(start, 100), 0, size)
+    // Zero the padding of the last word of the data
+    mstore(add(start, add(100, size)), 0)
+    revert(start, add(100, and(add(size, 31), not(31))))
+  }
+}" dep CallBytes,
+
+// Like CallBytes, but the callee cannot modify the state.
+StaticCallBytes: "(addr, gas_limit, data) -> success, ret {
+  let offs := $OffsetPtr(data)
+  success := staticcall(gas_limit, addr, add(offs, ${VECTOR_METADATA_SIZE}), $MemoryLoadU64(offs), 0, 0)
+  ret := $ReturnDataBytes()
+}" dep OffsetPtr dep MemoryLoadU64 dep ReturnDataBytes,
+
+// Like CallBytes, but executes the code at addr in the context of the current contract.
+DelegateCallBytes: "(addr, gas_limit, data) -> success, ret {
+  let offs := $OffsetPtr(data)
+  success := delegatecall(gas_limit, addr, add(offs, ${VECTOR_METADATA_SIZE}), $MemoryLoadU64(offs), 0, 0)
+  ret := $ReturnDataBytes()
+}" dep OffsetPtr dep MemoryLoadU64 dep ReturnDataBytes,
+
+// Releases the memory of the struct ptr points to, so that the next allocation reuses it.
+// Only valid if this struct is the most recent allocation.
+ReleaseScratch: "(ptr) {
+    mstore(${MEM_SIZE_LOC}, $OffsetPtr(ptr))
+}" dep OffsetPtr,
+
 // Makes a pointer, using the lowest bit to indicate whether it is for storage or memory.
 MakePtr: "(is_storage, offs) -> ptr {
   ptr := or(is_storage, shl(1, offs))
@@ -191,7 +351,7 @@ OffsetPtr: "(ptr) -> offs {
 // Constructs a bit mask for a value of size bytes. E.g. if size == 1, returns 0xff.
 // Note that we expect the Yul optimizer to specialize this for constant parameters.
 MaskForSize: "(size) -> mask {
-  mask := sub(shl(shl(size, 3), 1), 1)
+  mask := sub(shl(shl(3, size), 1), 1)
 }",
 
 // Extracts size bytes from word, starting at byte index start. The most significant byte
@@ -260,7 +420,7 @@ StorageLoadBytes: "(offs, size) -> val {
   let key := $StorageKey(${LINEAR_STORAGE_GROUP}, word_offs)
   val := $ExtractBytes(sload(key), byte_offs, size)
   let overflow_bytes := $OverflowBytes(byte_offs, size)
-  if not(iszero(overflow_bytes)) {
+  if overflow_bytes {
     key := $StorageKey(${LINEAR_STORAGE_GROUP}, add(word_offs, 1))
     let extra_bytes := $ExtractBytes(sload(key), 0, overflow_bytes)
     val := or(shl(shl(3, overflow_bytes), val), extra_bytes)
@@ -279,7 +439,7 @@ StorageStoreBytes: "(offs, size, bytes) {
   default {
     // Shift the higher bytes to the right
     let used_bytes := sub(size, overflow_bytes)
-    let higher_bytes := shr(used_bytes, bytes)
+    let higher_bytes := shr(shl(3, overflow_bytes), bytes)
     let lower_bytes := and(bytes, $MaskForSize(overflow_bytes))
     sstore(key, $InjectBytes(sload(key), byte_offs, used_bytes, higher_bytes))
     key := $StorageKey(${LINEAR_STORAGE_GROUP}, add(word_offs, 1))
@@ -287,12 +447,83 @@ StorageStoreBytes: "(offs, size, bytes) {
   }
 }" dep ToWordOffs dep StorageKey dep InjectBytes dep OverflowBytes,
 
+// Loads size bytes from the memory or storage ptr points to.
+LoadBytes: "(ptr, size) -> val {
+  let offs := $OffsetPtr(ptr)
+  switch $IsStoragePtr(ptr)
+  case 0 {
+    val := $MemoryLoadBytes(offs, size)
+  }
+  default {
+    val := $StorageLoadBytes(offs, size)
+  }
+}" dep OffsetPtr dep IsStoragePtr dep MemoryLoadBytes dep StorageLoadBytes,
+
+// Stores size bytes to the memory or storage ptr points to.
+StoreBytes: "(ptr, size, val) {
+  let offs := $OffsetPtr(ptr)
+  switch $IsStoragePtr(ptr)
+  case 0 {
+    $MemoryStoreBytes(offs, size, val)
+  }
+  default {
+    $StorageStoreBytes(offs, size, val)
+  }
+}" dep OffsetPtr dep IsStoragePtr dep MemoryStoreBytes dep StorageStoreBytes,
+
+// Stores the size lowest bytes of val to memory offset, in little-endian order.
+MemoryStoreLittleEndian: "(offs, size, val) {
+  for { let i := 0 } lt(i, size) { i := add(i, 1) } {
+    mstore8(add(offs, i), shr(shl(3, i), val))
+  }
+}",
+
+// Loads size bytes in little-endian order from memory offset.
+MemoryLoadLittleEndian: "(offs, size) -> val {
+  for { let i := 0 } lt(i, size) { i := add(i, 1) } {
+    val := or(val, shl(shl(3, i), byte(0, mload(add(offs, i)))))
+  }
+}",
+
+// Stores val to memory offset in ULEB128, and returns the end of the encoding.
+MemoryStoreUleb128: "(offs, val) -> end {
+  end := offs
+  for { } gt(val, 0x7f) { val := shr(7, val) } {
+    mstore8(end, or(and(val, 0x7f), 0x80))
+    end := add(end, 1)
+  }
+  mstore8(end, val)
+  end := add(end, 1)
+}",
+
+// Loads a ULEB128 value of at most 32 bits from memory offset, which must be canonically
+// encoded and end before end. Returns the value and the offset following its encoding.
+MemoryLoadUleb128: "(offs, end) -> val, next {
+  let shift := 0
+  for { } 1 { } {
+    if iszero(lt(offs, end)) { $AbortBuiltin() }
+    let b := byte(0, mload(offs))
+    offs := add(offs, 1)
+    val := or(val, shl(shift, and(b, 0x7f)))
+    if iszero(and(b, 0x80)) {
+      // Only the first byte of a canonical encoding can be zero
+      if and(iszero(b), gt(shift, 0)) { $AbortBuiltin() }
+      break
+    }
+    shift := add(shift, 7)
+    if gt(shift, 28) { $AbortBuiltin() }
+  }
+  if gt(val, 0xffffffff) { $AbortBuiltin() }
+  next := offs
+}" dep AbortBuiltin,
+
 // Make a unique key into storage, where word can have full 32 byte size, and type
 // indicates the kind of the key given as a byte. This uses keccak256 to fold
 // value and type into a unique storage key.
 StorageKey: "(group, word) -> key {
   mstore(${SCRATCH1_LOC}, word)
-  mstore(${SCRATCH2_LOC}, group)
+  // Only the 4 bytes of the group following the word are hashed
+  mstore(${SCRATCH2_LOC}, shl(224, group))
   key := keccak256(${SCRATCH1_LOC}, ${WORD_AND_STORAGE_GROUP_LENGTH})
 }",
 
@@ -309,20 +540,40 @@ StorageKey: "(group, word) -> key {
 // is interpreted. RESOURCE_STORAGE_CATEGORY indicates that id is a resource
 // address. LINKED_STORAGE_CATEGORY indicates that id is a handle for data linked
 // to from some other storage (for instance, a vector aggregated by a resource).
+// TRANSIENT_STORAGE_CATEGORY indicates that id is a key chosen by the program, and the
+// result is used as is as a key into transient storage.
 // The type_hash identifies the type of the stored value. The id is any 20 byte
 // number which identifies an instance of this type (e.g. an address if this is a resource).
 MakeTypeStorageBase: "(category, type_hash, id) -> offs {
   offs := or(shl(252, category), or(shl(220, type_hash), shl(60, id)))
 }",
 
-// Make a new base storage offset for linked storage. This creates a new handle
-// and the calls MakeTypeStorageBase.
+// Make a new base storage offset for linked storage. This creates a new handle, unique across
+// transactions, and then calls MakeTypeStorageBase.
 NewLinkedStorageBase: "(type_hash) -> offs {
-  let handle := mload(${LINKED_STORAGE_COUNTER_LOC})
-  mstore(${LINKED_STORAGE_COUNTER_LOC}, add(handle, 1))
+  let handle := sload(${LINKED_STORAGE_COUNTER_LOC})
+  sstore(${LINKED_STORAGE_COUNTER_LOC}, add(handle, 1))
   offs := $MakeTypeStorageBase(${LINKED_STORAGE_CATEGORY}, type_hash, handle)
 }" dep MakeTypeStorageBase,
 
+// Allocates a new storage group, unique across transactions, for a table. The first group
+// allocated follows the LINEAR_STORAGE_GROUP.
+NewStorageGroup: "() -> group {
+  group := add(sload(${STORAGE_GROUP_COUNTER_LOC}), 1)
+  sstore(${STORAGE_GROUP_COUNTER_LOC}, group)
+}",
+
+// Loads the word of type type_hash identified by id from transient storage (EIP-1153), which is
+// discarded at the end of the transaction.
+TransientLoad: "(type_hash, id) -> val {
+  val := tload($MakeTypeStorageBase(${TRANSIENT_STORAGE_CATEGORY}, type_hash, id))
+}" dep MakeTypeStorageBase,
+
+// Stores the word of type type_hash identified by id to transient storage.
+TransientStore: "(type_hash, id, val) {
+  tstore($MakeTypeStorageBase(${TRANSIENT_STORAGE_CATEGORY}, type_hash, id), val)
+}" dep MakeTypeStorageBase,
+
 // Indexes pointer by offset.
 IndexPtr: "(ptr, offs) -> new_ptr {
   new_ptr := $MakePtr($IsStoragePtr(ptr), add($OffsetPtr(ptr), offs))
@@ -469,6 +720,52 @@ StorageStoreU128: "(offs, val) {
 
 // ------------
 
+// Loads u160 (an address) from pointer.
+LoadU160: "(ptr) -> val {
+  let offs := $OffsetPtr(ptr)
+  switch $IsStoragePtr(ptr)
+  case 0 {
+    val := $MemoryLoadU160(offs)
+  }
+  default {
+    val := $StorageLoadU160(offs)
+  }
+}" dep OffsetPtr dep IsStoragePtr dep MemoryLoadU160 dep StorageLoadU160,
+
+// Loads u160 from memory offset.
+MemoryLoadU160: "(offs) -> val {
+  val := $MemoryLoadBytes(offs, 20)
+}" dep MemoryLoadBytes,
+
+// Loads u160 from storage offset.
+StorageLoadU160: "(offs) -> val {
+  val := $StorageLoadBytes(offs, 20)
+}" dep StorageLoadBytes,
+
+// Stores u160 to pointer.
+StoreU160: "(ptr, val) {
+  let offs := $OffsetPtr(ptr)
+  switch $IsStoragePtr(ptr)
+  case 0 {
+    $MemoryStoreU160(offs, val)
+  }
+  default {
+    $StorageStoreU160(offs, val)
+  }
+}" dep OffsetPtr dep IsStoragePtr dep MemoryStoreU160 dep StorageStoreU160,
+
+// Stores u160 to memory offset.
+MemoryStoreU160: "(offs, val) {
+  $MemoryStoreBytes(offs, 20, val)
+}" dep MemoryStoreBytes,
+
+// Stores u160 to storage offset.
+StorageStoreU160: "(offs, val) {
+  $StorageStoreBytes(offs, 20, val)
+}" dep StorageStoreBytes,
+
+// ------------
+
 // Loads u256 from pointer.
 LoadU256: "(ptr) -> val {
   let offs := $OffsetPtr(ptr)
@@ -527,66 +824,162 @@ AlignedStorageStore: "(offs, val) {
   sstore($StorageKey(${LINEAR_STORAGE_GROUP}, word_offs), val)
 }" dep StorageKey,
 
-// Copies size bytes from memory to memory.
+// Copies size bytes from memory to memory. The bytes following the copy at dst are preserved.
 CopyMemory: "(src, dst, size) {
   let i := 0
-  for { } and(lt(i, length), gt(i, 31)) { i := add(i, 32) } {
+  for { } lt(add(i, 31), size) { i := add(i, 32) } {
     mstore(add(dst, i), mload(add(src, i)))
   }
-  if lt(i, length) {
-    let mask := sub(shl(shl(3, i), 1), 1)
-    let dst_word := and(mload(add(dst, i)), not(mask))
-    let src_word := and(mload(add(src, i)), mask)
+  if lt(i, size) {
+    // The mask selects the trailing bytes of the last word, which are not copied
+    let mask := shr(shl(3, sub(size, i)), not(0))
+    let dst_word := and(mload(add(dst, i)), mask)
+    let src_word := and(mload(add(src, i)), not(mask))
     mstore(add(dst, i), or(dst_word, src_word))
   }
 }",
 
+// -------------------------------------------------------------------------------------------
+// Vectors
+
+// Returns a pointer to the element i of the vector vec points to, aborting if i is out of
+// bounds. The vector may be in memory or storage.
+VectorElemPtr: "(vec, elem_size, i) -> ptr {
+  if iszero(lt(i, $LoadU64(vec))) { $AbortOutOfBounds() }
+  ptr := $IndexPtr(vec, add(${VECTOR_METADATA_SIZE}, mul(i, elem_size)))
+}" dep LoadU64 dep IndexPtr dep AbortOutOfBounds,
+
+// Ensures that the vector ref points to has room for one more element, and returns a pointer
+// to it. A full vector in memory is moved to a new allocation with twice its capacity, which
+// ref is updated to point to. A vector in storage is never full.
+VectorReserve: "(ref, elem_size) -> vec {
+  vec := $LoadU256(ref)
+  if iszero($IsStoragePtr(vec)) {
+    let offs := $OffsetPtr(vec)
+    let length := $MemoryLoadU64(offs)
+    let capacity := $MemoryLoadU64(add(offs, 8))
+    if eq(length, capacity) {
+      capacity := shl(1, capacity)
+      if lt(capacity, 4) { capacity := 4 }
+      let new_offs := $Malloc(add(${VECTOR_METADATA_SIZE}, mul(elem_size, capacity)))
+      $CopyMemory(offs, new_offs, add(${VECTOR_METADATA_SIZE}, mul(elem_size, length)))
+      $MemoryStoreU64(add(new_offs, 8), capacity)
+      vec := $MakePtr(false, new_offs)
+      $StoreU256(ref, vec)
+    }
+  }
+}" dep LoadU256 dep IsStoragePtr dep OffsetPtr dep MemoryLoadU64 dep Malloc dep CopyMemory dep MemoryStoreU64 dep MakePtr dep StoreU256,
+
+// Appends val to the vector ref points to.
+VectorPushBack: "(ref, elem_size, val) {
+  let vec := $VectorReserve(ref, elem_size)
+  let length := $LoadU64(vec)
+  $StoreU64(vec, add(length, 1))
+  $StoreBytes($IndexPtr(vec, add(${VECTOR_METADATA_SIZE}, mul(length, elem_size))), elem_size, val)
+}" dep VectorReserve dep LoadU64 dep StoreU64 dep StoreBytes dep IndexPtr,
+
+// Removes the last element of the vector ref points to and returns it, aborting if the vector
+// is empty. The element is cleared in storage, for a refund.
+VectorPopBack: "(ref, elem_size) -> val {
+  let vec := $LoadU256(ref)
+  let length := $LoadU64(vec)
+  if iszero(length) { $AbortEmptyVector() }
+  length := sub(length, 1)
+  let ptr := $IndexPtr(vec, add(${VECTOR_METADATA_SIZE}, mul(length, elem_size)))
+  val := $LoadBytes(ptr, elem_size)
+  if $IsStoragePtr(vec) { $StoreBytes(ptr, elem_size, 0) }
+  $StoreU64(vec, length)
+}" dep LoadU256 dep LoadU64 dep AbortEmptyVector dep IndexPtr dep LoadBytes dep IsStoragePtr dep StoreBytes dep StoreU64,
+
+// Swaps the elements i and j of the vector ref points to, aborting if one is out of bounds.
+VectorSwap: "(ref, elem_size, i, j) {
+  let vec := $LoadU256(ref)
+  let ptr_i := $VectorElemPtr(vec, elem_size, i)
+  let ptr_j := $VectorElemPtr(vec, elem_size, j)
+  let val_i := $LoadBytes(ptr_i, elem_size)
+  $StoreBytes(ptr_i, elem_size, $LoadBytes(ptr_j, elem_size))
+  $StoreBytes(ptr_j, elem_size, val_i)
+}" dep LoadU256 dep VectorElemPtr dep LoadBytes dep StoreBytes,
+// Returns true if the length bytes in memory at data are valid UTF-8. Overlong encodings,
+// surrogates, and code points above 0x10ffff are rejected.
+MemoryIsUtf8: "(data, length) -> valid {
+  valid := 1
+  let end := add(data, length)
+  for { let pos := data } lt(pos, end) { } {
+    let b := byte(0, mload(pos))
+    // The number of continuation bytes, and the range of the first one
+    let n := 0
+    let lo := 0x80
+    let hi := 0xbf
+    if gt(b, 0x7f) {
+      n := 1
+      if gt(b, 0xdf) { n := 2 }
+      if gt(b, 0xef) { n := 3 }
+      if or(lt(b, 0xc2), gt(b, 0xf4)) { valid := 0 }
+      if eq(b, 0xe0) { lo := 0xa0 }
+      if eq(b, 0xed) { hi := 0x9f }
+      if eq(b, 0xf0) { lo := 0x90 }
+      if eq(b, 0xf4) { hi := 0x8f }
+      if gt(add(pos, add(n, 1)), end) { valid := 0 }
+    }
+    if iszero(valid) { break }
+    for { let i := 1 } iszero(gt(i, n)) { i := add(i, 1) } {
+      let c := byte(0, mload(add(pos, i)))
+      if or(lt(c, lo), gt(c, hi)) { valid := 0 }
+      lo := 0x80
+      hi := 0xbf
+    }
+    if iszero(valid) { break }
+    pos := add(pos, add(n, 1))
+  }
+}",
+
 // -------------------------------------------------------------------------------------------
 // Arithmetic, Logic, and Relations
 AddU64: "(x, y) -> r {
-    if lt(sub(${MAX_U64}, x), y) { $AbortBuiltin() }
+    if lt(sub(${MAX_U64}, x), y) { $AbortOverflow() }
     r := add(x, y)
-}" dep AbortBuiltin,
+}" dep AbortOverflow,
 MulU64: "(x, y) -> r {
-    if gt(y, div(${MAX_U64}, x)) { $AbortBuiltin() }
+    if and(iszero(iszero(x)), gt(y, div(${MAX_U64}, x))) { $AbortOverflow() }
     r := mul(x, y)
-}" dep AbortBuiltin,
+}" dep AbortOverflow,
 AddU8: "(x, y) -> r {
-    if lt(sub(${MAX_U8}, x), y) { $AbortBuiltin() }
+    if lt(sub(${MAX_U8}, x), y) { $AbortOverflow() }
     r := add(x, y)
-}" dep AbortBuiltin,
+}" dep AbortOverflow,
 MulU8: "(x, y) -> r {
-    if gt(y, div(${MAX_U8}, x)) { $AbortBuiltin() }
+    if and(iszero(iszero(x)), gt(y, div(${MAX_U8}, x))) { $AbortOverflow() }
     r := mul(x, y)
-}" dep AbortBuiltin,
+}" dep AbortOverflow,
 AddU128: "(x, y) -> r {
-    if lt(sub(${MAX_U128}, x), y) { $AbortBuiltin() }
+    if lt(sub(${MAX_U128}, x), y) { $AbortOverflow() }
     r := add(x, y)
-}" dep AbortBuiltin,
+}" dep AbortOverflow,
 MulU128: "(x, y) -> r {
-    if gt(y, div(${MAX_U128}, x)) { $AbortBuiltin() }
+    if and(iszero(iszero(x)), gt(y, div(${MAX_U128}, x))) { $AbortOverflow() }
     r := mul(x, y)
-}" dep AbortBuiltin,
+}" dep AbortOverflow,
 AddU256: "(x, y) -> r {
-    if lt(sub(${MAX_U256}, x), y) { $AbortBuiltin() }
+    if lt(sub(${MAX_U256}, x), y) { $AbortOverflow() }
     r := add(x, y)
-}" dep AbortBuiltin,
+}" dep AbortOverflow,
 MulU256: "(x, y) -> r {
-    if gt(y, div(${MAX_U256}, x)) { $AbortBuiltin() }
+    if and(iszero(iszero(x)), gt(y, div(${MAX_U256}, x))) { $AbortOverflow() }
     r := mul(x, y)
-}" dep AbortBuiltin,
+}" dep AbortOverflow,
 Sub: "(x, y) -> r {
-    if lt(x, y) { $AbortBuiltin() }
+    if lt(x, y) { $AbortOverflow() }
     r := sub(x, y)
-}" dep AbortBuiltin,
+}" dep AbortOverflow,
 Div: "(x, y) -> r {
-    if eq(y, 0) { $AbortBuiltin() }
+    if eq(y, 0) { $AbortDivisionByZero() }
     r := div(x, y)
-}" dep AbortBuiltin,
+}" dep AbortDivisionByZero,
 Mod: "(x, y) -> r {
-    if eq(y, 0) { $AbortBuiltin() }
+    if eq(y, 0) { $AbortDivisionByZero() }
     r := mod(x, y)
-}" dep AbortBuiltin,
+}" dep AbortDivisionByZero,
 Shr: "(x, y) -> r {
     r := shr(y, x)
 }",
@@ -641,21 +1034,209 @@ BitXor: "(x, y) -> r {
 BitNot: "(x) -> r {
     r := not(x)
 }",
+// Signed arithmetic on I256, in two's complement. Each operation aborts on overflow.
+AddI256: "(x, y) -> r {
+    r := add(x, y)
+    // Without overflow, r < x if and only if y < 0
+    if iszero(eq(slt(r, x), slt(y, 0))) { $AbortOverflow() }
+}" dep AbortOverflow,
+SubI256: "(x, y) -> r {
+    r := sub(x, y)
+    // Without overflow, r > x if and only if y < 0
+    if iszero(eq(sgt(r, x), slt(y, 0))) { $AbortOverflow() }
+}" dep AbortOverflow,
+MulI256: "(x, y) -> r {
+    // -1 * MIN_I256 overflows to MIN_I256, which the division below does not detect
+    if and(eq(x, ${MAX_U256}), eq(y, ${MIN_I256})) { $AbortOverflow() }
+    r := mul(x, y)
+    if iszero(or(iszero(x), eq(sdiv(r, x), y))) { $AbortOverflow() }
+}" dep AbortOverflow,
+DivI256: "(x, y) -> r {
+    if eq(y, 0) { $AbortDivisionByZero() }
+    if and(eq(x, ${MIN_I256}), eq(y, ${MAX_U256})) { $AbortOverflow() }
+    r := sdiv(x, y)
+}" dep AbortDivisionByZero dep AbortOverflow,
+ModI256: "(x, y) -> r {
+    if eq(y, 0) { $AbortDivisionByZero() }
+    r := smod(x, y)
+}" dep AbortDivisionByZero,
+NegI256: "(x) -> r {
+    if eq(x, ${MIN_I256}) { $AbortOverflow() }
+    r := sub(0, x)
+}" dep AbortOverflow,
+GtI256: "(x, y) -> r {
+    r := sgt(x, y)
+}",
+LtI256: "(x, y) -> r {
+    r := slt(x, y)
+}",
+GtEqI256: "(x, y) -> r {
+    r := iszero(slt(x, y))
+}",
+LtEqI256: "(x, y) -> r {
+    r := iszero(sgt(x, y))
+}",
 CastU8: "(x) -> r {
-    if gt(x, ${MAX_U8}) { $AbortBuiltin() }
+    if gt(x, ${MAX_U8}) { $AbortOverflow() }
     r := x
-}" dep AbortBuiltin,
+}" dep AbortOverflow,
 CastU64: "(x) -> r {
-    if gt(x, ${MAX_U64}) { $AbortBuiltin() }
+    if gt(x, ${MAX_U64}) { $AbortOverflow() }
     r := x
-}" dep AbortBuiltin,
+}" dep AbortOverflow,
 CastU128: "(x) -> r {
-    if gt(x, ${MAX_U128}) { $AbortBuiltin() }
+    if gt(x, ${MAX_U128}) { $AbortOverflow() }
     r := x
-}" dep AbortBuiltin,
+}" dep AbortOverflow,
 CastU256: "(hi, lo) -> r {
-    if gt(hi, ${MAX_U128}) { $AbortBuiltin() }
-    if gt(lo, ${MAX_U128}) { $AbortBuiltin() }
+    if gt(hi, ${MAX_U128}) { $AbortOverflow() }
+    if gt(lo, ${MAX_U128}) { $AbortOverflow() }
     r := add(shl(128, hi), lo)
+}" dep AbortOverflow,
+
+// -------------------------------------------------------------------------------------------
+// Cryptography
+
+// Returns the contents of the `vector<u8>` ptr points to, which must be 32 bytes long, as a word.
+BytesToWord: "(ptr) -> word {
+  let offs := $OffsetPtr(ptr)
+  if iszero(eq($MemoryLoadU64(offs), 32)) { $AbortBuiltin() }
+  word := mload(add(offs, ${VECTOR_METADATA_SIZE}))
+}" dep OffsetPtr dep MemoryLoadU64 dep AbortBuiltin,
+
+// Returns a pointer to a new `vector<u8>` holding the 32 bytes of word.
+WordToBytes: "(word) -> ptr {
+  let offs := $MallocVector(1, 32)
+  mstore(add(offs, ${VECTOR_METADATA_SIZE}), word)
+  ptr := $MakePtr(false, offs)
+}" dep MallocVector dep MakePtr,
+
+// Returns the keccak256 hash of the contents of the `vector<u8>` ptr points to, as a new
+// `vector<u8>`.
+Keccak256Bytes: "(ptr) -> hash {
+  let offs := $OffsetPtr(ptr)
+  hash := $WordToBytes(keccak256(add(offs, ${VECTOR_METADATA_SIZE}), $MemoryLoadU64(offs)))
+}" dep OffsetPtr dep MemoryLoadU64 dep WordToBytes,
+
+// Returns the sha256 hash of the contents of the `vector<u8>` ptr points to, as a new
+// `vector<u8>`, computed by the precompile at address 0x02.
+Sha256Bytes: "(ptr) -> hash {
+  let offs := $OffsetPtr(ptr)
+  let hash_offs := $MallocVector(1, 32)
+  let success := staticcall(gas(), 0x02, add(offs, ${VECTOR_METADATA_SIZE}), $MemoryLoadU64(offs),
+                            add(hash_offs, ${VECTOR_METADATA_SIZE}), 32)
+  if or(iszero(success), iszero(eq(returndatasize(), 32))) { $AbortBuiltin() }
+  hash := $MakePtr(false, hash_offs)
+}" dep OffsetPtr dep MemoryLoadU64 dep MallocVector dep MakePtr dep AbortBuiltin,
+
+// Recovers the address which signed hash, from the signature (v, r, s), using the precompile at
+// address 0x01. Returns the zero address if the signature is invalid.
+Ecrecover: "(hash, v, r, s) -> addr {
+  let input := $Malloc(128)
+  mstore(input, $BytesToWord(hash))
+  mstore(add(input, 32), v)
+  mstore(add(input, 64), $BytesToWord(r))
+  mstore(add(input, 96), $BytesToWord(s))
+  if iszero(staticcall(gas(), 0x01, input, 128, input, 32)) { $AbortBuiltin() }
+  // The precompile returns no data for an invalid signature
+  if eq(returndatasize(), 32) { addr := mload(input) }
+  $Free(input, 128)
+}" dep Malloc dep Free dep BytesToWord dep AbortBuiltin,
+
+// -------------------------------------------------------------------------------------------
+// Precompiles
+
+// Returns the memory offset and the length of the contents of the `vector<u8>` ptr points to.
+BytesRange: "(ptr) -> start, length {
+  let offs := $OffsetPtr(ptr)
+  start := add(offs, ${VECTOR_METADATA_SIZE})
+  length := $MemoryLoadU64(offs)
+}" dep OffsetPtr dep MemoryLoadU64,
+
+// Calls the precompile at addr with size bytes of input, writing its output to memory. Aborts if the
+// precompile fails or its output is not out_size bytes long, which is also the case if there is no
+// precompile at addr.
+CallPrecompile: "(addr, input, size, output, out_size) {
+  if iszero(staticcall(gas(), addr, input, size, output, out_size)) { $AbortBuiltin() }
+  if iszero(eq(returndatasize(), out_size)) { $AbortBuiltin() }
 }" dep AbortBuiltin,
+
+// Computes pow(base, exponent) % modulus with the precompile at address 0x05, where the numbers
+// are big-endian `vector<u8>`. The result has the length of the modulus.
+ModExp: "(base, exponent, modulus) -> result {
+  let base_start, base_len := $BytesRange(base)
+  let exp_start, exp_len := $BytesRange(exponent)
+  let mod_start, mod_len := $BytesRange(modulus)
+  let size := add(96, add(base_len, add(exp_len, mod_len)))
+  let input := $Malloc(size)
+  mstore(input, base_len)
+  mstore(add(input, 32), exp_len)
+  mstore(add(input, 64), mod_len)
+  let pos := add(input, 96)
+  $CopyMemory(base_start, pos, base_len)
+  pos := add(pos, base_len)
+  $CopyMemory(exp_start, pos, exp_len)
+  pos := add(pos, exp_len)
+  $CopyMemory(mod_start, pos, mod_len)
+  let offs := $MallocVector(1, mod_len)
+  $CallPrecompile(0x05, input, size, add(offs, ${VECTOR_METADATA_SIZE}), mod_len)
+  result := $MakePtr(false, offs)
+}" dep BytesRange dep Malloc dep CopyMemory dep MallocVector dep CallPrecompile dep MakePtr,
+
+// Adds the points (x1, y1) and (x2, y2) of the alt_bn128 curve with the precompile at address 0x06.
+Bn256Add: "(x1, y1, x2, y2) -> x, y {
+  let input := $Malloc(128)
+  mstore(input, x1)
+  mstore(add(input, 32), y1)
+  mstore(add(input, 64), x2)
+  mstore(add(input, 96), y2)
+  $CallPrecompile(0x06, input, 128, input, 64)
+  x := mload(input)
+  y := mload(add(input, 32))
+  $Free(input, 128)
+}" dep Malloc dep CallPrecompile dep Free,
+
+// Multiplies the point (x1, y1) of the alt_bn128 curve by scalar with the precompile at address 0x07.
+Bn256Mul: "(x1, y1, scalar) -> x, y {
+  let input := $Malloc(96)
+  mstore(input, x1)
+  mstore(add(input, 32), y1)
+  mstore(add(input, 64), scalar)
+  $CallPrecompile(0x07, input, 96, input, 64)
+  x := mload(input)
+  y := mload(add(input, 32))
+  $Free(input, 96)
+}" dep Malloc dep CallPrecompile dep Free,
+
+// Checks the alt_bn128 pairing equation for the points in the `vector<u8>` ptr points to, with the
+// precompile at address 0x08.
+Bn256Pairing: "(ptr) -> success {
+  let start, length := $BytesRange(ptr)
+  let output := $Malloc(32)
+  $CallPrecompile(0x08, start, length, output, 32)
+  success := mload(output)
+  $Free(output, 32)
+}" dep BytesRange dep Malloc dep CallPrecompile dep Free,
+
+// Computes the compression function F of BLAKE2 with the precompile at address 0x09, from the
+// `vector<u8>` state h of 64 bytes, message block m of 128 bytes, and offset counter t of 16 bytes.
+// Returns the new state as a `vector<u8>`.
+Blake2f: "(rounds, h, m, t, f) -> result {
+  if gt(rounds, 0xffffffff) { $AbortBuiltin() }
+  let h_start, h_len := $BytesRange(h)
+  let m_start, m_len := $BytesRange(m)
+  let t_start, t_len := $BytesRange(t)
+  if or(or(iszero(eq(h_len, 64)), iszero(eq(m_len, 128))), iszero(eq(t_len, 16))) {
+    $AbortBuiltin()
+  }
+  let input := $Malloc(213)
+  mstore(input, shl(224, rounds))
+  $CopyMemory(h_start, add(input, 4), 64)
+  $CopyMemory(m_start, add(input, 68), 128)
+  $CopyMemory(t_start, add(input, 196), 16)
+  mstore8(add(input, 212), f)
+  let offs := $MallocVector(1, 64)
+  $CallPrecompile(0x09, input, 213, add(offs, ${VECTOR_METADATA_SIZE}), 64)
+  result := $MakePtr(false, offs)
+}" dep AbortBuiltin dep BytesRange dep Malloc dep CopyMemory dep MallocVector dep CallPrecompile dep MakePtr,
 }
@@ -45,42 +45,42 @@ module 0x2::U256Arith {
     }
 
     #[callable]
-    fun gt(): U256 {
+    fun gt(): bool {
         let x = u256_from_words(6, 2);
         let y = u256_from_words(5, 1);
         U256::gt(x, y)
     }
 
     #[callable]
-    fun lt(): U256 {
+    fun lt(): bool {
         let x = u256_from_words(6, 2);
         let y = u256_from_words(5, 1);
         U256::lt(x, y)
     }
 
     #[callable]
-    fun ge(): U256 {
+    fun ge(): bool {
         let x = u256_from_words(6, 2);
         let y = u256_from_words(5, 1);
         U256::ge(x, y)
     }
 
     #[callable]
-    fun le(): U256 {
+    fun le(): bool {
         let x = u256_from_words(6, 2);
         let y = u256_from_words(5, 1);
         U256::le(x, y)
     }
 
     #[callable]
-    fun eq(): U256 {
+    fun eq(): bool {
         let x = u256_from_words(6, 2);
         let y = u256_from_words(5, 1);
         U256::eq(x, y)
     }
 
     #[callable]
-    fun ne(): U256 {
+    fun ne(): bool {
         let x = u256_from_words(6, 2);
         let y = u256_from_words(5, 1);
         U256::ne(x, y)
@@ -97,4 +97,77 @@ module 0x2::U256Arith {
         let x = u256_from_words(6, 2);
         U256::shr(x, 127)
     }
+
+    #[callable]
+    fun bitand(): U256 {
+        let x = u256_from_words(6, 2);
+        let y = u256_from_words(5, 1);
+        U256::bitand(x, y)
+    }
+
+    #[callable]
+    fun bitor(): U256 {
+        let x = u256_from_words(6, 2);
+        let y = u256_from_words(5, 1);
+        U256::bitor(x, y)
+    }
+
+    #[callable]
+    fun xor(): U256 {
+        let x = u256_from_words(6, 2);
+        let y = u256_from_words(5, 1);
+        U256::xor(x, y)
+    }
+
+    #[callable]
+    fun not(): U256 {
+        U256::not(u256_from_words(6, 2))
+    }
+
+    #[callable]
+    fun to_u64(x: U256): u64 {
+        U256::to_u64(x)
+    }
+
+    #[evm_test]
+    fun test_compare() {
+        let x = u256_from_words(6, 2);
+        let y = u256_from_words(5, 1);
+        assert!(U256::gt(x, y), 100);
+        assert!(!U256::lt(x, y), 101);
+        assert!(U256::ge(x, x), 102);
+        assert!(U256::ne(x, y), 103);
+    }
+
+    #[evm_test]
+    fun test_bit_operations() {
+        let x = U256::from_u8(6);
+        let y = U256::from_u8(3);
+        assert!(U256::to_u8(U256::bitand(x, y)) == 2, 100);
+        assert!(U256::to_u8(U256::bitor(x, y)) == 7, 101);
+        assert!(U256::to_u8(U256::xor(x, y)) == 5, 102);
+        assert!(U256::eq(U256::not(U256::not(x)), x), 103);
+    }
+
+    #[evm_test]
+    fun test_conversions() {
+        assert!(U256::to_u64(U256::from_u64(18446744073709551615)) == 18446744073709551615, 100);
+        assert!(U256::to_u128(U256::from_u128(7)) == 7, 101);
+    }
+
+    #[evm_test]
+    fun test_to_u64_overflow() {
+        to_u64(u256_from_words(1, 0));
+    }
+
+    #[evm_test]
+    fun test_mul_zero() {
+        let x = u256_from_words(6, 2);
+        assert!(U256::eq(U256::mul(U256::from_u8(0), x), U256::from_u8(0)), 100);
+    }
+
+    #[evm_test]
+    fun test_sub_underflow() {
+        U256::sub(U256::from_u8(1), U256::from_u8(2));
+    }
 }
@@ -0,0 +1,50 @@
+#[contract]
+module 0x2::Counter {
+    use Eth::Evm::{emit, sign};
+
+    struct Counter has key {
+        value: u64
+    }
+
+    #[event]
+    struct Incremented has drop {
+        value: u64
+    }
+
+    #[callable, pure]
+    fun double(x: u64): u64 {
+        x * 2
+    }
+
+    #[callable, view]
+    fun get(): u64 acquires Counter {
+        borrow_global<Counter>(@0x42).value
+    }
+
+    #[callable]
+    fun increment() acquires Counter {
+        let counter = borrow_global_mut<Counter>(@0x42);
+        counter.value = counter.value + 1;
+        emit(Incremented { value: counter.value })
+    }
+
+    #[callable, view]
+    fun publish() {
+        move_to(&sign(@0x42), Counter { value: 0 })
+    }
+
+    #[callable, view]
+    fun get_and_increment(): u64 acquires Counter {
+        increment();
+        get()
+    }
+
+    #[callable, pure]
+    fun is_published(): bool {
+        exists<Counter>(@0x42)
+    }
+
+    #[callable, view, payable]
+    fun pay() {
+    }
+}
@@ -0,0 +1,34 @@
+#[contract]
+module 0x2::Registry {
+    use Eth::Table::{Self, Table};
+
+    struct Registry has key {
+        balances: Table<u64, u64>,
+    }
+
+    #[callable, view]
+    fun has_balance(owner: u64): bool acquires Registry {
+        Table::contains(&borrow_global<Registry>(@0x42).balances, owner)
+    }
+
+    #[callable, view]
+    fun add_balance() {
+        let balances = Table::empty<u64, u64>();
+        Table::add(&mut balances, 1, 1);
+        abort 0
+    }
+
+    #[callable, view]
+    fun update_balance() {
+        let balances = Table::empty<u64, u64>();
+        *Table::borrow_mut(&mut balances, 1) = 2;
+        abort 0
+    }
+
+    #[callable, pure]
+    fun contains_balance(): bool {
+        let balances = Table::empty<u64, u64>();
+        if (Table::contains(&balances, 1)) abort 0;
+        abort 1
+    }
+}
@@ -0,0 +1,43 @@
+#[contract]
+module 0x2::Token {
+    use Eth::Evm::sender;
+    use 0x1::U256::U256;
+
+    struct Balance has key {
+        value: u128
+    }
+
+    struct Point has drop {
+        x: u64,
+        y: u64,
+    }
+
+    #[create]
+    fun create(supply: u128) {
+        supply;
+    }
+
+    #[callable]
+    fun balance_of(owner: address): u128 acquires Balance {
+        borrow_global<Balance>(owner).value
+    }
+
+    #[callable, payable]
+    fun mint(amount: U256, to: address): bool {
+        amount;
+        to == sender()
+    }
+
+    #[callable]
+    fun origin(): Point {
+        Point { x: 0, y: 0 }
+    }
+
+    #[receive, payable]
+    fun receive() {
+    }
+
+    #[fallback]
+    fun fallback() {
+    }
+}
@@ -0,0 +1,185 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use move_compiler::shared::NumericalAddress;
+use move_model::{model::GlobalEnv, options::ModelBuilderOptions, run_model_builder_with_options};
+use move_to_yul::{
+    abi_json::{AbiEntry, AbiEntryKind, AbiParam, ContractAbi},
+    generator::Generator,
+    interface_card::Mutability,
+    options::Options,
+};
+use std::path::PathBuf;
+
+fn path_from_crate_root(path: &str) -> String {
+    let mut buf = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    buf.push(path);
+    buf.to_string_lossy().to_string()
+}
+
+fn build_env(file_name: &str) -> Result<GlobalEnv> {
+    let mut named_address_mapping = move_stdlib::move_stdlib_named_addresses();
+    named_address_mapping.insert(
+        "Eth".to_string(),
+        NumericalAddress::parse_str("0x2").unwrap(),
+    );
+    let env = run_model_builder_with_options(
+        &[path_from_crate_root(&format!(
+            "tests/abi-json/{}",
+            file_name
+        ))],
+        &[
+            path_from_crate_root("../stdlib/sources"),
+            path_from_crate_root("../../move-stdlib/sources"),
+        ],
+        ModelBuilderOptions::default(),
+        named_address_mapping,
+    )?;
+    assert!(!env.has_errors());
+    Ok(env)
+}
+
+fn contract_abi(file_name: &str) -> Result<ContractAbi> {
+    let env = build_env(file_name)?;
+    Ok(ContractAbi::new(&Options::default(), &env))
+}
+
+fn param(name: &str, ty: &str) -> AbiParam {
+    AbiParam {
+        name: name.to_string(),
+        ty: ty.to_string(),
+        components: vec![],
+    }
+}
+
+fn find(abi: &ContractAbi, kind: AbiEntryKind, name: Option<&str>) -> Option<AbiEntry> {
+    abi.entries
+        .iter()
+        .find(|entry| entry.kind == kind && entry.name.as_deref() == name)
+        .cloned()
+}
+
+#[test]
+fn test_functions() -> Result<()> {
+    let abi = contract_abi("Token.move")?;
+
+    let balance_of = find(&abi, AbiEntryKind::Function, Some("balance_of")).unwrap();
+    assert_eq!(balance_of.inputs, Some(vec![param("owner", "address")]));
+    assert_eq!(balance_of.outputs, Some(vec![param("", "uint128")]));
+    assert_eq!(balance_of.state_mutability, Mutability::NonPayable);
+
+    let mint = find(&abi, AbiEntryKind::Function, Some("mint")).unwrap();
+    assert_eq!(
+        mint.inputs,
+        Some(vec![param("amount", "uint256"), param("to", "address")])
+    );
+    assert_eq!(mint.outputs, Some(vec![param("", "bool")]));
+    assert_eq!(mint.state_mutability, Mutability::Payable);
+
+    // Returned structs are tuples of their fields
+    let origin = find(&abi, AbiEntryKind::Function, Some("origin")).unwrap();
+    assert_eq!(
+        origin.outputs,
+        Some(vec![AbiParam {
+            name: String::new(),
+            ty: "tuple".to_string(),
+            components: vec![param("x", "uint64"), param("y", "uint64")],
+        }])
+    );
+    Ok(())
+}
+
+#[test]
+fn test_special_functions() -> Result<()> {
+    let abi = contract_abi("Token.move")?;
+
+    let constructor = find(&abi, AbiEntryKind::Constructor, None).unwrap();
+    assert_eq!(constructor.inputs, Some(vec![param("supply", "uint128")]));
+    assert_eq!(constructor.outputs, None);
+
+    let receive = find(&abi, AbiEntryKind::Receive, None).unwrap();
+    assert_eq!(receive.inputs, None);
+    assert_eq!(receive.state_mutability, Mutability::Payable);
+
+    let fallback = find(&abi, AbiEntryKind::Fallback, None).unwrap();
+    assert_eq!(fallback.state_mutability, Mutability::NonPayable);
+    Ok(())
+}
+
+#[test]
+fn test_json() -> Result<()> {
+    let abi = contract_abi("Token.move")?;
+    let json: serde_json::Value = serde_json::from_str(&abi.to_json())?;
+    let entries = json.as_array().unwrap();
+    assert_eq!(entries.len(), 6);
+    let receive = entries
+        .iter()
+        .find(|entry| entry["type"] == "receive")
+        .unwrap();
+    assert_eq!(
+        receive,
+        &serde_json::json!({"type": "receive", "stateMutability": "payable"})
+    );
+    let mint = entries
+        .iter()
+        .find(|entry| entry["name"] == "mint")
+        .unwrap();
+    assert_eq!(
+        mint["inputs"][0],
+        serde_json::json!({"name": "amount", "type": "uint256"})
+    );
+
+    let parsed: ContractAbi = serde_json::from_str(&abi.to_json())?;
+    assert_eq!(parsed, abi);
+    Ok(())
+}
+
+#[test]
+fn test_state_mutability() -> Result<()> {
+    let abi = contract_abi("Mutability.move")?;
+    let mutability = |name| {
+        find(&abi, AbiEntryKind::Function, Some(name))
+            .unwrap()
+            .state_mutability
+    };
+    assert_eq!(mutability("double"), Mutability::Pure);
+    assert_eq!(mutability("get"), Mutability::View);
+    assert_eq!(mutability("increment"), Mutability::NonPayable);
+
+    let json: serde_json::Value = serde_json::from_str(&abi.to_json())?;
+    let double = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|entry| entry["name"] == "double")
+        .unwrap();
+    assert_eq!(double["stateMutability"], "pure");
+    Ok(())
+}
+
+#[test]
+fn test_state_mutability_violations() -> Result<()> {
+    let env = build_env("Mutability.move")?;
+    Generator::run(&Options::default(), &env);
+    assert!(env.has_diag("#[view] function publishes a resource"));
+    assert!(env.has_diag("#[view] function mutably borrows a resource in `Counter::increment`"));
+    assert!(env.has_diag("#[pure] function reads a resource"));
+    assert!(env.has_diag("#[view] function cannot be #[payable]"));
+    // Functions which respect their attribute are not reported
+    assert!(!env.has_diag("`Counter::get`"));
+    assert!(!env.has_diag("function modifies the state"));
+    Ok(())
+}
+
+#[test]
+fn test_table_state_mutability() -> Result<()> {
+    let env = build_env("TableMutability.move")?;
+    Generator::run(&Options::default(), &env);
+    assert!(env.has_diag("#[view] function modifies the state in `Table::add`"));
+    assert!(env.has_diag("#[view] function modifies the state in `Table::borrow_mut`"));
+    assert!(env.has_diag("#[pure] function reads the state in `Table::contains`"));
+    // Reading a table is allowed in a #[view] function
+    assert!(!env.has_diag("#[view] function reads"));
+    Ok(())
+}
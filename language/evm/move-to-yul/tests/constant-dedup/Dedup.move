@@ -0,0 +1,54 @@
+// Tests the sharing of large constants with `--dedup-constants`. The large number and the type
+// hash of `Balance` are shared, while the small number and the type hash of `Config`, which is
+// accessed once, are pushed inline. The byte strings are copied from data objects, each in the
+// code block it is used in.
+#[contract]
+module 0x2::M {
+    use Eth::Evm::sign;
+
+    const LARGE: u128 = 0x1234567890abcdef1234567890abcdef;
+
+    struct Balance has key {
+        value: u128,
+    }
+
+    struct Config has key {
+        name: vector<u8>,
+    }
+
+    #[create]
+    fun create() {
+        move_to(&sign(@0x42), Config { name: b"short" })
+    }
+
+    #[callable]
+    fun open(owner: address) {
+        if (!exists<Balance>(owner)) {
+            move_to(&sign(owner), Balance { value: LARGE })
+        }
+    }
+
+    #[callable]
+    fun deposit(owner: address, amount: u128) acquires Balance {
+        let balance = borrow_global_mut<Balance>(owner);
+        balance.value = balance.value + amount % LARGE + 1
+    }
+
+    #[callable]
+    fun close(owner: address): u128 acquires Balance {
+        let Balance { value } = move_from<Balance>(owner);
+        if (value > LARGE) value - LARGE else 0
+    }
+
+    #[callable]
+    fun description(): vector<u8> {
+        b"A contract which keeps a balance per owner, whose description spans more than a word"
+    }
+
+    #[callable]
+    fun description_length(): u64 {
+        Std::Vector::length(
+            &b"A contract which keeps a balance per owner, whose description spans more than a word"
+        )
+    }
+}
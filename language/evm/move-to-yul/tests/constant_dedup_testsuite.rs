@@ -0,0 +1,60 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use codespan_reporting::{diagnostic::Severity, term::termcolor::Buffer};
+use move_command_line_common::testing::EXP_EXT;
+use move_compiler::shared::NumericalAddress;
+use move_model::{options::ModelBuilderOptions, run_model_builder_with_options};
+use move_prover_test_utils::baseline_test::verify_or_update_baseline;
+use move_stdlib::move_stdlib_named_addresses;
+use move_to_yul::{constant_dedup::SharedConstants, generator::Generator, options::Options};
+use std::path::{Path, PathBuf};
+
+/// Compares the Yul generated with `--dedup-constants` and the report of the shared constants
+/// with the baseline. Unlike the main testsuite, this does not need solc.
+fn test_runner(path: &Path) -> datatest_stable::Result<()> {
+    let mut named_address_mapping = move_stdlib_named_addresses();
+    named_address_mapping.insert(
+        "Eth".to_string(),
+        NumericalAddress::parse_str("0x2").unwrap(),
+    );
+    let env = run_model_builder_with_options(
+        &[path.to_string_lossy().to_string()],
+        &[
+            path_from_crate_root("../stdlib/sources"),
+            path_from_crate_root("../../move-stdlib/sources"),
+        ],
+        ModelBuilderOptions::default(),
+        named_address_mapping,
+    )?;
+    let options = Options {
+        dedup_constants: true,
+        ..Options::default()
+    };
+    let (_, mut out) = Generator::run(&options, &env);
+    if !env.has_errors() {
+        out = format!(
+            "{}\n\n!! Shared constants\n\n{}",
+            out,
+            SharedConstants::new(&options, &env)
+        );
+    }
+    let mut error_writer = Buffer::no_color();
+    env.report_diag(&mut error_writer, Severity::Help);
+    let diag = String::from_utf8_lossy(&error_writer.into_inner()).to_string();
+    if !diag.is_empty() {
+        out = format!("{}\n\n!! Move-To-Yul Diagnostics:\n {}", out, diag);
+    }
+    // The main testsuite, which also covers this directory, owns the `.exp` baseline
+    let baseline_path = path.with_extension(format!("dedup.{}", EXP_EXT));
+    verify_or_update_baseline(baseline_path.as_path(), &out)?;
+    Ok(())
+}
+
+fn path_from_crate_root(path: &str) -> String {
+    let mut buf = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    buf.push(path);
+    buf.to_string_lossy().to_string()
+}
+
+datatest_stable::harness!(test_runner, "tests/constant-dedup", r".*\.move$");
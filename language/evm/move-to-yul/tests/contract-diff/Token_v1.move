@@ -0,0 +1,16 @@
+#[contract]
+module 0x2::Token {
+    struct Balance has key {
+        value: u64
+    }
+
+    #[callable]
+    fun balance_of(owner: address): u64 acquires Balance {
+        borrow_global<Balance>(owner).value
+    }
+
+    #[callable]
+    fun burn(owner: address) acquires Balance {
+        let Balance { value: _ } = move_from<Balance>(owner);
+    }
+}
@@ -0,0 +1,17 @@
+#[contract]
+module 0x2::Token {
+    struct Balance has key {
+        value: u64,
+        frozen: bool
+    }
+
+    #[callable]
+    fun balance_of(owner: address): u64 acquires Balance {
+        borrow_global<Balance>(owner).value
+    }
+
+    #[callable]
+    fun is_frozen(owner: address): bool acquires Balance {
+        borrow_global<Balance>(owner).frozen
+    }
+}
@@ -0,0 +1,142 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use move_compiler::shared::NumericalAddress;
+use move_model::{options::ModelBuilderOptions, run_model_builder_with_options};
+use move_to_yul::{
+    contract_diff::{hash_yul_functions, ChangeArea, ChangeKind, ContractDiff, ContractSummary},
+    options::Options,
+};
+use std::path::PathBuf;
+
+fn path_from_crate_root(path: &str) -> String {
+    let mut buf = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    buf.push(path);
+    buf.to_string_lossy().to_string()
+}
+
+fn summarize(file_name: &str) -> Result<ContractSummary> {
+    let mut named_address_mapping = move_stdlib::move_stdlib_named_addresses();
+    named_address_mapping.insert(
+        "Eth".to_string(),
+        NumericalAddress::parse_str("0x2").unwrap(),
+    );
+    let env = run_model_builder_with_options(
+        &[path_from_crate_root(&format!(
+            "tests/contract-diff/{}",
+            file_name
+        ))],
+        &[
+            path_from_crate_root("../stdlib/sources"),
+            path_from_crate_root("../../move-stdlib/sources"),
+        ],
+        ModelBuilderOptions::default(),
+        named_address_mapping,
+    )?;
+    assert!(!env.has_errors());
+    ContractSummary::new(&Options::default(), &env)
+}
+
+#[test]
+fn test_summary() -> Result<()> {
+    let summary = summarize("Token_v1.move")?;
+    assert_eq!(summary.name, "A2_Token");
+    let signatures: Vec<_> = summary.callables.values().cloned().collect();
+    assert_eq!(signatures.len(), 2);
+    assert!(signatures.contains(&"balance_of(address)".to_string()));
+    assert!(signatures.contains(&"burn(address)".to_string()));
+    assert_eq!(summary.storage.len(), 1);
+    assert!(summary
+        .storage
+        .values()
+        .all(|layout| layout.type_hash.is_some()));
+    // The code of the deployed object outside of functions, which includes the dispatcher.
+    assert!(summary.functions.contains_key("0x2::Token"));
+    Ok(())
+}
+
+#[test]
+fn test_diff_same_build() -> Result<()> {
+    let diff = ContractDiff::new(&summarize("Token_v1.move")?, &summarize("Token_v1.move")?);
+    assert!(diff.changes.is_empty());
+    assert!(!diff.is_breaking());
+    Ok(())
+}
+
+#[test]
+fn test_diff_upgrade() -> Result<()> {
+    let diff = ContractDiff::new(&summarize("Token_v1.move")?, &summarize("Token_v2.move")?);
+    let find = |area: ChangeArea, kind: ChangeKind, item: &str| {
+        diff.changes
+            .iter()
+            .find(|change| change.area == area && change.kind == kind && change.item.contains(item))
+    };
+
+    let removed = find(ChangeArea::Callable, ChangeKind::Removed, "burn(address)").unwrap();
+    assert!(removed.breaking);
+    let added = find(
+        ChangeArea::Callable,
+        ChangeKind::Added,
+        "is_frozen(address)",
+    )
+    .unwrap();
+    assert!(!added.breaking);
+    let layout = find(ChangeArea::Storage, ChangeKind::Changed, "Balance").unwrap();
+    assert!(layout.breaking);
+    // The functions of the callables may be inlined by solc, but the dispatcher changes.
+    assert!(diff
+        .changes
+        .iter()
+        .any(|change| change.area == ChangeArea::Code
+            && change.kind == ChangeKind::Changed
+            && change.item == "0x2::Token"));
+    assert!(diff.is_breaking());
+
+    let mut report = vec![];
+    diff.report(&mut report)?;
+    let report = String::from_utf8(report)?;
+    assert!(report.contains("burn(address) [breaking]"));
+    assert!(report.ends_with("other change(s)\n"));
+    Ok(())
+}
+
+#[test]
+fn test_hash_yul_functions() {
+    let yul = r#"
+object "A2_M" {
+    code {
+        codecopy(0, dataoffset("A2_M_deployed"), datasize("A2_M_deployed"))
+        return(0, datasize("A2_M_deployed"))
+    }
+    object "A2_M_deployed" {
+        code {
+            /// @src 1:10:20
+            mstore(0, A2_M_one(1))
+            function A2_M_one(x) -> r { r := add(x, 1) }
+            function A2_M_two(x) -> r {
+                function nested(y) -> z { z := y }
+                r := nested(x)
+            }
+        }
+    }
+}
+"#;
+    let hashes = hash_yul_functions(yul);
+    assert_eq!(
+        hashes.keys().collect::<Vec<_>>(),
+        vec!["A2_M_deployed", "A2_M_one", "A2_M_two"]
+    );
+
+    // Locations and layout are ignored.
+    let relocated = yul
+        .replace("/// @src 1:10:20", "/// @src 1:30:40")
+        .replace("r := add(x, 1) }", "r := add(x, 1)\n            }");
+    assert_eq!(hash_yul_functions(&relocated), hashes);
+
+    // A change in a one-line function only changes the hash of that function.
+    let changed = hash_yul_functions(&yul.replace("add(x, 1)", "add(x, 2)"));
+    assert_ne!(changed["A2_M_one"], hashes["A2_M_one"]);
+    assert_eq!(changed["A2_M_two"], hashes["A2_M_two"]);
+    assert_eq!(changed["A2_M_deployed"], hashes["A2_M_deployed"]);
+}
@@ -0,0 +1,60 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use move_compiler::shared::NumericalAddress;
+use move_model::{model::GlobalEnv, options::ModelBuilderOptions, run_model_builder_with_options};
+use move_to_yul::mangling::{demangle, demangle_type_hash};
+use std::path::PathBuf;
+
+fn path_from_crate_root(path: &str) -> String {
+    let mut buf = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    buf.push(path);
+    buf.to_string_lossy().to_string()
+}
+
+fn build_env(file_name: &str) -> Result<GlobalEnv> {
+    let mut named_address_mapping = move_stdlib::move_stdlib_named_addresses();
+    named_address_mapping.insert(
+        "Eth".to_string(),
+        NumericalAddress::parse_str("0x2").unwrap(),
+    );
+    run_model_builder_with_options(
+        &[path_from_crate_root(&format!("tests/{}", file_name))],
+        &[
+            path_from_crate_root("../stdlib/sources"),
+            path_from_crate_root("../../move-stdlib/sources"),
+        ],
+        ModelBuilderOptions::default(),
+        named_address_mapping,
+    )
+}
+
+#[test]
+fn test_demangle_names() -> Result<()> {
+    let env = build_env("MoveCalls.move")?;
+    let demangle = |name: &str| demangle(&env, name);
+    assert_eq!(demangle("A2_M").as_deref(), Some("0x2::M"));
+    assert_eq!(demangle("A2_M_deployed").as_deref(), Some("0x2::M"));
+    assert_eq!(demangle("A2_M_f").as_deref(), Some("0x2::M::f"));
+    assert_eq!(demangle("A2_M_k$u64$").as_deref(), Some("0x2::M::k<u64>"));
+    assert_eq!(
+        demangle("A2_M_k$vec$u8$$").as_deref(),
+        Some("0x2::M::k<vector<u8>>")
+    );
+    assert_eq!(demangle("A2_M_unknown"), None);
+    assert_eq!(demangle("A2_M_k$u64"), None);
+    Ok(())
+}
+
+#[test]
+fn test_demangle_type_hash() -> Result<()> {
+    let env = build_env("Resources.move")?;
+    // The storage hash of `0x2::M::S`, as found in `Resources.exp`.
+    assert_eq!(
+        demangle_type_hash(&env, 0x698265eb).as_deref(),
+        Some("0x2::M::S")
+    );
+    assert_eq!(demangle_type_hash(&env, 0), None);
+    Ok(())
+}
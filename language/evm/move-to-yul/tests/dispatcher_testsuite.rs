@@ -3,10 +3,16 @@
 
 use anyhow::Result;
 use evm::{backend::MemoryVicinity, ExitReason};
-use evm_exec_utils::{compile, exec::Executor};
-use move_model::{options::ModelBuilderOptions, run_model_builder_with_options};
+use evm_exec_utils::{
+    compile,
+    exec::{derive_method_selector, Executor},
+};
+use move_compiler::shared::NumericalAddress;
+use move_model::{model::GlobalEnv, options::ModelBuilderOptions, run_model_builder_with_options};
 use move_to_yul::{generator::Generator, options::Options};
+use once_cell::sync::Lazy;
 use primitive_types::{H160, U256};
+use sha3::{Digest, Keccak256};
 use std::path::{Path, PathBuf};
 
 pub const DISPATCHER_TESTS_LOCATION: &str = "tests/test-dispatcher";
@@ -32,28 +38,82 @@ pub fn generate_testing_vincinity() -> MemoryVicinity {
     }
 }
 
-fn compile_yul_to_bytecode_bytes(filename: &str) -> Result<Vec<u8>> {
-    let env = run_model_builder_with_options(
+fn build_model(filename: &str) -> Result<GlobalEnv> {
+    let mut named_address_mapping = move_stdlib::move_stdlib_named_addresses();
+    named_address_mapping.insert(
+        "Eth".to_string(),
+        NumericalAddress::parse_str("0x2").unwrap(),
+    );
+    let crate_root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    run_model_builder_with_options(
         &[contract_path(filename).to_string_lossy().to_string()],
-        &[],
+        &[
+            crate_root
+                .join("../stdlib/sources")
+                .to_string_lossy()
+                .to_string(),
+            crate_root
+                .join("../../move-stdlib/sources")
+                .to_string_lossy()
+                .to_string(),
+        ],
         ModelBuilderOptions::default(),
-        move_stdlib::move_stdlib_named_addresses(),
-    )?;
-    let options = Options::default();
-    let (_, out) = Generator::run(&options, &env);
+        named_address_mapping,
+    )
+}
+
+fn compile_yul_to_bytecode_bytes(filename: &str) -> Result<Vec<u8>> {
+    compile_yul_to_bytecode_bytes_with_options(filename, &Options::default())
+}
+
+fn compile_yul_to_bytecode_bytes_with_options(
+    filename: &str,
+    options: &Options,
+) -> Result<Vec<u8>> {
+    let env = build_model(filename)?;
+    let (_, out) = Generator::run(options, &env);
     let (bc, _) = compile::solc_yul(&out, false)?;
     Ok(bc)
 }
 
-/// Test DispatcherBasic
-#[test]
-fn test_dispatch_basic() -> Result<()> {
-    let contract_code = compile_yul_to_bytecode_bytes("DispatcherBasic.move")?;
-    let vicinity = generate_testing_vincinity();
-    let mut exec = Executor::new(&vicinity);
+/// The vicinity of the executors deploying contracts.
+static VICINITY: Lazy<MemoryVicinity> = Lazy::new(generate_testing_vincinity);
+
+/// Compile the contract in the file with the options and deploy it to a new executor, returning
+/// the executor and the address of the contract.
+fn deploy(filename: &str, options: &Options) -> Result<(Executor<'static>, H160)> {
+    let contract_code = compile_yul_to_bytecode_bytes_with_options(filename, options)?;
+    let mut exec = Executor::new(&VICINITY);
     let contract_address = exec
         .create_contract(H160::zero(), contract_code)
         .expect("failed to create contract");
+    Ok((exec, contract_address))
+}
+
+/// Encode `bytes` as the only value of a tuple.
+fn abi_bytes(bytes: &[u8]) -> Vec<u8> {
+    let mut data = abi_words(&[0x20, bytes.len() as u64]);
+    data.extend_from_slice(bytes);
+    data.resize(data.len() + (32 - bytes.len() % 32) % 32, 0);
+    data
+}
+
+/// Encode each value as an ABI word.
+fn abi_words(words: &[u64]) -> Vec<u8> {
+    words
+        .iter()
+        .flat_map(|word| {
+            let mut buf = [0u8; 32];
+            U256::from(*word).to_big_endian(&mut buf);
+            buf
+        })
+        .collect()
+}
+
+/// Test DispatcherBasic
+#[test]
+fn test_dispatch_basic() -> Result<()> {
+    let (mut exec, contract_address) = deploy("DispatcherBasic.move", &Options::default())?;
     for i in 0..3 {
         let sig = format!("return_{}()", i);
         let (exit_reason, buffer) =
@@ -70,12 +130,7 @@ fn test_dispatch_basic() -> Result<()> {
 /// Test DispatcherRevert
 #[test]
 fn test_dispatch_revert() -> Result<()> {
-    let contract_code = compile_yul_to_bytecode_bytes("DispatcherRevert.move")?;
-    let vicinity = generate_testing_vincinity();
-    let mut exec = Executor::new(&vicinity);
-    let contract_address = exec
-        .create_contract(H160::zero(), contract_code)
-        .expect("failed to create contract");
+    let (mut exec, contract_address) = deploy("DispatcherRevert.move", &Options::default())?;
     let sig = "return_1";
     let (exit_reason, _) = exec.call_function(H160::zero(), contract_address, 0.into(), sig, &[]);
     assert!(matches!(exit_reason, ExitReason::Revert(_)));
@@ -85,14 +140,1265 @@ fn test_dispatch_revert() -> Result<()> {
 /// Test DispatcherFallback
 #[test]
 fn test_dispatch_fallback() -> Result<()> {
-    let contract_code = compile_yul_to_bytecode_bytes("DispatcherFallback.move")?;
+    let (mut exec, contract_address) = deploy("DispatcherFallback.move", &Options::default())?;
+    let sig = "return_1";
+    let (exit_reason, _) = exec.call_function(H160::zero(), contract_address, 0.into(), sig, &[]);
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    Ok(())
+}
+
+/// Test DispatcherCollision
+#[test]
+fn test_dispatch_selector_collision() -> Result<()> {
+    let env = build_model("DispatcherCollision.move")?;
+    let options = Options::default();
+    let (_, out) = Generator::run(&options, &env);
+    assert!(env.has_errors());
+    assert!(env.has_diag("function selector 0x5158e766"));
+    assert_eq!(out.matches("case 0x5158e766").count(), 1);
+    Ok(())
+}
+
+/// Test DispatcherTypeHashCollision
+#[test]
+fn test_dispatch_type_hash_collision() -> Result<()> {
+    let env = build_model("DispatcherTypeHashCollision.move")?;
+    Generator::run(&Options::default(), &env);
+    assert!(env.has_errors());
+    assert!(env.has_diag("type hash 0xa567755a of `M::R"));
+    Ok(())
+}
+
+/// Test DispatcherCodeSize
+#[test]
+fn test_dispatch_code_size() -> Result<()> {
+    let env = build_model("DispatcherCodeSize.move")?;
+    Generator::run(&Options::default(), &env);
+    assert!(!env.has_errors());
+    assert!(env.has_diag("estimated size of the deployed code of contract `A2_M`"));
+    assert!(env.has_diag("`0x2::M::data` contributes about"));
+    Ok(())
+}
+
+/// Test DispatcherUnsupportedType
+#[test]
+fn test_dispatch_unsupported_type() -> Result<()> {
+    let env = build_model("DispatcherUnsupportedType.move")?;
+    Generator::run(&Options::default(), &env);
+    assert!(env.has_errors());
+    assert!(env.has_diag("cannot be ABI encoded"));
+    Ok(())
+}
+
+/// Test DispatcherTransient
+#[test]
+fn test_dispatch_transient() -> Result<()> {
+    // The executor does not support transient storage yet, so only the generated code is checked
+    let env = build_model("DispatcherTransient.move")?;
+    let (_, out) = Generator::run(&Options::default(), &env);
+    assert!(!env.has_errors());
+    assert!(out.contains("tload("));
+    assert!(out.contains("tstore("));
+    assert!(!out.contains("sload("));
+
+    let env = build_model("DispatcherTransientError.move")?;
+    Generator::run(&Options::default(), &env);
+    assert!(env.has_diag("type `vector<u8>` cannot be kept in transient storage"));
+    assert!(env.has_diag("#[view] function modifies the state in `Transient::store`"));
+    Ok(())
+}
+
+/// Test DispatcherCreate
+#[test]
+fn test_dispatch_create() -> Result<()> {
+    let contract_code = compile_yul_to_bytecode_bytes("DispatcherCreate.move")?;
     let vicinity = generate_testing_vincinity();
     let mut exec = Executor::new(&vicinity);
+
+    // The arguments of the creator are ABI encoded after the code of the contract
+    let mut args = abi_words(&[1000, 0x60, 18, 5]);
+    let mut name = [0u8; 32];
+    name[..5].copy_from_slice(b"Token");
+    args.extend_from_slice(&name);
+    let contract_address = exec
+        .create_contract(H160::zero(), [contract_code.clone(), args].concat())
+        .expect("failed to create contract");
+    let (exit_reason, buffer) =
+        exec.call_function(H160::zero(), contract_address, 0.into(), "supply()", &[]);
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, abi_words(&[1000]));
+    let (exit_reason, buffer) =
+        exec.call_function(H160::zero(), contract_address, 0.into(), "decimals()", &[]);
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, abi_words(&[18]));
+
+    // Missing arguments make the creation fail
+    assert!(exec
+        .create_contract(H160::zero(), [contract_code, abi_words(&[1000])].concat())
+        .is_err());
+    Ok(())
+}
+
+/// Test DispatcherImmutable
+#[test]
+fn test_dispatch_immutable() -> Result<()> {
+    let env = build_model("DispatcherImmutable.move")?;
+    let (_, out) = Generator::run(&Options::default(), &env);
+    assert!(!env.has_errors());
+    assert!(out.contains("setimmutable("));
+    assert!(out.contains("loadimmutable("));
+    assert!(!out.contains("sload("));
+
+    let contract_code = compile_yul_to_bytecode_bytes("DispatcherImmutable.move")?;
+    let vicinity = generate_testing_vincinity();
+    let mut exec = Executor::new(&vicinity);
+    let contract_address = exec
+        .create_contract(
+            H160::zero(),
+            [contract_code, abi_words(&[0x42, 7, 1000])].concat(),
+        )
+        .expect("failed to create contract");
+    let mut call = |sig: &str| {
+        let (exit_reason, buffer) =
+            exec.call_function(H160::zero(), contract_address, 0.into(), sig, &[]);
+        assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+        buffer
+    };
+    assert_eq!(call("owner()"), abi_words(&[0x42]));
+    assert_eq!(call("fee()"), abi_words(&[7]));
+    assert_eq!(call("cap()"), abi_words(&[1000]));
+    assert_eq!(call("enabled()"), abi_words(&[1]));
+
+    let env = build_model("DispatcherImmutableError.move")?;
+    Generator::run(&Options::default(), &env);
+    assert!(env.has_diag("field `names` of #[immutable] struct `M::Names`"));
+    assert!(env.has_diag("must be set by a #[create] function"));
+    assert!(env.has_diag("can only be set during contract creation"));
+    assert!(env.has_diag("type `M::Counter` is not an #[immutable] struct"));
+    Ok(())
+}
+
+/// Test DispatcherInlineYul
+#[test]
+fn test_dispatch_inline_yul() -> Result<()> {
+    let (mut exec, contract_address) = deploy("DispatcherInlineYul.move", &Options::default())?;
+    let (exit_reason, buffer) = exec.call_function(
+        H160::zero(),
+        contract_address,
+        0.into(),
+        "compute(uint64,uint64)",
+        &abi_words(&[17, 5]),
+    );
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, abi_words(&[27, 3, 2]));
+    let (exit_reason, buffer) = exec.call_function(
+        H160::zero(),
+        contract_address,
+        0.into(),
+        "used_memory()",
+        &[],
+    );
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert!(U256::from_big_endian(&buffer) >= U256::from(160));
+
+    let env = build_model("DispatcherInlineYulError.move")?;
+    Generator::run(&Options::default(), &env);
+    assert!(env.has_diag("unknown placeholder `${UNKNOWN_LOC}` in #[inline_yul] code"));
+    assert!(
+        env.has_diag("#[view] function modifies the state with #[inline_yul] code in `M::store`")
+    );
+    assert!(env.has_diag("a function with #[inline_yul] code must be native"));
+    Ok(())
+}
+
+/// Test DispatcherCustomError
+#[test]
+fn test_dispatch_custom_error() -> Result<()> {
+    let (mut exec, contract_address) = deploy("DispatcherCustomError.move", &Options::default())?;
+    let mut call = |sig: &str, args: &[u8]| {
+        exec.call_function(H160::zero(), contract_address, 0.into(), sig, args)
+    };
+
+    let (exit_reason, buffer) = call("withdraw(uint64)", &abi_words(&[3]));
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, abi_words(&[7]));
+
+    // The revert data is the selector of the error followed by its ABI encoded fields
+    let (exit_reason, buffer) = call("withdraw(uint64)", &abi_words(&[20]));
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    let mut expected = derive_method_selector("InsufficientBalance(uint64,uint64)").to_vec();
+    expected.extend(abi_words(&[10, 20]));
+    assert_eq!(buffer, expected);
+
+    let (exit_reason, buffer) = call("guard(address)", &abi_words(&[0x42]));
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    let mut expected = derive_method_selector("Unauthorized(address,bytes)").to_vec();
+    expected.extend(abi_words(&[0x42, 0x40]));
+    expected.extend(&abi_bytes(b"owner only")[32..]);
+    assert_eq!(buffer, expected);
+
+    let env = build_model("DispatcherInvalidCustomError.move")?;
+    Generator::run(&Options::default(), &env);
+    assert!(env.has_diag("type `u64` cannot be a custom error"));
+    Ok(())
+}
+
+/// Test DispatcherAbortMessage
+#[test]
+fn test_dispatch_abort_message() -> Result<()> {
+    let (mut exec, contract_address) = deploy("DispatcherAbortMessage.move", &Options::default())?;
+    let mut call = |sig: &str, args: &[u8]| {
+        exec.call_function(H160::zero(), contract_address, 0.into(), sig, args)
+    };
+    let error = |message: &[u8]| {
+        let mut data = derive_method_selector("Error(string)").to_vec();
+        data.extend(abi_bytes(message));
+        data
+    };
+
+    let (exit_reason, buffer) = call("check(uint64)", &abi_words(&[1]));
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, abi_words(&[1]));
+
+    // Aborts revert with an `Error(string)` naming the aborting function and the code
+    let (exit_reason, buffer) = call("check(uint64)", &abi_words(&[0]));
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    assert_eq!(buffer, error(b"0x2::M::check aborted with code 42"));
+
+    let (exit_reason, buffer) = call("fail(uint64)", &abi_words(&[0]));
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    assert_eq!(buffer, error(b"0x2::M::fail aborted with code 0"));
+
+    let (exit_reason, buffer) = call("fail(uint64)", &abi_words(&[u64::MAX]));
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    assert_eq!(
+        buffer,
+        error(b"0x2::M::fail aborted with code 18446744073709551615")
+    );
+    Ok(())
+}
+
+/// Test DispatcherPanic
+#[test]
+fn test_dispatch_panic() -> Result<()> {
+    let (mut exec, contract_address) = deploy("DispatcherPanic.move", &Options::default())?;
+    let mut call = |sig: &str, args: &[u8]| {
+        exec.call_function(H160::zero(), contract_address, 0.into(), sig, args)
+    };
+    let panic = |code: u64| {
+        let mut data = derive_method_selector("Panic(uint256)").to_vec();
+        data.extend(abi_words(&[code]));
+        data
+    };
+
+    let (exit_reason, buffer) = call("add_max(uint64)", &abi_words(&[0]));
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, abi_words(&[u64::MAX]));
+
+    // Builtin failures revert with the same `Panic(uint256)` codes as Solidity
+    let (exit_reason, buffer) = call("add_max(uint64)", &abi_words(&[1]));
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    assert_eq!(buffer, panic(0x11));
+
+    let (exit_reason, buffer) = call("div(uint64,uint64)", &abi_words(&[1, 0]));
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    assert_eq!(buffer, panic(0x12));
+
+    let (exit_reason, buffer) = call("at(uint64)", &abi_words(&[0]));
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, abi_words(&[7]));
+
+    let (exit_reason, buffer) = call("at(uint64)", &abi_words(&[1]));
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    assert_eq!(buffer, panic(0x32));
+
+    let (exit_reason, buffer) = call("pop()", &[]);
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    assert_eq!(buffer, panic(0x31));
+    Ok(())
+}
+
+/// Test DispatcherEnv
+#[test]
+fn test_dispatch_env() -> Result<()> {
+    let contract_code = compile_yul_to_bytecode_bytes("DispatcherEnv.move")?;
+    let vicinity = MemoryVicinity {
+        block_number: 42.into(),
+        block_timestamp: 1_650_000_000.into(),
+        chain_id: 7.into(),
+        ..generate_testing_vincinity()
+    };
+    let mut exec = Executor::new(&vicinity);
     let contract_address = exec
         .create_contract(H160::zero(), contract_code)
         .expect("failed to create contract");
-    let sig = "return_1";
-    let (exit_reason, _) = exec.call_function(H160::zero(), contract_address, 0.into(), sig, &[]);
+    let caller = H160::from_low_u64_be(0x1234);
+    exec.mint(caller, 1000.into()).unwrap();
+    let mut call = |sig: &str, value: u64| {
+        let (exit_reason, buffer) =
+            exec.call_function(caller, contract_address, value.into(), sig, &[]);
+        assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+        buffer
+    };
+
+    assert_eq!(call("sender()", 0), abi_words(&[0x1234]));
+    assert_eq!(call("value()", 100), abi_words(&[100]));
+    assert_eq!(call("block()", 0), abi_words(&[42, 1_650_000_000, 7]));
+    assert_eq!(call("has_gas_left()", 0), abi_words(&[1]));
+    Ok(())
+}
+
+/// Test DispatcherFallbackBytes
+#[test]
+fn test_dispatch_fallback_bytes() -> Result<()> {
+    let (mut exec, contract_address) = deploy("DispatcherFallbackBytes.move", &Options::default())?;
+
+    // Callables can read the calldata, including their own selector
+    let (exit_reason, buffer) = exec.call_function(
+        H160::zero(),
+        contract_address,
+        0.into(),
+        "calldata_size(uint64)",
+        &abi_words(&[7]),
+    );
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, abi_words(&[36]));
+    let (exit_reason, buffer) =
+        exec.call_function(H160::zero(), contract_address, 0.into(), "selector()", &[]);
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    let mut expected = abi_words(&[0x20, 4]);
+    let mut data = [0u8; 32];
+    data[..4].copy_from_slice(&derive_method_selector("selector()"));
+    expected.extend_from_slice(&data);
+    assert_eq!(buffer, expected);
+
+    // Any other calldata reaches the fallback, whose result is returned as raw bytes
+    for data in [
+        &b"hello"[..],
+        &b"not a selector, and more than a word long"[..],
+    ] {
+        let (exit_reason, buffer) =
+            exec.call_with_data(H160::zero(), contract_address, 0.into(), data.to_vec());
+        assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+        assert_eq!(buffer, data.to_vec());
+    }
+    Ok(())
+}
+
+/// Test DispatcherAbi
+#[test]
+fn test_dispatch_abi() -> Result<()> {
+    let (mut exec, contract_address) = deploy("DispatcherAbi.move", &Options::default())?;
+    let mut call = |sig: &str, args: &[u8]| {
+        exec.call_function(H160::zero(), contract_address, 0.into(), sig, args)
+    };
+
+    let (exit_reason, buffer) = call("encode_u128(uint128)", &abi_words(&[7]));
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, abi_bytes(&abi_words(&[7])));
+
+    // A struct with a dynamic field is encoded as a dynamic tuple
+    let mut args = abi_words(&[3, 0x40]);
+    args.extend(abi_bytes(b"hi")[32..].iter());
+    let (exit_reason, buffer) = call("encode_pair(uint64,bytes)", &args);
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    let mut encoding = abi_words(&[0x20, 3, 0x40]);
+    encoding.extend(abi_bytes(b"hi")[32..].iter());
+    assert_eq!(buffer, abi_bytes(&encoding));
+
+    // Decoded values are validated
+    let (exit_reason, buffer) = call("decode_u8(bytes)", &abi_bytes(&abi_words(&[255])));
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, abi_words(&[255]));
+    let (exit_reason, _) = call("decode_u8(bytes)", &abi_bytes(&abi_words(&[256])));
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    let (exit_reason, _) = call("decode_u8(bytes)", &abi_bytes(&[1]));
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+
+    let vector = abi_words(&[0x20, 3, 1, 2, 3]);
+    let (exit_reason, buffer) = call("roundtrip(uint64[])", &vector);
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, vector);
+    Ok(())
+}
+
+/// Test DispatcherVector
+#[test]
+fn test_dispatch_vector() -> Result<()> {
+    let (mut exec, contract_address) = deploy("DispatcherVector.move", &Options::default())?;
+    let mut call = |sig: &str, args: &[u8]| {
+        exec.call_function(H160::zero(), contract_address, 0.into(), sig, args)
+    };
+    let ok = |(exit_reason, buffer): (ExitReason, Vec<u8>)| {
+        assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+        buffer
+    };
+
+    // Vectors in memory
+    assert_eq!(
+        ok(call("range(uint64)", &abi_words(&[10]))),
+        abi_words(&[0x20, 10, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9])
+    );
+    assert_eq!(
+        ok(call("reverse(uint128[])", &abi_words(&[0x20, 3, 1, 2, 3]))),
+        abi_words(&[0x20, 3, 3, 2, 1])
+    );
+    assert_eq!(
+        ok(call("pop_all(address[])", &abi_words(&[0x20, 3, 1, 2, 3]))),
+        abi_words(&[3])
+    );
+    let mut args = abi_words(&[0x40, 1]);
+    args.extend_from_slice(&abi_bytes(b"abc")[32..]);
+    assert_eq!(
+        ok(call("get(bytes,uint64)", &args)),
+        abi_words(&[b'b' as u64])
+    );
+    assert_eq!(
+        ok(call(
+            "set(uint64[],uint64,uint64)",
+            &abi_words(&[0x60, 1, 7, 3, 1, 2, 3])
+        )),
+        abi_words(&[0x20, 3, 1, 7, 3])
+    );
+    ok(call("destroy_empty(uint64[])", &abi_words(&[0x20, 0])));
+
+    // Out of bounds accesses and destroying a non-empty vector abort
+    let mut args = abi_words(&[0x40, 3]);
+    args.extend_from_slice(&abi_bytes(b"abc")[32..]);
+    let (exit_reason, _) = call("get(bytes,uint64)", &args);
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    let (exit_reason, _) = call(
+        "set(uint64[],uint64,uint64)",
+        &abi_words(&[0x60, 3, 7, 3, 1, 2, 3]),
+    );
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    let (exit_reason, _) = call("destroy_empty(uint64[])", &abi_words(&[0x20, 1, 1]));
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+
+    // Vectors in storage
+    ok(call("publish(uint64[])", &abi_words(&[0x20, 2, 1, 2])));
+    ok(call(
+        "register(uint64,address,uint64,uint64)",
+        &abi_words(&[3, 0x1234, 5, 6]),
+    ));
+    assert_eq!(ok(call("last()", &[])), abi_words(&[3, 3, 0x1234, 5, 6]));
+    ok(call(
+        "register(uint64,address,uint64,uint64)",
+        &abi_words(&[4, 0x5678, 7, 8]),
+    ));
+    assert_eq!(ok(call("last()", &[])), abi_words(&[4, 4, 0x5678, 7, 8]));
+    assert_eq!(ok(call("pop_point()", &[])), abi_words(&[7, 8]));
+    assert_eq!(ok(call("last()", &[])), abi_words(&[4, 4, 0x5678, 5, 6]));
+    assert_eq!(
+        ok(call("unpublish()", &[])),
+        abi_words(&[0x20, 4, 1, 2, 3, 4])
+    );
+    let (exit_reason, _) = call("last()", &[]);
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    Ok(())
+}
+
+/// Test DispatcherString
+#[test]
+fn test_dispatch_string() -> Result<()> {
+    let (mut exec, contract_address) = deploy("DispatcherString.move", &Options::default())?;
+    let mut call = |sig: &str, args: &[u8]| {
+        exec.call_function(H160::zero(), contract_address, 0.into(), sig, args)
+    };
+    let ok = |(exit_reason, buffer): (ExitReason, Vec<u8>)| {
+        assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+        buffer
+    };
+
+    assert_eq!(ok(call("hello()", &[])), abi_bytes(b"Hello"));
+    assert_eq!(
+        ok(call("greet(string)", &abi_bytes("wörld".as_bytes()))),
+        abi_bytes("Hello, wörld!".as_bytes())
+    );
+    assert_eq!(
+        ok(call("length(string)", &abi_bytes("wörld 🌍".as_bytes()))),
+        abi_words(&[11])
+    );
+    assert_eq!(
+        ok(call("from_bytes(bytes)", &abi_bytes("wörld".as_bytes()))),
+        abi_bytes("wörld".as_bytes())
+    );
+
+    // Invalid UTF-8 is rejected, when decoding a `string` and when making a `String`
+    let invalid_bytes: [&[u8]; 5] = [
+        &[0xff],
+        &[0xc0, 0x80],
+        &[0xe2, 0x82],
+        &[0xed, 0xa0, 0x80],
+        &[0xf4, 0x90, 0x80, 0x80],
+    ];
+    for invalid in invalid_bytes {
+        let (exit_reason, _) = call("length(string)", &abi_bytes(invalid));
+        assert!(matches!(exit_reason, ExitReason::Revert(_)));
+        let (exit_reason, _) = call("from_bytes(bytes)", &abi_bytes(invalid));
+        assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    }
+
+    // Strings persist in storage
+    let mut args = abi_words(&[0x40, 0x80]);
+    args.extend_from_slice(&abi_bytes(b"Token")[32..]);
+    args.extend_from_slice(&abi_bytes(b"TKN")[32..]);
+    ok(call("publish(string,string)", &args));
+    assert_eq!(ok(call("name()", &[])), abi_bytes(b"Token"));
+    assert_eq!(ok(call("symbol()", &[])), abi_bytes(b"TKN"));
+    ok(call("rename(string)", &abi_bytes(b"Coin")));
+    assert_eq!(ok(call("name()", &[])), abi_bytes(b"Coin"));
+    assert_eq!(ok(call("symbol()", &[])), abi_bytes(b"TKN"));
+    Ok(())
+}
+
+/// Test DispatcherTable
+#[test]
+fn test_dispatch_table() -> Result<()> {
+    let (mut exec, contract_address) = deploy("DispatcherTable.move", &Options::default())?;
+    let mut call = |sig: &str, args: &[u8]| {
+        exec.call_function(H160::zero(), contract_address, 0.into(), sig, args)
+    };
+    let ok = |(exit_reason, buffer): (ExitReason, Vec<u8>)| {
+        assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+        buffer
+    };
+
+    ok(call("publish()", &[]));
+    ok(call("deposit(address,uint64)", &abi_words(&[1, 100])));
+    ok(call("deposit(address,uint64)", &abi_words(&[2, 7])));
+    ok(call("deposit(address,uint64)", &abi_words(&[1, 20])));
+    assert_eq!(
+        ok(call("balance(address)", &abi_words(&[1]))),
+        abi_words(&[120])
+    );
+    assert_eq!(
+        ok(call("balance(address)", &abi_words(&[2]))),
+        abi_words(&[7])
+    );
+    assert_eq!(
+        ok(call("has_balance(address)", &abi_words(&[3]))),
+        abi_words(&[0])
+    );
+    let (exit_reason, _) = call("balance(address)", &abi_words(&[3]));
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    assert_eq!(
+        ok(call("withdraw_all(address)", &abi_words(&[1]))),
+        abi_words(&[120])
+    );
+    assert_eq!(
+        ok(call("has_balance(address)", &abi_words(&[1]))),
+        abi_words(&[0])
+    );
+    let (exit_reason, _) = call("withdraw_all(address)", &abi_words(&[1]));
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+
+    // Tables with keys of the same value do not share entries
+    ok(call(
+        "add_point(uint64,uint64,uint64)",
+        &abi_words(&[2, 3, 4]),
+    ));
+    let (exit_reason, _) = call("add_point(uint64,uint64,uint64)", &abi_words(&[2, 0, 0]));
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    ok(call("move_point(uint64,uint64)", &abi_words(&[2, 10])));
+    assert_eq!(
+        ok(call("point_x(uint64)", &abi_words(&[2]))),
+        abi_words(&[13])
+    );
+    assert_eq!(
+        ok(call("balance(address)", &abi_words(&[2]))),
+        abi_words(&[7])
+    );
+    assert_eq!(
+        ok(call("remove_point(uint64)", &abi_words(&[2]))),
+        abi_words(&[17])
+    );
+
+    let mut args = abi_words(&[5, 0x40]);
+    args.extend_from_slice(&abi_bytes(b"tag")[32..]);
+    ok(call("add_tag(uint64,bytes)", &args));
+    assert_eq!(ok(call("tag(uint64)", &abi_words(&[5]))), abi_bytes(b"tag"));
+    assert_eq!(
+        ok(call("remove_tag(uint64)", &abi_words(&[5]))),
+        abi_bytes(b"tag")
+    );
+    let (exit_reason, _) = call("tag(uint64)", &abi_words(&[5]));
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    Ok(())
+}
+
+/// Test DispatcherI256
+#[test]
+fn test_dispatch_i256() -> Result<()> {
+    let (mut exec, contract_address) = deploy("DispatcherI256.move", &Options::default())?;
+    let mut call = |sig: &str, args: &[u8]| {
+        exec.call_function(H160::zero(), contract_address, 0.into(), sig, args)
+    };
+    let ok = |(exit_reason, buffer): (ExitReason, Vec<u8>)| {
+        assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+        buffer
+    };
+    // Encode signed integers in two's complement
+    let int = |x: i64| -> [u8; 32] {
+        let mut buf = [if x < 0 { 0xff } else { 0 }; 32];
+        buf[24..].copy_from_slice(&x.to_be_bytes());
+        buf
+    };
+    let ints = |xs: &[i64]| xs.iter().flat_map(|x| int(*x)).collect::<Vec<_>>();
+    let mut min = [0u8; 32];
+    min[0] = 0x80;
+    let mut max = [0xffu8; 32];
+    max[0] = 0x7f;
+
+    assert_eq!(ok(call("add(int256,int256)", &ints(&[-5, 3]))), int(-2));
+    assert_eq!(ok(call("sub(int256,int256)", &ints(&[3, 5]))), int(-2));
+    assert_eq!(ok(call("mul(int256,int256)", &ints(&[-4, -6]))), int(24));
+    assert_eq!(ok(call("div(int256,int256)", &ints(&[-7, 2]))), int(-3));
+    assert_eq!(ok(call("mod(int256,int256)", &ints(&[-7, 2]))), int(-1));
+    assert_eq!(ok(call("neg(int256)", &int(5))), int(-5));
+    assert_eq!(ok(call("lt(int256,int256)", &ints(&[-1, 0]))), int(1));
+    assert_eq!(ok(call("ge(int256,int256)", &ints(&[-1, 0]))), int(0));
+    assert_eq!(ok(call("is_neg(int256)", &int(-1))), int(1));
+    assert_eq!(ok(call("to_u256(int256)", &int(42))), int(42));
+    assert_eq!(ok(call("from_u256(uint256)", &max)), max);
+    assert_eq!(
+        ok(call("average(uint128,uint128)", &abi_words(&[3, 10]))),
+        int(-3)
+    );
+
+    // Overflows, divisions by zero and out of range conversions abort
+    let mut args = max.to_vec();
+    args.extend_from_slice(&int(1));
+    let (exit_reason, _) = call("add(int256,int256)", &args);
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    let mut args = min.to_vec();
+    args.extend_from_slice(&int(1));
+    let (exit_reason, _) = call("sub(int256,int256)", &args);
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    let mut args = min.to_vec();
+    args.extend_from_slice(&int(-1));
+    let (exit_reason, _) = call("mul(int256,int256)", &args);
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    let (exit_reason, _) = call("div(int256,int256)", &args);
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    let (exit_reason, _) = call("div(int256,int256)", &ints(&[1, 0]));
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    let (exit_reason, _) = call("neg(int256)", &min);
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    let (exit_reason, _) = call("to_u256(int256)", &int(-1));
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    let (exit_reason, _) = call("from_u256(uint256)", &min);
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    Ok(())
+}
+
+/// Test DispatcherAddress
+#[test]
+fn test_dispatch_address() -> Result<()> {
+    let (mut exec, contract_address) = deploy("DispatcherAddress.move", &Options::default())?;
+    let mut call = |sig: &str, args: &[u8]| {
+        exec.call_function(H160::zero(), contract_address, 0.into(), sig, args)
+    };
+
+    let addr = H160::from_low_u64_be(0x1234);
+    let (exit_reason, buffer) = call("to_u256(address)", &abi_words(&[0x1234]));
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, abi_words(&[0x1234]));
+    let (exit_reason, buffer) = call("to_u128(address)", &abi_words(&[0x1234]));
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, abi_words(&[0x1234]));
+    let (exit_reason, buffer) = call("from_u128(uint128)", &abi_words(&[0x1234]));
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, abi_words(&[0x1234]));
+    let (exit_reason, buffer) = call("to_bytes(address)", &abi_words(&[0x1234]));
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, abi_bytes(addr.as_bytes()));
+    let (exit_reason, buffer) = call("from_bytes(bytes)", &abi_bytes(addr.as_bytes()));
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, abi_words(&[0x1234]));
+
+    // The largest address fits in a U256, but not in a u128
+    let max_address = [0xffu8; 20];
+    let mut max_word = [0u8; 32];
+    max_word[12..].copy_from_slice(&max_address);
+    let (exit_reason, buffer) = call("from_u256(uint256)", &max_word);
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, max_word);
+    let (exit_reason, _) = call("to_u128(address)", &max_word);
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+
+    // Numbers and bytes which do not fit in an address are rejected
+    let mut too_large = max_word;
+    too_large[11] = 1;
+    let (exit_reason, _) = call("from_u256(uint256)", &too_large);
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    let (exit_reason, _) = call("from_bytes(bytes)", &abi_bytes(&max_word));
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    let (exit_reason, _) = call("from_bytes(bytes)", &abi_bytes(&max_address[1..]));
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    Ok(())
+}
+
+/// Test DispatcherBcs
+#[test]
+fn test_dispatch_bcs() -> Result<()> {
+    let (mut exec, contract_address) = deploy("DispatcherBcs.move", &Options::default())?;
+    let mut call = |sig: &str, args: &[u8]| {
+        exec.call_function(H160::zero(), contract_address, 0.into(), sig, args)
+    };
+
+    let (exit_reason, buffer) = call("serialize_u64(uint64)", &abi_words(&[0x0102]));
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, abi_bytes(&[2, 1, 0, 0, 0, 0, 0, 0]));
+
+    // Fields are serialized in declaration order, integers in little-endian
+    let mut point = vec![2, 1, 0, 0, 0, 0, 0, 0, 7];
+    point.extend_from_slice(H160::from_low_u64_be(0x1234).as_bytes());
+    point.push(2);
+    for tag in [1u8, 2] {
+        let mut bytes = [0u8; 16];
+        bytes[0] = tag;
+        point.extend_from_slice(&bytes);
+    }
+    let fields = abi_words(&[0x0102, 7, 0x1234, 0x80, 2, 1, 2]);
+    let (exit_reason, buffer) = call("serialize_point(uint64,uint8,address,uint128[])", &fields);
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, abi_bytes(&point));
+    let (exit_reason, buffer) = call("deserialize_point(bytes)", &abi_bytes(&point));
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, fields);
+
+    // Truncated or trailing bytes are rejected
+    let (exit_reason, _) = call(
+        "deserialize_point(bytes)",
+        &abi_bytes(&point[..point.len() - 1]),
+    );
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    point.push(0);
+    let (exit_reason, _) = call("deserialize_point(bytes)", &abi_bytes(&point));
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+
+    let (exit_reason, buffer) = call("deserialize_bool(bytes)", &abi_bytes(&[1]));
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, abi_words(&[1]));
+    let (exit_reason, _) = call("deserialize_bool(bytes)", &abi_bytes(&[2]));
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    Ok(())
+}
+
+/// Test DispatcherCrypto
+#[test]
+fn test_dispatch_crypto() -> Result<()> {
+    let (mut exec, contract_address) = deploy("DispatcherCrypto.move", &Options::default())?;
+
+    for data in [&b""[..], &b"hello"[..], &[0xab; 100][..]] {
+        let (exit_reason, buffer) = exec.call_function(
+            H160::zero(),
+            contract_address,
+            0.into(),
+            "keccak256(bytes)",
+            &abi_bytes(data),
+        );
+        assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+        assert_eq!(buffer, abi_bytes(&Keccak256::digest(data)));
+    }
+
+    // An invalid signature recovers the zero address, and hashes must be 32 bytes long
+    let mut args = abi_words(&[0x80, 0, 0xc0, 0x100]);
+    for _ in 0..3 {
+        args.extend(abi_words(&[32, 1]));
+    }
+    let sig = "ecrecover(bytes,uint8,bytes,bytes)";
+    let (exit_reason, buffer) =
+        exec.call_function(H160::zero(), contract_address, 0.into(), sig, &args);
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, abi_words(&[0]));
+    args[0x80 + 31] = 31;
+    let (exit_reason, _) = exec.call_function(H160::zero(), contract_address, 0.into(), sig, &args);
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    Ok(())
+}
+
+/// Test DispatcherPrecompiles
+#[test]
+fn test_dispatch_precompiles() -> Result<()> {
+    let (mut exec, contract_address) = deploy("DispatcherPrecompiles.move", &Options::default())?;
+
+    // The executor has no precompiles: calls to their addresses succeed without returning data,
+    // which the stubs reject unless no output is expected
+    let (exit_reason, buffer) = exec.call_function(
+        H160::zero(),
+        contract_address,
+        0.into(),
+        "modexp(bytes,bytes,bytes)",
+        &abi_words(&[0x60, 0xa0, 0xe0, 1, 2, 1, 3, 0]),
+    );
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, abi_bytes(&[]));
+    let (exit_reason, _) = exec.call_function(
+        H160::zero(),
+        contract_address,
+        0.into(),
+        "bn256_add(uint256,uint256,uint256,uint256)",
+        &abi_words(&[1, 2, 1, 2]),
+    );
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    let (exit_reason, _) = exec.call_function(
+        H160::zero(),
+        contract_address,
+        0.into(),
+        "bn256_mul(uint256,uint256,uint256)",
+        &abi_words(&[1, 2, 3]),
+    );
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    let (exit_reason, _) = exec.call_function(
+        H160::zero(),
+        contract_address,
+        0.into(),
+        "bn256_pairing(bytes)",
+        &abi_bytes(&[]),
+    );
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+
+    // The inputs of blake2f are validated before calling the precompile
+    let blake2f_args = |rounds: u64, h_len: u64| {
+        let mut head = abi_words(&[rounds]);
+        let mut tail = vec![];
+        for len in [h_len, 128, 16] {
+            head.extend(abi_words(&[0xa0 + tail.len() as u64]));
+            tail.extend(abi_words(&[len]));
+            tail.resize(tail.len() + ((len as usize + 31) / 32) * 32, 0x11);
+        }
+        head.extend(abi_words(&[0]));
+        head.extend(tail);
+        head
+    };
+    let sig = "blake2f(uint64,bytes,bytes,bytes,bool)";
+    for (rounds, h_len) in [(12, 64), (1 << 32, 64), (12, 32)] {
+        let (exit_reason, _) = exec.call_function(
+            H160::zero(),
+            contract_address,
+            0.into(),
+            sig,
+            &blake2f_args(rounds, h_len),
+        );
+        assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    }
+    Ok(())
+}
+
+/// Test DispatcherGenerics
+#[test]
+fn test_dispatch_generics() -> Result<()> {
+    let (mut exec, contract_address) = deploy("DispatcherGenerics.move", &Options::default())?;
+    let mut call = |sig: &str, args: &[u8]| {
+        let (exit_reason, buffer) =
+            exec.call_function(H160::zero(), contract_address, 0.into(), sig, args);
+        assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+        buffer
+    };
+
+    assert_eq!(
+        call("swap_u64(uint64,uint64)", &abi_words(&[1, 2])),
+        abi_words(&[2, 1])
+    );
+    assert_eq!(
+        call(
+            "swap_address(address,address)",
+            &abi_words(&[0x1234, 0x5678])
+        ),
+        abi_words(&[0x5678, 0x1234])
+    );
+    assert_eq!(
+        call("update_u128(uint128,uint128)", &abi_words(&[1, 2])),
+        abi_words(&[2])
+    );
+    assert_eq!(
+        call("update_pair(uint8,uint8)", &abi_words(&[1, 2])),
+        abi_words(&[2, 1])
+    );
+    Ok(())
+}
+
+/// Test DispatcherPayable
+#[test]
+fn test_dispatch_payable() -> Result<()> {
+    let (mut exec, contract_address) = deploy("DispatcherPayable.move", &Options::default())?;
+    let caller = H160::from_low_u64_be(0x1234);
+    let recipient = H160::from_low_u64_be(0x77);
+    exec.mint(caller, 1000.into()).unwrap();
+    let address_word = |addr: H160| {
+        let mut word = vec![0u8; 12];
+        word.extend_from_slice(addr.as_bytes());
+        word
+    };
+
+    // Only payable callables accept value
+    let (exit_reason, _) =
+        exec.call_function(caller, contract_address, 300.into(), "deposit()", &[]);
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    let (exit_reason, _) =
+        exec.call_function(caller, contract_address, 300.into(), "non_payable()", &[]);
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    let (exit_reason, buffer) = exec.call_function(
+        caller,
+        contract_address,
+        0.into(),
+        "balance_of(address)",
+        &address_word(contract_address),
+    );
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, abi_words(&[300]));
+
+    // Transfers out of the contract
+    let mut args = address_word(recipient);
+    args.extend(abi_words(&[100]));
+    let (exit_reason, _) = exec.call_function(
+        caller,
+        contract_address,
+        0.into(),
+        "withdraw(address,uint128)",
+        &args,
+    );
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    let mut args = address_word(recipient);
+    args.extend(abi_words(&[50]));
+    let (exit_reason, buffer) = exec.call_function(
+        caller,
+        contract_address,
+        0.into(),
+        "try_withdraw(address,uint128)",
+        &args,
+    );
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, abi_words(&[1]));
+    let (_, buffer) = exec.call_function(
+        caller,
+        contract_address,
+        0.into(),
+        "balance_of(address)",
+        &address_word(recipient),
+    );
+    assert_eq!(buffer, abi_words(&[150]));
+
+    // Transfers exceeding the balance of the contract fail
+    let mut args = address_word(recipient);
+    args.extend(abi_words(&[1000]));
+    let (exit_reason, _) = exec.call_function(
+        caller,
+        contract_address,
+        0.into(),
+        "withdraw(address,uint128)",
+        &args,
+    );
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    let (exit_reason, buffer) = exec.call_function(
+        caller,
+        contract_address,
+        0.into(),
+        "try_withdraw(address,uint128)",
+        &args,
+    );
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, abi_words(&[0]));
+    Ok(())
+}
+
+/// Test DispatcherCall
+#[test]
+fn test_dispatch_call() -> Result<()> {
+    let (mut exec, contract_address) = deploy("DispatcherCall.move", &Options::default())?;
+
+    // The contract calls itself, forwarding the selector of another callable as calldata
+    let forward_args = |sig: &str| {
+        let mut args = vec![0u8; 12];
+        args.extend_from_slice(contract_address.as_bytes());
+        args.extend(abi_words(&[0x40, 4]));
+        let mut data = [0u8; 32];
+        data[..4].copy_from_slice(&derive_method_selector(sig));
+        args.extend_from_slice(&data);
+        args
+    };
+    for forward in [
+        "forward(address,bytes)",
+        "forward_static(address,bytes)",
+        "forward_delegate(address,bytes)",
+    ] {
+        let (exit_reason, buffer) = exec.call_function(
+            H160::zero(),
+            contract_address,
+            0.into(),
+            forward,
+            &forward_args("answer()"),
+        );
+        assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+        assert_eq!(buffer, abi_words(&[1, 0x40, 32, 42]));
+
+        // A failed call returns the data the callee reverted with
+        let (exit_reason, buffer) = exec.call_function(
+            H160::zero(),
+            contract_address,
+            0.into(),
+            forward,
+            &forward_args("fail()"),
+        );
+        assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+        let mut revert_data = derive_method_selector("Error(string)").to_vec();
+        revert_data.extend(abi_bytes(b"0x2::M::fail aborted with code 7"));
+        let mut expected = abi_words(&[0, 0x40]);
+        expected.extend(&abi_bytes(&revert_data)[32..]);
+        assert_eq!(buffer, expected);
+    }
+
+    for forward in [
+        "forward_or_revert(address,bytes)",
+        "forward_or_abort(address,bytes)",
+    ] {
+        let (exit_reason, buffer) = exec.call_function(
+            H160::zero(),
+            contract_address,
+            0.into(),
+            forward,
+            &forward_args("answer()"),
+        );
+        assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+        assert_eq!(buffer, abi_words(&[0x20, 32, 42]));
+    }
+
+    let mut revert_data = derive_method_selector("Error(string)").to_vec();
+    revert_data.extend(abi_bytes(b"0x2::M::fail aborted with code 7"));
+
+    // The data the callee reverted with is propagated unchanged
+    let (exit_reason, buffer) = exec.call_function(
+        H160::zero(),
+        contract_address,
+        0.into(),
+        "forward_or_revert(address,bytes)",
+        &forward_args("fail()"),
+    );
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    assert_eq!(buffer, revert_data);
+
+    // The failure is turned into an abort with the given code, which carries the data the callee
+    // reverted with
+    let (exit_reason, buffer) = exec.call_function(
+        H160::zero(),
+        contract_address,
+        0.into(),
+        "forward_or_abort(address,bytes)",
+        &forward_args("fail()"),
+    );
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    let mut expected = derive_method_selector("ExternalCallFailed(uint64,bytes)").to_vec();
+    expected.extend(abi_words(&[99, 0x40]));
+    expected.extend(&abi_bytes(&revert_data)[32..]);
+    assert_eq!(buffer, expected);
+    Ok(())
+}
+
+/// Test DispatcherDynamic
+#[test]
+fn test_dispatch_dynamic() -> Result<()> {
+    let (mut exec, contract_address) = deploy("DispatcherDynamic.move", &Options::default())?;
+    let mut call = |sig: &str, args: &[u8]| {
+        let (exit_reason, buffer) =
+            exec.call_function(H160::zero(), contract_address, 0.into(), sig, args);
+        assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+        buffer
+    };
+
+    let mut bytes = abi_words(&[0x20, 5]);
+    let mut data = [0u8; 32];
+    data[..5].copy_from_slice(b"hello");
+    bytes.extend_from_slice(&data);
+    assert_eq!(call("echo_bytes(bytes)", &bytes), bytes);
+
+    let u64s = abi_words(&[0x20, 3, 1, 2, 3]);
+    assert_eq!(call("echo_u64s(uint64[])", &u64s), u64s);
+
+    // [[1], [2, 3]], where the inner arrays are at offsets from the start of the elements
+    let nested = abi_words(&[0x20, 2, 0x40, 0x80, 1, 1, 2, 2, 3]);
+    assert_eq!(call("echo_nested(uint64[][])", &nested), nested);
+
+    let rotated = call(
+        "rotate(uint64,uint128[],bool)",
+        &abi_words(&[7, 0x60, 1, 2, 10, 20]),
+    );
+    assert_eq!(rotated, abi_words(&[1, 0x60, 7, 2, 10, 20]));
+    Ok(())
+}
+
+/// Test DispatcherDynamic with calldata too short for the array it declares
+#[test]
+fn test_dispatch_dynamic_too_short() -> Result<()> {
+    let (mut exec, contract_address) = deploy("DispatcherDynamic.move", &Options::default())?;
+    let (exit_reason, _) = exec.call_function(
+        H160::zero(),
+        contract_address,
+        0.into(),
+        "echo_u64s(uint64[])",
+        &abi_words(&[0x20, 3, 1, 2]),
+    );
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    Ok(())
+}
+
+/// Test DispatcherDynamic with strict calldata validation
+#[test]
+fn test_dispatch_dynamic_strict() -> Result<()> {
+    let options = Options {
+        strict_calldata: true,
+        ..Options::default()
+    };
+    let (mut exec, contract_address) = deploy("DispatcherDynamic.move", &options)?;
+    let mut call = |sig: &str, args: &[u8]| {
+        exec.call_function(H160::zero(), contract_address, 0.into(), sig, args)
+    };
+
+    // Canonical encodings are accepted
+    let nested = abi_words(&[0x20, 2, 0x40, 0x80, 1, 1, 2, 2, 3]);
+    let (exit_reason, buffer) = call("echo_nested(uint64[][])", &nested);
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, nested);
+    let (exit_reason, _) = call(
+        "rotate(uint64,uint128[],bool)",
+        &abi_words(&[7, 0x60, 1, 2, 10, 20]),
+    );
+    assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+
+    // Trailing data is rejected
+    let (exit_reason, _) = call("echo_u64s(uint64[])", &abi_words(&[0x20, 3, 1, 2, 3, 4]));
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+
+    // Values which overlap or leave a gap are rejected
+    let (exit_reason, _) = call("echo_u64s(uint64[])", &abi_words(&[0x40, 0, 3, 1, 2, 3]));
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    let overlapping = abi_words(&[0x20, 2, 0x40, 0x40, 1, 1]);
+    let (exit_reason, _) = call("echo_nested(uint64[][])", &overlapping);
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+
+    // Bytes must be padded to a multiple of 32
+    let mut bytes = abi_words(&[0x20, 5]);
+    bytes.extend_from_slice(b"hello");
+    let (exit_reason, _) = call("echo_bytes(bytes)", &bytes);
+    assert!(matches!(exit_reason, ExitReason::Revert(_)));
+    Ok(())
+}
+
+/// Test DispatcherStructs
+#[test]
+fn test_dispatch_structs() -> Result<()> {
+    let (mut exec, contract_address) = deploy("DispatcherStructs.move", &Options::default())?;
+    let mut call = |sig: &str, args: &[u8]| {
+        let (exit_reason, buffer) =
+            exec.call_function(H160::zero(), contract_address, 0.into(), sig, args);
+        assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+        buffer
+    };
+
+    // Static structs are encoded in place, with their fields in declaration order
+    assert_eq!(
+        call("point(uint64,uint128)", &abi_words(&[1, 2])),
+        abi_words(&[1, 2])
+    );
+    assert_eq!(
+        call(
+            "segment(uint64,uint128,uint64,uint128)",
+            &abi_words(&[1, 2, 3, 4])
+        ),
+        abi_words(&[1, 1, 2, 3, 4])
+    );
+
+    // Dynamic structs are encoded after the head, with a head and tail of their own
+    let mut args = abi_words(&[0x40, 7, 2]);
+    let mut data = [0u8; 32];
+    data[..2].copy_from_slice(b"hi");
+    args.extend_from_slice(&data);
+    let mut expected = abi_words(&[0x20, 0x40, 7, 2]);
+    expected.extend_from_slice(&data);
+    assert_eq!(call("tagged(bytes,uint64)", &args), expected);
+    Ok(())
+}
+
+/// Test DispatcherStackSlots
+#[test]
+fn test_dispatch_stack_slots() -> Result<()> {
+    let env = build_model("DispatcherStackSlots.move")?;
+    Generator::run(&Options::default(), &env);
+    assert!(!env.has_errors());
+    assert!(env.has_diag("`0x2::M::many_locals` uses an estimated"));
+    assert!(!env.has_diag("`0x2::M::few_locals` uses an estimated"));
+    Ok(())
+}
+
+/// Test DispatcherScratch
+#[test]
+fn test_dispatch_scratch_allocation() -> Result<()> {
+    let options = Options {
+        scratch_allocation: true,
+        ..Options::default()
+    };
+
+    // The memory of a struct is released when it is unpacked or dropped right after being
+    // packed, but not if another allocation sits in between
+    let env = build_model("DispatcherScratch.move")?;
+    let (_, out) = Generator::run(&options, &env);
+    assert!(!env.has_errors());
+    let releases = |fun: &str| {
+        let start = out.find(&format!("function A2_M_{}(", fun)).unwrap();
+        let end = start + out[start..].find("\n\n").unwrap();
+        out[start..end].matches("$ReleaseScratch(").count()
+    };
+    assert_eq!(releases("pack_unpack"), 2);
+    assert_eq!(releases("pack_destroy"), 2);
+    assert_eq!(releases("allocation_between"), 0);
+
+    let (mut exec, contract_address) = deploy("DispatcherScratch.move", &options)?;
+    let mut call = |sig: &str, args: &[u8]| {
+        let (exit_reason, buffer) =
+            exec.call_function(H160::zero(), contract_address, 0.into(), sig, args);
+        assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+        buffer
+    };
+    assert_eq!(
+        call("pack_unpack(uint64,uint64)", &abi_words(&[2, 3])),
+        abi_words(&[7])
+    );
+    assert_eq!(
+        call("pack_destroy(uint64,uint64)", &abi_words(&[2, 3])),
+        abi_words(&[6])
+    );
+    // The single survives the allocation of the wide struct
+    assert_eq!(
+        call("allocation_between(uint64,uint64)", &abi_words(&[2, 3])),
+        abi_words(&[11])
+    );
+    Ok(())
+}
+
+/// Test DispatcherSharedHelper
+#[test]
+fn test_dispatch_shared_helper() -> Result<()> {
+    // The helper and the auxiliary functions it needs are defined in both code blocks
+    let env = build_model("DispatcherSharedHelper.move")?;
+    let (_, out) = Generator::run(&Options::default(), &env);
+    assert!(!env.has_errors());
+    assert_eq!(out.matches("function A2_M_scaled(").count(), 2);
+    assert_eq!(out.matches("function $AddU64(").count(), 2);
+
+    let contract_code = compile_yul_to_bytecode_bytes("DispatcherSharedHelper.move")?;
+    let mut exec = Executor::new(&VICINITY);
+    let contract_address = exec
+        .create_contract(H160::zero(), [contract_code, abi_words(&[2])].concat())
+        .expect("failed to create contract");
+    let (exit_reason, buffer) = exec.call_function(
+        H160::zero(),
+        contract_address,
+        0.into(),
+        "bump(uint64)",
+        &abi_words(&[3]),
+    );
     assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+    assert_eq!(buffer, abi_words(&[52]));
     Ok(())
 }
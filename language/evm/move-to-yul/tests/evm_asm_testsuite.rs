@@ -0,0 +1,57 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use move_compiler::shared::NumericalAddress;
+use move_model::{options::ModelBuilderOptions, run_model_builder_with_options};
+use move_to_yul::evm_asm::annotate_locations;
+use std::{fs, path::PathBuf};
+
+fn path_from_crate_root(path: &str) -> String {
+    let mut buf = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    buf.push(path);
+    buf.to_string_lossy().to_string()
+}
+
+#[test]
+fn test_annotate_locations() -> Result<()> {
+    let file_name = path_from_crate_root("tests/Arithm.move");
+    let mut named_address_mapping = move_stdlib::move_stdlib_named_addresses();
+    named_address_mapping.insert(
+        "Eth".to_string(),
+        NumericalAddress::parse_str("0x2").unwrap(),
+    );
+    let env = run_model_builder_with_options(
+        std::slice::from_ref(&file_name),
+        &[
+            path_from_crate_root("../stdlib/sources"),
+            path_from_crate_root("../../move-stdlib/sources"),
+        ],
+        ModelBuilderOptions::default(),
+        named_address_mapping,
+    )?;
+    assert!(!env.has_errors());
+    let source = fs::read_to_string(&file_name)?;
+    let start = source.find("let res: u64 = x + y;").unwrap();
+    let end = start + "let res: u64 = x + y;".len();
+    let first_line_end = source.find('\n').unwrap();
+
+    // Canned output of `solc --asm`, with locations in the Move source, in a file unknown to the
+    // environment, and at the start of the Move source, whose first line is too long to be shown.
+    let asm = format!(
+        "    /* \"{file}\":{start}:{end}  */\n    add\n    /* \"other.move\":0:10  */\n    \
+         pop\n    /* \"{file}\":0:{first_line_end}  */\n    stop\n",
+        file = file_name,
+        start = start,
+        end = end,
+        first_line_end = first_line_end
+    );
+    let expected = format!(
+        "    /* {file}:9:3: let res: u64 = x + y; */\n    add\n    /* \"other.move\":0:10  */\n    \
+         pop\n    /* {file}:1:1: // Tests basic arithmetic. We only test for u64. Existing mo... \
+         */\n    stop\n",
+        file = file_name
+    );
+    assert_eq!(annotate_locations(&env, &asm), expected);
+    Ok(())
+}
@@ -6,8 +6,8 @@ module 0x2::M {
     }
 
     #[fallback]
-    fun fallback(x: u64) : u64 {
-        x + 1
+    fun fallback(input: vector<u8>): vector<u8> {
+        input
     }
 
 }
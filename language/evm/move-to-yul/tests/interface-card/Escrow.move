@@ -0,0 +1,23 @@
+#[contract]
+module 0x2::Escrow {
+    use Eth::Evm::{revert_with, sender, sign};
+
+    struct Deposit has key {
+        value: u64
+    }
+
+    struct NotEmpty has drop {
+        value: u64,
+    }
+
+    #[callable]
+    fun close() acquires Deposit {
+        let Deposit { value } = move_from<Deposit>(sender());
+        if (value > 0) revert_with(NotEmpty { value })
+    }
+
+    #[callable]
+    fun open() {
+        move_to(&sign(sender()), Deposit { value: 0 })
+    }
+}
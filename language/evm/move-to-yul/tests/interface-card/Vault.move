@@ -0,0 +1,33 @@
+#[contract]
+module 0x2::Vault {
+    use Eth::Evm::{emit, sender, sign};
+
+    const EINSUFFICIENT_FUNDS: u64 = 1;
+
+    struct Deposit has key {
+        value: u64
+    }
+
+    struct Withdrawn has drop {
+        owner: address,
+        amount: u64,
+    }
+
+    #[callable]
+    fun balance_of(owner: address): u64 acquires Deposit {
+        borrow_global<Deposit>(owner).value
+    }
+
+    #[callable, payable]
+    fun open() {
+        move_to(&sign(sender()), Deposit { value: 0 })
+    }
+
+    #[callable]
+    fun withdraw(amount: u64) acquires Deposit {
+        let deposit = borrow_global_mut<Deposit>(sender());
+        if (deposit.value < amount) abort EINSUFFICIENT_FUNDS;
+        deposit.value = deposit.value - amount;
+        emit(Withdrawn { owner: sender(), amount })
+    }
+}
@@ -0,0 +1,124 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use move_compiler::shared::NumericalAddress;
+use move_model::{options::ModelBuilderOptions, run_model_builder_with_options};
+use move_to_yul::{
+    interface_card::{InterfaceCard, Mutability, BUILTIN_ABORT_CODE},
+    options::Options,
+};
+use std::path::PathBuf;
+
+fn path_from_crate_root(path: &str) -> String {
+    let mut buf = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    buf.push(path);
+    buf.to_string_lossy().to_string()
+}
+
+fn interface_card(file_name: &str) -> Result<InterfaceCard> {
+    let mut named_address_mapping = move_stdlib::move_stdlib_named_addresses();
+    named_address_mapping.insert(
+        "Eth".to_string(),
+        NumericalAddress::parse_str("0x2").unwrap(),
+    );
+    let env = run_model_builder_with_options(
+        &[path_from_crate_root(&format!(
+            "tests/interface-card/{}",
+            file_name
+        ))],
+        &[
+            path_from_crate_root("../stdlib/sources"),
+            path_from_crate_root("../../move-stdlib/sources"),
+        ],
+        ModelBuilderOptions::default(),
+        named_address_mapping,
+    )?;
+    assert!(!env.has_errors());
+    Ok(InterfaceCard::new(&Options::default(), &env))
+}
+
+#[test]
+fn test_callables() -> Result<()> {
+    let card = interface_card("Vault.move")?;
+    assert_eq!(card.contract, "A2_Vault");
+    let signatures: Vec<_> = card
+        .callables
+        .iter()
+        .map(|callable| callable.signature.as_str())
+        .collect();
+    assert_eq!(signatures.len(), 3);
+    assert!(signatures.contains(&"balance_of(address)"));
+    assert!(signatures.contains(&"open()"));
+    assert!(signatures.contains(&"withdraw(uint64)"));
+
+    let balance_of = &card.callables[signatures
+        .iter()
+        .position(|s| *s == "balance_of(address)")
+        .unwrap()];
+    assert_eq!(balance_of.name, "0x2::Vault::balance_of");
+    assert_eq!(balance_of.mutability, Mutability::NonPayable);
+    assert_eq!(balance_of.returns, vec!["uint64".to_string()]);
+    assert!(balance_of.events.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_reverts_and_events() -> Result<()> {
+    let card = interface_card("Vault.move")?;
+    let find = |signature: &str| {
+        card.callables
+            .iter()
+            .find(|callable| callable.signature == signature)
+            .unwrap()
+    };
+
+    let open = find("open()");
+    assert_eq!(open.mutability, Mutability::Payable);
+    assert!(open.reverts.iter().all(|revert| revert.code != Some(99)));
+
+    let withdraw = find("withdraw(uint64)");
+    let codes: Vec<_> = withdraw.reverts.iter().map(|revert| revert.code).collect();
+    assert!(codes.contains(&Some(99)));
+    assert!(codes.contains(&Some(1)));
+    assert!(codes.contains(&Some(BUILTIN_ABORT_CODE)));
+    assert!(withdraw
+        .reverts
+        .iter()
+        .any(|revert| revert.code.is_none() && revert.reason.contains("Panic(0x11)")));
+    assert_eq!(withdraw.events.len(), 1);
+    assert_eq!(withdraw.events[0].name, "0x2::Vault::Withdrawn");
+    assert_eq!(withdraw.events[0].signature, "Withdrawn(address,uint64)");
+    assert!(withdraw.events[0].topic.starts_with("0x"));
+
+    let markdown = card.to_markdown();
+    assert!(markdown.starts_with("# Contract `A2_Vault`"));
+    assert!(markdown.contains("## `withdraw(uint64)`"));
+    assert!(markdown.contains("| `0x2::Vault::Withdrawn` | `Withdrawn(address,uint64)` |"));
+
+    let json: InterfaceCard = serde_json::from_str(&card.to_json())?;
+    assert_eq!(json, card);
+    Ok(())
+}
+
+#[test]
+fn test_custom_errors() -> Result<()> {
+    let card = interface_card("Escrow.move")?;
+    let close = card
+        .callables
+        .iter()
+        .find(|callable| callable.signature == "close()")
+        .unwrap();
+    let reasons: Vec<_> = close
+        .reverts
+        .iter()
+        .filter(|revert| revert.code.is_none())
+        .map(|revert| revert.reason.as_str())
+        .collect();
+    assert_eq!(reasons.len(), 1);
+    assert!(reasons[0].starts_with("custom error `NotEmpty(uint64)` with selector 0x"));
+
+    let markdown = card.to_markdown();
+    assert!(markdown.contains("| computed | custom error `NotEmpty(uint64)` with selector 0x"));
+    Ok(())
+}
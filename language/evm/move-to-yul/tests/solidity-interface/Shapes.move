@@ -0,0 +1,39 @@
+#[contract]
+module 0x2::Shapes {
+    use Std::Vector;
+    use 0x1::U256::U256;
+
+    struct Point has drop {
+        x: u64,
+        y: u64,
+    }
+
+    struct Polygon has drop {
+        name: vector<u8>,
+        vertices: vector<Point>,
+    }
+
+    #[callable, pure]
+    fun origin(): Point {
+        Point { x: 0, y: 0 }
+    }
+
+    #[callable, pure]
+    fun polygon(name: vector<u8>): Polygon {
+        Polygon { name, vertices: Vector::empty() }
+    }
+
+    #[callable]
+    fun scale(factors: vector<u64>, by: U256): (bool, vector<u64>) {
+        by;
+        (true, factors)
+    }
+
+    #[callable, payable]
+    fun buy(_id: u128) {
+    }
+
+    #[receive, payable]
+    fun receive() {
+    }
+}
@@ -0,0 +1,79 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use move_compiler::shared::NumericalAddress;
+use move_model::{options::ModelBuilderOptions, run_model_builder_with_options};
+use move_to_yul::{options::Options, solidity_interface::SolidityInterface};
+use std::path::PathBuf;
+
+fn path_from_crate_root(path: &str) -> String {
+    let mut buf = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    buf.push(path);
+    buf.to_string_lossy().to_string()
+}
+
+fn solidity_interface(file_name: &str) -> Result<SolidityInterface> {
+    let mut named_address_mapping = move_stdlib::move_stdlib_named_addresses();
+    named_address_mapping.insert(
+        "Eth".to_string(),
+        NumericalAddress::parse_str("0x2").unwrap(),
+    );
+    let env = run_model_builder_with_options(
+        &[path_from_crate_root(&format!(
+            "tests/solidity-interface/{}",
+            file_name
+        ))],
+        &[
+            path_from_crate_root("../stdlib/sources"),
+            path_from_crate_root("../../move-stdlib/sources"),
+        ],
+        ModelBuilderOptions::default(),
+        named_address_mapping,
+    )?;
+    assert!(!env.has_errors());
+    Ok(SolidityInterface::new(&Options::default(), &env))
+}
+
+#[test]
+fn test_structs() -> Result<()> {
+    let interface = solidity_interface("Shapes.move")?;
+    assert_eq!(interface.name, "IShapes");
+    let names: Vec<_> = interface.structs.iter().map(|st| &st.name).collect();
+    assert_eq!(names, vec!["Point", "Polygon"]);
+    let polygon = &interface.structs[1];
+    assert_eq!(polygon.fields[0].ty, "bytes");
+    assert_eq!(polygon.fields[1].ty, "Point[]");
+    Ok(())
+}
+
+#[test]
+fn test_solidity() -> Result<()> {
+    let interface = solidity_interface("Shapes.move")?;
+    assert_eq!(
+        interface.to_solidity(),
+        "\
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.0;
+
+interface IShapes {
+    struct Point {
+        uint64 x;
+        uint64 y;
+    }
+
+    struct Polygon {
+        bytes name;
+        Point[] vertices;
+    }
+
+    function buy(uint128 _id) external payable;
+    function origin() external pure returns (Point memory);
+    function polygon(bytes calldata name) external pure returns (Polygon memory);
+    function scale(uint64[] calldata factors, uint256 by) external returns (bool, uint64[] memory);
+    receive() external payable;
+}
+"
+    );
+    Ok(())
+}
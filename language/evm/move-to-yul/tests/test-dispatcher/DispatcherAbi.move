@@ -0,0 +1,29 @@
+#[contract]
+module 0x2::M {
+    use Eth::Evm;
+
+    struct Pair has drop {
+        x: u64,
+        data: vector<u8>,
+    }
+
+    #[callable]
+    fun encode_u128(x: u128): vector<u8> {
+        Evm::abi_encode(x)
+    }
+
+    #[callable]
+    fun encode_pair(x: u64, data: vector<u8>): vector<u8> {
+        Evm::abi_encode(Pair { x, data })
+    }
+
+    #[callable]
+    fun decode_u8(data: vector<u8>): u8 {
+        Evm::abi_decode<u8>(data)
+    }
+
+    #[callable]
+    fun roundtrip(v: vector<u64>): vector<u64> {
+        Evm::abi_decode<vector<u64>>(Evm::abi_encode(v))
+    }
+}
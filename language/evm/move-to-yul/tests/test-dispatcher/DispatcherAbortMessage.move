@@ -0,0 +1,15 @@
+#[contract]
+module 0x2::M {
+    const EZERO: u64 = 42;
+
+    #[callable]
+    fun check(x: u64): u64 {
+        assert!(x > 0, EZERO);
+        x
+    }
+
+    #[callable]
+    fun fail(code: u64) {
+        abort code
+    }
+}
@@ -0,0 +1,35 @@
+#[contract]
+module 0x2::M {
+    use Eth::Address;
+    use 0x1::U256::U256;
+
+    #[callable]
+    fun to_u256(addr: address): U256 {
+        Address::to_u256(addr)
+    }
+
+    #[callable]
+    fun from_u256(x: U256): address {
+        Address::from_u256(x)
+    }
+
+    #[callable]
+    fun to_u128(addr: address): u128 {
+        Address::to_u128(addr)
+    }
+
+    #[callable]
+    fun from_u128(x: u128): address {
+        Address::from_u128(x)
+    }
+
+    #[callable]
+    fun to_bytes(addr: address): vector<u8> {
+        Address::to_bytes(addr)
+    }
+
+    #[callable]
+    fun from_bytes(bytes: vector<u8>): address {
+        Address::from_bytes(bytes)
+    }
+}
@@ -0,0 +1,33 @@
+#[contract]
+module 0x2::M {
+    use Std::BCS;
+    use Eth::BCS as EthBCS;
+
+    struct Point has drop {
+        x: u64,
+        y: u8,
+        owner: address,
+        tags: vector<u128>,
+    }
+
+    #[callable]
+    fun serialize_u64(x: u64): vector<u8> {
+        BCS::to_bytes(&x)
+    }
+
+    #[callable]
+    fun serialize_point(x: u64, y: u8, owner: address, tags: vector<u128>): vector<u8> {
+        BCS::to_bytes(&Point { x, y, owner, tags })
+    }
+
+    #[callable]
+    fun deserialize_point(bytes: vector<u8>): (u64, u8, address, vector<u128>) {
+        let Point { x, y, owner, tags } = EthBCS::from_bytes<Point>(bytes);
+        (x, y, owner, tags)
+    }
+
+    #[callable]
+    fun deserialize_bool(bytes: vector<u8>): bool {
+        EthBCS::from_bytes<bool>(bytes)
+    }
+}
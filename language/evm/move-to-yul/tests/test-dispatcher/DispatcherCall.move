@@ -0,0 +1,39 @@
+#[contract]
+module 0x2::M {
+    use Eth::Evm;
+
+    #[callable]
+    fun answer(): u64 {
+        42
+    }
+
+    #[callable]
+    fun fail() {
+        abort 7
+    }
+
+    #[callable]
+    fun forward(target: address, data: vector<u8>): (bool, vector<u8>) {
+        Evm::call(target, 0, Evm::gas_left(), data)
+    }
+
+    #[callable]
+    fun forward_or_revert(target: address, data: vector<u8>): vector<u8> {
+        Evm::call_or_revert(target, 0, Evm::gas_left(), data)
+    }
+
+    #[callable]
+    fun forward_or_abort(target: address, data: vector<u8>): vector<u8> {
+        Evm::call_or_abort(target, 0, Evm::gas_left(), data, 99)
+    }
+
+    #[callable]
+    fun forward_static(target: address, data: vector<u8>): (bool, vector<u8>) {
+        Evm::staticcall(target, Evm::gas_left(), data)
+    }
+
+    #[callable]
+    fun forward_delegate(target: address, data: vector<u8>): (bool, vector<u8>) {
+        Evm::delegatecall(target, Evm::gas_left(), data)
+    }
+}
@@ -0,0 +1,10 @@
+// Tests error on callable functions with the same selector.
+#[contract]
+module 0x2::M {
+    // Both `f_75241()` and `f_130171()` have the selector 0x5158e766
+    #[callable]
+    fun f_75241(): u64 { 1 }
+
+    #[callable]
+    fun f_130171(): u64 { 2 }
+}
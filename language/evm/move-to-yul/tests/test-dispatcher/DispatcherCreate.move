@@ -0,0 +1,25 @@
+#[contract]
+module 0x2::M {
+    use Eth::Evm::sign;
+
+    struct Config has key {
+        supply: u128,
+        decimals: u8,
+    }
+
+    #[create]
+    fun create(supply: u128, name: vector<u8>, decimals: u8) {
+        name;
+        move_to(&sign(@0x42), Config { supply, decimals })
+    }
+
+    #[callable]
+    fun supply(): u128 acquires Config {
+        borrow_global<Config>(@0x42).supply
+    }
+
+    #[callable]
+    fun decimals(): u8 acquires Config {
+        borrow_global<Config>(@0x42).decimals
+    }
+}
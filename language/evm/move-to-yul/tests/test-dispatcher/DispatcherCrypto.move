@@ -0,0 +1,19 @@
+#[contract]
+module 0x2::M {
+    use Eth::Crypto;
+
+    #[callable]
+    fun keccak256(data: vector<u8>): vector<u8> {
+        Crypto::keccak256(data)
+    }
+
+    #[callable]
+    fun sha256(data: vector<u8>): vector<u8> {
+        Crypto::sha256(data)
+    }
+
+    #[callable]
+    fun ecrecover(hash: vector<u8>, v: u8, r: vector<u8>, s: vector<u8>): address {
+        Crypto::ecrecover(hash, v, r, s)
+    }
+}
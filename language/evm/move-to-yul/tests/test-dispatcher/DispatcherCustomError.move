@@ -0,0 +1,28 @@
+#[contract]
+module 0x2::M {
+    use Eth::Evm::revert_with;
+
+    struct InsufficientBalance has drop {
+        available: u64,
+        required: u64,
+    }
+
+    struct Unauthorized has drop {
+        account: address,
+        reason: vector<u8>,
+    }
+
+    #[callable]
+    fun withdraw(amount: u64): u64 {
+        let available = 10;
+        if (amount > available) {
+            revert_with(InsufficientBalance { available, required: amount })
+        };
+        available - amount
+    }
+
+    #[callable]
+    fun guard(account: address) {
+        revert_with(Unauthorized { account, reason: b"owner only" })
+    }
+}
@@ -0,0 +1,22 @@
+#[contract]
+module 0x2::M {
+    #[callable]
+    fun echo_bytes(b: vector<u8>): vector<u8> {
+        b
+    }
+
+    #[callable]
+    fun echo_u64s(v: vector<u64>): vector<u64> {
+        v
+    }
+
+    #[callable]
+    fun echo_nested(v: vector<vector<u64>>): vector<vector<u64>> {
+        v
+    }
+
+    #[callable]
+    fun rotate(x: u64, v: vector<u128>, y: bool): (bool, vector<u128>, u64) {
+        (y, v, x)
+    }
+}
@@ -0,0 +1,24 @@
+#[contract]
+module 0x2::M {
+    use Eth::Evm;
+
+    #[callable]
+    fun sender(): address {
+        Evm::sender()
+    }
+
+    #[callable, payable]
+    fun value(): u128 {
+        Evm::value()
+    }
+
+    #[callable]
+    fun block(): (u64, u64, u64) {
+        (Evm::block_number(), Evm::timestamp(), Evm::chain_id())
+    }
+
+    #[callable]
+    fun has_gas_left(): bool {
+        Evm::gas_left() > 0
+    }
+}
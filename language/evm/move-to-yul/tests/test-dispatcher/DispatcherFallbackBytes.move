@@ -0,0 +1,19 @@
+#[contract]
+module 0x2::M {
+    use Eth::Evm;
+
+    #[callable]
+    fun calldata_size(_x: u64): u64 {
+        Evm::calldatasize()
+    }
+
+    #[callable]
+    fun selector(): vector<u8> {
+        Evm::calldatacopy(0, 4)
+    }
+
+    #[fallback]
+    fun fallback(input: vector<u8>): vector<u8> {
+        input
+    }
+}
@@ -0,0 +1,43 @@
+#[contract]
+module 0x2::M {
+    struct Pair<T> has copy, drop {
+        first: T,
+        second: T,
+    }
+
+    // Generic functions are instantiated for each type they are called with
+    fun swap<T: copy + drop>(p: Pair<T>): Pair<T> {
+        Pair { first: p.second, second: p.first }
+    }
+
+    // Borrows a local of the type parameter
+    fun update<T: drop>(x: T, y: T): T {
+        let r = &mut x;
+        *r = y;
+        x
+    }
+
+    #[callable]
+    fun swap_u64(first: u64, second: u64): (u64, u64) {
+        let Pair { first, second } = swap(Pair { first, second });
+        (first, second)
+    }
+
+    #[callable]
+    fun swap_address(first: address, second: address): (address, address) {
+        let Pair { first, second } = swap(Pair { first, second });
+        (first, second)
+    }
+
+    #[callable]
+    fun update_u128(x: u128, y: u128): u128 {
+        update(x, y)
+    }
+
+    #[callable]
+    fun update_pair(first: u8, second: u8): (u8, u8) {
+        let p = Pair { first, second };
+        let Pair { first, second } = update(p, swap(p));
+        (first, second)
+    }
+}
@@ -0,0 +1,67 @@
+#[contract]
+module 0x2::M {
+    use Eth::I256::{Self, I256};
+    use 0x1::U256::U256;
+
+    #[callable]
+    fun add(x: I256, y: I256): I256 {
+        I256::add(x, y)
+    }
+
+    #[callable]
+    fun sub(x: I256, y: I256): I256 {
+        I256::sub(x, y)
+    }
+
+    #[callable]
+    fun mul(x: I256, y: I256): I256 {
+        I256::mul(x, y)
+    }
+
+    #[callable]
+    fun div(x: I256, y: I256): I256 {
+        I256::div(x, y)
+    }
+
+    #[callable]
+    fun mod(x: I256, y: I256): I256 {
+        I256::mod(x, y)
+    }
+
+    #[callable]
+    fun neg(x: I256): I256 {
+        I256::neg(x)
+    }
+
+    #[callable]
+    fun lt(x: I256, y: I256): bool {
+        I256::lt(x, y)
+    }
+
+    #[callable]
+    fun ge(x: I256, y: I256): bool {
+        I256::ge(x, y)
+    }
+
+    #[callable]
+    fun is_neg(x: I256): bool {
+        I256::is_neg(x)
+    }
+
+    #[callable]
+    fun from_u256(x: U256): I256 {
+        I256::from_u256(x)
+    }
+
+    #[callable]
+    fun to_u256(x: I256): U256 {
+        I256::to_u256(x)
+    }
+
+    #[callable]
+    fun average(x: u128, y: u128): I256 {
+        // A signed intermediate value, as when computing a price change
+        let diff = I256::sub(I256::from_u128(x), I256::from_u128(y));
+        I256::div(diff, I256::from_u128(2))
+    }
+}
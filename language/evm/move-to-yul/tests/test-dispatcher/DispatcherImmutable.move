@@ -0,0 +1,43 @@
+#[contract]
+module 0x2::M {
+    use Eth::Immutable;
+    use Eth::U256::U256;
+
+    #[immutable]
+    struct Config has copy, drop {
+        owner: address,
+        fee: u64,
+        cap: U256,
+        enabled: bool,
+    }
+
+    #[create]
+    fun create(owner: address, fee: u64, cap: U256) {
+        Immutable::set(Config { owner, fee, cap, enabled: true });
+        // The value can be read during creation once set
+        assert!(Immutable::get<Config>().fee == fee, 1);
+    }
+
+    #[callable, view]
+    fun owner(): address {
+        Immutable::get<Config>().owner
+    }
+
+    #[callable, view]
+    fun fee(): u64 {
+        Immutable::get<Config>().fee
+    }
+
+    #[callable, view]
+    fun cap(): U256 {
+        Immutable::get<Config>().cap
+    }
+
+    #[callable, view]
+    fun enabled(): bool {
+        // The value returned is a copy
+        let config = Immutable::get<Config>();
+        config.enabled = false;
+        Immutable::get<Config>().enabled
+    }
+}
@@ -0,0 +1,28 @@
+#[contract]
+module 0x2::M {
+    use Eth::Immutable;
+
+    #[immutable]
+    struct Config has copy, drop {
+        owner: address,
+    }
+
+    #[immutable]
+    struct Names has copy, drop {
+        names: vector<u8>,
+    }
+
+    struct Counter has copy, drop {
+        count: u64,
+    }
+
+    #[callable]
+    fun set_owner(owner: address) {
+        Immutable::set(Config { owner })
+    }
+
+    #[callable]
+    fun count(): u64 {
+        Immutable::get<Counter>().count
+    }
+}
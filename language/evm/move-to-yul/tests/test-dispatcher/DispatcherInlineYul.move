@@ -0,0 +1,22 @@
+#[contract]
+module 0x2::M {
+    #[inline_yul = b"$result := add(x, mul(y, 2))"]
+    native fun add_twice(x: u64, y: u64): u64;
+
+    #[inline_yul = b"$result0 := div(x, y)\n$result1 := mod(x, y)"]
+    native fun div_mod(x: u128, y: u128): (u128, u128);
+
+    #[inline_yul = b"$result := mload(${MEM_SIZE_LOC})"]
+    native fun free_memory(): u128;
+
+    #[callable, pure]
+    fun compute(x: u64, y: u64): (u64, u128, u128) {
+        let (q, r) = div_mod((x as u128), (y as u128));
+        (add_twice(x, y), q, r)
+    }
+
+    #[callable, pure]
+    fun used_memory(): u128 {
+        free_memory()
+    }
+}
@@ -0,0 +1,28 @@
+#[contract]
+module 0x2::M {
+    #[inline_yul = b"$result := mload(${UNKNOWN_LOC})"]
+    native fun unknown_placeholder(): u64;
+
+    #[inline_yul = b"sstore(0, x)"]
+    native fun store(x: u64);
+
+    #[inline_yul = b"$result := x"]
+    fun not_native(x: u64): u64 {
+        x
+    }
+
+    #[callable]
+    fun call_unknown_placeholder(): u64 {
+        unknown_placeholder()
+    }
+
+    #[callable, view]
+    fun store_in_view(x: u64) {
+        store(x)
+    }
+
+    #[callable]
+    fun call_not_native(x: u64): u64 {
+        not_native(x)
+    }
+}
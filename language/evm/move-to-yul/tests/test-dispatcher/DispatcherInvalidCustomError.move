@@ -0,0 +1,9 @@
+#[contract]
+module 0x2::M {
+    use Eth::Evm::revert_with;
+
+    #[callable]
+    fun revert_with_code() {
+        revert_with(1)
+    }
+}
@@ -0,0 +1,27 @@
+#[contract]
+module 0x2::M {
+    use Std::Vector;
+
+    #[callable]
+    fun add_max(x: u64): u64 {
+        x + 18446744073709551615
+    }
+
+    #[callable]
+    fun div(x: u64, y: u64): u64 {
+        x / y
+    }
+
+    #[callable]
+    fun at(i: u64): u64 {
+        let v = Vector::empty<u64>();
+        Vector::push_back(&mut v, 7);
+        *Vector::borrow(&v, i)
+    }
+
+    #[callable]
+    fun pop(): u64 {
+        let v = Vector::empty<u64>();
+        Vector::pop_back(&mut v)
+    }
+}
@@ -0,0 +1,27 @@
+#[contract]
+module 0x2::M {
+    use Eth::Evm;
+
+    #[callable, payable]
+    fun deposit() {
+    }
+
+    #[callable]
+    fun non_payable() {
+    }
+
+    #[callable]
+    fun balance_of(addr: address): u128 {
+        Evm::balance(addr)
+    }
+
+    #[callable]
+    fun withdraw(to: address, amount: u128) {
+        Evm::transfer(to, amount)
+    }
+
+    #[callable]
+    fun try_withdraw(to: address, amount: u128): bool {
+        Evm::send(to, amount)
+    }
+}
@@ -0,0 +1,30 @@
+#[contract]
+module 0x2::M {
+    use Eth::Precompiles;
+    use 0x1::U256::U256;
+
+    #[callable]
+    fun modexp(base: vector<u8>, exponent: vector<u8>, modulus: vector<u8>): vector<u8> {
+        Precompiles::modexp(base, exponent, modulus)
+    }
+
+    #[callable]
+    fun bn256_add(x1: U256, y1: U256, x2: U256, y2: U256): (U256, U256) {
+        Precompiles::bn256_add(x1, y1, x2, y2)
+    }
+
+    #[callable]
+    fun bn256_mul(x1: U256, y1: U256, scalar: U256): (U256, U256) {
+        Precompiles::bn256_mul(x1, y1, scalar)
+    }
+
+    #[callable]
+    fun bn256_pairing(input: vector<u8>): bool {
+        Precompiles::bn256_pairing(input)
+    }
+
+    #[callable]
+    fun blake2f(rounds: u64, h: vector<u8>, m: vector<u8>, t: vector<u8>, f: bool): vector<u8> {
+        Precompiles::blake2f(rounds, h, m, t, f)
+    }
+}
@@ -0,0 +1,50 @@
+// Tests the reuse of the memory of structs with `--scratch-allocation`. Each struct below takes
+// one word of memory, except `Wide` which takes two.
+#[contract]
+module 0x2::M {
+    struct Pair has drop {
+        x: u64,
+        y: u64,
+    }
+
+    struct Single has drop {
+        z: u64,
+    }
+
+    struct Wide has drop {
+        a: u128,
+        b: u128,
+        c: u128,
+    }
+
+    // The memory of each pair is released when it is unpacked, so that the next pair reuses it.
+    #[callable]
+    fun pack_unpack(x: u64, y: u64): u64 {
+        let p = Pair { x, y };
+        let Pair { x, y } = p;
+        let q = Pair { x: x + 1, y: y + 1 };
+        let Pair { x, y } = q;
+        x + y
+    }
+
+    // The memory of the pair is released when it is dropped, and reused by the single.
+    #[callable]
+    fun pack_destroy(x: u64, y: u64): u64 {
+        let _ = Pair { x, y };
+        let Single { z } = Single { z: x * y };
+        z
+    }
+
+    // The single is allocated after the pair, so the memory of the pair cannot be released when
+    // it is unpacked: the wide struct allocated next would overwrite the single.
+    #[callable]
+    fun allocation_between(x: u64, y: u64): u64 {
+        let p = Pair { x, y };
+        let s = Single { z: x * y };
+        let Pair { x, y } = p;
+        let w = Wide { a: (x as u128), b: (y as u128), c: 7 };
+        let z = s.z;
+        let Wide { a, b, c: _ } = w;
+        (a as u64) + (b as u64) + z
+    }
+}
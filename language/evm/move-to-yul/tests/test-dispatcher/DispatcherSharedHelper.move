@@ -0,0 +1,26 @@
+// Tests a function called both by the creator and by a callable function, which needs to be
+// defined in the code blocks of both the creation object and the deployed object.
+#[contract]
+module 0x2::M {
+    use Eth::Evm::sign;
+
+    struct Counter has key {
+        value: u64,
+    }
+
+    fun scaled(x: u64): u64 {
+        x * 10 + 1
+    }
+
+    #[create]
+    fun create(x: u64) {
+        move_to(&sign(@0x42), Counter { value: scaled(x) })
+    }
+
+    #[callable]
+    fun bump(x: u64): u64 acquires Counter {
+        let counter = borrow_global_mut<Counter>(@0x42);
+        counter.value = counter.value + scaled(x);
+        counter.value
+    }
+}
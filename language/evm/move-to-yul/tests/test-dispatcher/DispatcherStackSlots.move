@@ -0,0 +1,23 @@
+// Tests warning on functions using more stack slots than the EVM can reach.
+#[contract]
+module 0x2::M {
+    #[callable]
+    fun many_locals(
+        a: u64, b: u64, c: u64, d: u64, e: u64, f: u64, g: u64, h: u64, i: u64
+    ): u64 {
+        let x1 = a + b;
+        let x2 = c + d;
+        let x3 = e + f;
+        let x4 = g + h;
+        let x5 = x1 + x2;
+        let x6 = x3 + x4;
+        let x7 = x5 + x6;
+        let x8 = x7 + i;
+        x8
+    }
+
+    #[callable]
+    fun few_locals(a: u64, b: u64): u64 {
+        a + b
+    }
+}
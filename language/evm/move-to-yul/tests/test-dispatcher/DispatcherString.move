@@ -0,0 +1,53 @@
+#[contract]
+module 0x2::M {
+    use Eth::Evm::sign;
+    use Eth::String::{Self, String};
+
+    struct Metadata has key {
+        name: String,
+        symbol: String,
+    }
+
+    #[callable]
+    fun hello(): String {
+        String::utf8(b"Hello")
+    }
+
+    #[callable]
+    fun greet(name: String): String {
+        let greeting = String::utf8(b"Hello, ");
+        String::append(&mut greeting, name);
+        String::append_utf8(&mut greeting, b"!");
+        greeting
+    }
+
+    #[callable]
+    fun length(s: String): u64 {
+        String::length(&s)
+    }
+
+    #[callable]
+    fun from_bytes(bytes: vector<u8>): String {
+        String::utf8(bytes)
+    }
+
+    #[callable]
+    fun publish(name: String, symbol: String) {
+        move_to(&sign(@0x42), Metadata { name, symbol })
+    }
+
+    #[callable]
+    fun name(): String acquires Metadata {
+        *&borrow_global<Metadata>(@0x42).name
+    }
+
+    #[callable]
+    fun symbol(): String acquires Metadata {
+        *&borrow_global<Metadata>(@0x42).symbol
+    }
+
+    #[callable]
+    fun rename(name: String) acquires Metadata {
+        *&mut borrow_global_mut<Metadata>(@0x42).name = name
+    }
+}
@@ -0,0 +1,33 @@
+#[contract]
+module 0x2::M {
+    // Laid out in memory with `y` first, but encoded with `x` first
+    struct Point has drop {
+        x: u64,
+        y: u128,
+    }
+
+    struct Segment has drop {
+        from: Point,
+        to: Point,
+    }
+
+    struct Tagged has drop {
+        tag: vector<u8>,
+        value: u64,
+    }
+
+    #[callable]
+    fun point(x: u64, y: u128): Point {
+        Point { x, y }
+    }
+
+    #[callable]
+    fun segment(x1: u64, y1: u128, x2: u64, y2: u128): (bool, Segment) {
+        (true, Segment { from: Point { x: x1, y: y1 }, to: Point { x: x2, y: y2 } })
+    }
+
+    #[callable]
+    fun tagged(tag: vector<u8>, value: u64): Tagged {
+        Tagged { tag, value }
+    }
+}
@@ -0,0 +1,88 @@
+#[contract]
+module 0x2::M {
+    use Eth::Evm::sign;
+    use Eth::Table::{Self, Table};
+
+    struct Point has store {
+        x: u64,
+        y: u64,
+    }
+
+    struct Registry has key {
+        balances: Table<address, u64>,
+        points: Table<u64, Point>,
+        tags: Table<u64, vector<u8>>,
+    }
+
+    #[callable]
+    fun publish() {
+        move_to(&sign(@0x42), Registry {
+            balances: Table::empty(),
+            points: Table::empty(),
+            tags: Table::empty(),
+        })
+    }
+
+    #[callable]
+    fun deposit(owner: address, amount: u64) acquires Registry {
+        let balances = &mut borrow_global_mut<Registry>(@0x42).balances;
+        if (Table::contains(balances, owner)) {
+            let balance = Table::borrow_mut(balances, owner);
+            *balance = *balance + amount
+        } else {
+            Table::add(balances, owner, amount)
+        }
+    }
+
+    #[callable]
+    fun balance(owner: address): u64 acquires Registry {
+        *Table::borrow(&borrow_global<Registry>(@0x42).balances, owner)
+    }
+
+    #[callable]
+    fun has_balance(owner: address): bool acquires Registry {
+        Table::contains(&borrow_global<Registry>(@0x42).balances, owner)
+    }
+
+    #[callable]
+    fun withdraw_all(owner: address): u64 acquires Registry {
+        Table::remove(&mut borrow_global_mut<Registry>(@0x42).balances, owner)
+    }
+
+    #[callable]
+    fun add_point(id: u64, x: u64, y: u64) acquires Registry {
+        Table::add(&mut borrow_global_mut<Registry>(@0x42).points, id, Point { x, y })
+    }
+
+    #[callable]
+    fun move_point(id: u64, dx: u64) acquires Registry {
+        let point = Table::borrow_mut(&mut borrow_global_mut<Registry>(@0x42).points, id);
+        point.x = point.x + dx
+    }
+
+    #[callable]
+    fun point_x(id: u64): u64 acquires Registry {
+        Table::borrow(&borrow_global<Registry>(@0x42).points, id).x
+    }
+
+    #[callable]
+    fun remove_point(id: u64): u64 acquires Registry {
+        let Point { x, y } = Table::remove(&mut borrow_global_mut<Registry>(@0x42).points, id);
+        x + y
+    }
+
+    #[callable]
+    fun add_tag(id: u64, tag: vector<u8>) acquires Registry {
+        Table::add(&mut borrow_global_mut<Registry>(@0x42).tags, id, tag)
+    }
+
+    #[callable]
+    fun tag(id: u64): vector<u8> acquires Registry {
+        *Table::borrow(&borrow_global<Registry>(@0x42).tags, id)
+    }
+
+    #[callable]
+    fun remove_tag(id: u64): vector<u8> acquires Registry {
+        Table::remove(&mut borrow_global_mut<Registry>(@0x42).tags, id)
+    }
+}
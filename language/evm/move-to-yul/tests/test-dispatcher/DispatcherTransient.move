@@ -0,0 +1,20 @@
+#[contract]
+module 0x2::M {
+    use Eth::Transient;
+
+    const LOCK: u64 = 0;
+
+    #[callable]
+    fun guarded(): u64 {
+        assert!(!Transient::load<bool>(LOCK), 1);
+        Transient::store(LOCK, true);
+        let result = 42;
+        Transient::store(LOCK, false);
+        result
+    }
+
+    #[callable, view]
+    fun peek(): u128 {
+        Transient::load<u128>(1)
+    }
+}
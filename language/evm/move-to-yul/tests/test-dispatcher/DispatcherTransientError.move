@@ -0,0 +1,14 @@
+#[contract]
+module 0x2::M {
+    use Eth::Transient;
+
+    #[callable]
+    fun store_bytes(bytes: vector<u8>) {
+        Transient::store(0, bytes)
+    }
+
+    #[callable, view]
+    fun store_in_view() {
+        Transient::store(0, 1)
+    }
+}
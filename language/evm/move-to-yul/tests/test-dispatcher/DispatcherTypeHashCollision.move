@@ -0,0 +1,20 @@
+// Tests error on types with the same type hash.
+#[contract]
+module 0x2::M {
+    use Eth::Evm::{sender, sign};
+
+    // Both `R17697` and `R38378` have the type hash 0xa567755a
+    struct R17697 has key {
+        value: u64
+    }
+
+    struct R38378 has key {
+        value: u64
+    }
+
+    #[callable]
+    fun publish() {
+        move_to(&sign(sender()), R17697 { value: 1 });
+        move_to(&sign(sender()), R38378 { value: 2 })
+    }
+}
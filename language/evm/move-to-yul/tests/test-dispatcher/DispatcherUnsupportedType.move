@@ -0,0 +1,6 @@
+// Tests errors instead of crashes on types which cannot be ABI encoded.
+#[contract]
+module 0x2::M {
+    #[callable]
+    fun consume<T: drop>(_x: T) {}
+}
@@ -0,0 +1,107 @@
+#[contract]
+module 0x2::M {
+    use Std::Vector;
+    use Eth::Evm::sign;
+
+    struct Point has copy, drop, store {
+        x: u64,
+        y: u64,
+    }
+
+    struct Registry has key {
+        ids: vector<u64>,
+        owners: vector<address>,
+        points: vector<Point>,
+    }
+
+    #[callable]
+    fun range(n: u64): vector<u64> {
+        // Grows the vector beyond its initial capacity
+        let v = Vector::empty();
+        let i = 0;
+        while (i < n) {
+            Vector::push_back(&mut v, i);
+            i = i + 1
+        };
+        v
+    }
+
+    #[callable]
+    fun reverse(v: vector<u128>): vector<u128> {
+        let len = Vector::length(&v);
+        let i = 0;
+        while (i < len / 2) {
+            Vector::swap(&mut v, i, len - i - 1);
+            i = i + 1
+        };
+        v
+    }
+
+    #[callable]
+    fun pop_all(v: vector<address>): u64 {
+        let n = 0;
+        while (!Vector::is_empty(&v)) {
+            Vector::pop_back(&mut v);
+            n = n + 1
+        };
+        Vector::destroy_empty(v);
+        n
+    }
+
+    #[callable]
+    fun get(v: vector<u8>, i: u64): u8 {
+        *Vector::borrow(&v, i)
+    }
+
+    #[callable]
+    fun set(v: vector<u64>, i: u64, x: u64): vector<u64> {
+        *Vector::borrow_mut(&mut v, i) = x;
+        v
+    }
+
+    #[callable]
+    fun destroy_empty(v: vector<u64>) {
+        Vector::destroy_empty(v)
+    }
+
+    #[callable]
+    fun publish(ids: vector<u64>) {
+        let points = Vector::empty();
+        Vector::push_back(&mut points, Point { x: 1, y: 2 });
+        move_to(&sign(@0x42), Registry { ids, owners: Vector::empty(), points })
+    }
+
+    #[callable]
+    fun register(id: u64, owner: address, x: u64, y: u64) acquires Registry {
+        let registry = borrow_global_mut<Registry>(@0x42);
+        Vector::push_back(&mut registry.ids, id);
+        Vector::push_back(&mut registry.owners, owner);
+        Vector::push_back(&mut registry.points, Point { x, y });
+    }
+
+    #[callable]
+    fun last(): (u64, u64, address, u64, u64) acquires Registry {
+        let registry = borrow_global<Registry>(@0x42);
+        let len = Vector::length(&registry.ids);
+        let point = Vector::borrow(&registry.points, Vector::length(&registry.points) - 1);
+        (
+            len,
+            *Vector::borrow(&registry.ids, len - 1),
+            *Vector::borrow(&registry.owners, Vector::length(&registry.owners) - 1),
+            point.x,
+            point.y,
+        )
+    }
+
+    #[callable]
+    fun pop_point(): (u64, u64) acquires Registry {
+        let Point { x, y } = Vector::pop_back(&mut borrow_global_mut<Registry>(@0x42).points);
+        (x, y)
+    }
+
+    #[callable]
+    fun unpublish(): vector<u64> acquires Registry {
+        let Registry { ids, owners: _, points: _ } = move_from<Registry>(@0x42);
+        ids
+    }
+}
@@ -5,11 +5,11 @@ use codespan_reporting::{diagnostic::Severity, term::termcolor::Buffer};
 use evm::backend::MemoryVicinity;
 use evm_exec_utils::{compile, exec::Executor};
 use move_command_line_common::testing::EXP_EXT;
-use move_compiler::shared::NumericalAddress;
+use move_compiler::shared::{Flags, NumericalAddress};
 use move_model::{
     model::{FunId, GlobalEnv, QualifiedId},
     options::ModelBuilderOptions,
-    run_model_builder_with_options,
+    run_model_builder_with_options_and_compilation_flags,
 };
 use move_prover_test_utils::{baseline_test::verify_or_update_baseline, extract_test_directives};
 use move_stdlib::move_stdlib_named_addresses;
@@ -32,10 +32,12 @@ fn test_runner(path: &Path) -> datatest_stable::Result<()> {
         "Eth".to_string(),
         NumericalAddress::parse_str("0x2").unwrap(),
     );
-    let env = run_model_builder_with_options(
+    // Build in test mode, so `#[test_only]` helpers are available to the `#[evm_test]` functions
+    let env = run_model_builder_with_options_and_compilation_flags(
         &sources,
         &deps,
         ModelBuilderOptions::default(),
+        Flags::testing(),
         named_address_mapping,
     )?;
     let options = Options::default();
@@ -0,0 +1,22 @@
+/// Module which provides conversions between addresses, numbers and bytes. An address on the EVM is 20 bytes long.
+module Eth::Address {
+    use 0x1::U256::U256;
+
+    /// Returns the address as a number.
+    public native fun to_u256(addr: address): U256;
+
+    /// Returns the address represented by a number. Aborts if the number does not fit in 20 bytes.
+    public native fun from_u256(x: U256): address;
+
+    /// Returns the address as a number. Aborts if the address does not fit in a `u128`.
+    public native fun to_u128(addr: address): u128;
+
+    /// Returns the address represented by a number.
+    public native fun from_u128(x: u128): address;
+
+    /// Returns the 20 bytes of the address.
+    public native fun to_bytes(addr: address): vector<u8>;
+
+    /// Returns the address with the given 20 bytes. Aborts if `bytes` is not 20 bytes long.
+    public native fun from_bytes(bytes: vector<u8>): address;
+}
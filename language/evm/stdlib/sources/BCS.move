@@ -0,0 +1,8 @@
+/// Module which complements `Std::BCS` with the deserialization of values from their binary representation in BCS
+/// (Binary Canonical Serialization).
+module Eth::BCS {
+
+    /// Returns the value whose BCS representation is `bytes`. Aborts if `bytes` is not the representation of a value of
+    /// type `T`, including if bytes follow it. `T` cannot contain signers.
+    native public fun from_bytes<T>(bytes: vector<u8>): T;
+}
@@ -0,0 +1,13 @@
+/// Module which provides the hash functions and signature recovery of the EVM.
+module Eth::Crypto {
+
+    /// Returns the keccak256 hash of the given data, as 32 bytes.
+    public native fun keccak256(data: vector<u8>): vector<u8>;
+
+    /// Returns the sha256 hash of the given data, as 32 bytes.
+    public native fun sha256(data: vector<u8>): vector<u8>;
+
+    /// Recovers the address of the account which signed the 32 bytes `hash`, from the signature `(v, r, s)` where `r`
+    /// and `s` are 32 bytes each. Returns the zero address if the signature is invalid.
+    public native fun ecrecover(hash: vector<u8>, v: u8, r: vector<u8>, s: vector<u8>): address;
+}
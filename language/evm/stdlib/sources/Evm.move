@@ -13,15 +13,72 @@ module Eth::Evm {
     /// TODO: need u256
     public native fun value(): u128;
 
+    /// Returns the number of the current block.
+    public native fun block_number(): u64;
+
+    /// Returns the timestamp of the current block, in seconds since the Unix epoch.
+    public native fun timestamp(): u64;
+
+    /// Returns the identifier of the chain the transaction is executed on.
+    public native fun chain_id(): u64;
+
+    /// Returns the gas remaining for the execution of the transaction.
+    public native fun gas_left(): u64;
+
     /// Returns the balance, in Wei, of any account.
     public native fun balance(addr: address): u128;
 
-    /// Transfers the given amount to the target account.
+    /// Transfers the given amount to the target account, forwarding only a stipend of 2300 gas to it. Reverts with
+    /// the data the target reverted with if the transfer fails.
     public native fun transfer(addr: address, amount: u128);
 
+    /// Like `transfer`, but returns whether the transfer succeeded instead of reverting.
+    public native fun send(addr: address, amount: u128): bool;
+
     /// Emits an event. The type passed for `E` must be annotated with #[event].
     public native fun emit<E>(e: E);
 
+    /// Reverts with the Solidity custom error given by the struct `E`, declared as `error E(fields)` in Solidity. The
+    /// revert data is the selector of the error followed by the ABI encoding of its fields, so that integrators can
+    /// decode it. This function does not return.
+    public native fun revert_with<E>(error: E);
+
+    /// Returns the size, in bytes, of the calldata of the current call.
+    public native fun calldatasize(): u64;
+
+    /// Returns `size` bytes of the calldata, starting at `offset`. Bytes past the end of the calldata
+    /// are zero.
+    public native fun calldatacopy(offset: u64, size: u64): vector<u8>;
+
+    /// Calls the contract at `target` with the given calldata, sending `value` Wei and at most `gas` gas. Returns
+    /// whether the call succeeded, and the data it returned or reverted with.
+    public native fun call(target: address, value: u128, gas: u64, data: vector<u8>): (bool, vector<u8>);
+
+    /// Calls the contract at `target` like `call`, returning the data it returned. If the call fails, reverts with the
+    /// data the callee reverted with, so that the failure propagates to the caller unchanged.
+    public native fun call_or_revert(target: address, value: u128, gas: u64, data: vector<u8>): vector<u8>;
+
+    /// Calls the contract at `target` like `call`, returning the data it returned. If the call fails, aborts with
+    /// `code`, reverting with the Solidity custom error `ExternalCallFailed(uint64 code, bytes returndata)` so that the
+    /// data the callee reverted with remains accessible to the caller.
+    public native fun call_or_abort(target: address, value: u128, gas: u64, data: vector<u8>, code: u64): vector<u8>;
+
+    /// Calls the contract at `target` like `call`, without sending value and reverting any modification of the
+    /// state the callee attempts.
+    public native fun staticcall(target: address, gas: u64, data: vector<u8>): (bool, vector<u8>);
+
+    /// Executes the code of the contract at `target` in the context of the executing contract, with its
+    /// storage, sender and value.
+    public native fun delegatecall(target: address, gas: u64, data: vector<u8>): (bool, vector<u8>);
+
+    /// Returns the ABI encoding of `value`, as the Solidity expression `abi.encode(value)` would. A struct is encoded as a
+    /// tuple of its fields.
+    public native fun abi_encode<T>(value: T): vector<u8>;
+
+    /// Decodes a value from its ABI encoding, as the Solidity expression `abi.decode(data, (T))` would. Aborts if `data`
+    /// is not a valid encoding of a value of type `T`.
+    public native fun abi_decode<T>(data: vector<u8>): T;
+
     /// Creates a signer for the contract's address space.
     public native fun sign(addr: address): signer;
 }
@@ -0,0 +1,50 @@
+/// Module which provides signed 256 bit integers, represented in two's complement like Solidity's `int256`. Arithmetic
+/// operations abort on overflow, like those on unsigned integers.
+module Eth::I256 {
+    use 0x1::U256::U256;
+
+    /// A signed 256 bit integer.
+    native struct I256 has copy, drop, store;
+
+    /// Returns `x` as a signed integer.
+    public native fun from_u128(x: u128): I256;
+
+    /// Returns `x` as a signed integer. Aborts if `x` is larger than the largest `I256`, 2^255 - 1.
+    public native fun from_u256(x: U256): I256;
+
+    /// Returns `x` as an unsigned integer. Aborts if `x` is negative.
+    public native fun to_u256(x: I256): U256;
+
+    /// Returns whether `x` is negative.
+    public native fun is_neg(x: I256): bool;
+
+    /// Returns `-x`. Aborts if `x` is the smallest `I256`, -2^255.
+    public native fun neg(x: I256): I256;
+
+    /// Returns `x + y`. Aborts on overflow.
+    public native fun add(x: I256, y: I256): I256;
+
+    /// Returns `x - y`. Aborts on overflow.
+    public native fun sub(x: I256, y: I256): I256;
+
+    /// Returns `x * y`. Aborts on overflow.
+    public native fun mul(x: I256, y: I256): I256;
+
+    /// Returns `x / y`, rounded towards zero. Aborts if `y` is zero, or on overflow.
+    public native fun div(x: I256, y: I256): I256;
+
+    /// Returns the remainder of `x / y`, which has the sign of `x`. Aborts if `y` is zero.
+    public native fun mod(x: I256, y: I256): I256;
+
+    /// Returns `x < y`.
+    public native fun lt(x: I256, y: I256): bool;
+
+    /// Returns `x > y`.
+    public native fun gt(x: I256, y: I256): bool;
+
+    /// Returns `x <= y`.
+    public native fun le(x: I256, y: I256): bool;
+
+    /// Returns `x >= y`.
+    public native fun ge(x: I256, y: I256): bool;
+}
@@ -0,0 +1,16 @@
+/// Module which provides values set during contract creation and embedded in the deployed code, like Solidity
+/// `immutable` variables. Reading them costs no storage access, which makes them suitable for configuration values
+/// like the owner of a contract.
+///
+/// The values are those of structs declared with the `#[immutable]` attribute, whose fields must be of a primitive
+/// type, `U256` or `I256`. The value of each such struct must be set by the `#[create]` function, otherwise the
+/// creation of the contract aborts.
+module Eth::Immutable {
+
+    /// Sets the value of the `#[immutable]` struct `T`. Can only be called during contract creation.
+    public native fun set<T: copy + drop>(value: T);
+
+    /// Returns the value of the `#[immutable]` struct `T`. Aborts if called during contract creation before the value
+    /// is set.
+    public native fun get<T: copy + drop>(): T;
+}
@@ -0,0 +1,26 @@
+/// Module which provides the precompiled contracts of the EVM for modular exponentiation, the alt_bn128 curve, and
+/// BLAKE2. The hash and signature recovery precompiles are provided by `Eth::Crypto`.
+///
+/// Each function aborts if the precompile fails, e.g. for points not on the curve.
+module Eth::Precompiles {
+    use 0x1::U256::U256;
+
+    /// Returns `pow(base, exponent) % modulus`, where the numbers are encoded in big-endian. The result has the length
+    /// of `modulus`.
+    public native fun modexp(base: vector<u8>, exponent: vector<u8>, modulus: vector<u8>): vector<u8>;
+
+    /// Adds the points `(x1, y1)` and `(x2, y2)` of the alt_bn128 curve.
+    public native fun bn256_add(x1: U256, y1: U256, x2: U256, y2: U256): (U256, U256);
+
+    /// Multiplies the point `(x1, y1)` of the alt_bn128 curve by a scalar.
+    public native fun bn256_mul(x1: U256, y1: U256, scalar: U256): (U256, U256);
+
+    /// Checks the pairing equation of the alt_bn128 curve for `input`, the concatenation of pairs of a point of G1
+    /// (64 bytes) and a point of G2 (128 bytes).
+    public native fun bn256_pairing(input: vector<u8>): bool;
+
+    /// Computes the compression function F of BLAKE2 with the given number of rounds, which must fit in 32 bits, on
+    /// the state `h` (64 bytes), message block `m` (128 bytes) and offset counter `t` (16 bytes). `f` indicates the
+    /// final block. Returns the new state.
+    public native fun blake2f(rounds: u64, h: vector<u8>, m: vector<u8>, t: vector<u8>, f: bool): vector<u8>;
+}
@@ -0,0 +1,53 @@
+/// Module which defines the `String` type, holding a sequence of bytes which is valid UTF-8. A `String` is passed to and
+/// returned from callable functions as an ABI `string`.
+module Eth::String {
+    use Std::Vector;
+
+    /// The bytes are not valid UTF-8.
+    const EINVALID_UTF8: u64 = 1;
+
+    /// A `String` holds a sequence of bytes which is guaranteed to be valid UTF-8.
+    struct String has copy, drop, store {
+        bytes: vector<u8>,
+    }
+
+    /// Returns the string holding `bytes`. Aborts if `bytes` is not valid UTF-8.
+    public fun utf8(bytes: vector<u8>): String {
+        assert!(internal_check_utf8(&bytes), EINVALID_UTF8);
+        String { bytes }
+    }
+
+    /// Returns a reference to the bytes of the string.
+    public fun bytes(s: &String): &vector<u8> {
+        &s.bytes
+    }
+
+    /// Returns the bytes of the string.
+    public fun into_bytes(s: String): vector<u8> {
+        let String { bytes } = s;
+        bytes
+    }
+
+    /// Returns the length of the string, in bytes.
+    public fun length(s: &String): u64 {
+        Vector::length(&s.bytes)
+    }
+
+    /// Returns whether the string is empty.
+    public fun is_empty(s: &String): bool {
+        Vector::is_empty(&s.bytes)
+    }
+
+    /// Appends the string `r` to the string `s`.
+    public fun append(s: &mut String, r: String) {
+        Vector::append(&mut s.bytes, r.bytes)
+    }
+
+    /// Appends `bytes` to the string `s`. Aborts if `bytes` is not valid UTF-8.
+    public fun append_utf8(s: &mut String, bytes: vector<u8>) {
+        append(s, utf8(bytes))
+    }
+
+    /// Returns whether the bytes `v` references are valid UTF-8.
+    native fun internal_check_utf8(v: &vector<u8>): bool;
+}
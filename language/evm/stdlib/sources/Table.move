@@ -0,0 +1,27 @@
+/// Module which provides tables, mapping keys to values like Solidity mappings. The entries of a table are kept in
+/// storage, in a storage group allocated when the table is created. A table can only be kept in storage as part of a
+/// resource.
+///
+/// Keys must be of a primitive type, `U256` or `I256`.
+module Eth::Table {
+    /// A table mapping keys of type `K` to values of type `V`.
+    native struct Table<phantom K, phantom V> has store;
+
+    /// Returns a new, empty table.
+    native public fun empty<K, V>(): Table<K, V>;
+
+    /// Adds the entry mapping `key` to `val` to the table. Aborts if the table already contains `key`.
+    native public fun add<K, V>(table: &mut Table<K, V>, key: K, val: V);
+
+    /// Returns a reference to the value `key` maps to. Aborts if the table does not contain `key`.
+    native public fun borrow<K, V>(table: &Table<K, V>, key: K): &V;
+
+    /// Returns a mutable reference to the value `key` maps to. Aborts if the table does not contain `key`.
+    native public fun borrow_mut<K, V>(table: &mut Table<K, V>, key: K): &mut V;
+
+    /// Returns whether the table contains `key`.
+    native public fun contains<K, V>(table: &Table<K, V>, key: K): bool;
+
+    /// Removes the entry for `key` from the table, and returns its value. Aborts if the table does not contain `key`.
+    native public fun remove<K, V>(table: &mut Table<K, V>, key: K): V;
+}
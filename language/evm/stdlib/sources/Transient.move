@@ -0,0 +1,14 @@
+/// Module which provides access to transient storage (EIP-1153), which is discarded at the end of the transaction. It
+/// is cheaper than storage for values only needed during a transaction, like reentrancy locks.
+///
+/// Values are kept under a key chosen by the program, separately for each type `T`, which must be a primitive type,
+/// `U256` or `I256`.
+module Eth::Transient {
+
+    /// Stores `value` under `key` in transient storage.
+    public native fun store<T: copy + drop>(key: u64, value: T);
+
+    /// Returns the value under `key` in transient storage, or the zero value of `T` if none was stored during the
+    /// transaction.
+    public native fun load<T: copy + drop>(key: u64): T;
+}
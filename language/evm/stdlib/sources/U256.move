@@ -7,12 +7,23 @@ module 0x1::U256 {
     native public fun mul(x: U256, y: U256): U256;
     native public fun div(x: U256, y: U256): U256;
     native public fun mod(x: U256, y: U256): U256;
-    native public fun eq(x: U256, y: U256): U256;
-    native public fun ne(x: U256, y: U256): U256;
-    native public fun gt(x: U256, y: U256): U256;
-    native public fun lt(x: U256, y: U256): U256;
-    native public fun ge(x: U256, y: U256): U256;
-    native public fun le(x: U256, y: U256): U256;
+    native public fun eq(x: U256, y: U256): bool;
+    native public fun ne(x: U256, y: U256): bool;
+    native public fun gt(x: U256, y: U256): bool;
+    native public fun lt(x: U256, y: U256): bool;
+    native public fun ge(x: U256, y: U256): bool;
+    native public fun le(x: U256, y: U256): bool;
     native public fun shl(x: U256, y: u8): U256;
     native public fun shr(x: U256, y: u8): U256;
+    native public fun bitand(x: U256, y: U256): U256;
+    native public fun bitor(x: U256, y: U256): U256;
+    native public fun xor(x: U256, y: U256): U256;
+    native public fun not(x: U256): U256;
+    native public fun from_u8(x: u8): U256;
+    native public fun from_u64(x: u64): U256;
+    native public fun from_u128(x: u128): U256;
+    // The conversions to smaller integers abort if the value does not fit.
+    native public fun to_u8(x: U256): u8;
+    native public fun to_u64(x: U256): u64;
+    native public fun to_u128(x: U256): u128;
 }
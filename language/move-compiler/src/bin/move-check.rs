@@ -46,6 +46,11 @@ pub struct Options {
     )]
     pub named_addresses: Vec<(String, NumericalAddress)>,
 
+    /// A TOML or JSON file of named addresses, merged with those given with `-a`, which take
+    /// precedence
+    #[structopt(name = "ADDRESS_FILE", long = "address-file")]
+    pub address_file: Option<String>,
+
     #[structopt(flatten)]
     pub flags: Flags,
 }
@@ -57,8 +62,15 @@ pub fn main() -> anyhow::Result<()> {
         out_dir,
         flags,
         named_addresses,
+        address_file,
     } = Options::from_args();
-    let named_addr_map = verify_and_create_named_address_mapping(named_addresses)?;
+    let mut all_named_addresses = match &address_file {
+        None => vec![],
+        Some(path) => shared::parse_named_address_file(path)?,
+    };
+    all_named_addresses.retain(|(name, _)| !named_addresses.iter().any(|(n, _)| n == name));
+    all_named_addresses.extend(named_addresses);
+    let named_addr_map = verify_and_create_named_address_mapping(all_named_addresses)?;
     let _files = move_compiler::Compiler::new(
         vec![(source_files, named_addr_map.clone())],
         vec![(dependencies, named_addr_map)],
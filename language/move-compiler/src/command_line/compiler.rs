@@ -573,6 +573,8 @@ fn generate_interface_files_for_deps(
 ) -> anyhow::Result<()> {
     let interface_files_paths =
         generate_interface_files(deps, interface_files_dir_opt, named_address_mapping, true)?;
+    // Compiled modules given as files are only read through their interfaces
+    deps.retain(|(path, _)| !(Path::new(path).is_file() && has_compiled_module_magic_number(path)));
     deps.extend(interface_files_paths);
     Ok(())
 }
@@ -38,6 +38,7 @@ pub struct Program {
 pub enum AttributeValue_ {
     Value(Value),
     ModuleAccess(ModuleAccess),
+    Vector(Vec<AttributeValue>),
 }
 pub type AttributeValue = Spanned<AttributeValue_>;
 
@@ -818,6 +819,11 @@ impl AstDebug for AttributeValue_ {
         match self {
             AttributeValue_::Value(v) => v.ast_debug(w),
             AttributeValue_::ModuleAccess(n) => n.ast_debug(w),
+            AttributeValue_::Vector(vs) => {
+                w.write("[");
+                w.comma(vs, |w, v| v.ast_debug(w));
+                w.write("]");
+            }
         }
     }
 }
@@ -602,6 +602,11 @@ fn attribute_value(
         match avalue_ {
             PV::Value(v) => EV::Value(value(context, v)?),
             PV::ModuleAccess(ma) => EV::ModuleAccess(name_access_chain(context, Access::Type, ma)?),
+            PV::Vector(vs) => EV::Vector(
+                vs.into_iter()
+                    .map(|v| attribute_value(context, v))
+                    .collect::<Option<_>>()?,
+            ),
         },
     ))
 }
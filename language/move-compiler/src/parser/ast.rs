@@ -106,6 +106,7 @@ pub struct UseDecl {
 pub enum AttributeValue_ {
     Value(Value),
     ModuleAccess(NameAccessChain),
+    Vector(Vec<AttributeValue>),
 }
 pub type AttributeValue = Spanned<AttributeValue_>;
 
@@ -1022,6 +1023,11 @@ impl AstDebug for AttributeValue_ {
         match self {
             AttributeValue_::Value(v) => v.ast_debug(w),
             AttributeValue_::ModuleAccess(n) => n.ast_debug(w),
+            AttributeValue_::Vector(vs) => {
+                w.write("[");
+                w.comma(vs, |w, v| v.ast_debug(w));
+                w.write("]");
+            }
         }
     }
 }
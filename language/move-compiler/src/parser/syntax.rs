@@ -512,15 +512,34 @@ fn parse_visibility(context: &mut Context) -> Result<Visibility, Diagnostic> {
         }
     })
 }
-// Parse an attribute value. Either a value literal or a module access
+// Parse an attribute value. Either a value literal, a module access, or a list of values
 //      AttributeValue =
 //          <Value>
 //          | <NameAccessChain>
+//          | "[" Comma<AttributeValue> "]"
 fn parse_attribute_value(context: &mut Context) -> Result<AttributeValue, Diagnostic> {
     if let Some(v) = maybe_parse_value(context)? {
         return Ok(sp(v.loc, AttributeValue_::Value(v)));
     }
 
+    if context.tokens.peek() == Tok::LBracket {
+        let start_loc = context.tokens.start_loc();
+        let values = parse_comma_list(
+            context,
+            Tok::LBracket,
+            Tok::RBracket,
+            parse_attribute_value,
+            "attribute value",
+        )?;
+        let end_loc = context.tokens.previous_end_loc();
+        return Ok(spanned(
+            context.tokens.file_hash(),
+            start_loc,
+            end_loc,
+            AttributeValue_::Vector(values),
+        ));
+    }
+
     let ma = parse_name_access_chain(context, || "attribute name value")?;
     Ok(sp(ma.loc, AttributeValue_::ModuleAccess(ma)))
 }
@@ -194,6 +194,28 @@ pub fn parse_named_address(s: &str) -> anyhow::Result<(String, NumericalAddress)
     Ok((name, addr))
 }
 
+/// Read the named addresses of an address file, which maps the name of each address to its value,
+/// as in `Std = "0x1"`. The file is read as JSON if it has a `.json` extension and as TOML
+/// otherwise.
+pub fn parse_named_address_file(path: &str) -> anyhow::Result<Vec<(String, NumericalAddress)>> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|err| anyhow::format_err!("Unable to read address file {}: {}", path, err))?;
+    let named_addresses: BTreeMap<String, String> = if path.ends_with(".json") {
+        serde_json::from_str(&contents)?
+    } else {
+        toml::from_str(&contents)?
+    };
+    named_addresses
+        .into_iter()
+        .map(|(name, addr)| {
+            let addr = NumericalAddress::parse_str(&addr).map_err(|err| {
+                anyhow::format_err!("Invalid address for {} in {}: {}", name, path, err)
+            })?;
+            Ok((name, addr))
+        })
+        .collect()
+}
+
 impl PartialOrd for NumericalAddress {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         Some(self.cmp(other))
@@ -516,8 +538,19 @@ pub mod known_attributes {
         TestOnly,
         // Is a test that will be run
         Test,
+        // Is a test that will be run several times, with randomly generated arguments
+        RandomTest,
         // This test is expected to fail
         ExpectedFailure,
+        // This test runs on the storage state produced by a fixture shared with other tests
+        TestGroup,
+        // This test runs on a storage which fails to load some resources or modules
+        StorageFault,
+        // This test is expected to emit events of the given types
+        ExpectedEvents,
+        // Must not abort on the storage state left by each passing test, when invariants are
+        // checked
+        TestInvariant,
     }
 
     impl fmt::Display for AttributePosition {
@@ -540,10 +573,17 @@ pub mod known_attributes {
         pub fn resolve(attribute_str: impl AsRef<str>) -> Option<Self> {
             Some(match attribute_str.as_ref() {
                 TestingAttribute::TEST => Self::Testing(TestingAttribute::Test),
+                TestingAttribute::RANDOM_TEST => Self::Testing(TestingAttribute::RandomTest),
                 TestingAttribute::TEST_ONLY => Self::Testing(TestingAttribute::TestOnly),
                 TestingAttribute::EXPECTED_FAILURE => {
                     Self::Testing(TestingAttribute::ExpectedFailure)
                 }
+                TestingAttribute::TEST_GROUP => Self::Testing(TestingAttribute::TestGroup),
+                TestingAttribute::STORAGE_FAULT => Self::Testing(TestingAttribute::StorageFault),
+                TestingAttribute::EXPECTED_EVENTS => {
+                    Self::Testing(TestingAttribute::ExpectedEvents)
+                }
+                TestingAttribute::TEST_INVARIANT => Self::Testing(TestingAttribute::TestInvariant),
                 _ => return None,
             })
         }
@@ -563,15 +603,35 @@ pub mod known_attributes {
 
     impl TestingAttribute {
         pub const TEST: &'static str = "test";
+        pub const RANDOM_TEST: &'static str = "random_test";
         pub const EXPECTED_FAILURE: &'static str = "expected_failure";
         pub const TEST_ONLY: &'static str = "test_only";
+        pub const TEST_GROUP: &'static str = "test_group";
+        pub const STORAGE_FAULT: &'static str = "storage_fault";
+        pub const EXPECTED_EVENTS: &'static str = "expected_events";
+        pub const TEST_INVARIANT: &'static str = "test_invariant";
         pub const CODE_ASSIGNMENT_NAME: &'static str = "abort_code";
+        pub const LOCATION_ASSIGNMENT_NAME: &'static str = "location";
+        pub const ENV_ASSIGNMENT_NAME: &'static str = "env";
+        pub const TAGS_ASSIGNMENT_NAME: &'static str = "tags";
+        pub const CASES_ASSIGNMENT_NAME: &'static str = "cases";
+        pub const DATA_ASSIGNMENT_NAME: &'static str = "data";
+        pub const DEPENDS_ON_ASSIGNMENT_NAME: &'static str = "depends_on";
+        pub const FIXTURE_ASSIGNMENT_NAME: &'static str = "fixture";
+        pub const FAIL_LOAD_ASSIGNMENT_NAME: &'static str = "fail_load";
+        pub const MISSING_MODULE_ASSIGNMENT_NAME: &'static str = "missing_module";
+        pub const EVENT_TYPES_ASSIGNMENT_NAME: &'static str = "types";
 
         pub const fn name(&self) -> &str {
             match self {
                 Self::Test => Self::TEST,
+                Self::RandomTest => Self::RANDOM_TEST,
                 Self::TestOnly => Self::TEST_ONLY,
                 Self::ExpectedFailure => Self::EXPECTED_FAILURE,
+                Self::TestGroup => Self::TEST_GROUP,
+                Self::StorageFault => Self::STORAGE_FAULT,
+                Self::ExpectedEvents => Self::EXPECTED_EVENTS,
+                Self::TestInvariant => Self::TEST_INVARIANT,
             }
         }
 
@@ -592,10 +652,22 @@ pub mod known_attributes {
                 Lazy::new(|| IntoIterator::into_iter([AttributePosition::Function]).collect());
             static EXPECTED_FAILURE_POSITIONS: Lazy<BTreeSet<AttributePosition>> =
                 Lazy::new(|| IntoIterator::into_iter([AttributePosition::Function]).collect());
+            static TEST_GROUP_POSITIONS: Lazy<BTreeSet<AttributePosition>> =
+                Lazy::new(|| IntoIterator::into_iter([AttributePosition::Function]).collect());
+            static STORAGE_FAULT_POSITIONS: Lazy<BTreeSet<AttributePosition>> =
+                Lazy::new(|| IntoIterator::into_iter([AttributePosition::Function]).collect());
+            static EXPECTED_EVENTS_POSITIONS: Lazy<BTreeSet<AttributePosition>> =
+                Lazy::new(|| IntoIterator::into_iter([AttributePosition::Function]).collect());
+            static TEST_INVARIANT_POSITIONS: Lazy<BTreeSet<AttributePosition>> =
+                Lazy::new(|| IntoIterator::into_iter([AttributePosition::Function]).collect());
             match self {
                 TestingAttribute::TestOnly => &*TEST_ONLY_POSITIONS,
-                TestingAttribute::Test => &*TEST_POSITIONS,
+                TestingAttribute::Test | TestingAttribute::RandomTest => &*TEST_POSITIONS,
                 TestingAttribute::ExpectedFailure => &*EXPECTED_FAILURE_POSITIONS,
+                TestingAttribute::TestGroup => &*TEST_GROUP_POSITIONS,
+                TestingAttribute::StorageFault => &*STORAGE_FAULT_POSITIONS,
+                TestingAttribute::ExpectedEvents => &*EXPECTED_EVENTS_POSITIONS,
+                TestingAttribute::TestInvariant => &*TEST_INVARIANT_POSITIONS,
             }
         }
     }
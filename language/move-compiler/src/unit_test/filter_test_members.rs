@@ -28,6 +28,10 @@ impl<'env> Context<'env> {
 const UNIT_TEST_MODULE_NAME: &str = "UnitTest";
 const STDLIB_ADDRESS_NAME: &str = "Std";
 
+/// The name of the function inserted into modules compiled in test mode, so that they fail to link
+/// on a VM not running in test mode.
+pub const UNIT_TEST_POISON_FUN_NAME: &str = "unit_test_poison";
+
 // This filters out all test, and test-only annotated module member from `prog` if the `test` flag
 // in `compilation_env` is not set. If the test flag is set, no filtering is performed, and instead
 // a test plan is created for use by the testing framework.
@@ -257,7 +261,7 @@ fn insert_test_poison(context: &mut Context, mloc: Loc, members: &mut Vec<P::Mod
         visibility: P::Visibility::Internal,
         acquires: vec![],
         signature,
-        name: P::FunctionName(sp(mloc, "unit_test_poison".into())),
+        name: P::FunctionName(sp(mloc, UNIT_TEST_POISON_FUN_NAME.into())),
         body: sp(
             mloc,
             P::FunctionBody_::Defined((
@@ -298,19 +302,29 @@ fn filter_tests_from_module_member(
 }
 
 // A module member should be removed if:
-// * It is annotated as a test function (test_only, test, abort) and test mode is not set; or
-// * If it is a library and is annotated as #[test]
+// * It is annotated as a test function (test_only, test, random_test, test_invariant, abort) and
+//   test mode is not set; or
+// * If it is a library and is annotated as #[test] or #[random_test]
 fn should_remove_node(env: &CompilationEnv, attrs: &[P::Attributes], is_source_def: bool) -> bool {
     use known_attributes::TestingAttribute;
     let flattened_attrs: Vec<_> = attrs.iter().flat_map(test_attributes).collect();
-    let is_test_only = flattened_attrs
-        .iter()
-        .any(|attr| matches!(attr.1, TestingAttribute::Test | TestingAttribute::TestOnly));
+    let is_test_only = flattened_attrs.iter().any(|attr| {
+        matches!(
+            attr.1,
+            TestingAttribute::Test
+                | TestingAttribute::RandomTest
+                | TestingAttribute::TestOnly
+                | TestingAttribute::TestInvariant
+        )
+    });
     is_test_only && !env.flags().is_testing()
         || (!is_source_def
-            && flattened_attrs
-                .iter()
-                .any(|attr| attr.1 == TestingAttribute::Test))
+            && flattened_attrs.iter().any(|attr| {
+                matches!(
+                    attr.1,
+                    TestingAttribute::Test | TestingAttribute::RandomTest
+                )
+            }))
 }
 
 fn test_attributes(attrs: &P::Attributes) -> Vec<(Loc, known_attributes::TestingAttribute)> {
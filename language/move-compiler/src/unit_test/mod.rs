@@ -10,7 +10,7 @@ use move_core_types::{
     account_address::AccountAddress, identifier::Identifier, language_storage::ModuleId,
     value::MoveValue,
 };
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 pub mod filter_test_members;
 pub mod plan_builder;
@@ -28,6 +28,9 @@ pub struct TestPlan {
 pub struct ModuleTestPlan {
     pub module_id: ModuleId,
     pub tests: BTreeMap<TestName, TestCase>,
+    // functions of the module declared with #[test_invariant], which may be checked against the
+    // storage state left by each passing test
+    pub invariants: BTreeSet<TestName>,
 }
 
 #[derive(Debug, Clone)]
@@ -35,6 +38,57 @@ pub struct TestCase {
     pub test_name: TestName,
     pub arguments: Vec<MoveValue>,
     pub expected_failure: Option<ExpectedFailure>,
+    // key/value configuration made available to the test through `UnitTest::config`
+    pub env: BTreeMap<Vec<u8>, Vec<u8>>,
+    // tags of the test, used to select which tests to run
+    pub tags: BTreeSet<String>,
+    // fixture of the test's group, run once before the tests of the group to set up their storage
+    pub fixture: Option<TestName>,
+    // failures the storage the test runs on is configured to simulate
+    pub storage_faults: StorageFaults,
+    // arguments of a random test, by parameter name, which are generated anew by the test runner
+    // for each run of the test unless given in its attribute
+    pub random_arguments: Option<Vec<(String, RandomArgument)>>,
+    // types of the events the test is expected to emit, identified by their module and name, in
+    // the order they are emitted
+    pub expected_events: Option<Vec<(ModuleId, Identifier)>>,
+    // file, relative to the source file declaring the test, whose rows are the arguments of the
+    // cases of a data-driven test, read and decoded by the test runner
+    pub data_file: Option<String>,
+    // tests of the same module, by function name, which must pass for the test to be run. They
+    // are run before the test, which is skipped if any of them fails
+    pub depends_on: BTreeSet<TestName>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StorageFaults {
+    // resources, identified by their module and name, whose loads fail for any type arguments
+    pub failing_resources: BTreeSet<(ModuleId, Identifier)>,
+    // modules reported as missing from storage
+    pub missing_modules: BTreeSet<ModuleId>,
+}
+
+impl StorageFaults {
+    pub fn is_empty(&self) -> bool {
+        self.failing_resources.is_empty() && self.missing_modules.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum RandomArgument {
+    // a value assigned in the attribute of the test
+    Given(MoveValue),
+    // a value of this type generated by the test runner
+    Random(RandomType),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RandomType {
+    U8,
+    U64,
+    U128,
+    Address,
+    VectorU8,
 }
 
 #[derive(Debug, Clone)]
@@ -43,6 +97,15 @@ pub enum ExpectedFailure {
     Expected,
     // expected failure, abort code checked
     ExpectedWithCode(u64),
+    // expected failure, abort code and location of the abort checked
+    ExpectedWithCodeAndLocation(u64, AbortLocation),
+}
+
+#[derive(Debug, Clone)]
+pub struct AbortLocation {
+    pub module_id: ModuleId,
+    // function of the module the abort is expected in, if any function of the module will do
+    pub function: Option<Identifier>,
 }
 
 impl ModuleTestPlan {
@@ -50,11 +113,16 @@ impl ModuleTestPlan {
         addr: &NumericalAddress,
         module_name: &str,
         tests: BTreeMap<TestName, TestCase>,
+        invariants: BTreeSet<TestName>,
     ) -> Self {
         let addr = AccountAddress::new((*addr).into_bytes());
         let name = Identifier::new(module_name.to_owned()).unwrap();
         let module_id = ModuleId::new(addr, name);
-        ModuleTestPlan { module_id, tests }
+        ModuleTestPlan {
+            module_id,
+            tests,
+            invariants,
+        }
     }
 }
 
@@ -5,16 +5,26 @@ use crate::{
     cfgir::ast as G,
     diag,
     expansion::ast::{self as E, Address, ModuleIdent, ModuleIdent_},
+    hlir::ast as H,
+    naming::ast as N,
+    parser::ast::{ModuleName, Var},
     shared::{
         known_attributes::{KnownAttribute, TestingAttribute},
-        CompilationEnv, Identifier, NumericalAddress,
+        shortest_cycle, CompilationEnv, Identifier, NumericalAddress,
     },
-    unit_test::{ExpectedFailure, ModuleTestPlan, TestCase},
+    unit_test::{
+        AbortLocation, ExpectedFailure, ModuleTestPlan, RandomArgument, RandomType, StorageFaults,
+        TestCase, TestName,
+    },
+};
+use move_core_types::{
+    account_address::AccountAddress as MoveAddress, identifier::Identifier as MoveIdentifier,
+    language_storage::ModuleId, value::MoveValue,
 };
-use move_core_types::{account_address::AccountAddress as MoveAddress, value::MoveValue};
 use move_ir_types::location::Loc;
 use move_symbol_pool::Symbol;
-use std::collections::BTreeMap;
+use petgraph::{algo::toposort as petgraph_toposort, graphmap::DiGraphMap};
+use std::collections::{BTreeMap, BTreeSet};
 
 struct Context<'env> {
     env: &'env mut CompilationEnv,
@@ -30,6 +40,14 @@ impl<'env> Context<'env> {
     fn resolve_address(&self, addr: &Address) -> NumericalAddress {
         (*addr).into_addr_bytes()
     }
+
+    fn resolve_module_id(
+        &self,
+        sp!(_, ModuleIdent_ { address, module }): &ModuleIdent,
+    ) -> ModuleId {
+        let addr = MoveAddress::new(self.resolve_address(address).into_bytes());
+        ModuleId::new(addr, MoveIdentifier::new(module.0.value.as_str()).unwrap())
+    }
 }
 
 //***************************************************************************
@@ -64,27 +82,125 @@ fn construct_module_test_plan(
     let tests: BTreeMap<_, _> = module
         .functions
         .iter()
-        .filter_map(|(loc, fn_name, func)| {
-            build_test_info(context, loc, fn_name, func)
-                .map(|test_case| (fn_name.to_string(), test_case))
+        .flat_map(|(loc, fn_name, func)| {
+            build_test_info(context, &module_ident, module, loc, fn_name, func)
         })
         .collect();
+    check_dependency_cycles(context, module, &tests);
+    let invariants: BTreeSet<_> = module
+        .functions
+        .iter()
+        .filter_map(|(loc, fn_name, func)| build_invariant_info(context, loc, fn_name, func))
+        .collect();
 
-    if tests.is_empty() {
+    if tests.is_empty() && invariants.is_empty() {
         None
     } else {
         let sp!(_, ModuleIdent_ { address, module }) = &module_ident;
         let addr_bytes = context.resolve_address(address);
-        Some(ModuleTestPlan::new(&addr_bytes, &module.0.value, tests))
+        Some(ModuleTestPlan::new(
+            &addr_bytes,
+            &module.0.value,
+            tests,
+            invariants,
+        ))
     }
 }
 
+// Checks that a #[test_invariant] function can be run on its own against the storage state left
+// by a test, and returns its name.
+fn build_invariant_info(
+    context: &mut Context,
+    fn_loc: Loc,
+    fn_name: &str,
+    function: &G::Function,
+) -> Option<TestName> {
+    let get_attrs = |attr: TestingAttribute| {
+        function
+            .attributes
+            .get_(&E::AttributeName_::Known(KnownAttribute::Testing(attr)))
+    };
+    let invariant_attribute = get_attrs(TestingAttribute::TestInvariant)?;
+    if let Some(test_attribute) =
+        get_attrs(TestingAttribute::Test).or_else(|| get_attrs(TestingAttribute::RandomTest))
+    {
+        let msg = "Function annotated as both a test and #[test_invariant]. You need to declare \
+                   it as either one or the other";
+        context.env.add_diag(diag!(
+            Attributes::InvalidUsage,
+            (invariant_attribute.loc, msg),
+            (test_attribute.loc, "Previously annotated here"),
+        ));
+        return None;
+    }
+    if !function.signature.parameters.is_empty() || !function.signature.type_parameters.is_empty() {
+        let msg = format!(
+            "Invalid invariant '{}'. An invariant must be a function without parameters or type \
+             parameters",
+            fn_name
+        );
+        context
+            .env
+            .add_diag(diag!(Attributes::InvalidTest, (fn_loc, msg)));
+        return None;
+    }
+    Some(fn_name.to_string())
+}
+
+// Reports a cycle in the dependencies between the tests of `module`, as none of the tests of the
+// cycle could ever be run.
+fn check_dependency_cycles(
+    context: &mut Context,
+    module: &G::ModuleDefinition,
+    tests: &BTreeMap<TestName, TestCase>,
+) {
+    let mut graph = DiGraphMap::new();
+    for test_case in tests.values() {
+        graph.add_node(&test_case.test_name);
+        for dependency in &test_case.depends_on {
+            graph.add_edge(&test_case.test_name, dependency, ());
+        }
+    }
+    let cycle_node = match petgraph_toposort(&graph, None) {
+        Ok(_) => return,
+        Err(cycle) => cycle.node_id(),
+    };
+    let fn_loc = |name: &String| {
+        *module
+            .functions
+            .get_loc_(&Symbol::from(name.as_str()))
+            .unwrap()
+    };
+    // Start the cycle at its earliest declared test, which is where it is reported
+    let mut cycle = shortest_cycle(&graph, cycle_node);
+    cycle.pop();
+    let start = (0..cycle.len())
+        .min_by_key(|idx| fn_loc(cycle[*idx]))
+        .unwrap();
+    cycle.rotate_left(start);
+    cycle.push(cycle[0]);
+
+    let msg = format!(
+        "Cyclic test dependencies: {}. Tests which depend on each other can never be run",
+        cycle
+            .iter()
+            .map(|name| format!("'{}'", name))
+            .collect::<Vec<_>>()
+            .join(" depends on ")
+    );
+    context
+        .env
+        .add_diag(diag!(Attributes::InvalidTest, (fn_loc(cycle[0]), msg)));
+}
+
 fn build_test_info<'func>(
     context: &mut Context,
+    module_ident: &ModuleIdent,
+    module: &G::ModuleDefinition,
     fn_loc: Loc,
     fn_name: &str,
     function: &'func G::Function,
-) -> Option<TestCase> {
+) -> Vec<(TestName, TestCase)> {
     let get_attrs = |attr: TestingAttribute| -> Option<&'func E::Attribute> {
         function
             .attributes
@@ -95,10 +211,14 @@ fn build_test_info<'func>(
     const IN_THIS_TEST_MSG: &str = "Error found in this test";
 
     let test_attribute_opt = get_attrs(TestingAttribute::Test);
+    let random_test_attribute_opt = get_attrs(TestingAttribute::RandomTest);
     let abort_attribute_opt = get_attrs(TestingAttribute::ExpectedFailure);
     let test_only_attribute_opt = get_attrs(TestingAttribute::TestOnly);
+    let test_group_attribute_opt = get_attrs(TestingAttribute::TestGroup);
+    let storage_fault_attribute_opt = get_attrs(TestingAttribute::StorageFault);
+    let expected_events_attribute_opt = get_attrs(TestingAttribute::ExpectedEvents);
 
-    let test_attribute = match test_attribute_opt {
+    let test_attribute = match test_attribute_opt.or(random_test_attribute_opt) {
         None => {
             // expected failures cannot be annotated on non-#[test] functions
             if let Some(abort_attribute) = abort_attribute_opt {
@@ -111,11 +231,58 @@ fn build_test_info<'func>(
                     (abort_attribute.loc, abort_msg),
                 ))
             }
-            return None;
+            // neither can test groups
+            if let Some(group_attribute) = test_group_attribute_opt {
+                let fn_msg = "Only functions defined as a test with #[test] can also have a \
+                              #[test_group] attribute";
+                let group_msg = "Attributed as #[test_group] here";
+                context.env.add_diag(diag!(
+                    Attributes::InvalidUsage,
+                    (fn_loc, fn_msg),
+                    (group_attribute.loc, group_msg),
+                ))
+            }
+            // nor storage faults
+            if let Some(fault_attribute) = storage_fault_attribute_opt {
+                let fn_msg = "Only functions defined as a test with #[test] can also have a \
+                              #[storage_fault] attribute";
+                let fault_msg = "Attributed as #[storage_fault] here";
+                context.env.add_diag(diag!(
+                    Attributes::InvalidUsage,
+                    (fn_loc, fn_msg),
+                    (fault_attribute.loc, fault_msg),
+                ))
+            }
+            // nor expected events
+            if let Some(events_attribute) = expected_events_attribute_opt {
+                let fn_msg = "Only functions defined as a test with #[test] can also have an \
+                              #[expected_events] attribute";
+                let events_msg = "Attributed as #[expected_events] here";
+                context.env.add_diag(diag!(
+                    Attributes::InvalidUsage,
+                    (fn_loc, fn_msg),
+                    (events_attribute.loc, events_msg),
+                ))
+            }
+            return vec![];
         }
         Some(test_attribute) => test_attribute,
     };
 
+    // A #[test] function cannot also be a #[random_test]
+    if let (Some(test_attribute), Some(random_test_attribute)) =
+        (test_attribute_opt, random_test_attribute_opt)
+    {
+        let msg = "Function annotated as both #[test(...)] and #[random_test(...)]. You need to \
+                   declare it as either one or the other";
+        context.env.add_diag(diag!(
+            Attributes::InvalidUsage,
+            (random_test_attribute.loc, msg),
+            (test_attribute.loc, PREVIOUSLY_ANNOTATED_MSG),
+            (fn_loc, IN_THIS_TEST_MSG),
+        ))
+    }
+
     // A #[test] function cannot also be annotated #[test_only]
     if let Some(test_only_attribute) = test_only_attribute_opt {
         let msg = "Function annotated as both #[test(...)] and #[test_only]. You need to declare \
@@ -129,8 +296,64 @@ fn build_test_info<'func>(
     }
 
     let test_annotation_params = parse_test_attribute(context, test_attribute, 0);
+    let env = parse_test_env(context, test_attribute);
+    let tags = parse_test_tags(context, test_attribute);
+    let cases = parse_test_cases(context, test_attribute, &function.signature.parameters);
+    let data_file = parse_test_data(context, test_attribute);
+    let depends_on =
+        parse_test_dependencies(context, module_ident, module, fn_name, test_attribute);
+    let random_arguments = random_test_attribute_opt.map(|_| {
+        random_test_arguments(
+            context,
+            &test_annotation_params,
+            &function.signature.parameters,
+        )
+    });
+    if let (Some(_), Some(random_test_attribute)) = (&cases, random_test_attribute_opt) {
+        let msg = format!(
+            "Random tests cannot have #[test({}=...)]. Their arguments are generated instead",
+            TestingAttribute::CASES_ASSIGNMENT_NAME
+        );
+        context.env.add_diag(diag!(
+            Attributes::InvalidTest,
+            (random_test_attribute.loc, msg),
+            (fn_loc, IN_THIS_TEST_MSG),
+        ))
+    }
+    if cases.is_some() && !test_annotation_params.is_empty() {
+        let msg = format!(
+            "Test parameters cannot be both assigned by name and in #[test({}=...)]",
+            TestingAttribute::CASES_ASSIGNMENT_NAME
+        );
+        context.env.add_diag(diag!(
+            Attributes::InvalidTest,
+            (test_attribute.loc, msg),
+            (fn_loc, IN_THIS_TEST_MSG),
+        ))
+    }
+    if data_file.is_some()
+        && (cases.is_some() || random_arguments.is_some() || !test_annotation_params.is_empty())
+    {
+        let msg = format!(
+            "The parameters of a test with #[test({}=...)] are read from its data file. They \
+             cannot also be assigned in its attribute or generated",
+            TestingAttribute::DATA_ASSIGNMENT_NAME
+        );
+        context.env.add_diag(diag!(
+            Attributes::InvalidTest,
+            (test_attribute.loc, msg),
+            (fn_loc, IN_THIS_TEST_MSG),
+        ))
+    }
     let mut arguments = Vec::new();
-    for (var, _) in &function.signature.parameters {
+    // The arguments of a parameterized, data-driven or random test are given by each of its cases,
+    // read from its data file, or generated for each of its runs, instead
+    let parameters: &[_] = if cases.is_none() && data_file.is_none() && random_arguments.is_none() {
+        &function.signature.parameters[..]
+    } else {
+        &[]
+    };
+    for (var, _) in parameters {
         match test_annotation_params.get(&var.value()) {
             Some(value) => arguments.push(value.clone()),
             None => {
@@ -148,14 +371,57 @@ fn build_test_info<'func>(
 
     let expected_failure = match abort_attribute_opt {
         None => None,
-        Some(abort_attribute) => parse_failure_attribute(context, abort_attribute),
+        Some(abort_attribute) => {
+            parse_failure_attribute(context, module_ident, module, abort_attribute)
+        }
+    };
+
+    let fixture = match test_group_attribute_opt {
+        None => None,
+        Some(group_attribute) => {
+            parse_test_group_attribute(context, module_ident, module, group_attribute)
+        }
+    };
+
+    let storage_faults = match storage_fault_attribute_opt {
+        None => StorageFaults::default(),
+        Some(fault_attribute) => {
+            parse_storage_fault_attribute(context, module_ident, module, fault_attribute)
+        }
     };
 
-    Some(TestCase {
+    let expected_events = expected_events_attribute_opt.and_then(|events_attribute| {
+        parse_expected_events_attribute(context, module_ident, module, events_attribute)
+    });
+
+    let test_case = TestCase {
         test_name: fn_name.to_string(),
         arguments,
         expected_failure,
-    })
+        env,
+        tags,
+        fixture,
+        storage_faults,
+        random_arguments,
+        expected_events,
+        data_file,
+        depends_on,
+    };
+    match cases {
+        None => vec![(fn_name.to_string(), test_case)],
+        // Each case is a test of its own, named after the function and the index of the case
+        Some(cases) => cases
+            .into_iter()
+            .enumerate()
+            .map(|(idx, arguments)| {
+                let case = TestCase {
+                    arguments,
+                    ..test_case.clone()
+                };
+                (format!("{}[{}]", fn_name, idx), case)
+            })
+            .collect(),
+    }
 }
 
 //***************************************************************************
@@ -179,11 +445,26 @@ fn parse_test_attribute(
         }
         EA::Name(nm) => {
             assert!(
-                nm.value.as_str() == TestingAttribute::Test.name() && depth == 0,
+                (nm.value.as_str() == TestingAttribute::Test.name()
+                    || nm.value.as_str() == TestingAttribute::RandomTest.name())
+                    && depth == 0,
                 "ICE: We should only be parsing a raw test attribute"
             );
             BTreeMap::new()
         }
+        // The test environment, tags, cases, data file and dependencies are parsed separately by
+        // `parse_test_env`, `parse_test_tags`, `parse_test_cases`, `parse_test_data` and
+        // `parse_test_dependencies`
+        EA::Assigned(nm, _)
+            if depth == 1
+                && (nm.value.as_str() == TestingAttribute::ENV_ASSIGNMENT_NAME
+                    || nm.value.as_str() == TestingAttribute::TAGS_ASSIGNMENT_NAME
+                    || nm.value.as_str() == TestingAttribute::CASES_ASSIGNMENT_NAME
+                    || nm.value.as_str() == TestingAttribute::DATA_ASSIGNMENT_NAME
+                    || nm.value.as_str() == TestingAttribute::DEPENDS_ON_ASSIGNMENT_NAME) =>
+        {
+            BTreeMap::new()
+        }
         EA::Assigned(nm, attr_value) => {
             if depth != 1 {
                 context.env.add_diag(diag!(
@@ -211,7 +492,9 @@ fn parse_test_attribute(
         }
         EA::Parameterized(nm, attributes) => {
             assert!(
-                nm.value.as_str() == TestingAttribute::Test.name() && depth == 0,
+                (nm.value.as_str() == TestingAttribute::Test.name()
+                    || nm.value.as_str() == TestingAttribute::RandomTest.name())
+                    && depth == 0,
                 "ICE: We should only be parsing a raw test attribute"
             );
             attributes
@@ -222,11 +505,426 @@ fn parse_test_attribute(
     }
 }
 
+// Parses the `env = b"KEY=VALUE,..."` assignment of a #[test(...)] attribute into the key/value
+// configuration of the test.
+fn parse_test_env(
+    context: &mut Context,
+    sp!(_, test_attribute): &E::Attribute,
+) -> BTreeMap<Vec<u8>, Vec<u8>> {
+    use E::{AttributeValue_ as EAV, Attribute_ as EA, Value_ as EV};
+
+    let attributes = match test_attribute {
+        EA::Parameterized(_, attributes) => attributes,
+        _ => return BTreeMap::new(),
+    };
+    let mut env = BTreeMap::new();
+    for (_, _, attr) in attributes {
+        let (aloc, value) = match attr {
+            sp!(aloc, EA::Assigned(sp!(_, nm), value))
+                if nm.as_str() == TestingAttribute::ENV_ASSIGNMENT_NAME =>
+            {
+                (*aloc, value)
+            }
+            _ => continue,
+        };
+        let bytes = match &**value {
+            sp!(_, EAV::Value(sp!(_, EV::Bytearray(bytes)))) => bytes,
+            sp!(vloc, _) => {
+                let msg = format!(
+                    "Expected a byte string of comma separated KEY=VALUE pairs, as in \
+                     #[test({}=b\"KEY=VALUE\")]",
+                    TestingAttribute::ENV_ASSIGNMENT_NAME
+                );
+                context.env.add_diag(diag!(
+                    Attributes::InvalidValue,
+                    (*vloc, "Invalid value in test environment assignment"),
+                    (aloc, msg),
+                ));
+                continue;
+            }
+        };
+        for pair in bytes.split(|b| *b == b',') {
+            match pair.iter().position(|b| *b == b'=') {
+                Some(idx) => {
+                    env.insert(pair[..idx].to_vec(), pair[idx + 1..].to_vec());
+                }
+                None => {
+                    let msg = format!(
+                        "Invalid test environment entry '{}'. Expected an entry of the form \
+                         KEY=VALUE",
+                        String::from_utf8_lossy(pair)
+                    );
+                    context
+                        .env
+                        .add_diag(diag!(Attributes::InvalidValue, (value.loc, msg)));
+                }
+            }
+        }
+    }
+    env
+}
+
+// Parses the `tags = [b"TAG", ...]` assignment of a #[test(...)] attribute into the tags of the
+// test.
+fn parse_test_tags(
+    context: &mut Context,
+    sp!(_, test_attribute): &E::Attribute,
+) -> BTreeSet<String> {
+    use E::{AttributeValue_ as EAV, Attribute_ as EA, Value_ as EV};
+
+    let attributes = match test_attribute {
+        EA::Parameterized(_, attributes) => attributes,
+        _ => return BTreeSet::new(),
+    };
+    let expected_msg = format!(
+        "Expected a list of byte strings, as in #[test({}=[b\"TAG\", ...])]",
+        TestingAttribute::TAGS_ASSIGNMENT_NAME
+    );
+    let mut tags = BTreeSet::new();
+    for (_, _, attr) in attributes {
+        let (aloc, value) = match attr {
+            sp!(aloc, EA::Assigned(sp!(_, nm), value))
+                if nm.as_str() == TestingAttribute::TAGS_ASSIGNMENT_NAME =>
+            {
+                (*aloc, value)
+            }
+            _ => continue,
+        };
+        let values = match &**value {
+            sp!(_, EAV::Vector(values)) => values,
+            sp!(vloc, _) => {
+                context.env.add_diag(diag!(
+                    Attributes::InvalidValue,
+                    (*vloc, "Invalid value in test tags assignment"),
+                    (aloc, expected_msg.clone()),
+                ));
+                continue;
+            }
+        };
+        for value in values {
+            match value {
+                sp!(vloc, EAV::Value(sp!(_, EV::Bytearray(bytes)))) => {
+                    match std::str::from_utf8(bytes) {
+                        Ok(tag) => {
+                            tags.insert(tag.to_string());
+                        }
+                        Err(_) => context.env.add_diag(diag!(
+                            Attributes::InvalidValue,
+                            (*vloc, "Invalid test tag. Tags must be valid UTF-8"),
+                        )),
+                    }
+                }
+                sp!(vloc, _) => context.env.add_diag(diag!(
+                    Attributes::InvalidValue,
+                    (*vloc, "Invalid test tag. Expected a byte string"),
+                )),
+            }
+        }
+    }
+    tags
+}
+
+// Parses the `cases = [[<value>, ...], ...]` assignment of a #[test(...)] attribute into the
+// arguments of each case of a parameterized test, with a value for each parameter of the test.
+// Invalid cases are reported and left out.
+fn parse_test_cases(
+    context: &mut Context,
+    sp!(_, test_attribute): &E::Attribute,
+    parameters: &[(Var, H::SingleType)],
+) -> Option<Vec<Vec<MoveValue>>> {
+    use E::{AttributeValue_ as EAV, Attribute_ as EA};
+
+    let attributes = match test_attribute {
+        EA::Parameterized(_, attributes) => attributes,
+        _ => return None,
+    };
+    let (aloc, value) = attributes.iter().find_map(|(_, _, attr)| match attr {
+        sp!(aloc, EA::Assigned(sp!(_, nm), value))
+            if nm.as_str() == TestingAttribute::CASES_ASSIGNMENT_NAME =>
+        {
+            Some((*aloc, value))
+        }
+        _ => None,
+    })?;
+    let expected_msg = format!(
+        "Expected a list of cases, each a list with a value for each parameter of the test, as \
+         in #[test({}=[[<value>, ...], ...])]",
+        TestingAttribute::CASES_ASSIGNMENT_NAME
+    );
+    let cases = match &**value {
+        sp!(_, EAV::Vector(cases)) => cases,
+        sp!(vloc, _) => {
+            context.env.add_diag(diag!(
+                Attributes::InvalidValue,
+                (*vloc, "Invalid value in test cases assignment"),
+                (aloc, expected_msg),
+            ));
+            return Some(vec![]);
+        }
+    };
+    let mut all_arguments = vec![];
+    for case in cases {
+        let values = match case {
+            sp!(_, EAV::Vector(values)) => values,
+            sp!(cloc, _) => {
+                context.env.add_diag(diag!(
+                    Attributes::InvalidValue,
+                    (*cloc, "Invalid test case"),
+                    (aloc, expected_msg.clone()),
+                ));
+                continue;
+            }
+        };
+        if values.len() != parameters.len() {
+            let msg = format!(
+                "Invalid test case. Expected {} value(s), one for each parameter of the test, but \
+                 found {}",
+                parameters.len(),
+                values.len()
+            );
+            context
+                .env
+                .add_diag(diag!(Attributes::InvalidValue, (case.loc, msg)));
+            continue;
+        }
+        let arguments = values
+            .iter()
+            .zip(parameters)
+            .map(|(value, (var, ty))| {
+                let argument = convert_case_value_to_move_value(context, &value.value, ty);
+                if argument.is_none() {
+                    let msg = format!("Unsupported value for parameter '{}'", var.value());
+                    context.env.add_diag(diag!(
+                        Attributes::InvalidValue,
+                        (value.loc, msg),
+                        (ty.loc, "Parameter declared with this type"),
+                    ));
+                }
+                argument
+            })
+            .collect::<Option<Vec<_>>>();
+        if let Some(arguments) = arguments {
+            all_arguments.push(arguments);
+        }
+    }
+    Some(all_arguments)
+}
+
+// Parses the `data = b"<path>"` assignment of a #[test(...)] attribute into the path of the file,
+// relative to the source file, whose rows are the arguments of each case of a data-driven test.
+fn parse_test_data(context: &mut Context, sp!(_, test_attribute): &E::Attribute) -> Option<String> {
+    use E::{AttributeValue_ as EAV, Attribute_ as EA, Value_ as EV};
+
+    let attributes = match test_attribute {
+        EA::Parameterized(_, attributes) => attributes,
+        _ => return None,
+    };
+    let (aloc, value) = attributes.iter().find_map(|(_, _, attr)| match attr {
+        sp!(aloc, EA::Assigned(sp!(_, nm), value))
+            if nm.as_str() == TestingAttribute::DATA_ASSIGNMENT_NAME =>
+        {
+            Some((*aloc, value))
+        }
+        _ => None,
+    })?;
+    let expected_msg = format!(
+        "Expected a byte string with the path of a .json or .csv file, as in \
+         #[test({}=b\"vectors.json\")]",
+        TestingAttribute::DATA_ASSIGNMENT_NAME
+    );
+    match &**value {
+        sp!(vloc, EAV::Value(sp!(_, EV::Bytearray(bytes)))) => match std::str::from_utf8(bytes) {
+            Ok(path) if path.ends_with(".json") || path.ends_with(".csv") => Some(path.to_string()),
+            _ => {
+                context.env.add_diag(diag!(
+                    Attributes::InvalidValue,
+                    (*vloc, "Invalid path in test data assignment"),
+                    (aloc, expected_msg),
+                ));
+                None
+            }
+        },
+        sp!(vloc, _) => {
+            context.env.add_diag(diag!(
+                Attributes::InvalidValue,
+                (*vloc, "Invalid value in test data assignment"),
+                (aloc, expected_msg),
+            ));
+            None
+        }
+    }
+}
+
+// Parses the `depends_on = <test>` or `depends_on = [<test>, ...]` assignment of a #[test(...)]
+// attribute into the names of the tests of the module the test depends on.
+fn parse_test_dependencies(
+    context: &mut Context,
+    module_ident: &ModuleIdent,
+    module: &G::ModuleDefinition,
+    fn_name: &str,
+    sp!(_, test_attribute): &E::Attribute,
+) -> BTreeSet<TestName> {
+    use E::{AttributeValue_ as EAV, Attribute_ as EA, ModuleAccess_ as EMA};
+
+    let mut dependencies = BTreeSet::new();
+    let attributes = match test_attribute {
+        EA::Parameterized(_, attributes) => attributes,
+        _ => return dependencies,
+    };
+    let (aloc, value) = match attributes.iter().find_map(|(_, _, attr)| match attr {
+        sp!(aloc, EA::Assigned(sp!(_, nm), value))
+            if nm.as_str() == TestingAttribute::DEPENDS_ON_ASSIGNMENT_NAME =>
+        {
+            Some((*aloc, value))
+        }
+        _ => None,
+    }) {
+        Some(assignment) => assignment,
+        None => return dependencies,
+    };
+    let expected_msg = format!(
+        "Expected a test of this module or a list of them, as in #[test({}=<test>)] or \
+         #[test({}=[<test>, ...])]",
+        TestingAttribute::DEPENDS_ON_ASSIGNMENT_NAME,
+        TestingAttribute::DEPENDS_ON_ASSIGNMENT_NAME
+    );
+    let values = match &**value {
+        sp!(_, EAV::Vector(values)) => values.iter().collect(),
+        value => vec![value],
+    };
+    for value in values {
+        let name = match value {
+            sp!(_, EAV::ModuleAccess(sp!(_, EMA::Name(name)))) => name,
+            // The functions of the module may be resolved as its members
+            sp!(
+                _,
+                EAV::ModuleAccess(sp!(_, EMA::ModuleAccess(mident, name)))
+            ) if mident == module_ident => name,
+            sp!(vloc, _) => {
+                context.env.add_diag(diag!(
+                    Attributes::InvalidValue,
+                    (*vloc, "Invalid value in test dependencies assignment"),
+                    (aloc, expected_msg.clone()),
+                ));
+                continue;
+            }
+        };
+        match module.functions.get_(&name.value) {
+            Some(function) if is_test_function(function) && name.value.as_str() != fn_name => {
+                dependencies.insert(name.value.to_string());
+            }
+            Some(function) if is_test_function(function) => {
+                context.env.add_diag(diag!(
+                    Attributes::InvalidTest,
+                    (
+                        name.loc,
+                        "Invalid test dependency. A test cannot depend on itself"
+                    ),
+                ));
+            }
+            Some(_) => {
+                let msg = format!(
+                    "Invalid test dependency. '{}' is not a test. Tests can only depend on the \
+                     other tests of their module",
+                    name
+                );
+                context
+                    .env
+                    .add_diag(diag!(Attributes::InvalidTest, (name.loc, msg)));
+            }
+            None => {
+                let msg = format!("Unbound test '{}'", name);
+                context.env.add_diag(diag!(
+                    Attributes::InvalidValue,
+                    (name.loc, msg),
+                    (
+                        aloc,
+                        "Tests can only depend on the other tests of their module"
+                    ),
+                ));
+            }
+        }
+    }
+    dependencies
+}
+
+// The arguments of a random test: those assigned in its attribute, and a random value for each of
+// its other parameters, which must be of a type values can be generated for.
+fn random_test_arguments(
+    context: &mut Context,
+    test_annotation_params: &BTreeMap<Symbol, MoveValue>,
+    parameters: &[(Var, H::SingleType)],
+) -> Vec<(String, RandomArgument)> {
+    parameters
+        .iter()
+        .filter_map(|(var, ty)| {
+            let argument = match test_annotation_params.get(&var.value()) {
+                Some(value) => RandomArgument::Given(value.clone()),
+                None => match random_type(ty) {
+                    Some(random_ty) => RandomArgument::Random(random_ty),
+                    None => {
+                        let msg = format!(
+                            "Unsupported type for random test parameter '{}'. Values can only \
+                             be generated for u8, u64, u128, address and vector<u8>",
+                            var.value()
+                        );
+                        context
+                            .env
+                            .add_diag(diag!(Attributes::InvalidTest, (ty.loc, msg)));
+                        return None;
+                    }
+                },
+            };
+            Some((var.value().to_string(), argument))
+        })
+        .collect()
+}
+
+fn random_type(ty: &H::SingleType) -> Option<RandomType> {
+    use N::BuiltinTypeName_ as NB;
+
+    let (builtin, ty_args) = builtin_type(ty)?;
+    Some(match builtin {
+        NB::U8 => RandomType::U8,
+        NB::U64 => RandomType::U64,
+        NB::U128 => RandomType::U128,
+        NB::Address => RandomType::Address,
+        NB::Vector if is_u8_vector(ty_args) => RandomType::VectorU8,
+        _ => return None,
+    })
+}
+
+// The builtin type of a parameter which is not a reference, with its type arguments
+fn builtin_type(ty: &H::SingleType) -> Option<(&N::BuiltinTypeName_, &[H::BaseType])> {
+    use H::{BaseType_ as HB, SingleType_ as HS, TypeName_ as HT};
+    match &ty.value {
+        HS::Base(
+            sp!(
+                _,
+                HB::Apply(_, sp!(_, HT::Builtin(sp!(_, builtin))), ty_args)
+            ),
+        ) => Some((builtin, ty_args)),
+        _ => None,
+    }
+}
+
+// Whether the type arguments of a vector are those of a `vector<u8>`
+fn is_u8_vector(ty_args: &[H::BaseType]) -> bool {
+    use H::{BaseType_ as HB, TypeName_ as HT};
+    use N::BuiltinTypeName_ as NB;
+    matches!(
+        ty_args,
+        [sp!(_, HB::Apply(_, sp!(_, HT::Builtin(sp!(_, NB::U8))), _))]
+    )
+}
+
 fn parse_failure_attribute(
     context: &mut Context,
+    module_ident: &ModuleIdent,
+    module: &G::ModuleDefinition,
     sp!(aloc, expected_attr): &E::Attribute,
 ) -> Option<ExpectedFailure> {
-    use E::{AttributeValue_ as EAV, Attribute_ as EA, Value_ as EV};
+    use E::Attribute_ as EA;
     match expected_attr {
         EA::Name(nm) => {
             assert!(
@@ -250,11 +948,11 @@ fn parse_failure_attribute(
             None
         }
         EA::Parameterized(sp!(_, nm), attrs) => {
-            let mut attrs_vec = attrs.iter().map(|(_, _, attr)| attr).collect::<Vec<_>>();
-            if attrs_vec.len() != 1 {
+            if attrs.is_empty() || attrs.len() > 2 {
                 let invalid_attr_msg = format!(
-                    "Invalid #[expected_failure(...)] attribute, expected 1 argument but found {}",
-                    attrs_vec.len()
+                    "Invalid #[expected_failure(...)] attribute, expected 1 or 2 arguments but \
+                     found {}",
+                    attrs.len()
                 );
                 context
                     .env
@@ -265,65 +963,465 @@ fn parse_failure_attribute(
                 nm.as_str() == TestingAttribute::ExpectedFailure.name(),
                 "ICE: expected failure attribute must have the right name"
             );
-            let attr = attrs_vec.pop().unwrap();
-            match attr {
-                sp!(assign_loc, EA::Assigned(sp!(_, nm), value))
-                    if nm.as_str() == TestingAttribute::CODE_ASSIGNMENT_NAME =>
-                {
-                    match &**value {
-                        sp!(_, EAV::Value(sp!(_, EV::InferredNum(u))))
-                            if *u <= std::u64::MAX as u128 =>
-                        {
-                            Some(ExpectedFailure::ExpectedWithCode(*u as u64))
-                        }
-                        sp!(_, EAV::Value(sp!(_, EV::U64(u)))) => {
-                            Some(ExpectedFailure::ExpectedWithCode(*u))
-                        }
-                        sp!(vloc, EAV::Value(sp!(_, EV::U8(_))))
-                        | sp!(vloc, EAV::Value(sp!(_, EV::U128(_)))) => {
-                            let msg = "Invalid value in expected failure code assignment";
-                            context.env.add_diag(diag!(
-                                Attributes::InvalidValue,
-                                (*assign_loc, msg),
-                                (*vloc, "Annotated non-u64 literals are not permitted"),
-                            ));
-                            None
-                        }
-                        sp!(vloc, _) => {
-                            context.env.add_diag(diag!(
-                                Attributes::InvalidValue,
-                                (*vloc, "Invalid value in expected failure code assignment"),
-                                (*assign_loc, "Unsupported value in this assignment"),
-                            ));
-                            None
-                        }
+            let mut code = None;
+            let mut location = None;
+            for (_, _, attr) in attrs {
+                match attr {
+                    sp!(assign_loc, EA::Assigned(sp!(_, nm), value))
+                        if nm.as_str() == TestingAttribute::CODE_ASSIGNMENT_NAME =>
+                    {
+                        code = Some(parse_failure_code(context, *assign_loc, value)?)
+                    }
+                    sp!(assign_loc, EA::Assigned(sp!(_, nm), value))
+                        if nm.as_str() == TestingAttribute::LOCATION_ASSIGNMENT_NAME =>
+                    {
+                        location = Some(parse_abort_location(
+                            context,
+                            module_ident,
+                            module,
+                            *assign_loc,
+                            value,
+                        )?)
+                    }
+                    sp!(assign_loc, EA::Assigned(sp!(nmloc, _), _)) => {
+                        let invalid_name_msg = format!(
+                            "Invalid name in expected failure code assignment. Did you mean to \
+                             use '{}'?",
+                            TestingAttribute::CODE_ASSIGNMENT_NAME
+                        );
+                        context.env.add_diag(diag!(
+                            Attributes::InvalidName,
+                            (*nmloc, invalid_name_msg),
+                            (*assign_loc, "Invalid name in this assignment"),
+                        ));
+                        return None;
+                    }
+                    sp!(loc, _) => {
+                        let msg = "Unsupported attribute value for expected failure attribute";
+                        context.env.add_diag(diag!(
+                            Attributes::InvalidValue,
+                            (*aloc, msg),
+                            (*loc, "Unsupported value in this assignment")
+                        ));
+                        return None;
                     }
                 }
-                sp!(assign_loc, EA::Assigned(sp!(nmloc, _), _)) => {
-                    let invalid_name_msg = format!(
-                        "Invalid name in expected failure code assignment. Did you mean to use \
-                         '{}'?",
-                        TestingAttribute::CODE_ASSIGNMENT_NAME
+            }
+            match (code, location) {
+                (Some(code), None) => Some(ExpectedFailure::ExpectedWithCode(code)),
+                (Some(code), Some(location)) => {
+                    Some(ExpectedFailure::ExpectedWithCodeAndLocation(code, location))
+                }
+                (None, _) => {
+                    let msg = format!(
+                        "The location of an abort can only be checked along with its code, as in \
+                         #[expected_failure({}=..., {}=...)]",
+                        TestingAttribute::CODE_ASSIGNMENT_NAME,
+                        TestingAttribute::LOCATION_ASSIGNMENT_NAME
                     );
-                    context.env.add_diag(diag!(
-                        Attributes::InvalidName,
-                        (*nmloc, invalid_name_msg),
-                        (*assign_loc, "Invalid name in this assignment"),
-                    ));
+                    context
+                        .env
+                        .add_diag(diag!(Attributes::InvalidValue, (*aloc, msg)));
                     None
                 }
-                sp!(loc, _) => {
-                    let msg = "Unsupported attribute value for expected failure attribute";
+            }
+        }
+    }
+}
+
+// Parses the abort code assigned in an #[expected_failure(abort_code = <u64>)] attribute
+fn parse_failure_code(
+    context: &mut Context,
+    assign_loc: Loc,
+    value: &E::AttributeValue,
+) -> Option<u64> {
+    use E::{AttributeValue_ as EAV, Value_ as EV};
+    match value {
+        sp!(_, EAV::Value(sp!(_, EV::InferredNum(u)))) if *u <= std::u64::MAX as u128 => {
+            Some(*u as u64)
+        }
+        sp!(_, EAV::Value(sp!(_, EV::U64(u)))) => Some(*u),
+        sp!(vloc, EAV::Value(sp!(_, EV::U8(_)))) | sp!(vloc, EAV::Value(sp!(_, EV::U128(_)))) => {
+            let msg = "Invalid value in expected failure code assignment";
+            context.env.add_diag(diag!(
+                Attributes::InvalidValue,
+                (assign_loc, msg),
+                (*vloc, "Annotated non-u64 literals are not permitted"),
+            ));
+            None
+        }
+        sp!(vloc, _) => {
+            context.env.add_diag(diag!(
+                Attributes::InvalidValue,
+                (*vloc, "Invalid value in expected failure code assignment"),
+                (assign_loc, "Unsupported value in this assignment"),
+            ));
+            None
+        }
+    }
+}
+
+// Parses the location assigned in an #[expected_failure(location = ...)] attribute: either `Self`
+// for any function of the module of the test, or a function as in `Self::f` or `0x1::M::f`
+fn parse_abort_location(
+    context: &mut Context,
+    module_ident: &ModuleIdent,
+    module: &G::ModuleDefinition,
+    assign_loc: Loc,
+    value: &E::AttributeValue,
+) -> Option<AbortLocation> {
+    use E::{AttributeValue_ as EAV, ModuleAccess_ as EMA};
+    match value {
+        sp!(_, EAV::ModuleAccess(sp!(_, EMA::Name(name))))
+            if name.value.as_str() == ModuleName::SELF_NAME =>
+        {
+            Some(AbortLocation {
+                module_id: context.resolve_module_id(module_ident),
+                function: None,
+            })
+        }
+        sp!(
+            _,
+            EAV::ModuleAccess(sp!(_, EMA::ModuleAccess(mident, name)))
+        ) => {
+            if mident.value == module_ident.value && module.functions.get_(&name.value).is_none() {
+                let msg = format!("Unbound function '{}'", name);
+                context.env.add_diag(diag!(
+                    Attributes::InvalidValue,
+                    (name.loc, msg),
+                    (
+                        assign_loc,
+                        "Expected a function of this module in this assignment"
+                    ),
+                ));
+                return None;
+            }
+            Some(AbortLocation {
+                module_id: context.resolve_module_id(mident),
+                function: Some(MoveIdentifier::new(name.value.as_str()).unwrap()),
+            })
+        }
+        sp!(vloc, _) => {
+            context.env.add_diag(diag!(
+                Attributes::InvalidValue,
+                (
+                    *vloc,
+                    "Expected `Self`, or a function as in `Self::f` or `0x1::M::f`"
+                ),
+                (assign_loc, "Unsupported value in this assignment"),
+            ));
+            None
+        }
+    }
+}
+
+// Parses a #[test_group(fixture = <function>)] attribute, checking that the fixture is a function
+// of the same module that can be run on its own.
+fn parse_test_group_attribute(
+    context: &mut Context,
+    module_ident: &ModuleIdent,
+    module: &G::ModuleDefinition,
+    sp!(aloc, group_attr): &E::Attribute,
+) -> Option<TestName> {
+    use E::{AttributeValue_ as EAV, Attribute_ as EA, ModuleAccess_ as EMA};
+    let expected_msg = format!(
+        "Expected a #[{}({}=<function>)] attribute",
+        TestingAttribute::TEST_GROUP,
+        TestingAttribute::FIXTURE_ASSIGNMENT_NAME
+    );
+    let attrs = match group_attr {
+        EA::Parameterized(_, attrs) if attrs.len() == 1 => attrs,
+        _ => {
+            context
+                .env
+                .add_diag(diag!(Attributes::InvalidValue, (*aloc, expected_msg)));
+            return None;
+        }
+    };
+    let (_, _, attr) = attrs.iter().next().unwrap();
+    let fixture = match attr {
+        sp!(_, EA::Assigned(sp!(_, nm), value))
+            if nm.as_str() == TestingAttribute::FIXTURE_ASSIGNMENT_NAME =>
+        {
+            match &**value {
+                sp!(_, EAV::ModuleAccess(sp!(_, EMA::Name(fixture)))) => *fixture,
+                // The functions of the module may be resolved as its members
+                sp!(
+                    _,
+                    EAV::ModuleAccess(sp!(_, EMA::ModuleAccess(mident, fixture)))
+                ) if mident == module_ident => *fixture,
+                sp!(vloc, _) => {
                     context.env.add_diag(diag!(
                         Attributes::InvalidValue,
-                        (*aloc, msg),
-                        (*loc, "Unsupported value in this assignment")
+                        (
+                            *vloc,
+                            "Expected the name of a function declared in this module"
+                        ),
+                        (*aloc, expected_msg),
                     ));
-                    None
+                    return None;
                 }
             }
         }
+        sp!(loc, _) => {
+            context.env.add_diag(diag!(
+                Attributes::InvalidValue,
+                (*loc, "Unsupported attribute value for test group attribute"),
+                (*aloc, expected_msg),
+            ));
+            return None;
+        }
+    };
+
+    let function = match module.functions.get_(&fixture.value) {
+        Some(function) => function,
+        None => {
+            let msg = format!("Unbound fixture function '{}'", fixture);
+            context.env.add_diag(diag!(
+                Attributes::InvalidValue,
+                (fixture.loc, msg),
+                (
+                    *aloc,
+                    "Fixtures must be declared in the same module as the test"
+                ),
+            ));
+            return None;
+        }
+    };
+    if is_test_function(function)
+        || !function.signature.parameters.is_empty()
+        || !function.signature.type_parameters.is_empty()
+    {
+        let msg = format!(
+            "Invalid fixture '{}'. A fixture must be a function without parameters or type \
+             parameters that is not itself a test",
+            fixture
+        );
+        context
+            .env
+            .add_diag(diag!(Attributes::InvalidTest, (fixture.loc, msg)));
+        return None;
     }
+    Some(fixture.value.to_string())
+}
+
+// Whether `function` is declared as a test, with #[test] or #[random_test]
+fn is_test_function(function: &G::Function) -> bool {
+    [TestingAttribute::Test, TestingAttribute::RandomTest]
+        .iter()
+        .any(|attr| {
+            function
+                .attributes
+                .get_(&E::AttributeName_::Known(KnownAttribute::Testing(*attr)))
+                .is_some()
+        })
+}
+
+// Parses a #[storage_fault(fail_load = <struct>, missing_module = b"<address>::<module>")]
+// attribute into the failures simulated by the storage the test runs on.
+fn parse_storage_fault_attribute(
+    context: &mut Context,
+    module_ident: &ModuleIdent,
+    module: &G::ModuleDefinition,
+    sp!(aloc, fault_attr): &E::Attribute,
+) -> StorageFaults {
+    use E::{AttributeValue_ as EAV, Attribute_ as EA, Value_ as EV};
+    let expected_msg = format!(
+        "Expected a #[{}({}=<struct>, {}=b\"<address>::<module>\")] attribute, with at least \
+         one of the assignments",
+        TestingAttribute::STORAGE_FAULT,
+        TestingAttribute::FAIL_LOAD_ASSIGNMENT_NAME,
+        TestingAttribute::MISSING_MODULE_ASSIGNMENT_NAME
+    );
+    let mut faults = StorageFaults::default();
+    let attrs = match fault_attr {
+        EA::Parameterized(_, attrs) if !attrs.is_empty() => attrs,
+        _ => {
+            context
+                .env
+                .add_diag(diag!(Attributes::InvalidValue, (*aloc, expected_msg)));
+            return faults;
+        }
+    };
+    for (_, _, attr) in attrs {
+        match attr {
+            sp!(_, EA::Assigned(sp!(_, nm), value))
+                if nm.as_str() == TestingAttribute::FAIL_LOAD_ASSIGNMENT_NAME =>
+            {
+                if let Some(resource) =
+                    parse_struct_name(context, module_ident, module, value, *aloc, &expected_msg)
+                {
+                    faults.failing_resources.insert(resource);
+                }
+            }
+            sp!(_, EA::Assigned(sp!(_, nm), value))
+                if nm.as_str() == TestingAttribute::MISSING_MODULE_ASSIGNMENT_NAME =>
+            {
+                let module_id = match &**value {
+                    sp!(_, EAV::Value(sp!(_, EV::Bytearray(bytes)))) => parse_module_id(bytes),
+                    _ => None,
+                };
+                match module_id {
+                    Some(module_id) => {
+                        faults.missing_modules.insert(module_id);
+                    }
+                    None => {
+                        let msg = "Expected a byte string naming a module as in b\"0x1::M\", \
+                                   with a numerical address";
+                        context.env.add_diag(diag!(
+                            Attributes::InvalidValue,
+                            (value.loc, msg),
+                            (*aloc, expected_msg.clone()),
+                        ));
+                    }
+                }
+            }
+            sp!(loc, _) => {
+                context.env.add_diag(diag!(
+                    Attributes::InvalidValue,
+                    (
+                        *loc,
+                        "Unsupported attribute value for storage fault attribute"
+                    ),
+                    (*aloc, expected_msg.clone()),
+                ));
+            }
+        }
+    }
+    faults
+}
+
+// Parses the name of a struct in an attribute, either `R` for a struct of `module` or a fully
+// qualified `0x1::M::R`
+fn parse_struct_name(
+    context: &mut Context,
+    module_ident: &ModuleIdent,
+    module: &G::ModuleDefinition,
+    value: &E::AttributeValue,
+    aloc: Loc,
+    expected_msg: &str,
+) -> Option<(ModuleId, MoveIdentifier)> {
+    use E::{AttributeValue_ as EAV, ModuleAccess_ as EMA};
+    let (module_id, name) = match value {
+        sp!(_, EAV::ModuleAccess(sp!(_, EMA::Name(name)))) => {
+            if module.structs.get_(&name.value).is_none() {
+                let msg = format!("Unbound struct '{}'", name);
+                context.env.add_diag(diag!(
+                    Attributes::InvalidValue,
+                    (name.loc, msg),
+                    (aloc, expected_msg.to_string()),
+                ));
+                return None;
+            }
+            (context.resolve_module_id(module_ident), name)
+        }
+        sp!(
+            _,
+            EAV::ModuleAccess(sp!(_, EMA::ModuleAccess(mident, name)))
+        ) => (context.resolve_module_id(mident), name),
+        sp!(vloc, _) => {
+            context.env.add_diag(diag!(
+                Attributes::InvalidValue,
+                (*vloc, "Expected a struct, as in `R` or `0x1::M::R`"),
+                (aloc, expected_msg.to_string()),
+            ));
+            return None;
+        }
+    };
+    let name = MoveIdentifier::new(name.value.as_str()).unwrap();
+    Some((module_id, name))
+}
+
+// Parses a #[expected_events(types = [<struct>, ...])] attribute into the types of the events the
+// test is expected to emit, in order.
+fn parse_expected_events_attribute(
+    context: &mut Context,
+    module_ident: &ModuleIdent,
+    module: &G::ModuleDefinition,
+    sp!(aloc, events_attr): &E::Attribute,
+) -> Option<Vec<(ModuleId, MoveIdentifier)>> {
+    use E::{AttributeValue_ as EAV, Attribute_ as EA};
+    let expected_msg = format!(
+        "Expected a #[{}({}=[<struct>, ...])] attribute, listing the types of the events emitted \
+         by the test in the order they are emitted",
+        TestingAttribute::EXPECTED_EVENTS,
+        TestingAttribute::EVENT_TYPES_ASSIGNMENT_NAME
+    );
+    let values = match events_attr {
+        EA::Parameterized(_, attrs) if attrs.len() == 1 => {
+            attrs.iter().find_map(|(_, _, attr)| match attr {
+                sp!(_, EA::Assigned(sp!(_, nm), value))
+                    if nm.as_str() == TestingAttribute::EVENT_TYPES_ASSIGNMENT_NAME =>
+                {
+                    match &**value {
+                        sp!(_, EAV::Vector(values)) => Some(values),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            })
+        }
+        _ => None,
+    };
+    let values = match values {
+        Some(values) => values,
+        None => {
+            context
+                .env
+                .add_diag(diag!(Attributes::InvalidValue, (*aloc, expected_msg)));
+            return None;
+        }
+    };
+    let mut event_types = vec![];
+    for value in values {
+        // The types which fail to resolve are reported, and left out of the expected events
+        if let Some(event_type) =
+            parse_struct_name(context, module_ident, module, value, *aloc, &expected_msg)
+        {
+            event_types.push(event_type);
+        }
+    }
+    Some(event_types)
+}
+
+// Parses a module id of the form `<address>::<module>`, where the address is numerical
+fn parse_module_id(bytes: &[u8]) -> Option<ModuleId> {
+    let (addr, name) = std::str::from_utf8(bytes).ok()?.split_once("::")?;
+    let addr = NumericalAddress::parse_str(addr).ok()?;
+    let name = MoveIdentifier::new(name).ok()?;
+    Some(ModuleId::new(MoveAddress::new(addr.into_bytes()), name))
+}
+
+// Converts the value of a test case for a parameter of type `ty`. Signers, addresses, booleans,
+// integers and byte strings are supported.
+fn convert_case_value_to_move_value(
+    context: &mut Context,
+    value: &E::AttributeValue_,
+    ty: &H::SingleType,
+) -> Option<MoveValue> {
+    use E::{AttributeValue_ as EAV, Value_ as EV};
+    use N::BuiltinTypeName_ as NB;
+
+    let (builtin, ty_args) = builtin_type(ty)?;
+    let value = match value {
+        EAV::Value(sp!(_, value)) => value,
+        _ => return None,
+    };
+    Some(match (builtin, value) {
+        (NB::Signer, EV::Address(a)) | (NB::Address, EV::Address(a)) => {
+            MoveValue::Address(MoveAddress::new(context.resolve_address(a).into_bytes()))
+        }
+        (NB::Bool, EV::Bool(b)) => MoveValue::Bool(*b),
+        (NB::U8, EV::U8(u)) => MoveValue::U8(*u),
+        (NB::U8, EV::InferredNum(u)) if *u <= std::u8::MAX as u128 => MoveValue::U8(*u as u8),
+        (NB::U64, EV::U64(u)) => MoveValue::U64(*u),
+        (NB::U64, EV::InferredNum(u)) if *u <= std::u64::MAX as u128 => MoveValue::U64(*u as u64),
+        (NB::U128, EV::U128(u)) | (NB::U128, EV::InferredNum(u)) => MoveValue::U128(*u),
+        (NB::Vector, EV::Bytearray(bytes)) if is_u8_vector(ty_args) => {
+            MoveValue::vector_u8(bytes.clone())
+        }
+        _ => return None,
+    })
 }
 
 fn convert_attribute_value_to_move_value(
@@ -0,0 +1,15 @@
+// check that invalid expected events are rejected
+module 0x1::A {
+    struct E has drop, store {}
+
+    #[test]
+    #[expected_events(types = E)]
+    fun not_a_list() { }
+
+    #[test]
+    #[expected_events(types = [E, Missing])]
+    fun unbound_type() { }
+
+    #[expected_events(types = [E])]
+    fun not_a_test() { }
+}
@@ -0,0 +1,14 @@
+// check that an expected abort location is a module or one of its functions, given with a code
+module 0x1::M {
+    #[test]
+    #[expected_failure(abort_code=0, location=Self::nope)]
+    fun unbound_function() { abort 0 }
+
+    #[test]
+    #[expected_failure(location=Self)]
+    fun location_without_code() { abort 0 }
+
+    #[test]
+    #[expected_failure(abort_code=0, location=0)]
+    fun location_not_a_function() { abort 0 }
+}
@@ -0,0 +1,5 @@
+// check that values can be generated for the parameters of random tests
+module 0x1::A {
+    #[random_test]
+    fun unsupported(flag: bool) { }
+}
@@ -0,0 +1,13 @@
+// check that invalid storage faults are rejected
+module 0x1::A {
+    struct R has key { f: u64 }
+
+    #[storage_fault(fail_load = R)]
+    fun not_a_test() { }
+
+    #[test, storage_fault(fail_load = S)]
+    fun unbound_struct() { }
+
+    #[test, storage_fault(missing_module = b"M")]
+    fun invalid_module() { }
+}
@@ -0,0 +1,8 @@
+// check that invalid test cases are rejected
+module 0x1::A {
+    #[test(cases=[[1, 2]])]
+    fun wrong_arity(a: u64) { }
+
+    #[test(cases=[[true]])]
+    fun wrong_type(a: u64) { }
+}
@@ -0,0 +1,11 @@
+// check that the data file of a test is a .json or .csv file, which gives all of its arguments
+module 0x1::A {
+    #[test(data=0)]
+    fun not_a_path() { }
+
+    #[test(data=b"rows.txt")]
+    fun not_a_data_file() { }
+
+    #[test(a=1, data=b"rows.json")]
+    fun also_assigned(a: u64) { }
+}
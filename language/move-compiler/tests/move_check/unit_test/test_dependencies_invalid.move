@@ -0,0 +1,19 @@
+// check that tests only depend on the other tests of their module, without cycles
+module 0x1::A {
+    #[test(depends_on = helper)]
+    fun depends_on_non_test() { }
+
+    #[test(depends_on = missing)]
+    fun depends_on_unbound() { }
+
+    #[test(depends_on = depends_on_itself)]
+    fun depends_on_itself() { }
+
+    #[test(depends_on = second)]
+    fun first() { }
+
+    #[test(depends_on = first)]
+    fun second() { }
+
+    fun helper() { }
+}
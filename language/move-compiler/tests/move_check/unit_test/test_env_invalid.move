@@ -0,0 +1,8 @@
+// check that invalid test environments are rejected
+module 0x1::A {
+    #[test(env=true)]
+    fun env_not_bytes() { }
+
+    #[test(env=b"KEY")]
+    fun env_missing_value() { }
+}
@@ -0,0 +1,18 @@
+// check that invalid test groups are rejected
+module 0x1::A {
+    #[test_group(fixture = setup)]
+    fun not_a_test() { }
+
+    #[test, test_group(fixture = missing)]
+    fun unbound_fixture() { }
+
+    #[test, test_group(fixture = fixture_with_params)]
+    fun fixture_with_params_test() { }
+
+    #[test, test_group(fixture = 0)]
+    fun fixture_not_a_name() { }
+
+    fun setup() { }
+
+    fun fixture_with_params(_x: u64) { }
+}
@@ -0,0 +1,8 @@
+// check that invariants with parameters are rejected
+module 0x1::A {
+    #[test_invariant]
+    fun invariant_with_params(_x: u64) { }
+
+    #[test_invariant]
+    fun invariant_with_type_params<T>() { }
+}
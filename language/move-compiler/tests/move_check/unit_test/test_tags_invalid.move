@@ -0,0 +1,8 @@
+// check that invalid test tags are rejected
+module 0x1::A {
+    #[test(tags=b"slow")]
+    fun tags_not_a_list() { }
+
+    #[test(tags=[b"slow", 1])]
+    fun tag_not_bytes() { }
+}
@@ -67,6 +67,7 @@ pub struct SpecFunDecl {
 pub enum AttributeValue {
     Value(NodeId, Value),
     Name(NodeId, Option<ModuleName>, Symbol),
+    Vector(NodeId, Vec<AttributeValue>),
 }
 
 #[derive(Debug, Clone)]
@@ -270,49 +270,57 @@ impl<'env, 'translator> ModuleBuilder<'env, 'translator> {
                 Attribute::Apply(node_id, sym, self.translate_attributes(vs))
             }
             EA::Attribute_::Assigned(n, v) => {
-                let value_node_id = self
-                    .parent
-                    .env
-                    .new_node(self.parent.to_loc(&v.loc), Type::Tuple(vec![]));
-                let v = match &v.value {
-                    EA::AttributeValue_::Value(val) => {
-                        let val =
-                            if let Some((val, _)) = ExpTranslator::new(self).translate_value(val) {
-                                val
-                            } else {
-                                // Error reported
-                                Value::Bool(false)
-                            };
-                        AttributeValue::Value(value_node_id, val)
-                    }
-                    EA::AttributeValue_::ModuleAccess(macc) => match macc.value {
-                        EA::ModuleAccess_::Name(n) => AttributeValue::Name(
-                            value_node_id,
-                            None,
-                            self.symbol_pool().make(n.value.as_str()),
-                        ),
-                        EA::ModuleAccess_::ModuleAccess(mident, n) => {
-                            let addr_bytes = self.parent.resolve_address(
-                                &self.parent.to_loc(&macc.loc),
-                                &mident.value.address,
-                            );
-                            let module_name = ModuleName::from_address_bytes_and_name(
-                                addr_bytes,
-                                self.symbol_pool()
-                                    .make(mident.value.module.0.value.as_str()),
-                            );
-                            AttributeValue::Name(
-                                value_node_id,
-                                Some(module_name),
-                                self.symbol_pool().make(n.value.as_str()),
-                            )
-                        }
-                    },
-                };
+                let v = self.translate_attribute_value(v);
                 Attribute::Assign(node_id, self.symbol_pool().make(n.value.as_str()), v)
             }
         }
     }
+
+    fn translate_attribute_value(&mut self, v: &EA::AttributeValue) -> AttributeValue {
+        let value_node_id = self
+            .parent
+            .env
+            .new_node(self.parent.to_loc(&v.loc), Type::Tuple(vec![]));
+        match &v.value {
+            EA::AttributeValue_::Value(val) => {
+                let val = if let Some((val, _)) = ExpTranslator::new(self).translate_value(val) {
+                    val
+                } else {
+                    // Error reported
+                    Value::Bool(false)
+                };
+                AttributeValue::Value(value_node_id, val)
+            }
+            EA::AttributeValue_::ModuleAccess(macc) => match macc.value {
+                EA::ModuleAccess_::Name(n) => AttributeValue::Name(
+                    value_node_id,
+                    None,
+                    self.symbol_pool().make(n.value.as_str()),
+                ),
+                EA::ModuleAccess_::ModuleAccess(mident, n) => {
+                    let addr_bytes = self
+                        .parent
+                        .resolve_address(&self.parent.to_loc(&macc.loc), &mident.value.address);
+                    let module_name = ModuleName::from_address_bytes_and_name(
+                        addr_bytes,
+                        self.symbol_pool()
+                            .make(mident.value.module.0.value.as_str()),
+                    );
+                    AttributeValue::Name(
+                        value_node_id,
+                        Some(module_name),
+                        self.symbol_pool().make(n.value.as_str()),
+                    )
+                }
+            },
+            EA::AttributeValue_::Vector(vs) => AttributeValue::Vector(
+                value_node_id,
+                vs.iter()
+                    .map(|v| self.translate_attribute_value(v))
+                    .collect(),
+            ),
+        }
+    }
 }
 
 /// # Declaration Analysis
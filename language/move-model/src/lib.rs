@@ -24,11 +24,12 @@ use move_binary_format::{
 use move_compiler::{
     self,
     compiled_unit::{self, AnnotatedCompiledScript, AnnotatedCompiledUnit},
-    diagnostics::Diagnostics,
+    construct_pre_compiled_lib,
+    diagnostics::{Diagnostics, FilesSourceText},
     expansion::ast::{self as E, Address, ModuleDefinition, ModuleIdent, ModuleIdent_},
     parser::ast::{self as P, ModuleName as ParserModuleName},
     shared::{parse_named_address, unique_map::UniqueMap, NumericalAddress},
-    Compiler, Flags, PASS_COMPILATION, PASS_EXPANSION, PASS_PARSER,
+    Compiler, Flags, FullyCompiledProgram, PASS_COMPILATION, PASS_EXPANSION, PASS_PARSER,
 };
 use move_core_types::{account_address::AccountAddress, identifier::Identifier};
 use move_ir_types::location::sp;
@@ -243,6 +244,234 @@ pub fn run_model_builder_with_options_and_compilation_flags(
     Ok(env)
 }
 
+/// A model builder which checks target sources against a fixed set of dependencies, which are
+/// compiled only once when the builder is created. This avoids rebuilding the dependencies each
+/// time the targets change, e.g. when the targets are edited in an IDE.
+pub struct IncrementalModelBuilder {
+    deps_dir: Vec<String>,
+    options: ModelBuilderOptions,
+    flags: Flags,
+    named_address_mapping: BTreeMap<String, NumericalAddress>,
+    /// The compiled dependencies, or the diagnostics produced when compiling them.
+    deps: Result<CompiledDependencies, (FilesSourceText, Diagnostics)>,
+}
+
+/// The dependencies of an `IncrementalModelBuilder`.
+struct CompiledDependencies {
+    /// The dependencies as a library to compile targets against.
+    program: FullyCompiledProgram,
+    /// The compiled dependency modules, in dependency order.
+    units: Vec<AnnotatedCompiledUnit>,
+}
+
+impl IncrementalModelBuilder {
+    /// Create a builder for the transitive dependencies found in the provided directory list.
+    /// Errors in the dependencies are reported in the environment of each check.
+    pub fn new(
+        deps_dir: &[String],
+        options: ModelBuilderOptions,
+        flags: Flags,
+        named_address_mapping: BTreeMap<String, NumericalAddress>,
+    ) -> anyhow::Result<Self> {
+        let deps = match construct_pre_compiled_lib(
+            vec![(deps_dir.to_vec(), named_address_mapping.clone())],
+            None,
+            flags.clone(),
+        )? {
+            Err(files_and_diags) => Err(files_and_diags),
+            Ok(program) => {
+                // The library only keeps the dependencies as ASTs, so generate their bytecode
+                // as if they were sources, which is needed for the model.
+                let cfgir = {
+                    let mut cfgir = program.cfgir.clone();
+                    for (_, _, mdef) in cfgir.modules.iter_mut() {
+                        mdef.is_source_module = true;
+                    }
+                    cfgir
+                };
+                let (_, compiler_res) = Compiler::new(vec![], vec![])
+                    .set_flags(flags.clone())
+                    .run::<PASS_PARSER>()?;
+                match compiler_res
+                    .and_then(|(_, compiler)| compiler.into_ast().0.at_cfgir(cfgir).build())
+                {
+                    Err(diags) => Err((program.files.clone(), diags)),
+                    Ok((units, _warnings)) => Ok(CompiledDependencies { program, units }),
+                }
+            }
+        };
+        Ok(Self {
+            deps_dir: deps_dir.to_vec(),
+            options,
+            flags,
+            named_address_mapping,
+            deps,
+        })
+    }
+
+    /// Check the given target sources against the dependencies of this builder, returning a new
+    /// environment with the targets and the dependencies they use, or with the diagnostics of
+    /// the check.
+    pub fn check(&self, move_sources: &[String]) -> anyhow::Result<GlobalEnv> {
+        let mut env = GlobalEnv::new();
+        env.set_extension(self.options.clone());
+        let deps = match &self.deps {
+            Err((files, diags)) => {
+                for (fhash, (fname, fsrc)) in files {
+                    env.add_source(*fhash, fname.as_str(), fsrc, /* is_dep */ true);
+                }
+                add_move_lang_diagnostics(&mut env, diags.clone());
+                return Ok(env);
+            }
+            Ok(deps) => deps,
+        };
+
+        // Step 1: parse the targets, adding the sources of the targets and dependencies.
+        let (files, comments_and_compiler_res) = Compiler::new(
+            vec![(move_sources.to_vec(), self.named_address_mapping.clone())],
+            vec![],
+        )
+        .set_pre_compiled_lib(&deps.program)
+        .set_flags(self.flags.clone())
+        .run::<PASS_PARSER>()?;
+        let mut all_files = deps
+            .program
+            .files
+            .iter()
+            .map(|(fhash, file)| (*fhash, (file, true)))
+            .collect::<BTreeMap<_, _>>();
+        all_files.extend(files.iter().map(|(fhash, file)| (*fhash, (file, false))));
+        for (fhash, ((fname, fsrc), is_dep)) in all_files {
+            env.add_source(fhash, fname.as_str(), fsrc, is_dep);
+        }
+        let (comment_map, compiler) = match comments_and_compiler_res {
+            Err(diags) => {
+                add_move_lang_diagnostics(&mut env, diags);
+                return Ok(env);
+            }
+            Ok(res) => res,
+        };
+        for (fhash, documentation) in comment_map {
+            let file_id = env.get_file_id(fhash).expect("file name defined");
+            env.add_documentation(
+                file_id,
+                documentation
+                    .into_iter()
+                    .map(|(idx, s)| (ByteIndex(idx), s))
+                    .collect(),
+            )
+        }
+
+        // Step 2: run the compiler on the targets up to expansion.
+        let (compiler, expansion_ast) = match compiler.run::<PASS_EXPANSION>() {
+            Err(diags) => {
+                add_move_lang_diagnostics(&mut env, diags);
+                return Ok(env);
+            }
+            Ok(compiler) => compiler.into_ast(),
+        };
+
+        // The compiler does not track uses of the pre-compiled dependencies, so extract the
+        // closure of the targets from an expansion of the targets with the dependency sources.
+        // This is cheap compared to compiling the dependencies.
+        let (_, compiler_res) = Compiler::new(
+            vec![(move_sources.to_vec(), self.named_address_mapping.clone())],
+            vec![(self.deps_dir.clone(), self.named_address_mapping.clone())],
+        )
+        .set_flags(self.flags.clone())
+        .run::<PASS_EXPANSION>()?;
+        let E::Program {
+            modules: all_modules,
+            scripts: all_scripts,
+        } = match compiler_res {
+            Err(diags) => {
+                add_move_lang_diagnostics(&mut env, diags);
+                return Ok(env);
+            }
+            Ok((_, compiler)) => compiler.into_ast().1,
+        };
+        let mut visited_addresses = BTreeSet::new();
+        let mut visited_modules = BTreeSet::new();
+        for (_, mident, _) in &expansion_ast.modules {
+            collect_related_modules_recursive(
+                mident,
+                &all_modules,
+                &mut visited_addresses,
+                &mut visited_modules,
+            );
+        }
+        for sdef in all_scripts.values() {
+            for (_, mident, _neighbor) in &sdef.immediate_neighbors {
+                collect_related_modules_recursive(
+                    mident,
+                    &all_modules,
+                    &mut visited_addresses,
+                    &mut visited_modules,
+                );
+            }
+        }
+
+        // Step 3: compile the targets only, reusing the compiled dependencies.
+        let mut units = match compiler
+            .at_expansion(expansion_ast.clone())
+            .run::<PASS_COMPILATION>()
+        {
+            Err(diags) => {
+                add_move_lang_diagnostics(&mut env, diags);
+                return Ok(env);
+            }
+            Ok(compiler) => {
+                let (units, warnings) = compiler.into_compiled_units();
+                if !warnings.is_empty() {
+                    add_move_lang_diagnostics(&mut env, warnings);
+                }
+                units
+            }
+        };
+        let diags = compiled_unit::verify_units(&units);
+        if !diags.is_empty() {
+            add_move_lang_diagnostics(&mut env, diags);
+            return Ok(env);
+        }
+        let target_modules = units
+            .iter()
+            .filter_map(|unit| match unit {
+                AnnotatedCompiledUnit::Module(annot_module) => {
+                    Some(annot_module.module_ident().value)
+                }
+                AnnotatedCompiledUnit::Script(_) => None,
+            })
+            .collect::<BTreeSet<_>>();
+        // Dependencies come first, as the targets can use them but not vice versa.
+        units.splice(
+            0..0,
+            deps.units
+                .iter()
+                .filter(|unit| match unit {
+                    AnnotatedCompiledUnit::Module(annot_module) => {
+                        let mident = annot_module.module_ident().value;
+                        visited_modules.contains(&mident) && !target_modules.contains(&mident)
+                    }
+                    AnnotatedCompiledUnit::Script(_) => false,
+                })
+                .cloned(),
+        );
+
+        // Step 4: run the spec checker on the targets and the dependencies they use.
+        let expansion_ast = E::Program {
+            modules: all_modules.filter_map(|mident, mut mdef| {
+                visited_modules.contains(&mident.value).then(|| {
+                    mdef.is_source_module = true;
+                    mdef
+                })
+            }),
+            scripts: expansion_ast.scripts,
+        };
+        run_spec_checker(&mut env, units, expansion_ast);
+        Ok(env)
+    }
+}
+
 fn collect_used_addresses<'a>(
     used_addresses: &'a BTreeSet<Address>,
     visited_addresses: &mut BTreeSet<&'a str>,
@@ -458,7 +458,9 @@ pub struct GlobalEnv {
     file_hash_map: BTreeMap<FileHash, (String, FileId)>,
     /// Bijective mapping between FileId and a plain int. FileId's are themselves wrappers around
     /// ints, but the inner representation is opaque and cannot be accessed. This is used so we
-    /// can emit FileId's to generated code and read them back.
+    /// can emit FileId's to generated code and read them back. Source files are indexed in
+    /// order of their names, so the index of a file does not depend on the order in which
+    /// sources are added.
     file_id_to_idx: BTreeMap<FileId, u16>,
     file_idx_to_id: BTreeMap<u16, FileId>,
     /// A set indicating whether a file id is a target or a dependency.
@@ -641,9 +643,28 @@ impl GlobalEnv {
         if is_dep {
             self.file_id_is_dep.insert(file_id);
         }
+        self.reindex_source_files();
         file_id
     }
 
+    /// Reassigns the indices of source files in order of their names. The pseudo files of the
+    /// unknown and internal locations keep the first indices.
+    fn reindex_source_files(&mut self) {
+        let pseudo_files = [self.unknown_loc.file_id(), self.internal_loc.file_id()];
+        let mut files = self
+            .file_id_to_idx
+            .keys()
+            .filter(|file_id| !pseudo_files.contains(file_id))
+            .map(|file_id| (self.source_files.name(*file_id).to_os_string(), *file_id))
+            .collect::<Vec<_>>();
+        files.sort();
+        for (i, (_, file_id)) in files.into_iter().enumerate() {
+            let file_idx = (pseudo_files.len() + i) as u16;
+            self.file_id_to_idx.insert(file_id, file_idx);
+            self.file_idx_to_id.insert(file_idx, file_id);
+        }
+    }
+
     /// Find all target modules and return in a vector
     pub fn get_target_modules(&self) -> Vec<ModuleEnv> {
         let mut target_modules: Vec<ModuleEnv> = vec![];
@@ -777,6 +798,20 @@ impl GlobalEnv {
             .expect("file_idx undefined")
     }
 
+    /// Returns the index, name, and hash of each source file, in order of the indices. This
+    /// allows tools which store locations emitted with `file_id_to_idx` to map them back to
+    /// files across builds.
+    pub fn get_file_map(&self) -> Vec<(u16, String, FileHash)> {
+        let mut file_map = self
+            .file_hash_map
+            .iter()
+            .filter(|(_, (name, _))| name != "<internal>" && name != "<unknown>")
+            .map(|(hash, (name, file_id))| (self.file_id_to_idx(*file_id), name.clone(), *hash))
+            .collect::<Vec<_>>();
+        file_map.sort();
+        file_map
+    }
+
     /// Returns file name and line/column position for a location, if available.
     pub fn get_file_and_location(&self, loc: &Loc) -> Option<(String, Location)> {
         self.get_location(loc).map(|line_column| {
@@ -0,0 +1,95 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use codespan_reporting::{diagnostic::Severity, term::termcolor::Buffer};
+use move_compiler::Flags;
+use move_model::{
+    model::GlobalEnv, options::ModelBuilderOptions, run_model_builder, IncrementalModelBuilder,
+};
+use std::{collections::BTreeMap, path::PathBuf};
+
+fn path_from_crate_root(path: &str) -> String {
+    let mut buf = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    buf.push(path);
+    buf.to_string_lossy().to_string()
+}
+
+fn diagnostics(env: &GlobalEnv) -> String {
+    let mut writer = Buffer::no_color();
+    env.report_diag(&mut writer, Severity::Warning);
+    String::from_utf8_lossy(&writer.into_inner()).to_string()
+}
+
+/// The modules of the environment with their structs and functions, and whether they are
+/// targets.
+fn declarations(env: &GlobalEnv) -> Vec<(String, bool, Vec<String>)> {
+    let mut modules: Vec<_> = env
+        .get_modules()
+        .map(|module| {
+            let mut members: Vec<_> = module
+                .get_structs()
+                .map(|s| s.get_full_name_str())
+                .chain(module.get_functions().map(|f| f.get_full_name_str()))
+                .collect();
+            members.sort();
+            (module.get_full_name_str(), module.is_target(), members)
+        })
+        .collect();
+    modules.sort();
+    modules
+}
+
+/// Check a target with the builder, and compare the result with the one of a full build.
+/// Returns the environment and its diagnostics, which are only reported once.
+fn check_like_full_build(builder: &IncrementalModelBuilder, target: &str) -> (GlobalEnv, String) {
+    let targets = vec![path_from_crate_root(target)];
+    let deps = vec![path_from_crate_root("tests/incremental/deps")];
+    let env = builder.check(&targets).unwrap();
+    let full_env = run_model_builder(&targets, &deps).unwrap();
+    let diags = diagnostics(&env);
+    assert_eq!(diags, diagnostics(&full_env));
+    assert_eq!(declarations(&env), declarations(&full_env));
+    (env, diags)
+}
+
+#[test]
+fn test_check_versions_of_target() {
+    let builder = IncrementalModelBuilder::new(
+        &[path_from_crate_root("tests/incremental/deps")],
+        ModelBuilderOptions::default(),
+        Flags::empty(),
+        BTreeMap::new(),
+    )
+    .unwrap();
+
+    let (env, _) = check_like_full_build(&builder, "tests/incremental/user_v1.move");
+    assert!(!env.has_errors());
+    // Only the dependencies used by the target are in the environment
+    let modules: Vec<_> = declarations(&env)
+        .into_iter()
+        .map(|(module, is_target, _)| (module, is_target))
+        .collect();
+    assert_eq!(
+        modules,
+        vec![
+            ("0x2::Counter".to_string(), false),
+            ("0x3::User".to_string(), true)
+        ]
+    );
+    // The specs of the target are checked
+    let count_twice = env
+        .get_modules()
+        .flat_map(|module| module.into_functions())
+        .find(|fun| fun.get_full_name_str() == "User::count_twice")
+        .unwrap();
+    assert!(!count_twice.get_spec().conditions.is_empty());
+
+    // A later version of the target is checked against the same dependencies
+    let (env, diags) = check_like_full_build(&builder, "tests/incremental/user_v2.move");
+    assert!(env.has_errors());
+    assert!(diags.contains("decrement"));
+
+    // The builder is not affected by checking an erroneous version
+    let (env, _) = check_like_full_build(&builder, "tests/incremental/user_v1.move");
+    assert!(!env.has_errors());
+}
@@ -0,0 +1,17 @@
+module 0x2::Counter {
+    struct Counter has drop, store {
+        value: u64,
+    }
+
+    public fun new(): Counter {
+        Counter { value: 0 }
+    }
+
+    public fun increment(counter: &mut Counter) {
+        counter.value = counter.value + 1;
+    }
+
+    public fun value(counter: &Counter): u64 {
+        counter.value
+    }
+}
@@ -0,0 +1,5 @@
+module 0x2::Unused {
+    public fun unused(): u64 {
+        42
+    }
+}
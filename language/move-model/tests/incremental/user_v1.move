@@ -0,0 +1,18 @@
+module 0x3::User {
+    use 0x2::Counter::{Self, Counter};
+
+    struct Holder has key {
+        counter: Counter,
+    }
+
+    public fun count_twice(): u64 {
+        let counter = Counter::new();
+        Counter::increment(&mut counter);
+        Counter::increment(&mut counter);
+        let value = Counter::value(&counter);
+        value
+    }
+    spec count_twice {
+        ensures result == 2;
+    }
+}
@@ -6,7 +6,10 @@ use std::{env, path::Path};
 use move_command_line_common::{env::read_bool_env_var, testing::EXP_EXT};
 use move_prover_test_utils::baseline_test::verify_or_update_baseline;
 use move_stdlib::move_stdlib_files;
-use move_unit_test::UnitTestingConfig;
+use move_unit_test::{
+    test_reporter::{StatisticsFormat, TestReportFormat},
+    UnitTestingConfig,
+};
 
 fn test_runner(path: &Path) -> datatest_stable::Result<()> {
     env::set_var("NO_COLOR", "1");
@@ -14,23 +17,71 @@ fn test_runner(path: &Path) -> datatest_stable::Result<()> {
     let source_files = vec![path.to_str().unwrap().to_owned()];
     let config = UnitTestingConfig {
         instruction_execution_bound: 5000,
-        filter: None,
+        gas_schedule: None,
+        gas_limit: None,
+        max_call_depth: None,
+        max_value_depth: None,
+        max_vector_length: None,
+        filter: vec![],
+        exclude: vec![],
+        include_tags: vec![],
+        exclude_tags: vec![],
+        modules: vec![],
+        module_addresses: vec![],
         num_threads: 1,
         source_files,
         dep_files: move_stdlib_files(),
+        include_dep_tests: false,
+        cached: false,
+        cache_dir: None,
+        allow_warnings: false,
         check_stackless_vm: true,
+        stackless_instruction_tolerance: None,
+        stackless_only: false,
         report_storage_on_error: false,
+        report_full_storage_on_error: false,
+        state_output_dir: None,
+        snapshot_dir: None,
+        update_baseline: false,
+        check_invariants: false,
+        nocapture: false,
+        prove: false,
+        genesis: None,
+        timeout: None,
+        fail_fast: false,
+        shard: None,
+        retries: 0,
+        failed_tests_file: None,
+        rerun_failed: false,
+        watch: false,
+        isolate: false,
+        shuffle: None,
+        seed: None,
+        quiet: false,
+        progress: false,
         report_statistics: false,
+        statistics_format: StatisticsFormat::Table,
+        statistics_output: None,
+        report_slow_tests: None,
+        report_function_histogram: None,
         list: false,
         verbose: read_bool_env_var("VERBOSE"),
         named_address_values: move_stdlib::move_stdlib_named_addresses()
             .into_iter()
             .collect(),
+        address_file: None,
+        open_metrics_output: None,
+        coverage: None,
+        lcov: false,
+        test_env: vec![],
+        dry_run: false,
+        log_json: None,
+        format: TestReportFormat::Text,
     };
 
     let test_plan = config.build_test_plan().unwrap();
     let mut buffer = vec![];
-    config.run_and_report_unit_tests(test_plan, None, &mut buffer)?;
+    config.run_and_report_unit_tests(test_plan, None, None, &mut buffer)?;
     let output = String::from_utf8(buffer)?;
 
     let baseline_path = path.with_extension(EXP_EXT);
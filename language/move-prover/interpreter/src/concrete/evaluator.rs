@@ -848,7 +848,7 @@ impl<'env> Evaluator<'env> {
             .collect();
 
         // execute the function
-        let callee_result = player::entrypoint(
+        let (callee_result, _) = player::entrypoint(
             self.holder,
             callee_target,
             &ty_args,
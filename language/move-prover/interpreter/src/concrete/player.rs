@@ -133,6 +133,7 @@ impl<'env> FunctionContext<'env> {
                     bytecode.display(&self.target, &self.label_offsets)
                 );
             }
+            eval_state.count_instruction();
             self.exec_bytecode(bytecode, &mut local_state, global_state, eval_state)?;
         }
         Ok(local_state)
@@ -2248,7 +2249,8 @@ impl<'env> FunctionContext<'env> {
 // Entrypoint
 //**************************************************************************************************
 
-/// Entrypoint of the interpretation logic
+/// Entrypoint of the interpretation logic. Returns the result of the execution together with the
+/// number of bytecode instructions executed.
 pub fn entrypoint(
     holder: &FunctionTargetsHolder,
     target: FunctionTarget,
@@ -2257,14 +2259,15 @@ pub fn entrypoint(
     skip_specs: bool,
     level: usize,
     global_state: &mut GlobalState,
-) -> ExecResult<Vec<TypedValue>> {
+) -> (ExecResult<Vec<TypedValue>>, u64) {
     let mut eval_state = EvalState::default();
     let ctxt = FunctionContext::new(holder, target, ty_args.to_vec(), skip_specs, level);
-    let local_state = ctxt.exec_user_function(typed_args, global_state, &mut eval_state)?;
-    let termination = local_state.into_termination_status();
-    match termination {
-        TerminationStatus::Abort(abort_info) => Err(abort_info),
-        TerminationStatus::Return(return_vals) => Ok(return_vals),
-        TerminationStatus::None | TerminationStatus::PostAbort(_) => unreachable!(),
-    }
+    let result = ctxt
+        .exec_user_function(typed_args, global_state, &mut eval_state)
+        .and_then(|local_state| match local_state.into_termination_status() {
+            TerminationStatus::Abort(abort_info) => Err(abort_info),
+            TerminationStatus::Return(return_vals) => Ok(return_vals),
+            TerminationStatus::None | TerminationStatus::PostAbort(_) => unreachable!(),
+        });
+    (result, eval_state.instructions_executed())
 }
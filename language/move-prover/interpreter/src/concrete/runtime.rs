@@ -51,18 +51,21 @@ impl<'env> Runtime<'env> {
     }
 
     /// Execute a function (identified by `fun_id`) with given type arguments, arguments, and a
-    /// mutable reference of the global state. Returns the result of the execution. Any updates to
-    /// the global states is recorded in the mutable reference.
+    /// mutable reference of the global state. Returns the result of the execution and the number
+    /// of bytecode instructions executed. Any updates to the global states is recorded in the
+    /// mutable reference.
     pub fn execute(
         &self,
         fun_env: &FunctionEnv,
         ty_args: &[TypeTag],
         args: &[MoveValue],
         global_state: &mut GlobalState,
-    ) -> VMResult<Vec<TypedValue>> {
+    ) -> (VMResult<Vec<TypedValue>>, u64) {
         let (converted_ty_args, converted_args) =
-            check_and_convert_type_args_and_args(fun_env, ty_args, args)
-                .map_err(|e| e.finish(Location::Undefined))?;
+            match check_and_convert_type_args_and_args(fun_env, ty_args, args) {
+                Ok(converted) => converted,
+                Err(e) => return (Err(e.finish(Location::Undefined)), 0),
+            };
         let fun_target = choose_variant(self.functions, fun_env);
         self.execute_target(
             fun_target,
@@ -82,12 +85,12 @@ impl<'env> Runtime<'env> {
         ty_args: &[BaseType],
         args: &[TypedValue],
         global_state: &mut GlobalState,
-    ) -> VMResult<Vec<TypedValue>> {
+    ) -> (VMResult<Vec<TypedValue>>, u64) {
         let settings = self
             .env
             .get_extension::<InterpreterSettings>()
             .unwrap_or_default();
-        player::entrypoint(
+        let (result, instructions_executed) = player::entrypoint(
             self.functions,
             fun_target,
             ty_args,
@@ -95,8 +98,11 @@ impl<'env> Runtime<'env> {
             settings.no_expr_check,
             /* level */ 1,
             global_state,
+        );
+        (
+            result.map_err(|abort_info| abort_info.into_err()),
+            instructions_executed,
         )
-        .map_err(|abort_info| abort_info.into_err())
     }
 }
 
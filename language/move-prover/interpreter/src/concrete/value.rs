@@ -1149,9 +1149,21 @@ pub struct EvalState {
         MemoryLabel,
         BTreeMap<StructIdent, BTreeMap<StructInstantiation, BTreeMap<AccountAddress, BaseValue>>>,
     >,
+    // number of bytecode instructions executed so far
+    instructions_executed: u64,
 }
 
 impl EvalState {
+    /// Record the execution of one bytecode instruction
+    pub fn count_instruction(&mut self) {
+        self.instructions_executed += 1;
+    }
+
+    /// Return the number of bytecode instructions executed so far
+    pub fn instructions_executed(&self) -> u64 {
+        self.instructions_executed
+    }
+
     /// Collect resources of the (partial) instantiation type from the global state and save them
     /// under the given memory label
     pub fn save_memory(
@@ -3,6 +3,7 @@
 
 use anyhow::{bail, Result};
 use codespan_reporting::{diagnostic::Severity, term::termcolor::Buffer};
+use std::cell::Cell;
 use structopt::StructOpt;
 
 use move_binary_format::errors::{Location, PartialVMError, PartialVMResult, VMResult};
@@ -130,6 +131,7 @@ pub fn interpret_with_options(
 pub struct StacklessBytecodeInterpreter<'env> {
     pub env: &'env GlobalEnv,
     targets: FunctionTargetsHolder,
+    instructions_executed: Cell<u64>,
 }
 
 impl<'env> StacklessBytecodeInterpreter<'env> {
@@ -192,7 +194,11 @@ impl<'env> StacklessBytecodeInterpreter<'env> {
 
         // register settings with the env before returning
         env.set_extension(settings);
-        Self { env, targets }
+        Self {
+            env,
+            targets,
+            instructions_executed: Cell::new(0),
+        }
     }
 
     fn interpret_internal(
@@ -206,7 +212,9 @@ impl<'env> StacklessBytecodeInterpreter<'env> {
 
         // execute and convert results
         let vm = Runtime::new(self.env, &self.targets);
-        let vm_result = vm.execute(fun_env, ty_args, args, &mut new_global_state);
+        let (vm_result, instructions_executed) =
+            vm.execute(fun_env, ty_args, args, &mut new_global_state);
+        self.instructions_executed.set(instructions_executed);
         let serialized_vm_result = vm_result.map(|rets| {
             rets.into_iter()
                 .map(|v| {
@@ -286,6 +294,11 @@ impl<'env> StacklessBytecodeInterpreter<'env> {
         self.interpret_internal(&entrypoint_env, ty_args, &args, global_state)
     }
 
+    /// Return the number of bytecode instructions executed by the last interpretation.
+    pub fn instructions_executed(&self) -> u64 {
+        self.instructions_executed.get()
+    }
+
     pub fn report_property_checking_results(&self) -> Option<String> {
         if self.env.has_errors() {
             let mut buffer = Buffer::no_color();
@@ -9,4 +9,24 @@ module Std::UnitTest {
     /// This will cause a linking failure if an attempt is made to publish a
     /// test module in a VM that isn't in unit test mode.
     native public fun create_signers_for_testing(num_signers: u64): vector<signer>;
+
+    /// Return the value configured for `key` in the running test, either with a
+    /// `#[test(env = b"KEY=VALUE")]` attribute or with `--test-env KEY=VALUE` on the
+    /// command line. Aborts if `key` is not configured.
+    native public fun config(key: vector<u8>): vector<u8>;
+
+    /// Return the addresses holding a resource of type `T` in the storage of the
+    /// running test, in ascending order. This covers the resources published
+    /// before the test, e.g. by the fixture of its group, and those published by
+    /// the test itself.
+    native public fun resource_addresses<T: key>(): vector<address>;
+
+    /// Return the number of resources of type `T` published in the storage of the
+    /// running test.
+    native public fun resource_count<T: key>(): u64;
+
+    /// Assert that the running test emits an event equal to `msg`. The
+    /// assertion is checked once the test is done, against all the events
+    /// it emitted, so the event may be emitted before or after this call.
+    native public fun assert_event_emitted<T: drop + store>(msg: &T);
 }
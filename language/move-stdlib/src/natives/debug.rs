@@ -13,6 +13,24 @@ use move_vm_types::{
 use smallvec::smallvec;
 use std::collections::VecDeque;
 
+/// The output of the natives of this module in a session, if it is captured as a native context
+/// extension of the session rather than printed to stdout.
+#[cfg(feature = "testing")]
+#[derive(Debug, Default)]
+pub struct CapturedOutput(pub String);
+
+#[cfg(feature = "testing")]
+fn output_line(context: &mut NativeContext, line: &str) {
+    let extensions = context.extensions_mut();
+    if extensions.contains::<CapturedOutput>() {
+        let output = &mut extensions.get_mut::<CapturedOutput>().0;
+        output.push_str(line);
+        output.push('\n');
+    } else {
+        println!("{}", line);
+    }
+}
+
 #[allow(unused_mut)]
 #[allow(unused_variables)]
 pub fn native_print(
@@ -31,7 +49,7 @@ pub fn native_print(
 
         let mut buf = String::new();
         print_reference(&mut buf, &r)?;
-        println!("[debug] {}", buf);
+        output_line(context, &format!("[debug] {}", buf));
     }
 
     Ok(NativeResult::ok(ONE_GAS_UNIT, smallvec![]))
@@ -50,7 +68,7 @@ pub fn native_print_stack_trace(
     {
         let mut s = String::new();
         context.print_stack_trace(&mut s)?;
-        println!("{}", s);
+        output_line(context, &s);
     }
 
     Ok(NativeResult::ok(ONE_GAS_UNIT, smallvec![]))
@@ -45,6 +45,26 @@ pub fn all_natives(move_std_addr: AccountAddress) -> NativeFunctionTable {
             "create_signers_for_testing",
             unit_test::native_create_signers_for_testing,
         ),
+        #[cfg(feature = "testing")]
+        ("UnitTest", "config", unit_test::native_config),
+        #[cfg(feature = "testing")]
+        (
+            "UnitTest",
+            "resource_addresses",
+            unit_test::native_resource_addresses,
+        ),
+        #[cfg(feature = "testing")]
+        (
+            "UnitTest",
+            "resource_count",
+            unit_test::native_resource_count,
+        ),
+        #[cfg(feature = "testing")]
+        (
+            "UnitTest",
+            "assert_event_emitted",
+            unit_test::native_assert_event_emitted,
+        ),
     ];
     NATIVES
         .iter()
@@ -2,16 +2,53 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use move_binary_format::errors::PartialVMResult;
-use move_core_types::gas_schedule::ONE_GAS_UNIT;
+use move_core_types::{gas_schedule::ONE_GAS_UNIT, language_storage::TypeTag};
 use move_vm_runtime::native_functions::NativeContext;
 use move_vm_types::{
-    loaded_data::runtime_types::Type, natives::function::NativeResult, pop_arg, values::Value,
+    loaded_data::runtime_types::Type,
+    natives::function::NativeResult,
+    pop_arg,
+    values::{Reference, Value},
 };
 use smallvec::smallvec;
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 
 use move_core_types::account_address::AccountAddress;
 
+/// Abort code of `UnitTest::config` if the requested key is not configured.
+const ECONFIG_NOT_FOUND: u64 = 0;
+
+/// Abort code of `UnitTest::assert_event_emitted` if the event cannot be serialized.
+const EEVENT_NOT_SERIALIZABLE: u64 = 1;
+
+/// The state of the test executing in a session, which the natives of `UnitTest` read and update
+/// as a native context extension of the session. In a session without it, no key is configured,
+/// no address holds resources and the events asserted to be emitted are not recorded.
+#[derive(Debug, Default)]
+pub struct UnitTestContext {
+    /// The key/value configuration returned by `UnitTest::config`
+    pub config: BTreeMap<Vec<u8>, Vec<u8>>,
+    /// The addresses searched by `UnitTest::resource_addresses`, extended with the addresses of
+    /// the signers the test creates. These must include every address holding a resource in the
+    /// storage the test starts from, and the addresses of the signers passed to the test, as
+    /// resources can only be published under a signer.
+    pub addresses: BTreeSet<AccountAddress>,
+    /// The events, as their type and their BCS bytes, which the test asserts to be emitted with
+    /// `UnitTest::assert_event_emitted`, in the order of the assertions. The test runner checks
+    /// them against the events the test emitted once it is done.
+    pub expected_events: Vec<(TypeTag, Vec<u8>)>,
+}
+
+/// The test context of the session `context` belongs to, if it has one
+fn test_context<'a>(context: &'a mut NativeContext) -> Option<&'a mut UnitTestContext> {
+    let extensions = context.extensions_mut();
+    if extensions.contains::<UnitTestContext>() {
+        Some(extensions.get_mut::<UnitTestContext>())
+    } else {
+        None
+    }
+}
+
 fn to_le_bytes(i: u64) -> [u8; AccountAddress::LENGTH] {
     let bytes = i.to_le_bytes();
     let mut result = [0u8; AccountAddress::LENGTH];
@@ -20,7 +57,7 @@ fn to_le_bytes(i: u64) -> [u8; AccountAddress::LENGTH] {
 }
 
 pub fn native_create_signers_for_testing(
-    _context: &mut NativeContext,
+    context: &mut NativeContext,
     ty_args: Vec<Type>,
     mut args: VecDeque<Value>,
 ) -> PartialVMResult<NativeResult> {
@@ -28,9 +65,102 @@ pub fn native_create_signers_for_testing(
     debug_assert!(args.len() == 1);
 
     let num_signers = pop_arg!(args, u64);
-    let signers = Value::vector_for_testing_only(
-        (0..num_signers).map(|i| Value::signer(AccountAddress::new(to_le_bytes(i)))),
-    );
+    let addresses = (0..num_signers)
+        .map(|i| AccountAddress::new(to_le_bytes(i)))
+        .collect::<Vec<_>>();
+    if let Some(test_context) = test_context(context) {
+        test_context.addresses.extend(addresses.iter().cloned());
+    }
+    let signers = Value::vector_for_testing_only(addresses.into_iter().map(Value::signer));
 
     Ok(NativeResult::ok(ONE_GAS_UNIT, smallvec![signers]))
 }
+
+pub fn native_config(
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    debug_assert!(ty_args.is_empty());
+    debug_assert!(args.len() == 1);
+
+    let key = pop_arg!(args, Vec<u8>);
+    let value = test_context(context).and_then(|test_context| test_context.config.get(&key));
+    Ok(match value {
+        Some(value) => NativeResult::ok(ONE_GAS_UNIT, smallvec![Value::vector_u8(value.clone())]),
+        None => NativeResult::err(ONE_GAS_UNIT, ECONFIG_NOT_FOUND),
+    })
+}
+
+/// The addresses holding a resource of type `ty`, in ascending order.
+fn resource_addresses(
+    context: &mut NativeContext,
+    ty: &Type,
+) -> PartialVMResult<Vec<AccountAddress>> {
+    let candidates = test_context(context)
+        .map(|test_context| test_context.addresses.clone())
+        .unwrap_or_default();
+    let mut addresses = vec![];
+    for address in candidates {
+        if context.exists_at(address, ty)? {
+            addresses.push(address);
+        }
+    }
+    Ok(addresses)
+}
+
+pub fn native_resource_addresses(
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    debug_assert!(ty_args.len() == 1);
+    debug_assert!(args.is_empty());
+
+    let addresses = resource_addresses(context, &ty_args[0])?;
+    Ok(NativeResult::ok(
+        ONE_GAS_UNIT,
+        smallvec![Value::vector_address(addresses)],
+    ))
+}
+
+pub fn native_resource_count(
+    context: &mut NativeContext,
+    ty_args: Vec<Type>,
+    args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    debug_assert!(ty_args.len() == 1);
+    debug_assert!(args.is_empty());
+
+    let count = resource_addresses(context, &ty_args[0])?.len();
+    Ok(NativeResult::ok(
+        ONE_GAS_UNIT,
+        smallvec![Value::u64(count as u64)],
+    ))
+}
+
+pub fn native_assert_event_emitted(
+    context: &mut NativeContext,
+    mut ty_args: Vec<Type>,
+    mut args: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    debug_assert!(ty_args.len() == 1);
+    debug_assert!(args.len() == 1);
+
+    let event = pop_arg!(args, Reference).read_ref()?;
+    let ty = ty_args.pop().unwrap();
+    let bytes = match context.type_to_type_layout(&ty)? {
+        None => None,
+        Some(layout) => event.simple_serialize(&layout),
+    };
+    let bytes = match bytes {
+        Some(bytes) => bytes,
+        None => return Ok(NativeResult::err(ONE_GAS_UNIT, EEVENT_NOT_SERIALIZABLE)),
+    };
+    let tag = context.type_to_type_tag(&ty)?;
+    if let Some(test_context) = test_context(context) {
+        test_context.expected_events.push((tag, bytes));
+    }
+
+    Ok(NativeResult::ok(ONE_GAS_UNIT, smallvec![]))
+}
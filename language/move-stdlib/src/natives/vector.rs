@@ -54,6 +54,10 @@ pub fn native_push_back(
         NativeCostIndex::PUSH_BACK,
         e.size().get() as usize,
     );
+    if context.vm_config().max_vector_length.is_some() {
+        let len = r.len(&ty_args[0])?.value_as::<u64>()?;
+        context.vm_config().check_vector_length(len + 1)?;
+    }
     NativeResult::map_partial_vm_result_empty(cost, r.push_back(e, &ty_args[0]))
 }
 
@@ -0,0 +1,48 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_binary_format::errors::{PartialVMError, PartialVMResult};
+use move_core_types::vm_status::StatusCode;
+use move_vm_types::values::VEC_LENGTH_LIMIT_EXCEEDED;
+
+/// The default largest number of nested function calls
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 1024;
+/// The default largest depth of the type of a value loaded from or written to storage
+pub const DEFAULT_MAX_VALUE_DEPTH: usize = 256;
+
+/// The limits the VM enforces on the execution of functions, given to `MoveVM::new_with_config`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VMConfig {
+    /// The largest number of nested function calls, beyond which execution fails with
+    /// `CALL_STACK_OVERFLOW`
+    pub max_call_depth: usize,
+    /// The largest depth of the type of a value loaded from or written to storage, or passed to
+    /// or returned by a function, beyond which `VM_MAX_VALUE_DEPTH_REACHED` is raised
+    pub max_value_depth: usize,
+    /// The largest number of elements of a vector, if any, beyond which vector operations fail
+    /// with `VECTOR_OPERATION_ERROR`
+    pub max_vector_length: Option<u64>,
+}
+
+impl VMConfig {
+    /// Fail if a vector of `len` elements is longer than the VM allows.
+    pub fn check_vector_length(&self, len: u64) -> PartialVMResult<()> {
+        match self.max_vector_length {
+            Some(max_len) if len > max_len => {
+                Err(PartialVMError::new(StatusCode::VECTOR_OPERATION_ERROR)
+                    .with_sub_status(VEC_LENGTH_LIMIT_EXCEEDED))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for VMConfig {
+    fn default() -> Self {
+        Self {
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            max_value_depth: DEFAULT_MAX_VALUE_DEPTH,
+            max_vector_length: None,
+        }
+    }
+}
@@ -0,0 +1,23 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+/// Statistics on the memory used by the functions executed in a session. They are only collected
+/// with the `execution-statistics` feature, and if added to the native context extensions of the
+/// session, from which they are handed back by `Session::finish_with_extensions`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExecutionStatistics {
+    /// The largest number of values on the operand stack at any point
+    pub max_operand_stack_depth: usize,
+    /// The number of values pushed on the operand stack, each of them a value created by the
+    /// instruction pushing it
+    pub values_allocated: u64,
+}
+
+impl ExecutionStatistics {
+    /// Record the execution of a function which reached `max_operand_stack_depth` and allocated
+    /// `values_allocated` values.
+    pub(crate) fn record(&mut self, max_operand_stack_depth: usize, values_allocated: u64) {
+        self.max_operand_stack_depth = self.max_operand_stack_depth.max(max_operand_stack_depth);
+        self.values_allocated += values_allocated;
+    }
+}
@@ -1,8 +1,11 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(feature = "execution-statistics")]
+use crate::execution_statistics::ExecutionStatistics;
 use crate::{
     loader::{Function, Loader, Resolver},
+    native_extensions::NativeContextExtensions,
     native_functions::NativeContext,
     trace,
 };
@@ -74,20 +77,23 @@ impl Interpreter {
         args: Vec<Value>,
         data_store: &mut impl DataStore,
         gas_status: &mut GasStatus,
+        extensions: &mut NativeContextExtensions,
         loader: &Loader,
     ) -> VMResult<Vec<Value>> {
         // We count the intrinsic cost of the transaction here, since that needs to also cover the
         // setup of the function.
-        let mut interp = Self::new();
-        interp.execute(loader, data_store, gas_status, function, ty_args, args)
+        let mut interp = Self::new(loader.vm_config().max_call_depth);
+        interp.execute(
+            loader, data_store, gas_status, extensions, function, ty_args, args,
+        )
     }
 
     /// Create a new instance of an `Interpreter` in the context of a transaction with a
     /// given module cache and gas schedule.
-    fn new() -> Self {
+    fn new(max_call_depth: usize) -> Self {
         Interpreter {
             operand_stack: Stack::new(),
-            call_stack: CallStack::new(),
+            call_stack: CallStack::new(max_call_depth),
         }
     }
 
@@ -97,13 +103,23 @@ impl Interpreter {
         loader: &Loader,
         data_store: &mut impl DataStore,
         gas_status: &mut GasStatus,
+        extensions: &mut NativeContextExtensions,
         function: Arc<Function>,
         ty_args: Vec<Type>,
         args: Vec<Value>,
     ) -> VMResult<Vec<Value>> {
         // No unwinding of the call stack and value stack need to be done here -- the context will
         // take care of that.
-        self.execute_main(loader, data_store, gas_status, function, ty_args, args)
+        let result = self.execute_main(
+            loader, data_store, gas_status, extensions, function, ty_args, args,
+        );
+        #[cfg(feature = "execution-statistics")]
+        if extensions.contains::<ExecutionStatistics>() {
+            extensions
+                .get_mut::<ExecutionStatistics>()
+                .record(self.operand_stack.max_len, self.operand_stack.num_pushed);
+        }
+        result
     }
 
     /// Main loop for the execution of a function.
@@ -119,6 +135,7 @@ impl Interpreter {
         loader: &Loader,
         data_store: &mut impl DataStore,
         gas_status: &mut GasStatus,
+        extensions: &mut NativeContextExtensions,
         function: Arc<Function>,
         ty_args: Vec<Type>,
         args: Vec<Value>,
@@ -142,7 +159,7 @@ impl Interpreter {
                         current_frame = frame;
                         current_frame.pc += 1; // advance past the Call instruction in the caller
                     } else {
-                        return Ok(mem::take(&mut self.operand_stack.0));
+                        return Ok(mem::take(&mut self.operand_stack.values));
                     }
                 }
                 ExitCode::Call(fh_idx) => {
@@ -157,7 +174,14 @@ impl Interpreter {
                         )
                         .map_err(|e| set_err_info!(current_frame, e))?;
                     if func.is_native() {
-                        self.call_native(&resolver, data_store, gas_status, func, vec![])?;
+                        self.call_native(
+                            &resolver,
+                            data_store,
+                            gas_status,
+                            extensions,
+                            func,
+                            vec![],
+                        )?;
                         current_frame.pc += 1; // advance past the Call instruction in the caller
                         continue;
                     }
@@ -190,7 +214,9 @@ impl Interpreter {
                         )
                         .map_err(|e| set_err_info!(current_frame, e))?;
                     if func.is_native() {
-                        self.call_native(&resolver, data_store, gas_status, func, ty_args)?;
+                        self.call_native(
+                            &resolver, data_store, gas_status, extensions, func, ty_args,
+                        )?;
                         current_frame.pc += 1; // advance past the Call instruction in the caller
                         continue;
                     }
@@ -233,23 +259,29 @@ impl Interpreter {
         resolver: &Resolver,
         data_store: &mut dyn DataStore,
         gas_status: &mut GasStatus,
+        extensions: &mut NativeContextExtensions,
         function: Arc<Function>,
         ty_args: Vec<Type>,
     ) -> VMResult<()> {
         // Note: refactor if native functions push a frame on the stack
-        self.call_native_impl(resolver, data_store, gas_status, function.clone(), ty_args)
-            .map_err(|e| match function.module_id() {
-                Some(id) => e
-                    .at_code_offset(function.index(), 0)
-                    .finish(Location::Module(id.clone())),
-                None => {
-                    let err = PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
-                        .with_message(
-                            "Unexpected native function not located in a module".to_owned(),
-                        );
-                    self.set_location(err)
-                }
-            })
+        self.call_native_impl(
+            resolver,
+            data_store,
+            gas_status,
+            extensions,
+            function.clone(),
+            ty_args,
+        )
+        .map_err(|e| match function.module_id() {
+            Some(id) => e
+                .at_code_offset(function.index(), 0)
+                .finish(Location::Module(id.clone())),
+            None => {
+                let err = PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+                    .with_message("Unexpected native function not located in a module".to_owned());
+                self.set_location(err)
+            }
+        })
     }
 
     fn call_native_impl(
@@ -257,6 +289,7 @@ impl Interpreter {
         resolver: &Resolver,
         data_store: &mut dyn DataStore,
         gas_status: &mut GasStatus,
+        extensions: &mut NativeContextExtensions,
         function: Arc<Function>,
         ty_args: Vec<Type>,
     ) -> PartialVMResult<()> {
@@ -265,7 +298,8 @@ impl Interpreter {
         for _ in 0..expected_args {
             arguments.push_front(self.operand_stack.pop()?);
         }
-        let mut native_context = NativeContext::new(self, data_store, gas_status, resolver);
+        let mut native_context =
+            NativeContext::new(self, data_store, gas_status, resolver, extensions);
         let native_function = function.get_native()?;
         let result = native_function(&mut native_context, ty_args, arguments)?;
         gas_status.deduct_gas(result.cost)?;
@@ -482,11 +516,11 @@ impl Interpreter {
         loader: &Loader,
     ) -> PartialVMResult<()> {
         debug_writeln!(buf, "Call Stack:")?;
-        for (i, frame) in self.call_stack.0.iter().enumerate() {
+        for (i, frame) in self.call_stack.frames.iter().enumerate() {
             self.debug_print_frame(buf, loader, i, frame)?;
         }
         debug_writeln!(buf, "Operand Stack:")?;
-        for (idx, val) in self.operand_stack.0.iter().enumerate() {
+        for (idx, val) in self.operand_stack.values.iter().enumerate() {
             // TODO: Currently we do not know the types of the values on the operand stack.
             // Revisit.
             debug_write!(buf, "    [{}] ", idx)?;
@@ -504,7 +538,7 @@ impl Interpreter {
     /// of an execution.
     fn get_internal_state(&self, current_frame: &Frame) -> String {
         let mut internal_state = "Call stack:\n".to_string();
-        for (i, frame) in self.call_stack.0.iter().enumerate() {
+        for (i, frame) in self.call_stack.frames.iter().enumerate() {
             internal_state.push_str(
                 format!(
                     " frame #{}: {} [pc = {}]\n",
@@ -518,7 +552,7 @@ impl Interpreter {
         internal_state.push_str(
             format!(
                 "*frame #{}: {} [pc = {}]:\n",
-                self.call_stack.0.len(),
+                self.call_stack.frames.len(),
                 current_frame.function.pretty_string(),
                 current_frame.pc,
             )
@@ -536,7 +570,7 @@ impl Interpreter {
         }
         internal_state.push_str(format!("Locals:\n{}\n", current_frame.locals).as_str());
         internal_state.push_str("Operand Stack:\n");
-        for value in &self.operand_stack.0 {
+        for value in &self.operand_stack.values {
             internal_state.push_str(format!("{}\n", value).as_str());
         }
         internal_state
@@ -549,22 +583,40 @@ impl Interpreter {
 
 // TODO Determine stack size limits based on gas limit
 const OPERAND_STACK_SIZE_LIMIT: usize = 1024;
-const CALL_STACK_SIZE_LIMIT: usize = 1024;
 
 /// The operand stack.
-struct Stack(Vec<Value>);
+struct Stack {
+    values: Vec<Value>,
+    // The largest number of values on the stack so far
+    #[cfg(feature = "execution-statistics")]
+    max_len: usize,
+    // The number of values pushed on the stack so far
+    #[cfg(feature = "execution-statistics")]
+    num_pushed: u64,
+}
 
 impl Stack {
     /// Create a new empty operand stack.
     fn new() -> Self {
-        Stack(vec![])
+        Stack {
+            values: vec![],
+            #[cfg(feature = "execution-statistics")]
+            max_len: 0,
+            #[cfg(feature = "execution-statistics")]
+            num_pushed: 0,
+        }
     }
 
     /// Push a `Value` on the stack if the max stack size has not been reached. Abort execution
     /// otherwise.
     fn push(&mut self, value: Value) -> PartialVMResult<()> {
-        if self.0.len() < OPERAND_STACK_SIZE_LIMIT {
-            self.0.push(value);
+        if self.values.len() < OPERAND_STACK_SIZE_LIMIT {
+            self.values.push(value);
+            #[cfg(feature = "execution-statistics")]
+            {
+                self.max_len = self.max_len.max(self.values.len());
+                self.num_pushed += 1;
+            }
             Ok(())
         } else {
             Err(PartialVMError::new(StatusCode::EXECUTION_STACK_OVERFLOW))
@@ -573,7 +625,7 @@ impl Stack {
 
     /// Pop a `Value` off the stack or abort execution if the stack is empty.
     fn pop(&mut self) -> PartialVMResult<Value> {
-        self.0
+        self.values
             .pop()
             .ok_or_else(|| PartialVMError::new(StatusCode::EMPTY_VALUE_STACK))
     }
@@ -590,29 +642,36 @@ impl Stack {
     /// Pop n values off the stack.
     fn popn(&mut self, n: u16) -> PartialVMResult<Vec<Value>> {
         let remaining_stack_size = self
-            .0
+            .values
             .len()
             .checked_sub(n as usize)
             .ok_or_else(|| PartialVMError::new(StatusCode::EMPTY_VALUE_STACK))?;
-        let args = self.0.split_off(remaining_stack_size);
+        let args = self.values.split_off(remaining_stack_size);
         Ok(args)
     }
 }
 
 /// A call stack.
 // #[derive(Debug)]
-struct CallStack(Vec<Frame>);
+struct CallStack {
+    frames: Vec<Frame>,
+    // The largest number of frames on the stack
+    max_depth: usize,
+}
 
 impl CallStack {
-    /// Create a new empty call stack.
-    fn new() -> Self {
-        CallStack(vec![])
+    /// Create a new empty call stack, which holds at most `max_depth` frames.
+    fn new(max_depth: usize) -> Self {
+        CallStack {
+            frames: vec![],
+            max_depth,
+        }
     }
 
     /// Push a `Frame` on the call stack.
     fn push(&mut self, frame: Frame) -> ::std::result::Result<(), Frame> {
-        if self.0.len() < CALL_STACK_SIZE_LIMIT {
-            self.0.push(frame);
+        if self.frames.len() < self.max_depth {
+            self.frames.push(frame);
             Ok(())
         } else {
             Err(frame)
@@ -621,11 +680,11 @@ impl CallStack {
 
     /// Pop a `Frame` off the call stack.
     fn pop(&mut self) -> Option<Frame> {
-        self.0.pop()
+        self.frames.pop()
     }
 
     fn current_location(&self) -> Location {
-        let location_opt = self.0.last().map(|frame| frame.location());
+        let location_opt = self.frames.last().map(|frame| frame.location());
         location_opt.unwrap_or(Location::Undefined)
     }
 }
@@ -1096,6 +1155,7 @@ impl Frame {
                         gas_status.charge_instr(Opcodes::NOP)?;
                     }
                     Bytecode::VecPack(si, num) => {
+                        resolver.loader().vm_config().check_vector_length(*num)?;
                         let elements = interpreter.operand_stack.popn(*num as u16)?;
                         let size = AbstractMemorySize::new(*num);
                         gas_status.charge_instr_with_size(Opcodes::VEC_PACK, size)?;
@@ -1126,6 +1186,13 @@ impl Frame {
                         let elem = interpreter.operand_stack.pop()?;
                         let vec_ref = interpreter.operand_stack.pop_as::<VectorRef>()?;
                         gas_status.charge_instr_with_size(Opcodes::VEC_PUSH_BACK, elem.size())?;
+                        let vm_config = resolver.loader().vm_config();
+                        if vm_config.max_vector_length.is_some() {
+                            let len = vec_ref
+                                .len(resolver.single_type_at(*si))?
+                                .value_as::<u64>()?;
+                            vm_config.check_vector_length(len + 1)?;
+                        }
                         vec_ref.push_back(elem, resolver.single_type_at(*si))?;
                     }
                     Bytecode::VecPopBack(si) => {
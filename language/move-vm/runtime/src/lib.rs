@@ -12,11 +12,16 @@
 #[macro_use]
 extern crate mirai_annotations;
 
+pub mod config;
 pub mod data_cache;
+// Only collect execution statistics if requested, as they are updated on each push to the stack
+#[cfg(feature = "execution-statistics")]
+pub mod execution_statistics;
 mod interpreter;
 mod loader;
 pub mod logging;
 pub mod move_vm;
+pub mod native_extensions;
 pub mod native_functions;
 mod runtime;
 pub mod session;
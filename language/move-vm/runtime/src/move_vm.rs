@@ -2,8 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    data_cache::TransactionDataCache, native_functions::NativeFunction, runtime::VMRuntime,
-    session::Session,
+    config::VMConfig, data_cache::TransactionDataCache, native_extensions::NativeContextExtensions,
+    native_functions::NativeFunction, runtime::VMRuntime, session::Session,
 };
 use move_binary_format::errors::{Location, VMResult};
 use move_core_types::{
@@ -17,11 +17,20 @@ pub struct MoveVM {
 
 impl MoveVM {
     pub fn new<I>(natives: I) -> VMResult<Self>
+    where
+        I: IntoIterator<Item = (AccountAddress, Identifier, Identifier, NativeFunction)>,
+    {
+        Self::new_with_config(natives, VMConfig::default())
+    }
+
+    /// Create a VM which enforces the limits of `vm_config` rather than the default ones.
+    pub fn new_with_config<I>(natives: I, vm_config: VMConfig) -> VMResult<Self>
     where
         I: IntoIterator<Item = (AccountAddress, Identifier, Identifier, NativeFunction)>,
     {
         Ok(Self {
-            runtime: VMRuntime::new(natives).map_err(|err| err.finish(Location::Undefined))?,
+            runtime: VMRuntime::new(natives, vm_config)
+                .map_err(|err| err.finish(Location::Undefined))?,
         })
     }
 
@@ -43,6 +52,18 @@ impl MoveVM {
         self.runtime.new_session(remote)
     }
 
+    /// Create a new Session backed by the given storage, whose native functions have access to
+    /// `extensions` through their `NativeContext`.
+    ///
+    /// The same cache coherence requirements as for `new_session` apply.
+    pub fn new_session_with_extensions<'r, S: MoveResolver>(
+        &self,
+        remote: &'r S,
+        extensions: NativeContextExtensions,
+    ) -> Session<'r, '_, S> {
+        self.runtime.new_session_with_extensions(remote, extensions)
+    }
+
     /// Load a module into VM's code cache
     pub fn load_module<'r, S: MoveResolver>(
         &self,
@@ -0,0 +1,60 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+};
+
+/// A collection of extensions which are available to native functions through their
+/// `NativeContext`, such as the state of a table or event store kept outside of global storage.
+/// Each extension is keyed by its type, so there is at most one extension of each type.
+///
+/// The extensions of a session are handed to it with `MoveVM::new_session_with_extensions`, and
+/// handed back by `Session::finish_with_extensions`.
+#[derive(Default)]
+pub struct NativeContextExtensions {
+    map: HashMap<TypeId, Box<dyn Any>>,
+}
+
+impl NativeContextExtensions {
+    /// Add the extension `ext`. Panics if there already is an extension of the same type.
+    pub fn add<T: Any>(&mut self, ext: T) {
+        assert!(
+            self.map.insert(TypeId::of::<T>(), Box::new(ext)).is_none(),
+            "duplicate native context extension"
+        )
+    }
+
+    /// Whether there is an extension of type `T`.
+    pub fn contains<T: Any>(&self) -> bool {
+        self.map.contains_key(&TypeId::of::<T>())
+    }
+
+    /// The extension of type `T`. Panics if there is no such extension.
+    pub fn get<T: Any>(&self) -> &T {
+        self.map
+            .get(&TypeId::of::<T>())
+            .and_then(|ext| ext.downcast_ref::<T>())
+            .expect("missing native context extension")
+    }
+
+    /// The extension of type `T`, mutably. Panics if there is no such extension.
+    pub fn get_mut<T: Any>(&mut self) -> &mut T {
+        self.map
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|ext| ext.downcast_mut::<T>())
+            .expect("missing native context extension")
+    }
+
+    /// Remove the extension of type `T` and return it. Panics if there is no such extension.
+    pub fn remove<T: Any>(&mut self) -> T {
+        // The extension is keyed by its type, so the downcast cannot fail
+        *self
+            .map
+            .remove(&TypeId::of::<T>())
+            .expect("missing native context extension")
+            .downcast::<T>()
+            .unwrap()
+    }
+}
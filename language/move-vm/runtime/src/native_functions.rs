@@ -1,12 +1,16 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{interpreter::Interpreter, loader::Resolver};
+use crate::{
+    config::VMConfig, interpreter::Interpreter, loader::Resolver,
+    native_extensions::NativeContextExtensions,
+};
 use move_binary_format::errors::{PartialVMError, PartialVMResult};
 use move_core_types::{
     account_address::AccountAddress,
     gas_schedule::CostTable,
     identifier::Identifier,
+    language_storage::TypeTag,
     value::MoveTypeLayout,
     vm_status::{StatusCode, StatusType},
 };
@@ -62,6 +66,7 @@ pub struct NativeContext<'a> {
     data_store: &'a mut dyn DataStore,
     gas_status: &'a GasStatus<'a>,
     resolver: &'a Resolver<'a>,
+    extensions: &'a mut NativeContextExtensions,
 }
 
 impl<'a, 'b> NativeContext<'a> {
@@ -70,12 +75,14 @@ impl<'a, 'b> NativeContext<'a> {
         data_store: &'a mut dyn DataStore,
         gas_status: &'a mut GasStatus,
         resolver: &'a Resolver<'a>,
+        extensions: &'a mut NativeContextExtensions,
     ) -> Self {
         Self {
             interpreter,
             data_store,
             gas_status,
             resolver,
+            extensions,
         }
     }
 }
@@ -90,6 +97,11 @@ impl<'a> NativeContext<'a> {
         self.gas_status.cost_table()
     }
 
+    /// The limits the VM enforces, which natives growing values also enforce.
+    pub fn vm_config(&self) -> &VMConfig {
+        self.resolver.loader().vm_config()
+    }
+
     pub fn save_event(
         &mut self,
         guid: Vec<u8>,
@@ -108,6 +120,11 @@ impl<'a> NativeContext<'a> {
         self.data_store.events()
     }
 
+    /// Whether a resource of the given type is published at the given address.
+    pub fn exists_at(&mut self, address: AccountAddress, ty: &Type) -> PartialVMResult<bool> {
+        self.data_store.load_resource(address, ty)?.exists()
+    }
+
     pub fn type_to_type_layout(&self, ty: &Type) -> PartialVMResult<Option<MoveTypeLayout>> {
         match self.resolver.type_to_type_layout(ty) {
             Ok(ty_layout) => Ok(Some(ty_layout)),
@@ -115,4 +132,18 @@ impl<'a> NativeContext<'a> {
             Err(_) => Ok(None),
         }
     }
+
+    pub fn type_to_type_tag(&self, ty: &Type) -> PartialVMResult<TypeTag> {
+        self.resolver.loader().type_to_type_tag(ty)
+    }
+
+    /// The extensions of the session the native function is called in.
+    pub fn extensions(&self) -> &NativeContextExtensions {
+        self.extensions
+    }
+
+    /// The extensions of the session the native function is called in, mutably.
+    pub fn extensions_mut(&mut self) -> &mut NativeContextExtensions {
+        self.extensions
+    }
 }
@@ -2,9 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    config::VMConfig,
     data_cache::TransactionDataCache,
     interpreter::Interpreter,
     loader::Loader,
+    native_extensions::NativeContextExtensions,
     native_functions::{NativeFunction, NativeFunctions},
     session::Session,
 };
@@ -46,19 +48,28 @@ fn is_signer_reference(s: &Type) -> bool {
 }
 
 impl VMRuntime {
-    pub(crate) fn new<I>(natives: I) -> PartialVMResult<Self>
+    pub(crate) fn new<I>(natives: I, vm_config: VMConfig) -> PartialVMResult<Self>
     where
         I: IntoIterator<Item = (AccountAddress, Identifier, Identifier, NativeFunction)>,
     {
         Ok(VMRuntime {
-            loader: Loader::new(NativeFunctions::new(natives)?),
+            loader: Loader::new(NativeFunctions::new(natives)?, vm_config),
         })
     }
 
     pub fn new_session<'r, S: MoveResolver>(&self, remote: &'r S) -> Session<'r, '_, S> {
+        self.new_session_with_extensions(remote, NativeContextExtensions::default())
+    }
+
+    pub fn new_session_with_extensions<'r, S: MoveResolver>(
+        &self,
+        remote: &'r S,
+        native_extensions: NativeContextExtensions,
+    ) -> Session<'r, '_, S> {
         Session {
             runtime: self,
             data_cache: TransactionDataCache::new(remote, &self.loader),
+            native_extensions,
         }
     }
 
@@ -319,6 +330,7 @@ impl VMRuntime {
         senders: Vec<AccountAddress>,
         data_store: &mut impl DataStore,
         gas_status: &mut GasStatus,
+        extensions: &mut NativeContextExtensions,
     ) -> VMResult<()> {
         // load the script, perform verification
         let (main, ty_args, params) = self.loader.load_script(&script, &ty_args, data_store)?;
@@ -333,6 +345,7 @@ impl VMRuntime {
             signers_and_args,
             data_store,
             gas_status,
+            extensions,
             &self.loader,
         )?;
 
@@ -357,6 +370,7 @@ impl VMRuntime {
         args: Vec<Vec<u8>>,
         data_store: &mut impl DataStore,
         gas_status: &mut GasStatus,
+        extensions: &mut NativeContextExtensions,
     ) -> VMResult<(Vec<Vec<u8>>, Vec<Vec<u8>>)> {
         // TODO: convert numerous unwraps below into the appropriate error
         let is_script_execution = false;
@@ -398,8 +412,15 @@ impl VMRuntime {
             }
         }
 
-        let return_vals =
-            Interpreter::entrypoint(func, ty_args, actuals, data_store, gas_status, &self.loader)?;
+        let return_vals = Interpreter::entrypoint(
+            func,
+            ty_args,
+            actuals,
+            data_store,
+            gas_status,
+            extensions,
+            &self.loader,
+        )?;
 
         let return_layouts = return_tys
             .iter()
@@ -459,6 +480,7 @@ impl VMRuntime {
         is_script_execution: bool,
         data_store: &mut impl DataStore,
         gas_status: &mut GasStatus,
+        extensions: &mut NativeContextExtensions,
     ) -> VMResult<Vec<Vec<u8>>>
     where
         F: FnOnce(&VMRuntime, u32, &[Type]) -> PartialVMResult<Vec<Value>>,
@@ -493,8 +515,15 @@ impl VMRuntime {
         let args = make_args(self, func.file_format_version(), &params)
             .map_err(|err| err.finish(Location::Undefined))?;
 
-        let return_vals =
-            Interpreter::entrypoint(func, ty_args, args, data_store, gas_status, &self.loader)?;
+        let return_vals = Interpreter::entrypoint(
+            func,
+            ty_args,
+            args,
+            data_store,
+            gas_status,
+            extensions,
+            &self.loader,
+        )?;
 
         if return_layouts.len() != return_vals.len() {
             return Err(
@@ -530,6 +559,7 @@ impl VMRuntime {
         senders: Vec<AccountAddress>,
         data_store: &mut impl DataStore,
         gas_status: &mut GasStatus,
+        extensions: &mut NativeContextExtensions,
     ) -> VMResult<()> {
         let return_vals = self.execute_function_impl(
             module,
@@ -541,6 +571,7 @@ impl VMRuntime {
             true,
             data_store,
             gas_status,
+            extensions,
         )?;
 
         // A script function that serves as the entry point of execution cannot have return values,
@@ -570,6 +601,7 @@ impl VMRuntime {
         args: Vec<Vec<u8>>,
         data_store: &mut impl DataStore,
         gas_status: &mut GasStatus,
+        extensions: &mut NativeContextExtensions,
     ) -> VMResult<Vec<Vec<u8>>> {
         self.execute_function_impl(
             module,
@@ -579,6 +611,7 @@ impl VMRuntime {
             false,
             data_store,
             gas_status,
+            extensions,
         )
     }
 
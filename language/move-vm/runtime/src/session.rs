@@ -1,7 +1,10 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{data_cache::TransactionDataCache, runtime::VMRuntime};
+use crate::{
+    data_cache::TransactionDataCache, native_extensions::NativeContextExtensions,
+    runtime::VMRuntime,
+};
 use move_binary_format::errors::*;
 use move_core_types::{
     account_address::AccountAddress,
@@ -17,6 +20,7 @@ use move_vm_types::gas_schedule::GasStatus;
 pub struct Session<'r, 'l, S> {
     pub(crate) runtime: &'l VMRuntime,
     pub(crate) data_cache: TransactionDataCache<'r, 'l, S>,
+    pub(crate) native_extensions: NativeContextExtensions,
 }
 
 /// Result of executing a function in the VM
@@ -74,6 +78,7 @@ impl<'r, 'l, S: MoveResolver> Session<'r, 'l, S> {
             args,
             &mut self.data_cache,
             gas_status,
+            &mut self.native_extensions,
         )
     }
 
@@ -106,6 +111,7 @@ impl<'r, 'l, S: MoveResolver> Session<'r, 'l, S> {
             args,
             &mut self.data_cache,
             gas_status,
+            &mut self.native_extensions,
         );
         let gas_used = gas_budget - gas_status.remaining_gas().get();
         match execution_res {
@@ -164,6 +170,7 @@ impl<'r, 'l, S: MoveResolver> Session<'r, 'l, S> {
             senders,
             &mut self.data_cache,
             gas_status,
+            &mut self.native_extensions,
         )
     }
 
@@ -198,6 +205,7 @@ impl<'r, 'l, S: MoveResolver> Session<'r, 'l, S> {
             senders,
             &mut self.data_cache,
             gas_status,
+            &mut self.native_extensions,
         )
     }
 
@@ -260,6 +268,22 @@ impl<'r, 'l, S: MoveResolver> Session<'r, 'l, S> {
             .map_err(|e| e.finish(Location::Undefined))
     }
 
+    /// Same as `finish`, but also hand back the native context extensions of the session, e.g.
+    /// to collect the effects which natives recorded in them.
+    pub fn finish_with_extensions(
+        self,
+    ) -> VMResult<(ChangeSet, Vec<Event>, NativeContextExtensions)> {
+        let Self {
+            data_cache,
+            native_extensions,
+            ..
+        } = self;
+        let (change_set, events) = data_cache
+            .into_effects()
+            .map_err(|e| e.finish(Location::Undefined))?;
+        Ok((change_set, events, native_extensions))
+    }
+
     pub fn get_type_layout(&self, type_tag: &TypeTag) -> VMResult<MoveTypeLayout> {
         self.runtime
             .loader()
@@ -1,4 +1,5 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod native_extensions_tests;
 pub mod vm_arguments_tests;
@@ -0,0 +1,33 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::native_extensions::NativeContextExtensions;
+
+#[derive(Debug, PartialEq)]
+struct Counter(u64);
+
+#[derive(Debug, PartialEq)]
+struct Log(Vec<String>);
+
+#[test]
+fn extensions_are_keyed_by_type() {
+    let mut extensions = NativeContextExtensions::default();
+    extensions.add(Counter(0));
+    extensions.add(Log(vec![]));
+    assert!(extensions.contains::<Counter>());
+
+    extensions.get_mut::<Counter>().0 += 1;
+    extensions.get_mut::<Log>().0.push("called".to_string());
+    assert_eq!(extensions.get::<Counter>(), &Counter(1));
+
+    assert_eq!(extensions.remove::<Log>(), Log(vec!["called".to_string()]));
+    assert!(!extensions.contains::<Log>());
+}
+
+#[test]
+#[should_panic]
+fn duplicate_extension() {
+    let mut extensions = NativeContextExtensions::default();
+    extensions.add(Counter(0));
+    extensions.add(Counter(1));
+}
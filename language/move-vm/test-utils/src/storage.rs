@@ -183,6 +183,22 @@ impl InMemoryStorage {
         self.apply(delta).unwrap();
     }
 
+    /// The addresses of all accounts holding a module or a resource.
+    pub fn addresses(&self) -> impl Iterator<Item = &AccountAddress> {
+        self.accounts.keys()
+    }
+
+    /// The resources held by the account at `addr`.
+    pub fn resources_at(
+        &self,
+        addr: &AccountAddress,
+    ) -> impl Iterator<Item = (&StructTag, &Vec<u8>)> {
+        self.accounts
+            .get(addr)
+            .into_iter()
+            .flat_map(|account_storage| account_storage.resources.iter())
+    }
+
     pub fn publish_or_overwrite_resource(
         &mut self,
         addr: AccountAddress,
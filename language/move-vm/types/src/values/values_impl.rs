@@ -1524,6 +1524,7 @@ impl IntegerValue {
 pub const INDEX_OUT_OF_BOUNDS: u64 = NFE_VECTOR_ERROR_BASE + 1;
 pub const POP_EMPTY_VEC: u64 = NFE_VECTOR_ERROR_BASE + 2;
 pub const VEC_UNPACK_PARITY_MISMATCH: u64 = NFE_VECTOR_ERROR_BASE + 3;
+pub const VEC_LENGTH_LIMIT_EXCEEDED: u64 = NFE_VECTOR_ERROR_BASE + 4;
 
 fn check_elem_layout(ty: &Type, v: &Container) -> PartialVMResult<()> {
     match (ty, v) {
@@ -127,6 +127,8 @@ pub fn move_cli(
     cost_table: &CostTable,
     error_descriptions: &ErrorMapping,
 ) -> Result<()> {
+    // The test processes of `package test --isolate` run this executable again
+    move_unit_test::run_isolated_test_process(Some(natives.clone()), None);
     let args = MoveCLI::from_args();
     run_cli(
         natives,
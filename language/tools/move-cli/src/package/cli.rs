@@ -16,7 +16,8 @@ use anyhow::{bail, Result};
 use move_command_line_common::files::{FileHash, MOVE_COVERAGE_MAP_EXTENSION};
 use move_compiler::{
     compiled_unit::{CompiledUnit, NamedCompiledModule},
-    diagnostics::{self, codes::Severity},
+    diagnostics,
+    shared::{NumberFormat, NumericalAddress},
     unit_test::{plan_builder::construct_test_plan, TestPlan},
     PASS_CFGIR,
 };
@@ -28,11 +29,17 @@ use move_coverage::{
 };
 use move_disassembler::disassembler::Disassembler;
 use move_package::{
-    compilation::{build_plan::BuildPlan, compiled_package::CompiledUnitWithSource},
+    compilation::{
+        build_plan::BuildPlan, compiled_package::CompiledUnitWithSource,
+        package_layout::CompiledPackageLayout,
+    },
     source_package::layout::SourcePackageLayout,
     ModelConfig,
 };
-use move_unit_test::UnitTestingConfig;
+use move_unit_test::{
+    test_reporter::{StatisticsFormat, TestReportFormat},
+    UnitTestingConfig, FAILED_TESTS_FILE_NAME,
+};
 use structopt::StructOpt;
 
 use crate::{package::prover::run_move_prover, NativeFunctionRecord};
@@ -123,37 +130,241 @@ pub enum PackageCommand {
             long = "instructions"
         )]
         instruction_execution_bound: u64,
-        /// A filter string to determine which unit tests to run. A unit test will be run only if it
-        /// contains this string in its fully qualified (<addr>::<module_name>::<fn_name>) name.
-        #[structopt(name = "filter", short = "f", long = "filter")]
-        filter: Option<String>,
-        /// List all tests
+        /// Meter the tests with a gas schedule instead of bounding them by a number of
+        /// instructions, and report the gas used by each test. Either `initial` for the built-in
+        /// schedule, or the path of a JSON file holding a cost table.
+        #[structopt(
+            name = "gas_schedule",
+            long = "gas-schedule",
+            conflicts_with = "stackless_instruction_tolerance"
+        )]
+        gas_schedule: Option<String>,
+        /// The number of gas units each test may use when metered with a gas schedule. Defaults
+        /// to the maximum number of gas units of a transaction in the schedule.
+        #[structopt(name = "gas_limit", long = "gas-limit", requires = "gas_schedule")]
+        gas_limit: Option<u64>,
+        /// The largest number of nested function calls in a test, beyond which it fails.
+        /// Defaults to the limit of the Move VM, 1024
+        #[structopt(long = "max-call-depth")]
+        max_call_depth: Option<usize>,
+        /// The largest depth of the type of a value a test moves to or from storage, beyond
+        /// which it fails. Defaults to the limit of the Move VM, 256
+        #[structopt(long = "max-value-depth")]
+        max_value_depth: Option<usize>,
+        /// The largest number of elements of a vector in a test, beyond which it fails. Vectors
+        /// are not limited by default
+        #[structopt(long = "max-vector-length")]
+        max_vector_length: Option<u64>,
+        /// A regex to determine which unit tests to run. A unit test will be run only if it
+        /// matches this regex in its fully qualified (<addr>::<module_name>::<fn_name>) name.
+        /// Can be repeated, in which case a unit test is run if it matches any of the filters.
+        #[structopt(
+            name = "filter",
+            short = "f",
+            long = "filter",
+            number_of_values = 1,
+            validator = move_unit_test::validate_filter
+        )]
+        filter: Vec<String>,
+        /// A regex for the fully qualified names of unit tests not to run, even when they match a
+        /// filter. Can be repeated.
+        #[structopt(
+            name = "exclude",
+            long = "exclude",
+            number_of_values = 1,
+            validator = move_unit_test::validate_filter
+        )]
+        exclude: Vec<String>,
+        /// Only run the unit tests with the given tag, as declared in `#[test(tags = [b"tag"])]`.
+        /// Can be repeated, in which case the unit tests with any of the tags are run.
+        #[structopt(name = "include_tag", long = "include-tag", number_of_values = 1)]
+        include_tags: Vec<String>,
+        /// Do not run the unit tests with the given tag. Can be repeated.
+        #[structopt(name = "exclude_tag", long = "exclude-tag", number_of_values = 1)]
+        exclude_tags: Vec<String>,
+        /// Only run the unit tests of the given module, written `<address>::<module_name>` with a
+        /// numerical or named address. Can be repeated.
+        #[structopt(name = "module", long = "module", number_of_values = 1)]
+        modules: Vec<String>,
+        /// Only run the unit tests of the modules published at the given numerical or named
+        /// address. Can be repeated.
+        #[structopt(name = "address", long = "address", number_of_values = 1)]
+        module_addresses: Vec<String>,
+        /// Also run the tests declared in the dependencies of the package, e.g. to exercise a
+        /// vendored framework along with the code using it
+        #[structopt(long = "include-dep-tests")]
+        include_dep_tests: bool,
+        /// List all tests. With `--format json`, each test is listed as a line of JSON with its
+        /// module, name, and the file and line it is declared at.
         #[structopt(name = "list", short = "l", long = "list")]
         list: bool,
-        /// Number of threads to use for running tests.
+        /// Number of threads to use for running tests, or `auto` for one for each available core
         #[structopt(
             name = "num_threads",
             default_value = "8",
             short = "t",
-            long = "threads"
+            long = "threads",
+            parse(try_from_str = move_unit_test::parse_num_threads)
         )]
         num_threads: usize,
+        /// Only report the tests which fail, rather than each test as it completes
+        #[structopt(short = "q", long = "quiet")]
+        quiet: bool,
+        /// Show the number of completed tests and the module being tested on a line which is
+        /// redrawn as the tests run, and only report the tests which fail
+        #[structopt(long = "progress", conflicts_with = "quiet")]
+        progress: bool,
         /// Report test statistics at the end of testing
         #[structopt(name = "report_statistics", short = "s", long = "statistics")]
         report_statistics: bool,
+        /// The format test statistics are reported in: `table`, or `csv` and `json` to be
+        /// processed by other tools
+        #[structopt(
+            long = "statistics-format",
+            default_value = "table",
+            possible_values = &["table", "csv", "json"]
+        )]
+        statistics_format: StatisticsFormat,
+        /// Together with `--statistics`, write the test statistics to this file rather than
+        /// report them with the results
+        #[structopt(long = "statistics-output", requires = "report_statistics")]
+        statistics_output: Option<String>,
+        /// Report the given number of tests which took the longest to run, by wall clock time,
+        /// at the end of testing
+        #[structopt(long = "report-slow-tests")]
+        report_slow_tests: Option<usize>,
+        /// Together with `--statistics`, report the given number of functions which executed the
+        /// most instructions over all tests, including the functions of dependencies. This needs
+        /// the execution of the tests to be traced by the Move VM
+        #[structopt(
+            name = "report_function_histogram",
+            long = "function-histogram",
+            requires = "report_statistics"
+        )]
+        report_function_histogram: Option<usize>,
         /// Show the storage state at the end of execution of a failing test
         #[structopt(name = "global_state_on_error", short = "g", long = "state_on_error")]
         report_storage_on_error: bool,
+        /// Together with `--state_on_error`, show all of the storage state rather than only the
+        /// changes the failing test made to it
+        #[structopt(name = "full_global_state_on_error", long = "full_state_on_error")]
+        report_full_storage_on_error: bool,
+        /// Write the storage state at the end of each failing test to a JSON file in this
+        /// directory, with the resources keyed by address and type
+        #[structopt(name = "state_output_dir", long = "state-output-dir")]
+        state_output_dir: Option<String>,
+        /// Write the outcome of each test, with its failure and the storage state at the point
+        /// of failure, to a `.exp` file in this directory, and fail the tests whose outcome
+        /// differs from the existing file
+        #[structopt(name = "snapshot_dir", long = "snapshot-dir")]
+        snapshot_dir: Option<String>,
+        /// Together with `--snapshot-dir`, overwrite the snapshots with the outcome of the tests
+        /// rather than compare against them. Also set by the UPDATE_BASELINE environment variable
+        #[structopt(name = "update_baseline", long = "update-baseline")]
+        update_baseline: bool,
+        /// After each test passes, run the functions declared with #[test_invariant] against the
+        /// storage state it left, and fail the test if any of them aborts
+        #[structopt(name = "check_invariants", long = "check-invariants")]
+        check_invariants: bool,
+        /// Print the output of `std::debug` as the tests run, rather than only for the tests
+        /// which fail
+        #[structopt(name = "nocapture", long = "nocapture")]
+        nocapture: bool,
+        /// Once all tests pass, run the Move Prover over the specs of the modules of the package,
+        /// and fail the run if any of them cannot be verified
+        #[structopt(name = "prove", long = "prove")]
+        prove: bool,
+        /// Start each test from the modules and resources of this genesis file, as JSON if it has
+        /// a `.json` extension and as BCS otherwise. The modules of the package replace those of
+        /// the genesis.
+        #[structopt(
+            name = "genesis",
+            long = "genesis",
+            conflicts_with = "check-stackless-vm"
+        )]
+        genesis: Option<String>,
+        /// Abandon each test which runs for longer than the given number of seconds, and report
+        /// it as timed out. Unlike the instruction bound, this also covers tests hanging in natives
+        #[structopt(name = "timeout", long = "timeout")]
+        timeout: Option<u64>,
+        /// Run each failing test again up to this number of times before reporting it as failed.
+        /// The tests which pass on a retry are reported as flaky
+        #[structopt(long = "retries", default_value = "0")]
+        retries: usize,
+        /// Only run the tests which failed in the previous run of the tests of the package. All
+        /// tests are run if there is no record of a previous run
+        #[structopt(long = "rerun-failed")]
+        rerun_failed: bool,
+        /// Run the tests in a random order, determined by the given seed or by a random one
+        #[structopt(long = "shuffle")]
+        shuffle: Option<Option<u64>>,
+        /// The seed the inputs of random tests are generated from, a random one if it is not given
+        #[structopt(long = "seed")]
+        seed: Option<u64>,
+        /// Run each test in a process of its own, so that a crash while running a test only fails
+        /// that test
+        #[structopt(long = "isolate", conflicts_with = "compute-coverage")]
+        isolate: bool,
+        /// Stop running tests after the first failure, and report the tests which were skipped
+        #[structopt(name = "fail_fast", long = "fail-fast")]
+        fail_fast: bool,
+        /// Only run the tests of the given shard, written `i/n` for the `i`-th out of `n` shards
+        /// numbered from 1
+        #[structopt(long = "shard", parse(try_from_str = move_unit_test::parse_shard))]
+        shard: Option<(usize, usize)>,
         /// Use the stackless bytecode interpreter to run the tests and cross check its results with
         /// the execution result from Move VM.
         #[structopt(long = "stackless")]
         check_stackless_vm: bool,
+        /// When cross checking with the stackless bytecode interpreter, report tests whose
+        /// instruction counts on the two VMs differ by more than this percentage of the count on
+        /// the Move VM, 100 by default. This is a coarse ratio of the total counts only, as the
+        /// two VMs count different instructions.
+        #[structopt(
+            long = "stackless-instruction-tolerance",
+            requires = "check-stackless-vm"
+        )]
+        stackless_instruction_tolerance: Option<Option<f64>>,
+        /// Run the tests on the stackless bytecode interpreter only, without running them on the
+        /// Move VM or cross checking the results of the two. Storage faults and events are not
+        /// simulated by the interpreter.
+        #[structopt(
+            long = "stackless-only",
+            conflicts_with_all = &["check-stackless-vm", "gas_schedule", "genesis", "check_invariants"]
+        )]
+        stackless_only: bool,
         /// Verbose mode
         #[structopt(long = "verbose")]
         verbose_mode: bool,
         /// Collect coverage information for later use with the various `package coverage` subcommands
         #[structopt(long = "coverage")]
         compute_coverage: bool,
+        /// Write metrics of the test run in OpenMetrics text format to this file
+        #[structopt(long = "open-metrics")]
+        open_metrics_output: Option<String>,
+        /// Configuration available to all tests through `UnitTest::config`, overriding the values
+        /// set with `#[test(env = b"KEY=VALUE")]`
+        #[structopt(long = "test-env", parse(try_from_str = move_unit_test::parse_test_env))]
+        test_env: Vec<(String, String)>,
+        /// Build the test plan and check that all tests can be run, reporting the tests that
+        /// would be run, after filtering, without executing them. Also available as `--no-run`
+        #[structopt(long = "dry-run", alias = "no-run")]
+        dry_run: bool,
+        /// Report the warnings found while compiling the tests and run them nonetheless, rather
+        /// than failing on the first warning
+        #[structopt(long = "allow-warnings")]
+        allow_warnings: bool,
+        /// Write the tracing spans of the test run as JSON lines to this file
+        #[structopt(long = "log-json")]
+        log_json: Option<String>,
+        /// The format to report test results in: `text`, `junit` for a JUnit XML report, or
+        /// `json` for a stream of JSON lines with an event for each test started and completed
+        #[structopt(
+            long = "format",
+            default_value = "text",
+            possible_values = &["text", "junit", "json"]
+        )]
+        format: TestReportFormat,
     },
     /// Disassemble the Move bytecode pointed to
     #[structopt(name = "disassemble")]
@@ -366,24 +577,109 @@ pub fn handle_package_commands(
         }
         PackageCommand::UnitTest {
             instruction_execution_bound,
+            gas_schedule,
+            gas_limit,
+            max_call_depth,
+            max_value_depth,
+            max_vector_length,
             filter,
+            exclude,
+            include_tags,
+            exclude_tags,
+            modules,
+            module_addresses,
+            include_dep_tests,
             list,
             num_threads,
+            quiet,
+            progress,
             report_statistics,
+            statistics_format,
+            statistics_output,
+            report_slow_tests,
+            report_function_histogram,
             report_storage_on_error,
+            report_full_storage_on_error,
+            state_output_dir,
+            snapshot_dir,
+            update_baseline,
+            check_invariants,
+            nocapture,
+            prove,
+            genesis,
+            timeout,
+            retries,
+            rerun_failed,
+            isolate,
+            shuffle,
+            seed,
+            fail_fast,
+            shard,
             check_stackless_vm,
+            stackless_instruction_tolerance,
+            stackless_only,
             verbose_mode,
             compute_coverage,
+            open_metrics_output,
+            test_env,
+            dry_run,
+            allow_warnings,
+            log_json,
+            format,
         } => {
             let unit_test_config = UnitTestingConfig {
                 instruction_execution_bound: *instruction_execution_bound,
+                gas_schedule: gas_schedule.clone(),
+                gas_limit: *gas_limit,
+                max_call_depth: *max_call_depth,
+                max_value_depth: *max_value_depth,
+                max_vector_length: *max_vector_length,
                 filter: filter.clone(),
+                exclude: exclude.clone(),
+                include_tags: include_tags.clone(),
+                exclude_tags: exclude_tags.clone(),
+                modules: modules.clone(),
+                module_addresses: module_addresses.clone(),
+                include_dep_tests: *include_dep_tests,
                 list: *list,
                 num_threads: *num_threads,
+                quiet: *quiet,
+                progress: *progress,
                 report_statistics: *report_statistics,
+                statistics_format: *statistics_format,
+                statistics_output: statistics_output.clone(),
+                report_slow_tests: *report_slow_tests,
+                report_function_histogram: *report_function_histogram,
                 report_storage_on_error: *report_storage_on_error,
+                report_full_storage_on_error: *report_full_storage_on_error,
+                state_output_dir: state_output_dir.clone(),
+                snapshot_dir: snapshot_dir.clone(),
+                update_baseline: *update_baseline,
+                check_invariants: *check_invariants,
+                nocapture: *nocapture,
+                prove: *prove,
+                genesis: genesis.clone(),
+                timeout: *timeout,
+                retries: *retries,
+                rerun_failed: *rerun_failed,
+                isolate: *isolate,
+                shuffle: *shuffle,
+                seed: *seed,
+                fail_fast: *fail_fast,
+                shard: *shard,
                 check_stackless_vm: *check_stackless_vm,
+                stackless_instruction_tolerance: *stackless_instruction_tolerance,
+                stackless_only: *stackless_only,
                 verbose: *verbose_mode,
+                open_metrics_output: open_metrics_output.clone(),
+                // The coverage of a package is collected with `--coverage` by `run_move_unit_tests`
+                coverage: None,
+                lcov: false,
+                test_env: test_env.clone(),
+                dry_run: *dry_run,
+                allow_warnings: *allow_warnings,
+                log_json: log_json.clone(),
+                format: *format,
                 ..UnitTestingConfig::default_with_bound(None)
             };
             let result = run_move_unit_tests(
@@ -411,15 +707,52 @@ pub fn handle_package_commands(
 pub fn run_move_unit_tests(
     pkg_path: &Path,
     mut build_config: move_package::BuildConfig,
-    unit_test_config: UnitTestingConfig,
+    mut unit_test_config: UnitTestingConfig,
     natives: Vec<NativeFunctionRecord>,
     compute_coverage: bool,
 ) -> Result<UnitTestResult> {
+    // The failed tests are recorded with the build output of the package, and cleaned with it
+    if unit_test_config.failed_tests_file.is_none() {
+        let failed_tests_path = pkg_path
+            .join(CompiledPackageLayout::Root.path())
+            .join(FAILED_TESTS_FILE_NAME);
+        unit_test_config.failed_tests_file = Some(failed_tests_path.to_string_lossy().to_string());
+    }
     let mut test_plan = None;
     build_config.test_mode = true;
     build_config.dev_mode = true;
 
     let resolution_graph = build_config.resolution_graph_for_package(pkg_path)?;
+    // Named addresses in the selected modules are those of the root package
+    if unit_test_config.named_address_values.is_empty() {
+        let root_package =
+            resolution_graph.get_package(&resolution_graph.root_package.package.name);
+        unit_test_config.named_address_values = root_package
+            .resolution_table
+            .iter()
+            .map(|(name, address)| {
+                (
+                    name.to_string(),
+                    NumericalAddress::new(address.into_bytes(), NumberFormat::Hex),
+                )
+            })
+            .collect();
+    }
+    // The prover verifies the modules of the root package, against those of its dependencies
+    if unit_test_config.prove && unit_test_config.source_files.is_empty() {
+        for (name, rpkg) in &resolution_graph.package_table {
+            let sources = rpkg
+                .get_sources(&resolution_graph.build_options)?
+                .iter()
+                .map(|fname| fname.to_string())
+                .collect::<Vec<_>>();
+            if *name == resolution_graph.root_package.package.name {
+                unit_test_config.source_files.extend(sources);
+            } else {
+                unit_test_config.dep_files.extend(sources);
+            }
+        }
+    }
     let dep_file_map: HashMap<_, _> = resolution_graph
         .package_table
         .iter()
@@ -436,34 +769,43 @@ pub fn run_move_unit_tests(
         })
         .collect();
     let build_plan = BuildPlan::create(resolution_graph)?;
+    // The tests of the dependencies, if they are run along with those of the root package
+    let mut dep_tests = vec![];
     let pkg = build_plan.compile_with_driver(&mut std::io::stdout(), |compiler, is_root| {
-        if !is_root {
-            compiler.build_and_report()
-        } else {
-            let (files, comments_and_compiler_res) = compiler.run::<PASS_CFGIR>().unwrap();
-            let (_, compiler) =
-                diagnostics::unwrap_or_report_diagnostics(&files, comments_and_compiler_res);
-            let (mut compiler, cfgir) = compiler.into_ast();
-            let compilation_env = compiler.compilation_env();
-            let built_test_plan = construct_test_plan(compilation_env, &cfgir);
+        if !is_root && !unit_test_config.include_dep_tests {
+            return compiler.build_and_report();
+        }
+        let (files, comments_and_compiler_res) = compiler.run::<PASS_CFGIR>().unwrap();
+        let (_, compiler) =
+            diagnostics::unwrap_or_report_diagnostics(&files, comments_and_compiler_res);
+        let (mut compiler, cfgir) = compiler.into_ast();
+        let compilation_env = compiler.compilation_env();
+        let built_test_plan = construct_test_plan(compilation_env, &cfgir);
 
-            if let Err(diags) = compilation_env.check_diags_at_or_above_severity(Severity::Warning)
-            {
-                diagnostics::report_diagnostics(&files, diags);
-            }
+        if let Err(diags) = compilation_env
+            .check_diags_at_or_above_severity(unit_test_config.diagnostic_threshold())
+        {
+            diagnostics::report_diagnostics(&files, diags);
+        }
 
-            let compilation_result = compiler.at_cfgir(cfgir).build();
+        let compilation_result = compiler.at_cfgir(cfgir).build();
 
-            let (units, _) = diagnostics::unwrap_or_report_diagnostics(&files, compilation_result);
+        let (units, warnings) =
+            diagnostics::unwrap_or_report_diagnostics(&files, compilation_result);
+        diagnostics::report_warnings(&files, warnings);
 
+        if is_root {
             test_plan = Some((built_test_plan, files.clone(), units.clone()));
-            Ok((files, units))
+        } else {
+            dep_tests.extend(built_test_plan.into_iter().flatten());
         }
+        Ok((files, units))
     })?;
 
     let (test_plan, mut files, units) = test_plan.unwrap();
     files.extend(dep_file_map);
-    let test_plan = test_plan.unwrap();
+    let mut test_plan = test_plan.unwrap();
+    test_plan.extend(dep_tests);
     let no_tests = test_plan.is_empty();
     let mut test_plan = TestPlan::new(test_plan, files, units);
     for pkg in pkg.0.transitive_dependencies() {
@@ -496,7 +838,7 @@ pub fn run_move_unit_tests(
     }
 
     if !unit_test_config
-        .run_and_report_unit_tests(test_plan, Some(natives), std::io::stdout())
+        .run_and_report_unit_tests(test_plan, Some(natives), None, std::io::stdout())
         .unwrap()
         .1
     {
@@ -504,7 +846,8 @@ pub fn run_move_unit_tests(
         return Ok(UnitTestResult::Failure);
     }
 
-    if compute_coverage && !no_tests {
+    // A dry run does not execute any tests, so there is no trace to compute coverage from
+    if compute_coverage && !no_tests && !unit_test_config.dry_run {
         let coverage_map = CoverageMap::from_trace_file(trace_path);
         output_map_to_file(&coverage_map_path, &coverage_map).unwrap();
     }
@@ -0,0 +1,151 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use crate::coverage_map::ExecCoverageMap;
+use anyhow::{format_err, Result};
+use codespan::{ByteIndex, Files};
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{CodeOffset, FunctionDefinitionIndex},
+    CompiledModule,
+};
+use move_bytecode_source_map::source_map::SourceMap;
+use std::{collections::BTreeMap, io::Write};
+
+/// A module to report the coverage of, with its source map and the path and contents of the
+/// source file it was compiled from.
+pub struct LcovModule<'a> {
+    pub module: &'a CompiledModule,
+    pub source_map: &'a SourceMap,
+    pub file_path: &'a str,
+    pub file_contents: &'a str,
+    /// Functions generated by the compiler, which are left out of the report
+    pub skipped_functions: &'a [&'a str],
+}
+
+/// The coverage of the functions and lines of a source file
+#[derive(Default)]
+struct FileCoverage {
+    // The line of each function and the number of times it was called
+    functions: BTreeMap<String, (u32, u64)>,
+    // The number of times the most executed instruction of each line was executed
+    lines: BTreeMap<u32, u64>,
+}
+
+/// Write the coverage of `modules` in the LCOV tracefile format, as a record for each of their
+/// source files. Lines are numbered from 1, and a line is covered if any of its instructions was
+/// executed.
+pub fn output_lcov<W: Write>(
+    modules: &[LcovModule],
+    coverage_map: &ExecCoverageMap,
+    writer: &mut W,
+) -> Result<()> {
+    let mut files: BTreeMap<&str, FileCoverage> = BTreeMap::new();
+    for lcov_module in modules {
+        let file_coverage = files.entry(lcov_module.file_path).or_default();
+        add_module_coverage(lcov_module, coverage_map, file_coverage)?;
+    }
+
+    for (file_path, file_coverage) in files {
+        writeln!(writer, "TN:")?;
+        writeln!(writer, "SF:{}", file_path)?;
+        for (fn_name, (line, _)) in &file_coverage.functions {
+            writeln!(writer, "FN:{},{}", line, fn_name)?;
+        }
+        for (fn_name, (_, count)) in &file_coverage.functions {
+            writeln!(writer, "FNDA:{},{}", count, fn_name)?;
+        }
+        writeln!(writer, "FNF:{}", file_coverage.functions.len())?;
+        writeln!(
+            writer,
+            "FNH:{}",
+            file_coverage
+                .functions
+                .values()
+                .filter(|(_, count)| *count > 0)
+                .count()
+        )?;
+        for (line, count) in &file_coverage.lines {
+            writeln!(writer, "DA:{},{}", line, count)?;
+        }
+        writeln!(writer, "LF:{}", file_coverage.lines.len())?;
+        writeln!(
+            writer,
+            "LH:{}",
+            file_coverage
+                .lines
+                .values()
+                .filter(|count| **count > 0)
+                .count()
+        )?;
+        writeln!(writer, "end_of_record")?;
+    }
+    Ok(())
+}
+
+fn add_module_coverage(
+    lcov_module: &LcovModule,
+    coverage_map: &ExecCoverageMap,
+    file_coverage: &mut FileCoverage,
+) -> Result<()> {
+    let LcovModule {
+        module,
+        source_map,
+        file_path,
+        file_contents,
+        skipped_functions,
+    } = lcov_module;
+    let mut files = Files::new();
+    let file_id = files.add(*file_path, *file_contents);
+    let line_of = |offset: u32| -> Result<u32> {
+        files
+            .location(file_id, ByteIndex(offset))
+            .map(|location| location.line.0 + 1)
+            .map_err(|_| format_err!("Source map out of sync with {}", file_path))
+    };
+
+    let module_id = module.self_id();
+    let module_map = coverage_map
+        .module_maps
+        .get(&(*module_id.address(), module_id.name().to_owned()));
+    for (function_def_idx, function_def) in module.function_defs().iter().enumerate() {
+        let code_unit = match &function_def.code {
+            Some(code_unit) => code_unit,
+            // Natives have no code to cover
+            None => continue,
+        };
+        let fn_name = module.identifier_at(module.function_handle_at(function_def.function).name);
+        if skipped_functions.contains(&fn_name.as_str()) {
+            continue;
+        }
+        let function_coverage =
+            module_map.and_then(|module_map| module_map.function_maps.get(fn_name));
+        let count_at = |code_offset: u64| {
+            function_coverage
+                .and_then(|function_coverage| function_coverage.get(&code_offset))
+                .copied()
+                .unwrap_or(0)
+        };
+
+        let function_def_idx = FunctionDefinitionIndex(function_def_idx as u16);
+        let function_map = source_map.get_function_source_map(function_def_idx)?;
+        file_coverage.functions.insert(
+            format!("{}::{}", module_id.name(), fn_name),
+            (
+                line_of(function_map.definition_location.start())?,
+                count_at(0),
+            ),
+        );
+        for code_offset in 0..code_unit.code.len() {
+            let loc = source_map.get_code_location(function_def_idx, code_offset as CodeOffset)?;
+            let count = file_coverage
+                .lines
+                .entry(line_of(loc.start())?)
+                .or_insert(0);
+            *count = (*count).max(count_at(code_offset as u64));
+        }
+    }
+    Ok(())
+}
@@ -94,11 +94,12 @@ impl BuildPlan {
     }
 
     // Clean out old packages that are no longer used, or no longer used under the current
-    // compilation flags
+    // compilation flags. Files next to the packages, like the failed tests recorded by the unit
+    // test runner, are kept.
     fn clean(build_root: &Path, keep_paths: BTreeSet<&PackageName>) -> Result<()> {
         for dir in std::fs::read_dir(build_root)? {
             let path = dir?.path();
-            if !keep_paths.iter().any(|name| path.ends_with(name.as_str())) {
+            if path.is_dir() && !keep_paths.iter().any(|name| path.ends_with(name.as_str())) {
                 std::fs::remove_dir_all(&path)?;
             }
         }
@@ -0,0 +1,412 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Caches the test plan of a run on disk with `--cached`, so that later runs with the same
+//! sources, dependencies, named addresses and compilation flags reuse it rather than compiling
+//! them again.
+//!
+//! The plan is written as JSON to the cache directory, in a file named after a hash of the
+//! contents of the source and dependency files, of the named addresses, and of the flags. A plan
+//! with arguments which cannot be written, such as structs, is not cached.
+//!
+//! The same format hands the test plan of an isolated run to its test processes.
+
+use anyhow::{anyhow, Result};
+use move_binary_format::CompiledModule;
+use move_bytecode_source_map::source_map::SourceMap;
+use move_command_line_common::files::{
+    extension_equals, find_filenames, FileHash, MOVE_COMPILED_EXTENSION, MOVE_EXTENSION,
+};
+use move_compiler::{
+    compiled_unit::NamedCompiledModule,
+    shared::{Flags, NumericalAddress},
+    unit_test::{
+        AbortLocation, ExpectedFailure, ModuleTestPlan, RandomArgument, RandomType, StorageFaults,
+        TestCase, TestPlan,
+    },
+};
+use move_core_types::{
+    account_address::AccountAddress, identifier::Identifier, language_storage::ModuleId,
+    value::MoveValue,
+};
+use move_symbol_pool::Symbol;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeSet},
+    fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+#[derive(Serialize, Deserialize)]
+struct CachedTestPlan {
+    // the name and contents of each source file
+    files: Vec<(String, String)>,
+    modules: Vec<CachedModule>,
+    // the tests and the invariants of each module
+    module_tests: Vec<(ModuleId, Vec<(String, CachedTestCase)>, BTreeSet<String>)>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedModule {
+    address: String,
+    name: String,
+    bytecode: Vec<u8>,
+    source_map: SourceMap,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedTestCase {
+    test_name: String,
+    arguments: Vec<CachedValue>,
+    expected_failure: Option<CachedExpectedFailure>,
+    env: Vec<(Vec<u8>, Vec<u8>)>,
+    tags: BTreeSet<String>,
+    fixture: Option<String>,
+    failing_resources: BTreeSet<(ModuleId, Identifier)>,
+    missing_modules: BTreeSet<ModuleId>,
+    random_arguments: Option<Vec<(String, CachedRandomArgument)>>,
+    expected_events: Option<Vec<(ModuleId, Identifier)>>,
+    data_file: Option<String>,
+    depends_on: BTreeSet<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+enum CachedExpectedFailure {
+    Expected,
+    ExpectedWithCode(u64),
+    ExpectedWithCodeAndLocation(u64, ModuleId, Option<Identifier>),
+}
+
+#[derive(Serialize, Deserialize)]
+enum CachedRandomArgument {
+    Given(CachedValue),
+    Random(CachedRandomType),
+}
+
+#[derive(Serialize, Deserialize)]
+enum CachedRandomType {
+    U8,
+    U64,
+    U128,
+    Address,
+    VectorU8,
+}
+
+#[derive(Serialize, Deserialize)]
+enum CachedValue {
+    U8(u8),
+    U64(u64),
+    // JSON numbers do not hold all u128 values
+    U128(String),
+    Bool(bool),
+    Address(AccountAddress),
+    Vector(Vec<CachedValue>),
+    Signer(AccountAddress),
+}
+
+/// The file in `cache_dir` the test plan of `source_files` and `dep_files` compiled with
+/// `named_addresses` and `flags` is cached in. Whether warnings are allowed and whether the tests
+/// of the dependencies are included also change the plan.
+pub fn test_plan_cache_path(
+    cache_dir: &Path,
+    source_files: &[String],
+    dep_files: &[String],
+    named_addresses: &[(String, NumericalAddress)],
+    flags: &Flags,
+    allow_warnings: bool,
+    include_dep_tests: bool,
+) -> Result<PathBuf> {
+    let mut hasher = DefaultHasher::new();
+    env!("CARGO_PKG_VERSION").hash(&mut hasher);
+    for paths in &[source_files, dep_files] {
+        let files = find_filenames(*paths, |path| {
+            extension_equals(path, MOVE_EXTENSION)
+                || extension_equals(path, MOVE_COMPILED_EXTENSION)
+        })?;
+        files.len().hash(&mut hasher);
+        for file in files {
+            file.hash(&mut hasher);
+            fs::read(&file)?.hash(&mut hasher);
+        }
+    }
+    named_addresses.hash(&mut hasher);
+    // The flags don't implement `Hash`, but their debug output lists all of them
+    format!("{:?}", flags).hash(&mut hasher);
+    allow_warnings.hash(&mut hasher);
+    include_dep_tests.hash(&mut hasher);
+    Ok(cache_dir.join(format!("test-plan-{:016x}.json", hasher.finish())))
+}
+
+/// The test plan cached at `path`, if any
+pub fn read_cached_test_plan(path: &Path) -> Option<TestPlan> {
+    read_test_plan(path).ok()
+}
+
+/// The test plan written at `path`
+pub fn read_test_plan(path: &Path) -> Result<TestPlan> {
+    let contents = fs::read_to_string(path)
+        .map_err(|err| anyhow!("Unable to read test plan {}: {}", path.display(), err))?;
+    let cached = serde_json::from_str(&contents)
+        .map_err(|err| anyhow!("Invalid test plan {}: {}", path.display(), err))?;
+    from_cached(cached)
+}
+
+/// Write `test_plan` at `path`, failing if it has arguments which cannot be written
+pub fn write_test_plan(path: &Path, test_plan: &TestPlan) -> Result<()> {
+    let cached = to_cached(test_plan)
+        .ok_or_else(|| anyhow!("The test plan has arguments which cannot be written"))?;
+    fs::write(path, serde_json::to_string(&cached)?)?;
+    Ok(())
+}
+
+/// Cache `test_plan` at `path`, unless it has arguments which cannot be cached
+pub fn write_cached_test_plan(path: &Path, test_plan: &TestPlan) -> Result<()> {
+    let cached = match to_cached(test_plan) {
+        Some(cached) => cached,
+        None => return Ok(()),
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string(&cached)?)?;
+    Ok(())
+}
+
+fn to_cached(test_plan: &TestPlan) -> Option<CachedTestPlan> {
+    let files = test_plan
+        .files
+        .values()
+        .map(|(name, contents)| (name.to_string(), contents.clone()))
+        .collect();
+    let modules = test_plan
+        .module_info
+        .values()
+        .map(|unit| {
+            let mut bytecode = vec![];
+            unit.module.serialize(&mut bytecode).ok()?;
+            Some(CachedModule {
+                address: unit.address.to_string(),
+                name: unit.name.to_string(),
+                bytecode,
+                source_map: unit.source_map.clone(),
+            })
+        })
+        .collect::<Option<_>>()?;
+    let module_tests = test_plan
+        .module_tests
+        .iter()
+        .map(|(module_id, module_test_plan)| {
+            let tests = module_test_plan
+                .tests
+                .iter()
+                .map(|(name, test_case)| Some((name.clone(), to_cached_test_case(test_case)?)))
+                .collect::<Option<_>>()?;
+            Some((
+                module_id.clone(),
+                tests,
+                module_test_plan.invariants.clone(),
+            ))
+        })
+        .collect::<Option<_>>()?;
+    Some(CachedTestPlan {
+        files,
+        modules,
+        module_tests,
+    })
+}
+
+fn to_cached_test_case(test_case: &TestCase) -> Option<CachedTestCase> {
+    let random_arguments = match &test_case.random_arguments {
+        None => None,
+        Some(random_arguments) => Some(
+            random_arguments
+                .iter()
+                .map(|(name, argument)| {
+                    let argument = match argument {
+                        RandomArgument::Given(value) => {
+                            CachedRandomArgument::Given(to_cached_value(value)?)
+                        }
+                        RandomArgument::Random(ty) => CachedRandomArgument::Random(match ty {
+                            RandomType::U8 => CachedRandomType::U8,
+                            RandomType::U64 => CachedRandomType::U64,
+                            RandomType::U128 => CachedRandomType::U128,
+                            RandomType::Address => CachedRandomType::Address,
+                            RandomType::VectorU8 => CachedRandomType::VectorU8,
+                        }),
+                    };
+                    Some((name.clone(), argument))
+                })
+                .collect::<Option<_>>()?,
+        ),
+    };
+    Some(CachedTestCase {
+        test_name: test_case.test_name.clone(),
+        arguments: test_case
+            .arguments
+            .iter()
+            .map(to_cached_value)
+            .collect::<Option<_>>()?,
+        expected_failure: test_case.expected_failure.as_ref().map(|expected_failure| {
+            match expected_failure {
+                ExpectedFailure::Expected => CachedExpectedFailure::Expected,
+                ExpectedFailure::ExpectedWithCode(code) => {
+                    CachedExpectedFailure::ExpectedWithCode(*code)
+                }
+                ExpectedFailure::ExpectedWithCodeAndLocation(code, location) => {
+                    CachedExpectedFailure::ExpectedWithCodeAndLocation(
+                        *code,
+                        location.module_id.clone(),
+                        location.function.clone(),
+                    )
+                }
+            }
+        }),
+        env: test_case
+            .env
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect(),
+        tags: test_case.tags.clone(),
+        fixture: test_case.fixture.clone(),
+        failing_resources: test_case.storage_faults.failing_resources.clone(),
+        missing_modules: test_case.storage_faults.missing_modules.clone(),
+        random_arguments,
+        expected_events: test_case.expected_events.clone(),
+        data_file: test_case.data_file.clone(),
+        depends_on: test_case.depends_on.clone(),
+    })
+}
+
+fn to_cached_value(value: &MoveValue) -> Option<CachedValue> {
+    Some(match value {
+        MoveValue::U8(n) => CachedValue::U8(*n),
+        MoveValue::U64(n) => CachedValue::U64(*n),
+        MoveValue::U128(n) => CachedValue::U128(n.to_string()),
+        MoveValue::Bool(b) => CachedValue::Bool(*b),
+        MoveValue::Address(address) => CachedValue::Address(*address),
+        MoveValue::Vector(values) => {
+            CachedValue::Vector(values.iter().map(to_cached_value).collect::<Option<_>>()?)
+        }
+        MoveValue::Signer(address) => CachedValue::Signer(*address),
+        MoveValue::Struct(_) => return None,
+    })
+}
+
+fn from_cached(cached: CachedTestPlan) -> Result<TestPlan> {
+    let files = cached
+        .files
+        .into_iter()
+        .map(|(name, contents)| (FileHash::new(&contents), (Symbol::from(name), contents)))
+        .collect();
+    let module_info = cached
+        .modules
+        .into_iter()
+        .map(|cached_module| {
+            let module = CompiledModule::deserialize(&cached_module.bytecode)
+                .map_err(|err| anyhow!("Invalid cached module: {}", err))?;
+            let unit = NamedCompiledModule {
+                address: NumericalAddress::parse_str(&cached_module.address)
+                    .map_err(|err| anyhow!("Invalid cached address: {}", err))?,
+                name: Symbol::from(cached_module.name),
+                module,
+                source_map: cached_module.source_map,
+            };
+            Ok((unit.module.self_id(), unit))
+        })
+        .collect::<Result<_>>()?;
+    let module_tests = cached
+        .module_tests
+        .into_iter()
+        .map(|(module_id, tests, invariants)| {
+            let tests = tests
+                .into_iter()
+                .map(|(name, test_case)| (name, from_cached_test_case(test_case)))
+                .collect();
+            let module_test_plan = ModuleTestPlan {
+                module_id: module_id.clone(),
+                tests,
+                invariants,
+            };
+            (module_id, module_test_plan)
+        })
+        .collect();
+    Ok(TestPlan {
+        files,
+        module_tests,
+        module_info,
+    })
+}
+
+fn from_cached_test_case(cached: CachedTestCase) -> TestCase {
+    TestCase {
+        test_name: cached.test_name,
+        arguments: cached
+            .arguments
+            .into_iter()
+            .map(from_cached_value)
+            .collect(),
+        expected_failure: cached
+            .expected_failure
+            .map(|expected_failure| match expected_failure {
+                CachedExpectedFailure::Expected => ExpectedFailure::Expected,
+                CachedExpectedFailure::ExpectedWithCode(code) => {
+                    ExpectedFailure::ExpectedWithCode(code)
+                }
+                CachedExpectedFailure::ExpectedWithCodeAndLocation(code, module_id, function) => {
+                    ExpectedFailure::ExpectedWithCodeAndLocation(
+                        code,
+                        AbortLocation {
+                            module_id,
+                            function,
+                        },
+                    )
+                }
+            }),
+        env: cached.env.into_iter().collect(),
+        tags: cached.tags,
+        fixture: cached.fixture,
+        storage_faults: StorageFaults {
+            failing_resources: cached.failing_resources,
+            missing_modules: cached.missing_modules,
+        },
+        random_arguments: cached.random_arguments.map(|random_arguments| {
+            random_arguments
+                .into_iter()
+                .map(|(name, argument)| {
+                    let argument = match argument {
+                        CachedRandomArgument::Given(value) => {
+                            RandomArgument::Given(from_cached_value(value))
+                        }
+                        CachedRandomArgument::Random(ty) => RandomArgument::Random(match ty {
+                            CachedRandomType::U8 => RandomType::U8,
+                            CachedRandomType::U64 => RandomType::U64,
+                            CachedRandomType::U128 => RandomType::U128,
+                            CachedRandomType::Address => RandomType::Address,
+                            CachedRandomType::VectorU8 => RandomType::VectorU8,
+                        }),
+                    };
+                    (name, argument)
+                })
+                .collect()
+        }),
+        expected_events: cached.expected_events,
+        data_file: cached.data_file,
+        depends_on: cached.depends_on,
+    }
+}
+
+fn from_cached_value(cached: CachedValue) -> MoveValue {
+    match cached {
+        CachedValue::U8(n) => MoveValue::U8(n),
+        CachedValue::U64(n) => MoveValue::U64(n),
+        // Written from a u128
+        CachedValue::U128(n) => MoveValue::U128(n.parse().unwrap()),
+        CachedValue::Bool(b) => MoveValue::Bool(b),
+        CachedValue::Address(address) => MoveValue::Address(address),
+        CachedValue::Vector(values) => {
+            MoveValue::Vector(values.into_iter().map(from_cached_value).collect())
+        }
+        CachedValue::Signer(address) => MoveValue::Signer(address),
+    }
+}
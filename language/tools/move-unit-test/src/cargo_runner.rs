@@ -3,9 +3,17 @@
 
 use move_command_line_common::files::find_filenames;
 use move_vm_runtime::native_functions::NativeFunctionTable;
+use std::{
+    collections::BTreeMap,
+    fs, thread,
+    time::{Duration, SystemTime},
+};
 
 use crate::UnitTestingConfig;
 
+/// How often the files are checked for changes in watch mode
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
 pub fn run_tests_with_config_and_filter(
     mut config: UnitTestingConfig,
     root_path: &str,
@@ -13,6 +21,8 @@ pub fn run_tests_with_config_and_filter(
     dep_root: Option<&str>,
     native_function_table: Option<NativeFunctionTable>,
 ) {
+    // The test processes of an isolated run run this executable again
+    crate::run_isolated_test_process(native_function_table.clone(), None);
     let get_files = |root_path, pat| {
         let source_re = regex::Regex::new(pat)
             .unwrap_or_else(|_| panic!("Invalid regular expression: '{}'", pat));
@@ -22,17 +32,27 @@ pub fn run_tests_with_config_and_filter(
         .unwrap()
     };
 
-    let sources = get_files(root_path, source_pattern);
-    let deps = dep_root
-        .map(|root| get_files(root, r".*\.move$"))
-        .unwrap_or_else(Vec::new);
+    let collect_files = || {
+        let sources = get_files(root_path, source_pattern);
+        let deps = dep_root
+            .map(|root| get_files(root, r".*\.move$"))
+            .unwrap_or_else(Vec::new);
+        (sources, deps)
+    };
+    config
+        .load_address_file()
+        .expect("Unable to read the address file");
+    if config.watch {
+        watch_and_run_tests(config, collect_files, native_function_table)
+    }
 
+    let (sources, deps) = collect_files();
     config.source_files = sources;
     config.dep_files = deps;
     let test_plan = config.build_test_plan().expect("Unable to build test plan");
 
     let (_, all_tests_passed) = config
-        .run_and_report_unit_tests(test_plan, native_function_table, std::io::stdout())
+        .run_and_report_unit_tests(test_plan, native_function_table, None, std::io::stdout())
         .expect("Failed to execute tests");
 
     // If all tests passed, exit with 0 otherwise with a non-zero exit code.
@@ -43,6 +63,55 @@ pub fn run_tests_with_config_and_filter(
     }
 }
 
+/// Run the tests of the files returned by `collect_files`, which returns the source files and the
+/// dependency files, and run them again whenever one of those files is added, removed, or
+/// modified. This never returns.
+pub fn watch_and_run_tests(
+    mut config: UnitTestingConfig,
+    collect_files: impl Fn() -> (Vec<String>, Vec<String>),
+    native_function_table: Option<NativeFunctionTable>,
+) -> ! {
+    let mut last_modified = None;
+    loop {
+        let (sources, deps) = collect_files();
+        let modified = modification_times(sources.iter().chain(deps.iter()));
+        if last_modified.as_ref() != Some(&modified) {
+            last_modified = Some(modified);
+            config.source_files = sources;
+            config.dep_files = deps;
+            match config.try_build_test_plan() {
+                Ok(Some(test_plan)) => {
+                    if let Err(err) = config.run_and_report_unit_tests(
+                        test_plan,
+                        native_function_table.clone(),
+                        None,
+                        std::io::stdout(),
+                    ) {
+                        eprintln!("Failed to execute tests: {}", err);
+                    }
+                }
+                Ok(None) => (),
+                // The sources are compiled again once they are fixed
+                Err((files, diags)) => crate::report_compiler_diagnostics(&files, diags),
+            }
+            println!("\nWatching for changes...");
+        }
+        thread::sleep(WATCH_INTERVAL);
+    }
+}
+
+/// The last modification time of each of `files`, if it can be read
+fn modification_times<'a>(
+    files: impl Iterator<Item = &'a String>,
+) -> BTreeMap<String, Option<SystemTime>> {
+    files
+        .map(|file| {
+            let modified = fs::metadata(file).and_then(|metadata| metadata.modified());
+            (file.clone(), modified.ok())
+        })
+        .collect()
+}
+
 #[macro_export]
 macro_rules! register_move_unit_tests {
     ($config:expr, $root:expr, $pattern:expr) => {
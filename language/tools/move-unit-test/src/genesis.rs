@@ -0,0 +1,161 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Loads the genesis state the tests start from, given with `--genesis <path>`: modules and
+//! resources to publish in the storage before each test, e.g. to test upgrade or migration logic
+//! against an existing state.
+//!
+//! A `.json` file holds an object of the form
+//! `{"modules": ["0x<module>", ...], "resources": [{"address": "0x1", "type": "0x1::M::R",
+//! "value": "0x<resource>"}, ...]}`, where modules and resource values are hex encoded BCS.
+//!
+//! Any other file holds the same in BCS, as the encoding of
+//! `(Vec<Vec<u8>>, Vec<(AccountAddress, String, Vec<u8>)>)`: the modules, then the address, the
+//! type and the value of each resource.
+
+use crate::test_data::decode_hex;
+use anyhow::{anyhow, bail, Result};
+use move_binary_format::CompiledModule;
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::{ModuleId, StructTag},
+    parser::parse_struct_tag,
+    value::{MoveStructLayout, MoveTypeLayout, MoveValue},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{fs, path::Path};
+
+/// The modules and resources of a genesis state
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Genesis {
+    pub modules: Vec<(ModuleId, Vec<u8>)>,
+    pub resources: Vec<(AccountAddress, StructTag, Vec<u8>)>,
+}
+
+/// Load the genesis state at `path`, as JSON if it has a `.json` extension and as BCS otherwise.
+pub fn load_genesis(path: &str) -> Result<Genesis> {
+    let (modules, resources) = if Path::new(path)
+        .extension()
+        .map_or(false, |ext| ext == "json")
+    {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| anyhow!("Unable to read genesis {}: {}", path, err))?;
+        json_genesis(&contents)
+    } else {
+        let bytes =
+            fs::read(path).map_err(|err| anyhow!("Unable to read genesis {}: {}", path, err))?;
+        bcs_genesis(&bytes)
+    }
+    .map_err(|err| anyhow!("Invalid genesis {}: {}", path, err))?;
+
+    let modules = modules
+        .into_iter()
+        .map(|blob| {
+            let module = CompiledModule::deserialize(&blob)
+                .map_err(|err| anyhow!("Invalid module in genesis {}: {}", path, err))?;
+            Ok((module.self_id(), blob))
+        })
+        .collect::<Result<_>>()?;
+    let resources = resources
+        .into_iter()
+        .map(|(address, type_, value)| {
+            let struct_tag = parse_struct_tag(&type_)
+                .map_err(|err| anyhow!("Invalid resource type {}: {}", type_, err))?;
+            Ok((address, struct_tag, value))
+        })
+        .collect::<Result<_>>()?;
+    Ok(Genesis { modules, resources })
+}
+
+type RawGenesis = (Vec<Vec<u8>>, Vec<(AccountAddress, String, Vec<u8>)>);
+
+fn json_genesis(contents: &str) -> Result<RawGenesis> {
+    let mut genesis = match serde_json::from_str(contents)? {
+        Value::Object(genesis) => genesis,
+        _ => bail!("expected an object with modules and resources"),
+    };
+    let mut list = |key: &str| match genesis.remove(key) {
+        None => Ok(vec![]),
+        Some(Value::Array(values)) => Ok(values),
+        Some(_) => Err(anyhow!("expected a list of {}", key)),
+    };
+    let modules = list("modules")?
+        .iter()
+        .map(|module| match module {
+            Value::String(s) => decode_hex(s),
+            _ => bail!("expected a module as a hex string"),
+        })
+        .collect::<Result<_>>()?;
+    let resources = list("resources")?
+        .iter()
+        .map(|resource| {
+            let field = |key: &str| match resource.get(key) {
+                Some(Value::String(s)) => Ok(s.as_str()),
+                _ => Err(anyhow!("expected a resource with a '{}' string", key)),
+            };
+            Ok((
+                AccountAddress::from_hex_literal(field("address")?)?,
+                field("type")?.to_string(),
+                decode_hex(field("value")?)?,
+            ))
+        })
+        .collect::<Result<_>>()?;
+    if let Some(key) = genesis.keys().next() {
+        bail!("unknown key '{}'", key)
+    }
+    Ok((modules, resources))
+}
+
+fn bcs_genesis(bytes: &[u8]) -> Result<RawGenesis> {
+    let bytes_layout = || MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U8));
+    let layout = MoveTypeLayout::Struct(MoveStructLayout::new(vec![
+        MoveTypeLayout::Vector(Box::new(bytes_layout())),
+        MoveTypeLayout::Vector(Box::new(MoveTypeLayout::Struct(MoveStructLayout::new(
+            vec![MoveTypeLayout::Address, bytes_layout(), bytes_layout()],
+        )))),
+    ]));
+    let mut fields = struct_fields(MoveValue::simple_deserialize(bytes, &layout)?).into_iter();
+    let (modules, resources) = match (fields.next(), fields.next()) {
+        (Some(MoveValue::Vector(modules)), Some(MoveValue::Vector(resources))) => {
+            (modules, resources)
+        }
+        _ => unreachable!("deserialized with the genesis layout"),
+    };
+    let modules = modules.into_iter().map(move_bytes).collect();
+    let resources = resources
+        .into_iter()
+        .map(|resource| {
+            let mut fields = struct_fields(resource).into_iter();
+            match (fields.next(), fields.next(), fields.next()) {
+                (Some(MoveValue::Address(address)), Some(type_), Some(value)) => {
+                    let type_ = String::from_utf8(move_bytes(type_))?;
+                    Ok((address, type_, move_bytes(value)))
+                }
+                _ => unreachable!("deserialized with the genesis layout"),
+            }
+        })
+        .collect::<Result<_>>()?;
+    Ok((modules, resources))
+}
+
+fn struct_fields(value: MoveValue) -> Vec<MoveValue> {
+    match value {
+        MoveValue::Struct(s) => s.into_fields(),
+        _ => unreachable!("deserialized with the genesis layout"),
+    }
+}
+
+/// The bytes of a `vector<u8>` value
+fn move_bytes(value: MoveValue) -> Vec<u8> {
+    match value {
+        MoveValue::Vector(elems) => elems
+            .into_iter()
+            .map(|elem| match elem {
+                MoveValue::U8(byte) => byte,
+                _ => unreachable!("deserialized with the genesis layout"),
+            })
+            .collect(),
+        _ => unreachable!("deserialized with the genesis layout"),
+    }
+}
@@ -0,0 +1,526 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Runs each test in a process of its own, so that a crash while running a test, e.g. a panic in
+//! a native function or a violated VM invariant, only fails that test.
+//!
+//! An isolated run writes its test plan and the settings of its runner to a directory of its
+//! own. A test is then run by running the current executable again with the hidden command
+//! `__move-unit-test-isolated`, the directory, and the qualified name of the test. The executable
+//! hands the command to `run_isolated_test_process` along with its natives, which reads the plan
+//! rather than compiling it again, runs only that test, writes its result to stdout, and exits.
+
+use crate::{
+    cache::{read_test_plan, write_test_plan},
+    format_module_id,
+    genesis::Genesis,
+    test_reporter::{
+        ExpectedAbortLocation, FailureReason, InstructionCountMismatch, TestFailure, TestRunInfo,
+    },
+};
+use anyhow::anyhow;
+use move_binary_format::{
+    errors::{Location, PartialVMError, VMError, VMResult},
+    file_format::FunctionDefinitionIndex,
+};
+use move_compiler::unit_test::TestPlan;
+use move_core_types::{
+    account_address::AccountAddress,
+    effects::{AccountChangeSet, ChangeSet},
+    gas_schedule::CostTable,
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag},
+    vm_status::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    env, fs,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// The hidden command a test process is started with, followed by the directory of the run and
+/// the qualified name of the test to run
+const ISOLATED_TEST_COMMAND: &str = "__move-unit-test-isolated";
+
+/// The files of an isolated run, in its directory
+const PLAN_FILE_NAME: &str = "test-plan.json";
+const SETTINGS_FILE_NAME: &str = "settings.json";
+
+/// The prefix of the line a test process writes its result on
+const RESULT_LINE_PREFIX: &str = "move-unit-test-result: ";
+
+/// The prefix of the line a test process writes on if it cannot run its test
+const ERROR_LINE_PREFIX: &str = "move-unit-test-error: ";
+
+/// How often a test process is checked for completion when it may time out
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// The number of isolated runs started by this process, which tells their directories apart
+static NUM_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+/// The directory of the run and the qualified name of the test to run, e.g. `0x1::M::test`, if
+/// this is a test process.
+pub(crate) fn requested_test() -> Option<(PathBuf, String)> {
+    let mut args = env::args_os().skip(1);
+    if args.next()? != ISOLATED_TEST_COMMAND {
+        return None;
+    }
+    let dir = PathBuf::from(args.next()?);
+    let test_name = args.next()?.into_string().ok()?;
+    Some((dir, test_name))
+}
+
+/// The settings of the runner of an isolated run, which its test processes run their test with.
+/// The natives are given to each process by its executable, and the tests are selected, ordered,
+/// timed, and snapshotted by the run.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct IsolatedSettings {
+    pub execution_bound: u64,
+    pub cost_table: CostTable,
+    pub metered: bool,
+    pub max_call_depth: usize,
+    pub max_value_depth: usize,
+    pub max_vector_length: Option<u64>,
+    pub genesis: Option<Genesis>,
+    pub named_address_values: Vec<(String, String)>,
+    pub save_storage_state_on_failure: bool,
+    pub full_storage_state_on_failure: bool,
+    pub state_output_dir: Option<String>,
+    pub check_stackless_vm: bool,
+    pub stackless_instruction_tolerance: Option<f64>,
+    pub stackless_only: bool,
+    pub verbose: bool,
+    pub retries: usize,
+    pub random_seed: u64,
+    pub invariants: Vec<(ModuleId, String)>,
+    pub check_invariants: bool,
+    pub capture_output: bool,
+}
+
+/// The directory an isolated run hands its test plan and settings to its test processes in,
+/// which is removed once the run is dropped.
+pub(crate) struct IsolatedRun {
+    dir: PathBuf,
+}
+
+impl IsolatedRun {
+    /// Write `test_plan` and `settings` for the test processes of a new run.
+    pub fn new(test_plan: &TestPlan, settings: &IsolatedSettings) -> anyhow::Result<Self> {
+        let dir = env::temp_dir().join(format!(
+            "move-unit-test-isolated-{}-{}",
+            std::process::id(),
+            NUM_RUNS.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir)?;
+        // Removes the directory if writing the files fails
+        let run = Self { dir };
+        write_test_plan(&run.dir.join(PLAN_FILE_NAME), test_plan)?;
+        fs::write(
+            run.dir.join(SETTINGS_FILE_NAME),
+            serde_json::to_string(settings)?,
+        )?;
+        Ok(run)
+    }
+}
+
+impl Drop for IsolatedRun {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Read the test plan and the settings of the isolated run with the directory `dir`.
+pub(crate) fn read_run(dir: &Path) -> anyhow::Result<(TestPlan, IsolatedSettings)> {
+    let test_plan = read_test_plan(&dir.join(PLAN_FILE_NAME))?;
+    let settings_path = dir.join(SETTINGS_FILE_NAME);
+    let settings = serde_json::from_str(&fs::read_to_string(&settings_path)?).map_err(|err| {
+        anyhow!(
+            "Invalid settings of isolated run {}: {}",
+            settings_path.display(),
+            err
+        )
+    })?;
+    Ok((test_plan, settings))
+}
+
+/// The result of a test, as sent by a test process.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct IsolatedResult {
+    pub test_run_info: TestRunInfo,
+    pub outcome: IsolatedOutcome,
+    pub mismatch: Option<InstructionCountMismatch>,
+    // The warnings of the test, which are reported by the process which started it
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) enum IsolatedOutcome {
+    Passed,
+    Failed(IsolatedFailure),
+    TimedOut(IsolatedFailure),
+}
+
+/// A `TestFailure` without its run info, which is sent separately
+#[derive(Serialize, Deserialize)]
+pub(crate) struct IsolatedFailure {
+    failure_reason: IsolatedFailureReason,
+    vm_error: Option<IsolatedVMError>,
+    storage_state: Option<String>,
+    random_inputs: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[allow(clippy::large_enum_variant)]
+enum IsolatedFailureReason {
+    NoAbort(String),
+    WrongAbort(String, u64, u64),
+    WrongAbortLocation(String, u64, u64, ExpectedAbortLocation),
+    Aborted(String, u64),
+    Timeout(String),
+    Mismatch {
+        move_vm_return_values: Result<Vec<Vec<u8>>, IsolatedVMError>,
+        move_vm_change_set: Result<IsolatedChangeSet, IsolatedVMError>,
+        stackless_vm_return_values: Result<Vec<Vec<u8>>, IsolatedVMError>,
+        stackless_vm_change_set: Result<IsolatedChangeSet, IsolatedVMError>,
+    },
+    Property(String),
+    FixtureFailed(String),
+    Events(String),
+    InvariantViolated(String),
+    SnapshotMismatch(String),
+    Crashed(String),
+    Unknown(String),
+}
+
+/// A `VMError`, without the table indices which the reports do not show
+#[derive(Serialize, Deserialize)]
+struct IsolatedVMError {
+    major_status: StatusCode,
+    sub_status: Option<u64>,
+    message: Option<String>,
+    location: IsolatedLocation,
+    offsets: Vec<(u16, u16)>,
+}
+
+#[derive(Serialize, Deserialize)]
+enum IsolatedLocation {
+    Undefined,
+    Script,
+    Module(ModuleId),
+}
+
+/// The changes to the modules and resources of each account, as lists as JSON maps only have
+/// string keys
+type IsolatedChangeSet = Vec<(
+    AccountAddress,
+    Vec<(Identifier, Option<Vec<u8>>)>,
+    Vec<(StructTag, Option<Vec<u8>>)>,
+)>;
+
+impl IsolatedFailure {
+    pub fn new(failure: &TestFailure) -> Self {
+        let failure_reason = match &failure.failure_reason {
+            FailureReason::NoAbort(message) => IsolatedFailureReason::NoAbort(message.clone()),
+            FailureReason::WrongAbort(message, expected, received) => {
+                IsolatedFailureReason::WrongAbort(message.clone(), *expected, *received)
+            }
+            FailureReason::WrongAbortLocation(message, expected, received, location) => {
+                IsolatedFailureReason::WrongAbortLocation(
+                    message.clone(),
+                    *expected,
+                    *received,
+                    location.clone(),
+                )
+            }
+            FailureReason::Aborted(message, code) => {
+                IsolatedFailureReason::Aborted(message.clone(), *code)
+            }
+            FailureReason::Timeout(message) => IsolatedFailureReason::Timeout(message.clone()),
+            FailureReason::Mismatch {
+                move_vm_return_values,
+                move_vm_change_set,
+                stackless_vm_return_values,
+                stackless_vm_change_set,
+            } => IsolatedFailureReason::Mismatch {
+                move_vm_return_values: isolate_result(move_vm_return_values, Clone::clone),
+                move_vm_change_set: isolate_result(move_vm_change_set, isolate_change_set),
+                stackless_vm_return_values: isolate_result(
+                    stackless_vm_return_values,
+                    Clone::clone,
+                ),
+                stackless_vm_change_set: isolate_result(
+                    stackless_vm_change_set,
+                    isolate_change_set,
+                ),
+            },
+            FailureReason::Property(message) => IsolatedFailureReason::Property(message.clone()),
+            FailureReason::FixtureFailed(message) => {
+                IsolatedFailureReason::FixtureFailed(message.clone())
+            }
+            FailureReason::Events(message) => IsolatedFailureReason::Events(message.clone()),
+            FailureReason::InvariantViolated(message) => {
+                IsolatedFailureReason::InvariantViolated(message.clone())
+            }
+            FailureReason::SnapshotMismatch(message) => {
+                IsolatedFailureReason::SnapshotMismatch(message.clone())
+            }
+            FailureReason::Crashed(message) => IsolatedFailureReason::Crashed(message.clone()),
+            FailureReason::Unknown(message) => IsolatedFailureReason::Unknown(message.clone()),
+        };
+        Self {
+            failure_reason,
+            vm_error: failure.vm_error.as_ref().map(IsolatedVMError::new),
+            storage_state: failure.storage_state.clone(),
+            random_inputs: failure.random_inputs.clone(),
+        }
+    }
+
+    pub fn into_failure(self, test_run_info: TestRunInfo) -> TestFailure {
+        let failure_reason = match self.failure_reason {
+            IsolatedFailureReason::NoAbort(message) => FailureReason::NoAbort(message),
+            IsolatedFailureReason::WrongAbort(message, expected, received) => {
+                FailureReason::WrongAbort(message, expected, received)
+            }
+            IsolatedFailureReason::WrongAbortLocation(message, expected, received, location) => {
+                FailureReason::WrongAbortLocation(message, expected, received, location)
+            }
+            IsolatedFailureReason::Aborted(message, code) => FailureReason::Aborted(message, code),
+            IsolatedFailureReason::Timeout(message) => FailureReason::Timeout(message),
+            IsolatedFailureReason::Mismatch {
+                move_vm_return_values,
+                move_vm_change_set,
+                stackless_vm_return_values,
+                stackless_vm_change_set,
+            } => FailureReason::mismatch(
+                restore_result(move_vm_return_values, |values| values),
+                restore_result(move_vm_change_set, restore_change_set),
+                restore_result(stackless_vm_return_values, |values| values),
+                restore_result(stackless_vm_change_set, restore_change_set),
+            ),
+            IsolatedFailureReason::Property(message) => FailureReason::Property(message),
+            IsolatedFailureReason::FixtureFailed(message) => FailureReason::FixtureFailed(message),
+            IsolatedFailureReason::Events(message) => FailureReason::Events(message),
+            IsolatedFailureReason::InvariantViolated(message) => {
+                FailureReason::InvariantViolated(message)
+            }
+            IsolatedFailureReason::SnapshotMismatch(message) => {
+                FailureReason::SnapshotMismatch(message)
+            }
+            IsolatedFailureReason::Crashed(message) => FailureReason::Crashed(message),
+            IsolatedFailureReason::Unknown(message) => FailureReason::Unknown(message),
+        };
+        let failure = TestFailure::new(
+            failure_reason,
+            test_run_info,
+            self.vm_error.map(IsolatedVMError::into_vm_error),
+            self.storage_state,
+        );
+        match self.random_inputs {
+            None => failure,
+            Some(random_inputs) => failure.with_random_inputs(random_inputs),
+        }
+    }
+}
+
+impl IsolatedVMError {
+    fn new(vm_error: &VMError) -> Self {
+        let location = match vm_error.location() {
+            Location::Undefined => IsolatedLocation::Undefined,
+            Location::Script => IsolatedLocation::Script,
+            Location::Module(module_id) => IsolatedLocation::Module(module_id.clone()),
+        };
+        Self {
+            major_status: vm_error.major_status(),
+            sub_status: vm_error.sub_status(),
+            message: vm_error.message().cloned(),
+            location,
+            offsets: vm_error
+                .offsets()
+                .iter()
+                .map(|(fdef_idx, offset)| (fdef_idx.0, *offset))
+                .collect(),
+        }
+    }
+
+    fn into_vm_error(self) -> VMError {
+        let mut error = PartialVMError::new(self.major_status);
+        if let Some(sub_status) = self.sub_status {
+            error = error.with_sub_status(sub_status);
+        }
+        if let Some(message) = self.message {
+            error = error.with_message(message);
+        }
+        let location = match self.location {
+            IsolatedLocation::Undefined => Location::Undefined,
+            IsolatedLocation::Script => Location::Script,
+            IsolatedLocation::Module(module_id) => Location::Module(module_id),
+        };
+        error
+            .at_code_offsets(
+                self.offsets
+                    .into_iter()
+                    .map(|(fdef_idx, offset)| (FunctionDefinitionIndex(fdef_idx), offset))
+                    .collect(),
+            )
+            .finish(location)
+    }
+}
+
+fn isolate_result<T, U>(result: &VMResult<T>, f: impl Fn(&T) -> U) -> Result<U, IsolatedVMError> {
+    result.as_ref().map(f).map_err(IsolatedVMError::new)
+}
+
+fn restore_result<T, U>(result: Result<T, IsolatedVMError>, f: impl Fn(T) -> U) -> VMResult<U> {
+    result.map(f).map_err(IsolatedVMError::into_vm_error)
+}
+
+fn isolate_change_set(change_set: &ChangeSet) -> IsolatedChangeSet {
+    change_set
+        .accounts()
+        .iter()
+        .map(|(addr, account_change_set)| {
+            (
+                *addr,
+                account_change_set.modules().clone().into_iter().collect(),
+                account_change_set.resources().clone().into_iter().collect(),
+            )
+        })
+        .collect()
+}
+
+fn restore_change_set(change_set: IsolatedChangeSet) -> ChangeSet {
+    let mut restored = ChangeSet::new();
+    for (addr, modules, resources) in change_set {
+        restored.publish_or_overwrite_account_change_set(
+            addr,
+            AccountChangeSet::from_modules_resources(
+                modules.into_iter().collect(),
+                resources.into_iter().collect(),
+            ),
+        );
+    }
+    restored
+}
+
+/// Write the result of the test of this test process for the process which started it.
+pub(crate) fn write_result(result: &IsolatedResult) {
+    write_line(
+        RESULT_LINE_PREFIX,
+        &serde_json::to_string(result).expect("Test results must serialize to JSON"),
+    )
+}
+
+/// Write why this test process cannot run its test for the process which started it.
+pub(crate) fn write_error(message: &str) {
+    // Only the first line is read
+    write_line(ERROR_LINE_PREFIX, &message.replace('\n', " "))
+}
+
+fn write_line(prefix: &str, line: &str) {
+    let mut stdout = std::io::stdout();
+    writeln!(stdout, "{}{}", prefix, line)
+        .and_then(|_| stdout.flush())
+        .expect("Unable to write to the process which started the test");
+}
+
+/// The outcome of running a test process.
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum ProcessOutcome {
+    Completed(IsolatedResult),
+    Crashed(String, Duration),
+    TimedOut(Duration),
+}
+
+/// Run the test `function_name` of the module `module_id` of the isolated run `run` in a process
+/// of its own, which is killed if it runs for longer than `timeout`.
+pub(crate) fn run_test_process(
+    run: &IsolatedRun,
+    module_id: &ModuleId,
+    function_name: &str,
+    timeout: Option<Duration>,
+) -> ProcessOutcome {
+    let start = Instant::now();
+    let spawned = env::current_exe().and_then(|exe| {
+        Command::new(exe)
+            .arg(ISOLATED_TEST_COMMAND)
+            .arg(&run.dir)
+            .arg(format!(
+                "{}::{}",
+                format_module_id(module_id),
+                function_name
+            ))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+    });
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(err) => {
+            return ProcessOutcome::Crashed(
+                format!("could not be started: {}", err),
+                start.elapsed(),
+            )
+        }
+    };
+    // The output is read while the process runs, so that it does not block on a full pipe
+    let stdout = read_in_background(child.stdout.take());
+    let stderr = read_in_background(child.stderr.take());
+
+    let status = match timeout {
+        None => child.wait(),
+        Some(timeout) => loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break Ok(status),
+                Ok(None) if start.elapsed() > timeout => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return ProcessOutcome::TimedOut(start.elapsed());
+                }
+                Ok(None) => thread::sleep(POLL_INTERVAL),
+                Err(err) => break Err(err),
+            }
+        },
+    };
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+
+    if let Some(error) = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix(ERROR_LINE_PREFIX))
+    {
+        return ProcessOutcome::Crashed(
+            format!("could not run the test: {}", error),
+            start.elapsed(),
+        );
+    }
+    let result = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix(RESULT_LINE_PREFIX))
+        .and_then(|result| serde_json::from_str(result).ok());
+    match (result, status) {
+        (Some(result), Ok(status)) if status.success() => ProcessOutcome::Completed(result),
+        (_, Ok(status)) => ProcessOutcome::Crashed(
+            format!("exited with {}\n{}", status, stderr.trim_end()),
+            start.elapsed(),
+        ),
+        (_, Err(err)) => {
+            ProcessOutcome::Crashed(format!("could not be waited for: {}", err), start.elapsed())
+        }
+    }
+}
+
+fn read_in_background(pipe: Option<impl Read + Send + 'static>) -> JoinHandle<String> {
+    thread::spawn(move || {
+        let mut output = vec![];
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut output);
+        }
+        String::from_utf8_lossy(&output).into_owned()
+    })
+}
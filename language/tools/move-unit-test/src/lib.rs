@@ -1,27 +1,96 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+mod cache;
 pub mod cargo_runner;
+pub mod genesis;
+mod isolation;
+pub mod test_data;
 pub mod test_reporter;
 pub mod test_runner;
-use crate::test_runner::TestRunner;
-use move_command_line_common::files::verify_and_create_named_address_mapping;
+use crate::{
+    cache::{read_cached_test_plan, test_plan_cache_path, write_cached_test_plan},
+    genesis::load_genesis,
+    test_data::expand_data_driven_tests,
+    test_reporter::{
+        EventReporter, JunitReporter, ListedTest, SilentReporter, StatisticsFormat, TestEvent,
+        TestReportFormat, TestReporter, TestResults, TextProgress, TextReporter,
+        MOVE_VM_TRACE_ENV_VAR_NAME,
+    },
+    test_runner::{NativeExtensionsFactory, TestRunner},
+};
+use codespan_reporting::{
+    diagnostic::Diagnostic,
+    files::SimpleFiles,
+    term::{
+        emit,
+        termcolor::{ColorChoice, StandardStream},
+        Config,
+    },
+};
+use colored::control;
+use move_binary_format::{binary_views::BinaryIndexedView, CompiledModule};
+use move_bytecode_source_map::source_map::SourceMap;
+use move_command_line_common::{
+    files::{
+        extension_equals, find_filenames, verify_and_create_named_address_mapping, FileHash,
+        MOVE_COMPILED_EXTENSION,
+    },
+    testing::read_env_update_baseline,
+};
 use move_compiler::{
     self,
-    diagnostics::{self, codes::Severity},
-    shared::{self, NumericalAddress},
-    unit_test::{self, TestPlan},
+    compiled_unit::NamedCompiledModule,
+    diagnostics::{self, codes::Severity, Diagnostics, FilesSourceText},
+    shared::{self, NumberFormat, NumericalAddress},
+    unit_test::{self, filter_test_members::UNIT_TEST_POISON_FUN_NAME, TestPlan},
     Compiler, Flags, PASS_CFGIR,
 };
-use move_core_types::language_storage::ModuleId;
-use move_vm_runtime::native_functions::NativeFunctionTable;
+use move_core_types::{
+    account_address::AccountAddress,
+    gas_schedule::{CostTable, GasAlgebra},
+    identifier::Identifier,
+    language_storage::ModuleId,
+};
+use move_coverage::{
+    coverage_map::{output_map_to_file, CoverageMap},
+    lcov::{output_lcov, LcovModule},
+};
+use move_ir_types::location::Loc;
+use move_symbol_pool::Symbol;
+use move_vm_runtime::{config::VMConfig, native_functions::NativeFunctionTable};
+use move_vm_types::gas_schedule::INITIAL_COST_SCHEDULE;
+use regex::Regex;
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
+    fs::{self, File},
     io::{Result, Write},
     marker::Send,
-    sync::Mutex,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 use structopt::*;
+use tracing_subscriber::fmt::format::FmtSpan;
+
+/// The environment variable carrying the W3C trace context of the process running the tests, of
+/// the form `<version>-<trace-id>-<parent-id>-<trace-flags>`.
+const TRACEPARENT_ENV_VAR_NAME: &str = "TRACEPARENT";
+
+/// The default name of the file the failed tests of a run are recorded in.
+pub const FAILED_TESTS_FILE_NAME: &str = ".move-test-failures.json";
+
+/// The name of the built-in gas schedule, for use with `--gas-schedule`.
+pub const INITIAL_GAS_SCHEDULE_NAME: &str = "initial";
+
+/// The percentage by which the instruction counts of a test on the Move VM and on the stackless
+/// bytecode interpreter may differ when `--stackless-instruction-tolerance` is given no value.
+pub const DEFAULT_STACKLESS_INSTRUCTION_TOLERANCE: f64 = 100.0;
 
 #[derive(Debug, StructOpt, Clone)]
 #[structopt(name = "Move Unit Test", about = "Unit testing for Move code.")]
@@ -35,35 +104,272 @@ pub struct UnitTestingConfig {
     )]
     pub instruction_execution_bound: u64,
 
-    /// A filter string to determine which unit tests to run
-    #[structopt(name = "filter", short = "f", long = "filter")]
-    pub filter: Option<String>,
+    /// Meter the tests with a gas schedule instead of bounding them by a number of instructions,
+    /// and report the gas used by each test. Either `initial` for the built-in schedule, or the
+    /// path of a JSON file holding a cost table
+    #[structopt(
+        name = "gas_schedule",
+        long = "gas-schedule",
+        conflicts_with = "stackless_instruction_tolerance"
+    )]
+    pub gas_schedule: Option<String>,
+
+    /// The number of gas units each test may use when metered with a gas schedule. Defaults to
+    /// the maximum number of gas units of a transaction in the schedule
+    #[structopt(name = "gas_limit", long = "gas-limit", requires = "gas_schedule")]
+    pub gas_limit: Option<u64>,
+
+    /// The largest number of nested function calls in a test, beyond which it fails. Defaults to
+    /// the limit of the Move VM, 1024
+    #[structopt(name = "max_call_depth", long = "max-call-depth")]
+    pub max_call_depth: Option<usize>,
+
+    /// The largest depth of the type of a value a test moves to or from storage, beyond which it
+    /// fails. Defaults to the limit of the Move VM, 256
+    #[structopt(name = "max_value_depth", long = "max-value-depth")]
+    pub max_value_depth: Option<usize>,
+
+    /// The largest number of elements of a vector in a test, beyond which it fails. Vectors are
+    /// not limited by default
+    #[structopt(name = "max_vector_length", long = "max-vector-length")]
+    pub max_vector_length: Option<u64>,
+
+    /// Abandon each test which runs for longer than the given number of seconds, and report it
+    /// as timed out. Unlike the instruction bound, this also covers tests hanging in natives
+    #[structopt(name = "timeout", long = "timeout")]
+    pub timeout: Option<u64>,
+
+    /// Run each failing test again up to this number of times before reporting it as failed. The
+    /// tests which pass on a retry are reported as flaky
+    #[structopt(name = "retries", long = "retries", default_value = "0")]
+    pub retries: usize,
+
+    /// Stop running tests after the first failure, and report the tests which were skipped
+    #[structopt(name = "fail_fast", long = "fail-fast")]
+    pub fail_fast: bool,
+
+    /// Record the tests which failed in this file, as a JSON list of their qualified names. The
+    /// package CLI records them in `.move-test-failures.json` of the package by default
+    #[structopt(name = "failed_tests_file", long = "failed-tests-file")]
+    pub failed_tests_file: Option<String>,
+
+    /// Only run the tests which failed in the previous run, as recorded in the failed tests file.
+    /// All tests are run if there is no record of a previous run
+    #[structopt(name = "rerun_failed", long = "rerun-failed")]
+    pub rerun_failed: bool,
+
+    /// A regex to determine which unit tests to run, matched against the qualified
+    /// (<addr>::<module_name>::<fn_name>) name of each test. Can be repeated, in which case the
+    /// tests matching any of the filters are run
+    #[structopt(
+        name = "filter",
+        short = "f",
+        long = "filter",
+        number_of_values = 1,
+        validator = validate_filter
+    )]
+    pub filter: Vec<String>,
+
+    /// A regex for the qualified names of unit tests not to run, even when they match a filter.
+    /// Can be repeated
+    #[structopt(
+        name = "exclude",
+        long = "exclude",
+        number_of_values = 1,
+        validator = validate_filter
+    )]
+    pub exclude: Vec<String>,
+
+    /// Only run the unit tests with the given tag, as declared in `#[test(tags = [b"tag"])]`.
+    /// Can be repeated, in which case the tests with any of the tags are run
+    #[structopt(name = "include_tag", long = "include-tag", number_of_values = 1)]
+    pub include_tags: Vec<String>,
+
+    /// Do not run the unit tests with the given tag, even when they have an included tag. Can be
+    /// repeated
+    #[structopt(name = "exclude_tag", long = "exclude-tag", number_of_values = 1)]
+    pub exclude_tags: Vec<String>,
+
+    /// Only run the unit tests of the given module, written `<address>::<module_name>` with a
+    /// numerical or named address. Can be repeated
+    #[structopt(name = "module", long = "module", number_of_values = 1)]
+    pub modules: Vec<String>,
+
+    /// Only run the unit tests of the modules published at the given numerical or named address.
+    /// Can be repeated, and combined with `--module` to also run the tests of these modules
+    #[structopt(name = "address", long = "address", number_of_values = 1)]
+    pub module_addresses: Vec<String>,
 
-    /// List all tests
+    /// Only run the tests of the given shard, written `i/n` for the `i`-th out of `n` shards
+    /// numbered from 1. Tests are assigned to shards by a hash of their qualified name, so that
+    /// the shards partition the tests deterministically, e.g. across CI machines
+    #[structopt(
+        name = "shard",
+        long = "shard",
+        parse(try_from_str = parse_shard)
+    )]
+    pub shard: Option<(usize, usize)>,
+
+    /// Run the tests in a random order, determined by the given seed or by a random one. The seed
+    /// is reported with the results, to run the tests in the same order again
+    #[structopt(name = "shuffle", long = "shuffle")]
+    pub shuffle: Option<Option<u64>>,
+
+    /// The seed the inputs of #[random_test] functions are generated from, a random one if it is
+    /// not given. The seed is reported with each failing random test, to reproduce its inputs
+    #[structopt(name = "seed", long = "seed")]
+    pub seed: Option<u64>,
+
+    /// Run each test in a process of its own, so that a crash while running a test, e.g. a panic
+    /// in a native function, only fails that test. The test processes run this executable again,
+    /// which hands them to `run_isolated_test_process`
+    #[structopt(name = "isolate", long = "isolate")]
+    pub isolate: bool,
+
+    /// Keep running, and run the tests again whenever a source or dependency file changes
+    #[structopt(name = "watch", long = "watch")]
+    pub watch: bool,
+
+    /// List all tests. With `--format json`, each test is listed as a line of JSON with its module,
+    /// name, and the file and line it is declared at
     #[structopt(name = "list", short = "l", long = "list")]
     pub list: bool,
 
-    /// Number of threads to use for running tests.
+    /// Number of threads to use for running tests, or `auto` for one for each available core
     #[structopt(
         name = "num_threads",
         default_value = "8",
         short = "t",
-        long = "threads"
+        long = "threads",
+        parse(try_from_str = parse_num_threads)
     )]
     pub num_threads: usize,
 
-    /// Dependency files
+    /// Dependency files: Move sources, compiled `.mv` modules, or directories of them. Compiled
+    /// modules are published as they are, without being recompiled
     #[structopt(name = "dependencies", long = "dependencies", short = "d")]
     pub dep_files: Vec<String>,
 
-    /// Report test statistics at the end of testing
+    /// Also run the tests declared in the Move sources of the dependencies, e.g. to exercise a
+    /// vendored framework along with the code using it
+    #[structopt(name = "include_dep_tests", long = "include-dep-tests")]
+    pub include_dep_tests: bool,
+
+    /// Reuse the test plan compiled by a previous run with the same sources, dependencies,
+    /// named addresses and compilation flags, rather than compiling them again. The plan is
+    /// cached by the first run with this flag
+    #[structopt(name = "cached", long = "cached")]
+    pub cached: bool,
+
+    /// The directory the test plans are cached in with `--cached`, by default a directory of the
+    /// current user in the temporary directory
+    #[structopt(name = "cache_dir", long = "cache-dir")]
+    pub cache_dir: Option<String>,
+
+    /// Report the warnings found while compiling the tests and run them nonetheless, rather than
+    /// failing on the first warning
+    #[structopt(name = "allow_warnings", long = "allow-warnings")]
+    pub allow_warnings: bool,
+
+    /// Only report the tests which fail, rather than each test as it completes
+    #[structopt(name = "quiet", short = "q", long = "quiet")]
+    pub quiet: bool,
+
+    /// Show the number of completed tests and the module being tested on a line which is redrawn
+    /// as the tests run, and only report the tests which fail. Meant for terminals
+    #[structopt(name = "progress", long = "progress", conflicts_with = "quiet")]
+    pub progress: bool,
+
+    /// Report test statistics at the end of testing. The stack depth and the values allocated by
+    /// each test are only tracked when built with the `statistics` feature, and reported as 0
+    /// otherwise
     #[structopt(name = "report_statistics", short = "s", long = "statistics")]
     pub report_statistics: bool,
 
+    /// The format test statistics are reported in: `table`, or `csv` and `json` to be processed
+    /// by other tools
+    #[structopt(
+        name = "statistics_format",
+        long = "statistics-format",
+        default_value = "table",
+        possible_values = &["table", "csv", "json"]
+    )]
+    pub statistics_format: StatisticsFormat,
+
+    /// Together with `--statistics`, write the test statistics to this file rather than report
+    /// them with the results
+    #[structopt(
+        name = "statistics_output",
+        long = "statistics-output",
+        requires = "report_statistics"
+    )]
+    pub statistics_output: Option<String>,
+
+    /// Report the given number of tests which took the longest to run, by wall clock time, at
+    /// the end of testing
+    #[structopt(name = "report_slow_tests", long = "report-slow-tests")]
+    pub report_slow_tests: Option<usize>,
+
+    /// Together with `--statistics`, report the given number of functions which executed the
+    /// most instructions over all tests, including the functions of dependencies. This needs the
+    /// execution of the tests to be traced by the Move VM
+    #[structopt(
+        name = "report_function_histogram",
+        long = "function-histogram",
+        requires = "report_statistics"
+    )]
+    pub report_function_histogram: Option<usize>,
+
     /// Show the storage state at the end of execution of a failing test
     #[structopt(name = "global_state_on_error", short = "g", long = "state_on_error")]
     pub report_storage_on_error: bool,
 
+    /// Together with `--state_on_error`, show all of the storage state rather than only the
+    /// changes the failing test made to it
+    #[structopt(name = "full_global_state_on_error", long = "full_state_on_error")]
+    pub report_full_storage_on_error: bool,
+
+    /// Write the storage state at the end of each failing test to a JSON file in this directory,
+    /// with the resources keyed by address and type
+    #[structopt(name = "state_output_dir", long = "state-output-dir")]
+    pub state_output_dir: Option<String>,
+
+    /// Write the outcome of each test, with its failure and the storage state at the point of
+    /// failure, to a `.exp` file in this directory, and fail the tests whose outcome differs from
+    /// the existing file
+    #[structopt(name = "snapshot_dir", long = "snapshot-dir")]
+    pub snapshot_dir: Option<String>,
+
+    /// Together with `--snapshot-dir`, overwrite the snapshots with the outcome of the tests
+    /// rather than compare against them. Also set by the UPDATE_BASELINE environment variable
+    #[structopt(name = "update_baseline", long = "update-baseline")]
+    pub update_baseline: bool,
+
+    /// After each test passes, run the functions declared with #[test_invariant] against the
+    /// storage state it left, and fail the test if any of them aborts
+    #[structopt(name = "check_invariants", long = "check-invariants")]
+    pub check_invariants: bool,
+
+    /// Print the output of `std::debug` as the tests run, rather than only for the tests which
+    /// fail
+    #[structopt(name = "nocapture", long = "nocapture")]
+    pub nocapture: bool,
+
+    /// Once all tests pass, run the Move Prover over the specs of the modules of the source
+    /// files, and fail the run if any of them cannot be verified
+    #[structopt(name = "prove", long = "prove")]
+    pub prove: bool,
+
+    /// Start each test from the modules and resources of this genesis file, as JSON if it has a
+    /// `.json` extension and as BCS otherwise. The modules compiled for the tests replace those of
+    /// the genesis
+    #[structopt(
+        name = "genesis",
+        long = "genesis",
+        conflicts_with = "check-stackless-vm"
+    )]
+    pub genesis: Option<String>,
+
     /// Named address mapping
     #[structopt(
         name = "NAMED_ADDRESSES",
@@ -73,6 +379,11 @@ pub struct UnitTestingConfig {
     )]
     pub named_address_values: Vec<(String, NumericalAddress)>,
 
+    /// A TOML or JSON file of named addresses, merged with those given with `-a`, which take
+    /// precedence. The file is read by `load_address_file`
+    #[structopt(name = "address_file", long = "address-file")]
+    pub address_file: Option<String>,
+
     /// Source files
     #[structopt(name = "sources")]
     pub source_files: Vec<String>,
@@ -82,9 +393,368 @@ pub struct UnitTestingConfig {
     #[structopt(long = "stackless")]
     pub check_stackless_vm: bool,
 
+    /// When cross checking with the stackless bytecode interpreter, report tests whose
+    /// instruction counts on the two VMs differ by more than this percentage of the count on the
+    /// Move VM, 100 by default. This is a coarse ratio of the total counts only: the stackless
+    /// bytecode has no stack operations and the Move VM derives its count from its gas meter, so
+    /// the counts of a test rarely agree and small tolerances mostly flag short tests
+    #[structopt(
+        name = "stackless_instruction_tolerance",
+        long = "stackless-instruction-tolerance",
+        requires = "check-stackless-vm"
+    )]
+    pub stackless_instruction_tolerance: Option<Option<f64>>,
+
+    /// Run the tests on the stackless bytecode interpreter only, without running them on the Move
+    /// VM or cross checking the results of the two. Storage faults and events are not simulated
+    /// by the interpreter.
+    #[structopt(
+        name = "stackless_only",
+        long = "stackless-only",
+        conflicts_with_all = &["check-stackless-vm", "gas_schedule", "genesis", "check_invariants"]
+    )]
+    pub stackless_only: bool,
+
     /// Verbose mode
     #[structopt(short = "v", long = "verbose")]
     pub verbose: bool,
+
+    /// Write metrics of the test run in OpenMetrics text format to this file
+    #[structopt(name = "open_metrics", long = "open-metrics")]
+    pub open_metrics_output: Option<String>,
+
+    /// Write the coverage of the tests to this file, as a coverage map for the `move-coverage`
+    /// tools. The executed instructions are traced by the Move VM, which also runs the tests
+    /// cross checked with the stackless VM, to a `.trace` file next to it. Tracing is only
+    /// available in debug builds
+    #[structopt(name = "coverage", long = "coverage")]
+    pub coverage: Option<String>,
+
+    /// Together with `--coverage`, write the coverage of the source files of the tested modules
+    /// and their dependencies as an LCOV tracefile instead
+    #[structopt(name = "lcov", long = "lcov", requires = "coverage")]
+    pub lcov: bool,
+
+    /// Configuration available to all tests through `UnitTest::config`, overriding the values
+    /// set with `#[test(env = b"KEY=VALUE")]`
+    #[structopt(
+        name = "test_env",
+        long = "test-env",
+        parse(try_from_str = parse_test_env)
+    )]
+    pub test_env: Vec<(String, String)>,
+
+    /// Build the test plan and check that all tests can be run, reporting the tests that would
+    /// be run, after filtering, without executing them. Also available as `--no-run`
+    #[structopt(name = "dry_run", long = "dry-run", alias = "no-run")]
+    pub dry_run: bool,
+
+    /// Write the tracing spans of the test run (suite, modules, tests and VM sessions) as JSON
+    /// lines to this file. A W3C trace context in the `TRACEPARENT` environment variable is
+    /// recorded on the suite span.
+    #[structopt(name = "log_json", long = "log-json")]
+    pub log_json: Option<String>,
+
+    /// The format to report test results in: `text`, `junit` for a JUnit XML report, or `json`
+    /// for a stream of JSON lines with an event for each test started and completed
+    #[structopt(
+        name = "format",
+        long = "format",
+        default_value = "text",
+        possible_values = &["text", "junit", "json"]
+    )]
+    pub format: TestReportFormat,
+}
+
+/// Run the test requested by an isolated test run and exit, if this process is one of its test
+/// processes. These run the executable of the run again, so a binary running tests with
+/// `--isolate` calls this first thing in `main`, with the natives it runs the tests with.
+pub fn run_isolated_test_process(
+    native_function_table: Option<NativeFunctionTable>,
+    native_extensions: Option<NativeExtensionsFactory>,
+) {
+    if let Some((dir, test_name)) = isolation::requested_test() {
+        TestRunner::run_isolated_test(&dir, &test_name, native_function_table, native_extensions)
+    }
+}
+
+/// Parse a `KEY=VALUE` test environment entry.
+pub fn parse_test_env(s: &str) -> anyhow::Result<(String, String)> {
+    match s.split_once('=') {
+        Some((key, value)) => Ok((key.to_string(), value.to_string())),
+        None => anyhow::bail!("Invalid test environment entry '{}', expected KEY=VALUE", s),
+    }
+}
+
+/// Parse a number of threads, or `auto` for the number of available cores.
+pub fn parse_num_threads(s: &str) -> anyhow::Result<usize> {
+    if s == "auto" {
+        return Ok(num_cpus::get());
+    }
+    match s.parse()? {
+        0 => anyhow::bail!("Invalid number of threads '{}', expected at least 1", s),
+        num_threads => Ok(num_threads),
+    }
+}
+
+/// Parse an `i/n` shard specification, with `1 <= i <= n`.
+pub fn parse_shard(s: &str) -> anyhow::Result<(usize, usize)> {
+    let (index, count) = s
+        .split_once('/')
+        .ok_or_else(|| anyhow::anyhow!("Invalid shard '{}', expected i/n", s))?;
+    let index: usize = index.trim().parse()?;
+    let count: usize = count.trim().parse()?;
+    if index == 0 || index > count {
+        anyhow::bail!(
+            "Invalid shard '{}', the index must be between 1 and the number of shards",
+            s
+        );
+    }
+    Ok((index, count))
+}
+
+/// Check that a test filter is a valid regex, for use as a command line validator.
+pub fn validate_filter(s: String) -> std::result::Result<(), String> {
+    Regex::new(&s).map(|_| ()).map_err(|err| err.to_string())
+}
+
+/// Compile the regexes of test filters, which may not have been validated on the command line
+fn compile_filters(patterns: &[String]) -> anyhow::Result<Vec<Regex>> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            Regex::new(pattern)
+                .map_err(|err| anyhow::anyhow!("Invalid test filter '{}': {}", pattern, err))
+        })
+        .collect()
+}
+
+/// The cost table of the gas schedule `schedule`, either the built-in one or one read from a JSON
+/// file.
+fn load_gas_schedule(schedule: &str) -> anyhow::Result<CostTable> {
+    if schedule == INITIAL_GAS_SCHEDULE_NAME {
+        return Ok(INITIAL_COST_SCHEDULE.clone());
+    }
+    let contents = fs::read_to_string(schedule)
+        .map_err(|err| anyhow::anyhow!("Unable to read gas schedule {}: {}", schedule, err))?;
+    serde_json::from_str(&contents)
+        .map_err(|err| anyhow::anyhow!("Invalid gas schedule {}: {}", schedule, err))
+}
+
+/// Trace the instructions executed by the Move VM to a file next to the coverage output at
+/// `path`, and return the path of the trace. The trace of a previous run is discarded.
+fn start_coverage_trace(path: &str) -> anyhow::Result<String> {
+    let trace_path = std::env::var(MOVE_VM_TRACE_ENV_VAR_NAME).unwrap_or_else(|_| {
+        let trace_path = format!("{}.trace", path);
+        std::env::set_var(MOVE_VM_TRACE_ENV_VAR_NAME, &trace_path);
+        trace_path
+    });
+    // The test processes of an isolated run append to the trace of the run. The Move VM keeps
+    // the trace open once it has started tracing, so it is truncated rather than removed.
+    File::create(&trace_path)?;
+    Ok(trace_path)
+}
+
+/// Write the coverage computed from the Move VM trace at `trace_path` to `path`, as a coverage
+/// map or as an LCOV tracefile for the modules of `test_plan`.
+fn write_coverage(
+    path: &str,
+    trace_path: &str,
+    lcov: bool,
+    test_plan: &TestPlan,
+) -> anyhow::Result<()> {
+    let coverage_map = CoverageMap::from_trace_file(trace_path);
+    if !lcov {
+        return output_map_to_file(path, &coverage_map);
+    }
+    let modules = test_plan
+        .module_info
+        .values()
+        .filter_map(|unit| {
+            let file_hash = unit.source_map.definition_location.file_hash();
+            let (file_path, file_contents) = test_plan.files.get(&file_hash)?;
+            Some(LcovModule {
+                module: &unit.module,
+                source_map: &unit.source_map,
+                file_path: file_path.as_str(),
+                file_contents,
+                skipped_functions: &[UNIT_TEST_POISON_FUN_NAME],
+            })
+        })
+        .collect::<Vec<_>>();
+    output_lcov(
+        &modules,
+        &coverage_map.to_unified_exec_map(),
+        &mut File::create(path)?,
+    )
+}
+
+fn is_bytecode_file(path: &str) -> bool {
+    let path = Path::new(path);
+    path.is_file() && extension_equals(path, MOVE_COMPILED_EXTENSION)
+}
+
+/// The compiled modules among `deps`, given as `.mv` files or found in directories
+fn find_bytecode_deps(deps: &[String]) -> anyhow::Result<Vec<String>> {
+    let (files, dirs): (Vec<_>, Vec<_>) = deps
+        .iter()
+        .filter(|dep| Path::new(dep).is_dir() || is_bytecode_file(dep))
+        .cloned()
+        .partition(|dep| is_bytecode_file(dep));
+    let mut bytecode_deps = files;
+    bytecode_deps.extend(find_filenames(&dirs, |path| {
+        extension_equals(path, MOVE_COMPILED_EXTENSION)
+    })?);
+    Ok(bytecode_deps)
+}
+
+/// Deserialize the compiled modules at `paths`. As they have no source, their source maps point
+/// nowhere.
+fn load_bytecode_deps(paths: &[String]) -> anyhow::Result<BTreeMap<ModuleId, NamedCompiledModule>> {
+    paths
+        .iter()
+        .map(|path| {
+            let bytes = fs::read(path).map_err(|err| {
+                anyhow::anyhow!("Unable to read compiled module {}: {}", path, err)
+            })?;
+            let module = CompiledModule::deserialize(&bytes)
+                .map_err(|err| anyhow::anyhow!("Invalid compiled module {}: {}", path, err))?;
+            let source_map = SourceMap::dummy_from_view(
+                &BinaryIndexedView::Module(&module),
+                Loc::new(FileHash::empty(), 0, 0),
+            )?;
+            let module_id = module.self_id();
+            let unit = NamedCompiledModule {
+                address: NumericalAddress::new(module_id.address().into_bytes(), NumberFormat::Hex),
+                name: Symbol::from(module_id.name().as_str()),
+                module,
+                source_map,
+            };
+            Ok((module_id, unit))
+        })
+        .collect()
+}
+
+/// Report a diagnostic which doesn't concern a source file, in the style of those of the compiler
+fn report_diagnostic(diagnostic: Diagnostic<usize>) {
+    let writer = StandardStream::stderr(ColorChoice::Auto);
+    let files = SimpleFiles::<String, String>::new();
+    // Failing to write to stderr leaves no other place to report to
+    let _ = emit(&mut writer.lock(), &Config::default(), &files, &diagnostic);
+}
+
+/// Report the diagnostics of the compiler on the given sources, without exiting the process like
+/// the reporting functions of the compiler do
+fn report_compiler_diagnostics(files: &FilesSourceText, diags: Diagnostics) {
+    let buffer = if control::SHOULD_COLORIZE.should_colorize() {
+        diagnostics::report_diagnostics_to_color_buffer(files, diags)
+    } else {
+        diagnostics::report_diagnostics_to_buffer(files, diags)
+    };
+    // Failing to write to stderr leaves no other place to report to
+    let _ = std::io::stderr().write_all(&buffer);
+}
+
+/// The directory test plans are cached in by default, which is specific to the current user so
+/// that users sharing the temporary directory don't read each other's plans
+fn default_cache_dir() -> PathBuf {
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "default".to_string());
+    std::env::temp_dir().join(format!("move-unit-test-{}", user))
+}
+
+/// Read the qualified names of the tests recorded in the failed tests file at `path`. Returns
+/// `None` if there is no such file.
+fn read_failed_tests(path: &str) -> anyhow::Result<Option<BTreeSet<String>>> {
+    if !Path::new(path).exists() {
+        return Ok(None);
+    }
+    let failed_tests = serde_json::from_str(&fs::read_to_string(path)?)
+        .map_err(|err| anyhow::anyhow!("Invalid failed tests file {}: {}", path, err))?;
+    Ok(Some(failed_tests))
+}
+
+/// Record the qualified names of the tests which failed in `test_results` in the failed tests
+/// file at `path`.
+fn write_failed_tests(path: &str, test_results: &TestResults) -> Result<()> {
+    let failed_tests: BTreeSet<String> = test_results
+        .statistics()
+        .failed()
+        .iter()
+        .flat_map(|(module_id, test_failures)| {
+            test_failures.iter().map(move |test_failure| {
+                format!(
+                    "{}::{}",
+                    format_module_id(module_id),
+                    test_failure.test_run_info.function_ident
+                )
+            })
+        })
+        .collect();
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(&failed_tests)?)
+}
+
+/// Parse a W3C `traceparent` value into its trace id and parent span id. Returns `None` if the
+/// value is malformed.
+fn parse_traceparent(traceparent: &str) -> Option<(&str, &str)> {
+    let is_lower_hex = |s: &str, len: usize| {
+        s.len() == len && s.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f'))
+    };
+    let is_zero = |s: &str| s.chars().all(|c| c == '0');
+
+    let mut fields = traceparent.trim().split('-');
+    let version = fields.next()?;
+    let trace_id = fields.next()?;
+    let parent_id = fields.next()?;
+    let flags = fields.next()?;
+    // Version 00 has exactly four fields, later versions may append more
+    if !is_lower_hex(version, 2) || version == "ff" || (version == "00" && fields.next().is_some())
+    {
+        return None;
+    }
+    if !is_lower_hex(trace_id, 32)
+        || is_zero(trace_id)
+        || !is_lower_hex(parent_id, 16)
+        || is_zero(parent_id)
+        || !is_lower_hex(flags, 2)
+    {
+        return None;
+    }
+    Some((trace_id, parent_id))
+}
+
+/// A run of unit tests on a background thread, started by `UnitTestingConfig::spawn_unit_tests`.
+pub struct UnitTestRun {
+    /// The events of the run as they happen. The channel is disconnected once the run is over.
+    pub events: Receiver<TestEvent>,
+    cancelled: Arc<AtomicBool>,
+    handle: JoinHandle<anyhow::Result<bool>>,
+}
+
+impl UnitTestRun {
+    /// Cancel the run. The tests which are running complete, but no other test is started.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Wait for the run to be over. Returns `true` if all tests passed, and `false` if a test
+    /// failed or the run was cancelled.
+    pub fn join(self) -> anyhow::Result<bool> {
+        let all_tests_passed = self
+            .handle
+            .join()
+            .map_err(|_| anyhow::anyhow!("Unit test run panicked"))??;
+        Ok(all_tests_passed && !self.cancelled.load(Ordering::Relaxed))
+    }
+}
+
+/// Turn an error of the library entry points into one of those reporting to a writer.
+fn into_io_error(err: anyhow::Error) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Other, err)
 }
 
 fn format_module_id(module_id: &ModuleId) -> String {
@@ -100,16 +770,64 @@ impl UnitTestingConfig {
     pub fn default_with_bound(bound: Option<u64>) -> Self {
         Self {
             instruction_execution_bound: bound.unwrap_or(5000),
-            filter: None,
+            gas_schedule: None,
+            gas_limit: None,
+            max_call_depth: None,
+            max_value_depth: None,
+            max_vector_length: None,
+            filter: vec![],
+            exclude: vec![],
+            include_tags: vec![],
+            exclude_tags: vec![],
+            modules: vec![],
+            module_addresses: vec![],
             num_threads: 8,
+            quiet: false,
+            progress: false,
             report_statistics: false,
+            statistics_format: StatisticsFormat::Table,
+            statistics_output: None,
+            report_slow_tests: None,
+            report_function_histogram: None,
             report_storage_on_error: false,
+            report_full_storage_on_error: false,
+            state_output_dir: None,
+            snapshot_dir: None,
+            update_baseline: false,
+            check_invariants: false,
+            nocapture: false,
+            prove: false,
+            genesis: None,
+            timeout: None,
+            fail_fast: false,
+            shard: None,
+            retries: 0,
+            failed_tests_file: None,
+            rerun_failed: false,
+            watch: false,
+            isolate: false,
+            shuffle: None,
+            seed: None,
             source_files: vec![],
             dep_files: vec![],
+            include_dep_tests: false,
+            cached: false,
+            cache_dir: None,
+            allow_warnings: false,
             check_stackless_vm: false,
+            stackless_instruction_tolerance: None,
+            stackless_only: false,
             verbose: false,
             list: false,
             named_address_values: vec![],
+            address_file: None,
+            open_metrics_output: None,
+            coverage: None,
+            lcov: false,
+            test_env: vec![],
+            dry_run: false,
+            log_json: None,
+            format: TestReportFormat::Text,
         }
     }
 
@@ -122,99 +840,535 @@ impl UnitTestingConfig {
         self
     }
 
+    /// Add the named addresses of the address file, if any, to those of the config. The
+    /// addresses given with `-a` take precedence over those of the file.
+    pub fn load_address_file(&mut self) -> anyhow::Result<()> {
+        let path = match &self.address_file {
+            None => return Ok(()),
+            Some(path) => path,
+        };
+        let mut named_addresses = shared::parse_named_address_file(path)?;
+        named_addresses.retain(|(name, _)| {
+            !self
+                .named_address_values
+                .iter()
+                .any(|(other_name, _)| other_name == name)
+        });
+        self.named_address_values.extend(named_addresses);
+        Ok(())
+    }
+
+    /// The severity from which the diagnostics found while compiling the tests fail the run. The
+    /// diagnostics below it are reported once the tests are compiled.
+    pub fn diagnostic_threshold(&self) -> Severity {
+        if self.allow_warnings {
+            Severity::NonblockingError
+        } else {
+            Severity::Warning
+        }
+    }
+
+    /// Resolve an address given on the command line, either numerical or one of the named
+    /// addresses of the config
+    fn resolve_address(&self, s: &str) -> anyhow::Result<AccountAddress> {
+        if let Some((_, address)) = self.named_address_values.iter().find(|(name, _)| name == s) {
+            return Ok(address.into_inner());
+        }
+        NumericalAddress::parse_str(s)
+            .map(NumericalAddress::into_inner)
+            .map_err(|err| anyhow::anyhow!("Invalid address '{}': {}", s, err))
+    }
+
+    /// Resolve a module given on the command line as `<address>::<module_name>`
+    fn resolve_module_id(&self, s: &str) -> anyhow::Result<ModuleId> {
+        let (address, name) = s.split_once("::").ok_or_else(|| {
+            anyhow::anyhow!("Invalid module '{}', expected <address>::<module_name>", s)
+        })?;
+        Ok(ModuleId::new(
+            self.resolve_address(address)?,
+            Identifier::new(name)?,
+        ))
+    }
+
     fn compile_to_test_plan(
         &self,
         source_files: Vec<String>,
         deps: Vec<String>,
-    ) -> Option<TestPlan> {
+    ) -> std::result::Result<Option<TestPlan>, (FilesSourceText, Diagnostics)> {
         let addresses =
-            verify_and_create_named_address_mapping(self.named_address_values.clone()).ok()?;
+            match verify_and_create_named_address_mapping(self.named_address_values.clone()) {
+                Ok(addresses) => addresses,
+                Err(_) => return Ok(None),
+            };
         let (files, comments_and_compiler_res) = Compiler::new(
             vec![(source_files, addresses.clone())],
             vec![(deps, addresses)],
         )
-        .set_flags(Flags::testing())
+        .set_flags(self.compilation_flags())
+        // The interfaces of compiled dependencies are generated outside of the package
+        .set_interface_files_dir(
+            std::env::temp_dir()
+                .join("move-unit-test")
+                .to_string_lossy()
+                .into_owned(),
+        )
         .run::<PASS_CFGIR>()
         .unwrap();
-        let (_, compiler) =
-            diagnostics::unwrap_or_report_diagnostics(&files, comments_and_compiler_res);
+        let (_, compiler) = match comments_and_compiler_res {
+            Ok(res) => res,
+            Err(diags) => return Err((files, diags)),
+        };
 
         let (mut compiler, cfgir) = compiler.into_ast();
         let compilation_env = compiler.compilation_env();
         let test_plan = unit_test::plan_builder::construct_test_plan(compilation_env, &cfgir);
 
-        if let Err(diags) = compilation_env.check_diags_at_or_above_severity(Severity::Warning) {
-            diagnostics::report_diagnostics(&files, diags);
+        if let Err(diags) =
+            compilation_env.check_diags_at_or_above_severity(self.diagnostic_threshold())
+        {
+            return Err((files, diags));
         }
 
-        let compilation_result = compiler.at_cfgir(cfgir).build();
-
-        let (units, warnings) =
-            diagnostics::unwrap_or_report_diagnostics(&files, compilation_result);
-        diagnostics::report_warnings(&files, warnings);
-        test_plan.map(|tests| TestPlan::new(tests, files, units))
+        let (units, warnings) = match compiler.at_cfgir(cfgir).build() {
+            Ok(res) => res,
+            Err(diags) => return Err((files, diags)),
+        };
+        if !warnings.is_empty() {
+            report_compiler_diagnostics(&files, warnings);
+        }
+        Ok(test_plan.map(|tests| TestPlan::new(tests, files, units)))
     }
 
-    /// Build a test plan from a unit test config
+    /// Build a test plan from a unit test config, or reuse the cached one if asked to. Exits the
+    /// process after reporting them if the sources fail to compile.
     pub fn build_test_plan(&self) -> Option<TestPlan> {
+        self.try_build_test_plan()
+            .unwrap_or_else(|(files, diags)| diagnostics::report_diagnostics(&files, diags))
+    }
+
+    /// Build a test plan like `build_test_plan`, but return the diagnostics of the compiler if the
+    /// sources fail to compile, along with the sources they refer to.
+    pub fn try_build_test_plan(
+        &self,
+    ) -> std::result::Result<Option<TestPlan>, (FilesSourceText, Diagnostics)> {
+        if !self.cached {
+            return self.compile_test_plan();
+        }
+        let cache_dir = match &self.cache_dir {
+            Some(cache_dir) => PathBuf::from(cache_dir),
+            None => default_cache_dir(),
+        };
+        // The cache only saves compiling the tests, so they are compiled if it is unusable
+        let cache_path = match test_plan_cache_path(
+            &cache_dir,
+            &self.source_files,
+            &self.dep_files,
+            &self.named_address_values,
+            &self.compilation_flags(),
+            self.allow_warnings,
+            self.include_dep_tests,
+        ) {
+            Ok(cache_path) => cache_path,
+            Err(err) => {
+                report_diagnostic(
+                    Diagnostic::warning()
+                        .with_message(format!("Unable to look up the cached test plan: {}", err)),
+                );
+                return self.compile_test_plan();
+            }
+        };
+        if let Some(test_plan) = read_cached_test_plan(&cache_path) {
+            return Ok(Some(test_plan));
+        }
+        let test_plan = match self.compile_test_plan()? {
+            Some(test_plan) => test_plan,
+            None => return Ok(None),
+        };
+        if let Err(err) = write_cached_test_plan(&cache_path, &test_plan) {
+            report_diagnostic(Diagnostic::warning().with_message(format!(
+                "Unable to cache the test plan in {}: {}",
+                cache_path.display(),
+                err
+            )))
+        }
+        Ok(Some(test_plan))
+    }
+
+    /// The flags the tests are compiled with
+    fn compilation_flags(&self) -> Flags {
+        Flags::testing()
+    }
+
+    /// Compile the sources and dependencies of a unit test config into a test plan
+    fn compile_test_plan(
+        &self,
+    ) -> std::result::Result<Option<TestPlan>, (FilesSourceText, Diagnostics)> {
         let deps = self.dep_files.clone();
+        // The compiler reads the interfaces of compiled dependencies, while their modules are
+        // added to the plan as they are
+        let bytecode_deps = match find_bytecode_deps(&deps) {
+            Ok(bytecode_deps) => bytecode_deps,
+            Err(err) => {
+                report_diagnostic(
+                    Diagnostic::error()
+                        .with_message(format!("Unable to find the compiled dependencies: {}", err)),
+                );
+                return Ok(None);
+            }
+        };
+        // Loaded before compiling, which fails on invalid modules when generating their interfaces
+        let bytecode_modules = match load_bytecode_deps(&bytecode_deps) {
+            Ok(bytecode_modules) => bytecode_modules,
+            Err(err) => {
+                report_diagnostic(Diagnostic::error().with_message(err.to_string()));
+                return Ok(None);
+            }
+        };
+        let source_deps = deps
+            .iter()
+            .filter(|dep| !is_bytecode_file(dep))
+            .cloned()
+            .collect();
 
         let TestPlan {
-            files, module_info, ..
-        } = self.compile_to_test_plan(deps.clone(), vec![])?;
+            files,
+            module_tests: dep_module_tests,
+            module_info,
+        } = match self.compile_to_test_plan(source_deps, bytecode_deps.clone())? {
+            Some(test_plan) => test_plan,
+            None => return Ok(None),
+        };
 
-        let mut test_plan = self.compile_to_test_plan(self.source_files.clone(), deps)?;
+        let mut test_plan = match self.compile_to_test_plan(self.source_files.clone(), deps)? {
+            Some(test_plan) => test_plan,
+            None => return Ok(None),
+        };
+        // The tests of the dependencies are only kept when compiling them as sources
+        if self.include_dep_tests {
+            test_plan.module_tests.extend(dep_module_tests);
+        }
         test_plan.module_info.extend(module_info.into_iter());
+        test_plan.module_info.extend(bytecode_modules);
         test_plan.files.extend(files.into_iter());
-        Some(test_plan)
+        Ok(Some(test_plan))
     }
 
-    /// Public entry point to Move unit testing as a library
-    /// Returns `true` if all unit tests passed. Otherwise, returns `false`.
-    pub fn run_and_report_unit_tests<W: Write + Send>(
+    /// Set up a runner for the tests of the plan, as configured. Fails if an input of the config,
+    /// like a file or an address it refers to, is invalid.
+    fn test_runner(
         &self,
-        test_plan: TestPlan,
+        mut test_plan: TestPlan,
         native_function_table: Option<NativeFunctionTable>,
-        writer: W,
-    ) -> Result<(W, bool)> {
-        let shared_writer = Mutex::new(writer);
-
-        if self.list {
-            for (module_id, test_plan) in &test_plan.module_tests {
-                for test_name in test_plan.tests.keys() {
-                    writeln!(
-                        shared_writer.lock().unwrap(),
-                        "{}::{}: test",
-                        format_module_id(module_id),
-                        test_name
-                    )?;
+        native_extensions: Option<NativeExtensionsFactory>,
+    ) -> anyhow::Result<TestRunner> {
+        expand_data_driven_tests(&mut test_plan)?;
+        for module_test_plan in test_plan.module_tests.values_mut() {
+            for test_case in module_test_plan.tests.values_mut() {
+                for (key, value) in &self.test_env {
+                    test_case
+                        .env
+                        .insert(key.as_bytes().to_vec(), value.as_bytes().to_vec());
                 }
             }
-            return Ok((shared_writer.into_inner().unwrap(), true));
         }
 
-        writeln!(shared_writer.lock().unwrap(), "Running Move unit tests")?;
         let mut test_runner = TestRunner::new(
             self.instruction_execution_bound,
             self.num_threads,
             self.check_stackless_vm,
+            self.stackless_instruction_tolerance
+                .map(|tolerance| tolerance.unwrap_or(DEFAULT_STACKLESS_INSTRUCTION_TOLERANCE)),
             self.verbose,
             self.report_storage_on_error,
+            self.report_full_storage_on_error,
             test_plan,
             native_function_table,
-            verify_and_create_named_address_mapping(self.named_address_values.clone()).unwrap(),
-        )
-        .unwrap();
+            verify_and_create_named_address_mapping(self.named_address_values.clone())?,
+        )?;
 
-        if let Some(filter_str) = &self.filter {
-            test_runner.filter(filter_str)
+        if let Some(path) = &self.genesis {
+            test_runner.set_genesis(load_genesis(path)?)
         }
+        if self.check_invariants {
+            test_runner.set_invariant_checks()
+        }
+        if self.stackless_only {
+            test_runner.set_stackless_only()
+        }
+        if self.nocapture {
+            test_runner.set_nocapture()
+        }
+        if self.prove {
+            test_runner.set_prover_check(
+                self.source_files.clone(),
+                self.dep_files
+                    .iter()
+                    .filter(|dep| !is_bytecode_file(dep))
+                    .cloned()
+                    .collect(),
+            )
+        }
+        if let Some(factory) = native_extensions {
+            test_runner.set_native_extensions(factory)
+        }
+        if self.fail_fast {
+            test_runner.set_fail_fast()
+        }
+        test_runner.set_retries(self.retries);
+        if let Some(schedule) = &self.gas_schedule {
+            let cost_table = load_gas_schedule(schedule)?;
+            let gas_limit = self
+                .gas_limit
+                .unwrap_or_else(|| cost_table.gas_constants.maximum_number_of_gas_units.get());
+            test_runner.set_gas_schedule(cost_table, gas_limit)
+        }
+        let vm_config = VMConfig::default();
+        test_runner.set_vm_config(VMConfig {
+            max_call_depth: self.max_call_depth.unwrap_or(vm_config.max_call_depth),
+            max_value_depth: self.max_value_depth.unwrap_or(vm_config.max_value_depth),
+            max_vector_length: self.max_vector_length.or(vm_config.max_vector_length),
+        });
+        if self.isolate {
+            test_runner.set_isolation()
+        }
+        if let Some(seed) = self.shuffle {
+            test_runner.set_shuffle_seed(seed.unwrap_or_else(|| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+            }))
+        }
+        test_runner.set_random_seed(self.seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+        }));
+        if let Some(timeout) = self.timeout {
+            test_runner.set_timeout(Duration::from_secs(timeout))
+        }
+        if let Some(dir) = &self.state_output_dir {
+            test_runner.set_state_output_dir(dir.clone())
+        }
+        if let Some(dir) = &self.snapshot_dir {
+            test_runner.set_snapshot_dir(
+                dir.clone(),
+                self.update_baseline || read_env_update_baseline(),
+            )
+        }
+        if !self.modules.is_empty() || !self.module_addresses.is_empty() {
+            let modules = self
+                .modules
+                .iter()
+                .map(|module| self.resolve_module_id(module))
+                .collect::<anyhow::Result<BTreeSet<_>>>()?;
+            let addresses = self
+                .module_addresses
+                .iter()
+                .map(|address| self.resolve_address(address))
+                .collect::<anyhow::Result<BTreeSet<_>>>()?;
+            test_runner.select_modules(&modules, &addresses)
+        }
+        if !self.filter.is_empty() || !self.exclude.is_empty() {
+            test_runner.filter(
+                &compile_filters(&self.filter)?,
+                &compile_filters(&self.exclude)?,
+            )
+        }
+        if !self.include_tags.is_empty() || !self.exclude_tags.is_empty() {
+            test_runner.filter_tags(&self.include_tags, &self.exclude_tags)
+        }
+        if let Some((index, count)) = self.shard {
+            test_runner.shard(index, count)
+        }
+        if let (true, Some(path)) = (self.rerun_failed, &self.failed_tests_file) {
+            if let Some(failed_tests) = read_failed_tests(path)? {
+                test_runner.select(&failed_tests)
+            }
+        }
+        Ok(test_runner)
+    }
 
-        let test_results = test_runner.run(&shared_writer).unwrap();
-        if self.report_statistics {
-            test_results.report_statistics(&shared_writer)?;
+    /// Non-blocking entry point to Move unit testing as a library. The tests are run on a
+    /// background thread, which reports the events of the run over the channel of the returned
+    /// handle. The listing, dry run, and report options of the config do not apply.
+    pub fn spawn_unit_tests(
+        &self,
+        test_plan: TestPlan,
+        native_function_table: Option<NativeFunctionTable>,
+        native_extensions: Option<NativeExtensionsFactory>,
+    ) -> anyhow::Result<UnitTestRun> {
+        let mut test_runner =
+            self.test_runner(test_plan, native_function_table, native_extensions)?;
+        let cancelled = Arc::new(AtomicBool::new(false));
+        test_runner.set_cancellation_flag(cancelled.clone());
+        let (sender, events) = mpsc::channel();
+        let handle = thread::spawn(move || -> anyhow::Result<bool> {
+            let sender = Mutex::new(sender);
+            let reporter = EventReporter::new(|event: TestEvent| {
+                // The receiving end may have been dropped, in which case the events are discarded
+                let _ = sender.lock().unwrap().send(event);
+                Ok(())
+            });
+            let test_results = test_runner.run(&reporter)?;
+            Ok(test_results.all_tests_passed())
+        });
+        Ok(UnitTestRun {
+            events,
+            cancelled,
+            handle,
+        })
+    }
+
+    /// Entry point to Move unit testing as a library which reports nothing, and returns the
+    /// outcome of each test instead. The listing, dry run, and report options of the config do
+    /// not apply.
+    pub fn run_unit_tests(
+        &self,
+        test_plan: TestPlan,
+        native_function_table: Option<NativeFunctionTable>,
+        native_extensions: Option<NativeExtensionsFactory>,
+    ) -> anyhow::Result<TestResults> {
+        let test_runner = self.test_runner(test_plan, native_function_table, native_extensions)?;
+        test_runner.run(&SilentReporter)
+    }
+
+    /// Entry point to Move unit testing as a library with custom reporting. The progress and
+    /// the results of the run are reported through `reporter` only, and the listing, dry run,
+    /// and format options of the config do not apply.
+    /// Returns `true` if all unit tests passed. Otherwise, returns `false`.
+    pub fn run_and_report_unit_tests_with_reporter(
+        &self,
+        test_plan: TestPlan,
+        native_function_table: Option<NativeFunctionTable>,
+        native_extensions: Option<NativeExtensionsFactory>,
+        reporter: Box<dyn TestReporter + '_>,
+    ) -> anyhow::Result<bool> {
+        let trace_path = match &self.coverage {
+            Some(path) => Some(start_coverage_trace(path)?),
+            None => None,
+        };
+        let test_runner = self.test_runner(test_plan, native_function_table, native_extensions)?;
+        let test_results = test_runner.run(reporter.as_ref())?;
+        if let (Some(path), Some(trace_path)) = (&self.coverage, &trace_path) {
+            write_coverage(path, trace_path, self.lcov, test_results.test_plan())?;
+        }
+        if let (true, Some(path)) = (self.report_statistics, &self.statistics_output) {
+            test_results
+                .write_statistics(&Mutex::new(File::create(path)?), self.statistics_format)?;
         }
-        let all_tests_passed = test_results.summarize(&shared_writer)?;
+        if let Some(path) = &self.open_metrics_output {
+            test_results.report_open_metrics(&mut File::create(path)?)?;
+        }
+        if let Some(path) = &self.failed_tests_file {
+            write_failed_tests(path, &test_results)?;
+        }
+        Ok(test_results.all_tests_passed())
+    }
+
+    /// Public entry point to Move unit testing as a library. The natives of each test session
+    /// are given the extensions created by `native_extensions`, if any.
+    /// Returns `true` if all unit tests passed. Otherwise, returns `false`.
+    pub fn run_and_report_unit_tests<W: Write + Send>(
+        &self,
+        mut test_plan: TestPlan,
+        native_function_table: Option<NativeFunctionTable>,
+        native_extensions: Option<NativeExtensionsFactory>,
+        writer: W,
+    ) -> Result<(W, bool)> {
+        let shared_writer = Mutex::new(writer);
+
+        // Spans go to the JSON log if requested, and otherwise to the subscriber of the caller
+        let _dispatch_guard = match &self.log_json {
+            Some(path) => {
+                let subscriber = tracing_subscriber::fmt()
+                    .json()
+                    .with_span_list(true)
+                    .with_span_events(FmtSpan::CLOSE)
+                    .with_writer(Mutex::new(File::create(path)?))
+                    .finish();
+                Some(tracing::subscriber::set_default(subscriber))
+            }
+            _ => None,
+        };
+        let suite_span = tracing::info_span!(
+            "move_unit_tests",
+            trace_id = tracing::field::Empty,
+            parent_span_id = tracing::field::Empty,
+        );
+        if let Ok(traceparent) = std::env::var(TRACEPARENT_ENV_VAR_NAME) {
+            if let Some((trace_id, parent_id)) = parse_traceparent(&traceparent) {
+                suite_span.record("trace_id", &trace_id);
+                suite_span.record("parent_span_id", &parent_id);
+            }
+        }
+        let _suite_guard = suite_span.enter();
+
+        if self.list {
+            expand_data_driven_tests(&mut test_plan).map_err(into_io_error)?;
+            for (module_id, module_test_plan) in &test_plan.module_tests {
+                for test_name in module_test_plan.tests.keys() {
+                    if self.format == TestReportFormat::Json {
+                        ListedTest::new(&test_plan, module_id, test_name).report(&shared_writer)?;
+                    } else {
+                        writeln!(
+                            shared_writer.lock().unwrap(),
+                            "{}::{}: test",
+                            format_module_id(module_id),
+                            test_name
+                        )?;
+                    }
+                }
+            }
+            return Ok((shared_writer.into_inner().unwrap(), true));
+        }
+
+        if self.dry_run {
+            writeln!(shared_writer.lock().unwrap(), "Checking Move unit tests")?;
+        } else if self.format == TestReportFormat::Text {
+            writeln!(shared_writer.lock().unwrap(), "Running Move unit tests")?;
+        }
+        if self.dry_run {
+            let test_runner = self
+                .test_runner(test_plan, native_function_table, native_extensions)
+                .map_err(into_io_error)?;
+            let all_checks_passed = test_runner.dry_run(&shared_writer).map_err(into_io_error)?;
+            return Ok((shared_writer.into_inner().unwrap(), all_checks_passed));
+        }
+
+        let reporter: Box<dyn TestReporter + '_> = match self.format {
+            TestReportFormat::Text => Box::new(TextReporter::new(
+                &shared_writer,
+                // Statistics written to a file are not reported with the results
+                if self.report_statistics && self.statistics_output.is_none() {
+                    Some(self.statistics_format)
+                } else {
+                    None
+                },
+                self.report_slow_tests,
+                self.report_function_histogram,
+                if self.progress {
+                    TextProgress::Bar
+                } else if self.quiet {
+                    TextProgress::Quiet
+                } else {
+                    TextProgress::Verbose
+                },
+            )),
+            // The report must be the only output, so the progress of the run is not written
+            TestReportFormat::Junit => Box::new(JunitReporter::new(&shared_writer)),
+            // The events are the only output, ending with the counts of passed and failed tests
+            TestReportFormat::Json => Box::new(EventReporter::new(|event: TestEvent| {
+                event.report(&shared_writer)
+            })),
+        };
+        let all_tests_passed = self
+            .run_and_report_unit_tests_with_reporter(
+                test_plan,
+                native_function_table,
+                native_extensions,
+                reporter,
+            )
+            .map_err(into_io_error)?;
 
         let writer = shared_writer.into_inner().unwrap();
         Ok((writer, all_tests_passed))
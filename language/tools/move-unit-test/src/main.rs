@@ -1,15 +1,21 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use move_unit_test::UnitTestingConfig;
+use move_unit_test::{cargo_runner::watch_and_run_tests, UnitTestingConfig};
 use structopt::*;
 
-pub fn main() {
-    let args = UnitTestingConfig::from_args();
+pub fn main() -> anyhow::Result<()> {
+    move_unit_test::run_isolated_test_process(None, None);
+    let mut args = UnitTestingConfig::from_args();
+    args.load_address_file()?;
+    if args.watch {
+        let files = (args.source_files.clone(), args.dep_files.clone());
+        watch_and_run_tests(args, || files.clone(), None)
+    }
 
     let test_plan = args.build_test_plan();
     if let Some(test_plan) = test_plan {
-        args.run_and_report_unit_tests(test_plan, None, std::io::stdout())
-            .unwrap();
+        args.run_and_report_unit_tests(test_plan, None, None, std::io::stdout())?;
     }
+    Ok(())
 }
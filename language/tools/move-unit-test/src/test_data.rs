@@ -0,0 +1,290 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Expands data-driven tests, declared with `#[test(data = b"<path>")]`, into a test case for
+//! each row of their data file, named after the test and the index of the row.
+//!
+//! The path of the data file is relative to the source file declaring the test. A `.json` file
+//! holds a list of rows, each a list with a value for each parameter of the test or an object
+//! mapping the name of each parameter to its value. A `.csv` file starts with a header naming the
+//! parameters, followed by a line of comma separated values for each row.
+//!
+//! Integers and booleans are given as such or as strings, addresses and signers as `0x` prefixed
+//! hex strings, and vectors as lists of values. A `vector<u8>` can also be given as a `0x`
+//! prefixed hex string, which is the only way vectors can be given in a `.csv` file.
+
+use crate::format_module_id;
+use anyhow::{anyhow, bail, Result};
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{FunctionDefinitionIndex, SignatureToken},
+};
+use move_compiler::{
+    compiled_unit::NamedCompiledModule,
+    diagnostics::FilesSourceText,
+    unit_test::{TestCase, TestPlan},
+};
+use move_core_types::{account_address::AccountAddress, value::MoveValue};
+use serde_json::Value;
+use std::{collections::BTreeMap, convert::TryFrom, fs, path::Path};
+
+/// Replace each data-driven test of `test_plan` by a test case for each row of its data file.
+pub fn expand_data_driven_tests(test_plan: &mut TestPlan) -> Result<()> {
+    let TestPlan {
+        files,
+        module_tests,
+        module_info,
+    } = test_plan;
+    for (module_id, module_test_plan) in module_tests.iter_mut() {
+        if module_test_plan
+            .tests
+            .values()
+            .all(|test_case| test_case.data_file.is_none())
+        {
+            continue;
+        }
+        let module = module_info.get(module_id).ok_or_else(|| {
+            anyhow!(
+                "Unable to find the compiled module {}",
+                format_module_id(module_id)
+            )
+        })?;
+        let mut tests = BTreeMap::new();
+        for (test_name, test_case) in std::mem::take(&mut module_test_plan.tests) {
+            let data_file = match test_case.data_file.clone() {
+                None => {
+                    tests.insert(test_name, test_case);
+                    continue;
+                }
+                Some(data_file) => data_file,
+            };
+            let rows = read_test_data(files, module, &test_case, &data_file).map_err(|err| {
+                anyhow!(
+                    "Invalid data for test {}::{}: {}",
+                    format_module_id(module_id),
+                    test_name,
+                    err
+                )
+            })?;
+            for (idx, arguments) in rows.into_iter().enumerate() {
+                let case = TestCase {
+                    arguments,
+                    data_file: None,
+                    ..test_case.clone()
+                };
+                tests.insert(format!("{}[{}]", test_name, idx), case);
+            }
+        }
+        module_test_plan.tests = tests;
+    }
+    Ok(())
+}
+
+/// The arguments of each row of the data file of `test_case`, a test of `module`
+fn read_test_data(
+    files: &FilesSourceText,
+    module: &NamedCompiledModule,
+    test_case: &TestCase,
+    data_file: &str,
+) -> Result<Vec<Vec<MoveValue>>> {
+    let compiled = &module.module;
+    let fdef_idx = compiled
+        .function_defs()
+        .iter()
+        .position(|fdef| {
+            compiled
+                .identifier_at(compiled.function_handle_at(fdef.function).name)
+                .as_str()
+                == test_case.test_name
+        })
+        .ok_or_else(|| anyhow!("unable to find function {}", test_case.test_name))?;
+    let handle = compiled.function_handle_at(compiled.function_defs()[fdef_idx].function);
+    let types = &compiled.signature_at(handle.parameters).0;
+    let function_source_map = module
+        .source_map
+        .get_function_source_map(FunctionDefinitionIndex::new(fdef_idx as u16))
+        .map_err(|_| anyhow!("unable to find the source of {}", test_case.test_name))?;
+    let names = function_source_map
+        .parameters
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>();
+
+    let file_hash = function_source_map.definition_location.file_hash();
+    let source_path = files
+        .get(&file_hash)
+        .map(|(file_name, _)| file_name.as_str())
+        .ok_or_else(|| anyhow!("unable to find the source file of {}", test_case.test_name))?;
+    let path = Path::new(source_path)
+        .parent()
+        .unwrap_or_else(|| Path::new(""))
+        .join(data_file);
+    let contents = fs::read_to_string(&path)
+        .map_err(|err| anyhow!("unable to read {}: {}", path.display(), err))?;
+    let rows = if data_file.ends_with(".csv") {
+        csv_rows(&contents, &names)?
+    } else {
+        json_rows(&contents, &names)?
+    };
+    if rows.is_empty() {
+        bail!("{} has no rows", path.display())
+    }
+
+    rows.iter()
+        .enumerate()
+        .map(|(idx, row)| {
+            row.iter()
+                .zip(types)
+                .zip(&names)
+                .map(|((value, ty), name)| {
+                    decode_value(value, ty).map_err(|err| {
+                        anyhow!(
+                            "row {}: invalid value for parameter '{}': {}",
+                            idx,
+                            name,
+                            err
+                        )
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The rows of a JSON data file, with the values of each row in the order of `names`
+fn json_rows(contents: &str, names: &[&str]) -> Result<Vec<Vec<Value>>> {
+    let rows = match serde_json::from_str(contents)? {
+        Value::Array(rows) => rows,
+        _ => bail!("expected a list of rows"),
+    };
+    rows.into_iter()
+        .enumerate()
+        .map(|(idx, row)| match row {
+            Value::Array(values) if values.len() == names.len() => Ok(values),
+            Value::Array(values) => bail!(
+                "row {}: expected {} value(s), one for each parameter of the test, but found {}",
+                idx,
+                names.len(),
+                values.len()
+            ),
+            Value::Object(mut fields) => {
+                let values = names
+                    .iter()
+                    .map(|name| {
+                        fields.remove(*name).ok_or_else(|| {
+                            anyhow!("row {}: missing a value for parameter '{}'", idx, name)
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                if let Some(name) = fields.keys().next() {
+                    bail!("row {}: unknown parameter '{}'", idx, name)
+                }
+                Ok(values)
+            }
+            _ => bail!("row {}: expected a list of values or an object", idx),
+        })
+        .collect()
+}
+
+/// The rows of a CSV data file, with the values of each row in the order of `names`
+fn csv_rows(contents: &str, names: &[&str]) -> Result<Vec<Vec<Value>>> {
+    let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+    let header = match lines.next() {
+        Some(header) => header.split(',').map(str::trim).collect::<Vec<_>>(),
+        None => bail!("expected a header naming the parameters of the test"),
+    };
+    // The column of each parameter
+    let columns = names
+        .iter()
+        .map(|name| {
+            header
+                .iter()
+                .position(|column| column == name)
+                .ok_or_else(|| anyhow!("missing a column for parameter '{}'", name))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if let Some(column) = header.iter().find(|column| !names.contains(column)) {
+        bail!("unknown parameter '{}'", column)
+    }
+    lines
+        .enumerate()
+        .map(|(idx, line)| {
+            let fields = line.split(',').map(str::trim).collect::<Vec<_>>();
+            if fields.len() != header.len() {
+                bail!(
+                    "row {}: expected {} value(s) but found {}",
+                    idx,
+                    header.len(),
+                    fields.len()
+                )
+            }
+            Ok(columns
+                .iter()
+                .map(|column| Value::String(fields[*column].to_string()))
+                .collect())
+        })
+        .collect()
+}
+
+/// Decode `value` as a Move value of type `ty`
+fn decode_value(value: &Value, ty: &SignatureToken) -> Result<MoveValue> {
+    Ok(match (ty, value) {
+        (SignatureToken::Bool, Value::Bool(b)) => MoveValue::Bool(*b),
+        (SignatureToken::Bool, Value::String(s)) => MoveValue::Bool(s.parse()?),
+        (SignatureToken::U8, _) => MoveValue::U8(decode_integer(value)?),
+        (SignatureToken::U64, _) => MoveValue::U64(decode_integer(value)?),
+        (SignatureToken::U128, _) => MoveValue::U128(decode_integer(value)?),
+        // As for the other arguments of tests, signers are passed as their address
+        (SignatureToken::Address, Value::String(s))
+        | (SignatureToken::Signer, Value::String(s)) => {
+            MoveValue::Address(AccountAddress::from_hex_literal(s)?)
+        }
+        (SignatureToken::Vector(elem_ty), Value::String(s)) if **elem_ty == SignatureToken::U8 => {
+            MoveValue::vector_u8(decode_hex(s)?)
+        }
+        (SignatureToken::Vector(elem_ty), Value::Array(values)) => MoveValue::Vector(
+            values
+                .iter()
+                .map(|value| decode_value(value, elem_ty))
+                .collect::<Result<_>>()?,
+        ),
+        (
+            SignatureToken::Bool
+            | SignatureToken::Address
+            | SignatureToken::Signer
+            | SignatureToken::Vector(_),
+            _,
+        ) => bail!("unexpected value {}", value),
+        _ => bail!("values of this type cannot be read from a data file"),
+    })
+}
+
+/// Decode an integer given as a JSON number or as a string
+fn decode_integer<T: TryFrom<u128>>(value: &Value) -> Result<T> {
+    let n = match value {
+        Value::Number(n) => n
+            .as_u64()
+            .map(u128::from)
+            .ok_or_else(|| anyhow!("{} is not an unsigned integer", n))?,
+        Value::String(s) => s.parse::<u128>()?,
+        _ => bail!("unexpected value {}", value),
+    };
+    T::try_from(n).map_err(|_| anyhow!("{} is out of range", n))
+}
+
+/// Decode a `0x` prefixed hex string into bytes
+pub(crate) fn decode_hex(s: &str) -> Result<Vec<u8>> {
+    let digits = match s.strip_prefix("0x") {
+        Some(digits) if digits.len() % 2 == 0 => digits,
+        _ => bail!("expected a 0x prefixed hex string with an even number of digits"),
+    };
+    (0..digits.len())
+        .step_by(2)
+        .map(|idx| {
+            digits
+                .get(idx..idx + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(|| anyhow!("invalid hex string {}", s))
+        })
+        .collect()
+}
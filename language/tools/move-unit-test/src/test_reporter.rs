@@ -3,25 +3,558 @@
 
 use crate::format_module_id;
 use colored::{control, Colorize};
-use move_binary_format::errors::{Location, VMError, VMResult};
+use move_binary_format::{
+    access::ModuleAccess,
+    errors::{Location, VMError, VMResult},
+    file_format::FunctionDefinitionIndex,
+};
+use move_command_line_common::files::FileHash;
 use move_compiler::{
-    diagnostics::{self, Diagnostic},
+    diagnostics::{self, Diagnostic, Diagnostics},
     unit_test::{ModuleTestPlan, TestPlan},
 };
 use move_core_types::{effects::ChangeSet, language_storage::ModuleId};
+use move_coverage::{coverage_map::CoverageMap, summary::summarize_inst_cov};
+use serde::{Deserialize, Serialize};
 use std::{
+    cmp::Reverse,
     collections::{BTreeMap, BTreeSet},
+    fmt,
     io::{Result, Write},
-    sync::Mutex,
+    path::Path,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
     time::Duration,
 };
 
+/// The environment variable the Move VM reads the path of its execution trace from.
+pub(crate) const MOVE_VM_TRACE_ENV_VAR_NAME: &str = "MOVE_VM_TRACE";
+
+/// The format the results of a test run are reported in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestReportFormat {
+    // Human readable output, listing each test as it completes
+    Text,
+    // A JUnit XML report, with a test suite per module
+    Junit,
+    // A stream of JSON lines, one for each event of the test run
+    Json,
+}
+
+impl FromStr for TestReportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "text" => Ok(TestReportFormat::Text),
+            "junit" => Ok(TestReportFormat::Junit),
+            "json" => Ok(TestReportFormat::Json),
+            _ => anyhow::bail!(
+                "Invalid report format '{}', expected text, junit or json",
+                s
+            ),
+        }
+    }
+}
+
+/// The format the statistics of a test run are reported in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatisticsFormat {
+    // A table meant to be read in a terminal
+    Table,
+    // A header, followed by a line of comma separated values for each test
+    Csv,
+    // A JSON list, with an object for each test
+    Json,
+}
+
+impl FromStr for StatisticsFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "table" => Ok(StatisticsFormat::Table),
+            "csv" => Ok(StatisticsFormat::Csv),
+            "json" => Ok(StatisticsFormat::Json),
+            _ => anyhow::bail!(
+                "Invalid statistics format '{}', expected table, csv or json",
+                s
+            ),
+        }
+    }
+}
+
+/// The statistics of a test, as reported in CSV or JSON
+#[derive(Debug, Serialize)]
+struct TestStatisticsRow {
+    name: String,
+    outcome: &'static str,
+    elapsed_secs: f64,
+    wall_clock_secs: f64,
+    instructions_executed: u64,
+    gas_used: Option<u64>,
+    attempts: usize,
+    max_stack_depth: usize,
+    values_allocated: u64,
+    storage_bytes: u64,
+}
+
+impl TestStatisticsRow {
+    fn new(module_id: &ModuleId, run_info: &TestRunInfo, outcome: &'static str) -> Self {
+        Self {
+            name: format!(
+                "{}::{}",
+                format_module_id(module_id),
+                run_info.function_ident
+            ),
+            outcome,
+            elapsed_secs: run_info.elapsed_time.as_secs_f64(),
+            wall_clock_secs: run_info.wall_clock_time.as_secs_f64(),
+            instructions_executed: run_info.instructions_executed,
+            gas_used: run_info.gas_used,
+            attempts: run_info.attempts,
+            max_stack_depth: run_info.max_stack_depth,
+            values_allocated: run_info.values_allocated,
+            storage_bytes: run_info.storage_bytes,
+        }
+    }
+
+    const CSV_HEADER: &str = "name,outcome,elapsed_secs,wall_clock_secs,\
+        instructions_executed,gas_used,attempts,max_stack_depth,values_allocated,storage_bytes";
+
+    /// The values of this row, in the order of `CSV_HEADER`. The gas used is left empty if the
+    /// test was not metered.
+    fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{},{}",
+            self.name,
+            self.outcome,
+            self.elapsed_secs,
+            self.wall_clock_secs,
+            self.instructions_executed,
+            self.gas_used
+                .map_or_else(String::new, |gas| gas.to_string()),
+            self.attempts,
+            self.max_stack_depth,
+            self.values_allocated,
+            self.storage_bytes,
+        )
+    }
+}
+
+/// An event of a test run. Events are reported as JSON lines, tagged with the name of the event,
+/// so that tools can follow the progress of a run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TestEvent {
+    SuiteStarted {
+        test_count: usize,
+    },
+    Started {
+        name: String,
+    },
+    Passed {
+        name: String,
+        // In seconds
+        exec_time: f64,
+    },
+    Failed {
+        name: String,
+        exec_time: f64,
+        kind: &'static str,
+        message: String,
+    },
+    TimedOut {
+        name: String,
+        exec_time: f64,
+    },
+    SuiteFinished {
+        passed: usize,
+        failed: usize,
+    },
+    Warning {
+        message: String,
+    },
+}
+
+impl TestEvent {
+    /// Write the event as a line of JSON
+    pub fn report<W: Write>(&self, writer: &Mutex<W>) -> Result<()> {
+        let line = serde_json::to_string(self).expect("test events are serializable");
+        writeln!(writer.lock().unwrap(), "{}", line)
+    }
+}
+
+/// A test of a test plan, as listed by `--list` in the JSON format. Each test is reported as a
+/// line of JSON, with the location of its declaration when the source of its module is known.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ListedTest {
+    pub module: String,
+    pub name: String,
+    pub file: Option<String>,
+    pub line: Option<usize>,
+}
+
+impl ListedTest {
+    pub fn new(test_plan: &TestPlan, module_id: &ModuleId, test_name: &str) -> Self {
+        let position = test_declaration_position(test_plan, module_id, test_name);
+        Self {
+            module: format_module_id(module_id),
+            name: test_name.to_string(),
+            file: position.map(|(file_name, _, _)| file_name.to_string()),
+            line: position.map(|(_, line, _)| line),
+        }
+    }
+
+    /// Write the listed test as a line of JSON
+    pub fn report<W: Write>(&self, writer: &Mutex<W>) -> Result<()> {
+        let line = serde_json::to_string(self).expect("listed tests are serializable");
+        writeln!(writer.lock().unwrap(), "{}", line)
+    }
+}
+
+/// The file, line and column of the name of the function of the test `test_name` in `module_id`
+fn test_declaration_position<'a>(
+    test_plan: &'a TestPlan,
+    module_id: &ModuleId,
+    test_name: &str,
+) -> Option<(&'a str, usize, usize)> {
+    // The cases of a parameterized test are declared by the same function
+    let function_name = &test_plan
+        .module_tests
+        .get(module_id)?
+        .tests
+        .get(test_name)?
+        .test_name;
+    let compiled = test_plan.module_info.get(module_id)?;
+    let module = &compiled.module;
+    let fdef_idx = module.function_defs().iter().position(|fdef| {
+        module
+            .identifier_at(module.function_handle_at(fdef.function).name)
+            .as_str()
+            == function_name.as_str()
+    })?;
+    let loc = compiled
+        .source_map
+        .get_function_source_map(FunctionDefinitionIndex::new(fdef_idx as u16))
+        .ok()?
+        .definition_location;
+    source_position(test_plan, &loc.file_hash(), loc.start())
+}
+
+/// The file, line and column of the byte `offset` in the source file with hash `file_hash`
+fn source_position<'a>(
+    test_plan: &'a TestPlan,
+    file_hash: &FileHash,
+    offset: u32,
+) -> Option<(&'a str, usize, usize)> {
+    let (file_name, source) = test_plan.files.get(file_hash)?;
+    let before = source.get(..offset as usize)?;
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map(|i| i + 1).unwrap_or(0) + 1;
+    Some((file_name.as_str(), line, column))
+}
+
+/// The outcome of a test, as reported to `TestReporter::test_finished`
+#[derive(Debug, Clone, Copy)]
+pub enum TestOutcome<'a> {
+    Passed(&'a TestRunInfo),
+    // Includes timeouts
+    Failed(&'a TestFailure),
+}
+
+/// Hooks through which the progress and the results of a test run are reported. The hooks are
+/// called from the threads running the tests, so reporters which keep state need to
+/// synchronize access to it.
+pub trait TestReporter: Sync {
+    /// Called before any test is run, with the number of tests to run
+    fn suite_started(&self, _test_count: usize) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called before the test `function_name` of the module `module_id` is run
+    fn test_started(&self, _module_id: &ModuleId, _function_name: &str) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called once a test of the module `module_id` completes
+    fn test_finished(
+        &self,
+        _test_plan: &TestPlan,
+        _module_id: &ModuleId,
+        _outcome: TestOutcome,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called once all tests completed
+    fn summary(&self, _results: &TestResults) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called when an output of a test, like its snapshot, cannot be written, which does not
+    /// fail the test. Called before the test is reported as finished.
+    fn warning(&self, _message: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// How the text reporter shows the progress of a test run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextProgress {
+    // Each test as it completes
+    Verbose,
+    // Only the tests which fail
+    Quiet,
+    // The tests which fail, under a line with the number of completed tests and the module of the
+    // last test started, which is redrawn as the tests run. Meant for terminals.
+    Bar,
+}
+
+/// Reports each test as it completes, and the failures once all tests completed, in a human
+/// readable form
+pub struct TextReporter<'a, W> {
+    writer: &'a Mutex<W>,
+    // The format the statistics are reported in, if they are reported with the results
+    report_statistics: Option<StatisticsFormat>,
+    report_slow_tests: Option<usize>,
+    report_function_histogram: Option<usize>,
+    progress: TextProgress,
+    num_tests: AtomicUsize,
+    num_completed_tests: AtomicUsize,
+}
+
+impl<'a, W: Write + Send> TextReporter<'a, W> {
+    pub fn new(
+        writer: &'a Mutex<W>,
+        report_statistics: Option<StatisticsFormat>,
+        report_slow_tests: Option<usize>,
+        report_function_histogram: Option<usize>,
+        progress: TextProgress,
+    ) -> Self {
+        Self {
+            writer,
+            report_statistics,
+            report_slow_tests,
+            report_function_histogram,
+            progress,
+            num_tests: AtomicUsize::new(0),
+            num_completed_tests: AtomicUsize::new(0),
+        }
+    }
+
+    /// Redraw the progress bar, which is the last line written
+    fn draw_progress_bar(&self, writer: &mut W, module_id: &ModuleId) -> Result<()> {
+        const WIDTH: usize = 30;
+        let num_tests = self.num_tests.load(Ordering::Relaxed);
+        let num_completed_tests = self.num_completed_tests.load(Ordering::Relaxed);
+        let filled = (num_completed_tests * WIDTH)
+            .checked_div(num_tests)
+            .unwrap_or(WIDTH);
+        write!(
+            writer,
+            "\r\x1b[2K[{}{}] {}/{} {}",
+            "█".repeat(filled),
+            " ".repeat(WIDTH - filled),
+            num_completed_tests,
+            num_tests,
+            format_module_id(module_id)
+        )?;
+        writer.flush()
+    }
+}
+
+impl<'a, W: Write + Send> TestReporter for TextReporter<'a, W> {
+    fn suite_started(&self, test_count: usize) -> Result<()> {
+        self.num_tests.store(test_count, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn test_started(&self, module_id: &ModuleId, _function_name: &str) -> Result<()> {
+        if self.progress == TextProgress::Bar {
+            self.draw_progress_bar(&mut self.writer.lock().unwrap(), module_id)?;
+        }
+        Ok(())
+    }
+
+    fn test_finished(
+        &self,
+        _test_plan: &TestPlan,
+        module_id: &ModuleId,
+        outcome: TestOutcome,
+    ) -> Result<()> {
+        self.num_completed_tests.fetch_add(1, Ordering::Relaxed);
+        let mut writer = self.writer.lock().unwrap();
+        if matches!(outcome, TestOutcome::Passed(_)) && self.progress != TextProgress::Verbose {
+            if self.progress == TextProgress::Bar {
+                self.draw_progress_bar(&mut writer, module_id)?;
+            }
+            return Ok(());
+        }
+        if self.progress == TextProgress::Bar {
+            // The failure is written over the progress bar, which is redrawn below it
+            write!(writer, "\r\x1b[2K")?;
+        }
+
+        let (status, function_ident) = match outcome {
+            TestOutcome::Passed(info) => (
+                "PASS".bold().bright_green().to_string() + "   ",
+                &info.function_ident,
+            ),
+            TestOutcome::Failed(TestFailure {
+                failure_reason: FailureReason::Timeout(_),
+                test_run_info,
+                ..
+            }) => (
+                "TIMEOUT".bold().bright_yellow().to_string(),
+                &test_run_info.function_ident,
+            ),
+            TestOutcome::Failed(failure) => (
+                "FAIL".bold().bright_red().to_string() + "   ",
+                &failure.test_run_info.function_ident,
+            ),
+        };
+        writeln!(
+            writer,
+            "[ {} ] {}::{}",
+            status,
+            format_module_id(module_id),
+            function_ident
+        )?;
+        if self.progress == TextProgress::Bar {
+            self.draw_progress_bar(&mut writer, module_id)?;
+        }
+        Ok(())
+    }
+
+    fn summary(&self, results: &TestResults) -> Result<()> {
+        if self.progress == TextProgress::Bar && self.num_tests.load(Ordering::Relaxed) > 0 {
+            writeln!(self.writer.lock().unwrap())?;
+        }
+        if let Some(format) = self.report_statistics {
+            results.write_statistics(self.writer, format)?;
+            if let Some(count) = self.report_function_histogram {
+                results.report_function_histogram(self.writer, count)?;
+            }
+        }
+        if let Some(count) = self.report_slow_tests {
+            results.report_slow_tests(self.writer, count)?;
+        }
+        results.summarize(self.writer)?;
+        Ok(())
+    }
+
+    fn warning(&self, message: &str) -> Result<()> {
+        let mut writer = self.writer.lock().unwrap();
+        if self.progress == TextProgress::Bar {
+            // Written over the progress bar, which is redrawn once the test is reported
+            write!(writer, "\r\x1b[2K")?;
+        }
+        writeln!(writer, "{}: {}", "warning".bold().bright_yellow(), message)
+    }
+}
+
+/// Reports the results of all tests as a JUnit XML report once all tests completed
+pub struct JunitReporter<'a, W> {
+    writer: &'a Mutex<W>,
+}
+
+impl<'a, W: Write + Send> JunitReporter<'a, W> {
+    pub fn new(writer: &'a Mutex<W>) -> Self {
+        Self { writer }
+    }
+}
+
+impl<'a, W: Write + Send> TestReporter for JunitReporter<'a, W> {
+    fn summary(&self, results: &TestResults) -> Result<()> {
+        results.report_junit(self.writer)?;
+        Ok(())
+    }
+}
+
+/// Reports nothing, for runs whose results are only inspected once all tests completed
+pub struct SilentReporter;
+
+impl TestReporter for SilentReporter {}
+
+/// Reports the progress of a test run as `TestEvent`s to a callback
+pub struct EventReporter<F> {
+    callback: F,
+}
+
+impl<F: Fn(TestEvent) -> Result<()> + Sync> EventReporter<F> {
+    pub fn new(callback: F) -> Self {
+        Self { callback }
+    }
+}
+
+impl<F: Fn(TestEvent) -> Result<()> + Sync> TestReporter for EventReporter<F> {
+    fn suite_started(&self, test_count: usize) -> Result<()> {
+        (self.callback)(TestEvent::SuiteStarted { test_count })
+    }
+
+    fn test_started(&self, module_id: &ModuleId, function_name: &str) -> Result<()> {
+        (self.callback)(TestEvent::Started {
+            name: format!("{}::{}", format_module_id(module_id), function_name),
+        })
+    }
+
+    fn test_finished(
+        &self,
+        test_plan: &TestPlan,
+        module_id: &ModuleId,
+        outcome: TestOutcome,
+    ) -> Result<()> {
+        let name = |info: &TestRunInfo| {
+            format!("{}::{}", format_module_id(module_id), info.function_ident)
+        };
+        (self.callback)(match outcome {
+            TestOutcome::Passed(info) => TestEvent::Passed {
+                name: name(info),
+                exec_time: info.elapsed_time.as_secs_f64(),
+            },
+            TestOutcome::Failed(TestFailure {
+                failure_reason: FailureReason::Timeout(_),
+                test_run_info,
+                ..
+            }) => TestEvent::TimedOut {
+                name: name(test_run_info),
+                exec_time: test_run_info.elapsed_time.as_secs_f64(),
+            },
+            TestOutcome::Failed(failure) => TestEvent::Failed {
+                name: name(&failure.test_run_info),
+                exec_time: failure.test_run_info.elapsed_time.as_secs_f64(),
+                kind: failure.kind(),
+                message: failure.summary(test_plan),
+            },
+        })
+    }
+
+    fn summary(&self, results: &TestResults) -> Result<()> {
+        (self.callback)(TestEvent::SuiteFinished {
+            passed: results.final_statistics.num_passed(),
+            failed: results.final_statistics.num_failed(),
+        })
+    }
+
+    fn warning(&self, message: &str) -> Result<()> {
+        (self.callback)(TestEvent::Warning {
+            message: message.to_string(),
+        })
+    }
+}
+
 #[derive(Debug, Clone, Ord, PartialOrd, PartialEq, Eq)]
 pub enum FailureReason {
     // Expected to abort, but it didn't
     NoAbort(String),
     // Aborted with the wrong code
     WrongAbort(String, u64, u64),
+    // Aborted with the wrong code or in the wrong location
+    WrongAbortLocation(String, u64, u64, ExpectedAbortLocation),
     // Abort wasn't expected, but it did
     Aborted(String, u64),
     // Test timed out
@@ -35,35 +568,92 @@ pub enum FailureReason {
     },
     // Property checking failed
     Property(String),
+    // The fixture of the test's group failed to execute
+    FixtureFailed(String),
+    // The events emitted by the test differ from the expected ones
+    Events(String),
+    // An invariant aborted on the storage state left by the test
+    InvariantViolated(String),
+    // The output of the test differs from its snapshot
+    SnapshotMismatch(String),
+    // The process running the test in isolation exited without reporting a result
+    Crashed(String),
     // The test failed for some unknown reason. This shouldn't be encountered
     Unknown(String),
 }
 
+/// The module, and the function if it matters, a test is expected to abort in
+#[derive(Debug, Clone, Ord, PartialOrd, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpectedAbortLocation {
+    pub module_id: ModuleId,
+    pub function: Option<String>,
+}
+
+impl fmt::Display for ExpectedAbortLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", format_module_id(&self.module_id))?;
+        if let Some(function) = &self.function {
+            write!(f, "::{}", function)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Ord, PartialOrd, PartialEq, Eq)]
 pub struct TestFailure {
     pub test_run_info: TestRunInfo,
     pub vm_error: Option<VMError>,
     pub failure_reason: FailureReason,
     pub storage_state: Option<String>,
+    /// The seed and the generated inputs of the failing run of a random test
+    pub random_inputs: Option<String>,
 }
 
-#[derive(Debug, Clone, Ord, PartialOrd, PartialEq, Eq)]
+#[derive(Debug, Clone, Ord, PartialOrd, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TestRunInfo {
     pub function_ident: String,
+    /// The time spent executing the test in the Move VM
     pub elapsed_time: Duration,
     pub instructions_executed: u64,
+    /// The gas used by the test, when it is metered with a gas schedule
+    pub gas_used: Option<u64>,
+    /// The time spent running the test, including cross checks with the stackless VM
+    pub wall_clock_time: Duration,
+    /// The number of times the test was run, which is more than one if it was retried
+    pub attempts: usize,
+    /// The largest number of values on the operand stack of the Move VM during the test
+    pub max_stack_depth: usize,
+    /// The number of values the Move VM created during the test
+    pub values_allocated: u64,
+    /// The number of bytes of the resources the test loaded from and wrote to storage
+    pub storage_bytes: u64,
+    /// The output of the `debug` natives during the test, when it is captured
+    pub output: String,
+}
+
+/// A test whose instruction count on the stackless VM diverges from the one on the Move VM
+#[derive(Debug, Clone, Ord, PartialOrd, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstructionCountMismatch {
+    pub function_ident: String,
+    pub move_vm_instructions: u64,
+    pub stackless_vm_instructions: u64,
 }
 
 #[derive(Debug, Clone)]
 pub struct TestStatistics {
     passed: BTreeMap<ModuleId, BTreeSet<TestRunInfo>>,
     failed: BTreeMap<ModuleId, BTreeSet<TestFailure>>,
+    instruction_count_mismatches: BTreeMap<ModuleId, BTreeSet<InstructionCountMismatch>>,
 }
 
 #[derive(Debug, Clone)]
 pub struct TestResults {
     final_statistics: TestStatistics,
     test_plan: TestPlan,
+    shuffle_seed: Option<u64>,
+    // The errors of the prover, if the run was checked with the prover and a spec of the tested
+    // modules cannot be verified
+    verification_failure: Option<String>,
 }
 
 impl TestRunInfo {
@@ -72,6 +662,27 @@ impl TestRunInfo {
             function_ident,
             elapsed_time,
             instructions_executed,
+            gas_used: None,
+            wall_clock_time: elapsed_time,
+            attempts: 1,
+            max_stack_depth: 0,
+            values_allocated: 0,
+            storage_bytes: 0,
+            output: String::new(),
+        }
+    }
+}
+
+impl InstructionCountMismatch {
+    pub fn new(
+        function_ident: String,
+        move_vm_instructions: u64,
+        stackless_vm_instructions: u64,
+    ) -> Self {
+        Self {
+            function_ident,
+            move_vm_instructions,
+            stackless_vm_instructions,
         }
     }
 }
@@ -89,6 +700,19 @@ impl FailureReason {
         )
     }
 
+    pub fn wrong_abort_location(
+        expected: u64,
+        received: u64,
+        location: ExpectedAbortLocation,
+    ) -> Self {
+        FailureReason::WrongAbortLocation(
+            "Test did not abort with expected code and location".to_string(),
+            expected,
+            received,
+            location,
+        )
+    }
+
     pub fn aborted(abort_code: u64) -> Self {
         FailureReason::Aborted("Test was not expected to abort".to_string(), abort_code)
     }
@@ -97,6 +721,10 @@ impl FailureReason {
         FailureReason::Timeout("Test timed out".to_string())
     }
 
+    pub fn out_of_gas() -> Self {
+        FailureReason::Timeout("Test ran out of gas".to_string())
+    }
+
     pub fn mismatch(
         move_vm_return_values: VMResult<Vec<Vec<u8>>>,
         move_vm_change_set: VMResult<ChangeSet>,
@@ -115,6 +743,36 @@ impl FailureReason {
         FailureReason::Property(details)
     }
 
+    pub fn fixture_failed(fixture_name: &str) -> Self {
+        FailureReason::FixtureFailed(format!(
+            "Fixture '{}' of this test's group failed to execute",
+            fixture_name
+        ))
+    }
+
+    pub fn events(details: String) -> Self {
+        FailureReason::Events(details)
+    }
+
+    pub fn invariant_violated(invariant: &str) -> Self {
+        FailureReason::InvariantViolated(format!(
+            "Invariant {} does not hold after this test",
+            invariant
+        ))
+    }
+
+    pub fn snapshot_mismatch(snapshot_path: &str, diff: String) -> Self {
+        FailureReason::SnapshotMismatch(format!(
+            "Output of the test differs from its snapshot {}. Run with --update-baseline to \
+            update it:\n{}",
+            snapshot_path, diff
+        ))
+    }
+
+    pub fn crashed(details: &str) -> Self {
+        FailureReason::Crashed(format!("Test process {}", details))
+    }
+
     pub fn unknown() -> Self {
         FailureReason::Unknown("ITE: An unknown error was reported.".to_string())
     }
@@ -132,6 +790,14 @@ impl TestFailure {
             vm_error,
             failure_reason,
             storage_state,
+            random_inputs: None,
+        }
+    }
+
+    pub fn with_random_inputs(self, random_inputs: String) -> Self {
+        Self {
+            random_inputs: Some(random_inputs),
+            ..self
         }
     }
 
@@ -144,7 +810,20 @@ impl TestFailure {
                     "{}. Expected test to abort with {} but instead it aborted with {} here",
                     message, expected_code, other_code,
                 );
-                Self::report_error_with_location(test_plan, base_message, &self.vm_error)
+                self.render_wrong_abort(test_plan, base_message, *expected_code, *other_code, None)
+            }
+            FailureReason::WrongAbortLocation(message, expected_code, other_code, location) => {
+                let base_message = format!(
+                    "{}. Expected test to abort with {} in {} but instead it aborted with {} here",
+                    message, expected_code, location, other_code,
+                );
+                self.render_wrong_abort(
+                    test_plan,
+                    base_message,
+                    *expected_code,
+                    *other_code,
+                    Some(location),
+                )
             }
             FailureReason::Aborted(message, code) => {
                 let base_message = format!("{} but it aborted with {} here", message, code);
@@ -169,7 +848,13 @@ impl TestFailure {
                     stackless_vm_change_set
                 )
             }
-            FailureReason::Property(message) => message.clone(),
+            FailureReason::Property(message)
+            | FailureReason::Events(message)
+            | FailureReason::SnapshotMismatch(message)
+            | FailureReason::Crashed(message) => message.clone(),
+            FailureReason::FixtureFailed(message) | FailureReason::InvariantViolated(message) => {
+                Self::report_error_with_location(test_plan, message.clone(), &self.vm_error)
+            }
             FailureReason::Unknown(message) => {
                 format!(
                     "{} Location: {}\nVMError (if there is one): {}",
@@ -186,6 +871,10 @@ impl TestFailure {
                 )
             }
         };
+        let error_string = match &self.random_inputs {
+            None => error_string,
+            Some(random_inputs) => format!("{}\n{}", random_inputs, error_string),
+        };
 
         match &self.storage_state {
             None => error_string,
@@ -203,6 +892,191 @@ impl TestFailure {
         }
     }
 
+    /// Render an abort which is not the expected one, followed by the expected and the actual
+    /// abort side by side
+    fn render_wrong_abort(
+        &self,
+        test_plan: &TestPlan,
+        base_message: String,
+        expected_code: u64,
+        actual_code: u64,
+        expected_location: Option<&ExpectedAbortLocation>,
+    ) -> String {
+        let mut error = Self::report_error_with_location(test_plan, base_message, &self.vm_error);
+        if !error.ends_with('\n') {
+            error.push('\n');
+        }
+        error.push_str(&self.render_abort_diff(
+            test_plan,
+            expected_code,
+            actual_code,
+            expected_location,
+        ));
+        error
+    }
+
+    /// Render the expected and the actual abort side by side, highlighting what differs
+    fn render_abort_diff(
+        &self,
+        test_plan: &TestPlan,
+        expected_code: u64,
+        actual_code: u64,
+        expected_location: Option<&ExpectedAbortLocation>,
+    ) -> String {
+        let actual_module = match self.vm_error.as_ref().map(|err| err.location()) {
+            Some(Location::Module(module_id)) => format_module_id(module_id),
+            _ => "-".to_string(),
+        };
+        let actual_location =
+            Self::error_location(test_plan, &self.vm_error).unwrap_or_else(|| "-".to_string());
+        // Without a location, `#[expected_failure]` only constrains the abort code
+        let expected_module =
+            expected_location.map_or_else(|| "any".to_string(), |l| format_module_id(&l.module_id));
+        let module_differs = expected_location.is_some() && expected_module != actual_module;
+        let mut rows = vec![
+            (
+                "code",
+                expected_code.to_string(),
+                actual_code.to_string(),
+                expected_code != actual_code,
+            ),
+            ("module", expected_module, actual_module, module_differs),
+        ];
+        if let Some(location) = expected_location {
+            let actual_function =
+                Self::abort_function(test_plan, &self.vm_error).unwrap_or_else(|| "-".to_string());
+            let (expected_function, function_differs) = match &location.function {
+                Some(function) => (function.clone(), function != &actual_function),
+                None => ("any".to_string(), false),
+            };
+            rows.push((
+                "function",
+                expected_function,
+                actual_function,
+                function_differs,
+            ));
+        }
+        rows.push(("location", "any".to_string(), actual_location, false));
+        let width = rows
+            .iter()
+            .map(|(_, expected, _, _)| expected.len())
+            .chain(std::iter::once("expected".len()))
+            .max()
+            .unwrap();
+
+        let colorize = control::SHOULD_COLORIZE.should_colorize();
+        let mut out = format!(
+            "{:<8} │ {:<width$} │ {}\n",
+            "",
+            "expected",
+            "actual",
+            width = width
+        );
+        for (label, expected, actual, differs) in rows.iter() {
+            let expected = format!("{:<width$}", expected, width = width);
+            let (expected, actual) = if *differs && colorize {
+                (
+                    expected.green().to_string(),
+                    actual.as_str().red().bold().to_string(),
+                )
+            } else {
+                (expected, actual.clone())
+            };
+            out.push_str(&format!("{:<8} │ {} │ {}\n", label, expected, actual));
+        }
+        out
+    }
+
+    /// A short name for the kind of this failure
+    pub fn kind(&self) -> &'static str {
+        match &self.failure_reason {
+            FailureReason::NoAbort(_) => "no_abort",
+            FailureReason::WrongAbort(..) | FailureReason::WrongAbortLocation(..) => "wrong_abort",
+            FailureReason::Aborted(..) => "aborted",
+            FailureReason::Timeout(_) => "timeout",
+            FailureReason::Mismatch { .. } => "mismatch",
+            FailureReason::Property(_) => "property",
+            FailureReason::FixtureFailed(_) => "fixture_failed",
+            FailureReason::Events(_) => "events",
+            FailureReason::InvariantViolated(_) => "invariant",
+            FailureReason::SnapshotMismatch(_) => "snapshot_mismatch",
+            FailureReason::Crashed(_) => "crashed",
+            FailureReason::Unknown(_) => "unknown",
+        }
+    }
+
+    /// A one line description of this failure, with the abort code and the location of the
+    /// abort if there is one.
+    pub fn summary(&self, test_plan: &TestPlan) -> String {
+        let message = match &self.failure_reason {
+            FailureReason::NoAbort(message)
+            | FailureReason::Timeout(message)
+            | FailureReason::FixtureFailed(message)
+            | FailureReason::Events(message)
+            | FailureReason::InvariantViolated(message)
+            | FailureReason::Unknown(message) => message.clone(),
+            FailureReason::WrongAbort(message, expected_code, other_code) => format!(
+                "{}. Expected test to abort with {} but instead it aborted with {}",
+                message, expected_code, other_code,
+            ),
+            FailureReason::WrongAbortLocation(message, expected_code, other_code, location) => {
+                format!(
+                    "{}. Expected test to abort with {} in {} but instead it aborted with {}",
+                    message, expected_code, location, other_code,
+                )
+            }
+            FailureReason::Aborted(message, code) => {
+                format!("{} but it aborted with {}", message, code)
+            }
+            FailureReason::Mismatch { .. } => {
+                "Executions via Move VM and stackless VM yield different results".to_string()
+            }
+            FailureReason::Property(message)
+            | FailureReason::SnapshotMismatch(message)
+            | FailureReason::Crashed(message) => message.lines().next().unwrap_or("").to_string(),
+        };
+        match Self::error_location(test_plan, &self.vm_error) {
+            Some(location) => format!("{} at {}", message, location),
+            None => message,
+        }
+    }
+
+    /// The name of the function `vm_error` was raised in
+    fn abort_function(test_plan: &TestPlan, vm_error: &Option<VMError>) -> Option<String> {
+        let vm_error = vm_error.as_ref()?;
+        let module_id = match vm_error.location() {
+            Location::Module(module_id) => module_id,
+            _ => return None,
+        };
+        let (fdef_idx, _) = vm_error.offsets().first()?;
+        let module = &test_plan.module_info.get(module_id)?.module;
+        let fdef = module.function_defs.get(fdef_idx.0 as usize)?;
+        Some(
+            module
+                .identifier_at(module.function_handle_at(fdef.function).name)
+                .to_string(),
+        )
+    }
+
+    /// The source location of the error, as `<file>:<line>:<column>`
+    fn error_location(test_plan: &TestPlan, vm_error: &Option<VMError>) -> Option<String> {
+        let vm_error = vm_error.as_ref()?;
+        let module_id = match vm_error.location() {
+            Location::Module(module_id) => module_id,
+            _ => return None,
+        };
+        let (fdef_idx, offset) = vm_error.offsets().first()?;
+        let loc = test_plan
+            .module_info
+            .get(module_id)?
+            .source_map
+            .get_function_source_map(*fdef_idx)
+            .ok()?
+            .get_code_location(*offset)?;
+        let (file_name, line, column) = source_position(test_plan, &loc.file_hash(), loc.start())?;
+        Some(format!("{}:{}:{}", file_name, line, column))
+    }
+
     fn report_error_with_location(
         test_plan: &TestPlan,
         base_message: String,
@@ -231,6 +1105,13 @@ impl TestFailure {
                             .source_map
                             .get_function_source_map(*fdef_idx)
                             .ok()?;
+                        // Modules loaded from bytecode have no source to point to
+                        if !test_plan
+                            .files
+                            .contains_key(&function_source_map.definition_location.file_hash())
+                        {
+                            return None;
+                        }
                         let loc = function_source_map.get_code_location(*offset).unwrap();
                         let msg = format!("In this function in {}", format_module_id(module_id));
                         // TODO(tzakian) maybe migrate off of move-langs diagnostics?
@@ -240,7 +1121,10 @@ impl TestFailure {
                             vec![(function_source_map.definition_location, msg)],
                         ))
                     })
-                    .collect();
+                    .collect::<Diagnostics>();
+                if diags.is_empty() {
+                    return base_message;
+                }
 
                 String::from_utf8(report_diagnostics(&test_plan.files, diags)).unwrap()
             }
@@ -254,6 +1138,7 @@ impl TestStatistics {
         Self {
             passed: BTreeMap::new(),
             failed: BTreeMap::new(),
+            instruction_count_mismatches: BTreeMap::new(),
         }
     }
 
@@ -271,6 +1156,43 @@ impl TestStatistics {
             .insert(test_info);
     }
 
+    pub fn instruction_count_mismatch(
+        &mut self,
+        mismatch: InstructionCountMismatch,
+        test_plan: &ModuleTestPlan,
+    ) {
+        self.instruction_count_mismatches
+            .entry(test_plan.module_id.clone())
+            .or_insert_with(BTreeSet::new)
+            .insert(mismatch);
+    }
+
+    /// The tests which passed, by module
+    pub fn passed(&self) -> &BTreeMap<ModuleId, BTreeSet<TestRunInfo>> {
+        &self.passed
+    }
+
+    /// The tests which failed or timed out, by module
+    pub fn failed(&self) -> &BTreeMap<ModuleId, BTreeSet<TestFailure>> {
+        &self.failed
+    }
+
+    /// The tests whose instruction counts diverge between the Move VM and the stackless VM, by
+    /// module
+    pub fn instruction_count_mismatches(
+        &self,
+    ) -> &BTreeMap<ModuleId, BTreeSet<InstructionCountMismatch>> {
+        &self.instruction_count_mismatches
+    }
+
+    pub fn num_passed(&self) -> usize {
+        self.passed.values().map(BTreeSet::len).sum()
+    }
+
+    pub fn num_failed(&self) -> usize {
+        self.failed.values().map(BTreeSet::len).sum()
+    }
+
     pub fn combine(mut self, other: Self) -> Self {
         for (module_id, test_result) in other.passed {
             let entry = self.passed.entry(module_id).or_default();
@@ -280,6 +1202,13 @@ impl TestStatistics {
             let entry = self.failed.entry(module_id).or_default();
             entry.extend(test_result.into_iter());
         }
+        for (module_id, mismatches) in other.instruction_count_mismatches {
+            let entry = self
+                .instruction_count_mismatches
+                .entry(module_id)
+                .or_default();
+            entry.extend(mismatches.into_iter());
+        }
         self
     }
 }
@@ -289,14 +1218,55 @@ impl TestResults {
         Self {
             final_statistics,
             test_plan,
+            shuffle_seed: None,
+            verification_failure: None,
+        }
+    }
+
+    /// Record the seed of the random order the tests were run in
+    pub fn with_shuffle_seed(self, shuffle_seed: Option<u64>) -> Self {
+        Self {
+            shuffle_seed,
+            ..self
         }
     }
 
+    /// The seed of the random order the tests were run in, if they were not run in order
+    pub fn shuffle_seed(&self) -> Option<u64> {
+        self.shuffle_seed
+    }
+
+    /// Record the errors of the prover, if a spec of the tested modules cannot be verified
+    pub fn with_verification_failure(self, verification_failure: Option<String>) -> Self {
+        Self {
+            verification_failure,
+            ..self
+        }
+    }
+
+    /// The errors of the prover, if a spec of the tested modules cannot be verified
+    pub fn verification_failure(&self) -> Option<&str> {
+        self.verification_failure.as_deref()
+    }
+
+    /// The outcome of each test of the run
+    pub fn statistics(&self) -> &TestStatistics {
+        &self.final_statistics
+    }
+
+    /// The plan the tests were run from, e.g. to render the errors of failed tests
+    pub fn test_plan(&self) -> &TestPlan {
+        &self.test_plan
+    }
+
     pub fn report_statistics<W: Write>(&self, writer: &Mutex<W>) -> Result<()> {
         writeln!(writer.lock().unwrap(), "\nTest Statistics:\n")?;
 
         let mut max_function_name_size = 0;
         let mut stats = Vec::new();
+        // Whether the tests were metered with a gas schedule, in which case their gas is reported
+        // instead of their instruction count
+        let mut metered = false;
 
         for (module_id, test_results) in self.final_statistics.passed.iter() {
             for test_result in test_results {
@@ -307,10 +1277,14 @@ impl TestResults {
                 );
                 max_function_name_size =
                     std::cmp::max(max_function_name_size, qualified_function_name.len());
+                metered |= test_result.gas_used.is_some();
                 stats.push((
                     qualified_function_name,
                     test_result.elapsed_time.as_secs_f32(),
-                    test_result.instructions_executed,
+                    test_result
+                        .gas_used
+                        .unwrap_or(test_result.instructions_executed),
+                    test_result,
                 ))
             }
         }
@@ -324,10 +1298,13 @@ impl TestResults {
                 );
                 max_function_name_size =
                     std::cmp::max(max_function_name_size, qualified_function_name.len());
+                let run_info = &test_failure.test_run_info;
+                metered |= run_info.gas_used.is_some();
                 stats.push((
                     qualified_function_name,
-                    test_failure.test_run_info.elapsed_time.as_secs_f32(),
-                    test_failure.test_run_info.instructions_executed,
+                    run_info.elapsed_time.as_secs_f32(),
+                    run_info.gas_used.unwrap_or(run_info.instructions_executed),
+                    run_info,
                 ));
             }
         }
@@ -335,7 +1312,10 @@ impl TestResults {
         if !stats.is_empty() {
             writeln!(
                 writer.lock().unwrap(),
-                "┌─{:─^width$}─┬─{:─^10}─┬─{:─^25}─┐",
+                "┌─{:─^width$}─┬─{:─^10}─┬─{:─^25}─┬─{:─^11}─┬─{:─^16}─┬─{:─^13}─┐",
+                "",
+                "",
+                "",
                 "",
                 "",
                 "",
@@ -343,17 +1323,28 @@ impl TestResults {
             )?;
             writeln!(
                 writer.lock().unwrap(),
-                "│ {name:^width$} │ {time:^10} │ {instructions:^25} │",
+                "│ {name:^width$} │ {time:^10} │ {instructions:^25} │ {depth:^11} │ {values:^16} │ \
+                 {storage:^13} │",
                 width = max_function_name_size,
                 name = "Test Name",
                 time = "Time",
-                instructions = "Instructions Executed"
+                instructions = if metered {
+                    "Gas Used"
+                } else {
+                    "Instructions Executed"
+                },
+                depth = "Stack Depth",
+                values = "Values Allocated",
+                storage = "Storage Bytes",
             )?;
 
-            for (qualified_function_name, time, instructions) in stats {
+            for (qualified_function_name, time, instructions, run_info) in stats {
                 writeln!(
                     writer.lock().unwrap(),
-                    "├─{:─^width$}─┼─{:─^10}─┼─{:─^25}─┤",
+                    "├─{:─^width$}─┼─{:─^10}─┼─{:─^25}─┼─{:─^11}─┼─{:─^16}─┼─{:─^13}─┤",
+                    "",
+                    "",
+                    "",
                     "",
                     "",
                     "",
@@ -361,17 +1352,24 @@ impl TestResults {
                 )?;
                 writeln!(
                     writer.lock().unwrap(),
-                    "│ {name:<width$} │ {time:^10.3} │ {instructions:^25} │",
+                    "│ {name:<width$} │ {time:^10.3} │ {instructions:^25} │ {depth:^11} │ \
+                     {values:^16} │ {storage:^13} │",
                     name = qualified_function_name,
                     width = max_function_name_size,
                     time = time,
                     instructions = instructions,
+                    depth = run_info.max_stack_depth,
+                    values = run_info.values_allocated,
+                    storage = run_info.storage_bytes,
                 )?;
             }
 
             writeln!(
                 writer.lock().unwrap(),
-                "└─{:─^width$}─┴─{:─^10}─┴─{:─^25}─┘",
+                "└─{:─^width$}─┴─{:─^10}─┴─{:─^25}─┴─{:─^11}─┴─{:─^16}─┴─{:─^13}─┘",
+                "",
+                "",
+                "",
                 "",
                 "",
                 "",
@@ -382,8 +1380,432 @@ impl TestResults {
         writeln!(writer.lock().unwrap())
     }
 
+    /// Write the statistics of each test in `format`. The CSV and JSON formats are meant to be
+    /// processed by other tools, e.g. to be ingested into dashboards.
+    pub fn write_statistics<W: Write>(
+        &self,
+        writer: &Mutex<W>,
+        format: StatisticsFormat,
+    ) -> Result<()> {
+        match format {
+            StatisticsFormat::Table => self.report_statistics(writer),
+            StatisticsFormat::Csv => {
+                let mut writer = writer.lock().unwrap();
+                writeln!(writer, "{}", TestStatisticsRow::CSV_HEADER)?;
+                for row in self.statistics_rows() {
+                    writeln!(writer, "{}", row.to_csv())?;
+                }
+                Ok(())
+            }
+            StatisticsFormat::Json => {
+                let json = serde_json::to_string_pretty(&self.statistics_rows())
+                    .expect("test statistics are serializable");
+                writeln!(writer.lock().unwrap(), "{}", json)
+            }
+        }
+    }
+
+    /// The statistics of each test, passed tests first
+    fn statistics_rows(&self) -> Vec<TestStatisticsRow> {
+        let mut rows = vec![];
+        for (module_id, test_results) in &self.final_statistics.passed {
+            for test_result in test_results {
+                rows.push(TestStatisticsRow::new(module_id, test_result, "passed"));
+            }
+        }
+        for (module_id, test_failures) in &self.final_statistics.failed {
+            for test_failure in test_failures {
+                rows.push(TestStatisticsRow::new(
+                    module_id,
+                    &test_failure.test_run_info,
+                    "failed",
+                ));
+            }
+        }
+        rows
+    }
+
+    /// Write the metrics of this run in the OpenMetrics text format. The metrics of each test are
+    /// gauges labelled with the test, and their totals over all tests `*_total` counters of
+    /// separate families. Instruction coverage is included if the Move VM has been tracing the
+    /// execution of the tests.
+    pub fn report_open_metrics<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let mut tests = vec![];
+        for (module_id, test_results) in &self.final_statistics.passed {
+            for test_result in test_results {
+                tests.push((module_id, test_result, "passed"));
+            }
+        }
+        for (module_id, test_failures) in &self.final_statistics.failed {
+            for test_failure in test_failures {
+                tests.push((module_id, &test_failure.test_run_info, "failed"));
+            }
+        }
+
+        writeln!(writer, "# TYPE move_unit_test_tests gauge")?;
+        writeln!(
+            writer,
+            "# HELP move_unit_test_tests Number of tests by outcome."
+        )?;
+        for outcome in &["passed", "failed"] {
+            writeln!(
+                writer,
+                "move_unit_test_tests{{outcome=\"{}\"}} {}",
+                outcome,
+                tests.iter().filter(|(_, _, o)| o == outcome).count()
+            )?;
+        }
+
+        writeln!(writer, "# TYPE move_unit_test_duration_seconds gauge")?;
+        writeln!(
+            writer,
+            "# HELP move_unit_test_duration_seconds Time spent executing tests."
+        )?;
+        for (module_id, info, outcome) in &tests {
+            writeln!(
+                writer,
+                "move_unit_test_duration_seconds{{test=\"{}::{}\",outcome=\"{}\"}} {}",
+                format_module_id(module_id),
+                info.function_ident,
+                outcome,
+                info.elapsed_time.as_secs_f64()
+            )?;
+        }
+
+        writeln!(
+            writer,
+            "# TYPE move_unit_test_suite_duration_seconds counter"
+        )?;
+        writeln!(
+            writer,
+            "# HELP move_unit_test_suite_duration_seconds Time spent executing all tests."
+        )?;
+        writeln!(
+            writer,
+            "move_unit_test_suite_duration_seconds_total {}",
+            tests
+                .iter()
+                .map(|(_, info, _)| info.elapsed_time.as_secs_f64())
+                .sum::<f64>()
+        )?;
+
+        writeln!(writer, "# TYPE move_unit_test_instructions gauge")?;
+        writeln!(
+            writer,
+            "# HELP move_unit_test_instructions Number of instructions executed by tests."
+        )?;
+        for (module_id, info, outcome) in &tests {
+            writeln!(
+                writer,
+                "move_unit_test_instructions{{test=\"{}::{}\",outcome=\"{}\"}} {}",
+                format_module_id(module_id),
+                info.function_ident,
+                outcome,
+                info.instructions_executed
+            )?;
+        }
+
+        writeln!(writer, "# TYPE move_unit_test_suite_instructions counter")?;
+        writeln!(
+            writer,
+            "# HELP move_unit_test_suite_instructions Number of instructions executed by all tests."
+        )?;
+        writeln!(
+            writer,
+            "move_unit_test_suite_instructions_total {}",
+            tests
+                .iter()
+                .map(|(_, info, _)| info.instructions_executed)
+                .sum::<u64>()
+        )?;
+
+        if tests.iter().any(|(_, info, _)| info.gas_used.is_some()) {
+            writeln!(writer, "# TYPE move_unit_test_gas_used gauge")?;
+            writeln!(
+                writer,
+                "# HELP move_unit_test_gas_used Gas used by tests metered with a gas schedule."
+            )?;
+            for (module_id, info, outcome) in &tests {
+                if let Some(gas_used) = info.gas_used {
+                    writeln!(
+                        writer,
+                        "move_unit_test_gas_used{{test=\"{}::{}\",outcome=\"{}\"}} {}",
+                        format_module_id(module_id),
+                        info.function_ident,
+                        outcome,
+                        gas_used
+                    )?;
+                }
+            }
+
+            writeln!(writer, "# TYPE move_unit_test_suite_gas_used counter")?;
+            writeln!(
+                writer,
+                "# HELP move_unit_test_suite_gas_used Gas used by all tests metered with a gas \
+                 schedule."
+            )?;
+            writeln!(
+                writer,
+                "move_unit_test_suite_gas_used_total {}",
+                tests
+                    .iter()
+                    .filter_map(|(_, info, _)| info.gas_used)
+                    .sum::<u64>()
+            )?;
+        }
+
+        if let Some(coverage) = self.instruction_coverage_percent() {
+            writeln!(writer, "# TYPE move_unit_test_coverage_percent gauge")?;
+            writeln!(
+                writer,
+                "# HELP move_unit_test_coverage_percent Instruction coverage of the tested modules."
+            )?;
+            writeln!(writer, "move_unit_test_coverage_percent {}", coverage)?;
+        }
+
+        writeln!(writer, "# EOF")
+    }
+
+    /// Compute the instruction coverage of the modules containing tests from the trace of the
+    /// Move VM, if tracing is enabled.
+    fn instruction_coverage_percent(&self) -> Option<f64> {
+        let trace_path = std::env::var(MOVE_VM_TRACE_ENV_VAR_NAME).ok()?;
+        if !Path::new(&trace_path).exists() {
+            return None;
+        }
+        let coverage_map = CoverageMap::from_trace_file(&trace_path).to_unified_exec_map();
+        let (covered, total) = self
+            .test_plan
+            .module_tests
+            .keys()
+            .filter_map(|module_id| self.test_plan.module_info.get(module_id))
+            .flat_map(|unit| {
+                summarize_inst_cov(&unit.module, &coverage_map)
+                    .function_summaries
+                    .into_iter()
+                    .map(|(_, summary)| (summary.covered, summary.total))
+            })
+            .fold((0, 0), |(c, t), (covered, total)| (c + covered, t + total));
+        if total == 0 {
+            None
+        } else {
+            Some(covered as f64 / total as f64 * 100f64)
+        }
+    }
+
+    /// Write the results of this run as a JUnit XML report, with a test suite per module.
+    /// Returns `true` if all tests passed, `false` if there was a test failure/timeout
+    pub fn report_junit<W: Write>(&self, writer: &Mutex<W>) -> Result<bool> {
+        let mut suites: BTreeMap<&ModuleId, Vec<(&TestRunInfo, Option<&TestFailure>)>> =
+            BTreeMap::new();
+        for (module_id, test_results) in &self.final_statistics.passed {
+            let suite = suites.entry(module_id).or_default();
+            suite.extend(test_results.iter().map(|info| (info, None)));
+        }
+        for (module_id, test_failures) in &self.final_statistics.failed {
+            let suite = suites.entry(module_id).or_default();
+            suite.extend(
+                test_failures
+                    .iter()
+                    .map(|failure| (&failure.test_run_info, Some(failure))),
+            );
+        }
+        let total_time = |tests: &[(&TestRunInfo, Option<&TestFailure>)]| {
+            tests
+                .iter()
+                .map(|(info, _)| info.elapsed_time.as_secs_f64())
+                .sum::<f64>()
+        };
+
+        let mut writer = writer.lock().unwrap();
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        let all_tests: Vec<_> = suites.values().flatten().cloned().collect();
+        let num_failed_tests = all_tests.iter().filter(|(_, f)| f.is_some()).count();
+        writeln!(
+            writer,
+            r#"<testsuites name="move_unit_tests" tests="{}" failures="{}" time="{:.3}">"#,
+            all_tests.len(),
+            num_failed_tests,
+            total_time(&all_tests)
+        )?;
+        for (module_id, mut tests) in suites {
+            tests.sort_by(|(info1, _), (info2, _)| info1.function_ident.cmp(&info2.function_ident));
+            let module_name = xml_escape(&format_module_id(module_id));
+            writeln!(
+                writer,
+                r#"  <testsuite name="{}" tests="{}" failures="{}" time="{:.3}">"#,
+                module_name,
+                tests.len(),
+                tests.iter().filter(|(_, f)| f.is_some()).count(),
+                total_time(&tests)
+            )?;
+            for (info, failure) in tests {
+                let testcase = format!(
+                    r#"    <testcase name="{}" classname="{}" time="{:.3}""#,
+                    xml_escape(&info.function_ident),
+                    module_name,
+                    info.elapsed_time.as_secs_f64()
+                );
+                match failure {
+                    None => writeln!(writer, "{}/>", testcase)?,
+                    Some(failure) => {
+                        writeln!(writer, "{}>", testcase)?;
+                        writeln!(
+                            writer,
+                            r#"      <failure message="{}" type="{}">{}</failure>"#,
+                            xml_escape(&failure.summary(&self.test_plan)),
+                            failure.kind(),
+                            xml_escape(&failure.render_error(&self.test_plan))
+                        )?;
+                        if !info.output.is_empty() {
+                            writeln!(
+                                writer,
+                                "      <system-out>{}</system-out>",
+                                xml_escape(&info.output)
+                            )?;
+                        }
+                        writeln!(writer, "    </testcase>")?;
+                    }
+                }
+            }
+            writeln!(writer, "  </testsuite>")?;
+        }
+        writeln!(writer, "</testsuites>")?;
+        Ok(num_failed_tests == 0)
+    }
+
+    /// The tests of the plan which neither passed nor failed, as the run was stopped before they
+    /// started or a test they depend on did not pass
+    pub fn skipped_tests(&self) -> BTreeMap<&ModuleId, Vec<&str>> {
+        let mut skipped: BTreeMap<&ModuleId, Vec<&str>> = BTreeMap::new();
+        for (module_id, module_test_plan) in &self.test_plan.module_tests {
+            let passed = self.final_statistics.passed.get(module_id);
+            let failed = self.final_statistics.failed.get(module_id);
+            for test_name in module_test_plan.tests.keys() {
+                let ran = passed.map_or(false, |tests| {
+                    tests.iter().any(|info| &info.function_ident == test_name)
+                }) || failed.map_or(false, |tests| {
+                    tests
+                        .iter()
+                        .any(|failure| &failure.test_run_info.function_ident == test_name)
+                });
+                if !ran {
+                    skipped.entry(module_id).or_default().push(test_name);
+                }
+            }
+        }
+        skipped
+    }
+
+    /// Whether no test failed or timed out, and the specs of the tested modules were verified if
+    /// the run was checked with the prover
+    pub fn all_tests_passed(&self) -> bool {
+        self.final_statistics.num_failed() == 0 && self.verification_failure.is_none()
+    }
+
+    /// Report the `count` tests which took the longest to run, by wall clock time
+    pub fn report_slow_tests<W: Write>(&self, writer: &Mutex<W>, count: usize) -> Result<()> {
+        let passed = self
+            .final_statistics
+            .passed
+            .iter()
+            .flat_map(|(module_id, infos)| infos.iter().map(move |info| (module_id, info)));
+        let failed = self
+            .final_statistics
+            .failed
+            .iter()
+            .flat_map(|(module_id, failures)| {
+                failures
+                    .iter()
+                    .map(move |failure| (module_id, &failure.test_run_info))
+            });
+        let mut slowest: Vec<_> = passed.chain(failed).collect();
+        slowest.sort_by_key(|(_, info)| Reverse(info.wall_clock_time));
+        slowest.truncate(count);
+
+        let mut writer = writer.lock().unwrap();
+        writeln!(writer, "\nSlowest tests:\n")?;
+        for (module_id, info) in slowest {
+            writeln!(
+                writer,
+                "{:>10.3}s  {}::{}",
+                info.wall_clock_time.as_secs_f64(),
+                format_module_id(module_id),
+                info.function_ident
+            )?;
+        }
+        writeln!(writer)
+    }
+
+    /// Report the `count` functions which executed the most instructions over all tests, with a
+    /// histogram of their share of all instructions executed. The instructions are counted from
+    /// the trace of the Move VM, so this needs the execution of the tests to be traced.
+    pub fn report_function_histogram<W: Write>(
+        &self,
+        writer: &Mutex<W>,
+        count: usize,
+    ) -> Result<()> {
+        let mut writer = writer.lock().unwrap();
+        writeln!(writer, "\nInstructions executed per function:\n")?;
+        let trace_path = match std::env::var(MOVE_VM_TRACE_ENV_VAR_NAME) {
+            Ok(trace_path) if Path::new(&trace_path).exists() => trace_path,
+            _ => {
+                writeln!(
+                    writer,
+                    "No execution trace found, set {} to trace the execution of the tests\n",
+                    MOVE_VM_TRACE_ENV_VAR_NAME
+                )?;
+                return Ok(());
+            }
+        };
+        let coverage_map = CoverageMap::from_trace_file(&trace_path).to_unified_exec_map();
+        let mut functions: Vec<_> = coverage_map
+            .module_maps
+            .values()
+            .flat_map(|module_map| {
+                let module_id =
+                    ModuleId::new(module_map.module_addr, module_map.module_name.clone());
+                let is_dependency = !self.test_plan.module_tests.contains_key(&module_id);
+                module_map
+                    .function_maps
+                    .iter()
+                    .map(move |(function_name, coverage)| {
+                        (
+                            format!("{}::{}", format_module_id(&module_id), function_name),
+                            is_dependency,
+                            coverage.values().sum::<u64>(),
+                        )
+                    })
+            })
+            .collect();
+        let total: u64 = functions
+            .iter()
+            .map(|(_, _, instructions)| instructions)
+            .sum();
+        functions.sort_by(|(_, _, instructions1), (_, _, instructions2)| {
+            instructions2.cmp(instructions1)
+        });
+        functions.truncate(count);
+
+        const BAR_WIDTH: u64 = 30;
+        for (function_name, is_dependency, instructions) in functions {
+            let share = instructions as f64 / total.max(1) as f64;
+            writeln!(
+                writer,
+                "{:>12} {:>6.2}% {:<bar_width$} {}{}",
+                instructions,
+                share * 100f64,
+                "█".repeat((instructions * BAR_WIDTH / total.max(1)) as usize),
+                function_name,
+                if is_dependency { " (dependency)" } else { "" },
+                bar_width = BAR_WIDTH as usize,
+            )?;
+        }
+        writeln!(writer)
+    }
+
     /// Returns `true` if all tests passed, `false` if there was a test failure/timeout
-    pub fn summarize<W: Write>(self, writer: &Mutex<W>) -> Result<bool> {
+    pub fn summarize<W: Write>(&self, writer: &Mutex<W>) -> Result<bool> {
         let num_failed_tests = self
             .final_statistics
             .failed
@@ -408,30 +1830,173 @@ impl TestResults {
                         "\n┌── {} ──────",
                         test_failure.test_run_info.function_ident.bold()
                     )?;
+                    let mut error = test_failure.render_error(&self.test_plan);
+                    if !test_failure.test_run_info.output.is_empty() {
+                        error = format!(
+                            "{}\n\nOutput of the test:\n{}\n\n",
+                            error.trim_end(),
+                            test_failure.test_run_info.output.trim_end()
+                        );
+                    }
+                    writeln!(writer.lock().unwrap(), "│ {}", error.replace("\n", "\n│ "))?;
+                    writeln!(writer.lock().unwrap(), "└──────────────────\n")?;
+                }
+            }
+        }
+
+        if !self
+            .final_statistics
+            .instruction_count_mismatches
+            .is_empty()
+        {
+            writeln!(
+                writer.lock().unwrap(),
+                "\nInstruction counts diverging between Move VM [M] and stackless VM [S]:\n"
+            )?;
+            for (module_id, mismatches) in &self.final_statistics.instruction_count_mismatches {
+                for mismatch in mismatches {
                     writeln!(
                         writer.lock().unwrap(),
-                        "│ {}",
-                        test_failure
-                            .render_error(&self.test_plan)
-                            .replace("\n", "\n│ ")
+                        "{}::{}: [M] {} [S] {}",
+                        format_module_id(module_id),
+                        mismatch.function_ident,
+                        mismatch.move_vm_instructions,
+                        mismatch.stackless_vm_instructions
                     )?;
-                    writeln!(writer.lock().unwrap(), "└──────────────────\n")?;
                 }
             }
+            writeln!(writer.lock().unwrap())?;
+        }
+
+        let flaky_tests: Vec<_> = self
+            .final_statistics
+            .passed
+            .iter()
+            .flat_map(|(module_id, test_results)| {
+                test_results
+                    .iter()
+                    .filter(|test_result| test_result.attempts > 1)
+                    .map(move |test_result| (module_id, test_result))
+            })
+            .collect();
+        if !flaky_tests.is_empty() {
+            let mut writer = writer.lock().unwrap();
+            writeln!(writer, "\nFlaky tests:\n")?;
+            for (module_id, test_result) in flaky_tests {
+                writeln!(
+                    writer,
+                    "{}::{} passed after {} attempts",
+                    format_module_id(module_id),
+                    test_result.function_ident,
+                    test_result.attempts
+                )?;
+            }
+            writeln!(writer)?;
         }
 
+        // The results of each module are only worth reporting if there are several of them
+        let mut module_results: BTreeMap<&ModuleId, (usize, usize)> = BTreeMap::new();
+        for (module_id, test_results) in &self.final_statistics.passed {
+            module_results.entry(module_id).or_default().0 += test_results.len();
+        }
+        for (module_id, test_failures) in &self.final_statistics.failed {
+            module_results.entry(module_id).or_default().1 += test_failures.len();
+        }
+        if module_results.len() > 1 {
+            let module_names: Vec<_> = module_results
+                .iter()
+                .map(|(module_id, results)| (format_module_id(module_id), results))
+                .collect();
+            let width = module_names
+                .iter()
+                .map(|(name, _)| name.len())
+                .max()
+                .unwrap_or_default();
+            let mut writer = writer.lock().unwrap();
+            writeln!(writer, "\nTest results by module:\n")?;
+            for (name, (passed, failed)) in module_names {
+                writeln!(
+                    writer,
+                    "{:<width$}  passed: {}; failed: {}",
+                    name,
+                    passed,
+                    failed,
+                    width = width
+                )?;
+            }
+            writeln!(writer)?;
+        }
+
+        let skipped_tests = self.skipped_tests();
+        let num_skipped_tests: usize = skipped_tests.values().map(|tests| tests.len()).sum();
+        if num_skipped_tests > 0 {
+            let mut writer = writer.lock().unwrap();
+            writeln!(writer, "\nSkipped tests:\n")?;
+            for (module_id, test_names) in &skipped_tests {
+                for test_name in test_names {
+                    writeln!(writer, "{}::{}", format_module_id(module_id), test_name)?;
+                }
+            }
+            writeln!(writer)?;
+        }
+
+        if let Some(seed) = self.shuffle_seed {
+            writeln!(
+                writer.lock().unwrap(),
+                "Tests were run in a random order with seed {}, rerun with `--shuffle {}` to \
+                reproduce it",
+                seed,
+                seed
+            )?;
+        }
+
+        if let Some(errors) = &self.verification_failure {
+            writeln!(
+                writer.lock().unwrap(),
+                "\nVerification failures:\n\n{}\n",
+                errors.trim_end()
+            )?;
+        }
+
+        let passed = num_failed_tests == 0 && self.verification_failure.is_none();
         writeln!(
             writer.lock().unwrap(),
-            "Test result: {}. Total tests: {}; passed: {}; failed: {}",
-            if num_failed_tests == 0 {
+            "Test result: {}. Total tests: {}; passed: {}; failed: {}{}{}",
+            if passed {
                 "OK".bold().bright_green()
             } else {
                 "FAILED".bold().bright_red()
             },
             num_passed_tests + num_failed_tests,
             num_passed_tests,
-            num_failed_tests
+            num_failed_tests,
+            if num_skipped_tests > 0 {
+                format!("; skipped: {}", num_skipped_tests)
+            } else {
+                String::new()
+            },
+            if self.verification_failure.is_some() {
+                "; verification failed"
+            } else {
+                ""
+            }
         )?;
-        Ok(num_failed_tests == 0)
+        Ok(passed)
+    }
+}
+
+/// Escape text for use in XML attribute values and element content
+fn xml_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
     }
+    escaped
 }
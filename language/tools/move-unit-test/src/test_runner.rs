@@ -3,57 +3,246 @@
 
 use crate::{
     format_module_id,
-    test_reporter::{FailureReason, TestFailure, TestResults, TestRunInfo, TestStatistics},
+    genesis::Genesis,
+    isolation::{
+        self, IsolatedFailure, IsolatedOutcome, IsolatedResult, IsolatedRun, IsolatedSettings,
+        ProcessOutcome,
+    },
+    test_reporter::{
+        ExpectedAbortLocation, FailureReason, InstructionCountMismatch, TestFailure, TestOutcome,
+        TestReporter, TestResults, TestRunInfo, TestStatistics,
+    },
 };
 use anyhow::Result;
+use codespan_reporting::term::termcolor::Buffer;
 use colored::*;
-use move_binary_format::{errors::VMResult, file_format::CompiledModule};
+use move_binary_format::{
+    access::ModuleAccess,
+    errors::{Location, PartialVMError, VMError, VMResult},
+    file_format::{CompiledModule, FunctionDefinitionIndex},
+};
 use move_bytecode_utils::Modules;
+use move_command_line_common::testing::{format_diff, EXP_EXT};
 use move_compiler::{
     shared::{Flags, NumericalAddress},
-    unit_test::{ExpectedFailure, ModuleTestPlan, TestCase, TestPlan},
+    unit_test::{
+        AbortLocation, ExpectedFailure, ModuleTestPlan, RandomArgument, RandomType, StorageFaults,
+        TestCase, TestPlan,
+    },
 };
 use move_core_types::{
     account_address::AccountAddress,
-    effects::ChangeSet,
+    effects::{ChangeSet, Event},
     gas_schedule::{CostTable, GasAlgebra, GasCost, GasUnits},
-    identifier::IdentStr,
-    value::serialize_values,
+    identifier::{IdentStr, Identifier},
+    language_storage::{ModuleId, StructTag, TypeTag},
+    resolver::{ModuleResolver, ResourceResolver},
+    value::{serialize_values, MoveValue},
     vm_status::StatusCode,
 };
 use move_model::{
     model::GlobalEnv, options::ModelBuilderOptions,
     run_model_builder_with_options_and_compilation_flags,
 };
-use move_resource_viewer::MoveValueAnnotator;
+use move_prover::{
+    cli::{named_addresses_for_options, Options as ProverOptions},
+    run_move_prover,
+};
+use move_resource_viewer::{AnnotatedMoveValue, MoveValueAnnotator};
 use move_stackless_bytecode_interpreter::{
     concrete::{settings::InterpreterSettings, value::GlobalState},
     shared::bridge::{adapt_move_vm_change_set, adapt_move_vm_result},
     StacklessBytecodeInterpreter,
 };
-use move_vm_runtime::{move_vm::MoveVM, native_functions::NativeFunctionTable};
+use move_stdlib::natives::{debug::CapturedOutput, unit_test::UnitTestContext};
+#[cfg(feature = "statistics")]
+use move_vm_runtime::execution_statistics::ExecutionStatistics;
+use move_vm_runtime::{
+    config::VMConfig, move_vm::MoveVM, native_extensions::NativeContextExtensions,
+    native_functions::NativeFunctionTable,
+};
 use move_vm_test_utils::InMemoryStorage;
 use move_vm_types::gas_schedule::{zero_cost_schedule, GasStatus};
 use rayon::prelude::*;
-use std::{collections::BTreeMap, io::Write, marker::Send, sync::Mutex, time::Instant};
+use regex::Regex;
+use std::{
+    cell::Cell,
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::Write,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, RecvTimeoutError},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tracing::{dispatcher, info_span, Span};
+
+/// The number of times each random test is run, with newly generated inputs each time
+const RANDOM_TEST_ITERATIONS: usize = 32;
+
+/// The maximum length of the `vector<u8>` inputs generated for random tests
+const MAX_RANDOM_VECTOR_LENGTH: u64 = 32;
+
+/// Creates the native context extensions of each session a test is run in, for natives which
+/// keep their state in extensions, e.g. tables
+pub type NativeExtensionsFactory = Arc<dyn Fn() -> NativeContextExtensions + Send + Sync>;
 
 /// Test state common to all tests
+#[derive(Clone)]
 pub struct SharedTestingConfig {
     save_storage_state_on_failure: bool,
+    full_storage_state_on_failure: bool,
+    // The directory the storage state of each failing test is written to
+    state_output_dir: Option<String>,
+    // The directory the output of each test is written to and compared against
+    snapshot_dir: Option<String>,
+    // Whether the snapshots are overwritten rather than compared against
+    update_baseline: bool,
     execution_bound: u64,
     cost_table: CostTable,
+    // Whether the tests are metered with a gas schedule rather than bounded by a number of
+    // instructions
+    metered: bool,
+    // The limits the Move VM enforces on the execution of the tests
+    vm_config: VMConfig,
     native_function_table: NativeFunctionTable,
+    // Creates the native context extensions of each session, if the natives need any
+    native_extensions: Option<NativeExtensionsFactory>,
     starting_storage_state: InMemoryStorage,
     source_files: Vec<String>,
     named_address_values: BTreeMap<String, NumericalAddress>,
     check_stackless_vm: bool,
+    stackless_instruction_tolerance: Option<f64>,
+    // Whether the tests are run on the stackless VM instead of the Move VM
+    stackless_only: bool,
     verbose: bool,
+    // Once set, the tests which have not started yet are skipped
+    cancelled: Option<Arc<AtomicBool>>,
+    // Whether the first failing test cancels the run
+    fail_fast: bool,
+    // The time after which a test is abandoned and reported as timed out
+    timeout: Option<Duration>,
+    // The number of times a failing test is run again before it is reported as failed
+    retries: usize,
+    // Whether each test is run in a process of its own
+    isolated: bool,
+    // The files handed to the test processes of an isolated run, while it runs
+    isolated_run: Option<Arc<IsolatedRun>>,
+    // The genesis state the starting storage state includes, if any
+    genesis: Option<Genesis>,
+    // The seed of the random order the tests are run in, if they are not run in order
+    shuffle_seed: Option<u64>,
+    // The seed the inputs of random tests are generated from
+    random_seed: u64,
+    // The functions declared with #[test_invariant] in the modules of the plan
+    invariants: Vec<(ModuleId, String)>,
+    // Whether the invariants are checked against the storage state left by each passing test
+    check_invariants: bool,
+    // Whether the output of the `debug` natives is captured with each test, rather than printed
+    capture_output: bool,
+    // The warnings of the tests run so far which have not been reported yet
+    warnings: Arc<Mutex<Vec<String>>>,
+}
+
+/// The result of executing a test.
+enum TestExecution {
+    Passed(TestRunInfo),
+    Failed(TestFailure),
+    TimedOut(TestFailure),
+}
+
+impl TestExecution {
+    fn outcome(&self) -> &'static str {
+        match self {
+            TestExecution::Passed(_) => "pass",
+            TestExecution::Failed(_) => "fail",
+            TestExecution::TimedOut(_) => "timeout",
+        }
+    }
+
+    fn run_info_mut(&mut self) -> &mut TestRunInfo {
+        match self {
+            TestExecution::Passed(test_run_info) => test_run_info,
+            TestExecution::Failed(failure) | TestExecution::TimedOut(failure) => {
+                &mut failure.test_run_info
+            }
+        }
+    }
+}
+
+/// The progress of executing the tests of a module.
+#[allow(clippy::large_enum_variant)]
+enum TestProgress {
+    Started(String),
+    Finished(String, TestExecution, Option<InstructionCountMismatch>),
+}
+
+/// The events emitted by a test, and those it asserts to be emitted with
+/// `UnitTest::assert_event_emitted`.
+struct TestEvents {
+    emitted: Vec<Event>,
+    asserted: Vec<(TypeTag, Vec<u8>)>,
+}
+
+/// The storage state left behind by the fixture of a test group, from which each test of the
+/// group starts with its own copy.
+struct FixtureState {
+    storage: InMemoryStorage,
+    // Only populated if the stackless VM is checked as well
+    global_state: GlobalState,
+}
+
+/// A view of the storage a test runs on which simulates the failures the test is configured with:
+/// loads of the failing resources return a storage error, and the missing modules are not found.
+/// It also counts the bytes of the resources the test loads.
+struct FaultyStorage<'a> {
+    storage: &'a InMemoryStorage,
+    faults: &'a StorageFaults,
+    resource_bytes_read: Cell<u64>,
+}
+
+impl<'a> ModuleResolver for FaultyStorage<'a> {
+    type Error = ();
+
+    fn get_module(&self, module_id: &ModuleId) -> Result<Option<Vec<u8>>, Self::Error> {
+        if self.faults.missing_modules.contains(module_id) {
+            return Ok(None);
+        }
+        self.storage.get_module(module_id)
+    }
+}
+
+impl<'a> ResourceResolver for FaultyStorage<'a> {
+    type Error = ();
+
+    fn get_resource(
+        &self,
+        address: &AccountAddress,
+        tag: &StructTag,
+    ) -> Result<Option<Vec<u8>>, Self::Error> {
+        let resource = (tag.module_id(), tag.name.clone());
+        if self.faults.failing_resources.contains(&resource) {
+            return Err(());
+        }
+        let blob = self.storage.get_resource(address, tag)?;
+        if let Some(blob) = &blob {
+            self.resource_bytes_read
+                .set(self.resource_bytes_read.get() + blob.len() as u64);
+        }
+        Ok(blob)
+    }
 }
 
 pub struct TestRunner {
     num_threads: usize,
     testing_config: SharedTestingConfig,
     tests: TestPlan,
+    // The source and dependency files the prover is run over once all tests pass, if it is run
+    prover_check: Option<(Vec<String>, Vec<String>)>,
 }
 
 /// A gas schedule where every instruction has a cost of "1". This is used to bound execution of a
@@ -88,36 +277,548 @@ fn setup_test_storage<'a>(
     Ok(storage)
 }
 
+/// `storage` updated with `change_set`. The VM produces change sets which apply to the storage
+/// they were computed on, so a failure is reported as an invariant violation of the VM in
+/// `module_id`.
+fn apply_change_set(
+    storage: &InMemoryStorage,
+    change_set: ChangeSet,
+    module_id: &ModuleId,
+) -> VMResult<InMemoryStorage> {
+    let mut storage = storage.clone();
+    storage.apply(change_set).map_err(|err| {
+        PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+            .with_message(format!(
+                "Unable to apply the changes to the storage: {}",
+                err
+            ))
+            .finish(Location::Module(module_id.clone()))
+    })?;
+    Ok(storage)
+}
+
+/// The addresses which may hold resources while running `test_info` on `storage`: those already
+/// holding something in the storage, and those of the signers passed to the test. Signer
+/// arguments are passed as addresses, which the VM turns into signers.
+fn test_addresses(storage: &InMemoryStorage, test_info: &TestCase) -> BTreeSet<AccountAddress> {
+    let mut addresses: BTreeSet<_> = storage.addresses().cloned().collect();
+    addresses.extend(test_info.arguments.iter().filter_map(|arg| match arg {
+        MoveValue::Address(addr) | MoveValue::Signer(addr) => Some(*addr),
+        _ => None,
+    }));
+    addresses
+}
+
+/// Whether `tag` is the type of struct `name` of module `module_id`, with any type arguments.
+fn is_struct_type(tag: &TypeTag, module_id: &ModuleId, name: &Identifier) -> bool {
+    match tag {
+        TypeTag::Struct(struct_tag) => {
+            &struct_tag.module_id() == module_id && &struct_tag.name == name
+        }
+        _ => false,
+    }
+}
+
+fn expected_abort_location(location: &AbortLocation) -> ExpectedAbortLocation {
+    ExpectedAbortLocation {
+        module_id: location.module_id.clone(),
+        function: location.function.as_ref().map(|f| f.to_string()),
+    }
+}
+
+/// The name of the function at `fdef_idx` in the module `module_id` published in `storage`
+fn function_name_at(
+    storage: &InMemoryStorage,
+    module_id: &ModuleId,
+    fdef_idx: FunctionDefinitionIndex,
+) -> Option<Identifier> {
+    let bytes = storage.get_module(module_id).ok()??;
+    let module = CompiledModule::deserialize(&bytes).ok()?;
+    let fdef = module.function_defs().get(fdef_idx.0 as usize)?;
+    Some(
+        module
+            .identifier_at(module.function_handle_at(fdef.function).name)
+            .to_owned(),
+    )
+}
+
+/// Whether `err` was raised in the module, and the function if any, of `location`
+fn is_abort_in(storage: &InMemoryStorage, err: &VMError, location: &AbortLocation) -> bool {
+    match err.location() {
+        Location::Module(module_id) if module_id == &location.module_id => {
+            match &location.function {
+                None => true,
+                Some(function) => err.offsets().first().map_or(false, |(fdef_idx, _)| {
+                    function_name_at(storage, module_id, *fdef_idx).as_ref() == Some(function)
+                }),
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Reorder `tests` so that each test comes after the tests it depends on, keeping the order of
+/// the tests otherwise.
+fn order_by_dependencies<'a>(
+    tests: &[(&'a String, &'a TestCase)],
+) -> Vec<(&'a String, &'a TestCase)> {
+    fn visit<'a>(
+        idx: usize,
+        tests: &[(&'a String, &'a TestCase)],
+        visited: &mut [bool],
+        ordered: &mut Vec<(&'a String, &'a TestCase)>,
+    ) {
+        if visited[idx] {
+            return;
+        }
+        // Marked before its dependencies are visited, so that a cycle cannot loop forever
+        visited[idx] = true;
+        let (_, test_info) = tests[idx];
+        for (dep_idx, (_, dep_info)) in tests.iter().enumerate() {
+            if test_info.depends_on.contains(&dep_info.test_name) {
+                visit(dep_idx, tests, visited, ordered);
+            }
+        }
+        ordered.push(tests[idx]);
+    }
+
+    let mut visited = vec![false; tests.len()];
+    let mut ordered = Vec::with_capacity(tests.len());
+    for idx in 0..tests.len() {
+        visit(idx, tests, &mut visited, &mut ordered);
+    }
+    ordered
+}
+
+/// The test of `test_plan` in `unmet`, the tests which did not pass, that `test_info` depends on,
+/// if any.
+fn unmet_dependency<'a>(
+    test_plan: &'a ModuleTestPlan,
+    test_info: &TestCase,
+    unmet: &BTreeSet<String>,
+) -> Option<&'a String> {
+    test_plan
+        .tests
+        .iter()
+        .find(|(name, dep_info)| {
+            unmet.contains(*name) && test_info.depends_on.contains(&dep_info.test_name)
+        })
+        .map(|(name, _)| name)
+}
+
+/// Split the tests of `test_plan` into at most `count` parts with similar numbers of tests, to be
+/// run in parallel. Tests which depend on each other or share a fixture are kept in the same
+/// part, as they are run in order and from the state left by the fixture.
+fn split_module_tests(test_plan: &ModuleTestPlan, count: usize) -> Vec<ModuleTestPlan> {
+    fn find(parents: &mut [usize], mut idx: usize) -> usize {
+        while parents[idx] != idx {
+            parents[idx] = parents[parents[idx]];
+            idx = parents[idx];
+        }
+        idx
+    }
+
+    let tests: Vec<_> = test_plan.tests.iter().collect();
+    if count <= 1 || tests.len() <= 1 {
+        return vec![test_plan.clone()];
+    }
+    // The tests of each function, which has several test cases if it is data-driven
+    let mut by_function: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (idx, (_, test_info)) in tests.iter().enumerate() {
+        by_function
+            .entry(test_info.test_name.as_str())
+            .or_default()
+            .push(idx);
+    }
+    let mut parents: Vec<usize> = (0..tests.len()).collect();
+    let mut fixture_tests: BTreeMap<&str, usize> = BTreeMap::new();
+    for (idx, (_, test_info)) in tests.iter().enumerate() {
+        let related = test_info
+            .depends_on
+            .iter()
+            .flat_map(|dependency| by_function.get(dependency.as_str()))
+            .flatten()
+            .copied()
+            .chain(
+                test_info
+                    .fixture
+                    .as_deref()
+                    .map(|fixture| *fixture_tests.entry(fixture).or_insert(idx)),
+            )
+            .collect::<Vec<_>>();
+        for other in related {
+            let (root, other_root) = (find(&mut parents, idx), find(&mut parents, other));
+            parents[other_root] = root;
+        }
+    }
+    let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for idx in 0..tests.len() {
+        let root = find(&mut parents, idx);
+        groups.entry(root).or_default().push(idx);
+    }
+
+    // The largest groups are placed first, each in the part with the fewest tests so far
+    let mut groups: Vec<_> = groups.into_values().collect();
+    groups.sort_by_key(|group| std::cmp::Reverse(group.len()));
+    let mut parts = vec![BTreeMap::new(); count.min(groups.len())];
+    for group in groups {
+        let part = parts
+            .iter_mut()
+            .min_by_key(|part| part.len())
+            .expect("at least one part");
+        for idx in group {
+            let (name, test_info) = tests[idx];
+            part.insert(name.clone(), test_info.clone());
+        }
+    }
+    parts
+        .into_iter()
+        .map(|tests| ModuleTestPlan {
+            module_id: test_plan.module_id.clone(),
+            tests,
+            invariants: test_plan.invariants.clone(),
+        })
+        .collect()
+}
+
+/// The 64-bit FNV-1a hash of `bytes`, which unlike the hashers of the standard library is
+/// guaranteed to be stable across platforms and Rust versions.
+fn fnv1a_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// The next number drawn from the SplitMix64 generator with state `state`.
+fn split_mix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Shuffle `items` with the Fisher-Yates algorithm, drawing from a SplitMix64 generator seeded
+/// with `seed`.
+fn shuffle<T>(items: &mut [T], seed: u64) {
+    let mut state = seed;
+    for i in (1..items.len()).rev() {
+        let z = split_mix64(&mut state);
+        items.swap(i, (z % (i as u64 + 1)) as usize);
+    }
+}
+
+/// A value of type `ty` drawn from the SplitMix64 generator with state `state`. One in eight
+/// integers is an edge case of its type, which uniform draws would hardly ever hit.
+fn random_value(ty: RandomType, state: &mut u64) -> MoveValue {
+    let edge_case = |state: &mut u64, max: u128| match split_mix64(state) % 8 {
+        0 => match split_mix64(state) % 3 {
+            0 => Some(0),
+            1 => Some(1),
+            _ => Some(max),
+        },
+        _ => None,
+    };
+    match ty {
+        RandomType::U8 => MoveValue::U8(
+            edge_case(state, std::u8::MAX as u128)
+                .map_or_else(|| split_mix64(state) as u8, |v| v as u8),
+        ),
+        RandomType::U64 => MoveValue::U64(
+            edge_case(state, std::u64::MAX as u128)
+                .map_or_else(|| split_mix64(state), |v| v as u64),
+        ),
+        RandomType::U128 => {
+            MoveValue::U128(edge_case(state, std::u128::MAX).unwrap_or_else(|| {
+                ((split_mix64(state) as u128) << 64) | split_mix64(state) as u128
+            }))
+        }
+        RandomType::Address => {
+            let mut bytes = [0u8; AccountAddress::LENGTH];
+            for chunk in bytes.chunks_mut(8) {
+                let len = chunk.len();
+                chunk.copy_from_slice(&split_mix64(state).to_le_bytes()[..len]);
+            }
+            MoveValue::Address(AccountAddress::new(bytes))
+        }
+        RandomType::VectorU8 => {
+            let len = split_mix64(state) % (MAX_RANDOM_VECTOR_LENGTH + 1);
+            MoveValue::vector_u8((0..len).map(|_| split_mix64(state) as u8).collect())
+        }
+    }
+}
+
+/// Render `value` as a Move literal, as the inputs of random tests are shown.
+fn format_argument(value: &MoveValue) -> String {
+    match value {
+        MoveValue::U8(u) => format!("{}", u),
+        MoveValue::U64(u) => format!("{}", u),
+        MoveValue::U128(u) => format!("{}", u),
+        MoveValue::Bool(b) => format!("{}", b),
+        MoveValue::Address(addr) | MoveValue::Signer(addr) => {
+            format!("@0x{}", addr.short_str_lossless())
+        }
+        MoveValue::Vector(elems) if elems.iter().all(|elem| matches!(elem, MoveValue::U8(_))) => {
+            let hex: String = elems
+                .iter()
+                .map(|elem| match elem {
+                    MoveValue::U8(byte) => format!("{:02x}", byte),
+                    _ => unreachable!(),
+                })
+                .collect();
+            format!("x\"{}\"", hex)
+        }
+        other => format!("{:?}", other),
+    }
+}
+
+/// Have the VM keep statistics of the execution of a test in `extensions`. These are only kept
+/// with the `statistics` feature, which makes the VM do some bookkeeping on each instruction.
+#[cfg(feature = "statistics")]
+fn add_execution_statistics(extensions: &mut NativeContextExtensions) {
+    extensions.add(ExecutionStatistics::default());
+}
+
+#[cfg(not(feature = "statistics"))]
+fn add_execution_statistics(_extensions: &mut NativeContextExtensions) {}
+
+/// Record the statistics the VM kept in `extensions` of the execution of a test in `run_info`
+#[cfg(feature = "statistics")]
+fn record_execution_statistics(
+    extensions: &mut NativeContextExtensions,
+    run_info: &mut TestRunInfo,
+) {
+    let statistics = extensions.remove::<ExecutionStatistics>();
+    run_info.max_stack_depth = statistics.max_operand_stack_depth;
+    run_info.values_allocated = statistics.values_allocated;
+}
+
+#[cfg(not(feature = "statistics"))]
+fn record_execution_statistics(
+    _extensions: &mut NativeContextExtensions,
+    _run_info: &mut TestRunInfo,
+) {
+}
+
+/// Remove the ANSI escape sequences coloring `text`
+fn strip_colors(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // Skip the sequence up to its final letter, e.g. `m` in `\x1b[1;31m`
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+/// The number of bytes of the resources written by the changes `cs`
+fn resource_bytes_written(cs: &ChangeSet) -> u64 {
+    cs.accounts()
+        .values()
+        .flat_map(|account_state| account_state.resources().values())
+        .map(|resource_opt| resource_opt.as_ref().map_or(0, |blob| blob.len() as u64))
+        .sum()
+}
+
 /// Print the updates to storage represented by `cs` in the context of the starting storage state
-/// `storage`.
-fn print_resources(cs: &ChangeSet, storage: &InMemoryStorage) -> Result<String> {
+/// `storage`: the resources created (`+`), deleted (`-`), and modified (the old value followed by
+/// the new one).
+fn print_resource_changes(cs: &ChangeSet, storage: &InMemoryStorage) -> Result<String> {
     use std::fmt::Write;
     let mut buf = String::new();
     let annotator = MoveValueAnnotator::new(storage);
     for (account_addr, account_state) in cs.accounts() {
-        writeln!(&mut buf, "0x{}:", account_addr.short_str_lossless())?;
-
+        let mut account_buf = String::new();
         for (tag, resource_opt) in account_state.resources() {
+            let old_resource = storage.get_resource(account_addr, tag).ok().flatten();
+            if old_resource.as_ref() == resource_opt.as_ref() {
+                continue;
+            }
+            if let Some(old_resource) = &old_resource {
+                writeln!(
+                    &mut account_buf,
+                    "\t{}",
+                    format!("- {}", annotator.view_resource(tag, old_resource)?)
+                        .replace("\n", "\n\t")
+                )?;
+            }
             if let Some(resource) = resource_opt {
                 writeln!(
-                    &mut buf,
+                    &mut account_buf,
                     "\t{}",
-                    format!("=> {}", annotator.view_resource(tag, resource)?).replace("\n", "\n\t")
+                    format!("+ {}", annotator.view_resource(tag, resource)?).replace("\n", "\n\t")
                 )?;
             }
         }
+        if !account_buf.is_empty() {
+            writeln!(&mut buf, "0x{}:", account_addr.short_str_lossless())?;
+            buf.push_str(&account_buf);
+        }
+    }
+
+    Ok(buf)
+}
+
+/// Print all resources in the storage state `storage` after applying the updates represented by
+/// `cs`.
+fn print_resources(cs: &ChangeSet, storage: &InMemoryStorage) -> Result<String> {
+    use std::fmt::Write;
+    let mut buf = String::new();
+    let mut final_storage = storage.clone();
+    final_storage.apply(cs.clone())?;
+    let annotator = MoveValueAnnotator::new(&final_storage);
+    for account_addr in final_storage.addresses() {
+        let mut resources = final_storage.resources_at(account_addr).peekable();
+        if resources.peek().is_none() {
+            continue;
+        }
+        writeln!(&mut buf, "0x{}:", account_addr.short_str_lossless())?;
+        for (tag, resource) in resources {
+            writeln!(
+                &mut buf,
+                "\t{}",
+                format!("=> {}", annotator.view_resource(tag, resource)?).replace("\n", "\n\t")
+            )?;
+        }
     }
 
     Ok(buf)
 }
 
+/// Serialize all resources in the storage state `storage` after applying the updates represented
+/// by `cs` to JSON, keyed by address and then by type.
+fn resources_to_json(cs: &ChangeSet, storage: &InMemoryStorage) -> Result<serde_json::Value> {
+    let mut final_storage = storage.clone();
+    final_storage.apply(cs.clone())?;
+    let annotator = MoveValueAnnotator::new(&final_storage);
+    let mut accounts = serde_json::Map::new();
+    for account_addr in final_storage.addresses() {
+        let mut resources = serde_json::Map::new();
+        for (tag, resource) in final_storage.resources_at(account_addr) {
+            let value = AnnotatedMoveValue::Struct(annotator.view_resource(tag, resource)?);
+            resources.insert(tag.to_string(), value_to_json(&value));
+        }
+        if !resources.is_empty() {
+            accounts.insert(
+                format!("0x{}", account_addr.short_str_lossless()),
+                serde_json::Value::Object(resources),
+            );
+        }
+    }
+    Ok(serde_json::Value::Object(accounts))
+}
+
+/// Convert a Move value to JSON. Integers which may not fit into a JSON number and byte vectors
+/// are represented as strings.
+fn value_to_json(value: &AnnotatedMoveValue) -> serde_json::Value {
+    use serde_json::Value;
+    match value {
+        AnnotatedMoveValue::U8(v) => Value::from(*v),
+        AnnotatedMoveValue::U64(v) => Value::from(*v),
+        AnnotatedMoveValue::U128(v) => Value::from(v.to_string()),
+        AnnotatedMoveValue::Bool(v) => Value::from(*v),
+        AnnotatedMoveValue::Address(addr) => {
+            Value::from(format!("0x{}", addr.short_str_lossless()))
+        }
+        AnnotatedMoveValue::Vector(_, elems) => {
+            Value::Array(elems.iter().map(value_to_json).collect())
+        }
+        AnnotatedMoveValue::Bytes(bytes) => Value::from(format!(
+            "0x{}",
+            bytes
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>()
+        )),
+        AnnotatedMoveValue::Struct(s) => Value::Object(
+            s.value
+                .iter()
+                .map(|(field, value)| (field.to_string(), value_to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Write the storage state at the end of the failing test `function_name` of `module_id` to a
+/// JSON file in `dir`.
+fn write_state_file(
+    dir: &str,
+    module_id: &ModuleId,
+    function_name: &str,
+    cs: &ChangeSet,
+    storage: &InMemoryStorage,
+) -> Result<()> {
+    let json = resources_to_json(cs, storage)?;
+    fs::create_dir_all(dir)?;
+    let file_name = format!(
+        "0x{}.{}.{}.json",
+        module_id.address().short_str_lossless(),
+        module_id.name(),
+        function_name
+    );
+    fs::write(
+        Path::new(dir).join(file_name),
+        serde_json::to_string_pretty(&json)?,
+    )?;
+    Ok(())
+}
+
+/// Run the Move Prover over the specs of the modules of `source_files`. Returns the errors of the
+/// prover if any of them cannot be verified.
+fn verify(
+    source_files: &[String],
+    dep_files: &[String],
+    named_address_values: &BTreeMap<String, NumericalAddress>,
+) -> Option<String> {
+    let mut options = ProverOptions {
+        move_sources: source_files.to_vec(),
+        move_deps: dep_files.to_vec(),
+        move_named_address_values: named_addresses_for_options(named_address_values),
+        ..ProverOptions::default()
+    };
+    options.set_quiet();
+    // The Boogie output of each run is kept apart, as several runs may verify at the same time
+    let output_dir = std::env::temp_dir().join("move-unit-test");
+    if let Err(err) = fs::create_dir_all(&output_dir) {
+        return Some(format!(
+            "Unable to create {}: {}",
+            output_dir.display(),
+            err
+        ));
+    }
+    options.output_path = output_dir
+        .join(format!("output-{}.bpl", std::process::id()))
+        .to_string_lossy()
+        .to_string();
+
+    let mut error_writer = Buffer::no_color();
+    match run_move_prover(&mut error_writer, options) {
+        Ok(()) => None,
+        Err(err) => Some(format!(
+            "{}{}",
+            String::from_utf8_lossy(error_writer.as_slice()),
+            err
+        )),
+    }
+}
+
 impl TestRunner {
     pub fn new(
         execution_bound: u64,
         num_threads: usize,
         check_stackless_vm: bool,
+        stackless_instruction_tolerance: Option<f64>,
         verbose: bool,
         save_storage_state_on_failure: bool,
+        full_storage_state_on_failure: bool,
         tests: TestPlan,
         native_function_table: Option<NativeFunctionTable>,
         named_address_values: BTreeMap<String, NumericalAddress>,
@@ -129,6 +830,17 @@ impl TestRunner {
             .collect();
         let modules = tests.module_info.values().map(|info| &info.module);
         let starting_storage_state = setup_test_storage(modules)?;
+        // Collected before tests are selected, as the invariants of all modules are checked
+        let invariants = tests
+            .module_tests
+            .values()
+            .flat_map(|module_test| {
+                module_test
+                    .invariants
+                    .iter()
+                    .map(move |name| (module_test.module_id.clone(), name.clone()))
+            })
+            .collect();
         let native_function_table = native_function_table.unwrap_or_else(|| {
             move_stdlib::natives::all_natives(AccountAddress::from_hex_literal("0x1").unwrap())
         });
@@ -136,171 +848,1170 @@ impl TestRunner {
         Ok(Self {
             testing_config: SharedTestingConfig {
                 save_storage_state_on_failure,
+                full_storage_state_on_failure,
+                state_output_dir: None,
+                snapshot_dir: None,
+                update_baseline: false,
                 starting_storage_state,
                 execution_bound,
                 native_function_table,
+                native_extensions: None,
                 cost_table: unit_cost_table(num_of_native_funcs),
+                metered: false,
+                vm_config: VMConfig::default(),
                 source_files,
                 check_stackless_vm,
+                stackless_instruction_tolerance,
+                stackless_only: false,
                 verbose,
                 named_address_values,
+                cancelled: None,
+                fail_fast: false,
+                timeout: None,
+                retries: 0,
+                isolated: false,
+                isolated_run: None,
+                genesis: None,
+                shuffle_seed: None,
+                random_seed: 0,
+                invariants,
+                check_invariants: false,
+                capture_output: true,
+                warnings: Arc::new(Mutex::new(vec![])),
             },
             num_threads,
             tests,
+            prover_check: None,
         })
     }
 
-    pub fn run<W: Write + Send>(self, writer: &Mutex<W>) -> Result<TestResults> {
-        rayon::ThreadPoolBuilder::new()
+    /// Run the tests, reporting the progress and the results of the run to `reporter`. The
+    /// hooks of the reporter are called from the threads running the tests.
+    pub fn run(mut self, reporter: &dyn TestReporter) -> Result<TestResults> {
+        if self.testing_config.isolated {
+            let run = IsolatedRun::new(&self.tests, &self.testing_config.isolated_settings())
+                .map_err(|err| anyhow::anyhow!("Unable to run the tests in isolation: {}", err))?;
+            self.testing_config.isolated_run = Some(Arc::new(run));
+        }
+        reporter.suite_started(
+            self.tests
+                .module_tests
+                .values()
+                .map(|test_plan| test_plan.tests.len())
+                .sum(),
+        )?;
+        // The worker threads record their spans with the subscriber and under the span of the
+        // calling thread
+        let dispatch = dispatcher::get_default(|dispatch| dispatch.clone());
+        let suite_span = Span::current();
+        // The tests of a module are split into parts which the idle threads of the pool steal
+        // from the others, so that a large module does not keep a single thread busy. Modules are
+        // not split when cross checked with the stackless VM, as the model of the stackless VM
+        // is built for each part.
+        let num_parts =
+            if self.testing_config.check_stackless_vm || self.testing_config.stackless_only {
+                1
+            } else {
+                self.num_threads
+            };
+        let final_statistics = rayon::ThreadPoolBuilder::new()
             .num_threads(self.num_threads)
             .build()
             .unwrap()
             .install(|| {
-                let final_statistics = self
-                    .tests
-                    .module_tests
-                    .par_iter()
-                    .map(|(_, test_plan)| self.testing_config.exec_module_tests(test_plan, writer))
-                    .reduce(TestStatistics::new, |acc, stats| acc.combine(stats));
-
-                Ok(TestResults::new(final_statistics, self.tests))
-            })
+                let mut module_tests: Vec<_> = self.tests.module_tests.values().collect();
+                if let Some(seed) = self.testing_config.shuffle_seed {
+                    shuffle(&mut module_tests, seed);
+                }
+                module_tests
+                    .into_iter()
+                    .flat_map(|test_plan| split_module_tests(test_plan, num_parts))
+                    .collect::<Vec<_>>()
+                    .into_par_iter()
+                    .with_max_len(1)
+                    .map(|test_plan| {
+                        let _dispatch_guard = dispatcher::set_default(&dispatch);
+                        let module_span = info_span!(
+                            parent: &suite_span,
+                            "module",
+                            module = %format_module_id(&test_plan.module_id)
+                        );
+                        let _module_guard = module_span.enter();
+                        self.testing_config
+                            .exec_module_tests(&test_plan, &self.tests, reporter)
+                    })
+                    .reduce(TestStatistics::new, |acc, stats| acc.combine(stats))
+            });
+
+        let verification_failure = match &self.prover_check {
+            Some((source_files, dep_files)) if final_statistics.num_failed() == 0 => verify(
+                source_files,
+                dep_files,
+                &self.testing_config.named_address_values,
+            ),
+            _ => None,
+        };
+        let test_results = TestResults::new(final_statistics, self.tests)
+            .with_shuffle_seed(self.testing_config.shuffle_seed)
+            .with_verification_failure(verification_failure);
+        reporter.summary(&test_results)?;
+        Ok(test_results)
     }
 
-    pub fn filter(&mut self, test_name_slice: &str) {
-        for (module_id, module_test) in self.tests.module_tests.iter_mut() {
-            if module_id.name().as_str().contains(test_name_slice) {
-                continue;
-            } else {
-                let tests = std::mem::take(&mut module_test.tests);
-                module_test.tests = tests
-                    .into_iter()
-                    .filter(|(test_name, _)| test_name.as_str().contains(test_name_slice))
-                    .collect();
+    /// Report the tests that would be run without executing them. Returns `false` if a native
+    /// function declared by a module in the test plan has no implementation.
+    pub fn dry_run<W: Write>(self, writer: &Mutex<W>) -> Result<bool> {
+        let mut writer = writer.lock().unwrap();
+        let mut num_tests = 0;
+        for (module_id, test_plan) in &self.tests.module_tests {
+            for (test_name, test_info) in &test_plan.tests {
+                let expectation = match &test_info.expected_failure {
+                    None => "".to_string(),
+                    Some(ExpectedFailure::Expected) => " (expected failure)".to_string(),
+                    Some(ExpectedFailure::ExpectedWithCode(code)) => {
+                        format!(" (expected failure with code {})", code)
+                    }
+                    Some(ExpectedFailure::ExpectedWithCodeAndLocation(code, location)) => format!(
+                        " (expected failure with code {} in {})",
+                        code,
+                        expected_abort_location(location)
+                    ),
+                };
+                let fixture = match &test_info.fixture {
+                    None => "".to_string(),
+                    Some(fixture) => format!(" (fixture {})", fixture),
+                };
+                writeln!(
+                    writer,
+                    "[ {}     ] {}::{}{}{}",
+                    "RUN".bold(),
+                    format_module_id(module_id),
+                    test_name,
+                    expectation,
+                    fixture
+                )?;
+                num_tests += 1;
+            }
+        }
+
+        let natives: BTreeSet<_> = self
+            .testing_config
+            .native_function_table
+            .iter()
+            .map(|(addr, module_name, func_name, _)| {
+                (*addr, module_name.clone(), func_name.clone())
+            })
+            .collect();
+        let mut missing_natives = vec![];
+        for unit in self.tests.module_info.values() {
+            let module = &unit.module;
+            for function_def in module.function_defs() {
+                if function_def.is_native() {
+                    let handle = module.function_handle_at(function_def.function);
+                    let func_name = module.identifier_at(handle.name);
+                    let module_id = module.self_id();
+                    if !natives.contains(&(
+                        *module_id.address(),
+                        module_id.name().to_owned(),
+                        func_name.to_owned(),
+                    )) {
+                        missing_natives.push(format!(
+                            "{}::{}",
+                            format_module_id(&module_id),
+                            func_name
+                        ));
+                    }
+                }
             }
         }
+        for missing_native in &missing_natives {
+            writeln!(
+                writer,
+                "{}: no implementation for native function {}",
+                "error".bold().bright_red(),
+                missing_native
+            )?;
+        }
+
+        writeln!(
+            writer,
+            "Dry run: {}. Tests that would be run: {}; missing native functions: {}",
+            if missing_natives.is_empty() {
+                "OK".bold().bright_green()
+            } else {
+                "FAILED".bold().bright_red()
+            },
+            num_tests,
+            missing_natives.len()
+        )?;
+        Ok(missing_natives.is_empty())
     }
-}
 
-impl SharedTestingConfig {
-    fn execute_via_move_vm(
-        &self,
-        test_plan: &ModuleTestPlan,
-        function_name: &str,
-        test_info: &TestCase,
-    ) -> (VMResult<ChangeSet>, VMResult<Vec<Vec<u8>>>, TestRunInfo) {
-        let move_vm = MoveVM::new(self.native_function_table.clone()).unwrap();
-        let mut session = move_vm.new_session(&self.starting_storage_state);
-        let mut gas_meter = GasStatus::new(&self.cost_table, GasUnits::new(self.execution_bound));
-        // TODO: collect VM logs if the verbose flag (i.e, `self.verbose`) is set
+    /// Stop the run once `cancelled` is set: the tests which have not started by then are not
+    /// run, and are reported as skipped.
+    pub fn set_cancellation_flag(&mut self, cancelled: Arc<AtomicBool>) {
+        self.testing_config.cancelled = Some(cancelled);
+    }
 
-        let now = Instant::now();
-        let return_result = session.execute_function(
-            &test_plan.module_id,
-            IdentStr::new(function_name).unwrap(),
-            vec![], // no ty args, at least for now
-            serialize_values(test_info.arguments.iter()),
-            &mut gas_meter,
-        );
-        let test_run_info = TestRunInfo::new(
-            function_name.to_string(),
-            now.elapsed(),
-            self.execution_bound - gas_meter.remaining_gas().get(),
-        );
-        (
-            session.finish().map(|(cs, _)| cs),
-            return_result,
-            test_run_info,
-        )
+    /// Stop the run after the first failing test: the tests which have not started by then are
+    /// not run, and are reported as skipped.
+    pub fn set_fail_fast(&mut self) {
+        self.testing_config.fail_fast = true;
+        self.testing_config
+            .cancelled
+            .get_or_insert_with(|| Arc::new(AtomicBool::new(false)));
     }
 
-    fn execute_via_stackless_vm(
-        &self,
-        env: &GlobalEnv,
-        test_plan: &ModuleTestPlan,
-        function_name: &str,
-        test_info: &TestCase,
-    ) -> (
-        VMResult<ChangeSet>,
-        VMResult<Vec<Vec<u8>>>,
-        TestRunInfo,
-        Option<String>,
-    ) {
-        let now = Instant::now();
+    /// Run each failing test again up to `retries` times before reporting it as failed. The
+    /// tests which pass on a retry are reported as flaky.
+    pub fn set_retries(&mut self, retries: usize) {
+        self.testing_config.retries = retries;
+    }
 
-        let settings = if self.verbose {
-            InterpreterSettings::verbose_default()
-        } else {
-            InterpreterSettings::default()
-        };
-        let interpreter = StacklessBytecodeInterpreter::new(env, None, settings);
+    /// After each test passes, run the functions declared with #[test_invariant] against the
+    /// storage state it left, and fail the test if any of them aborts.
+    pub fn set_invariant_checks(&mut self) {
+        self.testing_config.check_invariants = true;
+    }
 
-        // NOTE: as of now, `self.starting_storage_state` contains modules only and no resources.
-        // The modules are captured by `env: &GlobalEnv` and the default GlobalState captures the
-        // empty-resource state.
-        let global_state = GlobalState::default();
-        let (return_result, change_set, _) = interpreter.interpret(
-            &test_plan.module_id,
-            IdentStr::new(function_name).unwrap(),
-            &[], // no ty args, at least for now
-            &test_info.arguments,
-            &global_state,
-        );
-        let prop_check_result = interpreter.report_property_checking_results();
+    /// Run the tests on the stackless VM only, rather than on the Move VM. The stackless VM does
+    /// not simulate storage faults nor emit events.
+    pub fn set_stackless_only(&mut self) {
+        self.testing_config.stackless_only = true;
+    }
 
-        let test_run_info = TestRunInfo::new(
-            function_name.to_string(),
-            now.elapsed(),
-            // NOTE (mengxu) instruction counting on stackless VM might not be very useful because
-            // gas is not charged against stackless VM instruction.
-            0,
-        );
-        (
-            Ok(change_set),
-            return_result,
-            test_run_info,
-            prop_check_result,
-        )
+    /// Print the output of the `debug` natives as the tests run, rather than capture it with each
+    /// test and only report it for the tests which fail.
+    pub fn set_nocapture(&mut self) {
+        self.testing_config.capture_output = false;
     }
 
-    fn exec_module_tests<W: Write>(
-        &self,
-        test_plan: &ModuleTestPlan,
-        writer: &Mutex<W>,
+    /// Once all tests pass, run the Move Prover over the specs of the modules of `source_files`,
+    /// which may use the modules of `dep_files`, and fail the run if any of them cannot be
+    /// verified.
+    pub fn set_prover_check(&mut self, source_files: Vec<String>, dep_files: Vec<String>) {
+        self.prover_check = Some((source_files, dep_files));
+    }
+
+    /// Meter each test with `cost_table` instead of bounding it by a number of instructions, and
+    /// report the gas it uses. A test using more than `gas_limit` units of gas is reported as
+    /// having run out of gas.
+    pub fn set_gas_schedule(&mut self, cost_table: CostTable, gas_limit: u64) {
+        self.testing_config.cost_table = cost_table;
+        self.testing_config.execution_bound = gas_limit;
+        self.testing_config.metered = true;
+    }
+
+    /// Run the tests on a Move VM enforcing the limits of `vm_config`, e.g. to exercise the
+    /// behavior of code near the limits of a production VM.
+    pub fn set_vm_config(&mut self, vm_config: VMConfig) {
+        self.testing_config.vm_config = vm_config;
+    }
+
+    /// Give the natives of each test session the extensions created by `factory`, which is
+    /// called once for the fixture of each test group and once for each run of each test.
+    pub fn set_native_extensions(&mut self, factory: NativeExtensionsFactory) {
+        self.testing_config.native_extensions = Some(factory);
+    }
+
+    /// Run each test in a process of its own, so that a crash while running a test only fails
+    /// that test. The test processes run the current executable again, whose `main` must hand
+    /// them to `run_isolated_test_process`.
+    pub fn set_isolation(&mut self) {
+        self.testing_config.isolated = true;
+    }
+
+    /// Run the test `test_name` of the isolated run with the directory `dir`, with the natives of
+    /// the executable, and exit once its result is written.
+    pub(crate) fn run_isolated_test(
+        dir: &Path,
+        test_name: &str,
+        native_function_table: Option<NativeFunctionTable>,
+        native_extensions: Option<NativeExtensionsFactory>,
+    ) -> ! {
+        let test_runner = isolation::read_run(dir).and_then(|(test_plan, settings)| {
+            Self::from_isolated_settings(
+                test_plan,
+                settings,
+                native_function_table,
+                native_extensions,
+            )
+        });
+        match test_runner {
+            Ok(test_runner) => test_runner
+                .testing_config
+                .run_isolated_test(&test_runner.tests, test_name),
+            Err(err) => {
+                isolation::write_error(&err.to_string());
+                std::process::exit(1)
+            }
+        }
+    }
+
+    /// The runner of a test process of an isolated run, as set up by the run.
+    fn from_isolated_settings(
+        test_plan: TestPlan,
+        settings: IsolatedSettings,
+        native_function_table: Option<NativeFunctionTable>,
+        native_extensions: Option<NativeExtensionsFactory>,
+    ) -> Result<Self> {
+        let named_address_values = settings
+            .named_address_values
+            .iter()
+            .map(|(name, address)| {
+                let address = NumericalAddress::parse_str(address)
+                    .map_err(|err| anyhow::anyhow!("Invalid address {}: {}", address, err))?;
+                Ok((name.clone(), address))
+            })
+            .collect::<Result<_>>()?;
+        let mut test_runner = Self::new(
+            settings.execution_bound,
+            1,
+            settings.check_stackless_vm,
+            settings.stackless_instruction_tolerance,
+            settings.verbose,
+            settings.save_storage_state_on_failure,
+            settings.full_storage_state_on_failure,
+            test_plan,
+            native_function_table,
+            named_address_values,
+        )?;
+        if let Some(genesis) = settings.genesis {
+            test_runner.set_genesis(genesis);
+        }
+        let config = &mut test_runner.testing_config;
+        config.cost_table = settings.cost_table;
+        config.metered = settings.metered;
+        config.vm_config = VMConfig {
+            max_call_depth: settings.max_call_depth,
+            max_value_depth: settings.max_value_depth,
+            max_vector_length: settings.max_vector_length,
+        };
+        config.native_extensions = native_extensions;
+        config.state_output_dir = settings.state_output_dir;
+        config.stackless_only = settings.stackless_only;
+        config.retries = settings.retries;
+        config.random_seed = settings.random_seed;
+        // The invariants of modules which are not tested are not in the plan of the run
+        config.invariants = settings.invariants;
+        config.check_invariants = settings.check_invariants;
+        config.capture_output = settings.capture_output;
+        Ok(test_runner)
+    }
+
+    /// Run the modules, and the tests of each module, in a random order determined by `seed`.
+    pub fn set_shuffle_seed(&mut self, seed: u64) {
+        self.testing_config.shuffle_seed = Some(seed);
+    }
+
+    /// Generate the inputs of random tests from `seed`, so that a failing run can be reproduced.
+    pub fn set_random_seed(&mut self, seed: u64) {
+        self.testing_config.random_seed = seed;
+    }
+
+    /// Abandon each test which does not complete within `timeout`, and report it as timed out.
+    /// Unlike the instruction bound, this also applies to tests hanging in natives or in the
+    /// stackless VM.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.testing_config.timeout = Some(timeout);
+    }
+
+    /// Start each test from the modules and resources of `genesis`, in addition to the modules
+    /// compiled for the tests. A module of the genesis which is also compiled for the tests is
+    /// replaced by its compiled version, so that upgrades can be tested against existing state.
+    pub fn set_genesis(&mut self, genesis: Genesis) {
+        self.testing_config.genesis = Some(genesis.clone());
+        let storage = &mut self.testing_config.starting_storage_state;
+        for (module_id, blob) in genesis.modules {
+            if let Ok(None) = storage.get_module(&module_id) {
+                storage.publish_or_overwrite_module(module_id, blob)
+            }
+        }
+        for (address, struct_tag, blob) in genesis.resources {
+            storage.publish_or_overwrite_resource(address, struct_tag, blob)
+        }
+    }
+
+    /// Write the storage state at the end of each failing test to a JSON file in `dir`.
+    pub fn set_state_output_dir(&mut self, dir: String) {
+        self.testing_config.state_output_dir = Some(dir);
+    }
+
+    /// Write the outcome of each test, with the failure and the storage state at the point of
+    /// failure if it fails, to a `.exp` file in `dir`, and fail the tests whose outcome differs
+    /// from the existing file. The files are overwritten instead if `update_baseline` is set.
+    /// The snapshots are written without colors, whether or not the output is colored.
+    pub fn set_snapshot_dir(&mut self, dir: String, update_baseline: bool) {
+        self.testing_config.save_storage_state_on_failure = true;
+        self.testing_config.snapshot_dir = Some(dir);
+        self.testing_config.update_baseline = update_baseline;
+    }
+
+    /// Keep only the tests of shard `index` out of `count`, numbered from 1. Each test is
+    /// assigned to a shard by hashing its fully qualified name, so that the partition does not
+    /// depend on the machine or on the other tests of the plan.
+    pub fn shard(&mut self, index: usize, count: usize) {
+        for (module_id, module_test) in self.tests.module_tests.iter_mut() {
+            let module_name = format_module_id(module_id);
+            let tests = std::mem::take(&mut module_test.tests);
+            module_test.tests = tests
+                .into_iter()
+                .filter(|(test_name, _)| {
+                    let qualified_name = format!("{}::{}", module_name, test_name);
+                    fnv1a_hash(qualified_name.as_bytes()) % count as u64 == (index - 1) as u64
+                })
+                .collect();
+        }
+    }
+
+    /// Keep only the tests named in `test_names`, which are qualified by their module, as in
+    /// `0x1::M::test`.
+    pub fn select(&mut self, test_names: &BTreeSet<String>) {
+        for (module_id, module_test) in self.tests.module_tests.iter_mut() {
+            let module_name = format_module_id(module_id);
+            let tests = std::mem::take(&mut module_test.tests);
+            module_test.tests = tests
+                .into_iter()
+                .filter(|(test_name, _)| {
+                    test_names.contains(&format!("{}::{}", module_name, test_name))
+                })
+                .collect();
+        }
+    }
+
+    /// Keep only the tests of the modules in `modules` or published at one of `addresses`.
+    pub fn select_modules(
+        &mut self,
+        modules: &BTreeSet<ModuleId>,
+        addresses: &BTreeSet<AccountAddress>,
+    ) {
+        self.tests.module_tests.retain(|module_id, _| {
+            modules.contains(module_id) || addresses.contains(module_id.address())
+        });
+    }
+
+    /// Keep only the tests whose qualified name, as in `0x1::M::test`, matches one of the
+    /// `include` regexes, or any test if there are none, and none of the `exclude` regexes.
+    pub fn filter(&mut self, include: &[Regex], exclude: &[Regex]) {
+        for (module_id, module_test) in self.tests.module_tests.iter_mut() {
+            let module_name = format_module_id(module_id);
+            let tests = std::mem::take(&mut module_test.tests);
+            module_test.tests = tests
+                .into_iter()
+                .filter(|(test_name, _)| {
+                    let qualified_name = format!("{}::{}", module_name, test_name);
+                    (include.is_empty() || include.iter().any(|re| re.is_match(&qualified_name)))
+                        && !exclude.iter().any(|re| re.is_match(&qualified_name))
+                })
+                .collect();
+        }
+    }
+
+    /// Keep only the tests tagged with one of `include`, or any test if it is empty, and with
+    /// none of `exclude`.
+    pub fn filter_tags(&mut self, include: &[String], exclude: &[String]) {
+        for module_test in self.tests.module_tests.values_mut() {
+            let tests = std::mem::take(&mut module_test.tests);
+            module_test.tests = tests
+                .into_iter()
+                .filter(|(_, test_case)| {
+                    (include.is_empty() || include.iter().any(|tag| test_case.tags.contains(tag)))
+                        && !exclude.iter().any(|tag| test_case.tags.contains(tag))
+                })
+                .collect();
+        }
+    }
+}
+
+impl SharedTestingConfig {
+    /// The settings the test processes of an isolated run are set up with
+    fn isolated_settings(&self) -> IsolatedSettings {
+        IsolatedSettings {
+            execution_bound: self.execution_bound,
+            cost_table: self.cost_table.clone(),
+            metered: self.metered,
+            max_call_depth: self.vm_config.max_call_depth,
+            max_value_depth: self.vm_config.max_value_depth,
+            max_vector_length: self.vm_config.max_vector_length,
+            genesis: self.genesis.clone(),
+            named_address_values: self
+                .named_address_values
+                .iter()
+                .map(|(name, address)| (name.clone(), address.to_string()))
+                .collect(),
+            save_storage_state_on_failure: self.save_storage_state_on_failure,
+            full_storage_state_on_failure: self.full_storage_state_on_failure,
+            state_output_dir: self.state_output_dir.clone(),
+            check_stackless_vm: self.check_stackless_vm,
+            stackless_instruction_tolerance: self.stackless_instruction_tolerance,
+            stackless_only: self.stackless_only,
+            verbose: self.verbose,
+            retries: self.retries,
+            random_seed: self.random_seed,
+            invariants: self.invariants.clone(),
+            check_invariants: self.check_invariants,
+            capture_output: self.capture_output,
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled
+            .as_ref()
+            .map_or(false, |cancelled| cancelled.load(Ordering::Relaxed))
+    }
+
+    /// The tests of `test_plan` in the order they are run in. Each module is shuffled with a seed
+    /// of its own, so that the order does not depend on which thread runs the module. Tests are
+    /// then moved after the tests they depend on.
+    fn test_order<'a>(&self, test_plan: &'a ModuleTestPlan) -> Vec<(&'a String, &'a TestCase)> {
+        let mut tests: Vec<_> = test_plan.tests.iter().collect();
+        if let Some(seed) = self.shuffle_seed {
+            let module_name = format_module_id(&test_plan.module_id);
+            shuffle(&mut tests, seed ^ fnv1a_hash(module_name.as_bytes()));
+        }
+        order_by_dependencies(&tests)
+    }
+
+    fn cancel(&self) {
+        if let Some(cancelled) = &self.cancelled {
+            cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Record a warning, which is reported with the result of the test it concerns
+    fn warn(&self, message: String) {
+        self.warnings.lock().unwrap().push(message);
+    }
+
+    /// The warnings recorded since the last call
+    fn take_warnings(&self) -> Vec<String> {
+        std::mem::take(&mut *self.warnings.lock().unwrap())
+    }
+
+    /// The native context extensions of a new session
+    fn new_native_extensions(&self) -> NativeContextExtensions {
+        self.native_extensions
+            .as_ref()
+            .map_or_else(NativeContextExtensions::default, |factory| factory())
+    }
+
+    /// The native context extensions of a new session executing a test in `test_context`, which
+    /// also capture the debug output of the test unless it is printed as it goes
+    fn new_test_extensions(&self, test_context: UnitTestContext) -> NativeContextExtensions {
+        let mut extensions = self.new_native_extensions();
+        extensions.add(test_context);
+        if self.capture_output {
+            extensions.add(CapturedOutput::default());
+        }
+        extensions
+    }
+
+    /// Run the fixture of a test group once on the starting storage state, and capture the
+    /// resulting state for the tests of the group.
+    fn execute_fixture(
+        &self,
+        env: Option<&GlobalEnv>,
+        test_plan: &ModuleTestPlan,
+        fixture_name: &str,
+    ) -> VMResult<FixtureState> {
+        let _fixture_guard = info_span!("fixture", fixture = fixture_name).entered();
+        let function_name = IdentStr::new(fixture_name).unwrap();
+        let move_vm =
+            MoveVM::new_with_config(self.native_function_table.clone(), self.vm_config).unwrap();
+        // A fixture is shared by the tests of its group, so it does not see their configuration
+        let test_context = UnitTestContext {
+            addresses: self.starting_storage_state.addresses().cloned().collect(),
+            ..UnitTestContext::default()
+        };
+        let mut session = move_vm.new_session_with_extensions(
+            &self.starting_storage_state,
+            self.new_test_extensions(test_context),
+        );
+        let mut gas_meter = GasStatus::new(&self.cost_table, GasUnits::new(self.execution_bound));
+
+        session.execute_function(
+            &test_plan.module_id,
+            function_name,
+            vec![],
+            vec![],
+            &mut gas_meter,
+        )?;
+        let (change_set, _) = session.finish()?;
+        let storage = apply_change_set(
+            &self.starting_storage_state,
+            change_set,
+            &test_plan.module_id,
+        )?;
+
+        let global_state = match env {
+            None => GlobalState::default(),
+            Some(env) => {
+                let interpreter =
+                    StacklessBytecodeInterpreter::new(env, None, InterpreterSettings::default());
+                // Diverging fixture executions surface as mismatches in the tests of the group
+                let (_, _, global_state) = interpreter.interpret(
+                    &test_plan.module_id,
+                    function_name,
+                    &[],
+                    &[],
+                    &GlobalState::default(),
+                );
+                global_state
+            }
+        };
+        Ok(FixtureState {
+            storage,
+            global_state,
+        })
+    }
+
+    fn execute_via_move_vm(
+        &self,
+        storage: &InMemoryStorage,
+        test_plan: &ModuleTestPlan,
+        function_name: &str,
+        test_info: &TestCase,
+    ) -> (
+        VMResult<ChangeSet>,
+        TestEvents,
+        VMResult<Vec<Vec<u8>>>,
+        TestRunInfo,
+    ) {
+        let _session_guard = info_span!("vm_session", vm = "move").entered();
+        let move_vm =
+            MoveVM::new_with_config(self.native_function_table.clone(), self.vm_config).unwrap();
+        let faulty_storage = FaultyStorage {
+            storage,
+            faults: &test_info.storage_faults,
+            resource_bytes_read: Cell::new(0),
+        };
+        let mut extensions = self.new_test_extensions(UnitTestContext {
+            config: test_info.env.clone(),
+            addresses: test_addresses(storage, test_info),
+            expected_events: vec![],
+        });
+        add_execution_statistics(&mut extensions);
+        let mut session = move_vm.new_session_with_extensions(&faulty_storage, extensions);
+        let mut gas_meter = GasStatus::new(&self.cost_table, GasUnits::new(self.execution_bound));
+        // TODO: collect VM logs if the verbose flag (i.e, `self.verbose`) is set
+
+        let now = Instant::now();
+        let return_result = session.execute_function(
+            &test_plan.module_id,
+            // The test may be one of the cases of a parameterized test function
+            IdentStr::new(&test_info.test_name).unwrap(),
+            vec![], // no ty args, at least for now
+            serialize_values(test_info.arguments.iter()),
+            &mut gas_meter,
+        );
+        let used = self.execution_bound - gas_meter.remaining_gas().get();
+        let mut test_run_info = if self.metered {
+            let mut test_run_info = TestRunInfo::new(function_name.to_string(), now.elapsed(), 0);
+            test_run_info.gas_used = Some(used);
+            test_run_info
+        } else {
+            TestRunInfo::new(function_name.to_string(), now.elapsed(), used)
+        };
+        let (cs_result, events) = match session.finish_with_extensions() {
+            Ok((cs, emitted, mut extensions)) => {
+                record_execution_statistics(&mut extensions, &mut test_run_info);
+                if self.capture_output {
+                    test_run_info.output = extensions.remove::<CapturedOutput>().0;
+                }
+                test_run_info.storage_bytes =
+                    faulty_storage.resource_bytes_read.get() + resource_bytes_written(&cs);
+                let asserted = extensions.remove::<UnitTestContext>().expected_events;
+                (Ok(cs), TestEvents { emitted, asserted })
+            }
+            Err(err) => (
+                Err(err),
+                TestEvents {
+                    emitted: vec![],
+                    asserted: vec![],
+                },
+            ),
+        };
+        (cs_result, events, return_result, test_run_info)
+    }
+
+    /// Check the events emitted by a test against its #[expected_events] attribute and the events
+    /// it asserts to be emitted, and describe the first discrepancy if there is one.
+    fn check_events(
+        &self,
+        test_info: &TestCase,
+        events: &TestEvents,
+        storage: &InMemoryStorage,
+    ) -> Option<String> {
+        if let Some(expected_events) = &test_info.expected_events {
+            if expected_events.len() != events.emitted.len()
+                || !expected_events
+                    .iter()
+                    .zip(events.emitted.iter())
+                    .all(|((module_id, name), (_, _, tag, _))| is_struct_type(tag, module_id, name))
+            {
+                let expected: Vec<_> = expected_events
+                    .iter()
+                    .map(|(module_id, name)| format!("{}::{}", format_module_id(module_id), name))
+                    .collect();
+                let emitted: Vec<_> = events
+                    .emitted
+                    .iter()
+                    .map(|(_, _, tag, _)| tag.to_string())
+                    .collect();
+                return Some(format!(
+                    "Expected the test to emit events of types [{}] but it emitted events of \
+                     types [{}]",
+                    expected.join(", "),
+                    emitted.join(", ")
+                ));
+            }
+        }
+        for (tag, bytes) in &events.asserted {
+            let is_emitted = events
+                .emitted
+                .iter()
+                .any(|(_, _, emitted_tag, emitted_bytes)| {
+                    emitted_tag == tag && emitted_bytes == bytes
+                });
+            if !is_emitted {
+                let event = MoveValueAnnotator::new(storage)
+                    .view_value(tag, bytes)
+                    .map_or_else(|_| format!("{:?}", bytes), |value| value.to_string());
+                return Some(format!(
+                    "Expected the test to emit this event but it did not:\n{}",
+                    event
+                ));
+            }
+        }
+        None
+    }
+
+    /// Run each invariant on `storage` updated with the changes a test made to it, and return the
+    /// first one which aborts, with its error.
+    fn violated_invariant(
+        &self,
+        storage: &InMemoryStorage,
+        change_set: &ChangeSet,
+    ) -> Option<(String, VMError)> {
+        let _invariants_guard = info_span!("invariants").entered();
+        let move_vm =
+            MoveVM::new_with_config(self.native_function_table.clone(), self.vm_config).unwrap();
+        // Without the state left by the test, none of the invariants can be checked
+        let (first_module_id, first_name) = self.invariants.first()?;
+        let storage = match apply_change_set(storage, change_set.clone(), first_module_id) {
+            Ok(storage) => storage,
+            Err(err) => {
+                let invariant = format!("{}::{}", format_module_id(first_module_id), first_name);
+                return Some((invariant, err));
+            }
+        };
+        for (module_id, name) in &self.invariants {
+            let mut session =
+                move_vm.new_session_with_extensions(&storage, self.new_native_extensions());
+            let mut gas_meter =
+                GasStatus::new(&self.cost_table, GasUnits::new(self.execution_bound));
+            if let Err(err) = session.execute_function(
+                module_id,
+                IdentStr::new(name).unwrap(),
+                vec![],
+                vec![],
+                &mut gas_meter,
+            ) {
+                return Some((format!("{}::{}", format_module_id(module_id), name), err));
+            }
+        }
+        None
+    }
+
+    fn execute_via_stackless_vm(
+        &self,
+        env: &GlobalEnv,
+        global_state: &GlobalState,
+        test_plan: &ModuleTestPlan,
+        function_name: &str,
+        test_info: &TestCase,
+    ) -> (
+        VMResult<ChangeSet>,
+        VMResult<Vec<Vec<u8>>>,
+        TestRunInfo,
+        Option<String>,
+    ) {
+        let _session_guard = info_span!("vm_session", vm = "stackless").entered();
+        let now = Instant::now();
+
+        let settings = if self.verbose {
+            InterpreterSettings::verbose_default()
+        } else {
+            InterpreterSettings::default()
+        };
+        let interpreter = StacklessBytecodeInterpreter::new(env, None, settings);
+
+        let (return_result, change_set, _) = interpreter.interpret(
+            &test_plan.module_id,
+            IdentStr::new(&test_info.test_name).unwrap(),
+            &[], // no ty args, at least for now
+            &test_info.arguments,
+            global_state,
+        );
+        let prop_check_result = interpreter.report_property_checking_results();
+
+        let test_run_info = TestRunInfo::new(
+            function_name.to_string(),
+            now.elapsed(),
+            // NOTE: this counts stackless bytecode instructions, which differ from the Move
+            // bytecode instructions counted by the Move VM (e.g., there are no stack operations).
+            interpreter.instructions_executed(),
+        );
+        (
+            Ok(change_set),
+            return_result,
+            test_run_info,
+            prop_check_result,
+        )
+    }
+
+    /// Compare the outcome of `execution` with its snapshot, if the tests are snapshotted. The
+    /// snapshot is written instead if there is none yet or the baseline is being updated.
+    fn check_snapshot(
+        &self,
+        full_test_plan: &TestPlan,
+        module_id: &ModuleId,
+        execution: TestExecution,
+    ) -> TestExecution {
+        let dir = match &self.snapshot_dir {
+            None => return execution,
+            Some(dir) => dir,
+        };
+        let (test_run_info, snapshot) = match &execution {
+            TestExecution::Passed(test_run_info) => (test_run_info, "PASS\n".to_string()),
+            TestExecution::Failed(failure) | TestExecution::TimedOut(failure) => (
+                &failure.test_run_info,
+                format!(
+                    "FAIL\n{}\n",
+                    strip_colors(&failure.render_error(full_test_plan)).trim_end()
+                ),
+            ),
+        };
+        let path = Path::new(dir).join(format!(
+            "0x{}.{}.{}.{}",
+            module_id.address().short_str_lossless(),
+            module_id.name(),
+            test_run_info.function_ident,
+            EXP_EXT
+        ));
+        let expected = if self.update_baseline {
+            None
+        } else {
+            fs::read_to_string(&path).ok()
+        };
+        match expected {
+            Some(expected) if expected == snapshot => execution,
+            Some(expected) => TestExecution::Failed(TestFailure::new(
+                FailureReason::snapshot_mismatch(
+                    &path.display().to_string(),
+                    format_diff(expected, snapshot),
+                ),
+                test_run_info.clone(),
+                None,
+                None,
+            )),
+            None => {
+                if let Err(err) = fs::create_dir_all(dir).and_then(|_| fs::write(&path, snapshot)) {
+                    self.warn(format!(
+                        "Unable to write the snapshot of test {}: {}",
+                        test_run_info.function_ident, err
+                    ));
+                }
+                execution
+            }
+        }
+    }
+
+    fn exec_module_tests(
+        &self,
+        test_plan: &ModuleTestPlan,
+        full_test_plan: &TestPlan,
+        reporter: &dyn TestReporter,
     ) -> TestStatistics {
         let mut stats = TestStatistics::new();
-        let pass = |fn_name: &str| {
-            writeln!(
-                writer.lock().unwrap(),
-                "[ {}    ] {}::{}",
-                "PASS".bold().bright_green(),
-                format_module_id(&test_plan.module_id),
-                fn_name
-            )
-            .unwrap()
+        let mut report = |execution: TestExecution, mismatch: Option<InstructionCountMismatch>| {
+            let execution = self.check_snapshot(full_test_plan, &test_plan.module_id, execution);
+            for warning in self.take_warnings() {
+                reporter.warning(&warning).unwrap();
+            }
+            if let Some(mismatch) = mismatch {
+                stats.instruction_count_mismatch(mismatch, test_plan);
+            }
+            match execution {
+                TestExecution::Passed(test_run_info) => {
+                    reporter
+                        .test_finished(
+                            full_test_plan,
+                            &test_plan.module_id,
+                            TestOutcome::Passed(&test_run_info),
+                        )
+                        .unwrap();
+                    stats.test_success(test_run_info, test_plan);
+                }
+                TestExecution::Failed(failure) | TestExecution::TimedOut(failure) => {
+                    if self.fail_fast {
+                        self.cancel();
+                    }
+                    reporter
+                        .test_finished(
+                            full_test_plan,
+                            &test_plan.module_id,
+                            TestOutcome::Failed(&failure),
+                        )
+                        .unwrap();
+                    stats.test_failure(failure, test_plan);
+                }
+            }
         };
-        let fail = |fn_name: &str| {
-            writeln!(
-                writer.lock().unwrap(),
-                "[ {}    ] {}::{}",
-                "FAIL".bold().bright_red(),
-                format_module_id(&test_plan.module_id),
-                fn_name,
-            )
-            .unwrap()
+        if let Some(isolated_run) = &self.isolated_run {
+            let mut unmet = BTreeSet::new();
+            for (function_name, test_info) in self.test_order(test_plan) {
+                if self.is_cancelled() {
+                    break;
+                }
+                let _test_guard = info_span!("test", test = function_name.as_str()).entered();
+                if let Some(dependency) = unmet_dependency(test_plan, test_info, &unmet) {
+                    tracing::info!(outcome = "skipped", dependency = dependency.as_str());
+                    unmet.insert(function_name.clone());
+                    continue;
+                }
+                reporter
+                    .test_started(&test_plan.module_id, function_name)
+                    .unwrap();
+                let (execution, mismatch) =
+                    self.execute_isolated_test(isolated_run, test_plan, function_name);
+                tracing::info!(outcome = execution.outcome());
+                if !matches!(execution, TestExecution::Passed(_)) {
+                    unmet.insert(function_name.clone());
+                }
+                report(execution, mismatch);
+            }
+            return stats;
+        }
+
+        let function_names: BTreeSet<String> = test_plan.tests.keys().cloned().collect();
+        let timeout = match self.timeout {
+            None => {
+                let unmet = BTreeSet::new();
+                self.execute_tests(test_plan, &function_names, &unmet, &mut |progress| {
+                    match progress {
+                        TestProgress::Started(function_name) => reporter
+                            .test_started(&test_plan.module_id, &function_name)
+                            .unwrap(),
+                        TestProgress::Finished(_, execution, mismatch) => {
+                            report(execution, mismatch)
+                        }
+                    }
+                    true
+                });
+                return stats;
+            }
+            Some(timeout) => timeout,
         };
-        let timeout = |fn_name: &str| {
-            writeln!(
-                writer.lock().unwrap(),
-                "[ {} ] {}::{}",
-                "TIMEOUT".bold().bright_yellow(),
-                format_module_id(&test_plan.module_id),
-                fn_name,
-            )
-            .unwrap();
+
+        // The tests are run on a separate thread, which is abandoned if a test does not complete
+        // in time. The remaining tests are then run on a new thread, which is told which of the
+        // tests run so far did not pass.
+        let mut remaining = function_names;
+        let mut unmet = BTreeSet::new();
+        while !remaining.is_empty() {
+            let (sender, receiver) = mpsc::channel();
+            let config = self.clone();
+            let worker_test_plan = test_plan.clone();
+            let worker_function_names = remaining.clone();
+            let worker_unmet = unmet.clone();
+            let dispatch = dispatcher::get_default(|dispatch| dispatch.clone());
+            let parent_span = Span::current();
+            let worker = thread::spawn(move || {
+                let _dispatch_guard = dispatcher::set_default(&dispatch);
+                let _span_guard = parent_span.entered();
+                config.execute_tests(
+                    &worker_test_plan,
+                    &worker_function_names,
+                    &worker_unmet,
+                    &mut |progress| sender.send(progress).is_ok(),
+                );
+            });
+            let mut current_test = None;
+            loop {
+                match receiver.recv_timeout(timeout) {
+                    Ok(TestProgress::Started(function_name)) => {
+                        reporter
+                            .test_started(&test_plan.module_id, &function_name)
+                            .unwrap();
+                        current_test = Some(function_name);
+                    }
+                    Ok(TestProgress::Finished(function_name, execution, mismatch)) => {
+                        remaining.remove(&function_name);
+                        current_test = None;
+                        if !matches!(execution, TestExecution::Passed(_)) {
+                            unmet.insert(function_name);
+                        }
+                        report(execution, mismatch);
+                    }
+                    Err(RecvTimeoutError::Timeout) => {
+                        // Still setting up the tests otherwise
+                        if let Some(function_name) = current_test.take() {
+                            tracing::info!(test = function_name.as_str(), outcome = "timeout");
+                            remaining.remove(&function_name);
+                            unmet.insert(function_name.clone());
+                            let failure = TestFailure::new(
+                                FailureReason::timeout(),
+                                TestRunInfo::new(function_name, timeout, 0),
+                                None,
+                                None,
+                            );
+                            report(TestExecution::TimedOut(failure), None);
+                            break;
+                        }
+                    }
+                    Err(RecvTimeoutError::Disconnected) => {
+                        // The thread completed, which leaves no tests to run unless the run was
+                        // cancelled
+                        if let Err(panic) = worker.join() {
+                            std::panic::resume_unwind(panic)
+                        }
+                        remaining.clear();
+                        break;
+                    }
+                }
+            }
+        }
+        stats
+    }
+
+    /// Execute a test in a process of its own, which also enforces the timeout.
+    fn execute_isolated_test(
+        &self,
+        isolated_run: &IsolatedRun,
+        test_plan: &ModuleTestPlan,
+        function_name: &str,
+    ) -> (TestExecution, Option<InstructionCountMismatch>) {
+        match isolation::run_test_process(
+            isolated_run,
+            &test_plan.module_id,
+            function_name,
+            self.timeout,
+        ) {
+            ProcessOutcome::Completed(IsolatedResult {
+                test_run_info,
+                outcome,
+                mismatch,
+                warnings,
+            }) => {
+                for warning in warnings {
+                    self.warn(warning);
+                }
+                let execution = match outcome {
+                    IsolatedOutcome::Passed => TestExecution::Passed(test_run_info),
+                    IsolatedOutcome::Failed(failure) => {
+                        TestExecution::Failed(failure.into_failure(test_run_info))
+                    }
+                    IsolatedOutcome::TimedOut(failure) => {
+                        TestExecution::TimedOut(failure.into_failure(test_run_info))
+                    }
+                };
+                (execution, mismatch)
+            }
+            ProcessOutcome::Crashed(details, elapsed) => {
+                let failure = TestFailure::new(
+                    FailureReason::crashed(&details),
+                    TestRunInfo::new(function_name.to_string(), elapsed, 0),
+                    None,
+                    None,
+                );
+                (TestExecution::Failed(failure), None)
+            }
+            ProcessOutcome::TimedOut(elapsed) => {
+                let failure = TestFailure::new(
+                    FailureReason::timeout(),
+                    TestRunInfo::new(function_name.to_string(), elapsed, 0),
+                    None,
+                    None,
+                );
+                (TestExecution::TimedOut(failure), None)
+            }
+        }
+    }
+
+    /// Run the test with the qualified name `test_name` as the test process of an isolated test
+    /// run, and exit once its result is written.
+    fn run_isolated_test(&self, test_plan: &TestPlan, test_name: &str) -> ! {
+        let module_test = test_plan.module_tests.values().find_map(|module_test| {
+            let function_name = test_name
+                .strip_prefix(&format_module_id(&module_test.module_id))?
+                .strip_prefix("::")?;
+            module_test
+                .tests
+                .contains_key(function_name)
+                .then(|| (module_test, function_name.to_string()))
+        });
+        let (module_test, function_name) = match module_test {
+            Some(module_test) => module_test,
+            None => {
+                isolation::write_error(&format!("No test {} in the test plan", test_name));
+                std::process::exit(1)
+            }
         };
 
-        let stackless_model = if self.check_stackless_vm {
+        let function_names: BTreeSet<String> = std::iter::once(function_name).collect();
+        let mut result = None;
+        self.execute_tests(
+            module_test,
+            &function_names,
+            &BTreeSet::new(),
+            &mut |progress| {
+                if let TestProgress::Finished(_, execution, mismatch) = progress {
+                    let (test_run_info, outcome) = match execution {
+                        TestExecution::Passed(test_run_info) => {
+                            (test_run_info, IsolatedOutcome::Passed)
+                        }
+                        TestExecution::Failed(failure) => (
+                            failure.test_run_info.clone(),
+                            IsolatedOutcome::Failed(IsolatedFailure::new(&failure)),
+                        ),
+                        TestExecution::TimedOut(failure) => (
+                            failure.test_run_info.clone(),
+                            IsolatedOutcome::TimedOut(IsolatedFailure::new(&failure)),
+                        ),
+                    };
+                    result = Some(IsolatedResult {
+                        test_run_info,
+                        outcome,
+                        mismatch,
+                        warnings: self.take_warnings(),
+                    });
+                }
+                true
+            },
+        );
+        match result {
+            Some(result) => {
+                isolation::write_result(&result);
+                std::process::exit(0)
+            }
+            None => {
+                isolation::write_error(&format!("The test {} was not run", test_name));
+                std::process::exit(1)
+            }
+        }
+    }
+
+    /// Execute the tests of `test_plan` named in `function_names`, reporting their progress to
+    /// `on_progress`, which returns `false` if the remaining tests should not be run.
+    fn execute_tests(
+        &self,
+        test_plan: &ModuleTestPlan,
+        function_names: &BTreeSet<String>,
+        unmet: &BTreeSet<String>,
+        on_progress: &mut dyn FnMut(TestProgress) -> bool,
+    ) {
+        let stackless_model = if self.check_stackless_vm || self.stackless_only {
             let model = run_model_builder_with_options_and_compilation_flags(
                 &self.source_files,
                 &[],
@@ -314,162 +2025,548 @@ impl SharedTestingConfig {
             None
         };
 
-        for (function_name, test_info) in &test_plan.tests {
-            let (cs_result, exec_result, test_run_info) =
-                self.execute_via_move_vm(test_plan, function_name, test_info);
-            if self.check_stackless_vm {
-                let (stackless_vm_change_set, stackless_vm_result, _, prop_check_result) = self
-                    .execute_via_stackless_vm(
-                        stackless_model.as_ref().unwrap(),
-                        test_plan,
-                        function_name,
-                        test_info,
-                    );
-                let move_vm_result = adapt_move_vm_result(exec_result.clone());
-                let move_vm_change_set =
-                    adapt_move_vm_change_set(cs_result.clone(), &self.starting_storage_state);
-                if stackless_vm_result != move_vm_result
-                    || stackless_vm_change_set != move_vm_change_set
-                {
-                    fail(function_name);
-                    stats.test_failure(
-                        TestFailure::new(
-                            FailureReason::mismatch(
-                                move_vm_result,
-                                move_vm_change_set,
-                                stackless_vm_result,
-                                stackless_vm_change_set,
-                            ),
-                            test_run_info,
-                            None,
-                            None,
-                        ),
-                        test_plan,
-                    );
+        // NOTE: as of now, `self.starting_storage_state` contains modules only and no resources,
+        // as a genesis state cannot be combined with the stackless VM. The modules are captured by
+        // the stackless model and the default GlobalState captures the empty-resource state.
+        let empty_global_state = GlobalState::default();
+
+        // Each fixture is run once, and the tests of its group start from a copy of its state
+        let fixture_states: BTreeMap<&str, VMResult<FixtureState>> = test_plan
+            .tests
+            .iter()
+            .filter(|(function_name, _)| function_names.contains(*function_name))
+            .filter_map(|(_, test_info)| test_info.fixture.as_deref())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|fixture| {
+                let state = self.execute_fixture(stackless_model.as_ref(), test_plan, fixture);
+                (fixture, state)
+            })
+            .collect();
+
+        // The tests which did not pass, whose dependents are skipped
+        let mut unmet = unmet.clone();
+        for (function_name, test_info) in self.test_order(test_plan) {
+            if self.is_cancelled() {
+                break;
+            }
+            if !function_names.contains(function_name) {
+                continue;
+            }
+            let _test_guard = info_span!("test", test = function_name.as_str()).entered();
+            if let Some(dependency) = unmet_dependency(test_plan, test_info, &unmet) {
+                tracing::info!(outcome = "skipped", dependency = dependency.as_str());
+                unmet.insert(function_name.clone());
+                continue;
+            }
+            if !on_progress(TestProgress::Started(function_name.clone())) {
+                break;
+            }
+            let mut attempts = 0;
+            let (mut execution, mismatch) = loop {
+                attempts += 1;
+                let (execution, mismatch) = self.execute_test(
+                    stackless_model.as_ref(),
+                    &fixture_states,
+                    &empty_global_state,
+                    test_plan,
+                    function_name,
+                    test_info,
+                );
+                match execution {
+                    TestExecution::Failed(_) | TestExecution::TimedOut(_)
+                        if attempts <= self.retries =>
+                    {
+                        tracing::info!(attempt = attempts, outcome = execution.outcome(), "retry");
+                    }
+                    _ => break (execution, mismatch),
+                }
+            };
+            execution.run_info_mut().attempts = attempts;
+            tracing::info!(outcome = execution.outcome());
+            if !matches!(execution, TestExecution::Passed(_)) {
+                unmet.insert(function_name.clone());
+            }
+            if !on_progress(TestProgress::Finished(
+                function_name.clone(),
+                execution,
+                mismatch,
+            )) {
+                break;
+            }
+        }
+    }
+
+    /// Execute a random test `RANDOM_TEST_ITERATIONS` times, each time with newly generated values
+    /// for its random parameters, and stop at the first failing run. The values are drawn from a
+    /// generator seeded with the random seed and the name of the test, so that they do not depend
+    /// on the other tests of the run.
+    #[allow(clippy::too_many_arguments)]
+    fn execute_random_test(
+        &self,
+        stackless_model: Option<&GlobalEnv>,
+        fixture_states: &BTreeMap<&str, VMResult<FixtureState>>,
+        empty_global_state: &GlobalState,
+        test_plan: &ModuleTestPlan,
+        function_name: &str,
+        test_info: &TestCase,
+        random_arguments: &[(String, RandomArgument)],
+    ) -> (TestExecution, Option<InstructionCountMismatch>) {
+        let qualified_name = format!(
+            "{}::{}",
+            format_module_id(&test_plan.module_id),
+            function_name
+        );
+        let mut state = self.random_seed ^ fnv1a_hash(qualified_name.as_bytes());
+        let mut test_run_info = TestRunInfo::new(function_name.to_string(), Duration::default(), 0);
+        let mut last_mismatch = None;
+        for _ in 0..RANDOM_TEST_ITERATIONS {
+            let arguments: Vec<_> = random_arguments
+                .iter()
+                .map(|(_, argument)| match argument {
+                    RandomArgument::Given(value) => value.clone(),
+                    RandomArgument::Random(ty) => random_value(*ty, &mut state),
+                })
+                .collect();
+            let run_case = TestCase {
+                arguments: arguments.clone(),
+                random_arguments: None,
+                ..test_info.clone()
+            };
+            let (execution, mismatch) = self.execute_test(
+                stackless_model,
+                fixture_states,
+                empty_global_state,
+                test_plan,
+                function_name,
+                &run_case,
+            );
+            last_mismatch = mismatch.or(last_mismatch);
+            let random_inputs = || {
+                let inputs: Vec<_> = random_arguments
+                    .iter()
+                    .zip(arguments.iter())
+                    .map(|((name, _), value)| format!("{} = {}", name, format_argument(value)))
+                    .collect();
+                format!(
+                    "Random test failed with seed {} on inputs: {}. Rerun with `--seed {}` to \
+                     reproduce it",
+                    self.random_seed,
+                    inputs.join(", "),
+                    self.random_seed
+                )
+            };
+            let execution = match execution {
+                TestExecution::Passed(run_info) => {
+                    test_run_info.elapsed_time += run_info.elapsed_time;
+                    test_run_info.wall_clock_time += run_info.wall_clock_time;
+                    test_run_info.instructions_executed += run_info.instructions_executed;
+                    test_run_info.max_stack_depth =
+                        test_run_info.max_stack_depth.max(run_info.max_stack_depth);
+                    test_run_info.values_allocated += run_info.values_allocated;
+                    test_run_info.storage_bytes += run_info.storage_bytes;
+                    if let Some(gas_used) = run_info.gas_used {
+                        *test_run_info.gas_used.get_or_insert(0) += gas_used;
+                    }
                     continue;
                 }
-                if let Some(prop_failure) = prop_check_result {
-                    fail(function_name);
-                    stats.test_failure(
-                        TestFailure::new(
-                            FailureReason::property(prop_failure),
-                            test_run_info,
-                            None,
-                            None,
-                        ),
-                        test_plan,
+                TestExecution::Failed(failure) => {
+                    TestExecution::Failed(failure.with_random_inputs(random_inputs()))
+                }
+                TestExecution::TimedOut(failure) => {
+                    TestExecution::TimedOut(failure.with_random_inputs(random_inputs()))
+                }
+            };
+            return (execution, last_mismatch);
+        }
+        (TestExecution::Passed(test_run_info), last_mismatch)
+    }
+
+    /// Execute a single test, starting from the state left by the fixture of its group if any.
+    fn execute_test(
+        &self,
+        stackless_model: Option<&GlobalEnv>,
+        fixture_states: &BTreeMap<&str, VMResult<FixtureState>>,
+        empty_global_state: &GlobalState,
+        test_plan: &ModuleTestPlan,
+        function_name: &str,
+        test_info: &TestCase,
+    ) -> (TestExecution, Option<InstructionCountMismatch>) {
+        if let Some(random_arguments) = &test_info.random_arguments {
+            return self.execute_random_test(
+                stackless_model,
+                fixture_states,
+                empty_global_state,
+                test_plan,
+                function_name,
+                test_info,
+                random_arguments,
+            );
+        }
+        let test_start = Instant::now();
+        let (storage, global_state) = match &test_info.fixture {
+            None => (&self.starting_storage_state, empty_global_state),
+            Some(fixture) => match &fixture_states[fixture.as_str()] {
+                Ok(state) => (&state.storage, &state.global_state),
+                Err(err) => {
+                    let failure = TestFailure::new(
+                        FailureReason::fixture_failed(fixture),
+                        TestRunInfo::new(function_name.to_string(), Duration::default(), 0),
+                        Some(err.clone()),
+                        None,
                     );
-                    continue;
+                    return (TestExecution::Failed(failure), None);
+                }
+            },
+        };
+
+        let (cs_result, events, exec_result, mut test_run_info) = if self.stackless_only {
+            let (cs_result, exec_result, test_run_info, prop_check_result) = self
+                .execute_via_stackless_vm(
+                    stackless_model.unwrap(),
+                    global_state,
+                    test_plan,
+                    function_name,
+                    test_info,
+                );
+            if let Some(prop_failure) = prop_check_result {
+                let failure = TestFailure::new(
+                    FailureReason::property(prop_failure),
+                    test_run_info,
+                    None,
+                    None,
+                );
+                return (TestExecution::Failed(failure), None);
+            }
+            let events = TestEvents {
+                emitted: vec![],
+                asserted: vec![],
+            };
+            (cs_result, events, exec_result, test_run_info)
+        } else {
+            self.execute_via_move_vm(storage, test_plan, function_name, test_info)
+        };
+        test_run_info.wall_clock_time = test_start.elapsed();
+        let mut mismatch = None;
+        // The stackless VM does not simulate storage faults, so there is nothing to compare
+        if self.check_stackless_vm && test_info.storage_faults.is_empty() {
+            let (
+                stackless_vm_change_set,
+                stackless_vm_result,
+                stackless_vm_run_info,
+                prop_check_result,
+            ) = self.execute_via_stackless_vm(
+                stackless_model.unwrap(),
+                global_state,
+                test_plan,
+                function_name,
+                test_info,
+            );
+            test_run_info.wall_clock_time = test_start.elapsed();
+            // Only the totals are compared, as the two VMs count different instructions
+            if let Some(tolerance) = self.stackless_instruction_tolerance {
+                let move_vm_instructions = test_run_info.instructions_executed;
+                let stackless_vm_instructions = stackless_vm_run_info.instructions_executed;
+                let difference =
+                    (move_vm_instructions as f64 - stackless_vm_instructions as f64).abs();
+                if difference > tolerance / 100.0 * move_vm_instructions.max(1) as f64 {
+                    mismatch = Some(InstructionCountMismatch::new(
+                        function_name.to_string(),
+                        move_vm_instructions,
+                        stackless_vm_instructions,
+                    ));
                 }
             }
+            let move_vm_result = adapt_move_vm_result(exec_result.clone());
+            let move_vm_change_set = adapt_move_vm_change_set(cs_result.clone(), storage);
+            if stackless_vm_result != move_vm_result
+                || stackless_vm_change_set != move_vm_change_set
+            {
+                let failure = TestFailure::new(
+                    FailureReason::mismatch(
+                        move_vm_result,
+                        move_vm_change_set,
+                        stackless_vm_result,
+                        stackless_vm_change_set,
+                    ),
+                    test_run_info,
+                    None,
+                    None,
+                );
+                return (TestExecution::Failed(failure), mismatch);
+            }
+            if let Some(prop_failure) = prop_check_result {
+                let failure = TestFailure::new(
+                    FailureReason::property(prop_failure),
+                    test_run_info,
+                    None,
+                    None,
+                );
+                return (TestExecution::Failed(failure), mismatch);
+            }
+        }
 
-            let save_session_state = || {
-                if self.save_storage_state_on_failure {
-                    cs_result.ok().and_then(|changeset| {
-                        print_resources(&changeset, &self.starting_storage_state).ok()
-                    })
-                } else {
-                    None
+        // Invariants are only checked against the state left by tests which complete normally
+        let violated_invariant = match (&exec_result, &cs_result) {
+            (Ok(_), Ok(change_set))
+                if self.check_invariants && test_info.expected_failure.is_none() =>
+            {
+                self.violated_invariant(storage, change_set)
+            }
+            _ => None,
+        };
+
+        let save_session_state = || {
+            if let (Some(dir), Ok(changeset)) = (&self.state_output_dir, &cs_result) {
+                if let Err(err) =
+                    write_state_file(dir, &test_plan.module_id, function_name, changeset, storage)
+                {
+                    self.warn(format!(
+                        "Unable to write the storage state of failing test {}: {}",
+                        function_name, err
+                    ));
                 }
-            };
-            match exec_result {
-                Err(err) => match (test_info.expected_failure.as_ref(), err.sub_status()) {
-                    // Ran out of ticks, report a test timeout and log a test failure
-                    _ if err.major_status() == StatusCode::OUT_OF_GAS => {
-                        timeout(function_name);
-                        stats.test_failure(
-                            TestFailure::new(
-                                FailureReason::timeout(),
-                                test_run_info,
-                                Some(err),
-                                save_session_state(),
-                            ),
-                            test_plan,
-                        )
-                    }
-                    // Expected the test to not abort, but it aborted with `code`
-                    (None, Some(code)) => {
-                        fail(function_name);
-                        stats.test_failure(
-                            TestFailure::new(
-                                FailureReason::aborted(code),
-                                test_run_info,
-                                Some(err),
-                                save_session_state(),
-                            ),
-                            test_plan,
-                        )
-                    }
-                    // Expected the test the abort with a specific `code`, and it did abort with
-                    // that abort code
-                    (Some(ExpectedFailure::ExpectedWithCode(code)), Some(other_code))
-                        if err.major_status() == StatusCode::ABORTED && *code == other_code =>
-                    {
-                        pass(function_name);
-                        stats.test_success(test_run_info, test_plan);
-                    }
-                    // Expected the test to abort with a specific `code` but it aborted with a
-                    // different `other_code`
-                    (Some(ExpectedFailure::ExpectedWithCode(code)), Some(other_code)) => {
-                        fail(function_name);
-                        stats.test_failure(
-                            TestFailure::new(
-                                FailureReason::wrong_abort(*code, other_code),
-                                test_run_info,
-                                Some(err),
-                                save_session_state(),
-                            ),
-                            test_plan,
-                        )
-                    }
-                    // Expected the test to abort and it aborted, but we don't need to check the code
-                    (Some(ExpectedFailure::Expected), Some(_)) => {
-                        pass(function_name);
-                        stats.test_success(test_run_info, test_plan);
-                    }
-                    // Expected the test to abort and it aborted with internal error
-                    (Some(ExpectedFailure::Expected), None)
-                        if err.major_status() != StatusCode::EXECUTED =>
-                    {
-                        pass(function_name);
-                        stats.test_success(test_run_info, test_plan);
-                    }
-                    // Unexpected return status from the VM, signal that we hit an unknown error.
-                    (_, None) => {
-                        fail(function_name);
-                        stats.test_failure(
-                            TestFailure::new(
-                                FailureReason::unknown(),
-                                test_run_info,
-                                Some(err),
-                                save_session_state(),
-                            ),
-                            test_plan,
-                        )
-                    }
-                },
-                Ok(_) => {
-                    // Expected the test to fail, but it executed
-                    if test_info.expected_failure.is_some() {
-                        fail(function_name);
-                        stats.test_failure(
-                            TestFailure::new(
-                                FailureReason::no_abort(),
-                                test_run_info,
-                                None,
-                                save_session_state(),
-                            ),
-                            test_plan,
-                        )
+            }
+            if self.save_storage_state_on_failure {
+                cs_result.ok().and_then(|changeset| {
+                    if self.full_storage_state_on_failure {
+                        print_resources(&changeset, storage).ok()
                     } else {
-                        // Expected the test to execute fully and it did
-                        pass(function_name);
-                        stats.test_success(test_run_info, test_plan);
+                        print_resource_changes(&changeset, storage).ok()
                     }
+                })
+            } else {
+                None
+            }
+        };
+        let execution = match exec_result {
+            Err(err) => match (test_info.expected_failure.as_ref(), err.sub_status()) {
+                // Ran out of ticks, report a test timeout and log a test failure
+                _ if err.major_status() == StatusCode::OUT_OF_GAS => {
+                    let failure = TestFailure::new(
+                        if self.metered {
+                            FailureReason::out_of_gas()
+                        } else {
+                            FailureReason::timeout()
+                        },
+                        test_run_info,
+                        Some(err),
+                        save_session_state(),
+                    );
+                    TestExecution::TimedOut(failure)
+                }
+                // Expected the test to not abort, but it aborted with `code`
+                (None, Some(code)) => {
+                    let failure = TestFailure::new(
+                        FailureReason::aborted(code),
+                        test_run_info,
+                        Some(err),
+                        save_session_state(),
+                    );
+                    TestExecution::Failed(failure)
+                }
+                // Expected the test the abort with a specific `code`, and it did abort with
+                // that abort code
+                (Some(ExpectedFailure::ExpectedWithCode(code)), Some(other_code))
+                    if err.major_status() == StatusCode::ABORTED && *code == other_code =>
+                {
+                    TestExecution::Passed(test_run_info)
+                }
+                // Expected the test to abort with a specific `code` but it aborted with a
+                // different `other_code`
+                (Some(ExpectedFailure::ExpectedWithCode(code)), Some(other_code)) => {
+                    let failure = TestFailure::new(
+                        FailureReason::wrong_abort(*code, other_code),
+                        test_run_info,
+                        Some(err),
+                        save_session_state(),
+                    );
+                    TestExecution::Failed(failure)
+                }
+                // Expected the test the abort with a specific `code` in a specific location, and
+                // it did abort with that abort code there
+                (
+                    Some(ExpectedFailure::ExpectedWithCodeAndLocation(code, location)),
+                    Some(other_code),
+                ) if err.major_status() == StatusCode::ABORTED
+                    && *code == other_code
+                    && is_abort_in(storage, &err, location) =>
+                {
+                    TestExecution::Passed(test_run_info)
+                }
+                // Expected the test to abort with a specific `code` in a specific location, but it
+                // aborted with a different `other_code` or somewhere else
+                (
+                    Some(ExpectedFailure::ExpectedWithCodeAndLocation(code, location)),
+                    Some(other_code),
+                ) => {
+                    let failure = TestFailure::new(
+                        FailureReason::wrong_abort_location(
+                            *code,
+                            other_code,
+                            expected_abort_location(location),
+                        ),
+                        test_run_info,
+                        Some(err),
+                        save_session_state(),
+                    );
+                    TestExecution::Failed(failure)
+                }
+                // Expected the test to abort and it aborted, but we don't need to check the code
+                (Some(ExpectedFailure::Expected), Some(_)) => TestExecution::Passed(test_run_info),
+                // Expected the test to abort and it aborted with internal error
+                (Some(ExpectedFailure::Expected), None)
+                    if err.major_status() != StatusCode::EXECUTED =>
+                {
+                    TestExecution::Passed(test_run_info)
+                }
+                // Unexpected return status from the VM, signal that we hit an unknown error.
+                (_, None) => {
+                    let failure = TestFailure::new(
+                        FailureReason::unknown(),
+                        test_run_info,
+                        Some(err),
+                        save_session_state(),
+                    );
+                    TestExecution::Failed(failure)
+                }
+            },
+            Ok(_) => {
+                // Expected the test to fail, but it executed
+                if test_info.expected_failure.is_some() {
+                    let failure = TestFailure::new(
+                        FailureReason::no_abort(),
+                        test_run_info,
+                        None,
+                        save_session_state(),
+                    );
+                    TestExecution::Failed(failure)
+                } else if let Some(details) = self.check_events(test_info, &events, storage) {
+                    // Expected the test to emit other events than it did
+                    let failure = TestFailure::new(
+                        FailureReason::events(details),
+                        test_run_info,
+                        None,
+                        save_session_state(),
+                    );
+                    TestExecution::Failed(failure)
+                } else if let Some((invariant, err)) = violated_invariant {
+                    // Expected the invariants to hold after the test, but one of them aborted
+                    let failure = TestFailure::new(
+                        FailureReason::invariant_violated(&invariant),
+                        test_run_info,
+                        Some(err),
+                        save_session_state(),
+                    );
+                    TestExecution::Failed(failure)
+                } else {
+                    // Expected the test to execute fully and it did
+                    TestExecution::Passed(test_run_info)
                 }
             }
+        };
+        (execution, mismatch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_module_tests;
+    use move_compiler::unit_test::{ModuleTestPlan, StorageFaults, TestCase};
+    use move_core_types::{
+        account_address::AccountAddress, identifier::Identifier, language_storage::ModuleId,
+    };
+    use std::collections::{BTreeMap, BTreeSet};
+
+    fn test_case(test_name: &str, fixture: Option<&str>, depends_on: &[&str]) -> TestCase {
+        TestCase {
+            test_name: test_name.to_string(),
+            arguments: vec![],
+            expected_failure: None,
+            env: BTreeMap::new(),
+            tags: BTreeSet::new(),
+            fixture: fixture.map(str::to_string),
+            storage_faults: StorageFaults::default(),
+            random_arguments: None,
+            expected_events: None,
+            data_file: None,
+            depends_on: depends_on.iter().map(|name| name.to_string()).collect(),
         }
+    }
 
-        stats
+    fn module_test_plan(tests: Vec<(&str, TestCase)>) -> ModuleTestPlan {
+        ModuleTestPlan {
+            module_id: ModuleId::new(
+                AccountAddress::from_hex_literal("0x1").unwrap(),
+                Identifier::new("M").unwrap(),
+            ),
+            tests: tests
+                .into_iter()
+                .map(|(name, test_case)| (name.to_string(), test_case))
+                .collect(),
+            invariants: BTreeSet::new(),
+        }
+    }
+
+    /// The names of the tests of each part
+    fn part_tests(parts: &[ModuleTestPlan]) -> Vec<BTreeSet<&str>> {
+        parts
+            .iter()
+            .map(|part| part.tests.keys().map(String::as_str).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_split_keeps_related_tests_together() {
+        let test_plan = module_test_plan(vec![
+            ("a", test_case("a", None, &[])),
+            ("b", test_case("b", None, &[])),
+            // `c` depends on `d`
+            ("c", test_case("c", None, &["d"])),
+            ("d", test_case("d", None, &[])),
+            // `e` and `g` share the fixture `f`
+            ("e", test_case("e", Some("f"), &[])),
+            ("g", test_case("g", Some("f"), &[])),
+            // `i` depends on both cases of the data-driven test `h`
+            ("h[0]", test_case("h", None, &[])),
+            ("h[1]", test_case("h", None, &[])),
+            ("i", test_case("i", None, &["h"])),
+        ]);
+        let parts = split_module_tests(&test_plan, 8);
+        let mut parts = part_tests(&parts);
+        parts.sort();
+        let expected: Vec<BTreeSet<&str>> = vec![
+            ["a"].iter().copied().collect(),
+            ["b"].iter().copied().collect(),
+            ["c", "d"].iter().copied().collect(),
+            ["e", "g"].iter().copied().collect(),
+            ["h[0]", "h[1]", "i"].iter().copied().collect(),
+        ];
+        assert_eq!(parts, expected);
+    }
+
+    #[test]
+    fn test_split_into_fewer_parts_than_groups() {
+        let test_plan = module_test_plan(vec![
+            ("a", test_case("a", None, &[])),
+            ("b", test_case("b", None, &[])),
+            ("c", test_case("c", None, &["d"])),
+            ("d", test_case("d", None, &[])),
+            ("e", test_case("e", Some("f"), &[])),
+            ("g", test_case("g", Some("f"), &[])),
+        ]);
+        let parts = split_module_tests(&test_plan, 2);
+        let parts = part_tests(&parts);
+        assert_eq!(parts.len(), 2);
+        // Each test is in exactly one part, and related tests in the same one
+        assert_eq!(parts.iter().map(BTreeSet::len).sum::<usize>(), 6);
+        assert!(parts
+            .iter()
+            .all(|part| part.contains("c") == part.contains("d")
+                && part.contains("e") == part.contains("g")));
+        // The groups are balanced over the parts
+        assert_eq!(parts[0].len(), 3);
+        assert_eq!(parts[1].len(), 3);
+        assert_eq!(split_module_tests(&test_plan, 1)[0].tests.len(), 6);
     }
 }
@@ -0,0 +1,91 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use common::source_path;
+use move_compiler::shared::NumericalAddress;
+use move_core_types::account_address::AccountAddress;
+use move_unit_test::{self, UnitTestingConfig};
+
+fn address_of(config: &UnitTestingConfig, name: &str) -> AccountAddress {
+    let matching = config
+        .named_address_values
+        .iter()
+        .filter(|(other_name, _)| other_name == name)
+        .map(|(_, address)| address.into_inner())
+        .collect::<Vec<_>>();
+    assert_eq!(matching.len(), 1, "expected a single address for {}", name);
+    matching[0]
+}
+
+fn load(
+    address_file: &str,
+    named_address_values: Vec<(String, NumericalAddress)>,
+) -> UnitTestingConfig {
+    let mut config = UnitTestingConfig::default_with_bound(None);
+    config.named_address_values = named_address_values;
+    config.address_file = Some(source_path(address_file));
+    config.load_address_file().unwrap();
+    config
+}
+
+#[test]
+fn test_toml_address_file() {
+    let config = load("addresses.toml", vec![]);
+    assert_eq!(
+        address_of(&config, "Std"),
+        AccountAddress::from_hex_literal("0x1").unwrap()
+    );
+    assert_eq!(
+        address_of(&config, "Other"),
+        AccountAddress::from_hex_literal("0x42").unwrap()
+    );
+}
+
+#[test]
+fn test_json_address_file() {
+    let config = load("addresses.json", vec![]);
+    assert_eq!(
+        address_of(&config, "Std"),
+        AccountAddress::from_hex_literal("0x1").unwrap()
+    );
+    assert_eq!(
+        address_of(&config, "Other"),
+        AccountAddress::from_hex_literal("0x42").unwrap()
+    );
+}
+
+#[test]
+fn test_named_addresses_take_precedence() {
+    let other = NumericalAddress::parse_str("0x43").unwrap();
+    let mut config = load("addresses.toml", vec![("Other".to_string(), other)]);
+    assert_eq!(
+        address_of(&config, "Std"),
+        AccountAddress::from_hex_literal("0x1").unwrap()
+    );
+    assert_eq!(
+        address_of(&config, "Other"),
+        AccountAddress::from_hex_literal("0x43").unwrap()
+    );
+
+    // Loading the file again doesn't change the addresses
+    config.load_address_file().unwrap();
+    assert_eq!(config.named_address_values.len(), 2);
+    assert_eq!(
+        address_of(&config, "Other"),
+        AccountAddress::from_hex_literal("0x43").unwrap()
+    );
+}
+
+#[test]
+fn test_invalid_address_file() {
+    let mut config = UnitTestingConfig::default_with_bound(None);
+    config.address_file = Some(source_path("invalid_addresses.toml"));
+    let err = config.load_address_file().unwrap_err().to_string();
+    assert!(err.contains("Invalid address for Other"));
+
+    config.address_file = Some(source_path("no_such_addresses.toml"));
+    let err = config.load_address_file().unwrap_err().to_string();
+    assert!(err.contains("Unable to read address file"));
+}
@@ -0,0 +1,72 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use common::source_path;
+use move_compiler::Compiler;
+use move_unit_test::{self, UnitTestingConfig};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// A fresh directory for the compiled dependencies of a test
+fn deps_dir(test_name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "move-unit-test-{}-{}",
+        test_name,
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Compile the module of `Counter.move` into `dir`, returning the path of the `.mv` file
+fn compile_counter(dir: &Path) -> String {
+    let (_, units) = Compiler::new(
+        vec![(vec![source_path("Counter.move")], BTreeMap::new())],
+        vec![],
+    )
+    .build_and_report()
+    .unwrap();
+    let path = dir.join("Counter.mv");
+    fs::write(&path, units[0].clone().into_compiled_unit().serialize()).unwrap();
+    path.to_string_lossy().to_string()
+}
+
+fn config_with_deps(dep_files: Vec<String>) -> UnitTestingConfig {
+    let mut testing_config = UnitTestingConfig::default_with_bound(None)
+        .with_named_addresses(move_stdlib::move_stdlib_named_addresses());
+    testing_config.source_files = vec![source_path("UsesCounter.move")];
+    testing_config.dep_files = move_stdlib::move_stdlib_files();
+    testing_config.dep_files.extend(dep_files);
+    testing_config
+}
+
+#[test]
+fn test_compiled_module_dependency() {
+    let dir = deps_dir("compiled-module");
+    let testing_config = config_with_deps(vec![compile_counter(&dir)]);
+    let test_plan = testing_config.build_test_plan().unwrap();
+    let results = testing_config
+        .run_unit_tests(test_plan, None, None)
+        .unwrap();
+    assert_eq!(results.statistics().num_passed(), 1);
+    assert_eq!(results.statistics().num_failed(), 0);
+
+    // A directory is searched for compiled modules
+    let testing_config = config_with_deps(vec![dir.to_string_lossy().to_string()]);
+    assert!(testing_config.build_test_plan().is_some());
+}
+
+#[test]
+fn test_invalid_compiled_module_dependency() {
+    let dir = deps_dir("invalid-compiled-module");
+    let path = dir.join("Counter.mv");
+    fs::write(&path, b"not a compiled module").unwrap();
+    let testing_config = config_with_deps(vec![path.to_string_lossy().to_string()]);
+    assert!(testing_config.build_test_plan().is_none());
+}
@@ -0,0 +1,37 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+// Helpers shared by the integration tests. Each test crate includes this module and only uses
+// some of them.
+#![allow(dead_code)]
+
+use move_core_types::{account_address::AccountAddress, identifier::Identifier};
+use move_vm_runtime::native_functions::{NativeFunction, NativeFunctionTable};
+use std::path::PathBuf;
+
+/// The path of the Move source `file_name` in `tests/sources`
+pub fn source_path(file_name: &str) -> String {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/sources")
+        .join(file_name)
+        .to_string_lossy()
+        .to_string()
+}
+
+/// The natives of the standard library, along with `native` implementing the native function
+/// `0x1::<module_name>::<function_name>` of a test source
+pub fn natives_with(
+    module_name: &str,
+    function_name: &str,
+    native: NativeFunction,
+) -> NativeFunctionTable {
+    let std_addr = AccountAddress::from_hex_literal("0x1").unwrap();
+    let mut natives = move_stdlib::natives::all_natives(std_addr);
+    natives.push((
+        std_addr,
+        Identifier::new(module_name).unwrap(),
+        Identifier::new(function_name).unwrap(),
+        native,
+    ));
+    natives
+}
@@ -0,0 +1,99 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+// Invalid inputs of a unit testing config are reported as errors of the run, rather than panics.
+
+mod common;
+
+use common::source_path;
+use move_unit_test::{self, UnitTestingConfig};
+
+// A config testing B.move, with A.move among its dependencies
+fn testing_config() -> UnitTestingConfig {
+    let mut testing_config = UnitTestingConfig::default_with_bound(None)
+        .with_named_addresses(move_stdlib::move_stdlib_named_addresses());
+    let mut deps = move_stdlib::move_stdlib_files();
+    deps.push(source_path("A.move"));
+    testing_config.source_files = vec![source_path("B.move")];
+    testing_config.dep_files = deps;
+    testing_config
+}
+
+// Runs the tests of the config, and returns the error of the run.
+fn run_error(testing_config: &UnitTestingConfig) -> String {
+    let test_plan = testing_config.build_test_plan().unwrap();
+    match testing_config.run_unit_tests(test_plan, None, None) {
+        Ok(_) => panic!("expected the run to fail"),
+        Err(err) => err.to_string(),
+    }
+}
+
+#[test]
+fn test_invalid_failed_tests_file() {
+    let mut testing_config = testing_config();
+    testing_config.rerun_failed = true;
+    testing_config.failed_tests_file = Some(source_path("invalid_failures.json"));
+    assert!(run_error(&testing_config).contains("Invalid failed tests file"));
+}
+
+#[test]
+fn test_invalid_module_selection() {
+    let mut module_config = testing_config();
+    module_config.modules = vec!["0x1".to_string()];
+    assert!(run_error(&module_config).contains("expected <address>::<module_name>"));
+
+    let mut address_config = testing_config();
+    address_config.module_addresses = vec!["NoSuchAddress".to_string()];
+    assert!(run_error(&address_config).contains("Invalid address 'NoSuchAddress'"));
+}
+
+#[test]
+fn test_missing_test_data() {
+    let mut testing_config = testing_config();
+    testing_config.source_files = vec![source_path("missing_data.move")];
+    assert!(run_error(&testing_config).contains("Invalid data for test 0x1::D::add"));
+
+    // Listing the tests also expands the data-driven ones
+    testing_config.list = true;
+    let test_plan = testing_config.build_test_plan().unwrap();
+    assert!(testing_config
+        .run_and_report_unit_tests(test_plan, None, None, Vec::new())
+        .is_err());
+}
+
+#[test]
+fn test_missing_gas_schedule() {
+    let mut testing_config = testing_config();
+    testing_config.gas_schedule = Some(source_path("no_such_schedule.json"));
+    assert!(run_error(&testing_config).contains("Unable to read gas schedule"));
+}
+
+#[test]
+fn test_invalid_genesis() {
+    let mut testing_config = testing_config();
+    // Not a JSON genesis, so read as BCS
+    testing_config.genesis = Some(source_path("A.move"));
+    assert!(run_error(&testing_config).contains("Invalid genesis"));
+}
+
+#[test]
+fn test_compilation_errors() {
+    let mut testing_config = testing_config();
+    testing_config.source_files = vec![source_path("type_error.move")];
+    // The errors are returned rather than reported before exiting, so that watch mode keeps going
+    match testing_config.try_build_test_plan() {
+        Ok(_) => panic!("expected the compilation to fail"),
+        Err((_, diags)) => assert!(!diags.is_empty()),
+    }
+}
+
+#[test]
+fn test_invalid_filter() {
+    let mut filter_config = testing_config();
+    filter_config.filter = vec!["add(".to_string()];
+    assert!(run_error(&filter_config).contains("Invalid test filter 'add('"));
+
+    let mut exclude_config = testing_config();
+    exclude_config.exclude = vec!["[".to_string()];
+    assert!(run_error(&exclude_config).contains("Invalid test filter '['"));
+}
@@ -0,0 +1,71 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The Move VM traces the executed instructions once per process, so this is the only test of
+//! this executable. Tracing is only available in debug builds.
+#![cfg(debug_assertions)]
+
+mod common;
+
+use common::source_path;
+use move_unit_test::{self, UnitTestingConfig};
+use std::fs;
+
+/// The LCOV tracefile of a run counts the executed functions and lines of the tested modules
+#[test]
+fn test_lcov() {
+    let dir = std::env::temp_dir().join(format!("move-unit-test-coverage-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let lcov_path = dir.join("lcov.info");
+    let coverage_path = source_path("Coverage.move");
+
+    let mut testing_config = UnitTestingConfig::default_with_bound(None)
+        .with_named_addresses(move_stdlib::move_stdlib_named_addresses());
+    testing_config.dep_files = move_stdlib::move_stdlib_files();
+    testing_config.source_files = vec![coverage_path.clone()];
+    testing_config.coverage = Some(lcov_path.to_string_lossy().to_string());
+    testing_config.lcov = true;
+    let test_plan = testing_config.build_test_plan().unwrap();
+    let (_, all_tests_passed) = testing_config
+        .run_and_report_unit_tests(test_plan, None, None, vec![])
+        .unwrap();
+    assert!(all_tests_passed);
+
+    let lcov = fs::read_to_string(&lcov_path).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    // The dependencies have records of their own, before or after the one of the tested module
+    let source_file = format!("SF:{}", coverage_path);
+    let record = lcov
+        .split_inclusive("end_of_record\n")
+        .find(|record| record.lines().nth(1) == Some(source_file.as_str()))
+        .unwrap_or_else(|| panic!("missing `{}` in:\n{}", source_file, lcov));
+    let lines: Vec<_> = record.lines().collect();
+    assert_eq!(lines[0], "TN:");
+    for line in [
+        "FN:2,Coverage::covered",
+        "FN:3,Coverage::uncovered",
+        "FN:6,Coverage::test_covered",
+        "FNDA:0,Coverage::uncovered",
+        "FNF:3",
+        "FNH:2",
+        "DA:3,0",
+        "LF:3",
+        "LH:2",
+    ] {
+        assert!(lines.contains(&line), "missing `{}` in:\n{}", line, lcov);
+    }
+    for covered in [
+        "FNDA:0,Coverage::covered",
+        "FNDA:0,Coverage::test_covered",
+        "DA:2,0",
+        "DA:6,0",
+    ] {
+        assert!(
+            !lines.contains(&covered),
+            "unexpected `{}` in:\n{}",
+            covered,
+            lcov
+        );
+    }
+    assert_eq!(lines.last(), Some(&"end_of_record"));
+}
@@ -0,0 +1,108 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use common::source_path;
+use move_compiler::unit_test::TestPlan;
+use move_core_types::language_storage::ModuleId;
+use move_unit_test::{
+    self,
+    test_reporter::{TestOutcome, TestReporter, TestResults},
+    UnitTestingConfig,
+};
+use std::{io::Result, sync::Mutex};
+
+/// Records the calls of its hooks
+struct RecordingReporter<'a> {
+    calls: &'a Mutex<Vec<String>>,
+}
+
+impl RecordingReporter<'_> {
+    fn record(&self, call: String) -> Result<()> {
+        self.calls.lock().unwrap().push(call);
+        Ok(())
+    }
+}
+
+impl TestReporter for RecordingReporter<'_> {
+    fn suite_started(&self, test_count: usize) -> Result<()> {
+        self.record(format!("suite_started {}", test_count))
+    }
+
+    fn test_started(&self, module_id: &ModuleId, function_name: &str) -> Result<()> {
+        self.record(format!(
+            "test_started {}::{}",
+            module_id.name(),
+            function_name
+        ))
+    }
+
+    fn test_finished(
+        &self,
+        _test_plan: &TestPlan,
+        module_id: &ModuleId,
+        outcome: TestOutcome,
+    ) -> Result<()> {
+        let (function_name, outcome) = match outcome {
+            TestOutcome::Passed(info) => (&info.function_ident, "passed"),
+            TestOutcome::Failed(failure) => (&failure.test_run_info.function_ident, "failed"),
+        };
+        self.record(format!(
+            "test_finished {}::{} {}",
+            module_id.name(),
+            function_name,
+            outcome
+        ))
+    }
+
+    fn summary(&self, results: &TestResults) -> Result<()> {
+        self.record(format!(
+            "summary {} {}",
+            results.statistics().num_passed(),
+            results.statistics().num_failed()
+        ))
+    }
+}
+
+/// The hooks of a custom reporter are called in order: the start of the suite first, the start
+/// of each test before its result, and the summary last
+#[test]
+fn test_hook_order() {
+    let mut testing_config = UnitTestingConfig::default_with_bound(None)
+        .with_named_addresses(move_stdlib::move_stdlib_named_addresses());
+    testing_config.dep_files = move_stdlib::move_stdlib_files();
+    testing_config.source_files = vec![source_path("A.move"), source_path("B.move")];
+    // A single thread, so that the tests are run one after the other
+    testing_config.num_threads = 1;
+    let test_plan = testing_config.build_test_plan().unwrap();
+    let calls = Mutex::new(vec![]);
+    let all_tests_passed = testing_config
+        .run_and_report_unit_tests_with_reporter(
+            test_plan,
+            None,
+            None,
+            Box::new(RecordingReporter { calls: &calls }),
+        )
+        .unwrap();
+    assert!(!all_tests_passed);
+
+    let calls = calls.into_inner().unwrap();
+    assert_eq!(
+        calls,
+        vec![
+            "suite_started 5",
+            "test_started A::a",
+            "test_finished A::a passed",
+            "test_started B::b",
+            "test_finished B::b passed",
+            "test_started B::b_other",
+            "test_finished B::b_other failed",
+            "test_started B::b_other0",
+            "test_finished B::b_other0 passed",
+            "test_started B::b_other1",
+            "test_finished B::b_other1 failed",
+            "summary 3 2",
+        ]
+    );
+}
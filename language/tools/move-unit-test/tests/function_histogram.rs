@@ -0,0 +1,65 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The Move VM traces the executed instructions once per process, so this is the only test of
+//! this executable. Tracing is only available in debug builds.
+#![cfg(debug_assertions)]
+
+mod common;
+
+use common::source_path;
+use move_unit_test::{self, UnitTestingConfig};
+use std::fs;
+
+/// The functions executing the most instructions are reported with their share of all
+/// instructions executed
+#[test]
+fn test_function_histogram() {
+    let trace_path = std::env::temp_dir().join(format!(
+        "move-unit-test-histogram-{}.trace",
+        std::process::id()
+    ));
+    std::env::set_var("MOVE_VM_TRACE", &trace_path);
+    let mut testing_config = UnitTestingConfig::default_with_bound(None)
+        .with_named_addresses(move_stdlib::move_stdlib_named_addresses());
+    testing_config.dep_files = move_stdlib::move_stdlib_files();
+    testing_config.source_files = vec![source_path("Coverage.move")];
+    testing_config.report_statistics = true;
+    testing_config.report_function_histogram = Some(2);
+    let test_plan = testing_config.build_test_plan().unwrap();
+    let (output, all_tests_passed) = testing_config
+        .run_and_report_unit_tests(test_plan, None, None, vec![])
+        .unwrap();
+    fs::remove_file(&trace_path).unwrap();
+    assert!(all_tests_passed);
+
+    // `test_covered` calls `covered` and discards its result, `covered` loads it
+    let output = String::from_utf8(output).unwrap();
+    let histogram: Vec<_> = output
+        .lines()
+        .skip_while(|line| *line != "Instructions executed per function:")
+        .skip(2)
+        .take(2)
+        .collect();
+    assert_eq!(
+        histogram,
+        vec![
+            format!(
+                "{:>12} {:>6.2}% {}{} 0x1::Coverage::test_covered",
+                3,
+                60.0,
+                "█".repeat(18),
+                " ".repeat(12)
+            ),
+            format!(
+                "{:>12} {:>6.2}% {}{} 0x1::Coverage::covered",
+                2,
+                40.0,
+                "█".repeat(12),
+                " ".repeat(18)
+            ),
+        ],
+        "in:\n{}",
+        output
+    );
+}
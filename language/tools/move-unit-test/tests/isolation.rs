@@ -0,0 +1,56 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Runs without the test harness, as the test processes of an isolated run run this executable
+//! again.
+
+mod common;
+
+use common::{natives_with, source_path};
+use move_binary_format::errors::PartialVMResult;
+use move_unit_test::{self, test_reporter::FailureReason, UnitTestingConfig};
+use move_vm_runtime::native_functions::{NativeContext, NativeFunctionTable};
+use move_vm_types::{
+    loaded_data::runtime_types::Type, natives::function::NativeResult, values::Value,
+};
+use std::collections::VecDeque;
+
+fn native_crash(
+    _context: &mut NativeContext,
+    _ty_args: Vec<Type>,
+    _arguments: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    panic!("crashing native")
+}
+
+fn natives() -> NativeFunctionTable {
+    natives_with("Crash", "crash", native_crash)
+}
+
+/// A native which crashes the process running a test only fails that test
+fn test_crashing_native() {
+    let mut testing_config = UnitTestingConfig::default_with_bound(None)
+        .with_named_addresses(move_stdlib::move_stdlib_named_addresses());
+    testing_config.dep_files = move_stdlib::move_stdlib_files();
+    testing_config.source_files = vec![source_path("Crash.move")];
+    testing_config.isolate = true;
+    let test_plan = testing_config.build_test_plan().unwrap();
+    let results = testing_config
+        .run_unit_tests(test_plan, Some(natives()), None)
+        .unwrap();
+
+    let statistics = results.statistics();
+    assert_eq!(statistics.num_passed(), 1);
+    assert_eq!(statistics.num_failed(), 1);
+    let failure = statistics.failed().values().flatten().next().unwrap();
+    assert_eq!(failure.test_run_info.function_ident, "crashes");
+    match &failure.failure_reason {
+        FailureReason::Crashed(details) => assert!(details.contains("crashing native")),
+        reason => panic!("expected the test process to crash, found {:?}", reason),
+    }
+}
+
+fn main() {
+    move_unit_test::run_isolated_test_process(Some(natives()), None);
+    test_crashing_native();
+}
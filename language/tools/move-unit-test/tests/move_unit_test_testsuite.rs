@@ -2,21 +2,140 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use move_command_line_common::testing::{format_diff, read_env_update_baseline, EXP_EXT};
-use move_unit_test::{self, UnitTestingConfig};
+use move_unit_test::{
+    self,
+    test_reporter::{StatisticsFormat, TestReportFormat},
+    UnitTestingConfig,
+};
 use regex::RegexBuilder;
 use std::{
     fs,
     path::{Path, PathBuf},
 };
 
-// We don't support statistics tests as that includes times which are variable and will make these
-// tests flaky.
-const TEST_MODIFIER_STRS: &[&str] = &["storage"];
+// The times reported with statistics vary between runs, so they are replaced in the outputs
+// compared against the expected ones.
+const TEST_MODIFIER_STRS: &[&str] = &[
+    "storage",
+    "full_storage",
+    "dry_run",
+    "junit",
+    "json",
+    "fail_fast",
+    "shard",
+    "quiet",
+    "shuffle",
+    "filter",
+    "tags",
+    "list_json",
+    "module",
+    "gas",
+    "genesis",
+    "invariants",
+    "stackless_only",
+    "vm_limits",
+    "wall_clock_timeout",
+    "dep_tests",
+    "snapshot",
+    "slow_tests",
+    "function_histogram",
+    "statistics",
+    "open_metrics",
+    "stackless_tolerance",
+];
 
 pub fn modify(mut base_config: UnitTestingConfig, modifier_str: &str) -> Option<UnitTestingConfig> {
     // Add future test modifiers here
     match modifier_str {
         "storage" => base_config.report_storage_on_error = true,
+        "full_storage" => {
+            base_config.report_storage_on_error = true;
+            base_config.report_full_storage_on_error = true;
+        }
+        "dry_run" => base_config.dry_run = true,
+        "junit" => base_config.format = TestReportFormat::Junit,
+        "json" => base_config.format = TestReportFormat::Json,
+        "fail_fast" => base_config.fail_fast = true,
+        // The first of two shards, which only has some of the tests of a file
+        "shard" => base_config.shard = Some((1, 2)),
+        "quiet" => base_config.quiet = true,
+        "shuffle" => base_config.shuffle = Some(Some(42)),
+        "filter" => {
+            base_config.filter = vec!["::published_".to_string(), "::supply_".to_string()];
+            base_config.exclude = vec!["_broken$".to_string()];
+        }
+        "tags" => {
+            base_config.include_tags = vec!["slow".to_string()];
+            base_config.exclude_tags = vec!["fuzz".to_string()];
+        }
+        "list_json" => {
+            base_config.list = true;
+            base_config.format = TestReportFormat::Json;
+        }
+        // Selects a module by its named address
+        "module" => base_config.modules = vec!["Std::M".to_string()],
+        "gas" => {
+            base_config.gas_schedule = Some("initial".to_string());
+            base_config.gas_limit = Some(100);
+        }
+        "genesis" => {
+            base_config.genesis = Some("tests/test_sources/genesis_state.json".to_string());
+        }
+        "invariants" => {
+            base_config.check_invariants = true;
+        }
+        "stackless_only" => {
+            base_config.stackless_only = true;
+        }
+        "vm_limits" => {
+            base_config.max_call_depth = Some(10);
+            base_config.max_value_depth = Some(3);
+            base_config.max_vector_length = Some(2);
+        }
+        // Tests are run on worker threads, which are abandoned once a test runs for a second,
+        // long before it exceeds the instruction bound
+        "wall_clock_timeout" => {
+            base_config.timeout = Some(1);
+            base_config.instruction_execution_bound = 1_000_000_000;
+        }
+        // The tests of a dependency compiled from source are run along with those of the file
+        "dep_tests" => {
+            base_config
+                .dep_files
+                .push("tests/sources/A.move".to_string());
+            base_config.include_dep_tests = true;
+        }
+        // Compared against the snapshots checked in next to the test sources
+        "snapshot" => {
+            base_config.snapshot_dir = Some("tests/test_sources/snapshots".to_string());
+        }
+        "slow_tests" => base_config.report_slow_tests = Some(3),
+        // The execution of the tests is not traced, so there are no instructions to report. Stack
+        // depths are only tracked with the `statistics` feature
+        "function_histogram" if cfg!(feature = "statistics") => {
+            base_config.report_statistics = true;
+            base_config.report_function_histogram = Some(3);
+        }
+        "statistics" if cfg!(feature = "statistics") => base_config.report_statistics = true,
+        // The metrics are written to a file of their own, which is appended to the output
+        "open_metrics" => {
+            let file_name = Path::new(&base_config.source_files[0]).file_stem().unwrap();
+            base_config.open_metrics_output = Some(
+                std::env::temp_dir()
+                    .join(format!(
+                        "move-unit-test-{}-{}.metrics",
+                        file_name.to_string_lossy(),
+                        std::process::id()
+                    ))
+                    .to_string_lossy()
+                    .to_string(),
+            );
+        }
+        // Any difference between the instruction counts of the two VMs is reported
+        "stackless_tolerance" => {
+            base_config.check_stackless_vm = true;
+            base_config.stackless_instruction_tolerance = Some(Some(0.0));
+        }
         _ => return None,
     };
     Some(base_config)
@@ -44,10 +163,13 @@ fn run_test_with_modifiers(
                 );
             }
 
-            results.push((
-                test_config.run_and_report_unit_tests(test_plan.unwrap(), None, buffer)?,
-                modified_exp_path,
-            ))
+            let (mut buffer, all_tests_passed) =
+                test_config.run_and_report_unit_tests(test_plan.unwrap(), None, None, buffer)?;
+            if let Some(path) = &test_config.open_metrics_output {
+                buffer.extend(fs::read(path)?);
+                fs::remove_file(path)?;
+            }
+            results.push(((buffer, all_tests_passed), modified_exp_path))
         }
     }
 
@@ -59,7 +181,7 @@ fn run_test_with_modifiers(
     }
 
     results.push((
-        unit_test_config.run_and_report_unit_tests(test_plan.unwrap(), None, buffer)?,
+        unit_test_config.run_and_report_unit_tests(test_plan.unwrap(), None, None, buffer)?,
         path.with_extension(EXP_EXT),
     ));
 
@@ -74,27 +196,112 @@ fn run_test_impl(path: &Path) -> anyhow::Result<()> {
     let unit_test_config = UnitTestingConfig {
         num_threads: 1,
         instruction_execution_bound: 1000,
-        filter: None,
+        gas_schedule: None,
+        gas_limit: None,
+        max_call_depth: None,
+        max_value_depth: None,
+        max_vector_length: None,
+        filter: vec![],
+        exclude: vec![],
+        include_tags: vec![],
+        exclude_tags: vec![],
+        modules: vec![],
+        module_addresses: vec![],
         source_files,
         dep_files: move_stdlib::move_stdlib_files(),
+        include_dep_tests: false,
+        cached: false,
+        cache_dir: None,
+        allow_warnings: false,
         check_stackless_vm: false,
+        stackless_instruction_tolerance: None,
+        stackless_only: false,
         verbose: false,
+        quiet: false,
+        progress: false,
         report_statistics: false,
+        statistics_format: StatisticsFormat::Table,
+        statistics_output: None,
+        report_slow_tests: None,
+        report_function_histogram: None,
         report_storage_on_error: false,
+        report_full_storage_on_error: false,
+        state_output_dir: None,
+        snapshot_dir: None,
+        update_baseline: false,
+        check_invariants: false,
+        nocapture: false,
+        prove: false,
+        genesis: None,
+        timeout: None,
+        fail_fast: false,
+        shard: None,
+        retries: 0,
+        failed_tests_file: None,
+        rerun_failed: false,
+        watch: false,
+        isolate: false,
+        shuffle: None,
+        // Random tests generate the same inputs on each run
+        seed: Some(0),
         list: false,
         named_address_values: move_stdlib::move_stdlib_named_addresses()
             .into_iter()
             .collect(),
+        address_file: None,
+        open_metrics_output: None,
+        coverage: None,
+        lcov: false,
+        test_env: vec![],
+        dry_run: false,
+        log_json: None,
+        format: TestReportFormat::Text,
     };
 
     let regex = RegexBuilder::new(r"(┌─ ).+/([^/]+)$")
         .multi_line(true)
         .build()
         .unwrap();
+    // Times and source paths in JUnit reports, JSON events, metrics, and reports of statistics and
+    // of the slowest tests vary between runs and machines
+    let junit_time_regex = RegexBuilder::new(r#"time="[0-9.]+""#).build().unwrap();
+    let json_time_regex = RegexBuilder::new(r#""exec_time":[0-9.eE+-]+"#)
+        .build()
+        .unwrap();
+    let junit_location_regex = RegexBuilder::new(r#"( at )[^ "]*/([^/ "]+:[0-9]+:[0-9]+")"#)
+        .build()
+        .unwrap();
+    let list_file_regex = RegexBuilder::new(r#"("file":")[^"]*/([^/"]+")"#)
+        .build()
+        .unwrap();
+    let statistics_time_regex = RegexBuilder::new(r"(│ +)[0-9]+\.[0-9]{3}( +│)")
+        .build()
+        .unwrap();
+    let metrics_time_regex = RegexBuilder::new(r"^(move_unit_test_\w*duration_seconds\S*) \S+$")
+        .multi_line(true)
+        .build()
+        .unwrap();
+    let slow_test_time_regex = RegexBuilder::new(r"^( *)[0-9]+\.[0-9]{3}s  ")
+        .multi_line(true)
+        .build()
+        .unwrap();
+    let abort_diff_location_regex =
+        RegexBuilder::new(r"(location │ [^│\n]*│ )\S*/([^/\s]+:[0-9]+:[0-9]+)$")
+            .multi_line(true)
+            .build()
+            .unwrap();
 
     for ((buffer, _), exp_path) in run_test_with_modifiers(unit_test_config, path)? {
         let base_output = String::from_utf8(buffer)?;
         let cleaned_output = regex.replacen(&base_output, 0, r"$1$2");
+        let cleaned_output = junit_time_regex.replace_all(&cleaned_output, r#"time="0""#);
+        let cleaned_output = json_time_regex.replace_all(&cleaned_output, r#""exec_time":0"#);
+        let cleaned_output = junit_location_regex.replace_all(&cleaned_output, r"$1$2");
+        let cleaned_output = abort_diff_location_regex.replace_all(&cleaned_output, r"$1$2");
+        let cleaned_output = list_file_regex.replace_all(&cleaned_output, r"$1$2");
+        let cleaned_output = statistics_time_regex.replace_all(&cleaned_output, "${1}0.000${2}");
+        let cleaned_output = metrics_time_regex.replace_all(&cleaned_output, "$1 0");
+        let cleaned_output = slow_test_time_regex.replace_all(&cleaned_output, "${1}0.000s  ");
         if update_baseline {
             fs::write(&exp_path, &*cleaned_output)?
         }
@@ -0,0 +1,69 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use common::source_path;
+use move_unit_test::{
+    self,
+    test_reporter::{EventReporter, TestEvent},
+    UnitTestingConfig,
+};
+use std::sync::Mutex;
+
+fn testing_config() -> UnitTestingConfig {
+    let mut testing_config = UnitTestingConfig::default_with_bound(None)
+        .with_named_addresses(move_stdlib::move_stdlib_named_addresses());
+    testing_config.dep_files = move_stdlib::move_stdlib_files();
+    testing_config.source_files = vec![source_path("NoAbort.move")];
+    testing_config
+}
+
+/// The events of running the tests of `testing_config`
+fn run_events(testing_config: &UnitTestingConfig) -> Vec<TestEvent> {
+    let events = Mutex::new(vec![]);
+    let test_plan = testing_config.build_test_plan().unwrap();
+    testing_config
+        .run_and_report_unit_tests_with_reporter(
+            test_plan,
+            None,
+            None,
+            Box::new(EventReporter::new(|event| {
+                events.lock().unwrap().push(event);
+                Ok(())
+            })),
+        )
+        .unwrap();
+    events.into_inner().unwrap()
+}
+
+/// The message of the warning reported right before the test finished
+fn warning_before_finished(events: &[TestEvent]) -> &str {
+    let finished = events
+        .iter()
+        .position(|event| matches!(event, TestEvent::Passed { .. } | TestEvent::Failed { .. }))
+        .expect("expected the test to finish");
+    match &events[finished - 1] {
+        TestEvent::Warning { message } => message,
+        event => panic!("expected a warning before the result, found {:?}", event),
+    }
+}
+
+#[test]
+fn test_state_output_warning() {
+    let mut testing_config = testing_config();
+    // A file, which cannot be used as a directory
+    testing_config.state_output_dir = Some(source_path("NoAbort.move"));
+    let events = run_events(&testing_config);
+    assert!(warning_before_finished(&events)
+        .starts_with("Unable to write the storage state of failing test no_abort"));
+}
+
+#[test]
+fn test_snapshot_warning() {
+    let mut testing_config = testing_config();
+    testing_config.snapshot_dir = Some(source_path("NoAbort.move"));
+    let events = run_events(&testing_config);
+    assert!(warning_before_finished(&events)
+        .starts_with("Unable to write the snapshot of test no_abort"));
+}
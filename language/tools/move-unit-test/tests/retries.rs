@@ -0,0 +1,66 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use common::{natives_with, source_path};
+use move_binary_format::errors::PartialVMResult;
+use move_core_types::gas_schedule::ONE_GAS_UNIT;
+use move_unit_test::{self, UnitTestingConfig};
+use move_vm_runtime::native_functions::NativeContext;
+use move_vm_types::{
+    loaded_data::runtime_types::Type, natives::function::NativeResult, values::Value,
+};
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+/// Set once `Flaky::fail_once` aborted
+static FAILED: AtomicBool = AtomicBool::new(false);
+
+fn native_fail_once(
+    _context: &mut NativeContext,
+    _ty_args: Vec<Type>,
+    _arguments: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    if FAILED.swap(true, Ordering::SeqCst) {
+        Ok(NativeResult::ok(ONE_GAS_UNIT, vec![].into()))
+    } else {
+        Ok(NativeResult::err(ONE_GAS_UNIT, 1))
+    }
+}
+
+/// A test passing on a retry is reported as flaky, and a test failing on each attempt as failed
+#[test]
+fn test_retries() {
+    let mut testing_config = UnitTestingConfig::default_with_bound(None)
+        .with_named_addresses(move_stdlib::move_stdlib_named_addresses());
+    testing_config.dep_files = move_stdlib::move_stdlib_files();
+    testing_config.source_files = vec![source_path("Flaky.move")];
+    testing_config.retries = 2;
+    let test_plan = testing_config.build_test_plan().unwrap();
+    let results = testing_config
+        .run_unit_tests(
+            test_plan,
+            Some(natives_with("Flaky", "fail_once", native_fail_once)),
+            None,
+        )
+        .unwrap();
+
+    let statistics = results.statistics();
+    let passed = statistics.passed().values().flatten().next().unwrap();
+    assert_eq!(passed.function_ident, "flaky");
+    assert_eq!(passed.attempts, 2);
+    let failed = statistics.failed().values().flatten().next().unwrap();
+    assert_eq!(failed.test_run_info.function_ident, "always_fails");
+    assert_eq!(failed.test_run_info.attempts, 3);
+
+    let output = Mutex::new(vec![]);
+    results.summarize(&output).unwrap();
+    let output = String::from_utf8(output.into_inner().unwrap()).unwrap();
+    assert!(output.contains("Flaky tests:\n\n0x1::Flaky::flaky passed after 2 attempts\n"));
+}
@@ -0,0 +1,72 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use common::source_path;
+use move_core_types::language_storage::ModuleId;
+use move_unit_test::{self, test_reporter::FailureReason, UnitTestingConfig};
+
+/// The results returned by `run_unit_tests` hold the outcome of each test, by module
+#[test]
+fn test_results() {
+    let mut testing_config = UnitTestingConfig::default_with_bound(None)
+        .with_named_addresses(move_stdlib::move_stdlib_named_addresses());
+    testing_config.dep_files = move_stdlib::move_stdlib_files();
+    testing_config.source_files = vec![source_path("A.move"), source_path("B.move")];
+    let test_plan = testing_config.build_test_plan().unwrap();
+    let results = testing_config
+        .run_unit_tests(test_plan, None, None)
+        .unwrap();
+    assert!(!results.all_tests_passed());
+
+    let module_name = |module_id: &ModuleId| module_id.name().to_string();
+    let tested_modules: Vec<_> = results
+        .test_plan()
+        .module_tests
+        .keys()
+        .map(module_name)
+        .collect();
+    assert_eq!(tested_modules, vec!["A", "B"]);
+
+    let statistics = results.statistics();
+    assert_eq!(statistics.num_passed(), 3);
+    assert_eq!(statistics.num_failed(), 2);
+    assert!(statistics.instruction_count_mismatches().is_empty());
+
+    let passed: Vec<_> = statistics
+        .passed()
+        .iter()
+        .flat_map(|(module_id, infos)| {
+            infos
+                .iter()
+                .map(move |info| (module_name(module_id), info.function_ident.as_str()))
+        })
+        .collect();
+    assert_eq!(
+        passed,
+        vec![
+            ("A".to_string(), "a"),
+            ("B".to_string(), "b"),
+            ("B".to_string(), "b_other0"),
+        ]
+    );
+
+    assert_eq!(statistics.failed().len(), 1);
+    let (failed_module, failed) = statistics.failed().iter().next().unwrap();
+    assert_eq!(module_name(failed_module), "B");
+    let failed: Vec<_> = failed.iter().collect();
+    assert_eq!(failed.len(), 2);
+    assert_eq!(failed[0].test_run_info.function_ident, "b_other");
+    assert!(failed[0].vm_error.is_some());
+    assert!(matches!(
+        failed[0].failure_reason,
+        FailureReason::Aborted(_, 0)
+    ));
+    assert_eq!(failed[1].test_run_info.function_ident, "b_other1");
+    assert!(failed[1].vm_error.is_some());
+    assert!(matches!(
+        failed[1].failure_reason,
+        FailureReason::WrongAbort(_, 1, 0)
+    ));
+}
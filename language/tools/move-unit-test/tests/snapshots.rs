@@ -0,0 +1,84 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use common::source_path;
+use move_command_line_common::testing::read_env_update_baseline;
+use move_unit_test::{self, test_reporter::FailureReason, UnitTestingConfig};
+use std::fs;
+
+/// A snapshot is written on the first run, compared against on the next ones, and overwritten
+/// when the baseline is updated
+#[test]
+fn test_snapshot_mismatch() {
+    let dir = std::env::temp_dir().join(format!("move-unit-test-snapshots-{}", std::process::id()));
+    let snapshot_path = dir.join("0x1.A.a.exp");
+    let mut testing_config = UnitTestingConfig::default_with_bound(None)
+        .with_named_addresses(move_stdlib::move_stdlib_named_addresses());
+    testing_config.dep_files = move_stdlib::move_stdlib_files();
+    testing_config.source_files = vec![source_path("A.move")];
+    testing_config.snapshot_dir = Some(dir.to_string_lossy().to_string());
+    let run = |testing_config: &UnitTestingConfig| {
+        let test_plan = testing_config.build_test_plan().unwrap();
+        testing_config
+            .run_unit_tests(test_plan, None, None)
+            .unwrap()
+    };
+
+    assert!(run(&testing_config).all_tests_passed());
+    assert_eq!(fs::read_to_string(&snapshot_path).unwrap(), "PASS\n");
+
+    // The snapshots are always overwritten while updating the baselines of the repository
+    if !read_env_update_baseline() {
+        fs::write(&snapshot_path, "FAIL\n").unwrap();
+        let results = run(&testing_config);
+        let failure = results
+            .statistics()
+            .failed()
+            .values()
+            .flatten()
+            .next()
+            .unwrap();
+        match &failure.failure_reason {
+            FailureReason::SnapshotMismatch(message) => {
+                assert!(message.contains("0x1.A.a.exp"), "{}", message)
+            }
+            reason => panic!("expected a snapshot mismatch, found {:?}", reason),
+        }
+        assert_eq!(fs::read_to_string(&snapshot_path).unwrap(), "FAIL\n");
+    }
+
+    testing_config.update_baseline = true;
+    assert!(run(&testing_config).all_tests_passed());
+    assert_eq!(fs::read_to_string(&snapshot_path).unwrap(), "PASS\n");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// The snapshot of a failing test is written without colors, and the output stays colored
+#[test]
+fn test_snapshot_without_colors() {
+    colored::control::set_override(true);
+    let dir = std::env::temp_dir().join(format!(
+        "move-unit-test-colored-snapshots-{}",
+        std::process::id()
+    ));
+    let mut testing_config = UnitTestingConfig::default_with_bound(None)
+        .with_named_addresses(move_stdlib::move_stdlib_named_addresses());
+    testing_config.dep_files = move_stdlib::move_stdlib_files();
+    testing_config.dep_files.push(source_path("A.move"));
+    // The failure of an unexpected abort shows its location like the diagnostics of the compiler
+    testing_config.source_files = vec![source_path("B.move")];
+    testing_config.snapshot_dir = Some(dir.to_string_lossy().to_string());
+    let test_plan = testing_config.build_test_plan().unwrap();
+    let (output, _) = testing_config
+        .run_and_report_unit_tests(test_plan, None, None, Vec::new())
+        .unwrap();
+
+    assert!(String::from_utf8(output).unwrap().contains('\u{1b}'));
+    let snapshot = fs::read_to_string(dir.join("0x1.B.b_other.exp")).unwrap();
+    assert!(snapshot.starts_with("FAIL\n"), "{}", snapshot);
+    assert!(!snapshot.contains('\u{1b}'), "{}", snapshot);
+    assert!(colored::control::SHOULD_COLORIZE.should_colorize());
+    fs::remove_dir_all(&dir).unwrap();
+}
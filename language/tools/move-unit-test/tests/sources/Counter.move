@@ -0,0 +1,5 @@
+module 0x1::Counter {
+    public fun one(): u64 {
+        1
+    }
+}
@@ -0,0 +1,7 @@
+module 0x1::Coverage {
+    public fun covered(): u64 { 1 }
+    public fun uncovered(): u64 { 2 }
+
+    #[test]
+    fun test_covered() { covered(); }
+}
@@ -0,0 +1,11 @@
+module 0x1::Crash {
+    native fun crash();
+
+    #[test]
+    fun crashes() {
+        crash()
+    }
+
+    #[test]
+    fun passes() { }
+}
@@ -0,0 +1,13 @@
+module 0x1::Flaky {
+    native fun fail_once();
+
+    #[test]
+    fun always_fails() {
+        abort 1
+    }
+
+    #[test]
+    fun flaky() {
+        fail_once()
+    }
+}
@@ -0,0 +1,14 @@
+module 0x1::Gate {
+    native fun wait();
+
+    #[test]
+    fun a_waits() {
+        wait()
+    }
+
+    #[test]
+    fun b_later() { }
+
+    #[test]
+    fun c_later() { }
+}
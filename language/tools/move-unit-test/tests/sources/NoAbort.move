@@ -0,0 +1,5 @@
+module 0x1::NoAbort {
+    #[test]
+    #[expected_failure]
+    fun no_abort() { }
+}
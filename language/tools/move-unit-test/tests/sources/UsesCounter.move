@@ -0,0 +1,8 @@
+module 0x1::UsesCounter {
+    use 0x1::Counter;
+
+    #[test]
+    fun uses_counter() {
+        assert!(Counter::one() == 1, 0)
+    }
+}
@@ -0,0 +1,6 @@
+module 0x1::D {
+    #[test(data = b"no_such_data.json")]
+    fun add(a: u64, b: u64, sum: u64) {
+        if (a + b != sum) abort 0
+    }
+}
@@ -0,0 +1,7 @@
+module 0x1::E {
+    #[test]
+    fun add() {
+        let x: u64 = true;
+        if (x != 1) abort 0
+    }
+}
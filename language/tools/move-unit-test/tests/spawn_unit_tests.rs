@@ -0,0 +1,74 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+use common::{natives_with, source_path};
+use move_binary_format::errors::PartialVMResult;
+use move_core_types::gas_schedule::ONE_GAS_UNIT;
+use move_unit_test::{self, test_reporter::TestEvent, UnitTestingConfig};
+use move_vm_runtime::native_functions::NativeContext;
+use move_vm_types::{
+    loaded_data::runtime_types::Type, natives::function::NativeResult, values::Value,
+};
+use std::{
+    collections::VecDeque,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+};
+
+/// Set once a test is blocked in `Gate::wait`
+static WAITING: AtomicBool = AtomicBool::new(false);
+/// Set to let the test blocked in `Gate::wait` complete
+static RELEASED: AtomicBool = AtomicBool::new(false);
+
+fn native_wait(
+    _context: &mut NativeContext,
+    _ty_args: Vec<Type>,
+    _arguments: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+    WAITING.store(true, Ordering::SeqCst);
+    while !RELEASED.load(Ordering::SeqCst) {
+        thread::yield_now();
+    }
+    Ok(NativeResult::ok(ONE_GAS_UNIT, vec![].into()))
+}
+
+/// Cancelling a run while a test is running lets that test complete, but starts no other test
+#[test]
+fn test_cancel_midway() {
+    let mut testing_config = UnitTestingConfig::default_with_bound(None)
+        .with_named_addresses(move_stdlib::move_stdlib_named_addresses());
+    testing_config.dep_files = move_stdlib::move_stdlib_files();
+    testing_config.source_files = vec![source_path("Gate.move")];
+    // A single thread, so that the tests of the module are run one after the other
+    testing_config.num_threads = 1;
+    let test_plan = testing_config.build_test_plan().unwrap();
+    let run = testing_config
+        .spawn_unit_tests(
+            test_plan,
+            Some(natives_with("Gate", "wait", native_wait)),
+            None,
+        )
+        .unwrap();
+
+    while !WAITING.load(Ordering::SeqCst) {
+        thread::yield_now();
+    }
+    run.cancel();
+    RELEASED.store(true, Ordering::SeqCst);
+
+    let events: Vec<_> = run.events.iter().collect();
+    let started: Vec<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            TestEvent::Started { name } => Some(name.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(started, vec!["0x1::Gate::a_waits"]);
+    assert!(events.iter().any(
+        |event| matches!(event, TestEvent::Passed { name, .. } if name == "0x1::Gate::a_waits")
+    ));
+    assert!(!run.join().unwrap());
+}
@@ -0,0 +1,88 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_compiler::shared::NumericalAddress;
+use move_unit_test::{self, UnitTestingConfig};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// A fresh directory for the sources and the cache of a test
+fn test_dir(test_name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "move-unit-test-{}-{}",
+        test_name,
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("cache")).unwrap();
+    dir
+}
+
+fn cached_config(dir: &Path, source: &str) -> UnitTestingConfig {
+    let source_path = dir.join("Cached.move");
+    fs::write(&source_path, source).unwrap();
+    let mut testing_config = UnitTestingConfig::default_with_bound(None)
+        .with_named_addresses(move_stdlib::move_stdlib_named_addresses());
+    testing_config.dep_files = move_stdlib::move_stdlib_files();
+    testing_config.source_files = vec![source_path.to_string_lossy().to_string()];
+    testing_config.cached = true;
+    testing_config.cache_dir = Some(dir.join("cache").to_string_lossy().to_string());
+    testing_config
+}
+
+fn cached_plans(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir.join("cache"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect()
+}
+
+fn test_names(testing_config: &UnitTestingConfig) -> Vec<String> {
+    let test_plan = testing_config.build_test_plan().unwrap();
+    test_plan
+        .module_tests
+        .values()
+        .flat_map(|module_tests| module_tests.tests.keys().cloned())
+        .collect()
+}
+
+#[test]
+fn test_cache_hit() {
+    let dir = test_dir("cache-hit");
+    let testing_config = cached_config(&dir, "module 0x1::Cached { #[test] fun cached() { } }");
+    assert_eq!(test_names(&testing_config), vec!["cached"]);
+    let plans = cached_plans(&dir);
+    assert_eq!(plans.len(), 1);
+
+    // The plan is read from the cache, rather than compiled again
+    let contents = fs::read_to_string(&plans[0]).unwrap();
+    fs::write(&plans[0], contents.replace("\"cached\"", "\"from_cache\"")).unwrap();
+    assert_eq!(test_names(&testing_config), vec!["from_cache"]);
+    assert_eq!(cached_plans(&dir).len(), 1);
+}
+
+#[test]
+fn test_cache_invalidation() {
+    let dir = test_dir("cache-invalidation");
+    let testing_config = cached_config(&dir, "module 0x1::Cached { #[test] fun cached() { } }");
+    assert_eq!(test_names(&testing_config), vec!["cached"]);
+
+    // A change of the sources compiles them again
+    let testing_config = cached_config(&dir, "module 0x1::Cached { #[test] fun changed() { } }");
+    assert_eq!(test_names(&testing_config), vec!["changed"]);
+    assert_eq!(cached_plans(&dir).len(), 2);
+
+    // So does a change of the way they are compiled
+    let mut testing_config = testing_config;
+    testing_config.allow_warnings = true;
+    assert_eq!(test_names(&testing_config), vec!["changed"]);
+    assert_eq!(cached_plans(&dir).len(), 3);
+    testing_config.named_address_values.push((
+        "Other".to_string(),
+        NumericalAddress::parse_str("0x2").unwrap(),
+    ));
+    assert_eq!(test_names(&testing_config), vec!["changed"]);
+    assert_eq!(cached_plans(&dir).len(), 4);
+}
@@ -0,0 +1,47 @@
+address 0x1 {
+module M {
+    #[test_only]
+    public fun aborts_with(code: u64) {
+        abort code
+    }
+
+    fun also_aborts() {
+        abort 1
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 1, location = Self::also_aborts)]
+    fun abort_in_function() {
+        also_aborts()
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 0, location = Self)]
+    fun abort_in_module() {
+        aborts_with(0)
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 1, location = Self::aborts_with)]
+    fun abort_in_wrong_function() {
+        also_aborts()
+    }
+}
+
+module B {
+    #[test_only]
+    use 0x1::M;
+
+    #[test]
+    #[expected_failure(abort_code = 0, location = 0x1::M::aborts_with)]
+    fun abort_in_other_module() {
+        M::aborts_with(0)
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 0, location = Self)]
+    fun abort_in_wrong_module() {
+        M::aborts_with(0)
+    }
+}
+}
@@ -0,0 +1,12 @@
+module 0x1::M {
+    #[test(data = b"data_driven.json")]
+    fun add(a: u64, b: u64, sum: u64) {
+        if (a + b != sum) abort 0
+    }
+
+    #[test(data = b"data_driven.csv")]
+    fun lengths(input: vector<u8>, len: u64, owner: address) {
+        assert!(Std::Vector::length(&input) == len, 0);
+        assert!(owner != @0x0, 1);
+    }
+}
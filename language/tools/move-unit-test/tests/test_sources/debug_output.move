@@ -0,0 +1,19 @@
+// Stands in for the Debug module of the nursery, which the tests are not compiled with
+module 0x1::Debug {
+    native public fun print<T>(x: &T);
+}
+
+module 0x1::M {
+    use 0x1::Debug;
+
+    #[test]
+    fun print_and_pass() {
+        Debug::print(&1);
+    }
+
+    #[test]
+    fun print_and_abort() {
+        Debug::print(&2);
+        abort 0
+    }
+}
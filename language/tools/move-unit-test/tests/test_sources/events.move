@@ -0,0 +1,40 @@
+module 0x1::M {
+    use Std::Event;
+    use Std::UnitTest;
+
+    struct Deposited has drop, store { amount: u64 }
+    struct Withdrawn has drop, store { amount: u64 }
+
+    fun deposit_and_withdraw(account: &signer, amount: u64) {
+        let deposits = Event::new_event_handle<Deposited>(account);
+        let withdrawals = Event::new_event_handle<Withdrawn>(account);
+        Event::emit_event(&mut deposits, Deposited { amount });
+        Event::emit_event(&mut withdrawals, Withdrawn { amount });
+        Event::destroy_handle(deposits);
+        Event::destroy_handle(withdrawals);
+    }
+
+    #[test(a = @0x1)]
+    #[expected_events(types = [Deposited, Withdrawn])]
+    fun emits_in_order(a: signer) {
+        deposit_and_withdraw(&a, 10);
+    }
+
+    #[test(a = @0x1)]
+    #[expected_events(types = [Withdrawn, Deposited])]
+    fun emits_in_wrong_order(a: signer) {
+        deposit_and_withdraw(&a, 10);
+    }
+
+    #[test(a = @0x1)]
+    fun asserts_emitted(a: signer) {
+        deposit_and_withdraw(&a, 10);
+        UnitTest::assert_event_emitted(&Deposited { amount: 10 });
+    }
+
+    #[test(a = @0x1)]
+    fun asserts_not_emitted(a: signer) {
+        UnitTest::assert_event_emitted(&Withdrawn { amount: 20 });
+        deposit_and_withdraw(&a, 10);
+    }
+}
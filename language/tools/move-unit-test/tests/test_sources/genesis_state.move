@@ -0,0 +1,9 @@
+module 0x1::G {
+    struct Counter has key { value: u64 }
+
+    #[test]
+    fun counter_from_genesis() acquires Counter {
+        assert!(exists<Counter>(@0x2), 0);
+        assert!(borrow_global<Counter>(@0x2).value == 42, 1);
+    }
+}
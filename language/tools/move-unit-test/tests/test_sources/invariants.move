@@ -0,0 +1,19 @@
+module 0x1::Bank {
+    struct Balance has key { value: u64 }
+
+    // No balance is ever above the cap
+    #[test_invariant]
+    fun balance_capped() acquires Balance {
+        assert!(!exists<Balance>(@0x2) || borrow_global<Balance>(@0x2).value <= 100, 0);
+    }
+
+    #[test(account = @0x2)]
+    fun deposit_within_cap(account: signer) {
+        move_to(&account, Balance { value: 50 });
+    }
+
+    #[test(account = @0x2)]
+    fun deposit_over_cap(account: signer) {
+        move_to(&account, Balance { value: 1000 });
+    }
+}
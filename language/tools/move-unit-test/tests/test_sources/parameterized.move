@@ -0,0 +1,12 @@
+module 0x1::M {
+    #[test(cases = [[1, 2, 3], [2, 2, 4], [0, 0, 1]])]
+    fun add(a: u64, b: u64, sum: u64) {
+        if (a + b != sum) abort 0
+    }
+
+    #[test(cases = [[@0x1, b"one"], [@0x2, b"two"]])]
+    fun signers(s: signer, name: vector<u8>) {
+        assert!(Std::Signer::address_of(&s) != @0x0, 0);
+        assert!(Std::Vector::length(&name) == 3, 1);
+    }
+}
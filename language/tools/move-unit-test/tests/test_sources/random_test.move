@@ -0,0 +1,25 @@
+module 0x1::M {
+    use Std::Signer;
+    use Std::Vector;
+
+    #[random_test]
+    fun add_commutes(a: u64, b: u64) {
+        assert!((a as u128) + (b as u128) == (b as u128) + (a as u128), 0);
+    }
+
+    #[random_test]
+    fun bytes_bounded(v: vector<u8>) {
+        assert!(Vector::length(&v) <= 32, 0);
+    }
+
+    #[random_test(s = @0x1)]
+    fun given_signer(s: signer, a: address) {
+        assert!(Signer::address_of(&s) == @0x1, 0);
+        assert!(a == a, 1);
+    }
+
+    #[random_test]
+    fun small(x: u64) {
+        assert!(x < 1000, 2);
+    }
+}
@@ -0,0 +1,72 @@
+module 0x1::M {
+    use Std::UnitTest;
+    use Std::Vector;
+
+    struct Balance has key { value: u64 }
+    struct Supply has key { total: u64 }
+
+    #[test_only]
+    fun setup() {
+        let signers = UnitTest::create_signers_for_testing(2);
+        let s1 = Vector::pop_back(&mut signers);
+        let s0 = Vector::pop_back(&mut signers);
+        move_to(&s0, Balance { value: 10 });
+        move_to(&s1, Balance { value: 5 });
+        move_to(&s0, Supply { total: 15 });
+    }
+
+    #[test_only]
+    fun total_balance(): u64 acquires Balance {
+        let addresses = UnitTest::resource_addresses<Balance>();
+        let total = 0;
+        while (!Vector::is_empty(&addresses)) {
+            total = total + borrow_global<Balance>(Vector::pop_back(&mut addresses)).value;
+        };
+        total
+    }
+
+    #[test]
+    fun no_resources() {
+        assert!(Vector::is_empty(&UnitTest::resource_addresses<Balance>()), 0);
+        assert!(UnitTest::resource_count<Balance>() == 0, 1);
+    }
+
+    #[test(a = @0x42, b = @0x43)]
+    fun published_by_test(a: signer, b: signer) {
+        move_to(&a, Balance { value: 1 });
+        move_to(&b, Balance { value: 2 });
+        assert!(UnitTest::resource_addresses<Balance>() == vector[@0x42, @0x43], 0);
+        assert!(UnitTest::resource_count<Balance>() == 2, 1);
+        assert!(UnitTest::resource_count<Supply>() == 0, 2);
+    }
+
+    #[test(a = @0x42)]
+    fun removed_by_test(a: signer) acquires Balance {
+        move_to(&a, Balance { value: 1 });
+        let Balance { value: _ } = move_from<Balance>(@0x42);
+        assert!(UnitTest::resource_count<Balance>() == 0, 0);
+    }
+
+    #[test]
+    fun published_with_created_signers() {
+        let signers = UnitTest::create_signers_for_testing(3);
+        while (!Vector::is_empty(&signers)) {
+            move_to(&Vector::pop_back(&mut signers), Supply { total: 0 });
+        };
+        assert!(UnitTest::resource_count<Supply>() == 3, 0);
+    }
+
+    #[test, test_group(fixture = setup)]
+    fun supply_invariant() acquires Balance, Supply {
+        assert!(UnitTest::resource_count<Balance>() == 2, 0);
+        assert!(total_balance() == borrow_global<Supply>(@0x0).total, 1);
+    }
+
+    #[test, test_group(fixture = setup)]
+    #[expected_failure(abort_code = 1)]
+    fun supply_invariant_broken() acquires Balance, Supply {
+        let addresses = UnitTest::resource_addresses<Balance>();
+        borrow_global_mut<Balance>(Vector::pop_back(&mut addresses)).value = 6;
+        assert!(total_balance() == borrow_global<Supply>(@0x0).total, 1);
+    }
+}
@@ -0,0 +1,33 @@
+module 0x1::M {
+    use Std::UnitTest;
+    use Std::Vector;
+
+    struct Counter has key { value: u64 }
+    struct Flag has key { }
+    struct Marker has key { }
+
+    #[test_only]
+    fun setup() {
+        let signers = UnitTest::create_signers_for_testing(1);
+        let s = Vector::pop_back(&mut signers);
+        move_to(&s, Counter { value: 10 });
+        move_to(&s, Flag {});
+    }
+
+    // only the resources created, modified, or deleted by the test are shown
+
+    #[test, test_group(fixture = setup)]
+    fun changes_storage() acquires Counter, Flag {
+        let counter = borrow_global_mut<Counter>(@0x0);
+        counter.value = 11;
+        let Flag {} = move_from<Flag>(@0x0);
+        let signers = UnitTest::create_signers_for_testing(1);
+        move_to(&Vector::pop_back(&mut signers), Marker {});
+        abort 0
+    }
+
+    #[test, test_group(fixture = setup)]
+    fun keeps_storage() {
+        abort 0
+    }
+}
@@ -0,0 +1,32 @@
+address 0x1 {
+module A {
+    public fun value(): u64 { 0 }
+}
+
+module M {
+    use 0x1::A;
+
+    struct R has key { v: u64 }
+    struct Unused has key { v: u64 }
+
+    #[test, storage_fault(fail_load = R), expected_failure]
+    fun failing_load() {
+        let _ = exists<R>(@0x1);
+    }
+
+    #[test, storage_fault(fail_load = Unused)]
+    fun other_resources_load() {
+        assert!(!exists<R>(@0x1), 0);
+    }
+
+    #[test, storage_fault(missing_module = b"0x1::A"), expected_failure]
+    fun missing_dependency() {
+        assert!(A::value() == 0, 0);
+    }
+
+    #[test]
+    fun dependency_available() {
+        assert!(A::value() == 0, 0);
+    }
+}
+}
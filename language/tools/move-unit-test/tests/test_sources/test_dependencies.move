@@ -0,0 +1,18 @@
+module 0x1::M {
+    #[test]
+    fun setup() { }
+
+    #[test]
+    fun broken_setup() {
+        abort 0
+    }
+
+    #[test(depends_on = setup)]
+    fun uses_setup() { }
+
+    #[test(depends_on = [setup, broken_setup])]
+    fun uses_broken_setup() { }
+
+    #[test(depends_on = uses_broken_setup)]
+    fun transitively_uses_broken_setup() { }
+}
@@ -0,0 +1,25 @@
+module 0x1::M {
+    use Std::UnitTest;
+
+    #[test(env = b"NAME=move,MODE=fast")]
+    fun config_from_attribute() {
+        assert!(UnitTest::config(b"NAME") == b"move", 0);
+        assert!(UnitTest::config(b"MODE") == b"fast", 1);
+    }
+
+    #[test(a = @0x1, env = b"EMPTY=")]
+    fun config_with_signer(a: signer) {
+        assert!(Std::Signer::address_of(&a) == @0x1, 0);
+        assert!(UnitTest::config(b"EMPTY") == b"", 1);
+    }
+
+    #[test(env = b"NAME=move"), expected_failure(abort_code = 0)]
+    fun config_missing_key() {
+        UnitTest::config(b"OTHER");
+    }
+
+    #[test, expected_failure(abort_code = 0)]
+    fun config_not_set() {
+        UnitTest::config(b"NAME");
+    }
+}
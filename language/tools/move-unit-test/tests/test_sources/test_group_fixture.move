@@ -0,0 +1,45 @@
+module 0x1::M {
+    use Std::UnitTest;
+    use Std::Vector;
+
+    struct Counter has key { value: u64 }
+
+    #[test_only]
+    fun setup() {
+        let signers = UnitTest::create_signers_for_testing(1);
+        let s = Vector::pop_back(&mut signers);
+        move_to(&s, Counter { value: 10 });
+    }
+
+    #[test_only]
+    fun failing_setup() {
+        abort 7
+    }
+
+    #[test, test_group(fixture = setup)]
+    fun fixture_state_visible() acquires Counter {
+        assert!(borrow_global<Counter>(@0x0).value == 10, 0);
+    }
+
+    #[test, test_group(fixture = setup)]
+    fun fixture_state_isolated_a() acquires Counter {
+        let counter = borrow_global_mut<Counter>(@0x0);
+        counter.value = counter.value + 1;
+        assert!(counter.value == 11, 0);
+    }
+
+    #[test, test_group(fixture = setup)]
+    fun fixture_state_isolated_b() acquires Counter {
+        let counter = borrow_global_mut<Counter>(@0x0);
+        counter.value = counter.value + 1;
+        assert!(counter.value == 11, 0);
+    }
+
+    #[test]
+    fun no_fixture() {
+        assert!(!exists<Counter>(@0x0), 0);
+    }
+
+    #[test, test_group(fixture = failing_setup)]
+    fun fixture_fails() {}
+}
@@ -0,0 +1,7 @@
+module 0x1::S {
+    fun id(x: u64): u64 { x }
+
+    // Pushes two values, the argument and the result of the call, with at most one on the stack
+    #[test]
+    fun call() { id(7); }
+}
@@ -0,0 +1,15 @@
+module 0x1::M {
+    #[test]
+    fun untagged() { }
+
+    #[test(tags = [b"slow"])]
+    fun slow() { }
+
+    #[test(tags = [b"slow", b"fuzz"])]
+    fun slow_fuzz() { }
+
+    #[test(a = @0x1, tags = [b"fuzz"])]
+    fun fuzz(a: signer) {
+        assert!(Std::Signer::address_of(&a) == @0x1, 0);
+    }
+}
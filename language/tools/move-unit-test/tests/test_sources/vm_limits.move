@@ -0,0 +1,34 @@
+address 0x1 {
+module M {
+    use Std::Vector;
+
+    struct R has key { v: vector<vector<u8>> }
+
+    fun recurse(n: u64) {
+        if (n > 0) recurse(n - 1)
+    }
+
+    // Each of these tests only aborts when run with the limits of the `vm_limits` modifier
+
+    #[test]
+    #[expected_failure]
+    fun deep_recursion() {
+        recurse(20)
+    }
+
+    #[test(s = @0x1)]
+    #[expected_failure]
+    fun deep_value(s: signer) {
+        move_to(&s, R { v: Vector::empty() })
+    }
+
+    #[test]
+    #[expected_failure]
+    fun long_vector() {
+        let v = Vector::empty();
+        Vector::push_back(&mut v, 1);
+        Vector::push_back(&mut v, 2);
+        Vector::push_back(&mut v, 3);
+    }
+}
+}